@@ -3,6 +3,7 @@
 use std::path::PathBuf;
 
 use clap::{Args, Subcommand};
+use murmur_core::agent::Watchdog;
 use murmur_core::{AgentFactory, AgentType, Config, OutputStreamer, PrintHandler};
 
 /// Arguments for the agent command
@@ -121,13 +122,18 @@ impl StartArgs {
         // Stream the output
         let mut streamer = OutputStreamer::new(stdout);
         let mut handler = PrintHandler::new(verbose);
-        streamer.stream(&mut handler).await?;
+        let watchdog = Watchdog::new(config.agent.watchdog_for_type(self.agent_type));
+        let stopped = watchdog
+            .supervise(&mut handle, &mut streamer, &mut handler)
+            .await?;
 
         // Wait for the process to complete
         let status = handle.wait().await?;
 
         println!();
-        if status.success() {
+        if let Some(reason) = stopped {
+            println!("Agent stopped by watchdog: {}", reason);
+        } else if status.success() {
             println!("Agent completed successfully");
         } else {
             println!("Agent exited with status: {}", status);
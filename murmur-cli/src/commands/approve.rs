@@ -0,0 +1,136 @@
+//! Approve and reject commands - decide on workflows waiting for a human
+
+use clap::Args;
+use murmur_core::workflow::ApprovalDecision;
+use murmur_db::{Approval, ApprovalRepository, Database};
+
+/// Arguments for the approve command
+#[derive(Args, Debug)]
+pub struct ApproveArgs {
+    /// The approval to grant (lists pending approvals if omitted)
+    pub id: Option<i64>,
+}
+
+/// Arguments for the reject command
+#[derive(Args, Debug)]
+pub struct RejectArgs {
+    /// The approval to reject
+    pub id: i64,
+
+    /// Why the work should be redone, passed to the agent
+    #[arg(short, long)]
+    pub reason: String,
+}
+
+impl ApproveArgs {
+    /// Execute the approve command
+    pub async fn execute(&self, _verbose: bool) -> anyhow::Result<()> {
+        let db = Database::open().map_err(|e| anyhow::anyhow!("Failed to open database: {}", e))?;
+        let repo = ApprovalRepository::new(&db);
+
+        let Some(id) = self.id else {
+            let pending = repo
+                .find_pending()
+                .map_err(|e| anyhow::anyhow!("Failed to load approvals: {}", e))?;
+            if pending.is_empty() {
+                println!("No workflows are waiting for approval");
+                return Ok(());
+            }
+            println!("Waiting for approval:");
+            for approval in pending {
+                println!(
+                    "  #{} {} {} -> {}: {}",
+                    approval.id.unwrap_or_default(),
+                    approval.workflow,
+                    approval.from_phase,
+                    approval.to_phase,
+                    approval.task
+                );
+                println!("      {}", approval.workdir);
+            }
+            return Ok(());
+        };
+
+        let mut approval = pending_approval(&repo, id)?;
+        approval.approve(decided_by());
+        repo.update(&approval)
+            .map_err(|e| anyhow::anyhow!("Failed to save approval: {}", e))?;
+        println!("Approved #{}: {} can start", id, approval.to_phase);
+        Ok(())
+    }
+}
+
+impl RejectArgs {
+    /// Execute the reject command
+    pub async fn execute(&self, _verbose: bool) -> anyhow::Result<()> {
+        let db = Database::open().map_err(|e| anyhow::anyhow!("Failed to open database: {}", e))?;
+        let repo = ApprovalRepository::new(&db);
+
+        let mut approval = pending_approval(&repo, self.id)?;
+        approval.reject(&self.reason, decided_by());
+        repo.update(&approval)
+            .map_err(|e| anyhow::anyhow!("Failed to save approval: {}", e))?;
+        println!(
+            "Rejected #{}: the work before {} will be redone",
+            self.id, approval.to_phase
+        );
+        Ok(())
+    }
+}
+
+/// Load approval `id`, failing unless it is still waiting for a decision
+fn pending_approval(repo: &ApprovalRepository, id: i64) -> anyhow::Result<Approval> {
+    let approval = repo
+        .find_by_id(id)
+        .map_err(|e| anyhow::anyhow!("Failed to load approval: {}", e))?
+        .ok_or_else(|| anyhow::anyhow!("No approval #{}", id))?;
+    if !approval.is_pending() {
+        anyhow::bail!("Approval #{} was already {}", id, approval.status);
+    }
+    Ok(approval)
+}
+
+/// Who the decision is recorded as made by
+fn decided_by() -> String {
+    std::env::var("USER").unwrap_or_else(|_| "cli".to_string())
+}
+
+/// Print that a workflow is waiting for approval to start `phase`
+pub fn print_pending(approval_id: i64, phase: &str, no_emoji: bool) {
+    println!();
+    println!(
+        "{} Waiting for approval #{} to start: {}",
+        emoji(no_emoji, "✋", "[WAIT]"),
+        approval_id,
+        phase
+    );
+    println!(
+        "Run `murmur approve {}` or `murmur reject {} --reason \"...\"`",
+        approval_id, approval_id
+    );
+    println!();
+}
+
+/// Print the decision a workflow was waiting for
+pub fn print_decision(decision: &ApprovalDecision, no_emoji: bool) {
+    match decision {
+        ApprovalDecision::Approved => println!("{} Approved", emoji(no_emoji, "👍", "[OK]")),
+        ApprovalDecision::Rejected(reason) => {
+            println!(
+                "{} Rejected: {}",
+                emoji(no_emoji, "👎", "[REJECTED]"),
+                reason
+            )
+        }
+    }
+    println!();
+}
+
+/// Get emoji or ASCII alternative based on no_emoji flag
+fn emoji<'a>(no_emoji: bool, emoji_char: &'a str, ascii_alt: &'a str) -> &'a str {
+    if no_emoji {
+        ascii_alt
+    } else {
+        emoji_char
+    }
+}
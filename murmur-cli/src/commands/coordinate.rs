@@ -0,0 +1,366 @@
+//! Coordinate command - plan, implement, test, review and open a PR for a task
+//!
+//! This command drives the coordinator phases:
+//! 1. Planning: The coordinator agent breaks the task down
+//! 2. SetupWorktree: A worktree and branch are created for the work
+//! 3. Implementing: The implement agent (or the TDD cycle) makes the changes,
+//!    running independent subtasks of the plan concurrently
+//! 4. Testing: The tests run, with fix attempts on failure
+//! 5. Reviewing: The review loop reviews and fixes the changes
+//! 6. CreatingPR: The branch is pushed and a pull request opened
+
+use std::path::PathBuf;
+
+use clap::Args;
+use murmur_core::agent::AgentType;
+use murmur_core::workflow::{
+    ApprovalDecision, ApprovalGates, CoordinatorConfig, CoordinatorExecutor, CoordinatorObserver,
+    CoordinatorOutcome, CoordinatorPhase, CoordinatorState, QualityCheck, ReviewLoopOutcome,
+    SubTask, TestResults,
+};
+use murmur_core::{Config, GitRepo, PrintHandler, TddPhase};
+use murmur_db::Database;
+
+use super::run_accounting;
+
+/// Arguments for the coordinate command
+#[derive(Args, Debug)]
+pub struct CoordinateArgs {
+    /// The task to carry out
+    pub task: String,
+
+    /// Repository directory (defaults to current directory)
+    #[arg(short = 'd', long, default_value = ".")]
+    pub workdir: PathBuf,
+
+    /// Implement through the TDD cycle (tests first, then implementation)
+    #[arg(long)]
+    pub tdd: bool,
+
+    /// Skip the review phase
+    #[arg(long)]
+    pub no_review: bool,
+
+    /// Push the branch and open a pull request (defaults to workflow.auto_pr)
+    #[arg(long, conflicts_with = "no_pr")]
+    pub pr: bool,
+
+    /// Don't open a pull request, even if workflow.auto_pr is set
+    #[arg(long)]
+    pub no_pr: bool,
+
+    /// Retries per phase when an agent fails or tests keep failing (default: 2)
+    #[arg(long)]
+    pub max_retries: Option<u32>,
+
+    /// Branch to create (defaults to one named after the task)
+    #[arg(short, long)]
+    pub branch: Option<String>,
+
+    /// Branch to start from and open the pull request against (defaults to the repository's default branch)
+    #[arg(long)]
+    pub base: Option<String>,
+
+    /// Recreate the worktree and branch if they already exist
+    #[arg(short, long)]
+    pub force: bool,
+
+    /// Maximum subtasks of the plan worked on at the same time (default: 3)
+    #[arg(long)]
+    pub parallel: Option<usize>,
+
+    /// Give concurrent subtasks worktrees of their own and merge them back
+    #[arg(long)]
+    pub isolate_subtasks: bool,
+
+    /// Wait for `murmur approve` before starting these phases (e.g. implementing,creating-pr)
+    #[arg(long, value_name = "PHASE", value_delimiter = ',')]
+    pub approve_before: Vec<CoordinatorPhase>,
+}
+
+impl CoordinateArgs {
+    /// Execute the coordinate command
+    pub async fn execute(
+        &self,
+        verbose: bool,
+        no_emoji: bool,
+        config: &Config,
+        repo: Option<&str>,
+    ) -> anyhow::Result<()> {
+        let workdir = if self.workdir.is_absolute() {
+            self.workdir.clone()
+        } else {
+            std::env::current_dir()?.join(&self.workdir)
+        };
+        let git_repo = GitRepo::open(&workdir)?;
+        let main_branch = match self.base {
+            Some(ref base) => base.clone(),
+            None => git_repo.default_branch()?,
+        };
+
+        let defaults = CoordinatorConfig::default();
+        let coordinator_config = CoordinatorConfig {
+            use_tdd: self.tdd,
+            run_review: !self.no_review,
+            auto_pr: (self.pr || config.workflow.auto_pr) && !self.no_pr,
+            max_retries: self.max_retries.unwrap_or(defaults.max_retries),
+            repo: repo.map(str::to_string),
+            main_branch,
+            max_parallel: self.parallel.unwrap_or(defaults.max_parallel),
+            isolate_subtasks: self.isolate_subtasks,
+        };
+
+        println!("Coordinator Workflow");
+        println!("====================");
+        println!();
+        println!("Task: {}", self.task);
+        println!("Repository: {}", git_repo.root().display());
+        println!("Base branch: {}", coordinator_config.main_branch);
+        println!(
+            "Implementation: {}",
+            if coordinator_config.use_tdd {
+                "TDD cycle"
+            } else {
+                "implement agent"
+            }
+        );
+        println!(
+            "Review: {}",
+            if coordinator_config.run_review {
+                "on"
+            } else {
+                "off"
+            }
+        );
+        println!(
+            "Pull request: {}",
+            if coordinator_config.auto_pr {
+                "on"
+            } else {
+                "off"
+            }
+        );
+        println!();
+
+        let mut executor = CoordinatorExecutor::new(
+            &self.task,
+            git_repo.root(),
+            coordinator_config,
+            config.agent.clone(),
+        )
+        .with_test_config(&config.test)
+        .with_quality_config(&config.quality)
+        .with_accounting(run_accounting(config, repo))
+        .with_review_iterations(config.workflow.max_review_iterations);
+        if let Some(ref branch) = self.branch {
+            executor = executor.with_branch_name(branch);
+        }
+        if self.force {
+            executor = executor.with_force();
+        }
+        if !self.approve_before.is_empty() {
+            executor = executor.with_approval_gates(ApprovalGates::before(
+                self.approve_before.iter().map(|phase| phase.as_str()),
+            ));
+        }
+        match Database::open() {
+            Ok(db) => executor = executor.with_database(db),
+            Err(e) => eprintln!("Warning: Agent runs won't be recorded: {}", e),
+        }
+
+        let mut printer = CoordinatorPrinter { verbose, no_emoji };
+        let mut handler = PrintHandler::new(verbose);
+        let outcome = executor.run(&mut printer, &mut handler).await?;
+        printer.print_outcome(&outcome)
+    }
+}
+
+/// Prints the progress of a coordinator run
+struct CoordinatorPrinter {
+    verbose: bool,
+    no_emoji: bool,
+}
+
+impl CoordinatorPrinter {
+    fn emoji<'a>(&self, emoji: &'a str, ascii: &'a str) -> &'a str {
+        if self.no_emoji {
+            ascii
+        } else {
+            emoji
+        }
+    }
+
+    /// Print the final status, failing if the workflow failed
+    fn print_outcome(&self, outcome: &CoordinatorOutcome) -> anyhow::Result<()> {
+        println!("═══════════════════════════════════════");
+        if outcome.completed {
+            println!(
+                "{} Coordinator workflow completed successfully!",
+                self.emoji("🎉", "[DONE]")
+            );
+        } else {
+            println!(
+                "{} Coordinator workflow failed{}",
+                self.emoji("💥", "[FAIL]"),
+                outcome
+                    .failed_phase
+                    .map(|phase| format!(" ({})", phase.description()))
+                    .unwrap_or_default()
+            );
+        }
+        println!("═══════════════════════════════════════");
+
+        if let Some(ref path) = outcome.worktree_path {
+            println!("Worktree: {}", path.display());
+        }
+        if let Some(ref branch) = outcome.branch_name {
+            println!("Branch:   {}", branch);
+        }
+        if let Some(ref url) = outcome.pr_url {
+            println!("PR:       {}", url);
+        }
+
+        match outcome.error {
+            Some(ref error) if !outcome.completed => {
+                Err(anyhow::anyhow!("Coordinator workflow failed: {}", error))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+impl CoordinatorObserver for CoordinatorPrinter {
+    fn on_phase_start(&mut self, phase: CoordinatorPhase, _state: &CoordinatorState) {
+        println!(
+            "Phase {}/6: {} {}",
+            phase_number(phase),
+            self.emoji(phase_emoji(phase), phase_ascii(phase)),
+            phase.description()
+        );
+        println!();
+    }
+
+    fn on_phase_skipped(&mut self, _phase: CoordinatorPhase, reason: &str) {
+        println!("{} Skipped: {}", self.emoji("⏭️", "[SKIP]"), reason);
+        println!();
+    }
+
+    fn on_agent_start(&mut self, _phase: CoordinatorPhase, agent_type: AgentType, prompt: &str) {
+        if self.verbose {
+            println!("Prompt: {}", prompt);
+            println!();
+        }
+        println!("Starting {} agent...", agent_type);
+    }
+
+    fn on_subtask_start(&mut self, subtask: &SubTask) {
+        println!();
+        println!("Subtask {}: {}", subtask.id, subtask.description);
+    }
+
+    fn on_tdd_phase(&mut self, phase: TddPhase) {
+        println!("  TDD: {}", phase.description());
+    }
+
+    fn on_test_output(&mut self, line: &str) {
+        println!("  {}", line);
+    }
+
+    fn on_test_results(&mut self, results: &TestResults) {
+        println!();
+        println!("Test results: {}", results.summary());
+        if self.verbose {
+            for test in results.failures() {
+                println!("  {} {}", self.emoji("❌", "[FAIL]"), test.name);
+            }
+        }
+    }
+
+    fn on_review(&mut self, outcome: &ReviewLoopOutcome) {
+        super::review::print_outcome(outcome, self.no_emoji);
+    }
+
+    fn on_phase_complete(&mut self, _phase: CoordinatorPhase, message: Option<&str>) {
+        println!();
+        match message {
+            Some(message) => println!("{} {}", self.emoji("✅", "[OK]"), message),
+            None => println!("{} Phase completed", self.emoji("✅", "[OK]")),
+        }
+        println!();
+    }
+
+    fn on_retry(&mut self, phase: CoordinatorPhase, reason: &str) {
+        println!();
+        println!("{} {}", self.emoji("⚠️", "[WARN]"), reason);
+        println!("Retrying: {}...", phase.description());
+        println!();
+    }
+
+    fn on_quality_check(&mut self, check: &QualityCheck, problem: Option<&str>) {
+        match problem {
+            None => println!("{} Check passed: {}", self.emoji("✅", "[OK]"), check),
+            Some(problem) => {
+                println!("{} Check failed: {}", self.emoji("❌", "[FAIL]"), check);
+                if self.verbose {
+                    println!("{}", problem);
+                }
+            }
+        }
+    }
+
+    fn on_failed(&mut self, _phase: CoordinatorPhase, error: &str) {
+        println!();
+        println!("{} {}", self.emoji("❌", "[FAIL]"), error);
+        println!();
+    }
+
+    fn on_approval_pending(
+        &mut self,
+        _from: CoordinatorPhase,
+        to: CoordinatorPhase,
+        approval_id: i64,
+    ) {
+        super::approve::print_pending(approval_id, to.description(), self.no_emoji);
+    }
+
+    fn on_approval(&mut self, _to: CoordinatorPhase, decision: &ApprovalDecision) {
+        super::approve::print_decision(decision, self.no_emoji);
+    }
+}
+
+fn phase_number(phase: CoordinatorPhase) -> u32 {
+    match phase {
+        CoordinatorPhase::Planning => 1,
+        CoordinatorPhase::SetupWorktree => 2,
+        CoordinatorPhase::Implementing => 3,
+        CoordinatorPhase::Testing => 4,
+        CoordinatorPhase::Reviewing => 5,
+        CoordinatorPhase::CreatingPR | CoordinatorPhase::Complete | CoordinatorPhase::Failed => 6,
+    }
+}
+
+fn phase_emoji(phase: CoordinatorPhase) -> &'static str {
+    match phase {
+        CoordinatorPhase::Planning => "🗺️",
+        CoordinatorPhase::SetupWorktree => "🌳",
+        CoordinatorPhase::Implementing => "🔨",
+        CoordinatorPhase::Testing => "🧪",
+        CoordinatorPhase::Reviewing => "🔍",
+        CoordinatorPhase::CreatingPR => "📬",
+        CoordinatorPhase::Complete => "🎉",
+        CoordinatorPhase::Failed => "💥",
+    }
+}
+
+fn phase_ascii(phase: CoordinatorPhase) -> &'static str {
+    match phase {
+        CoordinatorPhase::Planning => "[PLAN]",
+        CoordinatorPhase::SetupWorktree => "[WORKTREE]",
+        CoordinatorPhase::Implementing => "[IMPL]",
+        CoordinatorPhase::Testing => "[TEST]",
+        CoordinatorPhase::Reviewing => "[REVIEW]",
+        CoordinatorPhase::CreatingPR => "[PR]",
+        CoordinatorPhase::Complete => "[DONE]",
+        CoordinatorPhase::Failed => "[FAIL]",
+    }
+}
@@ -1,19 +1,45 @@
 //! CLI command implementations
 
 pub mod agent;
+pub mod approve;
+pub mod coordinate;
 pub mod issue;
 pub mod orchestrate;
+pub mod prompts;
+pub mod review;
 pub mod run;
+pub mod sandbox;
+pub mod stats;
 pub mod status;
 pub mod tdd;
 pub mod work;
+pub mod workflow;
 pub mod worktree;
 
 pub use agent::AgentArgs;
+pub use approve::{ApproveArgs, RejectArgs};
+pub use coordinate::CoordinateArgs;
 pub use issue::IssueArgs;
 pub use orchestrate::OrchestrateArgs;
+pub use prompts::PromptsArgs;
+pub use review::ReviewArgs;
 pub use run::RunArgs;
+pub use sandbox::SandboxBridgeArgs;
+pub use stats::StatsArgs;
 pub use status::StatusArgs;
 pub use tdd::TddArgs;
 pub use work::WorkArgs;
+pub use workflow::WorkflowArgs;
 pub use worktree::WorktreeArgs;
+
+use murmur_core::workflow::RunAccounting;
+use murmur_core::Config;
+
+/// Account workflow agent runs as configured, under the repository if known
+pub(crate) fn run_accounting(config: &Config, repo: Option<&str>) -> RunAccounting {
+    let accounting = RunAccounting::from_config(config);
+    match repo {
+        Some(repo) => accounting.with_repository(repo),
+        None => accounting,
+    }
+}
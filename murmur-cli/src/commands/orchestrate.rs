@@ -4,8 +4,11 @@
 //! builds a dependency graph, and executes issues in topological order.
 
 use clap::Args;
+use murmur_core::agent::{AgentType, BudgetHandler, BudgetTracker, SharedUsage, Usage, Watchdog};
+use murmur_core::workflow::RunAccounting;
 use murmur_core::{
-    AgentSpawner, BranchingOptions, Config, GitRepo, OutputStreamer, Secrets, WorktreeOptions,
+    AgentSpawner, BranchingOptions, Config, GitRepo, OutputStreamer, Secrets, TddPhase,
+    WorktreeOptions,
 };
 use murmur_db::{
     models::{AgentRun, ConversationLog, WorktreeRecord},
@@ -17,6 +20,8 @@ use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use tokio::sync::Mutex;
 
+use super::work::run_tdd;
+
 /// Orchestrate work on multiple issues from an epic
 #[derive(Args, Debug)]
 pub struct OrchestrateArgs {
@@ -42,6 +47,20 @@ pub struct OrchestrateArgs {
     /// Only run issues with this label (can be specified multiple times)
     #[arg(long)]
     pub label: Vec<String>,
+
+    /// Address unresolved review feedback on the open PRs of the epic's issues
+    #[arg(long, conflicts_with = "force")]
+    pub address_feedback: bool,
+
+    /// Work through the TDD cycle on each issue instead of a single agent run
+    #[arg(long, conflicts_with = "address_feedback")]
+    pub tdd: bool,
+
+    /// With --tdd, wait for approval on each issue before these phases
+    ///
+    /// Approve with `murmur approve` or a `/murmur approve` comment on the issue.
+    #[arg(long, value_name = "PHASE", value_delimiter = ',', requires = "tdd")]
+    pub approve_before: Vec<TddPhase>,
 }
 
 /// Execution configuration for orchestration
@@ -49,6 +68,10 @@ struct ExecutionConfig<'a> {
     config: &'a Config,
     verbose: bool,
     no_emoji: bool,
+    /// Repository being orchestrated (owner/repo format)
+    repository: String,
+    /// Usage accumulated by all agents of this orchestration
+    usage: SharedUsage,
 }
 
 /// Result of working on a single issue
@@ -161,6 +184,17 @@ impl OrchestrateArgs {
             return Ok(());
         }
 
+        if self.address_feedback {
+            let mut open: Vec<&Issue> = issues_to_process
+                .into_iter()
+                .filter(|issue| issue.state == IssueState::Open)
+                .collect();
+            open.sort_by_key(|issue| issue.number);
+            return self
+                .address_feedback(&client, &open, config, verbose, no_emoji)
+                .await;
+        }
+
         // Build dependency graph
         println!("Building dependency graph...");
         let issue_vec: Vec<Issue> = issues_to_process.iter().map(|i| (*i).clone()).collect();
@@ -305,6 +339,8 @@ impl OrchestrateArgs {
             config,
             verbose,
             no_emoji,
+            repository: format!("{}/{}", client.owner(), client.repo()),
+            usage: SharedUsage::default(),
         };
 
         let execution_result = self
@@ -427,10 +463,35 @@ impl OrchestrateArgs {
 
                 for &issue_num in chunk {
                     if let Some(issue) = issues.get(&issue_num) {
+                        // Don't start new agents once the orchestration budget is spent
+                        let spent = *exec_config.usage.lock().unwrap();
+                        if let Some(detail) =
+                            exec_config.config.budget.orchestration().check(&spent)
+                        {
+                            let reason = format!("orchestration budget exceeded: {}", detail);
+                            println!(
+                                "  {} #{}: Skipped ({})",
+                                emoji(exec_config.no_emoji, "⏭️", "[SKIP]"),
+                                issue_num,
+                                reason
+                            );
+                            failed.lock().await.insert(issue_num);
+                            results.push(IssueResult {
+                                issue_number: issue_num,
+                                success: false,
+                                error: Some(reason),
+                            });
+                            continue;
+                        }
+
                         let issue = issue.clone();
                         let config = exec_config.config.clone();
+                        let usage = Arc::clone(&exec_config.usage);
+                        let repository = exec_config.repository.clone();
                         let verbose = exec_config.verbose;
+                        let no_emoji = exec_config.no_emoji;
                         let force = self.force;
+                        let tdd = self.tdd.then(|| self.approve_before.clone());
                         let completed = Arc::clone(&completed);
                         let failed = Arc::clone(&failed);
 
@@ -442,8 +503,17 @@ impl OrchestrateArgs {
                         );
 
                         let handle = tokio::spawn(async move {
-                            let result =
-                                execute_single_issue(&issue, &config, verbose, force).await;
+                            let result = execute_single_issue(
+                                &issue,
+                                &config,
+                                &repository,
+                                usage,
+                                tdd,
+                                verbose,
+                                no_emoji,
+                                force,
+                            )
+                            .await;
 
                             match &result {
                                 Ok(_) => {
@@ -520,6 +590,79 @@ impl OrchestrateArgs {
         Ok(results)
     }
 
+    /// Address PR feedback for each issue in turn, reporting what was done
+    async fn address_feedback(
+        &self,
+        client: &GitHubClient,
+        issues: &[&Issue],
+        config: &Config,
+        verbose: bool,
+        no_emoji: bool,
+    ) -> anyhow::Result<()> {
+        use super::work::{address_feedback, FeedbackOutcome};
+
+        if self.dry_run {
+            println!("Would address PR feedback on {} issues:", issues.len());
+            for issue in issues {
+                println!("  #{}: {}", issue.number, issue.title);
+            }
+            return Ok(());
+        }
+
+        let db = Database::open().map_err(|e| anyhow::anyhow!("Failed to open database: {}", e))?;
+        let accounting = RunAccounting::from_config(config)
+            .with_repository(format!("{}/{}", client.owner(), client.repo()))
+            .with_orchestration_usage(SharedUsage::default());
+        let mut results = Vec::new();
+        let mut addressed = Vec::new();
+
+        for issue in issues {
+            println!("--- #{}: {} ---", issue.number, issue.title);
+            let result =
+                match address_feedback(client, &db, config, &accounting, issue, verbose, no_emoji)
+                    .await
+                {
+                    Ok(FeedbackOutcome::Addressed {
+                        pr_number,
+                        resolved,
+                    }) => {
+                        addressed.push((issue.number, pr_number, resolved));
+                        IssueResult {
+                            issue_number: issue.number,
+                            success: true,
+                            error: None,
+                        }
+                    }
+                    Ok(FeedbackOutcome::NoOpenPr | FeedbackOutcome::NothingToAddress) => continue,
+                    Ok(FeedbackOutcome::NoChanges) => IssueResult {
+                        issue_number: issue.number,
+                        success: false,
+                        error: Some("agent made no changes".to_string()),
+                    },
+                    Err(e) => IssueResult {
+                        issue_number: issue.number,
+                        success: false,
+                        error: Some(e.to_string()),
+                    },
+                };
+            println!();
+            results.push(result);
+        }
+
+        self.print_summary(&results, no_emoji);
+        if !addressed.is_empty() {
+            println!();
+            println!("Addressed feedback:");
+            for (issue_number, pr_number, resolved) in addressed {
+                println!(
+                    "  #{}: PR #{} ({} threads resolved)",
+                    issue_number, pr_number, resolved
+                );
+            }
+        }
+        Ok(())
+    }
+
     /// Print final summary of orchestration
     fn print_summary(&self, results: &[IssueResult], no_emoji: bool) {
         println!();
@@ -551,10 +694,18 @@ impl OrchestrateArgs {
 }
 
 /// Execute work on a single issue
+///
+/// With `tdd`, runs the TDD cycle waiting for approval before the given
+/// phases; otherwise runs a single implement agent.
+#[allow(clippy::too_many_arguments)]
 async fn execute_single_issue(
     issue: &Issue,
     config: &Config,
+    repository: &str,
+    usage: SharedUsage,
+    tdd: Option<Vec<TddPhase>>,
     verbose: bool,
+    no_emoji: bool,
     force: bool,
 ) -> anyhow::Result<()> {
     // Skip already closed issues
@@ -596,38 +747,104 @@ async fn execute_single_issue(
             )
         })?;
 
-    // Build prompt from issue
-    let prompt = build_prompt_from_issue(issue);
+    // Build prompt from issue, with repository context when GitHub is reachable
+    let mut prompt = build_prompt_from_issue(issue);
+    match GitHubClient::from_url(repository) {
+        Ok(client) => client
+            .collect_issue_context(issue, &info.path, &config.context)
+            .await
+            .append_to(&mut prompt),
+        Err(e) => tracing::warn!(error = %e, "Skipping repository context"),
+    }
 
-    // All database operations happen in a block that doesn't cross await points
-    let (run_id, worktree_path) = {
-        // Initialize database
+    // Track worktree in database
+    {
         let db = Database::open().map_err(|e| anyhow::anyhow!("Failed to open database: {}", e))?;
-
-        // Track worktree in database
         let worktree_record =
             WorktreeRecord::new(info.path.to_string_lossy().to_string(), branch_name.clone())
                 .with_issue_number(issue.number as i64)
                 .with_main_repo_path(git_repo.root().to_string_lossy().to_string())
                 .with_base_commit(&point.commit);
-
-        let worktree_repo = WorktreeRepository::new(&db);
-        worktree_repo
+        WorktreeRepository::new(&db)
             .insert(&worktree_record)
             .map_err(|e| anyhow::anyhow!("Failed to track worktree in database: {}", e))?;
+    }
+
+    if let Some(approve_before) = tdd {
+        let client = GitHubClient::from_url(repository).map_err(|e| anyhow::anyhow!("{}", e))?;
+        let accounting = RunAccounting::from_config(config)
+            .with_repository(repository)
+            .with_orchestration_usage(usage);
+        // The executor holds its database connection across awaits, so it
+        // can't move between worker threads; give it a thread of its own
+        let config = config.clone();
+        let issue_number = issue.number;
+        let completed = tokio::task::spawn_blocking(move || {
+            tokio::runtime::Handle::current().block_on(run_tdd(
+                &config,
+                accounting,
+                &client,
+                issue_number,
+                &approve_before,
+                &info,
+                &prompt,
+                verbose,
+                no_emoji,
+            ))
+        })
+        .await??;
+        return if completed {
+            Ok(())
+        } else {
+            Err(anyhow::anyhow!("TDD workflow failed after max iterations"))
+        };
+    }
+
+    let model = config.agent.resolve_for_type(AgentType::Implement).model;
+
+    // All database operations happen in a block that doesn't cross await points
+    let (run_id, worktree_path, issue_spent) = {
+        // Initialize database
+        let db = Database::open().map_err(|e| anyhow::anyhow!("Failed to open database: {}", e))?;
+        let worktree_repo = WorktreeRepository::new(&db);
 
         // Create agent run record
         let config_json = serde_json::to_string(&config.agent).unwrap_or_else(|_| "{}".to_string());
 
-        let agent_run = AgentRun::new(
+        let mut agent_run = AgentRun::new(
             "implementer",
             &prompt,
             info.path.to_str().unwrap_or(""),
             config_json,
         )
-        .with_issue_number(issue.number as i64);
+        .with_issue_number(issue.number as i64)
+        .with_repository(repository);
+
+        if let Some(ref model) = model {
+            agent_run = agent_run.with_model(model);
+        }
 
         let agent_repo = AgentRunRepository::new(&db);
+
+        // Refuse to start when earlier runs already used up the issue budget
+        let issue_spent = if config.budget.issue().is_unlimited() {
+            Usage::default()
+        } else {
+            let (issue_tokens, issue_cost) = agent_repo
+                .usage_by_issue(issue.number as i64)
+                .map_err(|e| {
+                    anyhow::anyhow!("Failed to load spend for issue #{}: {}", issue.number, e)
+                })?;
+            Usage {
+                input_tokens: issue_tokens as u64,
+                cost_usd: issue_cost,
+                ..Default::default()
+            }
+        };
+        if let Some(detail) = config.budget.issue().check(&issue_spent) {
+            return Err(anyhow::anyhow!("issue budget exceeded: {}", detail));
+        }
+
         let run_id = agent_repo
             .insert(&agent_run)
             .map_err(|e| anyhow::anyhow!("Failed to create agent run record: {}", e))?;
@@ -638,7 +855,7 @@ async fn execute_single_issue(
             let _ = worktree_repo.update(&wt_record);
         }
 
-        (run_id, info.path.to_string_lossy().to_string())
+        (run_id, info.path.to_string_lossy().to_string(), issue_spent)
     };
 
     // Spawn agent (async operations happen here)
@@ -677,13 +894,37 @@ async fn execute_single_issue(
 
     let handler_db = Database::open()
         .map_err(|e| anyhow::anyhow!("Failed to open database for handler: {}", e))?;
-    let mut handler = MinimalLoggingHandler::new(handler_db, run_id, verbose);
+    let tracker = BudgetTracker::new(config.budget.run())
+        .with_pricing(&config.pricing, model.as_deref())
+        .with_issue_budget(config.budget.issue(), issue_spent)
+        .with_orchestration_budget(config.budget.orchestration(), usage);
+    let mut handler = BudgetHandler::new(
+        MinimalLoggingHandler::new(handler_db, run_id, verbose),
+        tracker,
+    );
+
+    let watchdog = Watchdog::new(config.agent.watchdog_for_type(AgentType::Implement));
+    let stopped = match watchdog
+        .supervise(&mut handle, &mut streamer, &mut handler)
+        .await
+    {
+        Ok(stopped) => stopped,
+        Err(e) => {
+            tracing::warn!("Stream error for #{}: {}", issue.number, e);
+            None
+        }
+    };
 
-    if let Err(e) = streamer.stream(&mut handler).await {
-        tracing::warn!("Stream error for #{}: {}", issue.number, e);
+    // Stop the agent if it ran over budget
+    let budget_exceeded = handler.exceeded().cloned();
+    if budget_exceeded.is_some() {
+        if let Err(e) = handle.kill().await {
+            tracing::warn!("Failed to kill agent for #{}: {}", issue.number, e);
+        }
     }
 
     let status = handle.wait().await?;
+    let run_usage = handler.tracker().usage();
 
     // Update completion status in database (separate block)
     {
@@ -692,6 +933,19 @@ async fn execute_single_issue(
         // Update agent run
         let agent_repo = AgentRunRepository::new(&db);
         if let Ok(mut agent_run) = agent_repo.find_by_id(run_id) {
+            agent_run.record_usage(
+                run_usage.input_tokens,
+                run_usage.output_tokens,
+                run_usage.cache_read_tokens,
+                run_usage.cache_write_tokens,
+                run_usage.cost_usd,
+            );
+            if budget_exceeded.is_some() {
+                agent_run.mark_budget_exceeded();
+            }
+            if let Some(reason) = stopped {
+                agent_run.mark_stopped(reason.as_str());
+            }
             agent_run.complete(status.code().unwrap_or(-1));
             let _ = agent_repo.update(&agent_run);
         }
@@ -708,7 +962,11 @@ async fn execute_single_issue(
         }
     }
 
-    if status.success() {
+    if let Some(reason) = stopped {
+        Err(anyhow::anyhow!("Agent stopped by watchdog: {}", reason))
+    } else if let Some(exceeded) = budget_exceeded {
+        Err(anyhow::anyhow!("Agent stopped: {}", exceeded))
+    } else if status.success() {
         Ok(())
     } else {
         Err(anyhow::anyhow!(
@@ -785,6 +1043,15 @@ impl murmur_core::StreamHandler for MinimalLoggingHandler {
             "session_id": session_id,
         });
         self.log_message("system", &msg.to_string());
+
+        // Persist the session ID so `murmur work --resume` can continue it
+        if let Some(id) = session_id {
+            if let Err(e) = AgentRunRepository::new(&self.db).update_session_id(self.run_id, id) {
+                if self.verbose {
+                    tracing::warn!("Failed to record session ID: {}", e);
+                }
+            }
+        }
     }
 
     fn on_user(&mut self, message: &serde_json::Value) {
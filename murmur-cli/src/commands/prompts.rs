@@ -0,0 +1,128 @@
+//! Prompts command - inspect effective agent prompt templates
+
+use std::path::{Path, PathBuf};
+
+use clap::{Args, Subcommand};
+use murmur_core::agent::{PromptTemplates, TemplateSource};
+use murmur_core::{AgentType, GitRepo};
+
+/// Inspect agent prompt templates
+#[derive(Args, Debug)]
+pub struct PromptsArgs {
+    #[command(subcommand)]
+    pub command: PromptsCommand,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum PromptsCommand {
+    /// Print the effective template for an agent type
+    Show {
+        /// The agent type (implement, test, review, coordinator)
+        #[arg(value_parser = parse_agent_type)]
+        agent_type: AgentType,
+
+        /// Repository whose overrides to apply (defaults to current directory)
+        #[arg(short = 'd', long, default_value = ".")]
+        repo: PathBuf,
+
+        /// List the layers the template is built from instead of printing it
+        #[arg(long)]
+        sources: bool,
+    },
+
+    /// Show how the effective template differs from the built-in one
+    Diff {
+        /// The agent type (implement, test, review, coordinator)
+        #[arg(value_parser = parse_agent_type)]
+        agent_type: AgentType,
+
+        /// Repository whose overrides to apply (defaults to current directory)
+        #[arg(short = 'd', long, default_value = ".")]
+        repo: PathBuf,
+    },
+
+    /// Render templates in strict mode to catch errors and unknown variables
+    Check {
+        /// The agent type to check (defaults to all)
+        #[arg(value_parser = parse_agent_type)]
+        agent_type: Option<AgentType>,
+
+        /// Repository whose overrides to apply (defaults to current directory)
+        #[arg(short = 'd', long, default_value = ".")]
+        repo: PathBuf,
+    },
+}
+
+/// Parse agent type from string
+fn parse_agent_type(s: &str) -> Result<AgentType, String> {
+    s.parse::<AgentType>()
+}
+
+impl PromptsArgs {
+    /// Execute the prompts command
+    pub async fn execute(&self, _verbose: bool) -> anyhow::Result<()> {
+        match &self.command {
+            PromptsCommand::Show {
+                agent_type,
+                repo,
+                sources,
+            } => {
+                let templates = templates_for(repo);
+                if *sources {
+                    for layer in templates.layers(*agent_type)? {
+                        let lines = layer.content.lines().count();
+                        match layer.source {
+                            TemplateSource::Builtin => {
+                                println!("{} ({} lines)", layer.source, lines)
+                            }
+                            _ if layer.extends_base() => {
+                                println!("{} ({} lines, extends)", layer.source, lines)
+                            }
+                            _ => println!("{} ({} lines, replaces)", layer.source, lines),
+                        }
+                    }
+                } else {
+                    print!("{}", templates.resolve(*agent_type)?);
+                }
+            }
+            PromptsCommand::Diff { agent_type, repo } => {
+                let diff = templates_for(repo).diff(*agent_type)?;
+                if diff.is_empty() {
+                    println!("No overrides for the {} template.", agent_type);
+                } else {
+                    print!("{}", diff);
+                }
+            }
+            PromptsCommand::Check { agent_type, repo } => {
+                let templates = templates_for(repo);
+                let agent_types = match agent_type {
+                    Some(agent_type) => vec![*agent_type],
+                    None => AgentType::all().to_vec(),
+                };
+
+                let mut failed = 0;
+                for agent_type in agent_types {
+                    match templates.check(agent_type) {
+                        Ok(()) => println!("{}: ok", agent_type),
+                        Err(e) => {
+                            println!("{}: {}", agent_type, e);
+                            failed += 1;
+                        }
+                    }
+                }
+                if failed > 0 {
+                    anyhow::bail!("{} template(s) failed to render", failed);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Templates for the repository containing `path`
+fn templates_for(path: &Path) -> PromptTemplates {
+    let root = GitRepo::open(path)
+        .map(|repo| repo.root().to_path_buf())
+        .unwrap_or_else(|_| path.to_path_buf());
+    PromptTemplates::discover(root)
+}
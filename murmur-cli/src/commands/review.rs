@@ -0,0 +1,155 @@
+//! Review command - review a worktree and fix the findings in a loop
+
+use std::path::PathBuf;
+
+use clap::Args;
+use murmur_core::workflow::{ReviewLoop, ReviewLoopOutcome, ReviewOutputFormat};
+use murmur_core::{Config, PrintHandler};
+use murmur_db::Database;
+use murmur_github::GitHubClient;
+
+use super::run_accounting;
+
+/// Arguments for the review command
+#[derive(Args, Debug)]
+pub struct ReviewArgs {
+    /// Working directory to review (defaults to current directory)
+    #[arg(short = 'd', long, default_value = ".")]
+    pub workdir: PathBuf,
+
+    /// What the changes are meant to do, given to the review and fix agents
+    #[arg(short, long, default_value = "Review the changes on this branch")]
+    pub task: String,
+
+    /// Branch or commit to diff against (defaults to the repository's default branch)
+    #[arg(long)]
+    pub base: Option<String>,
+
+    /// Maximum review rounds (defaults to workflow.max_review_iterations)
+    #[arg(long)]
+    pub max_iterations: Option<u32>,
+
+    /// GitHub issue the changes are for, recorded with each round
+    #[arg(long)]
+    pub issue: Option<u64>,
+
+    /// Ask the review agent for JSON instead of Markdown
+    #[arg(long)]
+    pub json: bool,
+
+    /// Post the final review on this pull request
+    #[arg(long)]
+    pub pr: Option<u64>,
+
+    /// Repository of the pull request (owner/repo format, uses current repo if not specified)
+    #[arg(short, long)]
+    pub repo: Option<String>,
+}
+
+impl ReviewArgs {
+    /// Execute the review command
+    pub async fn execute(
+        &self,
+        verbose: bool,
+        no_emoji: bool,
+        config: &Config,
+        repo: Option<&str>,
+    ) -> anyhow::Result<()> {
+        let workdir = if self.workdir.is_absolute() {
+            self.workdir.clone()
+        } else {
+            std::env::current_dir()?.join(&self.workdir)
+        };
+        let max_iterations = self
+            .max_iterations
+            .unwrap_or(config.workflow.max_review_iterations);
+
+        println!("Review Loop");
+        println!("===========");
+        println!();
+        println!("Working directory: {}", workdir.display());
+        println!("Max iterations: {}", max_iterations);
+        println!();
+
+        let mut review_loop = ReviewLoop::new(&self.task, &workdir, config.agent.clone())
+            .with_max_iterations(max_iterations)
+            .with_accounting(run_accounting(config, self.repo.as_deref().or(repo)));
+        if let Some(ref base) = self.base {
+            review_loop = review_loop.with_base(base);
+        }
+        if let Some(issue) = self.issue {
+            review_loop = review_loop.with_issue_number(issue as i64);
+        }
+        if self.json {
+            review_loop = review_loop.with_output_format(ReviewOutputFormat::Json);
+        }
+        match Database::open() {
+            Ok(db) => review_loop = review_loop.with_database(db),
+            Err(e) => eprintln!("Warning: Review rounds won't be recorded: {}", e),
+        }
+
+        let mut handler = PrintHandler::new(verbose);
+        let outcome = review_loop.run(&mut handler).await?;
+        print_outcome(&outcome, no_emoji);
+
+        if let (Some(pr), Some(result)) = (self.pr, outcome.last()) {
+            let repo_str = self.repo.as_deref().or(repo).ok_or_else(|| {
+                anyhow::anyhow!(
+                    "No repository specified. Use --repo owner/repo or run from a git repository"
+                )
+            })?;
+            let client = GitHubClient::from_url(repo_str).map_err(|e| anyhow::anyhow!("{}", e))?;
+            let review = client
+                .post_review_result(pr, result)
+                .await
+                .map_err(|e| anyhow::anyhow!("Failed to post review on PR #{}: {}", pr, e))?;
+            println!();
+            println!("Posted review on PR #{} ({})", pr, review.state);
+        }
+
+        if outcome.approved || outcome.is_clean() {
+            Ok(())
+        } else {
+            Err(anyhow::anyhow!(
+                "Review not approved after {} round(s)",
+                outcome.rounds.len()
+            ))
+        }
+    }
+}
+
+/// Print a summary of each round and the remaining issues
+pub fn print_outcome(outcome: &ReviewLoopOutcome, no_emoji: bool) {
+    println!();
+    for (i, round) in outcome.rounds.iter().enumerate() {
+        println!(
+            "Round {}: {} ({} blocking, {} important, {} suggestions)",
+            i + 1,
+            round.verdict,
+            round.blocking.len(),
+            round.important.len(),
+            round.suggestions.len()
+        );
+    }
+    println!();
+
+    let Some(last) = outcome.last() else {
+        return;
+    };
+    if outcome.approved {
+        println!("{} Changes approved", if no_emoji { "[OK]" } else { "✅" });
+    } else if outcome.is_clean() {
+        println!(
+            "{} No blocking or important issues remain",
+            if no_emoji { "[OK]" } else { "✅" }
+        );
+    } else {
+        println!(
+            "{} Unresolved review issues:",
+            if no_emoji { "[WARN]" } else { "⚠️" }
+        );
+        for issue in last.blocking.iter().chain(&last.important) {
+            println!("  - {}", issue);
+        }
+    }
+}
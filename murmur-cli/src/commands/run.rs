@@ -3,6 +3,7 @@
 use std::path::PathBuf;
 
 use clap::Args;
+use murmur_core::agent::Watchdog;
 use murmur_core::{AgentSpawner, Config, OutputStreamer, PrintHandler};
 
 /// Arguments for the run command
@@ -72,7 +73,7 @@ impl RunArgs {
             murmur_core::agent::AgentType::default(),
         );
 
-        println!("Spawning Claude Code agent...");
+        println!("Spawning {} agent...", config.agent.backend);
         let mut handle = spawner.spawn(&self.prompt, &workdir).await?;
 
         // Get stdout for streaming
@@ -88,13 +89,22 @@ impl RunArgs {
         // Stream the output
         let mut streamer = OutputStreamer::new(stdout);
         let mut handler = PrintHandler::new(verbose);
-        streamer.stream(&mut handler).await?;
+        let watchdog = Watchdog::new(
+            config
+                .agent
+                .watchdog_for_type(murmur_core::agent::AgentType::default()),
+        );
+        let stopped = watchdog
+            .supervise(&mut handle, &mut streamer, &mut handler)
+            .await?;
 
         // Wait for the process to complete
         let status = handle.wait().await?;
 
         println!();
-        if status.success() {
+        if let Some(reason) = stopped {
+            println!("Agent stopped by watchdog: {}", reason);
+        } else if status.success() {
             println!("Agent completed successfully");
         } else {
             println!("Agent exited with status: {}", status);
@@ -0,0 +1,33 @@
+//! Sandbox bridge command - runs inside an agent's sandbox, not by hand
+
+use std::ffi::OsString;
+use std::path::PathBuf;
+
+use clap::Args;
+
+/// Arguments for the sandbox bridge command
+#[derive(Args, Debug)]
+pub struct SandboxBridgeArgs {
+    /// Unix socket of the egress proxy
+    #[arg(long)]
+    pub socket: PathBuf,
+
+    /// The agent command to run
+    #[arg(last = true, required = true)]
+    pub command: Vec<OsString>,
+}
+
+impl SandboxBridgeArgs {
+    /// Run the agent with the egress proxy reachable on loopback, exiting
+    /// with its status
+    pub async fn execute(&self) -> anyhow::Result<()> {
+        let (program, args) = self
+            .command
+            .split_first()
+            .ok_or_else(|| anyhow::anyhow!("No command to run"))?;
+        let status = murmur_core::agent::run_egress_bridge(&self.socket, program, args)
+            .await
+            .map_err(|e| anyhow::anyhow!("Egress bridge failed: {}", e))?;
+        std::process::exit(status.code().unwrap_or(1));
+    }
+}
@@ -0,0 +1,126 @@
+//! Stats command - report token usage and cost of agent runs
+
+use chrono::{Duration, Utc};
+use clap::{Args, Subcommand, ValueEnum};
+use murmur_db::{repos::AgentRunRepository, CostGroupBy, CostSummary, Database};
+
+/// Show usage statistics
+#[derive(Args, Debug)]
+pub struct StatsArgs {
+    #[command(subcommand)]
+    pub command: StatsCommand,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum StatsCommand {
+    /// Show token usage and cost of agent runs
+    Cost {
+        /// Dimension to aggregate by
+        #[arg(long, value_enum, default_value = "issue")]
+        by: CostDimension,
+
+        /// Only include runs started in the last N days
+        #[arg(long)]
+        days: Option<i64>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum CostDimension {
+    Issue,
+    AgentType,
+    Model,
+    Repository,
+    Day,
+}
+
+impl From<CostDimension> for CostGroupBy {
+    fn from(dimension: CostDimension) -> Self {
+        match dimension {
+            CostDimension::Issue => CostGroupBy::Issue,
+            CostDimension::AgentType => CostGroupBy::AgentType,
+            CostDimension::Model => CostGroupBy::Model,
+            CostDimension::Repository => CostGroupBy::Repository,
+            CostDimension::Day => CostGroupBy::Day,
+        }
+    }
+}
+
+impl StatsArgs {
+    /// Execute the stats command
+    pub async fn execute(&self, _verbose: bool) -> anyhow::Result<()> {
+        match &self.command {
+            StatsCommand::Cost { by, days } => show_cost(*by, *days),
+        }
+    }
+}
+
+fn show_cost(by: CostDimension, days: Option<i64>) -> anyhow::Result<()> {
+    let db = Database::open().map_err(|e| anyhow::anyhow!("Failed to open database: {}", e))?;
+    let repo = AgentRunRepository::new(&db);
+
+    let since = days.map(|d| Utc::now() - Duration::days(d));
+    let summaries = repo
+        .cost_summary(by.into(), since)
+        .map_err(|e| anyhow::anyhow!("Failed to query agent run costs: {}", e))?;
+
+    if summaries.is_empty() {
+        println!("No agent runs recorded.");
+        return Ok(());
+    }
+
+    let header = match by {
+        CostDimension::Issue => "Issue",
+        CostDimension::AgentType => "Agent type",
+        CostDimension::Model => "Model",
+        CostDimension::Repository => "Repository",
+        CostDimension::Day => "Day",
+    };
+    let key_width = summaries
+        .iter()
+        .map(|s| s.key.len())
+        .chain(std::iter::once(header.len()))
+        .max()
+        .unwrap_or(0);
+
+    println!(
+        "{:<width$}  {:>5}  {:>12}  {:>12}  {:>12}  {:>10}",
+        header,
+        "Runs",
+        "Input",
+        "Output",
+        "Cached",
+        "Cost",
+        width = key_width
+    );
+    for summary in &summaries {
+        print_row(summary, key_width);
+    }
+
+    let total = CostSummary {
+        key: "Total".to_string(),
+        runs: summaries.iter().map(|s| s.runs).sum(),
+        input_tokens: summaries.iter().map(|s| s.input_tokens).sum(),
+        output_tokens: summaries.iter().map(|s| s.output_tokens).sum(),
+        cache_read_tokens: summaries.iter().map(|s| s.cache_read_tokens).sum(),
+        cache_write_tokens: summaries.iter().map(|s| s.cache_write_tokens).sum(),
+        cost_usd: summaries.iter().map(|s| s.cost_usd).sum(),
+    };
+    println!();
+    print_row(&total, key_width);
+
+    Ok(())
+}
+
+fn print_row(summary: &CostSummary, key_width: usize) {
+    println!(
+        "{:<width$}  {:>5}  {:>12}  {:>12}  {:>12}  {:>10}",
+        summary.key,
+        summary.runs,
+        summary.input_tokens,
+        summary.output_tokens,
+        summary.cache_read_tokens + summary.cache_write_tokens,
+        format!("${:.2}", summary.cost_usd),
+        width = key_width
+    );
+}
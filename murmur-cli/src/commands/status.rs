@@ -223,6 +223,12 @@ impl StatusArgs {
                         println!("      Duration: {:.1}s", duration);
                     }
 
+                    if let Some(outcome @ ("timeout" | "stalled" | "budget_exceeded")) =
+                        run.outcome.as_deref()
+                    {
+                        println!("      Stopped: {}", outcome);
+                    }
+
                     println!();
                 }
             }
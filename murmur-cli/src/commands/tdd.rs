@@ -12,17 +12,24 @@
 use std::path::PathBuf;
 
 use clap::Args;
-use murmur_core::workflow::{TestFramework, TestRunner};
+use murmur_core::agent::AgentType;
+use murmur_core::workflow::{ApprovalDecision, ApprovalGates, QualityCheck, TestResults};
 use murmur_core::{
-    AgentFactory, AgentType, Config, OutputStreamer, PrintHandler, TddPhase, TddWorkflow,
+    Config, PrintHandler, TddExecutor, TddObserver, TddOutcome, TddPhase, TddState, TddWorkflow,
 };
+use murmur_db::{Database, TddSessionRepository};
+
+use super::run_accounting;
+
+/// Implement -> VerifyGreen iterations allowed when --max-iterations isn't given
+const DEFAULT_MAX_ITERATIONS: u32 = 3;
 
 /// Arguments for the tdd command
 #[derive(Args, Debug)]
 pub struct TddArgs {
     /// The behavior to implement using TDD
-    #[arg(required = true)]
-    pub behavior: String,
+    #[arg(required_unless_present = "resume", conflicts_with = "resume")]
+    pub behavior: Option<String>,
 
     /// Working directory (defaults to current directory)
     #[arg(short = 'd', long, default_value = ".")]
@@ -36,13 +43,21 @@ pub struct TddArgs {
     #[arg(long)]
     pub skip_refactor: bool,
 
-    /// Maximum iterations for Implement->VerifyGreen loop
-    #[arg(long, default_value = "3")]
-    pub max_iterations: u32,
+    /// Maximum iterations for Implement->VerifyGreen loop (default: 3)
+    #[arg(long)]
+    pub max_iterations: Option<u32>,
 
     /// Dry run - show what would be executed without running agents
     #[arg(long)]
     pub dry_run: bool,
+
+    /// Resume the last unfinished TDD workflow in the working directory
+    #[arg(long, conflicts_with_all = ["skip_spec", "dry_run"])]
+    pub resume: bool,
+
+    /// Wait for `murmur approve` before starting these phases (e.g. write-tests,implement)
+    #[arg(long, value_name = "PHASE", value_delimiter = ',')]
+    pub approve_before: Vec<TddPhase>,
 }
 
 impl TddArgs {
@@ -52,6 +67,7 @@ impl TddArgs {
         verbose: bool,
         no_emoji: bool,
         config: &Config,
+        repo: Option<&str>,
     ) -> anyhow::Result<()> {
         // Resolve to absolute path
         let workdir = if self.workdir.is_absolute() {
@@ -60,45 +76,84 @@ impl TddArgs {
             std::env::current_dir()?.join(&self.workdir)
         };
 
-        if verbose {
-            tracing::info!(
-                behavior = %self.behavior,
-                workdir = %workdir.display(),
-                skip_spec = %self.skip_spec,
-                skip_refactor = %self.skip_refactor,
-                max_iterations = %self.max_iterations,
-                "Starting TDD workflow"
-            );
-        }
-
-        // Create workflow
-        let mut workflow = if self.skip_spec {
-            TddWorkflow::new_without_spec(&self.behavior, &workdir)
+        let db = if self.dry_run {
+            None
         } else {
-            TddWorkflow::with_config(&self.behavior, &workdir, config.agent.clone())
+            match Database::open() {
+                Ok(db) => Some(db),
+                Err(e) if self.resume => {
+                    return Err(anyhow::anyhow!("Failed to open database: {}", e));
+                }
+                Err(e) => {
+                    eprintln!("Warning: TDD progress won't be saved for --resume: {}", e);
+                    None
+                }
+            }
         };
 
-        // Configure workflow
+        let mut executor = match (self.resume, &self.behavior) {
+            (true, _) => {
+                let db = db.as_ref().expect("database is required to resume");
+                let session = TddSessionRepository::new(db)
+                    .find_latest_resumable_by_workdir(&workdir.to_string_lossy())
+                    .map_err(|e| anyhow::anyhow!("{}", e))?
+                    .ok_or_else(|| {
+                        anyhow::anyhow!("No unfinished TDD workflow found in {}", workdir.display())
+                    })?;
+                println!(
+                    "Resuming TDD session #{} from {} (started {})",
+                    session.id.unwrap_or_default(),
+                    session.phase,
+                    session.created_at.format("%Y-%m-%d %H:%M")
+                );
+                println!();
+                TddExecutor::resume(session, config.agent.clone())?
+            }
+            (false, Some(behavior)) => {
+                let mut executor = TddExecutor::new(behavior, &workdir, config.agent.clone());
+                if self.skip_spec {
+                    executor = executor.with_skip_spec();
+                }
+                executor
+            }
+            (false, None) => unreachable!("clap requires a behavior without --resume"),
+        }
+        .with_test_config(&config.test)
+        .with_quality_config(&config.quality)
+        .with_accounting(run_accounting(config, repo));
+        if let Some(max_iterations) = self.max_iterations {
+            executor = executor.with_max_iterations(max_iterations);
+        } else if !self.resume {
+            executor = executor.with_max_iterations(DEFAULT_MAX_ITERATIONS);
+        }
         if self.skip_refactor {
-            workflow.state_mut().skip_refactor = true;
+            executor = executor.with_skip_refactor();
         }
-        workflow.state_mut().max_iterations = self.max_iterations;
-
-        // Helper macro for emoji/ASCII output
-        macro_rules! emoji {
-            ($e:expr, $ascii:expr) => {
-                if no_emoji {
-                    $ascii
-                } else {
-                    $e
-                }
-            };
+        if !self.approve_before.is_empty() {
+            executor = executor.with_approval_gates(ApprovalGates::before(
+                self.approve_before.iter().map(|phase| phase.as_str()),
+            ));
+        }
+        if let Some(db) = db {
+            executor = executor.with_database(db);
+        }
+
+        let state = executor.workflow().state();
+        if verbose {
+            tracing::info!(
+                behavior = %state.behavior,
+                workdir = %workdir.display(),
+                skip_spec = %state.skip_spec,
+                skip_refactor = %state.skip_refactor,
+                max_iterations = %state.max_iterations,
+                "Starting TDD workflow"
+            );
         }
 
         println!("TDD Workflow");
         println!("============");
         println!();
-        println!("Behavior: {}", self.behavior);
+        println!("Behavior: {}", state.behavior);
         println!("Working directory: {}", workdir.display());
         if let Some(ref model) = config.agent.model {
             println!("Model: {}", model);
@@ -108,196 +163,42 @@ impl TddArgs {
         if self.dry_run {
             println!("[Dry run] Would execute TDD workflow with the following phases:");
             println!();
-            self.show_planned_phases(&workflow, no_emoji);
+            self.show_planned_phases(executor.workflow(), no_emoji);
             return Ok(());
         }
 
-        // Detect test framework
-        let framework = TestFramework::detect(&workdir).unwrap_or(TestFramework::Cargo);
-        println!("Detected test framework: {}", framework.name());
-        println!();
-
-        // Create test runner for validation phases
-        let test_runner = TestRunner::new(workdir.clone()).with_framework(framework);
-
-        // Create agent factory
-        let factory = AgentFactory::with_config(config.agent.clone());
-
-        // Run the TDD cycle
-        while !workflow.is_complete() && !workflow.should_give_up() {
-            let phase = workflow.phase();
-            let phase_num = phase_number(&phase);
-            let total_phases = if self.skip_spec { 6 } else { 7 };
-
-            println!(
-                "Phase {}/{}: {} {}",
-                phase_num,
-                total_phases,
-                emoji!(phase_emoji(&phase), phase_ascii(&phase)),
-                phase.description()
-            );
-            println!();
-
-            match phase {
-                TddPhase::WriteSpec
-                | TddPhase::WriteTests
-                | TddPhase::Implement
-                | TddPhase::Refactor => {
-                    // Agent-driven phases
-                    let prompt = workflow.current_prompt();
-
-                    if verbose {
-                        println!("Prompt: {}", prompt);
-                        println!();
-                    }
-
-                    println!("Starting agent...");
-
-                    // Choose agent type based on phase
-                    let agent_type = match phase {
-                        TddPhase::WriteTests => AgentType::Test,
-                        _ => AgentType::Implement,
-                    };
-                    let typed_agent = factory.create(agent_type);
-
-                    // Spawn and run agent
-                    let mut handle = typed_agent.spawn_with_task(&prompt, &workdir).await?;
-
-                    let stdout = handle
-                        .child_mut()
-                        .stdout
-                        .take()
-                        .ok_or_else(|| anyhow::anyhow!("Failed to capture agent stdout"))?;
-
-                    // Stream output
-                    let mut streamer = OutputStreamer::new(stdout);
-                    let mut handler = PrintHandler::new(verbose);
-                    streamer.stream(&mut handler).await?;
-
-                    let status = handle.wait().await?;
-
-                    if status.success() {
-                        println!();
-                        println!("{} Phase completed", emoji!("✅", "[OK]"));
-                        workflow.advance(true, None);
-                    } else {
-                        println!();
-                        println!(
-                            "{} Agent exited with status: {}",
-                            emoji!("❌", "[FAIL]"),
-                            status
-                        );
-                        // Don't advance, let user decide what to do
-                        return Err(anyhow::anyhow!(
-                            "Agent failed in {} phase",
-                            phase.description()
-                        ));
-                    }
-                }
-                TddPhase::VerifyRed => {
-                    // Run tests and expect them to fail
-                    println!("Running tests (expecting failures)...");
-                    let results = test_runner.run();
-
-                    println!();
-                    println!(
-                        "Test results: {} passed, {} failed, {} skipped",
-                        results.passed, results.failed, results.skipped
-                    );
-
-                    if results.is_red() {
-                        println!();
-                        println!(
-                            "{} Tests failed as expected (red phase)",
-                            emoji!("✅", "[OK]")
-                        );
-                        workflow.advance(true, None);
-                    } else if results.passed > 0 && results.failed == 0 {
-                        println!();
-                        println!(
-                            "{} Tests passed unexpectedly - tests may not be testing new behavior",
-                            emoji!("⚠️", "[WARN]")
-                        );
-                        println!("Going back to WriteTests phase...");
-                        workflow.retry_tests(Some("Tests passed unexpectedly".to_string()));
-                    } else {
-                        println!();
-                        println!(
-                            "{} No tests found or error running tests",
-                            emoji!("❌", "[FAIL]")
-                        );
-                        workflow.retry_tests(Some("No tests found".to_string()));
-                    }
-                }
-                TddPhase::VerifyGreen => {
-                    // Run tests and expect them to pass
-                    let iteration = workflow.state().iterations;
-                    println!(
-                        "Running tests (iteration {}/{})...",
-                        iteration + 1,
-                        self.max_iterations
-                    );
-                    let results = test_runner.run();
-
-                    println!();
-                    println!(
-                        "Test results: {} passed, {} failed, {} skipped",
-                        results.passed, results.failed, results.skipped
-                    );
-
-                    if results.is_green() {
-                        println!();
-                        println!("{} All tests pass (green phase)", emoji!("✅", "[OK]"));
-                        workflow.advance(true, None);
-                    } else {
-                        println!();
-                        println!(
-                            "{} {} tests still failing",
-                            emoji!("❌", "[FAIL]"),
-                            results.failed
-                        );
-
-                        if workflow.state().iterations + 1 >= self.max_iterations {
-                            println!();
-                            println!(
-                                "{} Maximum iterations reached, giving up",
-                                emoji!("🛑", "[STOP]")
-                            );
-                        } else {
-                            println!("Returning to Implement phase...");
-                            workflow
-                                .retry_implement(Some(format!("{} tests failing", results.failed)));
-                        }
-                    }
-                }
-                TddPhase::Complete => {
-                    // Should not reach here due to while condition
-                    break;
+        match executor.test_suites() {
+            [] => {}
+            [suite] => {
+                println!("Detected test framework: {}", suite.label());
+                println!();
+            }
+            suites => {
+                println!("Test suites:");
+                for suite in suites {
+                    println!("  {} ({})", suite.name, suite.label());
                 }
+                println!();
             }
-            println!();
         }
 
-        // Final status
-        if workflow.is_complete() {
-            println!("═══════════════════════════════════════");
-            println!(
-                "{} TDD workflow completed successfully!",
-                emoji!("🎉", "[DONE]")
-            );
-            println!("═══════════════════════════════════════");
-        } else if workflow.should_give_up() {
-            println!("═══════════════════════════════════════");
+        let mut printer = TddPrinter::new(verbose, no_emoji);
+        let mut handler = PrintHandler::new(verbose);
+        let outcome = executor.run(&mut printer, &mut handler).await;
+        let resumable =
+            executor.session_id().is_some() && !outcome.as_ref().is_ok_and(|o| o.completed);
+        let result = match outcome {
+            Ok(outcome) => printer.print_outcome(&outcome),
+            Err(e) => Err(e.into()),
+        };
+        if resumable {
+            println!();
             println!(
-                "{} TDD workflow failed after {} iterations",
-                emoji!("💥", "[FAIL]"),
-                workflow.state().iterations
+                "Progress was saved. Continue with: murmur tdd --resume -d {}",
+                workdir.display()
             );
-            println!("═══════════════════════════════════════");
-            return Err(anyhow::anyhow!("TDD workflow failed after max iterations"));
         }
-
-        Ok(())
+        result
     }
 
     fn show_planned_phases(&self, workflow: &TddWorkflow, no_emoji: bool) {
@@ -360,6 +261,177 @@ impl TddArgs {
     }
 }
 
+/// Prints the progress of a TDD run
+pub(crate) struct TddPrinter {
+    verbose: bool,
+    no_emoji: bool,
+}
+
+impl TddPrinter {
+    /// Create a printer
+    pub(crate) fn new(verbose: bool, no_emoji: bool) -> Self {
+        Self { verbose, no_emoji }
+    }
+
+    fn emoji<'a>(&self, emoji: &'a str, ascii: &'a str) -> &'a str {
+        if self.no_emoji {
+            ascii
+        } else {
+            emoji
+        }
+    }
+
+    /// Print the final status, failing if the workflow gave up
+    pub(crate) fn print_outcome(&self, outcome: &TddOutcome) -> anyhow::Result<()> {
+        if !outcome.completed {
+            println!(
+                "{} Maximum iterations reached, giving up",
+                self.emoji("🛑", "[STOP]")
+            );
+            println!();
+        }
+        println!("═══════════════════════════════════════");
+        if outcome.completed {
+            println!(
+                "{} TDD workflow completed successfully!",
+                self.emoji("🎉", "[DONE]")
+            );
+            println!("═══════════════════════════════════════");
+            Ok(())
+        } else {
+            println!(
+                "{} TDD workflow failed after {} iterations",
+                self.emoji("💥", "[FAIL]"),
+                outcome.iterations
+            );
+            println!("═══════════════════════════════════════");
+            Err(anyhow::anyhow!("TDD workflow failed after max iterations"))
+        }
+    }
+}
+
+impl TddObserver for TddPrinter {
+    fn on_phase_start(&mut self, phase: TddPhase, state: &TddState) {
+        let total_phases = if state.skip_spec { 6 } else { 7 };
+        println!(
+            "Phase {}/{}: {} {}",
+            phase_number(&phase),
+            total_phases,
+            self.emoji(phase_emoji(&phase), phase_ascii(&phase)),
+            phase.description()
+        );
+        println!();
+
+        if phase == TddPhase::VerifyGreen {
+            println!(
+                "Running tests (iteration {}/{})...",
+                state.iterations, state.max_iterations
+            );
+        }
+    }
+
+    fn on_baseline(&mut self, results: &TestResults) {
+        println!("Running existing tests for a baseline...");
+        println!("Baseline: {}", results.summary());
+        if !results.has_test_cases() {
+            println!(
+                "{} Individual tests not reported, falling back to pass/fail counts",
+                self.emoji("⚠️", "[WARN]")
+            );
+        }
+        println!();
+    }
+
+    fn on_agent_start(&mut self, _phase: TddPhase, _agent_type: AgentType, prompt: &str) {
+        if self.verbose {
+            println!("Prompt: {}", prompt);
+            println!();
+        }
+        println!("Starting agent...");
+    }
+
+    fn on_tests_start(&mut self, phase: TddPhase) {
+        if phase == TddPhase::VerifyRed {
+            println!("Running tests (expecting failures)...");
+        }
+    }
+
+    fn on_test_output(&mut self, _phase: TddPhase, line: &str) {
+        println!("  {}", line);
+    }
+
+    fn on_test_results(&mut self, phase: TddPhase, results: &TestResults) {
+        println!();
+        println!(
+            "Test results: {} passed, {} failed, {} skipped",
+            results.passed, results.failed, results.skipped
+        );
+        if results.timed_out {
+            println!("{} {}", self.emoji("⏱️", "[TIMEOUT]"), results.summary());
+        } else if let Some(ref error) = results.execution_error {
+            println!("{} {}", self.emoji("❌", "[FAIL]"), error);
+        }
+        if self.verbose && phase == TddPhase::VerifyGreen {
+            for test in results.failures() {
+                match test.location {
+                    Some(ref location) => println!(
+                        "  {} {} ({})",
+                        self.emoji("❌", "[FAIL]"),
+                        test.name,
+                        location
+                    ),
+                    None => println!("  {} {}", self.emoji("❌", "[FAIL]"), test.name),
+                }
+                if let Some(ref message) = test.message {
+                    println!("      {}", message);
+                }
+            }
+        }
+    }
+
+    fn on_phase_complete(&mut self, phase: TddPhase) {
+        println!();
+        match phase {
+            TddPhase::VerifyRed => println!(
+                "{} New tests failed as expected (red phase)",
+                self.emoji("✅", "[OK]")
+            ),
+            TddPhase::VerifyGreen => {
+                println!("{} New tests pass (green phase)", self.emoji("✅", "[OK]"))
+            }
+            _ => println!("{} Phase completed", self.emoji("✅", "[OK]")),
+        }
+        println!();
+    }
+
+    fn on_retry(&mut self, _from: TddPhase, to: TddPhase, reason: &str) {
+        println!();
+        println!("{} {}", self.emoji("⚠️", "[WARN]"), reason);
+        println!("Returning to {} phase...", to.description());
+        println!();
+    }
+
+    fn on_quality_check(&mut self, _phase: TddPhase, check: &QualityCheck, problem: Option<&str>) {
+        match problem {
+            None => println!("{} Check passed: {}", self.emoji("✅", "[OK]"), check),
+            Some(problem) => {
+                println!("{} Check failed: {}", self.emoji("❌", "[FAIL]"), check);
+                if self.verbose {
+                    println!("{}", problem);
+                }
+            }
+        }
+    }
+
+    fn on_approval_pending(&mut self, _from: TddPhase, to: TddPhase, approval_id: i64) {
+        super::approve::print_pending(approval_id, to.description(), self.no_emoji);
+    }
+
+    fn on_approval(&mut self, _to: TddPhase, decision: &ApprovalDecision) {
+        super::approve::print_decision(decision, self.no_emoji);
+    }
+}
+
 fn phase_number(phase: &TddPhase) -> u32 {
     match phase {
         TddPhase::WriteSpec => 1,
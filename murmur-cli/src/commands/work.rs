@@ -1,15 +1,27 @@
 //! Work command - start working on an issue with dependency checking
 
 use clap::Args;
+use murmur_core::agent::{AgentType, BudgetHandler, BudgetTracker, Usage, Watchdog};
+use murmur_core::workflow::{AgentRunner, ApprovalGates, QualityGates, ReviewLoop, RunAccounting};
 use murmur_core::{
-    AgentSpawner, BranchingOptions, Config, GitRepo, OutputStreamer, Secrets, WorktreeOptions,
+    default_cache_dir, worktree_path, AgentSpawner, BranchingOptions, Config, GitRepo,
+    OutputStreamer, PrintHandler, Secrets, TddExecutor, TddPhase, WorktreeOptions,
 };
 use murmur_db::{
     models::{AgentRun, ConversationLog, WorktreeRecord},
     repos::{AgentRunRepository, ConversationRepository, WorktreeRepository},
     Database,
 };
-use murmur_github::{DependencyStatus, GitHubClient, IssueDependencies, IssueState};
+use murmur_github::{
+    parse_thread_reports, DependencyStatus, GitHubClient, IssueApprovals, IssueDependencies,
+    IssueState, PrState, ThreadOutcome, THREAD_REPORT_INSTRUCTIONS,
+};
+
+use super::tdd::TddPrinter;
+
+/// Times the implement agent is asked to fix failed quality checks before
+/// `murmur work` gives up without committing
+const QUALITY_FIX_ATTEMPTS: u32 = 2;
 
 /// Work on a GitHub issue
 #[derive(Args, Debug)]
@@ -36,6 +48,20 @@ pub struct WorkArgs {
     /// Resume from the last interrupted or failed run for this issue
     #[arg(long)]
     pub resume: bool,
+
+    /// Address unresolved review comments on the issue's open PR
+    #[arg(long, conflicts_with_all = ["resume", "no_agent", "prompt"])]
+    pub address_feedback: bool,
+
+    /// Work through the TDD cycle (tests first, then implementation) instead of a single agent run
+    #[arg(long, conflicts_with_all = ["resume", "no_agent", "address_feedback"])]
+    pub tdd: bool,
+
+    /// With --tdd, wait for approval before starting these phases (e.g. implement)
+    ///
+    /// Approve with `murmur approve` or a `/murmur approve` comment on the issue.
+    #[arg(long, value_name = "PHASE", value_delimiter = ',', requires = "tdd")]
+    pub approve_before: Vec<TddPhase>,
 }
 
 impl WorkArgs {
@@ -72,10 +98,29 @@ impl WorkArgs {
         println!("#{}: {}", issue.number, issue.title);
         println!();
 
+        if self.address_feedback {
+            let accounting = RunAccounting::from_config(config).with_repository(format!(
+                "{}/{}",
+                client.owner(),
+                client.repo()
+            ));
+            address_feedback(&client, &db, config, &accounting, &issue, verbose, no_emoji).await?;
+            return Ok(());
+        }
+
+        // Spawner for the implement agent (also used to check for native resume support)
+        let mut spawner = AgentSpawner::from_config(
+            config.agent.clone(),
+            murmur_core::agent::AgentType::default(),
+        );
+
         // Check for resumable runs if --resume flag is set
+        let mut resume_plan: Option<ResumePlan> = None;
+        let mut resumed_from = None;
         if self.resume {
             use murmur_core::{
-                build_resume_prompt, find_latest_incomplete_run, reconstruct_conversation,
+                build_resume_prompt, build_session_resume_prompt, find_latest_incomplete_run,
+                reconstruct_conversation,
             };
 
             println!("Checking for incomplete runs to resume...");
@@ -83,6 +128,8 @@ impl WorkArgs {
                 .map_err(|e| anyhow::anyhow!("{}", e))?
             {
                 Some(resumable) => {
+                    // The new run supersedes it, even when starting fresh
+                    resumed_from = Some(resumable.run_id);
                     println!("Found incomplete run from {}", resumable.start_time);
                     println!("  Run ID: {}", resumable.run_id);
                     println!("  Messages: {}", resumable.message_count);
@@ -91,15 +138,58 @@ impl WorkArgs {
                     } else {
                         println!("  Status: Interrupted (no exit code)");
                     }
+                    if let Some(ref session_id) = resumable.session_id {
+                        println!("  Session: {}", session_id);
+                    }
                     println!();
 
-                    if resumable.message_count == 0 {
+                    let reason = resumable.resume_reason();
+                    let native_session = resumable
+                        .session_id
+                        .clone()
+                        .filter(|id| spawner.can_resume(id, &resumable.workdir));
+
+                    if !resumable.workdir.exists() {
                         println!(
-                            "{}  No conversation history found. Starting fresh instead.",
+                            "{}  Worktree {} no longer exists. Starting fresh instead.",
+                            emoji(no_emoji, "⚠️", "[WARN]"),
+                            resumable.workdir.display()
+                        );
+                        println!();
+                    } else if let Some(session_id) = native_session {
+                        println!(
+                            "{} Resuming session {} in {}",
+                            emoji(no_emoji, "🔄", "[RESUME]"),
+                            session_id,
+                            resumable.workdir.display()
+                        );
+                        println!();
+
+                        resume_plan = Some(ResumePlan {
+                            workdir: resumable.workdir.clone(),
+                            prompt: Some(build_session_resume_prompt(&reason)),
+                            session_id: Some(session_id),
+                        });
+                    } else if resumable.message_count == 0 {
+                        println!(
+                            "{}  No conversation history found. Starting fresh in the existing worktree.",
                             emoji(no_emoji, "⚠️", "[WARN]")
                         );
                         println!();
+
+                        resume_plan = Some(ResumePlan {
+                            workdir: resumable.workdir.clone(),
+                            prompt: None,
+                            session_id: None,
+                        });
                     } else {
+                        if resumable.session_id.is_some() {
+                            println!(
+                                "{}  Previous session is no longer available. Falling back to a summary.",
+                                emoji(no_emoji, "⚠️", "[WARN]")
+                            );
+                        }
+
                         // Reconstruct conversation
                         let messages = reconstruct_conversation(&db, resumable.run_id)
                             .map_err(|e| anyhow::anyhow!("{}", e))?;
@@ -108,31 +198,15 @@ impl WorkArgs {
                             "Reconstructed {} messages from previous session",
                             messages.len()
                         );
-
-                        // Build resume prompt
-                        let original_prompt = build_prompt_from_issue(&issue);
-                        let reason = if resumable.had_error() {
-                            format!(
-                                "Previous session exited with error code {}",
-                                resumable.exit_code.unwrap()
-                            )
-                        } else {
-                            "Previous session was interrupted".to_string()
-                        };
-
-                        let _resume_prompt =
-                            build_resume_prompt(&original_prompt, &messages, &reason);
-
-                        println!("Resume prompt prepared. Note: Conversation history is for context only.");
                         println!("The agent will review the current state and continue work.");
                         println!();
 
-                        // TODO: Use resume_prompt instead of original prompt when spawning agent
-                        // This will require passing conversation history to Claude Code
-                        // For now, just inform the user
-                        println!("{}  Resume functionality detected previous session but will start fresh.", emoji(no_emoji, "⚠️", "[WARN]"));
-                        println!("Full resume with conversation history will be implemented in a future update.");
-                        println!();
+                        let original_prompt = build_prompt_from_issue(&issue);
+                        resume_plan = Some(ResumePlan {
+                            workdir: resumable.workdir.clone(),
+                            prompt: Some(build_resume_prompt(&original_prompt, &messages, &reason)),
+                            session_id: None,
+                        });
                     }
                 }
                 None => {
@@ -280,119 +354,134 @@ impl WorkArgs {
             }
         }
 
-        // Check for existing worktree in database
-        let worktree_repo = WorktreeRepository::new(&db);
         let branch_name = format!("murmur/issue-{}", self.issue);
 
-        if let Ok(Some(existing)) = worktree_repo.find_by_branch(&branch_name) {
-            let path = std::path::PathBuf::from(&existing.path);
-            let exists_on_disk = path.exists();
+        let info = if let Some(ref plan) = resume_plan {
+            // Reuse the worktree of the interrupted run so the session's context matches
+            println!("Reusing worktree {}", plan.workdir.display());
+            println!();
 
-            if existing.is_active() && exists_on_disk {
-                println!(
-                    "{}  Worktree already exists and is active:",
-                    emoji(no_emoji, "⚠️", "[WARN]")
-                );
-                println!("  Path:   {}", existing.path);
-                println!("  Branch: {}", existing.branch_name);
-                println!();
+            murmur_core::WorktreeInfo {
+                path: plan.workdir.clone(),
+                branch: branch_name.clone(),
+                commit: String::new(),
+            }
+        } else {
+            // Check for existing worktree in database
+            let worktree_repo = WorktreeRepository::new(&db);
 
-                if !self.force {
-                    println!("The worktree appears to be in use.");
-                    println!("Options:");
-                    println!("  1. Use --force to recreate it");
+            if let Ok(Some(existing)) = worktree_repo.find_by_branch(&branch_name) {
+                let path = std::path::PathBuf::from(&existing.path);
+                let exists_on_disk = path.exists();
+
+                if existing.is_active() && exists_on_disk {
                     println!(
-                        "  2. Use 'murmur worktree clean --stale-only' to clean up stale worktrees"
+                        "{}  Worktree already exists and is active:",
+                        emoji(no_emoji, "⚠️", "[WARN]")
                     );
-                    return Ok(());
-                } else {
-                    println!("Force flag detected. Removing existing worktree...");
-                    // Delete the old database record
-                    if let Err(e) = worktree_repo.delete_by_path(&existing.path) {
-                        eprintln!("Warning: Failed to remove old worktree record: {}", e);
+                    println!("  Path:   {}", existing.path);
+                    println!("  Branch: {}", existing.branch_name);
+                    println!();
+
+                    if !self.force {
+                        println!("The worktree appears to be in use.");
+                        println!("Options:");
+                        println!("  1. Use --force to recreate it");
+                        println!(
+                            "  2. Use 'murmur worktree clean --stale-only' to clean up stale worktrees"
+                        );
+                        return Ok(());
+                    } else {
+                        println!("Force flag detected. Removing existing worktree...");
+                        // Delete the old database record
+                        if let Err(e) = worktree_repo.delete_by_path(&existing.path) {
+                            eprintln!("Warning: Failed to remove old worktree record: {}", e);
+                        }
+                        // Worktree directory and branch will be cleaned up by create_worktree logic
                     }
-                    // Worktree directory and branch will be cleaned up by create_worktree logic
-                }
-            } else if !exists_on_disk {
-                println!(
-                    "{}  Found stale worktree entry in database:",
-                    emoji(no_emoji, "⚠️", "[WARN]")
-                );
-                println!("  Path:   {} (missing)", existing.path);
-                println!("  Branch: {}", existing.branch_name);
-                println!();
-                println!("Cleaning up stale entry...");
+                } else if !exists_on_disk {
+                    println!(
+                        "{}  Found stale worktree entry in database:",
+                        emoji(no_emoji, "⚠️", "[WARN]")
+                    );
+                    println!("  Path:   {} (missing)", existing.path);
+                    println!("  Branch: {}", existing.branch_name);
+                    println!();
+                    println!("Cleaning up stale entry...");
 
-                // Mark as stale and continue
-                let mut stale_record = existing.clone();
-                stale_record.mark_stale();
-                if let Err(e) = worktree_repo.update(&stale_record) {
-                    eprintln!("Warning: Failed to update stale record: {}", e);
+                    // Mark as stale and continue
+                    let mut stale_record = existing.clone();
+                    stale_record.mark_stale();
+                    if let Err(e) = worktree_repo.update(&stale_record) {
+                        eprintln!("Warning: Failed to update stale record: {}", e);
+                    }
                 }
             }
-        }
 
-        // Create worktree for the issue
-        println!("Creating worktree for #{}...", self.issue);
+            // Create worktree for the issue
+            println!("Creating worktree for #{}...", self.issue);
 
-        let cwd = std::env::current_dir()?;
-        let git_repo = GitRepo::open(&cwd)?;
+            let cwd = std::env::current_dir()?;
+            let git_repo = GitRepo::open(&cwd)?;
 
-        let branching_options = BranchingOptions {
-            base_branch: None,
-            fetch: true,
-            remote: None,
-        };
+            let branching_options = BranchingOptions {
+                base_branch: None,
+                fetch: true,
+                remote: None,
+            };
 
-        let point = git_repo.find_branching_point(&branching_options)?;
+            let point = git_repo.find_branching_point(&branching_options)?;
 
-        if verbose {
-            println!(
-                "  Branching from {} ({})",
-                point.reference,
-                &point.commit[..8]
-            );
-        }
+            if verbose {
+                println!(
+                    "  Branching from {} ({})",
+                    point.reference,
+                    &point.commit[..8]
+                );
+            }
 
-        let worktree_options = WorktreeOptions {
-            branch_name: branch_name.clone(),
-            force: self.force,
-        };
+            let worktree_options = WorktreeOptions {
+                branch_name: branch_name.clone(),
+                force: self.force,
+            };
 
-        let info = git_repo.create_cached_worktree(&point, &worktree_options)?;
+            let info = git_repo.create_cached_worktree(&point, &worktree_options)?;
 
-        // If --force was used, ensure the database record for this path is also deleted
-        // (it might not have been found by branch name earlier, e.g., if the path format differs)
-        if self.force {
-            let worktree_repo = WorktreeRepository::new(&db);
-            if let Err(e) = worktree_repo.delete_by_path(&info.path.to_string_lossy()) {
-                if verbose {
-                    eprintln!("Note: No existing DB record to clean up for path: {}", e);
+            // If --force was used, ensure the database record for this path is also deleted
+            // (it might not have been found by branch name earlier, e.g., if the path format differs)
+            if self.force {
+                let worktree_repo = WorktreeRepository::new(&db);
+                if let Err(e) = worktree_repo.delete_by_path(&info.path.to_string_lossy()) {
+                    if verbose {
+                        eprintln!("Note: No existing DB record to clean up for path: {}", e);
+                    }
                 }
             }
-        }
 
-        println!("  Created: {}", info.path.display());
-        println!("  Branch:  {}", info.branch);
-        println!();
+            println!("  Created: {}", info.path.display());
+            println!("  Branch:  {}", info.branch);
+            println!();
 
-        // Track worktree in database immediately after creation to avoid race conditions
-        // This is especially important when using --force, where the old record was deleted earlier
-        // Note: base_commit is now stored in database instead of .murmur-worktree.toml file
-        let worktree_record =
-            WorktreeRecord::new(info.path.to_string_lossy().to_string(), branch_name.clone())
-                .with_issue_number(self.issue as i64)
-                .with_main_repo_path(git_repo.root().to_string_lossy().to_string())
-                .with_base_commit(&point.commit);
+            // Track worktree in database immediately after creation to avoid race conditions
+            // This is especially important when using --force, where the old record was deleted earlier
+            // Note: base_commit is now stored in database instead of .murmur-worktree.toml file
+            let worktree_record =
+                WorktreeRecord::new(info.path.to_string_lossy().to_string(), branch_name.clone())
+                    .with_issue_number(self.issue as i64)
+                    .with_main_repo_path(git_repo.root().to_string_lossy().to_string())
+                    .with_base_commit(&point.commit);
 
-        let worktree_repo = WorktreeRepository::new(&db);
-        let worktree_id = worktree_repo
-            .insert(&worktree_record)
-            .map_err(|e| anyhow::anyhow!("Failed to track worktree in database: {}", e))?;
+            let worktree_repo = WorktreeRepository::new(&db);
+            let worktree_id = worktree_repo
+                .insert(&worktree_record)
+                .map_err(|e| anyhow::anyhow!("Failed to track worktree in database: {}", e))?;
 
-        if verbose {
-            println!("Worktree ID: {}", worktree_id);
-        }
+            if verbose {
+                println!("Worktree ID: {}", worktree_id);
+            }
+
+            info
+        };
 
         if self.no_agent {
             println!("Worktree ready. Run your agent manually:");
@@ -400,28 +489,118 @@ impl WorkArgs {
             return Ok(());
         }
 
-        // Build prompt from issue
-        let prompt = if let Some(ref custom_prompt) = self.prompt {
-            custom_prompt.clone()
+        // Build prompt from issue (or from the resume plan)
+        let prompt = if let Some(prompt) = resume_plan.as_ref().and_then(|p| p.prompt.clone()) {
+            prompt
         } else {
-            build_prompt_from_issue(&issue)
+            let mut prompt = match self.prompt {
+                Some(ref custom_prompt) => custom_prompt.clone(),
+                None => build_prompt_from_issue(&issue),
+            };
+            let context = client
+                .collect_issue_context(&issue, &info.path, &config.context)
+                .await;
+            if verbose && !context.is_empty() {
+                println!(
+                    "Injecting repository context (~{} tokens)",
+                    context.estimated_tokens()
+                );
+            }
+            context.append_to(&mut prompt);
+            prompt
         };
 
+        if self.tdd {
+            let accounting = RunAccounting::from_config(config).with_repository(format!(
+                "{}/{}",
+                client.owner(),
+                client.repo()
+            ));
+            let completed = run_tdd(
+                config,
+                accounting,
+                &client,
+                self.issue,
+                &self.approve_before,
+                &info,
+                &prompt,
+                verbose,
+                no_emoji,
+            )
+            .await?;
+            if !completed {
+                println!();
+                println!("Next steps:");
+                println!("  1. Review changes: cd {}", info.path.display());
+                println!("  2. Fix the failing tests and retry");
+                return Err(anyhow::anyhow!("TDD workflow failed after max iterations"));
+            }
+            return self
+                .finish_successful_run(
+                    config,
+                    &info,
+                    &branch_name,
+                    &issue,
+                    verbose,
+                    no_emoji,
+                    &client,
+                )
+                .await;
+        }
+
         println!("Starting agent...");
         println!();
 
         // Create agent run record in database
         let config_json = serde_json::to_string(&config.agent).unwrap_or_else(|_| "{}".to_string());
 
+        let model = config.agent.resolve_for_type(AgentType::Implement).model;
+
         let mut agent_run = AgentRun::new(
             "implementer",
             &prompt,
             info.path.to_str().unwrap_or(""),
             config_json,
         )
-        .with_issue_number(self.issue as i64);
+        .with_issue_number(self.issue as i64)
+        .with_repository(format!("{}/{}", client.owner(), client.repo()));
+
+        if let Some(ref model) = model {
+            agent_run = agent_run.with_model(model);
+        }
+
+        if let Some(session_id) = resume_plan.as_ref().and_then(|p| p.session_id.as_ref()) {
+            agent_run = agent_run.with_session_id(session_id);
+        }
+
+        if let Some(run_id) = resumed_from {
+            agent_run = agent_run.with_resumed_from(run_id);
+        }
 
         let agent_repo = AgentRunRepository::new(&db);
+
+        // Refuse to start when earlier runs already used up the issue budget
+        let issue_spent = if config.budget.issue().is_unlimited() {
+            Usage::default()
+        } else {
+            let (issue_tokens, issue_cost) =
+                agent_repo.usage_by_issue(self.issue as i64).map_err(|e| {
+                    anyhow::anyhow!("Failed to load spend for issue #{}: {}", self.issue, e)
+                })?;
+            Usage {
+                input_tokens: issue_tokens as u64,
+                cost_usd: issue_cost,
+                ..Default::default()
+            }
+        };
+        if let Some(detail) = config.budget.issue().check(&issue_spent) {
+            return Err(anyhow::anyhow!(
+                "Issue #{} budget exceeded: {}. Raise [budget] limits in config.toml to continue.",
+                self.issue,
+                detail
+            ));
+        }
+
         let run_id = agent_repo
             .insert(&agent_run)
             .map_err(|e| anyhow::anyhow!("Failed to create agent run record: {}", e))?;
@@ -444,11 +623,10 @@ impl WorkArgs {
             }
         }
 
-        // Spawn agent with GitHub token if available (using default Implement agent type)
-        let mut spawner = AgentSpawner::from_config(
-            config.agent.clone(),
-            murmur_core::agent::AgentType::default(),
-        );
+        // Resume the previous backend session natively if possible
+        if let Some(session_id) = resume_plan.as_ref().and_then(|p| p.session_id.as_ref()) {
+            spawner = spawner.with_resume_session(session_id);
+        }
 
         // Pass GitHub token to agent via environment variable
         if let Ok(secrets) = Secrets::load() {
@@ -486,15 +664,53 @@ impl WorkArgs {
         // Create a separate database connection for the handler
         let handler_db = Database::open()
             .map_err(|e| anyhow::anyhow!("Failed to open database for handler: {}", e))?;
-        let mut handler = DatabaseLoggingHandler::new(handler_db, run_id, verbose);
+        let tracker = BudgetTracker::new(config.budget.run())
+            .with_pricing(&config.pricing, model.as_deref())
+            .with_issue_budget(config.budget.issue(), issue_spent);
+        let mut handler = BudgetHandler::new(
+            DatabaseLoggingHandler::new(handler_db, run_id, verbose),
+            tracker,
+        );
 
-        if let Err(e) = streamer.stream(&mut handler).await {
-            eprintln!("Stream error: {}", e);
+        let watchdog = Watchdog::new(config.agent.watchdog_for_type(AgentType::Implement));
+        let stopped = match watchdog
+            .supervise(&mut handle, &mut streamer, &mut handler)
+            .await
+        {
+            Ok(stopped) => stopped,
+            Err(e) => {
+                eprintln!("Stream error: {}", e);
+                None
+            }
+        };
+        if let Some(reason) = stopped {
+            agent_run.mark_stopped(reason.as_str());
+        }
+
+        // Stop the agent if it ran over budget
+        let budget_exceeded = handler.exceeded().cloned();
+        if budget_exceeded.is_some() {
+            if let Err(e) = handle.kill().await {
+                eprintln!("Warning: Failed to kill agent: {}", e);
+            }
+            agent_run.mark_budget_exceeded();
         }
 
         let status = handle.wait().await?;
+        let (handler, tracker) = handler.into_parts();
 
-        // Update agent run with completion status
+        // Update agent run with usage and completion status
+        if let Some(session_id) = handler.session_id() {
+            agent_run.session_id = Some(session_id.to_string());
+        }
+        let usage = tracker.usage();
+        agent_run.record_usage(
+            usage.input_tokens,
+            usage.output_tokens,
+            usage.cache_read_tokens,
+            usage.cache_write_tokens,
+            usage.cost_usd,
+        );
         agent_run.complete(status.code().unwrap_or(-1));
         if let Err(e) = agent_repo.update(&agent_run) {
             eprintln!("Warning: Failed to update agent run record: {}", e);
@@ -518,31 +734,39 @@ impl WorkArgs {
                 "{} Agent completed successfully",
                 emoji(no_emoji, "✅", "[OK]")
             );
-
-            // Auto-commit, auto-push and auto-PR if configured
-            if config.workflow.auto_commit || config.workflow.auto_push || config.workflow.auto_pr {
-                println!();
-                self.handle_post_completion(
-                    config,
-                    &info,
-                    &branch_name,
-                    &issue,
-                    verbose,
-                    no_emoji,
-                    &client,
-                )
-                .await?;
-            } else {
-                println!();
-                println!("Next steps:");
-                println!("  1. Review changes: cd {}", info.path.display());
-                println!("  2. Commit changes: git add . && git commit");
-                println!("  3. Push branch: git push -u origin {}", branch_name);
-                println!(
-                    "  4. Create PR: gh pr create --title \"Fixes #{}\"",
-                    self.issue
-                );
-            }
+            self.finish_successful_run(
+                config,
+                &info,
+                &branch_name,
+                &issue,
+                verbose,
+                no_emoji,
+                &client,
+            )
+            .await?;
+        } else if let Some(reason) = stopped {
+            println!(
+                "{} Agent stopped by watchdog: {}",
+                emoji(no_emoji, "⏱️", "[TIMEOUT]"),
+                reason
+            );
+            println!();
+            println!("Next steps:");
+            println!("  1. Review changes: cd {}", info.path.display());
+            println!(
+                "  2. Resume with: murmur work {} --resume (or raise timeout_secs / stall_timeout_secs)",
+                self.issue
+            );
+        } else if let Some(exceeded) = budget_exceeded {
+            println!(
+                "{} Agent stopped: {}",
+                emoji(no_emoji, "💸", "[BUDGET]"),
+                exceeded
+            );
+            println!();
+            println!("Next steps:");
+            println!("  1. Review changes: cd {}", info.path.display());
+            println!("  2. Raise [budget] limits in config.toml or continue manually");
         } else {
             println!(
                 "{} Agent exited with code: {}",
@@ -558,6 +782,190 @@ impl WorkArgs {
         Ok(())
     }
 
+    /// Review, commit, push and open a PR for a finished run as configured
+    #[allow(clippy::too_many_arguments)]
+    async fn finish_successful_run(
+        &self,
+        config: &Config,
+        info: &murmur_core::WorktreeInfo,
+        branch_name: &str,
+        issue: &murmur_github::Issue,
+        verbose: bool,
+        no_emoji: bool,
+        client: &GitHubClient,
+    ) -> anyhow::Result<()> {
+        // Review the changes and let the agent address the findings
+        if config.workflow.auto_review_loop {
+            println!();
+            self.run_review_loop(config, client, info, issue, verbose, no_emoji)
+                .await?;
+        }
+
+        // Auto-commit, auto-push and auto-PR if configured
+        if config.workflow.auto_commit || config.workflow.auto_push || config.workflow.auto_pr {
+            println!();
+            self.run_quality_gates(config, client, info, issue, verbose, no_emoji)
+                .await?;
+            self.handle_post_completion(
+                config,
+                info,
+                branch_name,
+                issue,
+                verbose,
+                no_emoji,
+                client,
+            )
+            .await?;
+        } else {
+            println!();
+            println!("Next steps:");
+            println!("  1. Review changes: cd {}", info.path.display());
+            println!("  2. Commit changes: git add . && git commit");
+            println!("  3. Push branch: git push -u origin {}", branch_name);
+            println!(
+                "  4. Create PR: gh pr create --title \"Fixes #{}\"",
+                self.issue
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Run the review/fix loop on the worktree before it is committed
+    async fn run_review_loop(
+        &self,
+        config: &Config,
+        client: &GitHubClient,
+        info: &murmur_core::WorktreeInfo,
+        issue: &murmur_github::Issue,
+        verbose: bool,
+        no_emoji: bool,
+    ) -> anyhow::Result<()> {
+        println!(
+            "{} Reviewing changes (up to {} rounds)...",
+            emoji(no_emoji, "🔍", "[REVIEW]"),
+            config.workflow.max_review_iterations
+        );
+        println!();
+
+        let task = format!("#{}: {}\n\n{}", issue.number, issue.title, issue.body);
+        let mut review_loop = ReviewLoop::new(task, &info.path, config.agent.clone())
+            .with_max_iterations(config.workflow.max_review_iterations)
+            .with_issue_number(self.issue as i64)
+            .with_accounting(RunAccounting::from_config(config).with_repository(format!(
+                "{}/{}",
+                client.owner(),
+                client.repo()
+            )));
+
+        let db = Database::open().map_err(|e| anyhow::anyhow!("Failed to open database: {}", e))?;
+        if let Ok(Some(record)) =
+            WorktreeRepository::new(&db).find_by_path(&info.path.to_string_lossy())
+        {
+            if let Some(base) = record.base_commit {
+                review_loop = review_loop.with_base(base);
+            }
+        }
+        review_loop = review_loop.with_database(db);
+
+        let mut handler = PrintHandler::new(verbose);
+        match review_loop.run(&mut handler).await {
+            Ok(outcome) => super::review::print_outcome(&outcome, no_emoji),
+            Err(e) => eprintln!("Warning: Review loop failed: {}", e),
+        }
+        Ok(())
+    }
+
+    /// Run the `[quality]` checks, sending failures back to the implement
+    /// agent, so nothing is committed or pushed until they pass
+    async fn run_quality_gates(
+        &self,
+        config: &Config,
+        client: &GitHubClient,
+        info: &murmur_core::WorktreeInfo,
+        issue: &murmur_github::Issue,
+        verbose: bool,
+        no_emoji: bool,
+    ) -> anyhow::Result<()> {
+        let quality = QualityGates::from_config(&info.path, &config.quality);
+        if quality.is_empty() {
+            return Ok(());
+        }
+
+        let accounting = RunAccounting::from_config(config).with_repository(format!(
+            "{}/{}",
+            client.owner(),
+            client.repo()
+        ));
+        let db = Database::open().map_err(|e| anyhow::anyhow!("Failed to open database: {}", e))?;
+        let spawner = AgentSpawner::from_config(config.agent.clone(), AgentType::Implement);
+        let mut attempts = 0;
+        loop {
+            println!("Running quality checks...");
+            let problems = quality
+                .run(&mut |check, problem| match problem {
+                    None => println!("{} Check passed: {}", emoji(no_emoji, "✅", "[OK]"), check),
+                    Some(problem) => {
+                        println!(
+                            "{} Check failed: {}",
+                            emoji(no_emoji, "❌", "[FAIL]"),
+                            check
+                        );
+                        if verbose {
+                            println!("{}", problem);
+                        }
+                    }
+                })
+                .await
+                .map_err(|e| anyhow::anyhow!("Failed to run quality checks: {}", e))?;
+            let Some(problems) = problems else {
+                return Ok(());
+            };
+
+            if attempts == QUALITY_FIX_ATTEMPTS {
+                return Err(anyhow::anyhow!(
+                    "Quality checks still fail after {} fix attempts; not committing. \
+                     Fix them in {} and push manually",
+                    attempts,
+                    info.path.display()
+                ));
+            }
+            attempts += 1;
+
+            println!();
+            println!(
+                "{} Asking the agent to fix the failed checks ({}/{})...",
+                emoji(no_emoji, "🔧", "[FIX]"),
+                attempts,
+                QUALITY_FIX_ATTEMPTS
+            );
+            println!();
+            let prompt = format!(
+                "{}\n\nThe quality checks fail after your changes. Fix every problem \
+                 without disabling the checks.\n\n## Failed Checks\n\n{}\n",
+                build_prompt_from_issue(issue),
+                problems.trim()
+            );
+            let runner = AgentRunner {
+                config: &config.agent,
+                accounting: &accounting,
+                db: Some(&db),
+                issue_number: Some(self.issue as i64),
+            };
+            let mut handler = PrintHandler::new(verbose);
+            runner
+                .run(
+                    &spawner,
+                    AgentType::Implement,
+                    &prompt,
+                    &info.path,
+                    &mut handler,
+                )
+                .await?;
+            println!();
+        }
+    }
+
     /// Handle post-completion tasks: commit, push, and PR creation
     #[allow(clippy::too_many_arguments)]
     async fn handle_post_completion(
@@ -852,21 +1260,404 @@ impl WorkArgs {
             None
         };
 
-        // Step 6: Monitor for review feedback if configured and PR was created
-        if config.workflow.auto_review_loop && pr_number.is_some() {
+        // Step 6: Point at the feedback loop once reviewers respond
+        if let Some(pr_number) = pr_number {
             println!();
             println!(
-                "{}  Review feedback monitoring is enabled",
-                emoji(no_emoji, "👀", "[INFO]")
+                "{}  Once PR #{} is reviewed, run `murmur work {} --address-feedback`",
+                emoji(no_emoji, "👀", "[INFO]"),
+                pr_number,
+                self.issue
             );
-            println!("  Note: This feature will be implemented in a future update");
-            println!("  For now, you'll need to manually address review feedback");
         }
 
         Ok(())
     }
 }
 
+/// Run the TDD cycle on an issue in its worktree, returning whether it completed
+///
+/// Waits for approval on the issue before each of the `approve_before` phases.
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn run_tdd(
+    config: &Config,
+    accounting: RunAccounting,
+    client: &GitHubClient,
+    issue_number: u64,
+    approve_before: &[TddPhase],
+    info: &murmur_core::WorktreeInfo,
+    behavior: &str,
+    verbose: bool,
+    no_emoji: bool,
+) -> anyhow::Result<bool> {
+    // The issue already serves as the spec, so start from WriteTests
+    let mut executor = TddExecutor::new(behavior, &info.path, config.agent.clone())
+        .with_skip_spec()
+        .with_issue_number(issue_number as i64)
+        .with_test_config(&config.test)
+        .with_quality_config(&config.quality)
+        .with_accounting(accounting);
+    if !approve_before.is_empty() {
+        let mut gates = ApprovalGates::before(approve_before.iter().map(|phase| phase.as_str()));
+        match GitHubClient::new(client.owner(), client.repo()) {
+            Ok(comments) => gates = gates.with_source(IssueApprovals::new(comments, issue_number)),
+            Err(e) => eprintln!(
+                "Warning: Issue comments won't be checked for approvals: {}",
+                e
+            ),
+        }
+        executor = executor.with_approval_gates(gates);
+    }
+    match Database::open() {
+        Ok(db) => {
+            if let Ok(Some(record)) =
+                WorktreeRepository::new(&db).find_by_path(&info.path.to_string_lossy())
+            {
+                if let Some(id) = record.id {
+                    executor = executor.with_worktree_id(id);
+                }
+            }
+            executor = executor.with_database(db);
+        }
+        Err(e) => eprintln!("Warning: Agent runs won't be recorded: {}", e),
+    }
+
+    println!(
+        "{} Running TDD workflow{}",
+        emoji(no_emoji, "🧪", "[TDD]"),
+        match executor.test_suites() {
+            [] => String::new(),
+            [suite] => format!(" with {}", suite.label()),
+            suites => format!(" with {} test suites", suites.len()),
+        }
+    );
+    println!();
+
+    let mut printer = TddPrinter::new(verbose, no_emoji);
+    let mut handler = PrintHandler::new(verbose);
+    let outcome = executor.run(&mut printer, &mut handler).await;
+
+    // Update worktree status based on the outcome
+    let db = Database::open().map_err(|e| anyhow::anyhow!("Failed to open database: {}", e))?;
+    let worktree_repo = WorktreeRepository::new(&db);
+    if let Ok(Some(mut wt_record)) = worktree_repo.find_by_path(&info.path.to_string_lossy()) {
+        if outcome.as_ref().is_ok_and(|o| o.completed) {
+            wt_record.mark_completed();
+        } else {
+            wt_record.mark_abandoned();
+        }
+        if let Err(e) = worktree_repo.update(&wt_record) {
+            eprintln!("Warning: Failed to update worktree status: {}", e);
+        }
+    }
+
+    let outcome = outcome?;
+    Ok(printer.print_outcome(&outcome).is_ok())
+}
+
+/// What `--address-feedback` did for an issue
+pub(crate) enum FeedbackOutcome {
+    /// The issue has no open pull request
+    NoOpenPr,
+    /// The pull request has no unresolved feedback
+    NothingToAddress,
+    /// The agent ran but neither changed anything nor reported on a thread
+    NoChanges,
+    /// The agent reported on the threads and this many were resolved
+    Addressed { pr_number: u64, resolved: usize },
+}
+
+/// Run an implement agent on the unresolved review feedback of an issue's PR
+///
+/// Reuses the PR branch's worktree and pushes the agent's fixes, then replies
+/// to each thread with the agent's report on it, resolving the ones it
+/// addressed.
+pub(crate) async fn address_feedback(
+    client: &GitHubClient,
+    db: &Database,
+    config: &Config,
+    accounting: &RunAccounting,
+    issue: &murmur_github::Issue,
+    verbose: bool,
+    no_emoji: bool,
+) -> anyhow::Result<FeedbackOutcome> {
+    use std::process::Command;
+
+    let pr = client
+        .find_prs_for_issue(issue.number)
+        .await?
+        .into_iter()
+        .find(|pr| pr.state == PrState::Open && !pr.merged);
+    let Some(pr) = pr else {
+        println!(
+            "{}  No open pull request found for issue #{}",
+            emoji(no_emoji, "ℹ️", "[INFO]"),
+            issue.number
+        );
+        return Ok(FeedbackOutcome::NoOpenPr);
+    };
+
+    let feedback = client.get_unresolved_feedback(pr.number).await?;
+    if feedback.is_empty() {
+        println!(
+            "{} PR #{} has no unresolved review feedback",
+            emoji(no_emoji, "✅", "[OK]"),
+            pr.number
+        );
+        return Ok(FeedbackOutcome::NothingToAddress);
+    }
+
+    println!(
+        "PR #{}: {} unresolved thread(s), {} change request(s)",
+        pr.number,
+        feedback.threads.len(),
+        feedback.change_requests.len()
+    );
+
+    let workdir = match WorktreeRepository::new(db).find_by_branch(&pr.head_branch) {
+        Ok(Some(record)) if std::path::Path::new(&record.path).exists() => {
+            println!("Reusing worktree {}", record.path);
+            std::path::PathBuf::from(record.path)
+        }
+        _ => {
+            let path = checkout_pr_worktree(db, &pr.head_branch, issue.number)?;
+            println!("Checked out {} in {}", pr.head_branch, path.display());
+            path
+        }
+    };
+    println!();
+
+    // Pick up commits reviewers may have pushed (e.g. applied suggestions)
+    let pull = Command::new("git")
+        .args(["pull", "--ff-only", "origin", &pr.head_branch])
+        .current_dir(&workdir)
+        .output()?;
+    if !pull.status.success() {
+        eprintln!(
+            "{}  Failed to update branch from origin:",
+            emoji(no_emoji, "⚠️", "[WARN]")
+        );
+        eprintln!("{}", String::from_utf8_lossy(&pull.stderr));
+    }
+    let head_before = git_head(&workdir)?;
+
+    let prompt = format!(
+        "{}## Review Feedback on PR #{}\n\n\
+         Reviewers left feedback on your pull request. Address each item below \
+         without making unrelated changes. If you disagree with a comment, leave \
+         the code as is and explain why in your final message.\n\n{}\n\n{}\n",
+        build_prompt_from_issue(issue),
+        pr.number,
+        feedback.to_markdown(),
+        THREAD_REPORT_INSTRUCTIONS
+    );
+
+    println!("Starting agent...");
+    println!();
+    let mut spawner = AgentSpawner::from_config(config.agent.clone(), AgentType::Implement);
+    if let Ok(secrets) = Secrets::load() {
+        if let Some(token) = secrets.github_token() {
+            spawner = spawner.with_env("GITHUB_TOKEN", token);
+        }
+    }
+    let runner = AgentRunner {
+        config: &config.agent,
+        accounting,
+        db: Some(db),
+        issue_number: Some(issue.number as i64),
+    };
+    let mut handler = PrintHandler::new(verbose);
+    let (output, _) = runner
+        .run(
+            &spawner,
+            AgentType::Implement,
+            &prompt,
+            &workdir,
+            &mut handler,
+        )
+        .await?;
+    println!();
+
+    // Commit whatever the agent left uncommitted
+    let dirty = Command::new("git")
+        .args(["status", "--porcelain"])
+        .current_dir(&workdir)
+        .output()?;
+    if !dirty.stdout.is_empty() {
+        let message = format!("Address review feedback on PR #{}", pr.number);
+        for args in [vec!["add", "."], vec!["commit", "-m", &message]] {
+            let output = Command::new("git")
+                .args(&args)
+                .current_dir(&workdir)
+                .output()?;
+            if !output.status.success() {
+                return Err(anyhow::anyhow!(
+                    "git {} failed: {}",
+                    args[0],
+                    String::from_utf8_lossy(&output.stderr).trim()
+                ));
+            }
+        }
+    }
+
+    let reports = parse_thread_reports(&output);
+    let head_after = git_head(&workdir)?;
+    if head_after == head_before && reports.is_empty() {
+        println!(
+            "{}  Agent made no changes; leaving review threads open",
+            emoji(no_emoji, "⚠️", "[WARN]")
+        );
+        return Ok(FeedbackOutcome::NoChanges);
+    }
+
+    let fixed_in = if head_after == head_before {
+        None
+    } else {
+        println!("Pushing fixes to {}...", pr.head_branch);
+        let push = Command::new("git")
+            .args(["push", "origin", &format!("HEAD:{}", pr.head_branch)])
+            .current_dir(&workdir)
+            .output()?;
+        if !push.status.success() {
+            return Err(anyhow::anyhow!(
+                "Failed to push fixes: {}",
+                String::from_utf8_lossy(&push.stderr).trim()
+            ));
+        }
+        println!("{} Fixes pushed", emoji(no_emoji, "✅", "[OK]"));
+        Some(&head_after[..head_after.len().min(8)])
+    };
+
+    // Only threads the agent says it addressed are resolved; declined ones
+    // get its explanation and stay open for the reviewer
+    let mut resolved = 0;
+    let mut declined = 0;
+    for thread in &feedback.threads {
+        let Some(report) = reports.get(&thread.id) else {
+            eprintln!(
+                "Warning: Agent didn't report on the thread on {}; leaving it open",
+                thread.path
+            );
+            continue;
+        };
+        let mut reply = report.reply.trim().to_string();
+        if report.outcome == ThreadOutcome::Addressed {
+            if let Some(sha) = fixed_in {
+                if !reply.is_empty() {
+                    reply.push_str("\n\n");
+                }
+                reply.push_str(&format!("Addressed in {}.", sha));
+            }
+        }
+        if let Some(comment) = thread.root_comment().filter(|_| !reply.is_empty()) {
+            if let Err(e) = client
+                .reply_to_review_comment(pr.number, comment.id, &reply)
+                .await
+            {
+                eprintln!("Warning: Failed to reply on {}: {}", thread.path, e);
+            }
+        }
+        match report.outcome {
+            ThreadOutcome::Addressed => match client.resolve_review_thread(&thread.id).await {
+                Ok(()) => resolved += 1,
+                Err(e) => eprintln!(
+                    "Warning: Failed to resolve thread on {}: {}",
+                    thread.path, e
+                ),
+            },
+            ThreadOutcome::Declined => declined += 1,
+        }
+    }
+    println!(
+        "{} Resolved {} of {} review thread(s) on PR #{} ({} declined)",
+        emoji(no_emoji, "✅", "[OK]"),
+        resolved,
+        feedback.threads.len(),
+        pr.number,
+        declined
+    );
+
+    Ok(FeedbackOutcome::Addressed {
+        pr_number: pr.number,
+        resolved,
+    })
+}
+
+/// Create a worktree on an existing PR branch and track it in the database
+fn checkout_pr_worktree(
+    db: &Database,
+    branch: &str,
+    issue_number: u64,
+) -> anyhow::Result<std::path::PathBuf> {
+    use std::process::Command;
+
+    let git_repo = GitRepo::open(std::env::current_dir()?)?;
+    let path = worktree_path(&default_cache_dir()?, &git_repo.repo_name(), branch);
+    let path_str = path.to_string_lossy().to_string();
+
+    let fetch = Command::new("git")
+        .args(["fetch", "origin", branch])
+        .current_dir(git_repo.root())
+        .output()?;
+    if !fetch.status.success() {
+        return Err(anyhow::anyhow!(
+            "Failed to fetch {}: {}",
+            branch,
+            String::from_utf8_lossy(&fetch.stderr).trim()
+        ));
+    }
+
+    let remote_branch = format!("origin/{}", branch);
+    let args: Vec<&str> = if git_repo.branch_exists(branch)? {
+        vec!["worktree", "add", &path_str, branch]
+    } else {
+        vec![
+            "worktree",
+            "add",
+            "--track",
+            "-b",
+            branch,
+            &path_str,
+            &remote_branch,
+        ]
+    };
+    let add = Command::new("git")
+        .args(&args)
+        .current_dir(git_repo.root())
+        .output()?;
+    if !add.status.success() {
+        return Err(anyhow::anyhow!(
+            "Failed to create worktree for {}: {}",
+            branch,
+            String::from_utf8_lossy(&add.stderr).trim()
+        ));
+    }
+
+    let record = WorktreeRecord::new(path_str, branch)
+        .with_issue_number(issue_number as i64)
+        .with_main_repo_path(git_repo.root().to_string_lossy().to_string());
+    if let Err(e) = WorktreeRepository::new(db).insert(&record) {
+        eprintln!("Warning: Failed to track worktree in database: {}", e);
+    }
+
+    Ok(path)
+}
+
+/// Current commit of a worktree
+fn git_head(workdir: &std::path::Path) -> anyhow::Result<String> {
+    let output = std::process::Command::new("git")
+        .args(["rev-parse", "HEAD"])
+        .current_dir(workdir)
+        .output()?;
+    if !output.status.success() {
+        return Err(anyhow::anyhow!(
+            "Failed to read HEAD of {}",
+            workdir.display()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
 /// Get emoji or ASCII alternative based on no_emoji flag
 fn emoji<'a>(no_emoji: bool, emoji_char: &'a str, ascii_alt: &'a str) -> &'a str {
     if no_emoji {
@@ -903,12 +1694,23 @@ fn build_prompt_from_issue(issue: &murmur_github::Issue) -> String {
     prompt
 }
 
+/// How to relaunch an interrupted run
+struct ResumePlan {
+    /// Worktree of the interrupted run
+    workdir: std::path::PathBuf,
+    /// Prompt to send to the agent, or `None` to start the task afresh
+    prompt: Option<String>,
+    /// Backend session to resume natively, if still available
+    session_id: Option<String>,
+}
+
 /// StreamHandler that logs to database and prints to console
 struct DatabaseLoggingHandler {
     db: Database,
     run_id: i64,
     sequence: i64,
     verbose: bool,
+    session_id: Option<String>,
 }
 
 impl DatabaseLoggingHandler {
@@ -918,9 +1720,15 @@ impl DatabaseLoggingHandler {
             run_id,
             sequence: 0,
             verbose,
+            session_id: None,
         }
     }
 
+    /// Session ID reported by the agent, if any
+    fn session_id(&self) -> Option<&str> {
+        self.session_id.as_deref()
+    }
+
     fn log_message(&mut self, message_type: &str, message_json: &str) {
         let log = ConversationLog::new(self.run_id, self.sequence, message_type, message_json);
 
@@ -944,6 +1752,19 @@ impl murmur_core::StreamHandler for DatabaseLoggingHandler {
         });
         self.log_message("system", &msg.to_string());
 
+        // Persist the session ID right away so a crash can still be resumed
+        if let Some(id) = session_id {
+            if self.session_id.as_deref() != Some(id) {
+                self.session_id = Some(id.to_string());
+                if let Err(e) = AgentRunRepository::new(&self.db).update_session_id(self.run_id, id)
+                {
+                    if self.verbose {
+                        eprintln!("Warning: Failed to record session ID: {}", e);
+                    }
+                }
+            }
+        }
+
         if self.verbose {
             if let Some(st) = subtype {
                 eprintln!("[system: {}]", st);
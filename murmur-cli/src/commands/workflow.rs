@@ -0,0 +1,275 @@
+//! Workflow command - list, check and run workflows from `.murmur/workflows`
+
+use std::path::{Path, PathBuf};
+
+use clap::{Args, Subcommand};
+use murmur_core::agent::AgentType;
+use murmur_core::workflow::{
+    Gate, TestResults, WorkflowDefinition, WorkflowEngine, WorkflowObserver, WorkflowOutcome,
+};
+use murmur_core::{Config, GitRepo, PrintHandler};
+use murmur_db::Database;
+
+use super::run_accounting;
+
+/// Run workflows defined in the repository
+#[derive(Args, Debug)]
+pub struct WorkflowArgs {
+    #[command(subcommand)]
+    pub command: WorkflowCommand,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum WorkflowCommand {
+    /// List the workflows defined in the repository
+    List {
+        /// Repository directory (defaults to current directory)
+        #[arg(short = 'd', long, default_value = ".")]
+        repo: PathBuf,
+    },
+
+    /// Check that workflow definitions are valid
+    Check {
+        /// The workflow to check (defaults to all)
+        name: Option<String>,
+
+        /// Repository directory (defaults to current directory)
+        #[arg(short = 'd', long, default_value = ".")]
+        repo: PathBuf,
+    },
+
+    /// Run a workflow for a task
+    Run {
+        /// The workflow to run
+        name: String,
+
+        /// The task to carry out
+        task: String,
+
+        /// Working directory (defaults to current directory)
+        #[arg(short = 'd', long, default_value = ".")]
+        workdir: PathBuf,
+    },
+}
+
+impl WorkflowArgs {
+    /// Execute the workflow command
+    pub async fn execute(
+        &self,
+        verbose: bool,
+        no_emoji: bool,
+        config: &Config,
+        repo: Option<&str>,
+    ) -> anyhow::Result<()> {
+        match &self.command {
+            WorkflowCommand::List { repo } => {
+                let root = repo_root(repo);
+                let paths = WorkflowDefinition::paths(&root)?;
+                if paths.is_empty() {
+                    println!(
+                        "No workflows defined in {}",
+                        WorkflowDefinition::dir_for(&root).display()
+                    );
+                    return Ok(());
+                }
+                for path in paths {
+                    let definition = match WorkflowDefinition::load(&path) {
+                        Ok(definition) => definition,
+                        Err(_) => {
+                            let name = path.file_stem().unwrap_or_default().to_string_lossy();
+                            println!("{} (invalid, see `murmur workflow check`)", name);
+                            continue;
+                        }
+                    };
+                    let phases: Vec<&str> =
+                        definition.phases.iter().map(|p| p.name.as_str()).collect();
+                    match definition.description {
+                        Some(ref description) => {
+                            println!("{}: {}", definition.name, description)
+                        }
+                        None => println!("{}", definition.name),
+                    }
+                    println!("  {}", phases.join(" -> "));
+                }
+            }
+            WorkflowCommand::Check { name, repo } => {
+                let root = repo_root(repo);
+                let paths = match name {
+                    Some(name) => {
+                        vec![WorkflowDefinition::dir_for(&root).join(format!("{}.toml", name))]
+                    }
+                    None => WorkflowDefinition::paths(&root)?,
+                };
+                if paths.is_empty() {
+                    println!(
+                        "No workflows defined in {}",
+                        WorkflowDefinition::dir_for(&root).display()
+                    );
+                    return Ok(());
+                }
+
+                let mut failed = 0;
+                for path in paths {
+                    let name = path.file_stem().unwrap_or_default().to_string_lossy();
+                    match WorkflowDefinition::load(&path) {
+                        Ok(_) => println!("{}: ok", name),
+                        Err(e) => {
+                            println!("{}: {}", name, e);
+                            failed += 1;
+                        }
+                    }
+                }
+                if failed > 0 {
+                    anyhow::bail!("{} workflow(s) are invalid", failed);
+                }
+            }
+            WorkflowCommand::Run {
+                name,
+                task,
+                workdir,
+            } => {
+                let workdir = if workdir.is_absolute() {
+                    workdir.clone()
+                } else {
+                    std::env::current_dir()?.join(workdir)
+                };
+                let definition = WorkflowDefinition::find(repo_root(&workdir), name)?;
+
+                println!("Workflow: {}", definition.name);
+                if let Some(ref description) = definition.description {
+                    println!("{}", description);
+                }
+                println!("Task: {}", task);
+                println!("Working directory: {}", workdir.display());
+                println!();
+
+                let mut engine =
+                    WorkflowEngine::new(definition, task, &workdir, config.agent.clone())
+                        .with_test_config(&config.test)
+                        .with_quality_config(&config.quality)
+                        .with_accounting(run_accounting(config, repo));
+                match Database::open() {
+                    Ok(db) => engine = engine.with_database(db),
+                    Err(e) => eprintln!("Warning: Agent runs won't be recorded: {}", e),
+                }
+
+                let mut printer = WorkflowPrinter { verbose, no_emoji };
+                let mut handler = PrintHandler::new(verbose);
+                let outcome = engine.run(&mut printer, &mut handler).await?;
+                printer.print_outcome(&outcome)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The root of the repository containing `path`
+fn repo_root(path: &Path) -> PathBuf {
+    GitRepo::open(path)
+        .map(|repo| repo.root().to_path_buf())
+        .unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// Prints the progress of a workflow run
+struct WorkflowPrinter {
+    verbose: bool,
+    no_emoji: bool,
+}
+
+impl WorkflowPrinter {
+    fn emoji<'a>(&self, emoji: &'a str, ascii: &'a str) -> &'a str {
+        if self.no_emoji {
+            ascii
+        } else {
+            emoji
+        }
+    }
+
+    /// Print the final status, failing if the workflow failed
+    fn print_outcome(&self, outcome: &WorkflowOutcome) -> anyhow::Result<()> {
+        println!("═══════════════════════════════════════");
+        if outcome.completed {
+            println!(
+                "{} Workflow completed successfully!",
+                self.emoji("🎉", "[DONE]")
+            );
+        } else {
+            println!(
+                "{} Workflow failed{}",
+                self.emoji("💥", "[FAIL]"),
+                outcome
+                    .failed_phase
+                    .as_ref()
+                    .map(|phase| format!(" in {}", phase))
+                    .unwrap_or_default()
+            );
+        }
+        println!("═══════════════════════════════════════");
+        println!("Phases run: {}", outcome.history.join(" -> "));
+
+        match outcome.error {
+            Some(ref error) if !outcome.completed => {
+                Err(anyhow::anyhow!("Workflow failed: {}", error))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+impl WorkflowObserver for WorkflowPrinter {
+    fn on_phase_start(&mut self, phase: &str, attempt: u32) {
+        if attempt > 1 {
+            println!("Phase: {} (attempt {})", phase, attempt);
+        } else {
+            println!("Phase: {}", phase);
+        }
+        println!();
+    }
+
+    fn on_agent_start(&mut self, _phase: &str, agent_type: AgentType, prompt: &str) {
+        if self.verbose {
+            println!("Prompt: {}", prompt);
+            println!();
+        }
+        println!("Starting {} agent...", agent_type);
+    }
+
+    fn on_test_output(&mut self, line: &str) {
+        println!("  {}", line);
+    }
+
+    fn on_test_results(&mut self, results: &TestResults) {
+        println!();
+        println!("Test results: {}", results.summary());
+    }
+
+    fn on_baseline(&mut self, results: &TestResults) {
+        println!();
+        println!("Baseline: {}", results.summary());
+        if !results.has_test_cases() {
+            println!(
+                "{} Individual tests not reported, falling back to pass/fail counts",
+                self.emoji("⚠️", "[WARN]")
+            );
+        }
+        println!();
+    }
+
+    fn on_gate(&mut self, _phase: &str, gate: &Gate, problem: Option<&str>) {
+        match problem {
+            None => println!("{} Gate passed: {}", self.emoji("✅", "[OK]"), gate),
+            Some(problem) => {
+                println!("{} Gate failed: {}", self.emoji("❌", "[FAIL]"), gate);
+                if self.verbose {
+                    println!("{}", problem);
+                }
+            }
+        }
+    }
+
+    fn on_transition(&mut self, from: &str, to: &str) {
+        println!();
+        println!("{} -> {}", from, to);
+        println!();
+    }
+}
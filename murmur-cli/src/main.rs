@@ -9,7 +9,9 @@ use murmur_core::{Config, GitRepo, Secrets};
 use tracing_subscriber::{fmt, prelude::*, EnvFilter};
 
 use commands::{
-    AgentArgs, IssueArgs, OrchestrateArgs, RunArgs, StatusArgs, TddArgs, WorkArgs, WorktreeArgs,
+    AgentArgs, ApproveArgs, CoordinateArgs, IssueArgs, OrchestrateArgs, PromptsArgs, RejectArgs,
+    ReviewArgs, RunArgs, SandboxBridgeArgs, StatsArgs, StatusArgs, TddArgs, WorkArgs, WorkflowArgs,
+    WorktreeArgs,
 };
 
 /// Try to detect the GitHub repo from the current directory
@@ -61,7 +63,7 @@ struct Cli {
     #[arg(long, global = true, env = "MURMUR_MODEL")]
     model: Option<String>,
 
-    /// Backend to use: claude or cursor (overrides config and env)
+    /// Backend to use: claude, cursor or mock (overrides config and env)
     #[arg(long, global = true, env = "MURMUR_BACKEND")]
     backend: Option<String>,
 
@@ -109,12 +111,37 @@ enum Commands {
     /// Run a Test-Driven Development workflow
     Tdd(TddArgs),
 
+    /// Plan, implement, test, review and open a PR for a task
+    Coordinate(CoordinateArgs),
+
+    /// Review changes and fix the findings until approved
+    Review(ReviewArgs),
+
+    /// Run workflows defined in .murmur/workflows
+    Workflow(WorkflowArgs),
+
+    /// Approve a workflow waiting before its next phase
+    Approve(ApproveArgs),
+
+    /// Reject a workflow's work and send it back with a reason
+    Reject(RejectArgs),
+
+    /// Show token usage and cost statistics
+    Stats(StatsArgs),
+
+    /// Inspect agent prompt templates
+    Prompts(PromptsArgs),
+
     /// Show current configuration
     Config,
 
     /// Initialize secrets file
     #[command(visible_alias = "init")]
     SecretsInit,
+
+    /// Run a sandboxed agent behind the egress proxy (used by the sandbox)
+    #[command(hide = true)]
+    SandboxBridge(SandboxBridgeArgs),
 }
 
 #[tokio::main]
@@ -127,6 +154,11 @@ async fn main() -> anyhow::Result<()> {
 
     let cli = Cli::parse();
 
+    // Runs inside an agent's sandbox, where the user's config isn't mounted
+    if let Some(Commands::SandboxBridge(args)) = &cli.command {
+        return args.execute().await;
+    }
+
     if cli.verbose {
         tracing::info!("Verbose mode enabled");
     }
@@ -181,7 +213,40 @@ async fn main() -> anyhow::Result<()> {
             args.execute(cli.verbose, cli.no_emoji).await?;
         }
         Some(Commands::Tdd(args)) => {
-            args.execute(cli.verbose, cli.no_emoji, &config).await?;
+            // Try to detect repo from current directory
+            let repo = detect_repo();
+            args.execute(cli.verbose, cli.no_emoji, &config, repo.as_deref())
+                .await?;
+        }
+        Some(Commands::Coordinate(args)) => {
+            // Try to detect repo from current directory
+            let repo = detect_repo();
+            args.execute(cli.verbose, cli.no_emoji, &config, repo.as_deref())
+                .await?;
+        }
+        Some(Commands::Review(args)) => {
+            // Try to detect repo from current directory
+            let repo = detect_repo();
+            args.execute(cli.verbose, cli.no_emoji, &config, repo.as_deref())
+                .await?;
+        }
+        Some(Commands::Workflow(args)) => {
+            // Try to detect repo from current directory
+            let repo = detect_repo();
+            args.execute(cli.verbose, cli.no_emoji, &config, repo.as_deref())
+                .await?;
+        }
+        Some(Commands::Approve(args)) => {
+            args.execute(cli.verbose).await?;
+        }
+        Some(Commands::Reject(args)) => {
+            args.execute(cli.verbose).await?;
+        }
+        Some(Commands::Stats(args)) => {
+            args.execute(cli.verbose).await?;
+        }
+        Some(Commands::Prompts(args)) => {
+            args.execute(cli.verbose).await?;
         }
         Some(Commands::Config) => {
             println!("Murmur Configuration");
@@ -225,6 +290,7 @@ async fn main() -> anyhow::Result<()> {
                 std::process::exit(1);
             }
         },
+        Some(Commands::SandboxBridge(_)) => unreachable!("run before loading configuration"),
         None => {
             println!("Murmuration - Multi-agent orchestration for software development");
             println!();
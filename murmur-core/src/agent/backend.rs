@@ -6,10 +6,50 @@ use std::path::Path;
 use std::process::Stdio;
 use tokio::process::Command;
 
-use crate::config::Backend as BackendType;
+use crate::config::{Backend as BackendType, ResolvedConfig};
 use crate::{Error, Result};
 
-use super::spawn::AgentHandle;
+use super::mock::MockBackend;
+use super::sandbox::Sandbox;
+use super::spawn::{own_process_group, AgentHandle};
+
+/// Per-spawn options passed to a backend
+#[derive(Debug, Clone, Default)]
+pub struct SpawnOptions {
+    /// Environment variables to pass to the agent process
+    pub env_vars: Vec<(String, String)>,
+
+    /// Backend session to resume instead of starting a new conversation
+    pub resume_session: Option<String>,
+
+    /// Sandbox to confine the agent process in
+    pub sandbox: Option<Sandbox>,
+}
+
+impl SpawnOptions {
+    /// Create empty spawn options
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add an environment variable
+    pub fn with_env(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.env_vars.push((key.into(), value.into()));
+        self
+    }
+
+    /// Resume an existing backend session
+    pub fn with_resume_session(mut self, session_id: impl Into<String>) -> Self {
+        self.resume_session = Some(session_id.into());
+        self
+    }
+
+    /// Run the agent inside a sandbox
+    pub fn with_sandbox(mut self, sandbox: Sandbox) -> Self {
+        self.sandbox = Some(sandbox);
+        self
+    }
+}
 
 /// Trait for AI coding backends
 #[async_trait]
@@ -21,10 +61,81 @@ pub trait Backend: Send + Sync {
     fn build_command(&self, workdir: &Path) -> Command;
 
     /// Spawn an agent with a prompt
-    async fn spawn(&self, prompt: &str, workdir: &Path) -> Result<AgentHandle>;
+    async fn spawn(
+        &self,
+        prompt: &str,
+        workdir: &Path,
+        options: &SpawnOptions,
+    ) -> Result<AgentHandle>;
 
     /// Check if this backend is available on the system
     fn is_available(&self) -> bool;
+
+    /// Check if a previous session can be resumed natively in `workdir`
+    ///
+    /// Backends without native resume support return false, in which case
+    /// callers fall back to a summary prompt.
+    fn can_resume(&self, _session_id: &str, _workdir: &Path) -> bool {
+        false
+    }
+}
+
+/// Validate a working directory and return it as a string
+pub(crate) fn validate_workdir(workdir: &Path) -> Result<String> {
+    let workdir_str = workdir
+        .to_str()
+        .ok_or_else(|| Error::Agent("Invalid working directory path".to_string()))?
+        .to_string();
+
+    if !workdir.exists() {
+        return Err(Error::Agent(format!(
+            "Working directory does not exist: {}",
+            workdir_str
+        )));
+    }
+
+    Ok(workdir_str)
+}
+
+/// Spawn a prepared backend command, mapping a missing executable to a friendly error
+async fn spawn_command(
+    mut cmd: Command,
+    prompt: &str,
+    workdir_str: String,
+    options: &SpawnOptions,
+    executable: &str,
+    product: &str,
+) -> Result<AgentHandle> {
+    for (key, value) in &options.env_vars {
+        cmd.env(key, value);
+    }
+    cmd.arg(prompt);
+
+    let (mut cmd, proxy, executable, product) = match &options.sandbox {
+        Some(sandbox) => {
+            let (cmd, proxy) = sandbox.wrap(cmd, Path::new(&workdir_str)).await?;
+            (cmd, proxy, sandbox.bwrap_path(), "bubblewrap")
+        }
+        None => (cmd, None, executable, product),
+    };
+
+    own_process_group(&mut cmd);
+    let child = cmd.spawn().map_err(|e| {
+        if e.kind() == std::io::ErrorKind::NotFound {
+            Error::Agent(format!(
+                "{} executable not found at '{}'. Is {} installed?",
+                product, executable, product
+            ))
+        } else {
+            Error::Io(e)
+        }
+    })?;
+
+    let handle = AgentHandle::new(child, prompt.to_string(), workdir_str);
+    Ok(match proxy {
+        Some(proxy) => handle.with_egress_proxy(proxy),
+        None => handle,
+    })
 }
 
 /// Claude Code backend implementation
@@ -87,34 +198,26 @@ impl Backend for ClaudeBackend {
         cmd
     }
 
-    async fn spawn(&self, prompt: &str, workdir: &Path) -> Result<AgentHandle> {
-        let workdir_str = workdir
-            .to_str()
-            .ok_or_else(|| Error::Agent("Invalid working directory path".to_string()))?
-            .to_string();
-
-        if !workdir.exists() {
-            return Err(Error::Agent(format!(
-                "Working directory does not exist: {}",
-                workdir_str
-            )));
-        }
-
+    async fn spawn(
+        &self,
+        prompt: &str,
+        workdir: &Path,
+        options: &SpawnOptions,
+    ) -> Result<AgentHandle> {
+        let workdir_str = validate_workdir(workdir)?;
         let mut cmd = self.build_command(workdir);
-        cmd.arg(prompt);
-
-        let child = cmd.spawn().map_err(|e| {
-            if e.kind() == std::io::ErrorKind::NotFound {
-                Error::Agent(format!(
-                    "Claude executable not found at '{}'. Is Claude Code installed?",
-                    self.claude_path
-                ))
-            } else {
-                Error::Io(e)
-            }
-        })?;
-
-        Ok(AgentHandle::new(child, prompt.to_string(), workdir_str))
+        if let Some(ref session_id) = options.resume_session {
+            cmd.arg("--resume").arg(session_id);
+        }
+        spawn_command(
+            cmd,
+            prompt,
+            workdir_str,
+            options,
+            &self.claude_path,
+            "Claude Code",
+        )
+        .await
     }
 
     fn is_available(&self) -> bool {
@@ -125,12 +228,53 @@ impl Backend for ClaudeBackend {
             .status()
             .is_ok()
     }
+
+    fn can_resume(&self, session_id: &str, workdir: &Path) -> bool {
+        claude_session_path(session_id, workdir).is_some_and(|path| path.exists())
+    }
+}
+
+/// Location of a Claude Code session transcript
+///
+/// Claude Code stores sessions under `~/.claude/projects/<project>/<session>.jsonl`
+/// (or `$CLAUDE_CONFIG_DIR/projects/...`), where `<project>` is the working
+/// directory with every non-alphanumeric character replaced by `-`.
+fn claude_session_path(session_id: &str, workdir: &Path) -> Option<std::path::PathBuf> {
+    if session_id.is_empty()
+        || !session_id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-')
+    {
+        return None;
+    }
+
+    let config_dir = match std::env::var_os("CLAUDE_CONFIG_DIR") {
+        Some(dir) => std::path::PathBuf::from(dir),
+        None => dirs::home_dir()?.join(".claude"),
+    };
+
+    let workdir = workdir
+        .canonicalize()
+        .unwrap_or_else(|_| workdir.to_path_buf());
+    let project: String = workdir
+        .to_string_lossy()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect();
+
+    Some(
+        config_dir
+            .join("projects")
+            .join(project)
+            .join(format!("{}.jsonl", session_id)),
+    )
 }
 
 /// Cursor backend implementation
 #[derive(Debug, Clone)]
 pub struct CursorBackend {
     cursor_path: String,
+    model: Option<String>,
 }
 
 impl CursorBackend {
@@ -138,6 +282,7 @@ impl CursorBackend {
     pub fn new() -> Self {
         Self {
             cursor_path: "cursor".to_string(),
+            model: None,
         }
     }
 
@@ -146,6 +291,12 @@ impl CursorBackend {
         self.cursor_path = path.into();
         self
     }
+
+    /// Create a Cursor backend with a specific model
+    pub fn with_model(mut self, model: impl Into<String>) -> Self {
+        self.model = Some(model.into());
+        self
+    }
 }
 
 impl Default for CursorBackend {
@@ -162,40 +313,34 @@ impl Backend for CursorBackend {
 
     fn build_command(&self, workdir: &Path) -> Command {
         let mut cmd = Command::new(&self.cursor_path);
+
+        if let Some(ref model) = self.model {
+            cmd.arg("--model").arg(model);
+        }
+
         cmd.current_dir(workdir)
             .stdout(Stdio::piped())
             .stderr(Stdio::piped());
         cmd
     }
 
-    async fn spawn(&self, prompt: &str, workdir: &Path) -> Result<AgentHandle> {
-        let workdir_str = workdir
-            .to_str()
-            .ok_or_else(|| Error::Agent("Invalid working directory path".to_string()))?
-            .to_string();
-
-        if !workdir.exists() {
-            return Err(Error::Agent(format!(
-                "Working directory does not exist: {}",
-                workdir_str
-            )));
-        }
-
-        let mut cmd = self.build_command(workdir);
-        cmd.arg(prompt);
-
-        let child = cmd.spawn().map_err(|e| {
-            if e.kind() == std::io::ErrorKind::NotFound {
-                Error::Agent(format!(
-                    "Cursor executable not found at '{}'. Is Cursor installed?",
-                    self.cursor_path
-                ))
-            } else {
-                Error::Io(e)
-            }
-        })?;
-
-        Ok(AgentHandle::new(child, prompt.to_string(), workdir_str))
+    async fn spawn(
+        &self,
+        prompt: &str,
+        workdir: &Path,
+        options: &SpawnOptions,
+    ) -> Result<AgentHandle> {
+        let workdir_str = validate_workdir(workdir)?;
+        let cmd = self.build_command(workdir);
+        spawn_command(
+            cmd,
+            prompt,
+            workdir_str,
+            options,
+            &self.cursor_path,
+            "Cursor",
+        )
+        .await
     }
 
     fn is_available(&self) -> bool {
@@ -226,6 +371,37 @@ impl BackendRegistry {
         let mut registry = Self::new();
         registry.register(Box::new(ClaudeBackend::new()));
         registry.register(Box::new(CursorBackend::new()));
+        registry.register(Box::new(MockBackend::new()));
+        registry
+    }
+
+    /// Create a registry with default backends, where the backend selected by
+    /// `config` is configured with its resolved executable path and model
+    pub fn from_resolved(config: &ResolvedConfig) -> Self {
+        let mut registry = Self::with_defaults();
+        let model = config.model.as_deref();
+        let path = config.executable_path.as_str();
+
+        let backend: Box<dyn Backend> = match config.backend {
+            BackendType::Claude => {
+                let backend = ClaudeBackend::new().with_path(path);
+                Box::new(match model {
+                    Some(m) => backend.with_model(m),
+                    None => backend,
+                })
+            }
+            BackendType::Cursor => {
+                let backend = CursorBackend::new().with_path(path);
+                Box::new(match model {
+                    Some(m) => backend.with_model(m),
+                    None => backend,
+                })
+            }
+            BackendType::Mock if path.is_empty() => Box::new(MockBackend::new()),
+            BackendType::Mock => Box::new(MockBackend::new().with_script_path(path)),
+        };
+
+        registry.register(backend);
         registry
     }
 
@@ -258,6 +434,7 @@ impl BackendRegistry {
         match backend_type {
             BackendType::Claude => self.get("claude"),
             BackendType::Cursor => self.get("cursor"),
+            BackendType::Mock => self.get("mock"),
         }
     }
 }
@@ -307,7 +484,8 @@ mod tests {
         let registered = registry.list_registered();
         assert!(registered.contains(&"claude"));
         assert!(registered.contains(&"cursor"));
-        assert_eq!(registered.len(), 2);
+        assert!(registered.contains(&"mock"));
+        assert_eq!(registered.len(), 3);
     }
 
     #[test]
@@ -320,6 +498,10 @@ mod tests {
         let backend = registry.get_by_type(BackendType::Cursor);
         assert!(backend.is_some());
         assert_eq!(backend.unwrap().name(), "cursor");
+
+        let backend = registry.get_by_type(BackendType::Mock);
+        assert!(backend.is_some());
+        assert_eq!(backend.unwrap().name(), "mock");
     }
 
     #[test]
@@ -334,21 +516,63 @@ mod tests {
 
     #[test]
     fn test_cursor_backend_builder() {
-        let backend = CursorBackend::new().with_path("/custom/cursor");
+        let backend = CursorBackend::new()
+            .with_path("/custom/cursor")
+            .with_model("gpt-4");
         assert_eq!(backend.cursor_path, "/custom/cursor");
+        assert_eq!(backend.model, Some("gpt-4".to_string()));
+    }
+
+    #[test]
+    fn test_claude_session_path() {
+        let path = claude_session_path("abc-123", Path::new("/nonexistent/my_repo.git")).unwrap();
+        assert!(path.ends_with("projects/-nonexistent-my-repo-git/abc-123.jsonl"));
+
+        // Session IDs that could escape the projects directory are rejected
+        assert!(claude_session_path("../etc", Path::new("/tmp")).is_none());
+        assert!(claude_session_path("", Path::new("/tmp")).is_none());
+    }
+
+    #[test]
+    fn test_claude_cannot_resume_missing_session() {
+        let backend = ClaudeBackend::new();
+        assert!(!backend.can_resume("no-such-session", Path::new("/nonexistent")));
+        assert!(!CursorBackend::new().can_resume("abc", Path::new("/nonexistent")));
+    }
+
+    #[tokio::test]
+    async fn test_registry_from_resolved() {
+        let config = ResolvedConfig {
+            backend: BackendType::Cursor,
+            model: None,
+            executable_path: "/nonexistent/cursor".to_string(),
+        };
+        let registry = BackendRegistry::from_resolved(&config);
+        assert_eq!(registry.list_registered().len(), 3);
+
+        let backend = registry.get_by_type(BackendType::Cursor).unwrap();
+        let err = backend
+            .spawn("test", &std::env::temp_dir(), &SpawnOptions::new())
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("/nonexistent/cursor"));
     }
 
     #[tokio::test]
     async fn test_claude_spawn_invalid_workdir() {
         let backend = ClaudeBackend::new();
-        let result = backend.spawn("test", Path::new("/nonexistent/path")).await;
+        let result = backend
+            .spawn("test", Path::new("/nonexistent/path"), &SpawnOptions::new())
+            .await;
         assert!(result.is_err());
     }
 
     #[tokio::test]
     async fn test_cursor_spawn_invalid_workdir() {
         let backend = CursorBackend::new();
-        let result = backend.spawn("test", Path::new("/nonexistent/path")).await;
+        let result = backend
+            .spawn("test", Path::new("/nonexistent/path"), &SpawnOptions::new())
+            .await;
         assert!(result.is_err());
     }
 }
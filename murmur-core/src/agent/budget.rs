@@ -0,0 +1,471 @@
+//! Token and dollar budget enforcement for agent runs
+//!
+//! A [`BudgetTracker`] accumulates usage from stream events while an agent runs
+//! and reports when any of the configured limits (per run, per issue or per
+//! orchestration) has been crossed. [`BudgetHandler`] wraps another
+//! [`StreamHandler`] so the stream stops as soon as a budget is exceeded.
+
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+use serde::{Deserialize, Serialize};
+
+use super::output::{CostInfo, StreamHandler};
+use super::pricing::PricingTable;
+
+/// Cumulative token and dollar usage
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct Usage {
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cache_read_tokens: u64,
+    pub cache_write_tokens: u64,
+    pub cost_usd: f64,
+}
+
+impl Usage {
+    /// Total tokens counted against token budgets (input + output)
+    pub fn total_tokens(&self) -> u64 {
+        self.input_tokens + self.output_tokens
+    }
+
+    /// Convert reported usage of `model`, priced as [`PricingTable::cost_usd`] says
+    pub fn from_cost_info(info: &CostInfo, pricing: &PricingTable, model: Option<&str>) -> Self {
+        Self {
+            input_tokens: info.input_tokens,
+            output_tokens: info.output_tokens,
+            cache_read_tokens: info.cache_read_tokens.unwrap_or(0),
+            cache_write_tokens: info.cache_write_tokens.unwrap_or(0),
+            cost_usd: pricing.cost_usd(model, info),
+        }
+    }
+
+    /// Add another usage sample to this one
+    pub fn add(&mut self, other: &Usage) {
+        self.input_tokens += other.input_tokens;
+        self.output_tokens += other.output_tokens;
+        self.cache_read_tokens += other.cache_read_tokens;
+        self.cache_write_tokens += other.cache_write_tokens;
+        self.cost_usd += other.cost_usd;
+    }
+
+    /// Subtract another usage sample, saturating at zero
+    pub fn sub(&mut self, other: &Usage) {
+        self.input_tokens = self.input_tokens.saturating_sub(other.input_tokens);
+        self.output_tokens = self.output_tokens.saturating_sub(other.output_tokens);
+        self.cache_read_tokens = self
+            .cache_read_tokens
+            .saturating_sub(other.cache_read_tokens);
+        self.cache_write_tokens = self
+            .cache_write_tokens
+            .saturating_sub(other.cache_write_tokens);
+        self.cost_usd = (self.cost_usd - other.cost_usd).max(0.0);
+    }
+}
+
+/// A token and/or dollar limit
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct Budget {
+    /// Maximum input + output tokens
+    pub max_tokens: Option<u64>,
+    /// Maximum spend in dollars
+    pub max_cost_usd: Option<f64>,
+}
+
+impl Budget {
+    /// Create a budget from optional limits
+    pub fn new(max_tokens: Option<u64>, max_cost_usd: Option<f64>) -> Self {
+        Self {
+            max_tokens,
+            max_cost_usd,
+        }
+    }
+
+    /// Whether no limit is configured
+    pub fn is_unlimited(&self) -> bool {
+        self.max_tokens.is_none() && self.max_cost_usd.is_none()
+    }
+
+    /// Describe the first limit the usage has reached, if any
+    pub fn check(&self, usage: &Usage) -> Option<String> {
+        if let Some(max) = self.max_tokens {
+            if usage.total_tokens() >= max {
+                return Some(format!(
+                    "{} tokens used (limit {})",
+                    usage.total_tokens(),
+                    max
+                ));
+            }
+        }
+        if let Some(max) = self.max_cost_usd {
+            if usage.cost_usd >= max {
+                return Some(format!("${:.4} spent (limit ${:.2})", usage.cost_usd, max));
+            }
+        }
+        None
+    }
+}
+
+/// Which budget was exceeded
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BudgetScope {
+    Run,
+    Issue,
+    Orchestration,
+}
+
+impl fmt::Display for BudgetScope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BudgetScope::Run => write!(f, "run"),
+            BudgetScope::Issue => write!(f, "issue"),
+            BudgetScope::Orchestration => write!(f, "orchestration"),
+        }
+    }
+}
+
+/// Details of an exceeded budget
+#[derive(Debug, Clone, PartialEq)]
+pub struct BudgetExceeded {
+    pub scope: BudgetScope,
+    pub detail: String,
+}
+
+impl fmt::Display for BudgetExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} budget exceeded: {}", self.scope, self.detail)
+    }
+}
+
+/// Usage shared between all runs of one orchestration
+pub type SharedUsage = Arc<Mutex<Usage>>;
+
+/// Tracks usage of a single agent run against its budgets
+#[derive(Debug, Clone)]
+pub struct BudgetTracker {
+    pricing: PricingTable,
+    model: Option<String>,
+    run_budget: Budget,
+    issue_budget: Budget,
+    issue_spent: Usage,
+    orchestration_budget: Budget,
+    orchestration_usage: Option<SharedUsage>,
+    usage: Usage,
+    exceeded: Option<BudgetExceeded>,
+}
+
+impl BudgetTracker {
+    /// Create a tracker for a run with the given per-run budget
+    pub fn new(run_budget: Budget) -> Self {
+        Self {
+            pricing: PricingTable::default(),
+            model: None,
+            run_budget,
+            issue_budget: Budget::default(),
+            issue_spent: Usage::default(),
+            orchestration_budget: Budget::default(),
+            orchestration_usage: None,
+            usage: Usage::default(),
+            exceeded: None,
+        }
+    }
+
+    /// Price the run as `model` with the configured prices
+    pub fn with_pricing(mut self, pricing: &PricingTable, model: Option<&str>) -> Self {
+        self.pricing = pricing.clone();
+        self.model = model.map(str::to_string);
+        self
+    }
+
+    /// Apply an issue budget, given what earlier runs for the issue already spent
+    pub fn with_issue_budget(mut self, budget: Budget, already_spent: Usage) -> Self {
+        self.issue_budget = budget;
+        self.issue_spent = already_spent;
+        self
+    }
+
+    /// Apply an orchestration budget shared with other runs through `usage`
+    pub fn with_orchestration_budget(mut self, budget: Budget, usage: SharedUsage) -> Self {
+        self.orchestration_budget = budget;
+        self.orchestration_usage = Some(usage);
+        self
+    }
+
+    /// Usage of this run so far
+    pub fn usage(&self) -> Usage {
+        self.usage
+    }
+
+    /// The exceeded budget, if any
+    pub fn exceeded(&self) -> Option<&BudgetExceeded> {
+        self.exceeded.as_ref()
+    }
+
+    /// Record the usage of one assistant turn
+    pub fn record_turn(&mut self, info: &CostInfo) {
+        let turn = Usage::from_cost_info(info, &self.pricing, self.model.as_deref());
+        self.usage.add(&turn);
+        if let Some(shared) = &self.orchestration_usage {
+            shared.lock().unwrap().add(&turn);
+        }
+        self.check();
+    }
+
+    /// Record the final cumulative usage reported at the end of the run
+    ///
+    /// The final report replaces the per-turn estimate when it is present.
+    pub fn record_final(&mut self, info: &CostInfo) {
+        let total = Usage::from_cost_info(info, &self.pricing, self.model.as_deref());
+        if total.total_tokens() == 0 && total.cost_usd == 0.0 {
+            return;
+        }
+        if let Some(shared) = &self.orchestration_usage {
+            let mut shared = shared.lock().unwrap();
+            shared.sub(&self.usage);
+            shared.add(&total);
+        }
+        self.usage = total;
+        self.check();
+    }
+
+    fn check(&mut self) {
+        if self.exceeded.is_some() {
+            return;
+        }
+
+        let mut issue_usage = self.issue_spent;
+        issue_usage.add(&self.usage);
+        let orchestration_usage = self
+            .orchestration_usage
+            .as_ref()
+            .map(|shared| *shared.lock().unwrap())
+            .unwrap_or_default();
+
+        let checks = [
+            (BudgetScope::Run, &self.run_budget, self.usage),
+            (BudgetScope::Issue, &self.issue_budget, issue_usage),
+            (
+                BudgetScope::Orchestration,
+                &self.orchestration_budget,
+                orchestration_usage,
+            ),
+        ];
+        self.exceeded = checks.iter().find_map(|(scope, budget, usage)| {
+            budget.check(usage).map(|detail| BudgetExceeded {
+                scope: *scope,
+                detail,
+            })
+        });
+    }
+}
+
+/// Stream handler wrapper that enforces a [`BudgetTracker`]
+///
+/// Stops the stream once a budget is exceeded; the caller is then expected to
+/// kill the agent and record the outcome.
+pub struct BudgetHandler<H> {
+    inner: H,
+    tracker: BudgetTracker,
+}
+
+impl<H: StreamHandler> BudgetHandler<H> {
+    /// Wrap a handler with budget enforcement
+    pub fn new(inner: H, tracker: BudgetTracker) -> Self {
+        Self { inner, tracker }
+    }
+
+    /// The wrapped handler
+    pub fn inner(&self) -> &H {
+        &self.inner
+    }
+
+    /// The wrapped handler, mutably
+    pub fn inner_mut(&mut self) -> &mut H {
+        &mut self.inner
+    }
+
+    /// The budget tracker
+    pub fn tracker(&self) -> &BudgetTracker {
+        &self.tracker
+    }
+
+    /// The exceeded budget, if any
+    pub fn exceeded(&self) -> Option<&BudgetExceeded> {
+        self.tracker.exceeded()
+    }
+
+    /// Unwrap into the inner handler and tracker
+    pub fn into_parts(self) -> (H, BudgetTracker) {
+        (self.inner, self.tracker)
+    }
+}
+
+impl<H: StreamHandler> StreamHandler for BudgetHandler<H> {
+    fn on_system(&mut self, subtype: Option<&str>, session_id: Option<&str>) {
+        self.inner.on_system(subtype, session_id);
+    }
+
+    fn on_user(&mut self, message: &serde_json::Value) {
+        self.inner.on_user(message);
+    }
+
+    fn on_assistant_text(&mut self, text: &str) {
+        self.inner.on_assistant_text(text);
+    }
+
+    fn on_tool_use(&mut self, tool: &str, input: &serde_json::Value) {
+        self.inner.on_tool_use(tool, input);
+    }
+
+    fn on_tool_result(&mut self, output: &str, is_error: bool) {
+        self.inner.on_tool_result(output, is_error);
+    }
+
+    fn on_usage(&mut self, usage: &CostInfo) {
+        self.tracker.record_turn(usage);
+        self.inner.on_usage(usage);
+    }
+
+    fn on_complete(&mut self, cost: Option<&CostInfo>, duration_ms: Option<u64>) {
+        if let Some(cost) = cost {
+            self.tracker.record_final(cost);
+        }
+        self.inner.on_complete(cost, duration_ms);
+    }
+
+    fn on_parse_error(&mut self, line: &str, error: &serde_json::Error) {
+        self.inner.on_parse_error(line, error);
+    }
+
+    fn should_stop(&self) -> bool {
+        self.tracker.exceeded().is_some() || self.inner.should_stop()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::pricing::ModelPrice;
+
+    fn turn(input: u64, output: u64) -> CostInfo {
+        CostInfo {
+            input_tokens: input,
+            output_tokens: output,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_unlimited_never_exceeds() {
+        let mut tracker = BudgetTracker::new(Budget::default());
+        tracker.record_turn(&turn(1_000_000, 1_000_000));
+        assert!(tracker.exceeded().is_none());
+        assert_eq!(tracker.usage().total_tokens(), 2_000_000);
+    }
+
+    #[test]
+    fn test_run_token_budget() {
+        let mut tracker = BudgetTracker::new(Budget::new(Some(100), None));
+        tracker.record_turn(&turn(40, 20));
+        assert!(tracker.exceeded().is_none());
+        tracker.record_turn(&turn(30, 10));
+        let exceeded = tracker.exceeded().unwrap();
+        assert_eq!(exceeded.scope, BudgetScope::Run);
+        assert!(exceeded.to_string().starts_with("run budget exceeded"));
+    }
+
+    #[test]
+    fn test_cost_estimated_from_price() {
+        let mut tracker = BudgetTracker::new(Budget::new(None, Some(1.0)))
+            .with_pricing(&PricingTable::new(), Some("claude-opus-4"));
+        // 1M output tokens of opus costs $75
+        tracker.record_turn(&turn(0, 1_000_000));
+        assert!((tracker.usage().cost_usd - 75.0).abs() < 1e-9);
+        assert!(tracker.exceeded().is_some());
+    }
+
+    #[test]
+    fn test_reported_cost_preferred() {
+        let mut tracker = BudgetTracker::new(Budget::new(None, Some(0.5)));
+        tracker.record_final(&CostInfo {
+            input_tokens: 10,
+            output_tokens: 10,
+            cost_usd: Some(0.75),
+            ..Default::default()
+        });
+        assert_eq!(tracker.usage().cost_usd, 0.75);
+        assert!(tracker.exceeded().is_some());
+    }
+
+    #[test]
+    fn test_configured_price_preferred() {
+        let pricing = PricingTable::new().with_price("sonnet", ModelPrice::new(1.0, 1.0));
+        let mut tracker = BudgetTracker::new(Budget::new(None, Some(0.5)))
+            .with_pricing(&pricing, Some("claude-sonnet-4"));
+        tracker.record_final(&CostInfo {
+            input_tokens: 100_000,
+            output_tokens: 100_000,
+            cost_usd: Some(0.75),
+            ..Default::default()
+        });
+        assert!((tracker.usage().cost_usd - 0.2).abs() < 1e-9);
+        assert!(tracker.exceeded().is_none());
+    }
+
+    #[test]
+    fn test_issue_budget_includes_previous_runs() {
+        let spent = Usage {
+            input_tokens: 90,
+            ..Default::default()
+        };
+        let mut tracker = BudgetTracker::new(Budget::default())
+            .with_issue_budget(Budget::new(Some(100), None), spent);
+        tracker.record_turn(&turn(5, 5));
+        assert_eq!(tracker.exceeded().unwrap().scope, BudgetScope::Issue);
+    }
+
+    #[test]
+    fn test_orchestration_budget_is_shared() {
+        let shared = SharedUsage::default();
+        let budget = Budget::new(Some(100), None);
+        let mut a =
+            BudgetTracker::new(Budget::default()).with_orchestration_budget(budget, shared.clone());
+        let mut b =
+            BudgetTracker::new(Budget::default()).with_orchestration_budget(budget, shared.clone());
+
+        a.record_turn(&turn(30, 30));
+        assert!(a.exceeded().is_none());
+        b.record_turn(&turn(30, 30));
+        assert_eq!(b.exceeded().unwrap().scope, BudgetScope::Orchestration);
+
+        // The final report replaces the per-turn estimate in the shared pool
+        a.record_final(&turn(10, 10));
+        assert_eq!(shared.lock().unwrap().total_tokens(), 80);
+    }
+
+    struct Collecting {
+        texts: Vec<String>,
+    }
+
+    impl StreamHandler for Collecting {
+        fn on_assistant_text(&mut self, text: &str) {
+            self.texts.push(text.to_string());
+        }
+    }
+
+    #[test]
+    fn test_handler_stops_when_exceeded() {
+        let tracker = BudgetTracker::new(Budget::new(Some(10), None));
+        let mut handler = BudgetHandler::new(Collecting { texts: vec![] }, tracker);
+
+        handler.on_assistant_text("hello");
+        assert!(!handler.should_stop());
+        handler.on_usage(&turn(8, 4));
+        assert!(handler.should_stop());
+        assert!(handler.exceeded().is_some());
+
+        let (inner, tracker) = handler.into_parts();
+        assert_eq!(inner.texts, vec!["hello"]);
+        assert_eq!(tracker.usage().total_tokens(), 12);
+    }
+}
@@ -0,0 +1,406 @@
+//! Repository context injected into agent prompts
+//!
+//! A [`ContextCollector`] gathers what an agent would otherwise rediscover on
+//! every run: project guidelines (`CLAUDE.md`, `CONTRIBUTING.md`), the test
+//! framework, recent commits, issue discussion and the diffs of the PRs the
+//! task builds on. [`ContextCollector::collect`] trims the result to a token
+//! budget so large diffs can't crowd out the task itself.
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use serde::{Deserialize, Serialize};
+
+use crate::workflow::TestFramework;
+
+/// Rough characters-per-token ratio used for budgeting
+const CHARS_PER_TOKEN: usize = 4;
+
+/// Sections with less room than this are dropped rather than truncated
+const MIN_SECTION_TOKENS: usize = 64;
+
+/// Repository context configuration (`[context]`)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ContextConfig {
+    /// Whether to inject repository context at all
+    pub enabled: bool,
+
+    /// Approximate token budget for all injected context
+    pub max_tokens: usize,
+
+    /// Guideline files read from the repository root, in priority order
+    pub files: Vec<String>,
+
+    /// Number of recent commits to include (0 to skip)
+    pub commit_log: usize,
+
+    /// Include comments on the linked issue
+    pub issue_comments: bool,
+
+    /// Include the diffs of PRs for the issue's dependencies
+    pub dependency_diffs: bool,
+}
+
+impl Default for ContextConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_tokens: 8000,
+            files: vec![
+                "CLAUDE.md".to_string(),
+                "AGENTS.md".to_string(),
+                "CONTRIBUTING.md".to_string(),
+            ],
+            commit_log: 10,
+            issue_comments: true,
+            dependency_diffs: true,
+        }
+    }
+}
+
+/// One titled block of context
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContextSection {
+    /// Section heading
+    pub title: String,
+    /// Markdown body
+    pub body: String,
+}
+
+impl ContextSection {
+    /// Create a section
+    pub fn new(title: impl Into<String>, body: impl Into<String>) -> Self {
+        Self {
+            title: title.into(),
+            body: body.into(),
+        }
+    }
+
+    /// Approximate token count of the rendered section
+    pub fn estimated_tokens(&self) -> usize {
+        estimate_tokens(&self.title) + estimate_tokens(&self.body)
+    }
+}
+
+/// Context selected for a prompt
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RepositoryContext {
+    /// Sections that fit the budget, in priority order
+    pub sections: Vec<ContextSection>,
+    /// Titles of sections that were cut short or left out
+    pub truncated: Vec<String>,
+}
+
+impl RepositoryContext {
+    /// Whether there is nothing to inject
+    pub fn is_empty(&self) -> bool {
+        self.sections.is_empty()
+    }
+
+    /// Approximate token count of all sections
+    pub fn estimated_tokens(&self) -> usize {
+        self.sections
+            .iter()
+            .map(ContextSection::estimated_tokens)
+            .sum()
+    }
+
+    /// Render the sections as Markdown, one `###` heading each
+    pub fn to_markdown(&self) -> String {
+        let mut out = String::new();
+        for section in &self.sections {
+            if !out.is_empty() {
+                out.push('\n');
+            }
+            out.push_str(&format!(
+                "### {}\n\n{}\n",
+                section.title,
+                section.body.trim_end()
+            ));
+        }
+        out
+    }
+
+    /// Append a `## Repository Context` section to a free-form prompt
+    pub fn append_to(&self, prompt: &mut String) {
+        if self.is_empty() {
+            return;
+        }
+        prompt.push_str("\n\n## Repository Context\n\n");
+        prompt.push_str(&self.to_markdown());
+    }
+}
+
+/// Gathers repository context for an agent working in `workdir`
+#[derive(Debug, Clone)]
+pub struct ContextCollector {
+    config: ContextConfig,
+    workdir: PathBuf,
+    touched_files: Vec<String>,
+    issue_comments: Vec<(String, String)>,
+    dependency_diffs: Vec<(String, String)>,
+}
+
+impl ContextCollector {
+    /// Create a collector for a working directory
+    pub fn new(workdir: impl Into<PathBuf>, config: ContextConfig) -> Self {
+        Self {
+            config,
+            workdir: workdir.into(),
+            touched_files: Vec::new(),
+            issue_comments: Vec::new(),
+            dependency_diffs: Vec::new(),
+        }
+    }
+
+    /// Get the configuration
+    pub fn config(&self) -> &ContextConfig {
+        &self.config
+    }
+
+    /// Limit the commit log to these paths
+    pub fn with_touched_files(mut self, files: &[String]) -> Self {
+        self.touched_files.extend(files.iter().cloned());
+        self
+    }
+
+    /// Add a comment from the linked issue
+    pub fn with_issue_comment(
+        mut self,
+        author: impl Into<String>,
+        body: impl Into<String>,
+    ) -> Self {
+        self.issue_comments.push((author.into(), body.into()));
+        self
+    }
+
+    /// Add the diff of a PR this task depends on
+    pub fn with_dependency_diff(
+        mut self,
+        title: impl Into<String>,
+        diff: impl Into<String>,
+    ) -> Self {
+        self.dependency_diffs.push((title.into(), diff.into()));
+        self
+    }
+
+    /// Gather all sections in priority order, without applying the budget
+    pub fn sections(&self) -> Vec<ContextSection> {
+        if !self.config.enabled {
+            return Vec::new();
+        }
+
+        let mut sections = Vec::new();
+
+        if let Some(framework) = TestFramework::detect(&self.workdir) {
+            let command = framework.run_command(&self.workdir);
+            // Show wrapper scripts such as mvnw relative to the project
+            let program = Path::new(command.get_program())
+                .strip_prefix(&self.workdir)
+                .map(|p| Path::new(".").join(p).into_os_string())
+                .unwrap_or_else(|_| command.get_program().to_os_string());
+            let command = std::iter::once(program.as_os_str())
+                .chain(command.get_args())
+                .map(|s| s.to_string_lossy())
+                .collect::<Vec<_>>()
+                .join(" ");
+            sections.push(ContextSection::new(
+                "Test Framework",
+                format!(
+                    "This project uses {}. Run the tests with `{}`.",
+                    framework.name(),
+                    command
+                ),
+            ));
+        }
+
+        for name in &self.config.files {
+            if let Ok(content) = std::fs::read_to_string(self.workdir.join(name)) {
+                if !content.trim().is_empty() {
+                    sections.push(ContextSection::new(name.clone(), content));
+                }
+            }
+        }
+
+        if !self.issue_comments.is_empty() {
+            let body = self
+                .issue_comments
+                .iter()
+                .map(|(author, body)| format!("**@{}:**\n{}", author, body.trim()))
+                .collect::<Vec<_>>()
+                .join("\n\n");
+            sections.push(ContextSection::new("Issue Discussion", body));
+        }
+
+        if let Some(log) = self.commit_log() {
+            sections.push(ContextSection::new(
+                "Recent Commits",
+                format!("```\n{}\n```", log.trim_end()),
+            ));
+        }
+
+        for (title, diff) in &self.dependency_diffs {
+            sections.push(ContextSection::new(
+                title.clone(),
+                format!("```diff\n{}\n```", diff.trim_end()),
+            ));
+        }
+
+        sections
+    }
+
+    /// Gather context and fit it to the token budget
+    pub fn collect(&self) -> RepositoryContext {
+        fit_to_budget(self.sections(), self.config.max_tokens)
+    }
+
+    /// Recent commits touching the task's files (or the whole repository)
+    fn commit_log(&self) -> Option<String> {
+        if self.config.commit_log == 0 {
+            return None;
+        }
+
+        let mut cmd = Command::new("git");
+        cmd.arg("log")
+            .arg(format!("-n{}", self.config.commit_log))
+            .arg("--format=%h %s (%an, %ar)")
+            .current_dir(&self.workdir);
+        if !self.touched_files.is_empty() {
+            cmd.arg("--").args(&self.touched_files);
+        }
+
+        let output = cmd.output().ok()?;
+        let log = String::from_utf8_lossy(&output.stdout).into_owned();
+        (output.status.success() && !log.trim().is_empty()).then_some(log)
+    }
+}
+
+/// Approximate the token count of some text
+pub fn estimate_tokens(text: &str) -> usize {
+    text.len().div_ceil(CHARS_PER_TOKEN)
+}
+
+/// Keep sections in order until the budget runs out, truncating the one that
+/// crosses it
+fn fit_to_budget(sections: Vec<ContextSection>, max_tokens: usize) -> RepositoryContext {
+    let mut context = RepositoryContext::default();
+    let mut remaining = max_tokens;
+
+    for section in sections {
+        let tokens = section.estimated_tokens();
+        if tokens <= remaining {
+            remaining -= tokens;
+            context.sections.push(section);
+            continue;
+        }
+
+        context.truncated.push(section.title.clone());
+        let room = remaining.saturating_sub(estimate_tokens(&section.title));
+        if room < MIN_SECTION_TOKENS {
+            continue;
+        }
+
+        let body = truncate_lines(&section.body, room * CHARS_PER_TOKEN);
+        remaining -= estimate_tokens(&section.title) + estimate_tokens(&body);
+        context
+            .sections
+            .push(ContextSection::new(section.title, body));
+    }
+
+    context
+}
+
+/// Cut text to at most `max_chars` at a line boundary, noting the truncation
+fn truncate_lines(text: &str, max_chars: usize) -> String {
+    const MARKER: &str = "… (truncated)";
+    let budget = max_chars.saturating_sub(MARKER.len() + 1);
+
+    let mut out = String::new();
+    for line in text.lines() {
+        if out.len() + line.len() + 1 > budget {
+            break;
+        }
+        out.push_str(line);
+        out.push('\n');
+    }
+
+    // Keep code fences balanced so the rest of the prompt isn't swallowed
+    if out.matches("```").count() % 2 == 1 {
+        out.push_str("```\n");
+    }
+    out.push_str(MARKER);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_collects_guidelines_and_framework() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("Cargo.toml"), "[package]\n").unwrap();
+        std::fs::write(dir.path().join("CLAUDE.md"), "Use anyhow in binaries.\n").unwrap();
+
+        let context = ContextCollector::new(dir.path(), ContextConfig::default())
+            .with_issue_comment("alice", "Please keep the API stable.")
+            .collect();
+
+        let titles: Vec<_> = context.sections.iter().map(|s| s.title.as_str()).collect();
+        assert_eq!(titles, ["Test Framework", "CLAUDE.md", "Issue Discussion"]);
+        let markdown = context.to_markdown();
+        assert!(markdown.contains("`cargo test --no-fail-fast`"));
+        assert!(markdown.contains("### CLAUDE.md\n\nUse anyhow in binaries."));
+        assert!(markdown.contains("**@alice:**"));
+        assert!(context.truncated.is_empty());
+    }
+
+    #[test]
+    fn test_disabled_collects_nothing() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("CLAUDE.md"), "rules").unwrap();
+        let config = ContextConfig {
+            enabled: false,
+            ..Default::default()
+        };
+        assert!(ContextCollector::new(dir.path(), config)
+            .collect()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_budget_truncates_and_drops() {
+        let sections = vec![
+            ContextSection::new("Small", "short"),
+            ContextSection::new("Large", "line of diff\n".repeat(500)),
+            ContextSection::new("Later", "never fits"),
+        ];
+
+        let context = fit_to_budget(sections, 300);
+        assert!(context.estimated_tokens() <= 300);
+        assert_eq!(context.sections.len(), 2);
+        assert!(context.sections[1].body.ends_with("… (truncated)"));
+        assert_eq!(context.truncated, ["Large", "Later"]);
+    }
+
+    #[test]
+    fn test_truncation_closes_code_fence() {
+        let body = format!("```diff\n{}```", "+ added\n".repeat(200));
+        let truncated = truncate_lines(&body, 200);
+        assert!(truncated.len() <= 200 + "```\n".len());
+        assert_eq!(truncated.matches("```").count() % 2, 0);
+    }
+
+    #[test]
+    fn test_dependency_diffs_are_fenced() {
+        let dir = tempfile::tempdir().unwrap();
+        let context = ContextCollector::new(dir.path(), ContextConfig::default())
+            .with_dependency_diff("PR #12: Add parser", "+fn parse() {}\n")
+            .collect();
+        assert_eq!(
+            context.sections[0],
+            ContextSection::new("PR #12: Add parser", "```diff\n+fn parse() {}\n```")
+        );
+    }
+}
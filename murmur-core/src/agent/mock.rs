@@ -0,0 +1,540 @@
+//! Scriptable mock backend for offline end-to-end tests
+//!
+//! The mock backend replays a fixture of stream-json messages, file edits and
+//! delays through a real child process, so the rest of the pipeline (output
+//! streaming, database logging, git automation) runs unchanged without a real
+//! AI agent installed.
+//!
+//! Fixtures can be written as TOML or JSON:
+//!
+//! ```toml
+//! exit_code = 0
+//!
+//! [[steps]]
+//! message = { type = "system", subtype = "init", session_id = "mock-session" }
+//!
+//! [[steps]]
+//! message = { type = "tool_use", tool = "Write", input = { file_path = "hello.txt" } }
+//!
+//! [[steps]]
+//! write_file = { path = "hello.txt", content = "hello\n" }
+//!
+//! [[steps]]
+//! delay_ms = 50
+//!
+//! [[steps]]
+//! message = { type = "result", duration_ms = 50 }
+//! ```
+//!
+//! A `.jsonl` fixture is replayed verbatim, one stream-json line at a time.
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+use tokio::process::Command;
+
+use crate::{Error, Result};
+
+use super::backend::{validate_workdir, Backend, SpawnOptions};
+use super::output::{AssistantMessage, ContentBlock, StreamMessage};
+use super::spawn::own_process_group;
+use super::spawn::AgentHandle;
+
+/// Counter used to give each generated replay script a unique name
+static SCRIPT_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// A single step in a mock agent script
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MockStep {
+    /// Emit a stream-json message on stdout
+    Message(StreamMessage),
+    /// Emit a raw line on stdout (useful for malformed output)
+    Raw(String),
+    /// Emit a line on stderr
+    Stderr(String),
+    /// Sleep for the given number of milliseconds
+    DelayMs(u64),
+    /// Write a file relative to the working directory
+    WriteFile { path: String, content: String },
+    /// Delete a file relative to the working directory
+    DeleteFile { path: String },
+}
+
+/// A scripted run of the mock agent
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct MockScript {
+    /// Steps to replay, in order
+    pub steps: Vec<MockStep>,
+
+    /// Exit code of the mock agent process
+    pub exit_code: i32,
+}
+
+impl MockScript {
+    /// Create an empty script that exits successfully
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Load a script from a fixture file
+    ///
+    /// The format is chosen by extension: `.toml`, `.jsonl` (raw stream-json
+    /// lines) or JSON for anything else.
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = std::fs::read_to_string(path).map_err(|e| {
+            Error::Agent(format!(
+                "Failed to read mock fixture '{}': {}",
+                path.display(),
+                e
+            ))
+        })?;
+
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("toml") => toml::from_str(&contents)
+                .map_err(|e| Error::Config(format!("Failed to parse mock fixture: {}", e))),
+            Some("jsonl") => Ok(Self {
+                steps: contents
+                    .lines()
+                    .filter(|line| !line.trim().is_empty())
+                    .map(|line| MockStep::Raw(line.to_string()))
+                    .collect(),
+                exit_code: 0,
+            }),
+            _ => serde_json::from_str(&contents)
+                .map_err(|e| Error::Config(format!("Failed to parse mock fixture: {}", e))),
+        }
+    }
+
+    /// The script used when no fixture is configured
+    pub fn default_script() -> Self {
+        Self::new()
+            .message(StreamMessage::System {
+                subtype: Some("init".to_string()),
+                session_id: Some("mock-session".to_string()),
+            })
+            .assistant_text("Mock agent completed the task.")
+            .message(StreamMessage::Result {
+                cost: None,
+                duration_ms: Some(0),
+                duration_api_ms: Some(0),
+                usage: None,
+                total_cost_usd: None,
+            })
+    }
+
+    /// Add a stream message step
+    pub fn message(mut self, message: StreamMessage) -> Self {
+        self.steps.push(MockStep::Message(message));
+        self
+    }
+
+    /// Add an assistant text message step
+    pub fn assistant_text(self, text: impl Into<String>) -> Self {
+        self.message(StreamMessage::Assistant {
+            message: AssistantMessage {
+                content: vec![ContentBlock::Text { text: text.into() }],
+                ..Default::default()
+            },
+        })
+    }
+
+    /// Add a tool_use event step
+    pub fn tool_use(self, tool: impl Into<String>, input: serde_json::Value) -> Self {
+        self.message(StreamMessage::ToolUse {
+            tool: tool.into(),
+            input,
+        })
+    }
+
+    /// Add a tool_result event step
+    pub fn tool_result(self, output: impl Into<String>, is_error: bool) -> Self {
+        self.message(StreamMessage::ToolResult {
+            output: output.into(),
+            is_error,
+        })
+    }
+
+    /// Add a raw stdout line step
+    pub fn raw(mut self, line: impl Into<String>) -> Self {
+        self.steps.push(MockStep::Raw(line.into()));
+        self
+    }
+
+    /// Add a stderr line step
+    pub fn stderr(mut self, line: impl Into<String>) -> Self {
+        self.steps.push(MockStep::Stderr(line.into()));
+        self
+    }
+
+    /// Add a delay step
+    pub fn delay(mut self, delay: Duration) -> Self {
+        self.steps.push(MockStep::DelayMs(delay.as_millis() as u64));
+        self
+    }
+
+    /// Add a file write step
+    pub fn write_file(mut self, path: impl Into<String>, content: impl Into<String>) -> Self {
+        self.steps.push(MockStep::WriteFile {
+            path: path.into(),
+            content: content.into(),
+        });
+        self
+    }
+
+    /// Add a file delete step
+    pub fn delete_file(mut self, path: impl Into<String>) -> Self {
+        self.steps.push(MockStep::DeleteFile { path: path.into() });
+        self
+    }
+
+    /// Set the exit code
+    pub fn with_exit_code(mut self, exit_code: i32) -> Self {
+        self.exit_code = exit_code;
+        self
+    }
+
+    /// Render the script as a POSIX shell script
+    fn to_shell(&self) -> Result<String> {
+        let mut script = String::from("rm -f -- \"$0\"\n");
+
+        for step in &self.steps {
+            match step {
+                MockStep::Message(message) => {
+                    let line = serde_json::to_string(message)?;
+                    script.push_str(&format!("printf '%s\\n' {}\n", shell_quote(&line)));
+                }
+                MockStep::Raw(line) => {
+                    script.push_str(&format!("printf '%s\\n' {}\n", shell_quote(line)));
+                }
+                MockStep::Stderr(line) => {
+                    script.push_str(&format!("printf '%s\\n' {} >&2\n", shell_quote(line)));
+                }
+                MockStep::DelayMs(ms) => {
+                    script.push_str(&format!("sleep {}.{:03}\n", ms / 1000, ms % 1000));
+                }
+                MockStep::WriteFile { path, content } => {
+                    let quoted = shell_quote(path);
+                    script.push_str(&format!("mkdir -p -- \"$(dirname -- {})\"\n", quoted));
+                    script.push_str(&format!(
+                        "printf '%s' {} > {}\n",
+                        shell_quote(content),
+                        quoted
+                    ));
+                }
+                MockStep::DeleteFile { path } => {
+                    script.push_str(&format!("rm -f -- {}\n", shell_quote(path)));
+                }
+            }
+        }
+
+        script.push_str(&format!("exit {}\n", self.exit_code));
+        Ok(script)
+    }
+}
+
+/// Quote a string for safe inclusion in a POSIX shell script
+fn shell_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', "'\\''"))
+}
+
+/// Mock backend that replays a scripted agent run
+#[derive(Debug, Clone, Default)]
+pub struct MockBackend {
+    script_path: Option<PathBuf>,
+    script: Option<MockScript>,
+}
+
+impl MockBackend {
+    /// Create a new mock backend that replays the default script
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a mock backend that replays a fixture file
+    pub fn with_script_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.script_path = Some(path.into());
+        self
+    }
+
+    /// Create a mock backend that replays an in-memory script
+    pub fn with_script(mut self, script: MockScript) -> Self {
+        self.script = Some(script);
+        self
+    }
+
+    /// Resolve the script to replay
+    fn resolve_script(&self) -> Result<MockScript> {
+        if let Some(ref script) = self.script {
+            return Ok(script.clone());
+        }
+        match self.script_path {
+            Some(ref path) => MockScript::load(path),
+            None => Ok(MockScript::default_script()),
+        }
+    }
+}
+
+#[async_trait]
+impl Backend for MockBackend {
+    fn name(&self) -> &'static str {
+        "mock"
+    }
+
+    fn build_command(&self, workdir: &Path) -> Command {
+        let mut cmd = Command::new("sh");
+        cmd.current_dir(workdir)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        cmd
+    }
+
+    async fn spawn(
+        &self,
+        prompt: &str,
+        workdir: &Path,
+        options: &SpawnOptions,
+    ) -> Result<AgentHandle> {
+        let workdir_str = validate_workdir(workdir)?;
+
+        let script = self.resolve_script()?.to_shell()?;
+        let script_path = std::env::temp_dir().join(format!(
+            "murmur-mock-{}-{}.sh",
+            std::process::id(),
+            SCRIPT_COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        tokio::fs::write(&script_path, script).await?;
+
+        let mut cmd = self.build_command(workdir);
+        for (key, value) in &options.env_vars {
+            cmd.env(key, value);
+        }
+        cmd.arg(&script_path);
+        own_process_group(&mut cmd);
+
+        let child = cmd.spawn().map_err(|e| {
+            let _ = std::fs::remove_file(&script_path);
+            Error::Agent(format!("Failed to spawn mock agent: {}", e))
+        })?;
+
+        Ok(AgentHandle::new(child, prompt.to_string(), workdir_str))
+    }
+
+    fn is_available(&self) -> bool {
+        true
+    }
+
+    fn can_resume(&self, _session_id: &str, _workdir: &Path) -> bool {
+        // Replays are stateless, so any session can be "resumed"
+        true
+    }
+}
+
+/// Mock-backed spawners shared by the workflow tests
+#[cfg(test)]
+pub(crate) mod fixtures {
+    use super::MockScript;
+    use crate::agent::{AgentSpawner, AgentType};
+    use crate::config::{AgentConfig, Backend};
+    use std::path::Path;
+
+    /// Agent config that runs the mock backend
+    pub(crate) fn mock_config() -> AgentConfig {
+        AgentConfig {
+            backend: Backend::Mock,
+            ..Default::default()
+        }
+    }
+
+    /// Spawner that replays `script`, saved as `<name>.json` in `dir`
+    pub(crate) fn spawner(dir: &Path, name: &str, script: MockScript) -> AgentSpawner {
+        let path = dir.join(format!("{}.json", name));
+        std::fs::write(&path, serde_json::to_string(&script).unwrap()).unwrap();
+        AgentSpawner::from_config(mock_config(), AgentType::Implement)
+            .with_executable_path(path.to_string_lossy())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::output::{CostInfo, OutputStreamer, StreamHandler};
+    use tempfile::TempDir;
+
+    #[derive(Default)]
+    struct CollectingHandler {
+        session_id: Option<String>,
+        text: String,
+        tools: Vec<String>,
+        tool_results: Vec<(String, bool)>,
+        completed: bool,
+        parse_errors: usize,
+    }
+
+    impl StreamHandler for CollectingHandler {
+        fn on_system(&mut self, _subtype: Option<&str>, session_id: Option<&str>) {
+            self.session_id = session_id.map(String::from);
+        }
+
+        fn on_assistant_text(&mut self, text: &str) {
+            self.text.push_str(text);
+        }
+
+        fn on_tool_use(&mut self, tool: &str, _input: &serde_json::Value) {
+            self.tools.push(tool.to_string());
+        }
+
+        fn on_tool_result(&mut self, output: &str, is_error: bool) {
+            self.tool_results.push((output.to_string(), is_error));
+        }
+
+        fn on_complete(&mut self, _cost: Option<&CostInfo>, _duration_ms: Option<u64>) {
+            self.completed = true;
+        }
+
+        fn on_parse_error(&mut self, _line: &str, _error: &serde_json::Error) {
+            self.parse_errors += 1;
+        }
+    }
+
+    async fn run(backend: &MockBackend, workdir: &Path) -> (CollectingHandler, i32) {
+        let mut handle = backend
+            .spawn("do the thing", workdir, &SpawnOptions::new())
+            .await
+            .unwrap();
+        let stdout = handle.child_mut().stdout.take().unwrap();
+        let mut handler = CollectingHandler::default();
+        OutputStreamer::new(stdout)
+            .stream(&mut handler)
+            .await
+            .unwrap();
+        let status = handle.wait().await.unwrap();
+        (handler, status.code().unwrap())
+    }
+
+    #[test]
+    fn test_mock_backend_name() {
+        assert_eq!(MockBackend::new().name(), "mock");
+        assert!(MockBackend::new().is_available());
+    }
+
+    #[test]
+    fn test_shell_quote() {
+        assert_eq!(shell_quote("abc"), "'abc'");
+        assert_eq!(shell_quote("it's"), "'it'\\''s'");
+    }
+
+    #[test]
+    fn test_parse_toml_fixture() {
+        let toml = r#"
+exit_code = 2
+
+[[steps]]
+message = { type = "system", subtype = "init", session_id = "abc" }
+
+[[steps]]
+delay_ms = 10
+
+[[steps]]
+write_file = { path = "src/lib.rs", content = "fn main() {}" }
+
+[[steps]]
+message = { type = "tool_result", output = "ok" }
+"#;
+        let script: MockScript = toml::from_str(toml).unwrap();
+        assert_eq!(script.exit_code, 2);
+        assert_eq!(script.steps.len(), 4);
+        assert!(matches!(script.steps[1], MockStep::DelayMs(10)));
+        assert!(matches!(script.steps[2], MockStep::WriteFile { .. }));
+    }
+
+    #[test]
+    fn test_load_jsonl_fixture() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("run.jsonl");
+        std::fs::write(&path, "{\"type\":\"system\"}\n\n{\"type\":\"result\"}\n").unwrap();
+
+        let script = MockScript::load(&path).unwrap();
+        assert_eq!(script.steps.len(), 2);
+        assert_eq!(script.exit_code, 0);
+    }
+
+    #[tokio::test]
+    async fn test_default_script() {
+        let dir = TempDir::new().unwrap();
+        let (handler, code) = run(&MockBackend::new(), dir.path()).await;
+
+        assert_eq!(code, 0);
+        assert_eq!(handler.session_id.as_deref(), Some("mock-session"));
+        assert_eq!(handler.text, "Mock agent completed the task.");
+        assert!(handler.completed);
+    }
+
+    #[tokio::test]
+    async fn test_replay_script() {
+        let dir = TempDir::new().unwrap();
+        let script = MockScript::new()
+            .tool_use("Write", serde_json::json!({"file_path": "nested/out.txt"}))
+            .write_file("nested/out.txt", "it's done\n")
+            .tool_result("wrote file", false)
+            .delay(Duration::from_millis(10))
+            .raw("not json")
+            .stderr("some warning")
+            .assistant_text("Done")
+            .with_exit_code(3);
+        let backend = MockBackend::new().with_script(script);
+
+        let (handler, code) = run(&backend, dir.path()).await;
+
+        assert_eq!(code, 3);
+        assert_eq!(handler.tools, vec!["Write"]);
+        assert_eq!(
+            handler.tool_results,
+            vec![("wrote file".to_string(), false)]
+        );
+        assert_eq!(handler.parse_errors, 1);
+        assert_eq!(handler.text, "Done");
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("nested/out.txt")).unwrap(),
+            "it's done\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_replay_fixture_file() {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(dir.path().join("stale.txt"), "old").unwrap();
+        let fixture = dir.path().join("fixture.json");
+        let script = MockScript::new().delete_file("stale.txt").with_exit_code(1);
+        std::fs::write(&fixture, serde_json::to_string(&script).unwrap()).unwrap();
+
+        let backend = MockBackend::new().with_script_path(&fixture);
+        let (_, code) = run(&backend, dir.path()).await;
+
+        assert_eq!(code, 1);
+        assert!(!dir.path().join("stale.txt").exists());
+    }
+
+    #[tokio::test]
+    async fn test_missing_fixture() {
+        let dir = TempDir::new().unwrap();
+        let backend = MockBackend::new().with_script_path("/nonexistent/fixture.toml");
+        assert!(backend
+            .spawn("test", dir.path(), &SpawnOptions::new())
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_mock_spawn_invalid_workdir() {
+        let backend = MockBackend::new();
+        let result = backend
+            .spawn("test", Path::new("/nonexistent/path"), &SpawnOptions::new())
+            .await;
+        assert!(result.is_err());
+    }
+}
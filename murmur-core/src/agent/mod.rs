@@ -1,18 +1,47 @@
 //! Agent module for spawning and managing Claude Code processes
 
 mod backend;
+mod budget;
+mod context;
+mod mock;
 mod output;
+mod pricing;
 mod prompts;
+mod sandbox;
 mod selection;
 mod spawn;
 mod typed;
 mod types;
+mod watchdog;
 
-pub use backend::{Backend, BackendRegistry, ClaudeBackend, CursorBackend};
-pub use output::{CostInfo, OutputStreamer, PrintHandler, StreamHandler, StreamMessage};
-pub use prompts::{get_template, render, PromptBuilder, PromptContext};
+pub use backend::{Backend, BackendRegistry, ClaudeBackend, CursorBackend, SpawnOptions};
+pub use budget::{
+    Budget, BudgetExceeded, BudgetHandler, BudgetScope, BudgetTracker, SharedUsage, Usage,
+};
+pub use context::{
+    estimate_tokens, ContextCollector, ContextConfig, ContextSection, RepositoryContext,
+};
+#[cfg(test)]
+pub(crate) use mock::fixtures;
+pub use mock::{MockBackend, MockScript, MockStep};
+pub use output::{
+    last_json_block, CostInfo, OutputStreamer, PrintHandler, StreamHandler, StreamMessage,
+};
+pub use pricing::{ModelPrice, PricingTable};
+pub use prompts::{
+    get_template, render, PromptBuilder, PromptContext, PromptTemplates, TemplateLayer,
+    TemplateSource, BASE_TEMPLATE_PLACEHOLDER,
+};
+pub use sandbox::{
+    run_egress_bridge, EgressProxy, NetworkPolicy, Sandbox, SandboxConfig, SandboxMode,
+    EGRESS_BRIDGE_COMMAND,
+};
+pub(crate) use spawn::own_process_group;
+#[cfg(unix)]
+pub(crate) use spawn::signal_process_group;
 pub use spawn::{AgentHandle, AgentSpawner};
 pub use typed::{
     AgentFactory, CoordinatorAgent, ImplementAgent, ReviewAgent, TestAgent, TypedAgent,
 };
 pub use types::AgentType;
+pub use watchdog::{StopReason, Watchdog, WatchdogConfig};
@@ -1,5 +1,7 @@
 //! Output streaming and parsing for Claude Code JSON stream format
 
+use std::collections::HashSet;
+
 use serde::{Deserialize, Serialize};
 use tokio::io::{AsyncBufReadExt, BufReader};
 use tokio::process::ChildStdout;
@@ -53,6 +55,12 @@ pub enum StreamMessage {
         duration_ms: Option<u64>,
         #[serde(default)]
         duration_api_ms: Option<u64>,
+        /// Cumulative token usage (Claude Code reports this instead of `cost`)
+        #[serde(default)]
+        usage: Option<CostInfo>,
+        /// Total cost in dollars as reported by the backend
+        #[serde(default)]
+        total_cost_usd: Option<f64>,
     },
 }
 
@@ -74,8 +82,14 @@ pub enum ContentBlock {
 /// Assistant message content
 #[derive(Debug, Clone, Default, Deserialize, Serialize)]
 pub struct AssistantMessage {
+    /// API message ID, shared by the events of one message's content blocks
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
     #[serde(default)]
     pub content: Vec<ContentBlock>,
+    /// Token usage for the API call that produced this message
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub usage: Option<CostInfo>,
 }
 
 impl AssistantMessage {
@@ -99,10 +113,13 @@ pub struct CostInfo {
     pub input_tokens: u64,
     #[serde(default)]
     pub output_tokens: u64,
-    #[serde(default)]
+    #[serde(default, alias = "cache_read_input_tokens")]
     pub cache_read_tokens: Option<u64>,
-    #[serde(default)]
+    #[serde(default, alias = "cache_creation_input_tokens")]
     pub cache_write_tokens: Option<u64>,
+    /// Dollar cost reported by the backend, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cost_usd: Option<f64>,
 }
 
 /// Handler for processing stream messages
@@ -122,11 +139,21 @@ pub trait StreamHandler: Send {
     /// Called when a tool returns a result
     fn on_tool_result(&mut self, _output: &str, _is_error: bool) {}
 
+    /// Called with the token usage of each assistant turn while the agent runs
+    ///
+    /// A turn split into several events (one per content block) is reported once.
+    fn on_usage(&mut self, _usage: &CostInfo) {}
+
     /// Called when the stream completes
     fn on_complete(&mut self, _cost: Option<&CostInfo>, _duration_ms: Option<u64>) {}
 
     /// Called when a parse error occurs (allows handler to skip malformed lines)
     fn on_parse_error(&mut self, _line: &str, _error: &serde_json::Error) {}
+
+    /// Whether streaming should stop early (e.g. because a budget was exceeded)
+    fn should_stop(&self) -> bool {
+        false
+    }
 }
 
 /// Simple handler that prints assistant output to stdout
@@ -196,6 +223,8 @@ impl StreamHandler for PrintHandler {
 /// Stream output from a Claude Code process
 pub struct OutputStreamer {
     reader: BufReader<ChildStdout>,
+    /// IDs of the assistant messages whose usage was already reported
+    counted_messages: HashSet<String>,
 }
 
 impl OutputStreamer {
@@ -203,12 +232,14 @@ impl OutputStreamer {
     pub fn new(stdout: ChildStdout) -> Self {
         Self {
             reader: BufReader::new(stdout),
+            counted_messages: HashSet::new(),
         }
     }
 
     /// Stream output, calling the handler for each message
     ///
-    /// Returns when the stream ends (process closes stdout)
+    /// Returns when the stream ends (process closes stdout) or as soon as the
+    /// handler asks to stop
     pub async fn stream<H: StreamHandler>(&mut self, handler: &mut H) -> Result<()> {
         let mut line = String::new();
 
@@ -227,47 +258,90 @@ impl OutputStreamer {
             }
 
             match serde_json::from_str::<StreamMessage>(trimmed) {
-                Ok(msg) => Self::dispatch_message(handler, msg),
+                Ok(msg) => dispatch_message(handler, msg, &mut self.counted_messages),
                 Err(e) => handler.on_parse_error(trimmed, &e),
             }
+
+            if handler.should_stop() {
+                break;
+            }
         }
 
         Ok(())
     }
+}
 
-    fn dispatch_message<H: StreamHandler>(handler: &mut H, msg: StreamMessage) {
-        match msg {
-            StreamMessage::System {
-                subtype,
-                session_id,
-            } => {
-                handler.on_system(subtype.as_deref(), session_id.as_deref());
-            }
-            StreamMessage::User { message } => {
-                handler.on_user(&message);
-            }
-            StreamMessage::Assistant { message } => {
-                handler.on_assistant_text(&message.text());
-            }
-            StreamMessage::ToolUse { tool, input } => {
-                handler.on_tool_use(&tool, &input);
-            }
-            StreamMessage::ToolResult { output, is_error } => {
-                handler.on_tool_result(&output, is_error);
+/// Pass a message to the handler, reporting each assistant message's usage
+/// only the first time its ID is seen in `counted_messages`
+fn dispatch_message<H: StreamHandler>(
+    handler: &mut H,
+    msg: StreamMessage,
+    counted_messages: &mut HashSet<String>,
+) {
+    match msg {
+        StreamMessage::System {
+            subtype,
+            session_id,
+        } => {
+            handler.on_system(subtype.as_deref(), session_id.as_deref());
+        }
+        StreamMessage::User { message } => {
+            handler.on_user(&message);
+        }
+        StreamMessage::Assistant { message } => {
+            handler.on_assistant_text(&message.text());
+            if let Some(ref usage) = message.usage {
+                // Every content block's event carries the whole message's usage
+                let counted = message.id.is_some_and(|id| !counted_messages.insert(id));
+                if !counted {
+                    handler.on_usage(usage);
+                }
             }
-            StreamMessage::Result {
-                cost, duration_ms, ..
-            } => {
-                handler.on_complete(cost.as_ref(), duration_ms);
+        }
+        StreamMessage::ToolUse { tool, input } => {
+            handler.on_tool_use(&tool, &input);
+        }
+        StreamMessage::ToolResult { output, is_error } => {
+            handler.on_tool_result(&output, is_error);
+        }
+        StreamMessage::Result {
+            cost,
+            duration_ms,
+            usage,
+            total_cost_usd,
+            ..
+        } => {
+            let mut cost = cost.or(usage);
+            if let Some(total) = total_cost_usd {
+                cost.get_or_insert_with(CostInfo::default).cost_usd = Some(total);
             }
+            handler.on_complete(cost.as_ref(), duration_ms);
         }
     }
 }
 
+/// The contents of the last ```json fenced block in an agent's output
+///
+/// A block left unclosed, as when the output is cut off, runs to the end.
+pub fn last_json_block(output: &str) -> Option<&str> {
+    let start = output.rfind("```json")? + "```json".len();
+    let rest = &output[start..];
+    let json = rest.find("```").map_or(rest, |end| &rest[..end]);
+    Some(json.trim())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_last_json_block() {
+        let output = "```json\n{\"a\": 1}\n```\nThen:\n```json\n{\"b\": 2}\n```\n";
+        assert_eq!(last_json_block(output), Some("{\"b\": 2}"));
+        assert_eq!(last_json_block("```json\n{\"c\": 3}"), Some("{\"c\": 3}"));
+        assert_eq!(last_json_block("{\"d\": 4}"), None);
+    }
+
     #[test]
     fn test_parse_assistant_message_new_format() {
         // New Claude Code format with content as array
@@ -339,6 +413,64 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_parse_claude_usage() {
+        let json = r#"{"type":"assistant","message":{"content":[],"usage":{"input_tokens":10,"output_tokens":5,"cache_read_input_tokens":100,"cache_creation_input_tokens":20}}}"#;
+        let msg: StreamMessage = serde_json::from_str(json).unwrap();
+        match msg {
+            StreamMessage::Assistant { message } => {
+                let usage = message.usage.unwrap();
+                assert_eq!(usage.input_tokens, 10);
+                assert_eq!(usage.cache_read_tokens, Some(100));
+                assert_eq!(usage.cache_write_tokens, Some(20));
+            }
+            _ => panic!("Expected Assistant message"),
+        }
+
+        let json = r#"{"type":"result","total_cost_usd":0.25,"usage":{"input_tokens":7,"output_tokens":3}}"#;
+        let msg: StreamMessage = serde_json::from_str(json).unwrap();
+        match msg {
+            StreamMessage::Result {
+                usage,
+                total_cost_usd,
+                ..
+            } => {
+                assert_eq!(usage.unwrap().input_tokens, 7);
+                assert_eq!(total_cost_usd, Some(0.25));
+            }
+            _ => panic!("Expected Result message"),
+        }
+    }
+
+    #[test]
+    fn test_usage_reported_once_per_message() {
+        #[derive(Default)]
+        struct Usages(Vec<u64>);
+        impl StreamHandler for Usages {
+            fn on_assistant_text(&mut self, _text: &str) {}
+            fn on_usage(&mut self, usage: &CostInfo) {
+                self.0.push(usage.output_tokens);
+            }
+        }
+
+        let events = [
+            r#"{"type":"assistant","message":{"id":"msg_1","content":[{"type":"text","text":"Reading"}],"usage":{"input_tokens":10,"output_tokens":5}}}"#,
+            r#"{"type":"assistant","message":{"id":"msg_1","content":[{"type":"tool_use","id":"toolu_1","name":"Read","input":{}}],"usage":{"input_tokens":10,"output_tokens":5}}}"#,
+            r#"{"type":"assistant","message":{"id":"msg_2","content":[{"type":"text","text":"Done"}],"usage":{"input_tokens":20,"output_tokens":7}}}"#,
+            r#"{"type":"assistant","message":{"content":[],"usage":{"input_tokens":1,"output_tokens":1}}}"#,
+        ];
+        let mut handler = Usages::default();
+        let mut counted = HashSet::new();
+        for event in events {
+            dispatch_message(
+                &mut handler,
+                serde_json::from_str(event).unwrap(),
+                &mut counted,
+            );
+        }
+        assert_eq!(handler.0, [5, 7, 1]);
+    }
+
     #[test]
     fn test_parse_system() {
         let json = r#"{"type":"system","subtype":"init","session_id":"abc123"}"#;
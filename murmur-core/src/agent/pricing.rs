@@ -0,0 +1,244 @@
+//! Model pricing for turning token usage into dollar amounts
+//!
+//! Prices are expressed in dollars per million tokens. A [`PricingTable`] can be
+//! configured per model in `config.toml`; models without an entry fall back to
+//! built-in estimates for the Claude model families.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::output::CostInfo;
+
+/// Prices for one model, in dollars per million tokens
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ModelPrice {
+    /// Input (prompt) tokens
+    pub input: f64,
+
+    /// Output (completion) tokens
+    pub output: f64,
+
+    /// Prompt cache reads (defaults to 10% of the input price)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_read: Option<f64>,
+
+    /// Prompt cache writes (defaults to 125% of the input price)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_write: Option<f64>,
+}
+
+impl ModelPrice {
+    /// Create a price from input/output rates, using the usual cache rates
+    pub fn new(input: f64, output: f64) -> Self {
+        Self {
+            input,
+            output,
+            cache_read: None,
+            cache_write: None,
+        }
+    }
+
+    /// Set the cache read and write rates
+    pub fn with_cache(mut self, cache_read: f64, cache_write: f64) -> Self {
+        self.cache_read = Some(cache_read);
+        self.cache_write = Some(cache_write);
+        self
+    }
+
+    /// Built-in estimate for a model name (defaults to Sonnet pricing)
+    pub fn builtin(model: Option<&str>) -> Self {
+        let model = model.unwrap_or_default().to_lowercase();
+        if model.contains("opus") {
+            Self::new(15.0, 75.0)
+        } else if model.contains("haiku") {
+            Self::new(0.8, 4.0)
+        } else {
+            Self::new(3.0, 15.0)
+        }
+    }
+
+    /// Dollar cost of the given token counts
+    pub fn cost_usd(&self, info: &CostInfo) -> f64 {
+        let per_token = |count: u64, rate: f64| count as f64 * rate / 1_000_000.0;
+        per_token(info.input_tokens, self.input)
+            + per_token(info.output_tokens, self.output)
+            + per_token(
+                info.cache_read_tokens.unwrap_or(0),
+                self.cache_read.unwrap_or(self.input * 0.1),
+            )
+            + per_token(
+                info.cache_write_tokens.unwrap_or(0),
+                self.cache_write.unwrap_or(self.input * 1.25),
+            )
+    }
+}
+
+/// Per-model prices, keyed by model name
+///
+/// Lookup tries an exact match, then the longest key contained in the model
+/// name (so `haiku` matches `claude-haiku-4-20250514`), then a `default` entry,
+/// and finally the built-in estimates.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct PricingTable {
+    models: BTreeMap<String, ModelPrice>,
+}
+
+impl PricingTable {
+    /// Create an empty table (only built-in estimates)
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add or replace the price for a model
+    pub fn with_price(mut self, model: impl Into<String>, price: ModelPrice) -> Self {
+        self.models.insert(model.into(), price);
+        self
+    }
+
+    /// Whether no prices are configured
+    pub fn is_empty(&self) -> bool {
+        self.models.is_empty()
+    }
+
+    /// Configured prices, by model name
+    pub fn models(&self) -> &BTreeMap<String, ModelPrice> {
+        &self.models
+    }
+
+    /// Resolve the price for a model
+    pub fn price_for(&self, model: Option<&str>) -> ModelPrice {
+        self.configured_price(model)
+            .unwrap_or_else(|| ModelPrice::builtin(model))
+    }
+
+    /// The price configured for a model, ignoring the built-in estimates
+    pub fn configured_price(&self, model: Option<&str>) -> Option<ModelPrice> {
+        if let Some(name) = model {
+            let name = name.to_lowercase();
+            if let Some(price) = self
+                .models
+                .iter()
+                .find(|(key, _)| key.to_lowercase() == name)
+            {
+                return Some(*price.1);
+            }
+
+            if let Some(price) = self
+                .models
+                .iter()
+                .filter(|(key, _)| key.as_str() != "default" && name.contains(&key.to_lowercase()))
+                .max_by_key(|(key, _)| key.len())
+            {
+                return Some(*price.1);
+            }
+        }
+
+        self.models.get("default").copied()
+    }
+
+    /// Dollar cost of the given usage for a model
+    ///
+    /// A configured price wins over the cost the backend reported, which in
+    /// turn wins over the built-in estimate.
+    pub fn cost_usd(&self, model: Option<&str>, info: &CostInfo) -> f64 {
+        match self.configured_price(model) {
+            Some(price) => price.cost_usd(info),
+            None => info
+                .cost_usd
+                .unwrap_or_else(|| ModelPrice::builtin(model).cost_usd(info)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tokens(input: u64, output: u64, cache_read: u64, cache_write: u64) -> CostInfo {
+        CostInfo {
+            input_tokens: input,
+            output_tokens: output,
+            cache_read_tokens: Some(cache_read),
+            cache_write_tokens: Some(cache_write),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_builtin_prices() {
+        assert_eq!(ModelPrice::builtin(Some("claude-opus-4")).input, 15.0);
+        assert_eq!(ModelPrice::builtin(Some("haiku")).output, 4.0);
+        assert_eq!(ModelPrice::builtin(None), ModelPrice::new(3.0, 15.0));
+    }
+
+    #[test]
+    fn test_cost_includes_cache_rates() {
+        let price = ModelPrice::new(3.0, 15.0);
+        let cost = price.cost_usd(&tokens(1_000_000, 1_000_000, 1_000_000, 1_000_000));
+        // 3 + 15 + 0.3 + 3.75
+        assert!((cost - 22.05).abs() < 1e-9);
+
+        let price = price.with_cache(0.0, 0.0);
+        let cost = price.cost_usd(&tokens(1_000_000, 0, 1_000_000, 1_000_000));
+        assert!((cost - 3.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_table_lookup_order() {
+        let table = PricingTable::new()
+            .with_price("haiku", ModelPrice::new(1.0, 5.0))
+            .with_price("claude-haiku-4-custom", ModelPrice::new(0.5, 2.5));
+
+        assert_eq!(
+            table.price_for(Some("claude-haiku-4-custom")),
+            ModelPrice::new(0.5, 2.5)
+        );
+        assert_eq!(
+            table.price_for(Some("claude-haiku-4-20250514")),
+            ModelPrice::new(1.0, 5.0)
+        );
+        // Unconfigured models fall back to built-in estimates
+        assert_eq!(table.price_for(Some("opus")), ModelPrice::new(15.0, 75.0));
+
+        assert_eq!(table.configured_price(Some("opus")), None);
+
+        let table = table.with_price("default", ModelPrice::new(2.0, 10.0));
+        assert_eq!(table.price_for(None), ModelPrice::new(2.0, 10.0));
+        assert_eq!(table.price_for(Some("gpt-5")), ModelPrice::new(2.0, 10.0));
+    }
+
+    #[test]
+    fn test_configured_price_overrides_reported_cost() {
+        let info = CostInfo {
+            cost_usd: Some(9.0),
+            ..tokens(1_000_000, 0, 0, 0)
+        };
+        let table = PricingTable::new().with_price("haiku", ModelPrice::new(1.0, 5.0));
+
+        assert_eq!(table.cost_usd(Some("claude-haiku-4"), &info), 1.0);
+        // Unconfigured models keep the reported cost
+        assert_eq!(table.cost_usd(Some("claude-opus-4"), &info), 9.0);
+        // and are estimated when there is none
+        let unreported = tokens(1_000_000, 0, 0, 0);
+        assert_eq!(table.cost_usd(Some("claude-opus-4"), &unreported), 15.0);
+    }
+
+    #[test]
+    fn test_table_from_toml() {
+        let table: PricingTable = toml::from_str(
+            r#"
+"claude-sonnet-4" = { input = 3.0, output = 15.0, cache_read = 0.3 }
+haiku = { input = 0.8, output = 4.0 }
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(table.models().len(), 2);
+        assert_eq!(
+            table.price_for(Some("claude-sonnet-4")).cache_read,
+            Some(0.3)
+        );
+    }
+}
@@ -1,10 +1,23 @@
 //! Agent prompt templates
 //!
 //! This module provides embedded prompt templates for different agent types.
-//! Templates use `{{VARIABLE}}` placeholders that can be rendered with context.
-
-use crate::agent::AgentType;
-use std::collections::HashMap;
+//! Templates are [minijinja](https://docs.rs/minijinja) (Jinja2) templates:
+//! `{{VARIABLE}}` substitutes a value, `{% if %}`/`{% for %}` omit or repeat
+//! sections, and `{% include "partials/<name>.md" %}` pulls in a shared
+//! fragment. Unset variables render as empty unless strict mode is on.
+//!
+//! The built-in templates can be overridden per user (`~/.config/murmur/prompts`)
+//! and per repository (`.murmur/prompts`) with files named after the agent type,
+//! e.g. `.murmur/prompts/review.md`. An override that contains
+//! `{{BASE_TEMPLATE}}` extends the template beneath it instead of replacing it.
+
+use crate::agent::{AgentType, RepositoryContext};
+use crate::{Error, Result};
+use minijinja::{Environment, UndefinedBehavior};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
 
 /// Embedded prompt templates for each agent type
 const IMPLEMENT_PROMPT: &str = include_str!("prompts/implement.md");
@@ -12,6 +25,36 @@ const TEST_PROMPT: &str = include_str!("prompts/test.md");
 const REVIEW_PROMPT: &str = include_str!("prompts/review.md");
 const COORDINATOR_PROMPT: &str = include_str!("prompts/coordinator.md");
 
+/// Embedded fragments available to `{% include %}`, by name
+const BUILTIN_PARTIALS: &[(&str, &str)] = &[
+    (
+        "partials/file_list.md",
+        include_str!("prompts/partials/file_list.md"),
+    ),
+    (
+        "partials/repository_context.md",
+        include_str!("prompts/partials/repository_context.md"),
+    ),
+    (
+        "partials/conventions.md",
+        include_str!("prompts/partials/conventions.md"),
+    ),
+];
+
+/// String variables every template can use, empty unless set
+const STANDARD_VARIABLES: &[&str] = &[
+    "TASK_DESCRIPTION",
+    "FILES",
+    "DEPENDENCIES",
+    "DIFF",
+    "REPO",
+    "MAIN_BRANCH",
+    "REPOSITORY_CONTEXT",
+];
+
+/// List variables every template can loop over, empty unless set
+const STANDARD_LISTS: &[&str] = &["files", "dependencies"];
+
 /// Get the raw prompt template for an agent type
 pub fn get_template(agent_type: AgentType) -> &'static str {
     match agent_type {
@@ -22,11 +65,234 @@ pub fn get_template(agent_type: AgentType) -> &'static str {
     }
 }
 
+/// Placeholder in an override that expands to the template beneath it
+pub const BASE_TEMPLATE_PLACEHOLDER: &str = "{{BASE_TEMPLATE}}";
+
+/// Where a prompt template layer comes from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TemplateSource {
+    /// Compiled into the binary
+    Builtin,
+    /// The user's prompt directory
+    User(PathBuf),
+    /// The repository's `.murmur/prompts` directory
+    Repository(PathBuf),
+}
+
+impl fmt::Display for TemplateSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TemplateSource::Builtin => write!(f, "built-in"),
+            TemplateSource::User(path) => write!(f, "user ({})", path.display()),
+            TemplateSource::Repository(path) => write!(f, "repository ({})", path.display()),
+        }
+    }
+}
+
+/// One layer of a prompt template
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TemplateLayer {
+    /// Where this layer was loaded from
+    pub source: TemplateSource,
+    /// The raw template text
+    pub content: String,
+}
+
+impl TemplateLayer {
+    /// Whether this layer extends the one beneath it
+    pub fn extends_base(&self) -> bool {
+        self.content.contains(BASE_TEMPLATE_PLACEHOLDER)
+    }
+}
+
+/// Prompt templates with user and repository overrides
+///
+/// Layers are applied built-in → user → repository. Each override replaces
+/// the layer beneath it unless it contains `{{BASE_TEMPLATE}}`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PromptTemplates {
+    user_dir: Option<PathBuf>,
+    repo_dir: Option<PathBuf>,
+}
+
+impl PromptTemplates {
+    /// Only the built-in templates
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Templates for a repository, with the user's overrides
+    pub fn discover(repo_root: impl AsRef<Path>) -> Self {
+        let mut templates = Self::new().with_repo_dir(Self::repo_dir_for(repo_root));
+        if let Some(dir) = Self::default_user_dir() {
+            templates = templates.with_user_dir(dir);
+        }
+        templates
+    }
+
+    /// Set the user-level override directory
+    pub fn with_user_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.user_dir = Some(dir.into());
+        self
+    }
+
+    /// Set the repository-level override directory
+    pub fn with_repo_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.repo_dir = Some(dir.into());
+        self
+    }
+
+    /// Get the user-level override directory
+    ///
+    /// Returns `~/.config/murmur/prompts` on Unix
+    pub fn default_user_dir() -> Option<PathBuf> {
+        dirs::config_dir().map(|p| p.join("murmur").join("prompts"))
+    }
+
+    /// Get the override directory inside a repository
+    pub fn repo_dir_for(repo_root: impl AsRef<Path>) -> PathBuf {
+        repo_root.as_ref().join(".murmur").join("prompts")
+    }
+
+    /// Path of the override file for an agent type in a directory
+    pub fn template_path(dir: &Path, agent_type: AgentType) -> PathBuf {
+        dir.join(format!("{}.md", agent_type.name()))
+    }
+
+    /// All layers for an agent type, lowest first
+    pub fn layers(&self, agent_type: AgentType) -> Result<Vec<TemplateLayer>> {
+        let mut layers = vec![TemplateLayer {
+            source: TemplateSource::Builtin,
+            content: get_template(agent_type).to_string(),
+        }];
+
+        if let Some((path, content)) = read_override(self.user_dir.as_deref(), agent_type)? {
+            layers.push(TemplateLayer {
+                source: TemplateSource::User(path),
+                content,
+            });
+        }
+        if let Some((path, content)) = read_override(self.repo_dir.as_deref(), agent_type)? {
+            layers.push(TemplateLayer {
+                source: TemplateSource::Repository(path),
+                content,
+            });
+        }
+
+        Ok(layers)
+    }
+
+    /// The effective template for an agent type
+    pub fn resolve(&self, agent_type: AgentType) -> Result<String> {
+        let layers = self.layers(agent_type)?;
+        Ok(layers.into_iter().fold(String::new(), |base, layer| {
+            layer
+                .content
+                .replace(BASE_TEMPLATE_PLACEHOLDER, base.trim_end())
+        }))
+    }
+
+    /// Fragments available to `{% include %}`, by name
+    ///
+    /// Files in `partials/` under the user and repository directories replace
+    /// built-in fragments of the same name or add new ones.
+    pub fn partials(&self) -> Result<BTreeMap<String, String>> {
+        let mut partials: BTreeMap<String, String> = BUILTIN_PARTIALS
+            .iter()
+            .map(|(name, source)| (name.to_string(), source.to_string()))
+            .collect();
+
+        for dir in [&self.user_dir, &self.repo_dir].into_iter().flatten() {
+            let entries = match std::fs::read_dir(dir.join("partials")) {
+                Ok(entries) => entries,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(e) => return Err(Error::Io(e)),
+            };
+            for entry in entries {
+                let path = entry?.path();
+                if !path.is_file() {
+                    continue;
+                }
+                if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
+                    let name = format!("partials/{}", name);
+                    partials.insert(name, std::fs::read_to_string(&path)?);
+                }
+            }
+        }
+
+        Ok(partials)
+    }
+
+    /// Render the effective template for an agent type
+    pub fn render(
+        &self,
+        agent_type: AgentType,
+        context: &PromptContext,
+        strict: bool,
+    ) -> Result<String> {
+        let template = self.resolve(agent_type)?;
+        render_template(&template, context, &self.partials()?, strict)
+    }
+
+    /// Check that the effective template renders in strict mode
+    ///
+    /// Every standard variable is given a sample value, so this catches syntax
+    /// errors, missing fragments and references to unknown variables.
+    pub fn check(&self, agent_type: AgentType) -> Result<()> {
+        let sample = vec!["sample".to_string()];
+        let context = PromptContext::new()
+            .with_task("sample task")
+            .with_files(&sample)
+            .with_dependencies(&sample)
+            .with_diff("sample diff")
+            .with_repo("owner/repo")
+            .with_main_branch("main")
+            .with("REPOSITORY_CONTEXT", "sample context");
+        self.render(agent_type, &context, true).map(|_| ())
+    }
+
+    /// Unified diff from the built-in template to the effective one
+    ///
+    /// Returns an empty string when no override applies.
+    pub fn diff(&self, agent_type: AgentType) -> Result<String> {
+        let effective = self.resolve(agent_type)?;
+        let builtin = get_template(agent_type);
+        if effective == builtin {
+            return Ok(String::new());
+        }
+
+        Ok(similar::TextDiff::from_lines(builtin, effective.as_str())
+            .unified_diff()
+            .header(
+                &format!("built-in/{}.md", agent_type.name()),
+                &format!("effective/{}.md", agent_type.name()),
+            )
+            .to_string())
+    }
+}
+
+/// Read the override for an agent type from a directory, if present
+fn read_override(dir: Option<&Path>, agent_type: AgentType) -> Result<Option<(PathBuf, String)>> {
+    let Some(dir) = dir else {
+        return Ok(None);
+    };
+    let path = PromptTemplates::template_path(dir, agent_type);
+    match std::fs::read_to_string(&path) {
+        Ok(content) => Ok(Some((path, content))),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(Error::Config(format!(
+            "Failed to read prompt template {}: {}",
+            path.display(),
+            e
+        ))),
+    }
+}
+
 /// Context for rendering a prompt template
 #[derive(Debug, Clone, Default)]
 pub struct PromptContext {
-    /// Variable substitutions
-    variables: HashMap<String, String>,
+    /// Variable values (strings or lists of strings)
+    variables: BTreeMap<String, Value>,
 }
 
 impl PromptContext {
@@ -37,7 +303,15 @@ impl PromptContext {
 
     /// Set a variable value
     pub fn set(&mut self, key: impl Into<String>, value: impl Into<String>) -> &mut Self {
-        self.variables.insert(key.into(), value.into());
+        self.variables
+            .insert(key.into(), Value::String(value.into()));
+        self
+    }
+
+    /// Set a list variable for use in `{% for %}` loops
+    pub fn set_list(&mut self, key: impl Into<String>, items: &[String]) -> &mut Self {
+        let items = items.iter().cloned().map(Value::String).collect();
+        self.variables.insert(key.into(), Value::Array(items));
         self
     }
 
@@ -52,6 +326,12 @@ impl PromptContext {
         self.with("TASK_DESCRIPTION", description)
     }
 
+    /// Set a list variable (builder pattern)
+    pub fn with_list(mut self, key: impl Into<String>, items: &[String]) -> Self {
+        self.set_list(key, items);
+        self
+    }
+
     /// Set the files to work on
     pub fn with_files(self, files: &[String]) -> Self {
         let files_str = if files.is_empty() {
@@ -63,7 +343,7 @@ impl PromptContext {
                 .collect::<Vec<_>>()
                 .join("\n")
         };
-        self.with("FILES", files_str)
+        self.with("FILES", files_str).with_list("files", files)
     }
 
     /// Set the dependencies
@@ -77,6 +357,7 @@ impl PromptContext {
                 .join("\n")
         };
         self.with("DEPENDENCIES", deps_str)
+            .with_list("dependencies", deps)
     }
 
     /// Set the diff for review
@@ -93,50 +374,70 @@ impl PromptContext {
     pub fn with_main_branch(self, branch: impl Into<String>) -> Self {
         self.with("MAIN_BRANCH", branch)
     }
+
+    /// Set the collected repository context
+    pub fn with_repository_context(self, context: &RepositoryContext) -> Self {
+        self.with("REPOSITORY_CONTEXT", context.to_markdown().trim_end())
+    }
 }
 
-/// Render a prompt template with the given context
+/// Render a built-in prompt template with the given context
 pub fn render(agent_type: AgentType, context: &PromptContext) -> String {
-    let template = get_template(agent_type);
-    render_template(template, context)
+    let partials = PromptTemplates::new().partials().unwrap_or_default();
+    render_template(get_template(agent_type), context, &partials, false)
+        .expect("built-in prompt templates are valid")
 }
 
-/// Render a template string with variable substitution
-fn render_template(template: &str, context: &PromptContext) -> String {
-    let mut result = template.to_string();
+/// Render a template string
+///
+/// In strict mode, using a variable that is neither standard nor set in the
+/// context is an error; otherwise it renders as empty.
+fn render_template(
+    template: &str,
+    context: &PromptContext,
+    partials: &BTreeMap<String, String>,
+    strict: bool,
+) -> Result<String> {
+    let mut env = Environment::new();
+    env.set_trim_blocks(true);
+    env.set_lstrip_blocks(true);
+    env.set_keep_trailing_newline(true);
+    env.set_undefined_behavior(if strict {
+        UndefinedBehavior::Strict
+    } else {
+        UndefinedBehavior::Lenient
+    });
+    for (name, source) in partials {
+        env.add_template(name, source).map_err(template_error)?;
+    }
 
+    let mut variables: BTreeMap<&str, Value> = STANDARD_VARIABLES
+        .iter()
+        .map(|name| (*name, Value::String(String::new())))
+        .chain(
+            STANDARD_LISTS
+                .iter()
+                .map(|name| (*name, Value::Array(Vec::new()))),
+        )
+        .collect();
     for (key, value) in &context.variables {
-        let placeholder = format!("{{{{{}}}}}", key);
-        result = result.replace(&placeholder, value);
-    }
-
-    // Remove any remaining unset placeholders (simple pattern matching)
-    // Replace {{UPPERCASE_NAME}} with "(not specified)"
-    let mut pos = 0;
-    while let Some(start) = result[pos..].find("{{") {
-        let start = pos + start;
-        if let Some(end_offset) = result[start + 2..].find("}}") {
-            let end = start + 2 + end_offset;
-            let inside = &result[start + 2..end];
-            if inside.chars().all(|c| c.is_ascii_uppercase() || c == '_') {
-                let placeholder = &result[start..=end + 1];
-                result = result.replacen(placeholder, "(not specified)", 1);
-                pos = start; // Re-scan from same position since string changed
-            } else {
-                pos = end + 2; // Skip past this placeholder
-            }
-        } else {
-            break;
-        }
+        variables.insert(key, value.clone());
     }
 
-    result
+    env.render_named_str("prompt", template, variables)
+        .map_err(template_error)
+}
+
+fn template_error(e: minijinja::Error) -> Error {
+    Error::Template(e.to_string())
 }
 
 /// Build a complete prompt for an agent
 pub struct PromptBuilder {
     agent_type: AgentType,
     context: PromptContext,
+    templates: PromptTemplates,
+    strict: bool,
 }
 
 impl PromptBuilder {
@@ -145,9 +446,23 @@ impl PromptBuilder {
         Self {
             agent_type,
             context: PromptContext::new(),
+            templates: PromptTemplates::new(),
+            strict: false,
         }
     }
 
+    /// Use user and repository template overrides
+    pub fn templates(mut self, templates: PromptTemplates) -> Self {
+        self.templates = templates;
+        self
+    }
+
+    /// Fail on variables that aren't set instead of rendering them as empty
+    pub fn strict(mut self) -> Self {
+        self.strict = true;
+        self
+    }
+
     /// Set the task description
     pub fn task(mut self, description: impl Into<String>) -> Self {
         self.context = self.context.with_task(description);
@@ -184,21 +499,40 @@ impl PromptBuilder {
         self
     }
 
+    /// Set the collected repository context
+    pub fn repository_context(mut self, context: &RepositoryContext) -> Self {
+        self.context = self.context.with_repository_context(context);
+        self
+    }
+
     /// Set a custom variable
     pub fn var(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
         self.context = self.context.with(key, value);
         self
     }
 
+    /// Build the final prompt, failing if the template can't be loaded or rendered
+    pub fn try_build(&self) -> Result<String> {
+        self.templates
+            .render(self.agent_type, &self.context, self.strict)
+    }
+
     /// Build the final prompt
+    ///
+    /// Falls back to the built-in template if an override can't be loaded or
+    /// rendered.
     pub fn build(self) -> String {
-        render(self.agent_type, &self.context)
+        self.try_build().unwrap_or_else(|e| {
+            tracing::warn!(error = %e, "Using built-in prompt template");
+            render(self.agent_type, &self.context)
+        })
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::agent::ContextSection;
 
     #[test]
     fn test_get_template() {
@@ -222,7 +556,10 @@ mod tests {
     fn test_render_empty_context() {
         let context = PromptContext::new();
         let rendered = render(AgentType::Implement, &context);
-        assert!(rendered.contains("(not specified)"));
+        assert!(!rendered.contains("{{"));
+        assert!(!rendered.contains("{%"));
+        assert!(!rendered.contains("## Files to Focus On"));
+        assert!(!rendered.contains("## Dependencies"));
     }
 
     #[test]
@@ -277,7 +614,10 @@ mod tests {
         let context = PromptContext::new().with_task("Task").with_files(&[]);
 
         let rendered = render(AgentType::Implement, &context);
-        assert!(rendered.contains("(no specific files)"));
+        assert!(!rendered.contains("## Files to Focus On"));
+        // The legacy string variable is still available to overrides
+        let legacy = render_template("{{FILES}}", &context, &BTreeMap::new(), false).unwrap();
+        assert_eq!(legacy, "(no specific files)");
     }
 
     #[test]
@@ -287,28 +627,179 @@ mod tests {
             .with_dependencies(&[]);
 
         let rendered = render(AgentType::Implement, &context);
-        assert!(rendered.contains("(no dependencies)"));
+        assert!(!rendered.contains("## Dependencies"));
     }
 
     #[test]
-    fn test_mixed_case_placeholders() {
-        // Test that lowercase placeholders don't prevent processing uppercase ones
-        let template =
-            "{{lowercase}} ... {{UPPERCASE}} ... {{another_lower}} ... {{ANOTHER_UPPER}}";
-        let context = PromptContext::new();
-        let rendered = render_template(template, &context);
+    fn test_review_omits_missing_diff() {
+        let prompt = PromptBuilder::new(AgentType::Review)
+            .task("Review changes")
+            .build();
+        assert!(!prompt.contains("## Changes to Review"));
+        assert!(prompt.contains("## Original Task"));
+    }
 
-        // Lowercase/mixed-case placeholders should remain unchanged
-        assert!(rendered.contains("{{lowercase}}"));
-        assert!(rendered.contains("{{another_lower}}"));
+    #[test]
+    fn test_repository_context_section() {
+        let context = RepositoryContext {
+            sections: vec![ContextSection::new("CLAUDE.md", "Prefer small commits.")],
+            truncated: Vec::new(),
+        };
+        for agent_type in AgentType::all() {
+            let prompt = PromptBuilder::new(*agent_type)
+                .task("Do it")
+                .repository_context(&context)
+                .build();
+            assert!(prompt.contains(
+                "\n\n## Repository Context\n\n### CLAUDE.md\n\nPrefer small commits.\n\nBegin by"
+            ));
+        }
+
+        let prompt = PromptBuilder::new(AgentType::Implement)
+            .task("Do it")
+            .build();
+        assert!(!prompt.contains("## Repository Context"));
+    }
+
+    fn write_override(dir: &Path, agent_type: AgentType, content: &str) {
+        std::fs::create_dir_all(dir).unwrap();
+        std::fs::write(PromptTemplates::template_path(dir, agent_type), content).unwrap();
+    }
+
+    #[test]
+    fn test_templates_without_overrides() {
+        let dir = tempfile::tempdir().unwrap();
+        let templates = PromptTemplates::new().with_repo_dir(dir.path());
+
+        let layers = templates.layers(AgentType::Review).unwrap();
+        assert_eq!(layers.len(), 1);
+        assert_eq!(layers[0].source, TemplateSource::Builtin);
+        assert_eq!(
+            templates.resolve(AgentType::Review).unwrap(),
+            get_template(AgentType::Review)
+        );
+        assert_eq!(templates.diff(AgentType::Review).unwrap(), "");
+    }
+
+    #[test]
+    fn test_repo_override_replaces_builtin() {
+        let repo = tempfile::tempdir().unwrap();
+        let templates = PromptTemplates::discover(repo.path());
+        write_override(
+            &PromptTemplates::repo_dir_for(repo.path()),
+            AgentType::Implement,
+            "# Our Implement Agent\n\n{{TASK_DESCRIPTION}}\n",
+        );
+
+        let prompt = PromptBuilder::new(AgentType::Implement)
+            .templates(templates.clone())
+            .task("Add caching")
+            .build();
+        assert_eq!(prompt, "# Our Implement Agent\n\nAdd caching\n");
+
+        let diff = templates.diff(AgentType::Implement).unwrap();
+        assert!(diff.contains("+# Our Implement Agent"));
+        assert!(diff.contains("-# Implement Agent"));
+    }
 
-        // Uppercase placeholders should be replaced with "(not specified)"
-        assert!(rendered.contains("(not specified)"));
-        assert!(!rendered.contains("{{UPPERCASE}}"));
-        assert!(!rendered.contains("{{ANOTHER_UPPER}}"));
+    #[test]
+    fn test_overrides_extend_base_template() {
+        let user = tempfile::tempdir().unwrap();
+        let repo = tempfile::tempdir().unwrap();
+        write_override(
+            user.path(),
+            AgentType::Test,
+            "{{BASE_TEMPLATE}}\n\nUser rule.\n",
+        );
+        write_override(
+            repo.path(),
+            AgentType::Test,
+            "{{BASE_TEMPLATE}}\n\nRepo rule.\n",
+        );
+
+        let templates = PromptTemplates::new()
+            .with_user_dir(user.path())
+            .with_repo_dir(repo.path());
+        let layers = templates.layers(AgentType::Test).unwrap();
+        assert_eq!(layers.len(), 3);
+        assert!(matches!(layers[1].source, TemplateSource::User(_)));
+        assert!(matches!(layers[2].source, TemplateSource::Repository(_)));
+        assert!(layers[2].extends_base());
+
+        let resolved = templates.resolve(AgentType::Test).unwrap();
+        assert!(resolved.starts_with("# Test Agent"));
+        let user_pos = resolved.find("User rule.").unwrap();
+        let repo_pos = resolved.find("Repo rule.").unwrap();
+        assert!(user_pos < repo_pos);
+        assert!(!resolved.contains(BASE_TEMPLATE_PLACEHOLDER));
+    }
+
+    #[test]
+    fn test_loops_and_conditionals() {
+        let template = "{% if files %}Files:\n{% for f in files %}- {{f}}\n{% endfor %}{% endif %}";
+        let context = PromptContext::new().with_files(&["a.rs".to_string(), "b.rs".to_string()]);
+        let rendered = render_template(template, &context, &BTreeMap::new(), false).unwrap();
+        assert_eq!(rendered, "Files:\n- a.rs\n- b.rs\n");
+
+        let rendered =
+            render_template(template, &PromptContext::new(), &BTreeMap::new(), false).unwrap();
+        assert_eq!(rendered, "");
+    }
+
+    #[test]
+    fn test_strict_mode_rejects_unknown_variables() {
+        let context = PromptContext::new().with("custom", "value");
+        let partials = BTreeMap::new();
+
+        // Lenient: unknown variables render as empty
+        let rendered = render_template("[{{TYPO}}]", &context, &partials, false).unwrap();
+        assert_eq!(rendered, "[]");
+
+        // Strict: standard and custom variables are fine, unknown ones fail
+        let rendered = render_template("[{{DIFF}}{{custom}}]", &context, &partials, true).unwrap();
+        assert_eq!(rendered, "[value]");
+        let err = render_template("[{{TYPO}}]", &context, &partials, true).unwrap_err();
+        assert!(matches!(err, Error::Template(_)));
+    }
 
-        // Verify there are exactly 2 replacements (for the 2 uppercase placeholders)
-        let count = rendered.matches("(not specified)").count();
-        assert_eq!(count, 2, "Expected 2 uppercase placeholders to be replaced");
+    #[test]
+    fn test_includes_and_partial_overrides() {
+        let repo = tempfile::tempdir().unwrap();
+        let partials_dir = repo.path().join("partials");
+        std::fs::create_dir_all(&partials_dir).unwrap();
+        std::fs::write(
+            partials_dir.join("conventions.md"),
+            "\n## Project Conventions\n\nUse tabs.\n",
+        )
+        .unwrap();
+
+        let templates = PromptTemplates::new().with_repo_dir(repo.path());
+        assert!(templates
+            .partials()
+            .unwrap()
+            .contains_key("partials/file_list.md"));
+        for agent_type in AgentType::all() {
+            let prompt = PromptBuilder::new(*agent_type)
+                .templates(templates.clone())
+                .task("Task")
+                .try_build()
+                .unwrap();
+            assert!(prompt.contains("## Project Conventions"), "{}", agent_type);
+        }
+
+        write_override(
+            repo.path(),
+            AgentType::Test,
+            "{% include \"partials/missing.md\" %}",
+        );
+        assert!(templates.check(AgentType::Test).is_err());
+        assert!(templates.check(AgentType::Implement).is_ok());
+    }
+
+    #[test]
+    fn test_builtin_templates_pass_strict_check() {
+        for agent_type in AgentType::all() {
+            PromptTemplates::new().check(*agent_type).unwrap();
+        }
     }
 }
@@ -0,0 +1,850 @@
+//! Filesystem and network confinement for agent processes
+//!
+//! When enabled, agents are launched under [bubblewrap](https://github.com/containers/bubblewrap)
+//! (`bwrap`): only the worktree (and its git metadata) is writable, toolchain
+//! directories are mounted read-only, and the rest of the host filesystem is
+//! hidden. Network access is either left alone (the default), cut off
+//! entirely, or limited to an [`EgressProxy`] that only tunnels to allowlisted
+//! hosts. Under the allowlist the agent gets its own network namespace with
+//! nothing but loopback, where [`run_egress_bridge`] forwards the proxy port
+//! to the proxy's unix socket, so the proxy is the only way out.
+//!
+//! Agent types that [run isolated](super::AgentType::runs_isolated) get their
+//! worktree mounted read-only as well.
+
+use std::ffi::{OsStr, OsString};
+use std::net::Ipv4Addr;
+use std::path::{Path, PathBuf};
+use std::process::{ExitStatus, Stdio};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::net::{TcpListener, TcpStream, UnixListener, UnixStream};
+use tokio::process::Command;
+use tokio::task::JoinHandle;
+
+use super::types::AgentType;
+use crate::{Error, Result};
+
+/// Host directories mounted read-only so toolchains keep working
+const SYSTEM_READ_ONLY_PATHS: &[&str] = &[
+    "/usr", "/bin", "/sbin", "/lib", "/lib32", "/lib64", "/etc", "/opt", "/nix",
+];
+
+/// Home directories mounted read-only (relative to `$HOME`)
+const HOME_READ_ONLY_PATHS: &[&str] = &[
+    ".cargo",
+    ".rustup",
+    ".local",
+    ".nvm",
+    ".npm-global",
+    ".gitconfig",
+];
+
+/// Home directories the agent may write to (relative to `$HOME`)
+const HOME_WRITABLE_PATHS: &[&str] = &[
+    ".claude",
+    ".claude.json",
+    ".cursor",
+    ".cargo/registry",
+    ".cargo/git",
+    ".cache",
+];
+
+/// Hosts reachable by default under [`NetworkPolicy::Allowlist`]
+const DEFAULT_ALLOWED_HOSTS: &[&str] = &[
+    "api.anthropic.com",
+    "github.com",
+    "api.github.com",
+    "crates.io",
+    "index.crates.io",
+    "static.crates.io",
+];
+
+/// Longest CONNECT request head the egress proxy accepts
+const MAX_REQUEST_HEAD: usize = 8 * 1024;
+
+/// Loopback port the egress bridge listens on inside the sandbox
+const BRIDGE_PORT: u16 = 3128;
+
+/// Subcommand of the bridge executable that runs [`run_egress_bridge`]
+pub const EGRESS_BRIDGE_COMMAND: &str = "sandbox-bridge";
+
+/// Distinguishes the socket directories of proxies started by this process
+static PROXY_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// Which agents are sandboxed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SandboxMode {
+    /// Agents run directly on the host
+    #[default]
+    Off,
+    /// Only agent types that run isolated (reviewers) are sandboxed
+    Isolated,
+    /// Every agent is sandboxed
+    All,
+}
+
+/// Network access inside the sandbox
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NetworkPolicy {
+    /// Share the host network
+    #[default]
+    Host,
+    /// Reach allowed hosts only, through an HTTPS proxy
+    ///
+    /// The agent has no network of its own; tools that ignore the proxy
+    /// variables can't connect anywhere.
+    Allowlist,
+    /// No network access at all
+    Offline,
+}
+
+/// Sandbox configuration (`[agent.sandbox]`)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SandboxConfig {
+    /// Which agents are sandboxed
+    pub mode: SandboxMode,
+
+    /// Path to the bubblewrap executable
+    pub bwrap_path: String,
+
+    /// Extra host paths mounted read-only
+    pub read_only_paths: Vec<String>,
+
+    /// Extra host paths mounted writable
+    pub writable_paths: Vec<String>,
+
+    /// Network access inside the sandbox
+    pub network: NetworkPolicy,
+
+    /// Hosts reachable under the allowlist policy (`*.example.com` matches subdomains)
+    pub allowed_hosts: Vec<String>,
+}
+
+impl Default for SandboxConfig {
+    fn default() -> Self {
+        Self {
+            mode: SandboxMode::Off,
+            bwrap_path: "bwrap".to_string(),
+            read_only_paths: Vec::new(),
+            writable_paths: Vec::new(),
+            network: NetworkPolicy::Host,
+            allowed_hosts: DEFAULT_ALLOWED_HOSTS
+                .iter()
+                .map(|h| h.to_string())
+                .collect(),
+        }
+    }
+}
+
+impl SandboxConfig {
+    /// Whether agents of this type are sandboxed
+    pub fn applies_to(&self, agent_type: AgentType) -> bool {
+        match self.mode {
+            SandboxMode::Off => false,
+            SandboxMode::Isolated => agent_type.runs_isolated(),
+            SandboxMode::All => true,
+        }
+    }
+
+    /// Build the sandbox for an agent type, or `None` if it runs unconfined
+    pub fn for_agent_type(&self, agent_type: AgentType) -> Option<Sandbox> {
+        if !self.applies_to(agent_type) {
+            return None;
+        }
+
+        let mut sandbox = Sandbox::new()
+            .with_bwrap_path(&self.bwrap_path)
+            .with_network(self.network);
+        for path in &self.read_only_paths {
+            sandbox = sandbox.with_read_only_path(expand_home(path));
+        }
+        for path in &self.writable_paths {
+            sandbox = sandbox.with_writable_path(expand_home(path));
+        }
+        sandbox.allowed_hosts = self.allowed_hosts.clone();
+        if agent_type.runs_isolated() {
+            sandbox = sandbox.with_read_only_workdir();
+        }
+        Some(sandbox)
+    }
+}
+
+/// A bubblewrap sandbox for one agent process
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sandbox {
+    bwrap_path: String,
+    read_only_paths: Vec<PathBuf>,
+    writable_paths: Vec<PathBuf>,
+    workdir_writable: bool,
+    network: NetworkPolicy,
+    allowed_hosts: Vec<String>,
+    bridge_path: Option<PathBuf>,
+}
+
+impl Default for Sandbox {
+    fn default() -> Self {
+        let home = home_dir();
+        let mut read_only_paths: Vec<PathBuf> =
+            SYSTEM_READ_ONLY_PATHS.iter().map(PathBuf::from).collect();
+        let mut writable_paths = Vec::new();
+        if let Some(home) = &home {
+            read_only_paths.extend(HOME_READ_ONLY_PATHS.iter().map(|p| home.join(p)));
+            writable_paths.extend(HOME_WRITABLE_PATHS.iter().map(|p| home.join(p)));
+        }
+
+        Self {
+            bwrap_path: "bwrap".to_string(),
+            read_only_paths,
+            writable_paths,
+            workdir_writable: true,
+            network: NetworkPolicy::Host,
+            allowed_hosts: Vec::new(),
+            bridge_path: std::env::current_exe().ok(),
+        }
+    }
+}
+
+impl Sandbox {
+    /// Create a sandbox with the default toolchain mounts and no allowed hosts
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the path to the bubblewrap executable
+    pub fn with_bwrap_path(mut self, path: impl Into<String>) -> Self {
+        self.bwrap_path = path.into();
+        self
+    }
+
+    /// Mount a host path read-only
+    pub fn with_read_only_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.read_only_paths.push(path.into());
+        self
+    }
+
+    /// Mount a host path writable
+    pub fn with_writable_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.writable_paths.push(path.into());
+        self
+    }
+
+    /// Mount the worktree read-only
+    pub fn with_read_only_workdir(mut self) -> Self {
+        self.workdir_writable = false;
+        self
+    }
+
+    /// Set the network policy
+    pub fn with_network(mut self, network: NetworkPolicy) -> Self {
+        self.network = network;
+        self
+    }
+
+    /// Allow a host under the allowlist policy
+    pub fn with_allowed_host(mut self, host: impl Into<String>) -> Self {
+        self.allowed_hosts.push(host.into());
+        self
+    }
+
+    /// Set the executable that runs the egress bridge under the allowlist
+    /// policy (the running `murmur` by default)
+    pub fn with_bridge_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.bridge_path = Some(path.into());
+        self
+    }
+
+    /// Path to the bubblewrap executable
+    pub fn bwrap_path(&self) -> &str {
+        &self.bwrap_path
+    }
+
+    /// Whether the worktree is writable
+    pub fn workdir_writable(&self) -> bool {
+        self.workdir_writable
+    }
+
+    /// Get the network policy
+    pub fn network(&self) -> NetworkPolicy {
+        self.network
+    }
+
+    /// Arguments passed to bubblewrap before the `--` separator
+    pub fn bwrap_args(&self, workdir: &Path) -> Vec<OsString> {
+        let mut args: Vec<OsString> = [
+            "--die-with-parent",
+            "--unshare-all",
+            "--proc",
+            "/proc",
+            "--dev",
+            "/dev",
+            "--tmpfs",
+            "/tmp",
+        ]
+        .iter()
+        .map(OsString::from)
+        .collect();
+
+        if self.network == NetworkPolicy::Host {
+            args.push("--share-net".into());
+        }
+
+        // An empty, throwaway home so tools can write dotfiles; the mounts
+        // below are layered on top of it.
+        if let Some(home) = home_dir() {
+            args.push("--tmpfs".into());
+            args.push(home.into_os_string());
+        }
+
+        let mut bind = |flag: &str, path: &Path| {
+            args.push(flag.into());
+            args.push(path.as_os_str().to_owned());
+            args.push(path.as_os_str().to_owned());
+        };
+
+        for path in &self.read_only_paths {
+            bind("--ro-bind-try", path);
+        }
+        for path in &self.writable_paths {
+            bind("--bind-try", path);
+        }
+
+        let flag = if self.workdir_writable {
+            "--bind"
+        } else {
+            "--ro-bind"
+        };
+        bind(flag, workdir);
+        // Worktrees keep their objects and refs in the main repository
+        if let Some(git_dir) = git_common_dir(workdir) {
+            if !git_dir.starts_with(workdir) {
+                bind(flag, &git_dir);
+            }
+        }
+
+        args.push("--chdir".into());
+        args.push(workdir.as_os_str().to_owned());
+        args
+    }
+
+    /// Rewrap a prepared command so it runs inside the sandbox
+    ///
+    /// Under [`NetworkPolicy::Allowlist`] this also starts the egress proxy,
+    /// which must be kept alive for as long as the agent runs, and runs the
+    /// command under the egress bridge.
+    pub async fn wrap(
+        &self,
+        cmd: Command,
+        workdir: &Path,
+    ) -> Result<(Command, Option<EgressProxy>)> {
+        let inner = cmd.as_std();
+        let mut wrapped = Command::new(&self.bwrap_path);
+        wrapped.args(self.bwrap_args(workdir));
+
+        let proxy = if self.network == NetworkPolicy::Allowlist {
+            let bridge = self.bridge_path.as_ref().ok_or_else(|| {
+                Error::Config("No egress bridge executable for the sandbox allowlist".to_string())
+            })?;
+            let proxy = EgressProxy::start(self.allowed_hosts.clone()).await?;
+            let socket = proxy.socket_path();
+            wrapped
+                .arg("--bind")
+                .arg(socket)
+                .arg(socket)
+                .arg("--ro-bind")
+                .arg(bridge)
+                .arg(bridge)
+                .arg("--")
+                .arg(bridge)
+                .arg(EGRESS_BRIDGE_COMMAND)
+                .arg("--socket")
+                .arg(socket);
+            Some(proxy)
+        } else {
+            None
+        };
+
+        wrapped
+            .arg("--")
+            .arg(inner.get_program())
+            .args(inner.get_args())
+            .current_dir(workdir)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+
+        for (key, value) in inner.get_envs() {
+            match value {
+                Some(value) => wrapped.env(key, value),
+                None => wrapped.env_remove(key),
+            };
+        }
+
+        if proxy.is_some() {
+            let url = format!("http://{}:{}", Ipv4Addr::LOCALHOST, BRIDGE_PORT);
+            for key in ["HTTPS_PROXY", "https_proxy", "HTTP_PROXY", "http_proxy"] {
+                wrapped.env(key, &url);
+            }
+            wrapped.env_remove("NO_PROXY").env_remove("no_proxy");
+        }
+
+        Ok((wrapped, proxy))
+    }
+}
+
+/// An HTTP CONNECT proxy on a unix socket that only tunnels to allowed hosts
+///
+/// The proxy stops and its socket is removed when dropped.
+#[derive(Debug)]
+pub struct EgressProxy {
+    dir: PathBuf,
+    socket_path: PathBuf,
+    task: JoinHandle<()>,
+}
+
+impl EgressProxy {
+    /// Start a proxy on a socket in a fresh temporary directory
+    pub async fn start(allowed_hosts: Vec<String>) -> Result<Self> {
+        let dir = std::env::temp_dir().join(format!(
+            "murmur-egress-{}-{}",
+            std::process::id(),
+            PROXY_COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        std::fs::create_dir_all(&dir)?;
+        let socket_path = dir.join("proxy.sock");
+        let listener = UnixListener::bind(&socket_path)?;
+
+        let task = tokio::spawn(async move {
+            while let Ok((client, _)) = listener.accept().await {
+                let allowed_hosts = allowed_hosts.clone();
+                tokio::spawn(async move {
+                    if let Err(e) = tunnel(client, &allowed_hosts).await {
+                        tracing::debug!(error = %e, "Egress proxy connection failed");
+                    }
+                });
+            }
+        });
+
+        Ok(Self {
+            dir,
+            socket_path,
+            task,
+        })
+    }
+
+    /// Unix socket the proxy listens on
+    pub fn socket_path(&self) -> &Path {
+        &self.socket_path
+    }
+}
+
+impl Drop for EgressProxy {
+    fn drop(&mut self) {
+        self.task.abort();
+        let _ = std::fs::remove_dir_all(&self.dir);
+    }
+}
+
+/// Run a sandboxed command with the egress proxy reachable on loopback
+///
+/// This runs inside the sandbox's network namespace, which has nothing but
+/// loopback. Connections to the proxy port are forwarded to the proxy's
+/// `socket`, bind-mounted in from the host. Returns the command's exit status.
+pub async fn run_egress_bridge(
+    socket: &Path,
+    program: &OsStr,
+    args: &[OsString],
+) -> Result<ExitStatus> {
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, BRIDGE_PORT)).await?;
+    bridge(listener, socket, program, args).await
+}
+
+/// Run a command while forwarding `listener` to the unix `socket`
+async fn bridge(
+    listener: TcpListener,
+    socket: &Path,
+    program: &OsStr,
+    args: &[OsString],
+) -> Result<ExitStatus> {
+    let forward = tokio::spawn(forward_to_socket(listener, socket.to_path_buf()));
+    let status = Command::new(program).args(args).status().await;
+    forward.abort();
+    Ok(status?)
+}
+
+/// Forward every connection on `listener` to the unix `socket`
+async fn forward_to_socket(listener: TcpListener, socket: PathBuf) {
+    while let Ok((mut client, _)) = listener.accept().await {
+        let socket = socket.clone();
+        tokio::spawn(async move {
+            let forwarded = async {
+                let mut upstream = UnixStream::connect(&socket).await?;
+                tokio::io::copy_bidirectional(&mut client, &mut upstream).await
+            };
+            if let Err(e) = forwarded.await {
+                tracing::debug!(error = %e, "Egress bridge connection failed");
+            }
+        });
+    }
+}
+
+/// Serve one proxy client
+async fn tunnel<S>(client: S, allowed_hosts: &[String]) -> std::io::Result<()>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let mut client = BufReader::new(client);
+
+    let mut request_line = String::new();
+    client.read_line(&mut request_line).await?;
+    let mut head_len = request_line.len();
+    loop {
+        let mut line = String::new();
+        let n = client.read_line(&mut line).await?;
+        head_len += n;
+        if n == 0 || line == "\r\n" || line == "\n" || head_len > MAX_REQUEST_HEAD {
+            break;
+        }
+    }
+
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or_default();
+    let target = parts.next().unwrap_or_default();
+
+    if !method.eq_ignore_ascii_case("CONNECT") {
+        return respond(&mut client, "405 Method Not Allowed").await;
+    }
+
+    let host = target
+        .rsplit_once(':')
+        .map(|(host, _)| host)
+        .unwrap_or(target)
+        .trim_start_matches('[')
+        .trim_end_matches(']');
+    if !host_allowed(host, allowed_hosts) {
+        tracing::warn!(
+            host,
+            "Sandbox blocked connection to host outside the allowlist"
+        );
+        return respond(&mut client, "403 Forbidden").await;
+    }
+
+    let mut upstream = match TcpStream::connect(target).await {
+        Ok(upstream) => upstream,
+        Err(_) => return respond(&mut client, "502 Bad Gateway").await,
+    };
+    respond(&mut client, "200 Connection Established").await?;
+    tokio::io::copy_bidirectional(&mut client, &mut upstream).await?;
+    Ok(())
+}
+
+async fn respond<S>(client: &mut BufReader<S>, status: &str) -> std::io::Result<()>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    client
+        .write_all(format!("HTTP/1.1 {}\r\n\r\n", status).as_bytes())
+        .await?;
+    client.flush().await
+}
+
+/// Check a host against an allowlist (`*.example.com` matches subdomains)
+fn host_allowed(host: &str, allowed_hosts: &[String]) -> bool {
+    let host = host.to_lowercase();
+    allowed_hosts.iter().any(|pattern| {
+        let pattern = pattern.to_lowercase();
+        match pattern.strip_prefix("*.") {
+            Some(domain) => host.ends_with(&format!(".{}", domain)),
+            None => host == pattern,
+        }
+    })
+}
+
+/// Locate the repository's shared git directory for a worktree
+///
+/// Linked worktrees have a `.git` file pointing at `<repo>/.git/worktrees/<name>`,
+/// whose `commondir` file leads back to `<repo>/.git`.
+fn git_common_dir(workdir: &Path) -> Option<PathBuf> {
+    let contents = std::fs::read_to_string(workdir.join(".git")).ok()?;
+    let gitdir = workdir.join(contents.strip_prefix("gitdir:")?.trim());
+    let common = match std::fs::read_to_string(gitdir.join("commondir")) {
+        Ok(common) => gitdir.join(common.trim()),
+        Err(_) => gitdir,
+    };
+    common.canonicalize().ok()
+}
+
+fn home_dir() -> Option<PathBuf> {
+    std::env::var_os("HOME")
+        .filter(|h| !h.is_empty())
+        .map(PathBuf::from)
+}
+
+fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), home_dir()) {
+        (Some(rest), Some(home)) => home.join(rest),
+        _ => PathBuf::from(path),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::AsyncReadExt;
+
+    fn args_of(sandbox: &Sandbox, workdir: &Path) -> Vec<String> {
+        sandbox
+            .bwrap_args(workdir)
+            .into_iter()
+            .map(|a| a.to_string_lossy().into_owned())
+            .collect()
+    }
+
+    fn has_mount(args: &[String], flag: &str, path: &Path) -> bool {
+        let path = path.to_string_lossy();
+        args.windows(3)
+            .any(|w| w[0] == flag && w[1] == path && w[2] == path)
+    }
+
+    #[test]
+    fn test_mode_applies_to() {
+        let mut config = SandboxConfig::default();
+        assert!(!config.applies_to(AgentType::Review));
+        assert!(config.for_agent_type(AgentType::Implement).is_none());
+
+        config.mode = SandboxMode::Isolated;
+        assert!(config.applies_to(AgentType::Review));
+        assert!(!config.applies_to(AgentType::Implement));
+
+        config.mode = SandboxMode::All;
+        assert!(config.applies_to(AgentType::Implement));
+    }
+
+    #[test]
+    fn test_isolated_agents_get_read_only_worktree() {
+        let config = SandboxConfig {
+            mode: SandboxMode::All,
+            ..Default::default()
+        };
+        let review = config.for_agent_type(AgentType::Review).unwrap();
+        let implement = config.for_agent_type(AgentType::Implement).unwrap();
+        assert!(!review.workdir_writable());
+        assert!(implement.workdir_writable());
+
+        let workdir = Path::new("/work/tree");
+        assert!(has_mount(&args_of(&review, workdir), "--ro-bind", workdir));
+        assert!(has_mount(&args_of(&implement, workdir), "--bind", workdir));
+    }
+
+    #[test]
+    fn test_network_defaults_to_host() {
+        assert_eq!(SandboxConfig::default().network, NetworkPolicy::Host);
+        assert_eq!(Sandbox::new().network(), NetworkPolicy::Host);
+    }
+
+    #[test]
+    fn test_bwrap_args() {
+        let workdir = Path::new("/work/tree");
+        let sandbox = Sandbox::new()
+            .with_read_only_path("/srv/toolchain")
+            .with_writable_path("/srv/cache");
+        let args = args_of(&sandbox, workdir);
+
+        assert!(args.contains(&"--unshare-all".to_string()));
+        assert!(args.contains(&"--share-net".to_string()));
+        assert!(has_mount(&args, "--ro-bind-try", Path::new("/usr")));
+        assert!(has_mount(
+            &args,
+            "--ro-bind-try",
+            Path::new("/srv/toolchain")
+        ));
+        assert!(has_mount(&args, "--bind-try", Path::new("/srv/cache")));
+        assert_eq!(&args[args.len() - 2..], ["--chdir", "/work/tree"]);
+
+        let offline = Sandbox::new().with_network(NetworkPolicy::Offline);
+        assert!(!args_of(&offline, workdir).contains(&"--share-net".to_string()));
+        let allowlist = Sandbox::new().with_network(NetworkPolicy::Allowlist);
+        assert!(!args_of(&allowlist, workdir).contains(&"--share-net".to_string()));
+    }
+
+    #[test]
+    fn test_worktree_git_dir_is_mounted() {
+        let repo = tempfile::tempdir().unwrap();
+        let git_dir = repo.path().join(".git");
+        let admin = git_dir.join("worktrees").join("wt");
+        std::fs::create_dir_all(&admin).unwrap();
+        std::fs::write(admin.join("commondir"), "../..\n").unwrap();
+
+        let worktree = tempfile::tempdir().unwrap();
+        std::fs::write(
+            worktree.path().join(".git"),
+            format!("gitdir: {}\n", admin.display()),
+        )
+        .unwrap();
+
+        let common = git_common_dir(worktree.path()).unwrap();
+        assert_eq!(common, git_dir.canonicalize().unwrap());
+
+        let args = args_of(&Sandbox::new().with_read_only_workdir(), worktree.path());
+        assert!(has_mount(&args, "--ro-bind", &common));
+    }
+
+    #[tokio::test]
+    async fn test_wrap_preserves_command() {
+        let mut cmd = Command::new("claude");
+        cmd.arg("--print")
+            .arg("do the thing")
+            .env("MURMUR_TEST", "1");
+
+        let sandbox = Sandbox::new()
+            .with_bwrap_path("/opt/bwrap")
+            .with_network(NetworkPolicy::Host);
+        let (wrapped, proxy) = sandbox.wrap(cmd, Path::new("/work")).await.unwrap();
+        assert!(proxy.is_none());
+
+        let std = wrapped.as_std();
+        assert_eq!(std.get_program(), "/opt/bwrap");
+        let args: Vec<_> = std.get_args().map(|a| a.to_string_lossy()).collect();
+        let sep = args.iter().position(|a| a == "--").unwrap();
+        assert_eq!(args[sep + 1..], ["claude", "--print", "do the thing"]);
+        assert!(std
+            .get_envs()
+            .any(|(k, v)| k == "MURMUR_TEST" && v == Some("1".as_ref())));
+    }
+
+    #[tokio::test]
+    async fn test_wrap_with_allowlist_runs_under_bridge() {
+        let sandbox = Sandbox::new()
+            .with_network(NetworkPolicy::Allowlist)
+            .with_allowed_host("example.com")
+            .with_bridge_path("/opt/murmur");
+        let (wrapped, proxy) = sandbox
+            .wrap(Command::new("claude"), Path::new("/work"))
+            .await
+            .unwrap();
+        let proxy = proxy.unwrap();
+        let socket = proxy.socket_path().to_string_lossy().into_owned();
+        assert!(proxy.socket_path().exists());
+
+        let std = wrapped.as_std();
+        let args: Vec<_> = std
+            .get_args()
+            .map(|a| a.to_string_lossy().into_owned())
+            .collect();
+        assert!(!args.contains(&"--share-net".to_string()));
+        assert!(has_mount(&args, "--bind", proxy.socket_path()));
+        assert!(has_mount(&args, "--ro-bind", Path::new("/opt/murmur")));
+        let sep = args.iter().position(|a| a == "--").unwrap();
+        assert_eq!(
+            args[sep + 1..],
+            [
+                "/opt/murmur",
+                EGRESS_BRIDGE_COMMAND,
+                "--socket",
+                &socket,
+                "--",
+                "claude"
+            ]
+        );
+        assert!(std
+            .get_envs()
+            .any(|(k, v)| k == "HTTPS_PROXY" && v == Some("http://127.0.0.1:3128".as_ref())));
+
+        let dir = proxy.socket_path().parent().unwrap().to_path_buf();
+        drop(proxy);
+        assert!(!dir.exists());
+    }
+
+    #[test]
+    fn test_host_allowed() {
+        let allowed = vec!["api.anthropic.com".to_string(), "*.github.com".to_string()];
+        assert!(host_allowed("api.anthropic.com", &allowed));
+        assert!(host_allowed("API.Anthropic.com", &allowed));
+        assert!(host_allowed("codeload.github.com", &allowed));
+        assert!(!host_allowed("github.com", &allowed));
+        assert!(!host_allowed("evil.com", &allowed));
+        assert!(!host_allowed("anthropic.com", &allowed));
+    }
+
+    async fn connect(proxy: &EgressProxy, request: &str) -> (UnixStream, String) {
+        let mut stream = UnixStream::connect(proxy.socket_path()).await.unwrap();
+        stream.write_all(request.as_bytes()).await.unwrap();
+        let mut buf = [0u8; 128];
+        let n = stream.read(&mut buf).await.unwrap();
+        (stream, String::from_utf8_lossy(&buf[..n]).into_owned())
+    }
+
+    #[tokio::test]
+    async fn test_proxy_enforces_allowlist() {
+        let upstream = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let upstream_addr = upstream.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (mut socket, _) = upstream.accept().await.unwrap();
+            let mut buf = [0u8; 4];
+            socket.read_exact(&mut buf).await.unwrap();
+            socket.write_all(&buf).await.unwrap();
+        });
+
+        let proxy = EgressProxy::start(vec!["127.0.0.1".to_string()])
+            .await
+            .unwrap();
+
+        let (_, response) = connect(&proxy, "CONNECT evil.com:443 HTTP/1.1\r\n\r\n").await;
+        assert!(response.starts_with("HTTP/1.1 403"));
+
+        let (_, response) = connect(&proxy, "GET http://127.0.0.1/ HTTP/1.1\r\n\r\n").await;
+        assert!(response.starts_with("HTTP/1.1 405"));
+
+        let request = format!(
+            "CONNECT {} HTTP/1.1\r\nHost: {}\r\n\r\n",
+            upstream_addr, upstream_addr
+        );
+        let (mut stream, response) = connect(&proxy, &request).await;
+        assert!(response.starts_with("HTTP/1.1 200"));
+        stream.write_all(b"ping").await.unwrap();
+        let mut echo = [0u8; 4];
+        stream.read_exact(&mut echo).await.unwrap();
+        assert_eq!(&echo, b"ping");
+    }
+
+    #[tokio::test]
+    async fn test_bridge_forwards_to_proxy() {
+        let proxy = EgressProxy::start(Vec::new()).await.unwrap();
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let forward = tokio::spawn(forward_to_socket(
+            listener,
+            proxy.socket_path().to_path_buf(),
+        ));
+
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        stream
+            .write_all(b"CONNECT evil.com:443 HTTP/1.1\r\n\r\n")
+            .await
+            .unwrap();
+        let mut buf = [0u8; 128];
+        let n = stream.read(&mut buf).await.unwrap();
+        assert!(String::from_utf8_lossy(&buf[..n]).starts_with("HTTP/1.1 403"));
+        forward.abort();
+    }
+
+    #[tokio::test]
+    async fn test_bridge_returns_command_status() {
+        let proxy = EgressProxy::start(Vec::new()).await.unwrap();
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let status = bridge(
+            listener,
+            proxy.socket_path(),
+            OsStr::new("sh"),
+            &["-c".into(), "exit 3".into()],
+        )
+        .await
+        .unwrap();
+        assert_eq!(status.code(), Some(3));
+    }
+}
@@ -1,12 +1,15 @@
 //! Agent spawning logic for Claude Code subprocess management
 
 use std::path::Path;
-use std::process::Stdio;
-use tokio::process::{Child, Command};
+use std::time::Duration;
+use tokio::process::Child;
 
 use crate::config::{AgentConfig, ResolvedConfig};
 use crate::{Error, Result};
 
+use super::backend::{BackendRegistry, SpawnOptions};
+use super::sandbox::{EgressProxy, Sandbox};
+
 /// Handle to a running Claude Code agent process
 pub struct AgentHandle {
     /// The child process (not Debug, so we skip it)
@@ -15,6 +18,8 @@ pub struct AgentHandle {
     prompt: String,
     /// Working directory for the agent
     workdir: String,
+    /// Egress proxy serving a sandboxed agent, stopped when the handle is dropped
+    egress_proxy: Option<EgressProxy>,
 }
 
 impl std::fmt::Debug for AgentHandle {
@@ -23,6 +28,7 @@ impl std::fmt::Debug for AgentHandle {
             .field("prompt", &self.prompt)
             .field("workdir", &self.workdir)
             .field("child", &"<Child>")
+            .field("egress_proxy", &self.egress_proxy)
             .finish()
     }
 }
@@ -34,9 +40,16 @@ impl AgentHandle {
             child,
             prompt,
             workdir,
+            egress_proxy: None,
         }
     }
 
+    /// Keep a sandbox egress proxy alive for the lifetime of this handle
+    pub(crate) fn with_egress_proxy(mut self, proxy: EgressProxy) -> Self {
+        self.egress_proxy = Some(proxy);
+        self
+    }
+
     /// Get the prompt this agent is working on
     pub fn prompt(&self) -> &str {
         &self.prompt
@@ -62,10 +75,51 @@ impl AgentHandle {
         self.child.wait().await.map_err(Error::Io)
     }
 
-    /// Kill the agent process
+    /// Kill the agent process along with any subprocesses it started
     pub async fn kill(&mut self) -> Result<()> {
+        #[cfg(unix)]
+        if let Some(pid) = self.pid() {
+            signal_process_group(pid, libc::SIGKILL);
+        }
+
         self.child.kill().await.map_err(Error::Io)
     }
+
+    /// Ask the agent's process group to exit with SIGTERM, then kill it if the
+    /// agent is still running after `grace`
+    pub async fn terminate(&mut self, grace: Duration) -> Result<()> {
+        #[cfg(unix)]
+        if let Some(pid) = self.pid() {
+            if signal_process_group(pid, libc::SIGTERM)
+                && tokio::time::timeout(grace, self.child.wait()).await.is_ok()
+            {
+                // The agent is gone; take down anything it left behind
+                signal_process_group(pid, libc::SIGKILL);
+                return Ok(());
+            }
+        }
+
+        self.kill().await
+    }
+}
+
+/// Put a command in its own process group so the whole agent tree can be signalled
+pub(crate) fn own_process_group(cmd: &mut tokio::process::Command) {
+    #[cfg(unix)]
+    cmd.process_group(0);
+    #[cfg(not(unix))]
+    let _ = cmd;
+}
+
+/// Send `signal` to the process group led by `pid`, returning whether it was delivered
+#[cfg(unix)]
+#[allow(unsafe_code)]
+pub(crate) fn signal_process_group(pid: u32, signal: libc::c_int) -> bool {
+    let Ok(pgid) = libc::pid_t::try_from(pid) else {
+        return false;
+    };
+    // SAFETY: kill(2) takes no pointers; a negative pid addresses the process group
+    unsafe { libc::kill(-pgid, signal) == 0 }
 }
 
 /// Spawner for Claude Code agent processes
@@ -75,6 +129,10 @@ pub struct AgentSpawner {
     config: ResolvedConfig,
     /// Environment variables to pass to spawned agents
     env_vars: Vec<(String, String)>,
+    /// Backend session to resume on spawn
+    resume_session: Option<String>,
+    /// Sandbox to confine spawned agents in
+    sandbox: Option<Sandbox>,
 }
 
 impl Default for AgentSpawner {
@@ -94,8 +152,10 @@ impl AgentSpawner {
     /// This resolves the configuration for the specific agent type
     pub fn from_config(config: AgentConfig, agent_type: crate::agent::AgentType) -> Self {
         Self {
+            sandbox: config.sandbox.for_agent_type(agent_type),
             config: config.resolve_for_type(agent_type),
             env_vars: Vec::new(),
+            resume_session: None,
         }
     }
 
@@ -104,6 +164,8 @@ impl AgentSpawner {
         Self {
             config,
             env_vars: Vec::new(),
+            resume_session: None,
+            sandbox: None,
         }
     }
 
@@ -125,7 +187,34 @@ impl AgentSpawner {
         self
     }
 
-    /// Spawn a new Claude Code agent with the given prompt
+    /// Resume an existing backend session instead of starting a new one
+    pub fn with_resume_session(mut self, session_id: impl Into<String>) -> Self {
+        self.resume_session = Some(session_id.into());
+        self
+    }
+
+    /// Run spawned agents inside a sandbox
+    pub fn with_sandbox(mut self, sandbox: Sandbox) -> Self {
+        self.sandbox = Some(sandbox);
+        self
+    }
+
+    /// Get the sandbox spawned agents run in, if any
+    pub fn sandbox(&self) -> Option<&Sandbox> {
+        self.sandbox.as_ref()
+    }
+
+    /// Check if the configured backend can natively resume a session in `workdir`
+    pub fn can_resume(&self, session_id: &str, workdir: impl AsRef<Path>) -> bool {
+        BackendRegistry::from_resolved(&self.config)
+            .get_by_type(self.config.backend)
+            .is_some_and(|backend| backend.can_resume(session_id, workdir.as_ref()))
+    }
+
+    /// Spawn a new agent with the given prompt
+    ///
+    /// The backend is resolved from the configured backend type, so per-type
+    /// overrides (e.g. `[agent.review] backend = "cursor"`) take effect.
     ///
     /// # Arguments
     /// * `prompt` - The task prompt for the agent
@@ -139,56 +228,18 @@ impl AgentSpawner {
         workdir: impl AsRef<Path>,
     ) -> Result<AgentHandle> {
         let prompt = prompt.into();
-        let workdir_path = workdir.as_ref();
-        let workdir_str = workdir_path
-            .to_str()
-            .ok_or_else(|| Error::Agent("Invalid working directory path".to_string()))?
-            .to_string();
-
-        // Verify working directory exists
-        if !workdir_path.exists() {
-            return Err(Error::Agent(format!(
-                "Working directory does not exist: {}",
-                workdir_str
-            )));
-        }
-
-        let executable_path = &self.config.executable_path;
-
-        let mut cmd = Command::new(executable_path);
-        cmd.arg("--print")
-            .arg("--verbose")
-            .arg("--output-format")
-            .arg("stream-json")
-            .arg("--dangerously-skip-permissions");
-
-        // Add model flag if specified
-        if let Some(ref model) = self.config.model {
-            cmd.arg("--model").arg(model);
-        }
-
-        // Add environment variables
-        for (key, value) in &self.env_vars {
-            cmd.env(key, value);
-        }
-
-        cmd.arg(&prompt)
-            .current_dir(workdir_path)
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped());
-
-        let child = cmd.spawn().map_err(|e| {
-            if e.kind() == std::io::ErrorKind::NotFound {
-                Error::Agent(format!(
-                    "Executable not found at '{}'. Is the agent backend installed?",
-                    executable_path
-                ))
-            } else {
-                Error::Io(e)
-            }
+        let registry = BackendRegistry::from_resolved(&self.config);
+        let backend = registry.get_by_type(self.config.backend).ok_or_else(|| {
+            Error::Agent(format!("Backend not registered: {}", self.config.backend))
         })?;
 
-        Ok(AgentHandle::new(child, prompt, workdir_str))
+        let options = SpawnOptions {
+            env_vars: self.env_vars.clone(),
+            resume_session: self.resume_session.clone(),
+            sandbox: self.sandbox.clone(),
+        };
+
+        backend.spawn(&prompt, workdir.as_ref(), &options).await
     }
 }
 
@@ -213,4 +264,68 @@ mod tests {
         assert!(result.is_err());
         // Should fail to find the executable
     }
+
+    #[tokio::test]
+    async fn test_spawn_mock_backend() {
+        let config = AgentConfig {
+            backend: crate::config::Backend::Mock,
+            ..AgentConfig::default()
+        };
+        let spawner = AgentSpawner::from_config(config, crate::agent::AgentType::Implement);
+        let mut handle = spawner
+            .spawn("test", env::current_dir().unwrap())
+            .await
+            .unwrap();
+        assert!(handle.wait().await.unwrap().success());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn test_terminate_signals_agent_subprocesses() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let agent = dir.path().join("agent.sh");
+        std::fs::write(&agent, "#!/bin/sh\nsleep 30 &\necho $! > child.pid\nwait\n").unwrap();
+        std::fs::set_permissions(&agent, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let spawner = AgentSpawner::new().with_executable_path(agent.to_str().unwrap());
+        let mut handle = spawner.spawn("test", dir.path()).await.unwrap();
+
+        let pid_file = dir.path().join("child.pid");
+        let child_pid = loop {
+            match std::fs::read_to_string(&pid_file) {
+                Ok(pid) if pid.ends_with('\n') => break pid.trim().to_string(),
+                _ => tokio::time::sleep(Duration::from_millis(10)).await,
+            }
+        };
+
+        handle.terminate(Duration::from_secs(5)).await.unwrap();
+
+        // The orphaned sleep may linger as a zombie until init reaps it
+        let alive = || {
+            std::fs::read_to_string(format!("/proc/{child_pid}/stat"))
+                .is_ok_and(|stat| !stat.contains(") Z "))
+        };
+        for _ in 0..100 {
+            if !alive() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert!(!alive(), "agent subprocess survived terminate");
+    }
+
+    #[test]
+    fn test_can_resume_depends_on_backend() {
+        let workdir = env::current_dir().unwrap();
+        assert!(!AgentSpawner::new().can_resume("no-such-session", &workdir));
+
+        let config = AgentConfig {
+            backend: crate::config::Backend::Mock,
+            ..AgentConfig::default()
+        };
+        let spawner = AgentSpawner::from_config(config, crate::agent::AgentType::Implement);
+        assert!(spawner.can_resume("any-session", &workdir));
+    }
 }
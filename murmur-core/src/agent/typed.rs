@@ -3,7 +3,7 @@
 //! This module provides concrete agent implementations that use type-specific
 //! prompts and behaviors.
 
-use crate::agent::{AgentHandle, AgentSpawner, AgentType, PromptBuilder};
+use crate::agent::{AgentHandle, AgentSpawner, AgentType, PromptBuilder, PromptTemplates};
 use crate::config::AgentConfig;
 use crate::{Error, Result};
 use std::path::Path;
@@ -45,7 +45,10 @@ impl TypedAgent {
         task: impl Into<String>,
         workdir: impl AsRef<Path>,
     ) -> Result<AgentHandle> {
-        let prompt = PromptBuilder::new(self.agent_type).task(task).build();
+        let prompt = PromptBuilder::new(self.agent_type)
+            .templates(PromptTemplates::discover(&workdir))
+            .task(task)
+            .try_build()?;
 
         self.spawner.spawn(prompt, workdir).await
     }
@@ -58,9 +61,10 @@ impl TypedAgent {
         workdir: impl AsRef<Path>,
     ) -> Result<AgentHandle> {
         let prompt = PromptBuilder::new(self.agent_type)
+            .templates(PromptTemplates::discover(&workdir))
             .task(task)
             .files(files)
-            .build();
+            .try_build()?;
 
         self.spawner.spawn(prompt, workdir).await
     }
@@ -102,10 +106,11 @@ impl ImplementAgent {
         workdir: impl AsRef<Path>,
     ) -> Result<AgentHandle> {
         let prompt = PromptBuilder::new(AgentType::Implement)
+            .templates(PromptTemplates::discover(&workdir))
             .task(task)
             .files(files)
             .dependencies(dependencies)
-            .build();
+            .try_build()?;
 
         self.inner.spawner.spawn(prompt, workdir).await
     }
@@ -146,9 +151,10 @@ impl TestAgent {
         workdir: impl AsRef<Path>,
     ) -> Result<AgentHandle> {
         let prompt = PromptBuilder::new(AgentType::Test)
+            .templates(PromptTemplates::discover(&workdir))
             .task(task)
             .files(files)
-            .build();
+            .try_build()?;
 
         self.inner.spawner.spawn(prompt, workdir).await
     }
@@ -167,9 +173,10 @@ impl TestAgent {
         );
 
         let prompt = PromptBuilder::new(AgentType::Test)
+            .templates(PromptTemplates::discover(&workdir))
             .task(task)
             .files(files)
-            .build();
+            .try_build()?;
 
         self.inner.spawner.spawn(prompt, workdir).await
     }
@@ -181,9 +188,10 @@ impl TestAgent {
         workdir: impl AsRef<Path>,
     ) -> Result<AgentHandle> {
         let prompt = PromptBuilder::new(AgentType::Test)
+            .templates(PromptTemplates::discover(&workdir))
             .task("Run the test suite and verify all tests pass. Report any failures.")
             .files(files)
-            .build();
+            .try_build()?;
 
         self.inner.spawner.spawn(prompt, workdir).await
     }
@@ -224,9 +232,10 @@ impl ReviewAgent {
         workdir: impl AsRef<Path>,
     ) -> Result<AgentHandle> {
         let prompt = PromptBuilder::new(AgentType::Review)
+            .templates(PromptTemplates::discover(&workdir))
             .task(task)
             .diff(diff)
-            .build();
+            .try_build()?;
 
         self.inner.spawner.spawn(prompt, workdir).await
     }
@@ -242,9 +251,10 @@ impl ReviewAgent {
         let diff = get_git_diff(workdir_path)?;
 
         let prompt = PromptBuilder::new(AgentType::Review)
+            .templates(PromptTemplates::discover(&workdir))
             .task(task)
             .diff(diff)
-            .build();
+            .try_build()?;
 
         self.inner.spawner.spawn(prompt, workdir).await
     }
@@ -286,10 +296,11 @@ impl CoordinatorAgent {
         workdir: impl AsRef<Path>,
     ) -> Result<AgentHandle> {
         let prompt = PromptBuilder::new(AgentType::Coordinator)
+            .templates(PromptTemplates::discover(&workdir))
             .task(task)
             .repo(repo)
             .main_branch(main_branch)
-            .build();
+            .try_build()?;
 
         self.inner.spawner.spawn(prompt, workdir).await
     }
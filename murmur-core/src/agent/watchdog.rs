@@ -0,0 +1,325 @@
+//! Wall-clock and inactivity supervision for running agents
+//!
+//! [`Watchdog::supervise`] streams an agent's output like
+//! [`OutputStreamer::stream`], but stops the agent (SIGTERM, then SIGKILL after
+//! a grace period) when it runs too long or produces no output for too long.
+
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use super::output::{CostInfo, OutputStreamer, StreamHandler};
+use super::spawn::AgentHandle;
+use crate::Result;
+
+/// Default time between SIGTERM and SIGKILL
+const DEFAULT_GRACE_PERIOD: Duration = Duration::from_secs(10);
+
+/// Longest interval between watchdog checks
+const MAX_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Timeouts applied to a running agent
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WatchdogConfig {
+    /// Maximum total run time
+    pub timeout: Option<Duration>,
+    /// Maximum time without any output
+    pub stall_timeout: Option<Duration>,
+    /// Time to wait after SIGTERM before sending SIGKILL
+    pub grace_period: Duration,
+}
+
+impl Default for WatchdogConfig {
+    fn default() -> Self {
+        Self {
+            timeout: None,
+            stall_timeout: None,
+            grace_period: DEFAULT_GRACE_PERIOD,
+        }
+    }
+}
+
+impl WatchdogConfig {
+    /// Create a config with no timeouts
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the wall-clock timeout
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Set the no-output timeout
+    pub fn with_stall_timeout(mut self, stall_timeout: Duration) -> Self {
+        self.stall_timeout = Some(stall_timeout);
+        self
+    }
+
+    /// Set the grace period between SIGTERM and SIGKILL
+    pub fn with_grace_period(mut self, grace_period: Duration) -> Self {
+        self.grace_period = grace_period;
+        self
+    }
+
+    /// Whether any timeout is configured
+    pub fn is_enabled(&self) -> bool {
+        self.timeout.is_some() || self.stall_timeout.is_some()
+    }
+
+    fn check_interval(&self) -> Duration {
+        [self.timeout, self.stall_timeout]
+            .into_iter()
+            .flatten()
+            .map(|d| d / 4)
+            .fold(MAX_CHECK_INTERVAL, Duration::min)
+            .max(Duration::from_millis(10))
+    }
+}
+
+/// Why the watchdog stopped an agent
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StopReason {
+    /// The wall-clock timeout elapsed
+    Timeout,
+    /// The agent produced no output for too long
+    Stalled,
+}
+
+impl StopReason {
+    /// The outcome string recorded for the run
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            StopReason::Timeout => "timeout",
+            StopReason::Stalled => "stalled",
+        }
+    }
+}
+
+impl fmt::Display for StopReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// Supervises an agent's output stream
+#[derive(Debug, Clone, Default)]
+pub struct Watchdog {
+    config: WatchdogConfig,
+}
+
+impl Watchdog {
+    /// Create a watchdog with the given timeouts
+    pub fn new(config: WatchdogConfig) -> Self {
+        Self { config }
+    }
+
+    /// Get the timeouts
+    pub fn config(&self) -> &WatchdogConfig {
+        &self.config
+    }
+
+    /// Stream the agent's output into `handler`, stopping the agent on timeout
+    ///
+    /// Returns `Some(reason)` if the watchdog stopped the agent, or `None` if
+    /// the stream ended on its own (or the handler asked to stop).
+    pub async fn supervise<H: StreamHandler>(
+        &self,
+        handle: &mut AgentHandle,
+        streamer: &mut OutputStreamer,
+        handler: &mut H,
+    ) -> Result<Option<StopReason>> {
+        if !self.config.is_enabled() {
+            streamer.stream(handler).await?;
+            return Ok(None);
+        }
+
+        let started = Instant::now();
+        let last_activity = Arc::new(Mutex::new(started));
+        let mut watched = ActivityHandler {
+            inner: handler,
+            last_activity: Arc::clone(&last_activity),
+        };
+
+        let reason = {
+            let stream = streamer.stream(&mut watched);
+            tokio::pin!(stream);
+            let mut ticker = tokio::time::interval(self.config.check_interval());
+
+            loop {
+                tokio::select! {
+                    result = &mut stream => {
+                        result?;
+                        return Ok(None);
+                    }
+                    _ = ticker.tick() => {
+                        let idle = last_activity.lock().unwrap().elapsed();
+                        if self.config.timeout.is_some_and(|t| started.elapsed() >= t) {
+                            break StopReason::Timeout;
+                        }
+                        if self.config.stall_timeout.is_some_and(|t| idle >= t) {
+                            break StopReason::Stalled;
+                        }
+                    }
+                }
+            }
+        };
+
+        tracing::warn!(reason = %reason, pid = ?handle.pid(), "Stopping agent");
+        handle.terminate(self.config.grace_period).await?;
+        Ok(Some(reason))
+    }
+}
+
+/// Forwards to the wrapped handler, recording when output was last seen
+struct ActivityHandler<'a, H> {
+    inner: &'a mut H,
+    last_activity: Arc<Mutex<Instant>>,
+}
+
+impl<H> ActivityHandler<'_, H> {
+    fn touch(&self) {
+        *self.last_activity.lock().unwrap() = Instant::now();
+    }
+}
+
+impl<H: StreamHandler> StreamHandler for ActivityHandler<'_, H> {
+    fn on_system(&mut self, subtype: Option<&str>, session_id: Option<&str>) {
+        self.touch();
+        self.inner.on_system(subtype, session_id);
+    }
+
+    fn on_user(&mut self, message: &serde_json::Value) {
+        self.touch();
+        self.inner.on_user(message);
+    }
+
+    fn on_assistant_text(&mut self, text: &str) {
+        self.touch();
+        self.inner.on_assistant_text(text);
+    }
+
+    fn on_tool_use(&mut self, tool: &str, input: &serde_json::Value) {
+        self.touch();
+        self.inner.on_tool_use(tool, input);
+    }
+
+    fn on_tool_result(&mut self, output: &str, is_error: bool) {
+        self.touch();
+        self.inner.on_tool_result(output, is_error);
+    }
+
+    fn on_usage(&mut self, usage: &CostInfo) {
+        self.inner.on_usage(usage);
+    }
+
+    fn on_complete(&mut self, cost: Option<&CostInfo>, duration_ms: Option<u64>) {
+        self.touch();
+        self.inner.on_complete(cost, duration_ms);
+    }
+
+    fn on_parse_error(&mut self, line: &str, error: &serde_json::Error) {
+        self.touch();
+        self.inner.on_parse_error(line, error);
+    }
+
+    fn should_stop(&self) -> bool {
+        self.inner.should_stop()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::{Backend, MockBackend, MockScript, SpawnOptions};
+
+    struct Counting {
+        texts: usize,
+    }
+
+    impl StreamHandler for Counting {
+        fn on_assistant_text(&mut self, _text: &str) {
+            self.texts += 1;
+        }
+    }
+
+    async fn run(script: MockScript, config: WatchdogConfig) -> (Option<StopReason>, usize) {
+        let dir = tempfile::tempdir().unwrap();
+        let backend = MockBackend::new().with_script(script);
+        let mut handle = backend
+            .spawn("task", dir.path(), &SpawnOptions::new())
+            .await
+            .unwrap();
+        let stdout = handle.child_mut().stdout.take().unwrap();
+        let mut streamer = OutputStreamer::new(stdout);
+        let mut handler = Counting { texts: 0 };
+
+        let reason = Watchdog::new(config)
+            .supervise(&mut handle, &mut streamer, &mut handler)
+            .await
+            .unwrap();
+        handle.wait().await.unwrap();
+        (reason, handler.texts)
+    }
+
+    #[test]
+    fn test_stop_reason_strings() {
+        assert_eq!(StopReason::Timeout.as_str(), "timeout");
+        assert_eq!(StopReason::Stalled.to_string(), "stalled");
+    }
+
+    #[test]
+    fn test_check_interval() {
+        assert_eq!(WatchdogConfig::new().check_interval(), MAX_CHECK_INTERVAL);
+        let config = WatchdogConfig::new().with_stall_timeout(Duration::from_millis(200));
+        assert_eq!(config.check_interval(), Duration::from_millis(50));
+    }
+
+    #[tokio::test]
+    async fn test_completes_within_limits() {
+        let script = MockScript::new()
+            .assistant_text("one")
+            .assistant_text("two");
+        let config = WatchdogConfig::new().with_timeout(Duration::from_secs(30));
+        let (reason, texts) = run(script, config).await;
+        assert_eq!(reason, None);
+        assert_eq!(texts, 2);
+    }
+
+    #[tokio::test]
+    async fn test_stalled_agent_is_stopped() {
+        let script = MockScript::new()
+            .assistant_text("working")
+            .delay(Duration::from_secs(10))
+            .assistant_text("never seen");
+        let config = WatchdogConfig::new()
+            .with_stall_timeout(Duration::from_millis(200))
+            .with_grace_period(Duration::from_millis(100));
+        let started = Instant::now();
+        let (reason, texts) = run(script, config).await;
+        assert_eq!(reason, Some(StopReason::Stalled));
+        assert_eq!(texts, 1);
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+
+    #[tokio::test]
+    async fn test_wall_clock_timeout() {
+        let mut script = MockScript::new();
+        for _ in 0..50 {
+            script = script
+                .assistant_text("tick")
+                .delay(Duration::from_millis(50));
+        }
+        let config = WatchdogConfig::new()
+            .with_timeout(Duration::from_millis(300))
+            .with_stall_timeout(Duration::from_secs(5))
+            .with_grace_period(Duration::from_millis(100));
+        let (reason, texts) = run(script, config).await;
+        assert_eq!(reason, Some(StopReason::Timeout));
+        assert!(texts < 50);
+    }
+}
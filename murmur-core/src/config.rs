@@ -7,10 +7,12 @@
 //! 4. Default values
 
 use std::path::PathBuf;
+use std::time::Duration;
 
 use serde::{Deserialize, Serialize};
 
-use crate::agent::AgentType;
+use crate::agent::{AgentType, Budget, ContextConfig, PricingTable, SandboxConfig, WatchdogConfig};
+use crate::workflow::{QualityConfig, TestConfig};
 use crate::{Error, Result};
 
 /// Backend type for agent execution
@@ -19,6 +21,7 @@ use crate::{Error, Result};
 pub enum Backend {
     Claude,
     Cursor,
+    Mock,
 }
 
 impl std::str::FromStr for Backend {
@@ -28,8 +31,9 @@ impl std::str::FromStr for Backend {
         match s.to_lowercase().as_str() {
             "claude" => Ok(Backend::Claude),
             "cursor" => Ok(Backend::Cursor),
+            "mock" => Ok(Backend::Mock),
             _ => Err(format!(
-                "Invalid backend: {}. Valid options: claude, cursor",
+                "Invalid backend: {}. Valid options: claude, cursor, mock",
                 s
             )),
         }
@@ -41,6 +45,7 @@ impl std::fmt::Display for Backend {
         match self {
             Backend::Claude => write!(f, "claude"),
             Backend::Cursor => write!(f, "cursor"),
+            Backend::Mock => write!(f, "mock"),
         }
     }
 }
@@ -54,6 +59,12 @@ pub struct TypeConfig {
 
     /// Override model for this agent type
     pub model: Option<String>,
+
+    /// Override the wall-clock timeout (seconds) for this agent type
+    pub timeout_secs: Option<u64>,
+
+    /// Override the no-output timeout (seconds) for this agent type
+    pub stall_timeout_secs: Option<u64>,
 }
 
 /// Fully resolved configuration for spawning an agent
@@ -68,7 +79,7 @@ pub struct ResolvedConfig {
     /// The model to use (resolved, may still be None to use backend default)
     pub model: Option<String>,
 
-    /// Path to the executable for this backend
+    /// Path to the executable for this backend (the fixture file for the mock backend)
     pub executable_path: String,
 }
 
@@ -88,6 +99,18 @@ pub struct AgentConfig {
     /// Path to the cursor executable (optional)
     pub cursor_path: Option<String>,
 
+    /// Path to the fixture replayed by the mock backend (optional)
+    pub mock_script: Option<String>,
+
+    /// Kill agents that run longer than this many seconds (optional)
+    pub timeout_secs: Option<u64>,
+
+    /// Kill agents that produce no output for this many seconds (optional)
+    pub stall_timeout_secs: Option<u64>,
+
+    /// Filesystem and network confinement for agent processes
+    pub sandbox: SandboxConfig,
+
     /// Configuration overrides for implement agent type
     pub implement: Option<TypeConfig>,
 
@@ -108,6 +131,10 @@ impl Default for AgentConfig {
             model: None, // Let claude use its default
             claude_path: "claude".to_string(),
             cursor_path: None,
+            mock_script: None,
+            timeout_secs: None,
+            stall_timeout_secs: None,
+            sandbox: SandboxConfig::default(),
             implement: None,
             test: None,
             review: None,
@@ -117,6 +144,16 @@ impl Default for AgentConfig {
 }
 
 impl AgentConfig {
+    /// Get the type-specific overrides for an agent type, if any
+    fn type_config(&self, agent_type: AgentType) -> &Option<TypeConfig> {
+        match agent_type {
+            AgentType::Implement => &self.implement,
+            AgentType::Test => &self.test,
+            AgentType::Review => &self.review,
+            AgentType::Coordinator => &self.coordinator,
+        }
+    }
+
     /// Resolve the effective configuration for a specific agent type
     ///
     /// Resolution chain: type-specific config → global config → defaults
@@ -128,12 +165,7 @@ impl AgentConfig {
     /// A fully resolved configuration with all values determined
     pub fn resolve_for_type(&self, agent_type: AgentType) -> ResolvedConfig {
         // Get the type-specific config if it exists
-        let type_config = match agent_type {
-            AgentType::Implement => &self.implement,
-            AgentType::Test => &self.test,
-            AgentType::Review => &self.review,
-            AgentType::Coordinator => &self.coordinator,
-        };
+        let type_config = self.type_config(agent_type);
 
         // Resolve backend: type-specific → global
         let backend = type_config
@@ -154,6 +186,7 @@ impl AgentConfig {
                 .cursor_path
                 .clone()
                 .unwrap_or_else(|| "cursor".to_string()),
+            Backend::Mock => self.mock_script.clone().unwrap_or_default(),
         };
 
         ResolvedConfig {
@@ -162,6 +195,28 @@ impl AgentConfig {
             executable_path,
         }
     }
+
+    /// Resolve the watchdog timeouts for a specific agent type
+    ///
+    /// Resolution chain: type-specific config → global config → no timeout
+    pub fn watchdog_for_type(&self, agent_type: AgentType) -> WatchdogConfig {
+        let type_config = self.type_config(agent_type).as_ref();
+        let timeout = type_config
+            .and_then(|tc| tc.timeout_secs)
+            .or(self.timeout_secs);
+        let stall_timeout = type_config
+            .and_then(|tc| tc.stall_timeout_secs)
+            .or(self.stall_timeout_secs);
+
+        let mut watchdog = WatchdogConfig::new();
+        if let Some(secs) = timeout {
+            watchdog = watchdog.with_timeout(Duration::from_secs(secs));
+        }
+        if let Some(secs) = stall_timeout {
+            watchdog = watchdog.with_stall_timeout(Duration::from_secs(secs));
+        }
+        watchdog
+    }
 }
 
 /// Workflow automation configuration
@@ -179,6 +234,9 @@ pub struct WorkflowConfig {
 
     /// Re-spawn agent to address review feedback (opt-in)
     pub auto_review_loop: bool,
+
+    /// Maximum review rounds before the review loop gives up
+    pub max_review_iterations: u32,
 }
 
 impl Default for WorkflowConfig {
@@ -188,10 +246,57 @@ impl Default for WorkflowConfig {
             auto_push: true,
             auto_pr: true,
             auto_review_loop: false,
+            max_review_iterations: 3,
         }
     }
 }
 
+/// Token and dollar spending limits
+///
+/// Token limits count input + output tokens; dollar limits use the cost
+/// reported by the backend or an estimate from the model's price.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct BudgetConfig {
+    /// Maximum tokens a single agent run may use
+    pub max_tokens_per_run: Option<u64>,
+
+    /// Maximum dollars a single agent run may spend
+    pub max_cost_per_run: Option<f64>,
+
+    /// Maximum tokens across all runs for one issue
+    pub max_tokens_per_issue: Option<u64>,
+
+    /// Maximum dollars across all runs for one issue
+    pub max_cost_per_issue: Option<f64>,
+
+    /// Maximum tokens across all runs of one orchestration
+    pub max_tokens_per_orchestration: Option<u64>,
+
+    /// Maximum dollars across all runs of one orchestration
+    pub max_cost_per_orchestration: Option<f64>,
+}
+
+impl BudgetConfig {
+    /// Budget for a single agent run
+    pub fn run(&self) -> Budget {
+        Budget::new(self.max_tokens_per_run, self.max_cost_per_run)
+    }
+
+    /// Budget for all runs of one issue
+    pub fn issue(&self) -> Budget {
+        Budget::new(self.max_tokens_per_issue, self.max_cost_per_issue)
+    }
+
+    /// Budget for one orchestration
+    pub fn orchestration(&self) -> Budget {
+        Budget::new(
+            self.max_tokens_per_orchestration,
+            self.max_cost_per_orchestration,
+        )
+    }
+}
+
 /// Root configuration structure
 #[derive(Debug, Clone, Default, Deserialize, Serialize)]
 #[serde(default)]
@@ -201,6 +306,21 @@ pub struct Config {
 
     /// Workflow automation configuration
     pub workflow: WorkflowConfig,
+
+    /// Spending limits
+    pub budget: BudgetConfig,
+
+    /// Per-model prices in dollars per million tokens
+    pub pricing: PricingTable,
+
+    /// Repository context injected into prompts
+    pub context: ContextConfig,
+
+    /// How tests are run in the TDD workflow
+    pub test: TestConfig,
+
+    /// Build, format and lint checks the agents' changes must pass
+    pub quality: QualityConfig,
 }
 
 impl Config {
@@ -238,7 +358,8 @@ impl Config {
     /// Supported variables:
     /// - MURMUR_CLAUDE_PATH: Path to claude executable
     /// - MURMUR_MODEL: Model to use
-    /// - MURMUR_BACKEND: Backend to use (claude or cursor)
+    /// - MURMUR_BACKEND: Backend to use (claude, cursor or mock)
+    /// - MURMUR_MOCK_SCRIPT: Fixture replayed by the mock backend
     pub fn with_env_overrides(mut self) -> Self {
         if let Ok(claude_path) = std::env::var("MURMUR_CLAUDE_PATH") {
             self.agent.claude_path = claude_path;
@@ -254,6 +375,10 @@ impl Config {
             }
         }
 
+        if let Ok(mock_script) = std::env::var("MURMUR_MOCK_SCRIPT") {
+            self.agent.mock_script = Some(mock_script);
+        }
+
         self
     }
 
@@ -336,6 +461,7 @@ model = "claude-sonnet-4-20250514"
 auto_commit = false
 auto_push = false
 auto_pr = true
+max_review_iterations = 5
 "#;
         let config: Config = toml::from_str(toml).unwrap();
         assert_eq!(config.agent.claude_path, "/usr/local/bin/claude");
@@ -346,6 +472,7 @@ auto_pr = true
         assert!(!config.workflow.auto_commit);
         assert!(!config.workflow.auto_push);
         assert!(config.workflow.auto_pr);
+        assert_eq!(config.workflow.max_review_iterations, 5);
     }
 
     #[test]
@@ -521,6 +648,10 @@ model = "claude-haiku-4-20250514"
             model: Some("global-model".to_string()),
             claude_path: "/usr/bin/claude".to_string(),
             cursor_path: Some("/usr/bin/cursor".to_string()),
+            mock_script: None,
+            timeout_secs: None,
+            stall_timeout_secs: None,
+            sandbox: SandboxConfig::default(),
             implement: None,
             test: None,
             review: None,
@@ -541,9 +672,15 @@ model = "claude-haiku-4-20250514"
             model: Some("global-model".to_string()),
             claude_path: "/usr/bin/claude".to_string(),
             cursor_path: Some("/usr/bin/cursor".to_string()),
+            mock_script: None,
+            timeout_secs: None,
+            stall_timeout_secs: None,
+            sandbox: SandboxConfig::default(),
             implement: Some(TypeConfig {
                 backend: Some(Backend::Cursor),
                 model: Some("implement-model".to_string()),
+                timeout_secs: None,
+                stall_timeout_secs: None,
             }),
             test: None,
             review: None,
@@ -575,10 +712,16 @@ model = "claude-haiku-4-20250514"
             model: Some("global-model".to_string()),
             claude_path: "/usr/bin/claude".to_string(),
             cursor_path: Some("/usr/bin/cursor".to_string()),
+            mock_script: None,
+            timeout_secs: None,
+            stall_timeout_secs: None,
+            sandbox: SandboxConfig::default(),
             implement: None,
             test: Some(TypeConfig {
                 backend: None,                         // Don't override backend
                 model: Some("test-model".to_string()), // Only override model
+                timeout_secs: None,
+                stall_timeout_secs: None,
             }),
             review: None,
             coordinator: None,
@@ -598,21 +741,33 @@ model = "claude-haiku-4-20250514"
             model: Some("global-model".to_string()),
             claude_path: "claude".to_string(),
             cursor_path: None,
+            mock_script: None,
+            timeout_secs: None,
+            stall_timeout_secs: None,
+            sandbox: SandboxConfig::default(),
             implement: Some(TypeConfig {
                 backend: None,
                 model: Some("impl-model".to_string()),
+                timeout_secs: None,
+                stall_timeout_secs: None,
             }),
             test: Some(TypeConfig {
                 backend: None,
                 model: Some("test-model".to_string()),
+                timeout_secs: None,
+                stall_timeout_secs: None,
             }),
             review: Some(TypeConfig {
                 backend: None,
                 model: Some("review-model".to_string()),
+                timeout_secs: None,
+                stall_timeout_secs: None,
             }),
             coordinator: Some(TypeConfig {
                 backend: None,
                 model: Some("coord-model".to_string()),
+                timeout_secs: None,
+                stall_timeout_secs: None,
             }),
         };
 
@@ -637,9 +792,15 @@ model = "claude-haiku-4-20250514"
             model: Some("global-model".to_string()),
             claude_path: "/usr/bin/claude".to_string(),
             cursor_path: Some("/usr/local/bin/cursor".to_string()),
+            mock_script: None,
+            timeout_secs: None,
+            stall_timeout_secs: None,
+            sandbox: SandboxConfig::default(),
             implement: Some(TypeConfig {
                 backend: Some(Backend::Cursor),
                 model: None,
+                timeout_secs: None,
+                stall_timeout_secs: None,
             }),
             test: None,
             review: None,
@@ -660,9 +821,15 @@ model = "claude-haiku-4-20250514"
             model: None,
             claude_path: "claude".to_string(),
             cursor_path: None, // No cursor path set
+            mock_script: None,
+            timeout_secs: None,
+            stall_timeout_secs: None,
+            sandbox: SandboxConfig::default(),
             implement: Some(TypeConfig {
                 backend: Some(Backend::Cursor),
                 model: None,
+                timeout_secs: None,
+                stall_timeout_secs: None,
             }),
             test: None,
             review: None,
@@ -708,6 +875,7 @@ model = "claude-haiku-4-20250514"
         assert_eq!(Backend::from_str("CURSOR").unwrap(), Backend::Cursor);
         assert_eq!(Backend::from_str("Claude").unwrap(), Backend::Claude);
         assert_eq!(Backend::from_str("Cursor").unwrap(), Backend::Cursor);
+        assert_eq!(Backend::from_str("mock").unwrap(), Backend::Mock);
 
         assert!(Backend::from_str("invalid").is_err());
         assert!(Backend::from_str("").is_err());
@@ -717,6 +885,151 @@ model = "claude-haiku-4-20250514"
     fn test_backend_display() {
         assert_eq!(Backend::Claude.to_string(), "claude");
         assert_eq!(Backend::Cursor.to_string(), "cursor");
+        assert_eq!(Backend::Mock.to_string(), "mock");
+    }
+
+    #[test]
+    fn test_mock_backend_config() {
+        let toml = r#"
+[agent]
+backend = "mock"
+mock_script = "tests/fixtures/implement.toml"
+"#;
+        let config: Config = toml::from_str(toml).unwrap();
+        assert_eq!(config.agent.backend, Backend::Mock);
+
+        let resolved = config.agent.resolve_for_type(AgentType::Implement);
+        assert_eq!(resolved.backend, Backend::Mock);
+        assert_eq!(resolved.executable_path, "tests/fixtures/implement.toml");
+    }
+
+    #[test]
+    fn test_watchdog_for_type() {
+        let toml = r#"
+[agent]
+timeout_secs = 3600
+stall_timeout_secs = 300
+
+[agent.review]
+timeout_secs = 600
+"#;
+        let config: Config = toml::from_str(toml).unwrap();
+
+        let implement = config.agent.watchdog_for_type(AgentType::Implement);
+        assert_eq!(implement.timeout, Some(Duration::from_secs(3600)));
+        assert_eq!(implement.stall_timeout, Some(Duration::from_secs(300)));
+
+        let review = config.agent.watchdog_for_type(AgentType::Review);
+        assert_eq!(review.timeout, Some(Duration::from_secs(600)));
+        assert_eq!(review.stall_timeout, Some(Duration::from_secs(300)));
+
+        assert!(!Config::default()
+            .agent
+            .watchdog_for_type(AgentType::Test)
+            .is_enabled());
+    }
+
+    #[test]
+    fn test_sandbox_config() {
+        let toml = r#"
+[agent.sandbox]
+mode = "isolated"
+network = "offline"
+read_only_paths = ["/srv/sdk"]
+"#;
+        let config: Config = toml::from_str(toml).unwrap();
+        let sandbox = &config.agent.sandbox;
+        assert_eq!(sandbox.mode, crate::agent::SandboxMode::Isolated);
+        assert_eq!(sandbox.network, crate::agent::NetworkPolicy::Offline);
+        assert_eq!(sandbox.bwrap_path, "bwrap");
+        assert!(sandbox
+            .allowed_hosts
+            .contains(&"api.anthropic.com".to_string()));
+
+        assert!(sandbox.for_agent_type(AgentType::Implement).is_none());
+        let review = sandbox.for_agent_type(AgentType::Review).unwrap();
+        assert!(!review.workdir_writable());
+
+        assert_eq!(
+            Config::default().agent.sandbox.mode,
+            crate::agent::SandboxMode::Off
+        );
+    }
+
+    #[test]
+    fn test_budget_config() {
+        let toml = r#"
+[budget]
+max_cost_per_run = 2.5
+max_tokens_per_issue = 1000000
+"#;
+        let config: Config = toml::from_str(toml).unwrap();
+        assert_eq!(config.budget.run(), Budget::new(None, Some(2.5)));
+        assert_eq!(config.budget.issue(), Budget::new(Some(1_000_000), None));
+        assert!(config.budget.orchestration().is_unlimited());
+        assert!(Config::default().budget.run().is_unlimited());
+    }
+
+    #[test]
+    fn test_pricing_config() {
+        let toml = r#"
+[agent.review]
+model = "claude-haiku-4-20250514"
+
+[pricing]
+haiku = { input = 1.0, output = 5.0 }
+"#;
+        let config: Config = toml::from_str(toml).unwrap();
+        let model = config.agent.resolve_for_type(AgentType::Review).model;
+        let price = config.pricing.price_for(model.as_deref());
+        assert_eq!(price.input, 1.0);
+        assert_eq!(price.output, 5.0);
+    }
+
+    #[test]
+    fn test_context_config() {
+        let toml = r#"
+[context]
+max_tokens = 2000
+files = ["CONTRIBUTING.md"]
+dependency_diffs = false
+"#;
+        let config: Config = toml::from_str(toml).unwrap();
+        assert!(config.context.enabled);
+        assert_eq!(config.context.max_tokens, 2000);
+        assert_eq!(config.context.files, ["CONTRIBUTING.md"]);
+        assert_eq!(config.context.commit_log, 10);
+        assert!(!config.context.dependency_diffs);
+    }
+
+    #[test]
+    fn test_test_config() {
+        let toml = r#"
+[test]
+timeout_secs = 900
+
+[[test.suites]]
+path = "backend"
+framework = "maven"
+
+[[test.suites]]
+path = "e2e"
+command = "./run-e2e.sh"
+format = "junit"
+report = "results/junit.xml"
+"#;
+        let config: Config = toml::from_str(toml).unwrap();
+        assert_eq!(config.test.timeout_secs, Some(900));
+        assert_eq!(config.test.suites.len(), 2);
+        assert_eq!(
+            config.test.suites[0].framework,
+            Some(crate::workflow::TestFramework::Maven)
+        );
+        assert_eq!(
+            config.test.suites[1].command.as_deref(),
+            Some("./run-e2e.sh")
+        );
+        assert!(Config::default().test.suites.is_empty());
     }
 
     #[test]
@@ -24,6 +24,10 @@ pub enum Error {
     #[error("Configuration error: {0}")]
     Config(String),
 
+    /// Prompt template error
+    #[error("Template error: {0}")]
+    Template(String),
+
     /// Generic error with message
     #[error("{0}")]
     Other(String),
@@ -27,7 +27,8 @@ pub use git::{
 pub use plan::{parse_plan, Phase, Plan, PlannedPR};
 pub use secrets::{GitHubSecrets, Secrets};
 pub use workflow::{
-    build_resume_prompt, find_incomplete_runs, find_latest_incomplete_run,
-    reconstruct_conversation, ConversationMessage, PhaseValidation, ResumableRun, StateMachine,
-    TddPhase, TddState, TddTransition, TddWorkflow, Workflow,
+    build_resume_prompt, build_session_resume_prompt, find_incomplete_runs,
+    find_latest_incomplete_run, reconstruct_conversation, ConversationMessage, PhaseValidation,
+    ResumableRun, StateMachine, TddExecutor, TddObserver, TddOutcome, TddPhase, TddState,
+    TddTransition, TddWorkflow, Workflow,
 };
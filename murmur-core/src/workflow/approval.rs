@@ -0,0 +1,369 @@
+//! Human approval gates between workflow phases
+//!
+//! With [`ApprovalGates`] attached, a workflow stores a pending [`Approval`]
+//! in the database before it starts a gated phase and waits until someone
+//! decides it, with `murmur approve` / `murmur reject` or through an
+//! [`ApprovalSource`] such as `/murmur approve` comments on the issue. The
+//! reason given for a rejection goes back to the agent as retry context.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use murmur_db::models::Approval;
+use murmur_db::repos::ApprovalRepository;
+use murmur_db::Database;
+
+use crate::{Error, Result};
+
+/// How often a waiting workflow checks for a decision by default
+const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Command that approves a transition in a comment
+const APPROVE_COMMAND: &str = "/murmur approve";
+
+/// Command that rejects a transition in a comment
+const REJECT_COMMAND: &str = "/murmur reject";
+
+/// The decision on a gated transition
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ApprovalDecision {
+    /// Go ahead with the next phase
+    Approved,
+    /// Redo the work, for the given reason
+    Rejected(String),
+}
+
+impl ApprovalDecision {
+    /// The decision recorded on an approval, if it has been decided
+    pub fn of(approval: &Approval) -> Option<Self> {
+        match approval.status.as_str() {
+            "approved" => Some(ApprovalDecision::Approved),
+            "rejected" => Some(ApprovalDecision::Rejected(
+                approval
+                    .reason
+                    .clone()
+                    .unwrap_or_else(|| "No reason given".to_string()),
+            )),
+            _ => None,
+        }
+    }
+}
+
+/// A place outside murmur where approvals can be decided
+#[async_trait]
+pub trait ApprovalSource: Send + Sync {
+    /// Look for a decision on approval `id` made since `requested_at`
+    ///
+    /// Returns the decision and who made it.
+    async fn check(
+        &self,
+        id: i64,
+        requested_at: DateTime<Utc>,
+    ) -> Result<Option<(ApprovalDecision, String)>>;
+}
+
+/// The phases a workflow waits for human approval before starting
+#[derive(Clone)]
+pub struct ApprovalGates {
+    phases: Vec<String>,
+    poll_interval: Duration,
+    source: Option<Arc<dyn ApprovalSource>>,
+}
+
+impl Default for ApprovalGates {
+    fn default() -> Self {
+        Self {
+            phases: Vec::new(),
+            poll_interval: DEFAULT_POLL_INTERVAL,
+            source: None,
+        }
+    }
+}
+
+impl ApprovalGates {
+    /// Wait for approval before starting any of `phases`
+    ///
+    /// Phases are named as they are persisted, e.g. `write_tests` or
+    /// `creating_pr`.
+    pub fn before<I, S>(phases: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self {
+            phases: phases.into_iter().map(Into::into).collect(),
+            ..Self::default()
+        }
+    }
+
+    /// Check for a decision this often while waiting
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    /// Also accept decisions made through `source`
+    pub fn with_source(mut self, source: impl ApprovalSource + 'static) -> Self {
+        self.source = Some(Arc::new(source));
+        self
+    }
+
+    /// Whether starting `phase` needs approval
+    pub fn is_gated(&self, phase: &str) -> bool {
+        self.phases.iter().any(|p| p == phase)
+    }
+
+    /// Whether no phase needs approval
+    pub fn is_empty(&self) -> bool {
+        self.phases.is_empty()
+    }
+
+    /// Store `approval` as pending and wait until it is decided
+    ///
+    /// A resumed workflow picks up the pending approval it was waiting for
+    /// rather than asking again. `on_pending` is called with the approval's
+    /// ID once it is stored.
+    pub(crate) async fn wait(
+        &self,
+        db: Option<&Database>,
+        approval: Approval,
+        on_pending: impl FnOnce(i64),
+    ) -> Result<ApprovalDecision> {
+        let db = db.ok_or_else(|| {
+            Error::Config("Approval gates need a database to store pending approvals".to_string())
+        })?;
+        let repo = ApprovalRepository::new(db);
+        let existing = repo
+            .find_pending()
+            .map_err(|e| Error::Other(format!("Failed to load approvals: {}", e)))?
+            .into_iter()
+            .find(|a| {
+                a.workflow == approval.workflow
+                    && a.workdir == approval.workdir
+                    && a.from_phase == approval.from_phase
+                    && a.to_phase == approval.to_phase
+            })
+            .and_then(|a| a.id);
+        let id = match existing {
+            Some(id) => id,
+            None => repo
+                .insert(&approval)
+                .map_err(|e| Error::Other(format!("Failed to save approval: {}", e)))?,
+        };
+        on_pending(id);
+
+        loop {
+            let mut current = repo
+                .find_by_id(id)
+                .map_err(|e| Error::Other(format!("Failed to load approval {}: {}", id, e)))?
+                .ok_or_else(|| Error::Other(format!("Approval {} was deleted", id)))?;
+            if let Some(decision) = ApprovalDecision::of(&current) {
+                return Ok(decision);
+            }
+
+            if let Some(ref source) = self.source {
+                match source.check(id, current.created_at).await {
+                    Ok(Some((decision, by))) => {
+                        match decision {
+                            ApprovalDecision::Approved => current.approve(by),
+                            ApprovalDecision::Rejected(ref reason) => current.reject(reason, by),
+                        }
+                        if let Err(e) = repo.update(&current) {
+                            tracing::warn!(error = %e, "Failed to record approval decision");
+                        }
+                        return Ok(decision);
+                    }
+                    Ok(None) => {}
+                    Err(e) => tracing::warn!(error = %e, "Failed to check for approval"),
+                }
+            }
+
+            tokio::time::sleep(self.poll_interval).await;
+        }
+    }
+}
+
+/// Parse a `/murmur approve` or `/murmur reject` command in a comment
+///
+/// Either command may name the approval it is for (`/murmur approve 12`).
+/// Whatever follows `/murmur reject`, including later lines, is the reason.
+/// Returns the approval ID, if given, and the decision.
+pub fn parse_approval_command(text: &str) -> Option<(Option<i64>, ApprovalDecision)> {
+    let mut lines = text.lines();
+    while let Some(line) = lines.next() {
+        let line = line.trim();
+        let (rest, approve) = if let Some(rest) = line.strip_prefix(APPROVE_COMMAND) {
+            (rest, true)
+        } else if let Some(rest) = line.strip_prefix(REJECT_COMMAND) {
+            (rest, false)
+        } else {
+            continue;
+        };
+        // "/murmur approved" is not a command
+        if !rest.is_empty() && !rest.starts_with(char::is_whitespace) {
+            continue;
+        }
+
+        let rest = rest.trim_start();
+        let (id, rest) = match rest.split_once(char::is_whitespace) {
+            Some((word, tail)) => match word.trim_start_matches('#').parse::<i64>() {
+                Ok(id) => (Some(id), tail),
+                Err(_) => (None, rest),
+            },
+            None => match rest.trim_start_matches('#').parse::<i64>() {
+                Ok(id) => (Some(id), ""),
+                Err(_) => (None, rest),
+            },
+        };
+
+        if approve {
+            return Some((id, ApprovalDecision::Approved));
+        }
+        let remaining: Vec<&str> = lines.collect();
+        let reason = format!("{}\n{}", rest, remaining.join("\n"))
+            .trim()
+            .to_string();
+        let reason = if reason.is_empty() {
+            "No reason given".to_string()
+        } else {
+            reason
+        };
+        return Some((id, ApprovalDecision::Rejected(reason)));
+    }
+    None
+}
+
+/// Decides approvals with canned decisions, one per check
+#[cfg(test)]
+pub(crate) struct Decisions(pub(crate) std::sync::Mutex<Vec<ApprovalDecision>>);
+
+#[cfg(test)]
+#[async_trait]
+impl ApprovalSource for Decisions {
+    async fn check(
+        &self,
+        _id: i64,
+        _requested_at: DateTime<Utc>,
+    ) -> Result<Option<(ApprovalDecision, String)>> {
+        let mut decisions = self.0.lock().unwrap();
+        Ok((!decisions.is_empty()).then(|| (decisions.remove(0), "alice".to_string())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    /// Source that gives out canned decisions, one per check
+    struct Canned(Mutex<Vec<Option<(ApprovalDecision, String)>>>);
+
+    #[async_trait]
+    impl ApprovalSource for Canned {
+        async fn check(
+            &self,
+            _id: i64,
+            _requested_at: DateTime<Utc>,
+        ) -> Result<Option<(ApprovalDecision, String)>> {
+            let mut decisions = self.0.lock().unwrap();
+            Ok(if decisions.is_empty() {
+                None
+            } else {
+                decisions.remove(0)
+            })
+        }
+    }
+
+    fn approval() -> Approval {
+        Approval::new("tdd", "Add parser", "/tmp", "write_spec", "write_tests")
+    }
+
+    #[test]
+    fn test_parse_approval_command() {
+        assert_eq!(
+            parse_approval_command("/murmur approve"),
+            Some((None, ApprovalDecision::Approved))
+        );
+        assert_eq!(
+            parse_approval_command("Looks good\n/murmur approve #12"),
+            Some((Some(12), ApprovalDecision::Approved))
+        );
+        assert_eq!(
+            parse_approval_command("/murmur reject 3 Cover empty input\nand unicode"),
+            Some((
+                Some(3),
+                ApprovalDecision::Rejected("Cover empty input\nand unicode".to_string())
+            ))
+        );
+        assert_eq!(
+            parse_approval_command("/murmur reject"),
+            Some((
+                None,
+                ApprovalDecision::Rejected("No reason given".to_string())
+            ))
+        );
+        assert_eq!(parse_approval_command("/murmur approved it already"), None);
+        assert_eq!(parse_approval_command("please approve"), None);
+    }
+
+    #[test]
+    fn test_gates() {
+        let gates = ApprovalGates::before(["write_tests", "creating_pr"]);
+        assert!(gates.is_gated("write_tests"));
+        assert!(!gates.is_gated("implement"));
+        assert!(ApprovalGates::default().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_decision_in_database() {
+        let db = Database::in_memory().unwrap();
+        let gates = ApprovalGates::before(["write_tests"]);
+
+        let decision = gates
+            .wait(Some(&db), approval(), |id| {
+                let repo = ApprovalRepository::new(&db);
+                let mut approval = repo.find_by_id(id).unwrap().unwrap();
+                approval.reject("Spec misses the error cases", "alice");
+                repo.update(&approval).unwrap();
+            })
+            .await
+            .unwrap();
+        assert_eq!(
+            decision,
+            ApprovalDecision::Rejected("Spec misses the error cases".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_decision_from_source() {
+        let db = Database::in_memory().unwrap();
+        let gates = ApprovalGates::before(["write_tests"])
+            .with_poll_interval(Duration::from_millis(1))
+            .with_source(Canned(Mutex::new(vec![
+                None,
+                Some((ApprovalDecision::Approved, "bob".to_string())),
+            ])));
+
+        let mut pending = None;
+        let decision = gates
+            .wait(Some(&db), approval(), |id| pending = Some(id))
+            .await
+            .unwrap();
+        assert_eq!(decision, ApprovalDecision::Approved);
+
+        let stored = ApprovalRepository::new(&db)
+            .find_by_id(pending.unwrap())
+            .unwrap()
+            .unwrap();
+        assert!(stored.is_approved());
+        assert_eq!(stored.decided_by.as_deref(), Some("bob"));
+    }
+
+    #[tokio::test]
+    async fn test_wait_needs_database() {
+        let gates = ApprovalGates::before(["write_tests"]);
+        assert!(gates.wait(None, approval(), |_| {}).await.is_err());
+    }
+}
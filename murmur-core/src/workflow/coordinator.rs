@@ -5,7 +5,11 @@
 //! 2. Delegates to appropriate agents (Implement, Test, Review)
 //! 3. Manages worktrees and branches
 //! 4. Creates PRs when work is complete
+//!
+//! [`CoordinatorWorkflow`] tracks the phases and builds the prompts; the
+//! [`CoordinatorExecutor`](super::CoordinatorExecutor) carries them out.
 
+use super::subtask_parser::PLAN_FORMAT_INSTRUCTIONS;
 use crate::agent::{AgentFactory, AgentType, CoordinatorAgent};
 use crate::config::AgentConfig;
 use serde::{Deserialize, Serialize};
@@ -61,6 +65,20 @@ impl CoordinatorPhase {
         }
     }
 
+    /// Get the identifier used when persisting the phase
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CoordinatorPhase::Planning => "planning",
+            CoordinatorPhase::SetupWorktree => "setup_worktree",
+            CoordinatorPhase::Implementing => "implementing",
+            CoordinatorPhase::Testing => "testing",
+            CoordinatorPhase::Reviewing => "reviewing",
+            CoordinatorPhase::CreatingPR => "creating_pr",
+            CoordinatorPhase::Complete => "complete",
+            CoordinatorPhase::Failed => "failed",
+        }
+    }
+
     /// Check if this is a terminal state
     pub fn is_terminal(&self) -> bool {
         matches!(self, CoordinatorPhase::Complete | CoordinatorPhase::Failed)
@@ -73,6 +91,25 @@ impl std::fmt::Display for CoordinatorPhase {
     }
 }
 
+impl std::str::FromStr for CoordinatorPhase {
+    type Err = String;
+
+    /// Parse a phase from its persisted name, e.g. `creating_pr` or `creating-pr`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().replace('-', "_").as_str() {
+            "planning" => Ok(CoordinatorPhase::Planning),
+            "setup_worktree" => Ok(CoordinatorPhase::SetupWorktree),
+            "implementing" => Ok(CoordinatorPhase::Implementing),
+            "testing" => Ok(CoordinatorPhase::Testing),
+            "reviewing" => Ok(CoordinatorPhase::Reviewing),
+            "creating_pr" => Ok(CoordinatorPhase::CreatingPR),
+            "complete" => Ok(CoordinatorPhase::Complete),
+            "failed" => Ok(CoordinatorPhase::Failed),
+            _ => Err(format!("Unknown coordinator phase: {}", s)),
+        }
+    }
+}
+
 /// A subtask identified during planning
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SubTask {
@@ -140,6 +177,7 @@ impl SubTask {
 
 /// Configuration for the coordinator workflow
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct CoordinatorConfig {
     /// Whether to use TDD workflow
     pub use_tdd: bool,
@@ -153,6 +191,10 @@ pub struct CoordinatorConfig {
     pub repo: Option<String>,
     /// Main branch name
     pub main_branch: String,
+    /// Maximum subtasks worked on at the same time
+    pub max_parallel: usize,
+    /// Run concurrent subtasks in worktrees of their own and merge them back
+    pub isolate_subtasks: bool,
 }
 
 impl Default for CoordinatorConfig {
@@ -164,6 +206,8 @@ impl Default for CoordinatorConfig {
             max_retries: 2,
             repo: None,
             main_branch: "main".to_string(),
+            max_parallel: 3,
+            isolate_subtasks: false,
         }
     }
 }
@@ -181,6 +225,15 @@ pub struct CoordinatorState {
     pub worktree_path: Option<PathBuf>,
     /// Branch name
     pub branch_name: Option<String>,
+    /// Commit the worktree branched from
+    #[serde(default)]
+    pub base_commit: Option<String>,
+    /// The coordinator agent's plan from the Planning phase
+    #[serde(default)]
+    pub plan: Option<String>,
+    /// URL of the pull request, once created
+    #[serde(default)]
+    pub pr_url: Option<String>,
     /// Subtasks identified during planning
     pub subtasks: Vec<SubTask>,
     /// Completed subtask IDs
@@ -193,6 +246,9 @@ pub struct CoordinatorState {
     pub config: CoordinatorConfig,
     /// Any error message
     pub error: Option<String>,
+    /// Why a human rejected the last attempt at the current phase's work
+    #[serde(default)]
+    pub rejection: Option<String>,
 }
 
 /// A phase transition in the workflow
@@ -213,12 +269,16 @@ impl CoordinatorState {
             project_dir: project_dir.into(),
             worktree_path: None,
             branch_name: None,
+            base_commit: None,
+            plan: None,
+            pr_url: None,
             subtasks: Vec::new(),
             completed_subtasks: Vec::new(),
             history: Vec::new(),
             retries: 0,
             config: CoordinatorConfig::default(),
             error: None,
+            rejection: None,
         }
     }
 
@@ -252,6 +312,20 @@ impl CoordinatorState {
         }
     }
 
+    /// Go back to an earlier phase to redo its work after a rejection
+    pub fn send_back(&mut self, target: CoordinatorPhase, reason: impl Into<String>) {
+        let reason = reason.into();
+        self.history.push(PhaseTransition {
+            from: self.phase,
+            to: target,
+            success: false,
+            message: Some(reason.clone()),
+        });
+        self.phase = target;
+        self.retries = 0;
+        self.rejection = Some(reason);
+    }
+
     /// Retry the current phase
     pub fn retry(&mut self) -> bool {
         if self.retries < self.config.max_retries {
@@ -278,9 +352,31 @@ impl CoordinatorState {
         self.branch_name = Some(branch);
     }
 
-    /// Add subtasks from planning
+    /// Add subtasks from planning, replacing those of an earlier plan
     pub fn set_subtasks(&mut self, subtasks: Vec<SubTask>) {
         self.subtasks = subtasks;
+        self.completed_subtasks.clear();
+    }
+
+    /// Mark a subtask as in progress
+    pub fn start_subtask(&mut self, id: &str) {
+        if let Some(subtask) = self.subtasks.iter_mut().find(|s| s.id == id) {
+            subtask.status = SubTaskStatus::InProgress;
+        }
+    }
+
+    /// Mark a subtask as failed
+    pub fn fail_subtask(&mut self, id: &str) {
+        if let Some(subtask) = self.subtasks.iter_mut().find(|s| s.id == id) {
+            subtask.status = SubTaskStatus::Failed;
+        }
+    }
+
+    /// Put a subtask back to pending so it runs again
+    pub fn reset_subtask(&mut self, id: &str) {
+        if let Some(subtask) = self.subtasks.iter_mut().find(|s| s.id == id) {
+            subtask.status = SubTaskStatus::Pending;
+        }
     }
 
     /// Mark a subtask as complete
@@ -298,6 +394,14 @@ impl CoordinatorState {
             .find(|s| s.is_ready(&self.completed_subtasks))
     }
 
+    /// Get every subtask whose dependencies are complete
+    pub fn ready_subtasks(&self) -> Vec<&SubTask> {
+        self.subtasks
+            .iter()
+            .filter(|s| s.is_ready(&self.completed_subtasks))
+            .collect()
+    }
+
     /// Check if all subtasks are complete
     pub fn all_subtasks_complete(&self) -> bool {
         self.subtasks
@@ -356,7 +460,25 @@ impl CoordinatorWorkflow {
     }
 
     /// Get the prompt for the current phase
+    ///
+    /// When a human rejected the last attempt, the reason is included.
     pub fn current_prompt(&self) -> String {
+        self.with_rejection(self.phase_prompt())
+    }
+
+    /// Add the reason for the last rejection, if any, to a prompt
+    pub fn with_rejection(&self, prompt: String) -> String {
+        match self.state.rejection {
+            Some(ref reason) => format!(
+                "{}\n\n## Rejected\n\nA reviewer rejected the previous attempt:\n\n{}",
+                prompt,
+                reason.trim()
+            ),
+            None => prompt,
+        }
+    }
+
+    fn phase_prompt(&self) -> String {
         match self.state.phase {
             CoordinatorPhase::Planning => self.planning_prompt(),
             CoordinatorPhase::SetupWorktree => self.worktree_prompt(),
@@ -379,9 +501,8 @@ impl CoordinatorWorkflow {
              1. What needs to be done\n\
              2. Which files are involved\n\
              3. What type of work it is (implement, test, review)\n\
-             4. Dependencies on other subtasks\n\n\
-             Output your plan in a structured format.",
-            self.state.task
+             4. Dependencies on other subtasks\n\n{}",
+            self.state.task, PLAN_FORMAT_INSTRUCTIONS
         )
     }
 
@@ -391,15 +512,36 @@ impl CoordinatorWorkflow {
 
     fn implement_prompt(&self) -> String {
         if let Some(subtask) = self.state.next_subtask() {
-            format!(
-                "Implement the following subtask:\n\n{}\n\nFiles: {:?}",
-                subtask.description, subtask.files
-            )
+            self.subtask_prompt(subtask)
+        } else if self.state.subtasks.is_empty() || self.state.rejection.is_some() {
+            let mut prompt = format!("Implement the following task:\n\n{}", self.state.task);
+            if let Some(ref plan) = self.state.plan {
+                prompt.push_str(&format!("\n\n## Plan\n\n{}", plan.trim()));
+            }
+            prompt
         } else {
             "Implementation phase - no subtasks remaining.".to_string()
         }
     }
 
+    /// Get the prompt for working on one subtask
+    pub fn subtask_prompt(&self, subtask: &SubTask) -> String {
+        let verb = match subtask.agent_type {
+            AgentType::Test => "Write tests for",
+            AgentType::Review => "Review",
+            _ => "Implement",
+        };
+        let mut prompt = format!("{} the following subtask:\n\n{}", verb, subtask.description);
+        if !subtask.files.is_empty() {
+            prompt.push_str(&format!("\n\nFiles: {}", subtask.files.join(", ")));
+        }
+        prompt.push_str(&format!(
+            "\n\nIt is part of this task:\n\n{}",
+            self.state.task
+        ));
+        prompt
+    }
+
     fn test_prompt(&self) -> String {
         "Run the test suite and verify all tests pass.".to_string()
     }
@@ -423,6 +565,11 @@ impl CoordinatorWorkflow {
         self.state.advance(success, message)
     }
 
+    /// Go back to an earlier phase to redo its work after a rejection
+    pub fn send_back(&mut self, target: CoordinatorPhase, reason: impl Into<String>) {
+        self.state.send_back(target, reason);
+    }
+
     /// Retry the current phase
     pub fn retry(&mut self) -> bool {
         self.state.retry()
@@ -516,6 +663,52 @@ mod tests {
         assert!(prompt.contains("subtasks"));
     }
 
+    #[test]
+    fn test_implement_prompt_without_subtasks() {
+        let mut workflow = CoordinatorWorkflow::new("Build feature", "/tmp/project");
+        workflow.state_mut().phase = CoordinatorPhase::Implementing;
+        workflow.state_mut().plan = Some("1. Add the parser\n".to_string());
+        let prompt = workflow.current_prompt();
+        assert!(prompt.contains("Implement the following task:\n\nBuild feature"));
+        assert!(prompt.ends_with("## Plan\n\n1. Add the parser"));
+
+        workflow
+            .state_mut()
+            .set_subtasks(vec![SubTask::new("task-1", "Add the parser")]);
+        assert!(workflow
+            .current_prompt()
+            .contains("subtask:\n\nAdd the parser"));
+        workflow.state_mut().complete_subtask("task-1");
+        assert!(workflow.current_prompt().contains("no subtasks remaining"));
+    }
+
+    #[test]
+    fn test_send_back_after_rejection() {
+        let mut workflow = CoordinatorWorkflow::new("Build feature", "/tmp/project");
+        workflow
+            .state_mut()
+            .set_subtasks(vec![SubTask::new("task-1", "Add the parser")]);
+        workflow.state_mut().complete_subtask("task-1");
+        workflow.state_mut().phase = CoordinatorPhase::Reviewing;
+
+        workflow.send_back(CoordinatorPhase::Implementing, "Handle empty input");
+        assert_eq!(workflow.phase(), CoordinatorPhase::Implementing);
+        let prompt = workflow.current_prompt();
+        assert!(prompt.contains("Implement the following task:\n\nBuild feature"));
+        assert!(prompt.ends_with("rejected the previous attempt:\n\nHandle empty input"));
+        assert!(!workflow.state().history.last().unwrap().success);
+    }
+
+    #[test]
+    fn test_phase_from_str() {
+        assert_eq!("creating-pr".parse(), Ok(CoordinatorPhase::CreatingPR));
+        assert_eq!(
+            CoordinatorPhase::SetupWorktree.as_str().parse(),
+            Ok(CoordinatorPhase::SetupWorktree)
+        );
+        assert!("write_tests".parse::<CoordinatorPhase>().is_err());
+    }
+
     #[test]
     fn test_subtask_completion() {
         let mut state = CoordinatorState::new("task", "/tmp");
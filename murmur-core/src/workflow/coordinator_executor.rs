@@ -0,0 +1,1806 @@
+//! Executor that drives a [`CoordinatorWorkflow`] through its phases
+//!
+//! [`CoordinatorExecutor`] carries out each phase for real: the coordinator
+//! agent plans the task, a worktree is created for the work, the implement
+//! agent (or a [`TddExecutor`] when `use_tdd` is set) makes the changes, the
+//! project's tests run with fix attempts on failure, a [`ReviewLoop`]
+//! reviews the result and the branch is pushed and opened as a pull request.
+//! The Reviewing and CreatingPR phases are skipped unless `run_review` and
+//! `auto_pr` are set.
+//!
+//! When the plan lists subtasks, ready subtasks run concurrently, up to
+//! `max_parallel` at a time. They share the worktree when the files they
+//! list don't overlap, or with `isolate_subtasks` each gets a worktree of its
+//! own that is merged back when it's done.
+//!
+//! Agents that fail and tests that keep failing are retried up to
+//! `max_retries` times per phase before the workflow moves to Failed.
+//!
+//! The [`QualityGates`] a `[quality]` config section asks for run once the
+//! tests pass and again before the pull request is opened, with failures
+//! handed to the implement agent to fix like failing tests.
+//!
+//! With [`ApprovalGates`] the executor waits for a human to approve before
+//! starting a gated phase. A rejection sends it back to Planning or
+//! Implementing, with the reason in the agent's prompt.
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::Mutex;
+
+use futures::future::join_all;
+
+use murmur_db::models::{Approval, WorktreeRecord};
+use murmur_db::repos::WorktreeRepository;
+use murmur_db::Database;
+
+use super::approval::{ApprovalDecision, ApprovalGates};
+use super::coordinator::{CoordinatorConfig, CoordinatorPhase, CoordinatorState};
+use super::coordinator::{CoordinatorWorkflow, SubTask};
+use super::quality::{QualityCheck, QualityConfig, QualityGates};
+use super::review_loop::{git, ReviewLoop, ReviewLoopOutcome};
+use super::runner::{AgentRunner, RunAccounting, SharedHandler};
+use super::subtask_parser::parse_subtasks;
+use super::tdd::{TddPhase, TddState};
+use super::tdd_executor::{TddExecutor, TddObserver};
+use super::test_config::TestConfig;
+use super::test_runner::{TestResults, TestRunner};
+use crate::agent::{AgentSpawner, AgentType, PromptBuilder, PromptTemplates, StreamHandler};
+use crate::config::AgentConfig;
+use crate::git::{
+    default_cache_dir, worktree_path, BranchingOptions, BranchingPoint, GitRepo, WorktreeInfo,
+    WorktreeOptions,
+};
+use crate::{Error, Result};
+
+/// Lines of test output included in a fix prompt when no failing tests are listed
+const FIX_PROMPT_OUTPUT_LINES: usize = 50;
+
+/// Callbacks for following a coordinator run
+///
+/// Every method has an empty default, so observers only implement the
+/// events they care about.
+pub trait CoordinatorObserver: Send {
+    /// Called when a phase starts
+    fn on_phase_start(&mut self, _phase: CoordinatorPhase, _state: &CoordinatorState) {}
+
+    /// Called when a phase is skipped because the configuration turns it off
+    fn on_phase_skipped(&mut self, _phase: CoordinatorPhase, _reason: &str) {}
+
+    /// Called before an agent is spawned
+    fn on_agent_start(&mut self, _phase: CoordinatorPhase, _agent_type: AgentType, _prompt: &str) {}
+
+    /// Called before a subtask from the plan is worked on
+    fn on_subtask_start(&mut self, _subtask: &SubTask) {}
+
+    /// Called when a TDD phase starts while implementing with `use_tdd`
+    fn on_tdd_phase(&mut self, _phase: TddPhase) {}
+
+    /// Called with each line of test output while the tests run
+    fn on_test_output(&mut self, _line: &str) {}
+
+    /// Called with the results of a test run
+    fn on_test_results(&mut self, _results: &TestResults) {}
+
+    /// Called with the outcome of the review loop
+    fn on_review(&mut self, _outcome: &ReviewLoopOutcome) {}
+
+    /// Called when a phase succeeds and the workflow advances
+    fn on_phase_complete(&mut self, _phase: CoordinatorPhase, _message: Option<&str>) {}
+
+    /// Called when a phase is attempted again after a failure
+    fn on_retry(&mut self, _phase: CoordinatorPhase, _reason: &str) {}
+
+    /// Called after a quality check runs, with the problem if it failed
+    fn on_quality_check(&mut self, _check: &QualityCheck, _problem: Option<&str>) {}
+
+    /// Called when a phase fails for good and the workflow stops
+    fn on_failed(&mut self, _phase: CoordinatorPhase, _error: &str) {}
+
+    /// Called when the workflow starts waiting for approval to start `to`
+    fn on_approval_pending(
+        &mut self,
+        _from: CoordinatorPhase,
+        _to: CoordinatorPhase,
+        _approval_id: i64,
+    ) {
+    }
+
+    /// Called with the decision on starting `to`
+    fn on_approval(&mut self, _to: CoordinatorPhase, _decision: &ApprovalDecision) {}
+}
+
+/// Observer that ignores every event
+impl CoordinatorObserver for () {}
+
+/// Outcome of a coordinator run
+#[derive(Debug, Clone)]
+pub struct CoordinatorOutcome {
+    /// Whether the workflow reached Complete
+    pub completed: bool,
+    /// The phase the workflow failed in, if it failed
+    pub failed_phase: Option<CoordinatorPhase>,
+    /// Why the workflow failed, if it failed
+    pub error: Option<String>,
+    /// The worktree the work was done in, once created
+    pub worktree_path: Option<PathBuf>,
+    /// The branch the work was done on, once created
+    pub branch_name: Option<String>,
+    /// URL of the pull request, if one was created
+    pub pr_url: Option<String>,
+    /// Results of the last test run, if any
+    pub last_results: Option<TestResults>,
+}
+
+/// What a phase did
+enum Step {
+    /// The phase ran, with an optional note for the history
+    Done(Option<String>),
+    /// The configuration turned the phase off
+    Skipped(String),
+}
+
+/// Runs a coordinator workflow's agents, tests, review and PR creation
+pub struct CoordinatorExecutor {
+    workflow: CoordinatorWorkflow,
+    config: AgentConfig,
+    test_config: TestConfig,
+    quality_config: QualityConfig,
+    coordinator_spawner: AgentSpawner,
+    implement_spawner: AgentSpawner,
+    test_spawner: AgentSpawner,
+    review_spawner: AgentSpawner,
+    templates: PromptTemplates,
+    branch_name: Option<String>,
+    cache_dir: Option<PathBuf>,
+    force: bool,
+    review_iterations: Option<u32>,
+    tdd_iterations: Option<u32>,
+    accounting: RunAccounting,
+    db: Option<Database>,
+    worktree_record: Option<WorktreeRecord>,
+    approval_gates: ApprovalGates,
+    last_results: Option<TestResults>,
+}
+
+impl CoordinatorExecutor {
+    /// Create an executor for `task` in the repository at `project_dir`
+    pub fn new(
+        task: impl Into<String>,
+        project_dir: impl Into<PathBuf>,
+        coordinator_config: CoordinatorConfig,
+        config: AgentConfig,
+    ) -> Self {
+        let workflow =
+            CoordinatorWorkflow::with_config(task, project_dir, coordinator_config, config.clone());
+        Self {
+            templates: PromptTemplates::discover(&workflow.state().project_dir),
+            workflow,
+            test_config: TestConfig::default(),
+            quality_config: QualityConfig::default(),
+            coordinator_spawner: AgentSpawner::from_config(config.clone(), AgentType::Coordinator),
+            implement_spawner: AgentSpawner::from_config(config.clone(), AgentType::Implement),
+            test_spawner: AgentSpawner::from_config(config.clone(), AgentType::Test),
+            review_spawner: AgentSpawner::from_config(config.clone(), AgentType::Review),
+            config,
+            branch_name: None,
+            cache_dir: None,
+            force: false,
+            review_iterations: None,
+            tdd_iterations: None,
+            accounting: RunAccounting::default(),
+            db: None,
+            worktree_record: None,
+            approval_gates: ApprovalGates::default(),
+            last_results: None,
+        }
+    }
+
+    /// Work on this branch instead of one named after the task
+    pub fn with_branch_name(mut self, branch_name: impl Into<String>) -> Self {
+        self.branch_name = Some(branch_name.into());
+        self
+    }
+
+    /// Create the worktree under this directory instead of the default cache
+    pub fn with_cache_dir(mut self, cache_dir: impl Into<PathBuf>) -> Self {
+        self.cache_dir = Some(cache_dir.into());
+        self
+    }
+
+    /// Recreate the worktree and branch if they already exist
+    pub fn with_force(mut self) -> Self {
+        self.force = true;
+        self
+    }
+
+    /// Run tests as a `[test]` config section says
+    ///
+    /// A `[test]` section in the repository's `.murmur.toml` still takes
+    /// precedence.
+    pub fn with_test_config(mut self, config: &TestConfig) -> Self {
+        self.test_config = config.clone();
+        self
+    }
+
+    /// Run the checks a `[quality]` config section asks for
+    ///
+    /// A `[quality]` section in the repository's `.murmur.toml` still takes
+    /// precedence.
+    pub fn with_quality_config(mut self, config: &QualityConfig) -> Self {
+        self.quality_config = config.clone();
+        self
+    }
+
+    /// Stop the review loop after this many rounds
+    pub fn with_review_iterations(mut self, iterations: u32) -> Self {
+        self.review_iterations = Some(iterations);
+        self
+    }
+
+    /// Give the TDD cycle this many Implement → VerifyGreen iterations
+    pub fn with_tdd_iterations(mut self, iterations: u32) -> Self {
+        self.tdd_iterations = Some(iterations);
+        self
+    }
+
+    /// Wait for human approval before starting the gated phases
+    ///
+    /// Pending approvals are stored in the database, which must be attached.
+    pub fn with_approval_gates(mut self, gates: ApprovalGates) -> Self {
+        self.approval_gates = gates;
+        self
+    }
+
+    /// Enforce spending limits on the agent runs
+    pub fn with_accounting(mut self, accounting: RunAccounting) -> Self {
+        self.accounting = accounting;
+        self
+    }
+
+    /// Record agent runs, review rounds and the worktree in a database
+    pub fn with_database(mut self, db: Database) -> Self {
+        self.db = Some(db);
+        self
+    }
+
+    /// Use a custom spawner for the coordinator agent
+    pub fn with_coordinator_spawner(mut self, spawner: AgentSpawner) -> Self {
+        self.coordinator_spawner = spawner;
+        self
+    }
+
+    /// Use a custom spawner for the implement agent
+    pub fn with_implement_spawner(mut self, spawner: AgentSpawner) -> Self {
+        self.implement_spawner = spawner;
+        self
+    }
+
+    /// Use a custom spawner for the test agent
+    pub fn with_test_spawner(mut self, spawner: AgentSpawner) -> Self {
+        self.test_spawner = spawner;
+        self
+    }
+
+    /// Use a custom spawner for the review agent
+    pub fn with_review_spawner(mut self, spawner: AgentSpawner) -> Self {
+        self.review_spawner = spawner;
+        self
+    }
+
+    /// Get the underlying workflow
+    pub fn workflow(&self) -> &CoordinatorWorkflow {
+        &self.workflow
+    }
+
+    /// Run the workflow until it completes or fails
+    ///
+    /// Agent output is streamed to `handler` and progress reported to
+    /// `observer`. A phase that fails is recorded in the workflow's history
+    /// and reported through the outcome rather than as an error.
+    pub async fn run<O: CoordinatorObserver, H: StreamHandler>(
+        &mut self,
+        observer: &mut O,
+        handler: &mut H,
+    ) -> Result<CoordinatorOutcome> {
+        let mut failed_phase = None;
+
+        while !self.workflow.phase().is_terminal() {
+            let phase = self.workflow.phase();
+            observer.on_phase_start(phase, self.workflow.state());
+
+            let step = match phase {
+                CoordinatorPhase::Planning => self.plan(observer, handler).await,
+                CoordinatorPhase::SetupWorktree => self.setup_worktree(),
+                CoordinatorPhase::Implementing => self.implement(observer, handler).await,
+                CoordinatorPhase::Testing => self.test(observer, handler).await,
+                CoordinatorPhase::Reviewing => self.review(observer, handler).await,
+                CoordinatorPhase::CreatingPR => self.create_pr(observer, handler).await,
+                CoordinatorPhase::Complete | CoordinatorPhase::Failed => break,
+            };
+
+            let result = match step {
+                Ok(Step::Done(message)) => {
+                    observer.on_phase_complete(phase, message.as_deref());
+                    self.workflow.state_mut().rejection = None;
+                    self.workflow.advance(true, message);
+                    self.await_approval(phase, observer).await
+                }
+                Ok(Step::Skipped(reason)) => {
+                    observer.on_phase_skipped(phase, &reason);
+                    self.workflow.advance(true, Some(reason));
+                    self.await_approval(phase, observer).await
+                }
+                Err(e) => Err(e),
+            };
+            if let Err(e) = result {
+                // Failing to get approval fails the phase waiting for it
+                let phase = self.workflow.phase();
+                let error = e.to_string();
+                tracing::warn!(phase = ?phase, error = %error, "Coordinator phase failed");
+                observer.on_failed(phase, &error);
+                self.workflow.advance(false, Some(error.clone()));
+                self.workflow.state_mut().error = Some(error);
+                failed_phase = Some(phase);
+            }
+        }
+
+        self.finish_worktree();
+        let state = self.workflow.state();
+        Ok(CoordinatorOutcome {
+            completed: self.workflow.is_complete(),
+            failed_phase,
+            error: state.error.clone(),
+            worktree_path: state.worktree_path.clone(),
+            branch_name: state.branch_name.clone(),
+            pr_url: state.pr_url.clone(),
+            last_results: self.last_results.clone(),
+        })
+    }
+
+    /// Wait for approval if the phase the workflow advanced to is gated
+    ///
+    /// A rejection sends the workflow back to Planning when the work hadn't
+    /// started yet, and to Implementing otherwise.
+    async fn await_approval<O: CoordinatorObserver>(
+        &mut self,
+        from: CoordinatorPhase,
+        observer: &mut O,
+    ) -> Result<()> {
+        let to = self.workflow.phase();
+        if to == CoordinatorPhase::Failed || !self.approval_gates.is_gated(to.as_str()) {
+            return Ok(());
+        }
+
+        let state = self.workflow.state();
+        let workdir = state.worktree_path.as_ref().unwrap_or(&state.project_dir);
+        let approval = Approval::new(
+            "coordinator",
+            &state.task,
+            workdir.to_string_lossy(),
+            from.as_str(),
+            to.as_str(),
+        );
+        let decision = self
+            .approval_gates
+            .wait(self.db.as_ref(), approval, |id| {
+                observer.on_approval_pending(from, to, id)
+            })
+            .await?;
+        observer.on_approval(to, &decision);
+
+        if let ApprovalDecision::Rejected(reason) = decision {
+            let redo = match from {
+                CoordinatorPhase::Planning | CoordinatorPhase::SetupWorktree => {
+                    CoordinatorPhase::Planning
+                }
+                _ => CoordinatorPhase::Implementing,
+            };
+            self.workflow.send_back(redo, reason);
+        }
+        Ok(())
+    }
+
+    /// Have the coordinator agent plan the task
+    async fn plan<O: CoordinatorObserver, H: StreamHandler>(
+        &mut self,
+        observer: &mut O,
+        handler: &mut H,
+    ) -> Result<Step> {
+        let task = self.workflow.current_prompt();
+        let mut prompt = self.build_prompt(AgentType::Coordinator, task.clone())?;
+        loop {
+            let plan = self
+                .run_with_retries(AgentType::Coordinator, &prompt, observer, handler)
+                .await?;
+            let subtasks = match parse_subtasks(&plan) {
+                Ok(subtasks) => subtasks,
+                Err(e) if self.workflow.retry() => {
+                    observer.on_retry(CoordinatorPhase::Planning, &e.to_string());
+                    prompt = self.build_prompt(
+                        AgentType::Coordinator,
+                        format!(
+                            "{}\n\nYour previous plan could not be used: {}. \
+                             Answer again with a corrected JSON block.",
+                            task, e
+                        ),
+                    )?;
+                    continue;
+                }
+                Err(e) => return Err(e),
+            };
+
+            let state = self.workflow.state_mut();
+            if !plan.trim().is_empty() {
+                state.plan = Some(plan);
+            }
+            return Ok(Step::Done(subtasks.map(|subtasks| {
+                let message = format!("Planned {} subtasks", subtasks.len());
+                state.set_subtasks(subtasks);
+                message
+            })));
+        }
+    }
+
+    /// Create the worktree and branch the work is done in
+    fn setup_worktree(&mut self) -> Result<Step> {
+        // Planning again after a rejection keeps the worktree
+        if let Some(ref path) = self.workflow.state().worktree_path {
+            return Ok(Step::Done(Some(format!("Reusing {}", path.display()))));
+        }
+        let state = self.workflow.state();
+        let repo = GitRepo::open(&state.project_dir)?;
+        let point = branching_point(&repo, &state.config.main_branch)?;
+        let branch = self
+            .branch_name
+            .clone()
+            .unwrap_or_else(|| default_branch_name(&state.task));
+        let cache_dir = self.cache_dir()?;
+        let dir = worktree_path(&cache_dir, &repo.repo_name(), &branch);
+
+        let info = repo.create_worktree(
+            &dir,
+            &point,
+            &WorktreeOptions {
+                branch_name: branch,
+                force: self.force,
+            },
+        )?;
+        self.record_worktree(&repo, &info.path, &info.branch, &point.commit);
+
+        let message = format!("{} on branch {}", info.path.display(), info.branch);
+        let state = self.workflow.state_mut();
+        state.set_worktree(info.path, info.branch);
+        state.base_commit = Some(point.commit);
+        Ok(Step::Done(Some(message)))
+    }
+
+    /// Implement the task, or each subtask of the plan in dependency order
+    async fn implement<O: CoordinatorObserver, H: StreamHandler>(
+        &mut self,
+        observer: &mut O,
+        handler: &mut H,
+    ) -> Result<Step> {
+        // Work on a rejection as a whole once the plan's subtasks are done
+        let state = self.workflow.state();
+        if state.subtasks.is_empty() || (state.rejection.is_some() && state.all_subtasks_complete())
+        {
+            let task = self.workflow.with_rejection(state.task.clone());
+            let prompt = self.workflow.current_prompt();
+            self.implement_one(AgentType::Implement, &task, prompt, observer, handler)
+                .await?;
+            return Ok(Step::Done(None));
+        }
+
+        loop {
+            let batch = self.next_batch();
+            if batch.is_empty() {
+                break;
+            }
+            for subtask in &batch {
+                self.workflow.state_mut().start_subtask(&subtask.id);
+                observer.on_subtask_start(subtask);
+            }
+
+            let results = match batch.as_slice() {
+                [subtask] => vec![self.run_subtask(subtask, observer, handler).await],
+                _ if self.workflow.state().config.isolate_subtasks => {
+                    self.run_isolated(&batch, observer, handler).await?
+                }
+                _ => {
+                    let workdirs = vec![self.worktree()?; batch.len()];
+                    self.run_concurrently(&batch, &workdirs, observer, handler)
+                        .await?
+                }
+            };
+
+            // Finish the whole batch before giving up, so subtasks that
+            // succeeded alongside a failure are recorded as complete
+            let mut failure = None;
+            for (subtask, result) in batch.iter().zip(results) {
+                match result {
+                    Ok(()) => self.workflow.state_mut().complete_subtask(&subtask.id),
+                    Err(e) => {
+                        let error = format!("Subtask {} failed: {}", subtask.id, e);
+                        if self.workflow.retry() {
+                            self.workflow.state_mut().reset_subtask(&subtask.id);
+                            observer.on_retry(CoordinatorPhase::Implementing, &error);
+                        } else {
+                            self.workflow.state_mut().fail_subtask(&subtask.id);
+                            failure.get_or_insert(error);
+                        }
+                    }
+                }
+            }
+            if let Some(error) = failure {
+                return Err(Error::Agent(error));
+            }
+        }
+
+        let state = self.workflow.state();
+        if !state.all_subtasks_complete() {
+            let blocked: Vec<&str> = state
+                .subtasks
+                .iter()
+                .filter(|s| !state.completed_subtasks.contains(&s.id))
+                .map(|s| s.id.as_str())
+                .collect();
+            return Err(Error::Agent(format!(
+                "Subtasks blocked by unmet dependencies: {}",
+                blocked.join(", ")
+            )));
+        }
+        Ok(Step::Done(Some(format!(
+            "{} subtasks complete",
+            state.subtasks.len()
+        ))))
+    }
+
+    /// Run one piece of implementation work with the right agent
+    ///
+    /// With `use_tdd`, implementation work goes through the TDD cycle with
+    /// `description` as the behavior.
+    async fn implement_one<O: CoordinatorObserver, H: StreamHandler>(
+        &mut self,
+        agent_type: AgentType,
+        description: &str,
+        task_prompt: String,
+        observer: &mut O,
+        handler: &mut H,
+    ) -> Result<()> {
+        if agent_type == AgentType::Implement && self.workflow.state().config.use_tdd {
+            return self.run_tdd(description, observer, handler).await;
+        }
+        let prompt = self.build_prompt(agent_type, task_prompt)?;
+        self.run_with_retries(agent_type, &prompt, observer, handler)
+            .await?;
+        Ok(())
+    }
+
+    /// The ready subtasks to work on next
+    ///
+    /// Up to `max_parallel` subtasks run at once. In a shared worktree only
+    /// subtasks that list their files, none of them in common, run together.
+    /// TDD cycles always run alone since they need the test suite to
+    /// themselves.
+    fn next_batch(&self) -> Vec<SubTask> {
+        let state = self.workflow.state();
+        let config = &state.config;
+        let mut batch: Vec<SubTask> = Vec::new();
+
+        for subtask in state.ready_subtasks() {
+            let tdd = config.use_tdd && subtask.agent_type == AgentType::Implement;
+            if tdd || (!config.isolate_subtasks && subtask.files.is_empty()) {
+                if batch.is_empty() {
+                    batch.push(subtask.clone());
+                    break;
+                }
+                continue;
+            }
+            if batch.len() >= config.max_parallel.max(1) {
+                break;
+            }
+            let overlaps = batch
+                .iter()
+                .any(|other| other.files.iter().any(|f| subtask.files.contains(f)));
+            if config.isolate_subtasks || !overlaps {
+                batch.push(subtask.clone());
+            }
+        }
+        batch
+    }
+
+    /// Work on one subtask in the worktree
+    async fn run_subtask<O: CoordinatorObserver, H: StreamHandler>(
+        &mut self,
+        subtask: &SubTask,
+        observer: &mut O,
+        handler: &mut H,
+    ) -> Result<()> {
+        let prompt = self.workflow.subtask_prompt(subtask);
+        self.implement_one(
+            subtask.agent_type,
+            &subtask.description,
+            prompt,
+            observer,
+            handler,
+        )
+        .await
+    }
+
+    /// Run the agents for several subtasks at once, one per `workdirs` entry
+    ///
+    /// The agents' output is interleaved on `handler`. Failures are reported
+    /// per subtask rather than retried.
+    async fn run_concurrently<O: CoordinatorObserver, H: StreamHandler>(
+        &self,
+        batch: &[SubTask],
+        workdirs: &[PathBuf],
+        observer: &mut O,
+        handler: &mut H,
+    ) -> Result<Vec<Result<()>>> {
+        let mut prompts = Vec::with_capacity(batch.len());
+        for subtask in batch {
+            let prompt =
+                self.build_prompt(subtask.agent_type, self.workflow.subtask_prompt(subtask))?;
+            observer.on_agent_start(CoordinatorPhase::Implementing, subtask.agent_type, &prompt);
+            prompts.push(prompt);
+        }
+
+        let handler = Mutex::new(handler);
+        let runs = batch
+            .iter()
+            .zip(&prompts)
+            .zip(workdirs)
+            .map(|((subtask, prompt), workdir)| {
+                let mut handler = SharedHandler::new(&handler);
+                async move {
+                    self.run_agent_in(subtask.agent_type, prompt, workdir, &mut handler)
+                        .await
+                        .map(|_| ())
+                }
+            });
+        Ok(join_all(runs).await)
+    }
+
+    /// Run subtasks concurrently in worktrees of their own, then merge them
+    ///
+    /// Each subtask branches off the work so far, which is committed first.
+    /// A subtask whose agent succeeds has its changes committed and merged
+    /// into the main worktree; a merge conflict fails it. The subtask
+    /// worktrees and branches are removed afterwards.
+    async fn run_isolated<O: CoordinatorObserver, H: StreamHandler>(
+        &mut self,
+        batch: &[SubTask],
+        observer: &mut O,
+        handler: &mut H,
+    ) -> Result<Vec<Result<()>>> {
+        let main = self.worktree()?;
+        let state = self.workflow.state();
+        let branch = state
+            .branch_name
+            .clone()
+            .ok_or_else(|| Error::Other("Worktree has not been set up".to_string()))?;
+        let repo = GitRepo::open(&state.project_dir)?;
+        let cache_dir = self.cache_dir()?;
+
+        commit_all(&main, "Work in progress before parallel subtasks")?;
+        let point = BranchingPoint {
+            reference: branch.clone(),
+            commit: git(&main, &["rev-parse", "HEAD"])?.trim().to_string(),
+            branch_name: branch.clone(),
+        };
+        let mut worktrees = Vec::with_capacity(batch.len());
+        for subtask in batch {
+            let options = WorktreeOptions {
+                branch_name: format!("{}-{}", branch, branch_slug(&subtask.id)),
+                force: true,
+            };
+            let dir = worktree_path(&cache_dir, &repo.repo_name(), &options.branch_name);
+            match repo.create_worktree(&dir, &point, &options) {
+                Ok(info) => worktrees.push(info),
+                Err(e) => {
+                    remove_worktrees(&repo, &worktrees);
+                    return Err(e);
+                }
+            }
+        }
+
+        let workdirs: Vec<PathBuf> = worktrees.iter().map(|info| info.path.clone()).collect();
+        let results = self
+            .run_concurrently(batch, &workdirs, observer, handler)
+            .await
+            .map(|results| {
+                batch
+                    .iter()
+                    .zip(&worktrees)
+                    .zip(results)
+                    .map(|((subtask, info), result)| {
+                        result
+                            .and_then(|()| {
+                                let message = format!("{}: {}", subtask.id, subtask.description);
+                                commit_all(&info.path, &message)
+                            })
+                            .and_then(|()| merge(&main, &info.branch))
+                    })
+                    .collect()
+            });
+        remove_worktrees(&repo, &worktrees);
+        results
+    }
+
+    /// Run the TDD cycle in the worktree, retrying if an agent fails
+    async fn run_tdd<O: CoordinatorObserver, H: StreamHandler>(
+        &mut self,
+        behavior: &str,
+        observer: &mut O,
+        handler: &mut H,
+    ) -> Result<()> {
+        loop {
+            // The plan already serves as the spec, so start from WriteTests
+            let mut executor = TddExecutor::new(behavior, self.worktree()?, self.config.clone())
+                .with_skip_spec()
+                .with_test_config(&self.test_config)
+                .with_quality_config(&self.quality_config)
+                .with_accounting(self.accounting.clone())
+                .with_test_spawner(self.test_spawner.clone())
+                .with_implement_spawner(self.implement_spawner.clone());
+            if let Some(iterations) = self.tdd_iterations {
+                executor = executor.with_max_iterations(iterations);
+            }
+            if let Some(id) = self.worktree_record.as_ref().and_then(|r| r.id) {
+                executor = executor.with_worktree_id(id);
+            }
+            if let Some(db) = self.db.take() {
+                executor = executor.with_database(db);
+            }
+
+            let outcome = executor.run(&mut TddForwarder { observer }, handler).await;
+            self.db = executor.take_database();
+
+            match outcome {
+                Ok(outcome) => {
+                    self.last_results = outcome.last_results;
+                    if outcome.completed {
+                        return Ok(());
+                    }
+                    return Err(Error::Agent(format!(
+                        "TDD workflow gave up after {} iterations",
+                        outcome.iterations
+                    )));
+                }
+                Err(e) if self.workflow.retry() => {
+                    observer.on_retry(CoordinatorPhase::Implementing, &e.to_string());
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// Run the tests and quality checks, asking the implement agent to fix failures
+    async fn test<O: CoordinatorObserver, H: StreamHandler>(
+        &mut self,
+        observer: &mut O,
+        handler: &mut H,
+    ) -> Result<Step> {
+        if self.test_runner()?.is_none() && self.quality_gates()?.is_empty() {
+            return Ok(Step::Skipped("No test suites found".to_string()));
+        }
+        let summary = self.verify_changes(false, observer, handler).await?;
+        Ok(Step::Done(Some(
+            summary.unwrap_or_else(|| "Quality checks passed".to_string()),
+        )))
+    }
+
+    /// Run the tests, then the quality checks, until both pass
+    ///
+    /// Failures go to the implement agent to fix while the phase has retries
+    /// left. With `tests_passed` the tests are only run again after a fix.
+    /// Returns the summary of the last test run, if the tests ran.
+    async fn verify_changes<O: CoordinatorObserver, H: StreamHandler>(
+        &mut self,
+        mut tests_passed: bool,
+        observer: &mut O,
+        handler: &mut H,
+    ) -> Result<Option<String>> {
+        let runner = self.test_runner()?;
+        let quality = self.quality_gates()?;
+        let mut summary = None;
+
+        loop {
+            if let (Some(ref runner), false) = (&runner, tests_passed) {
+                let results = self.run_tests(runner, observer).await;
+                if !passed(&results) {
+                    let prompt = self.fix_prompt(&results)?;
+                    let reason = format!("Tests still failing: {}", results.summary());
+                    self.fix(&reason, &prompt, observer, handler).await?;
+                    continue;
+                }
+                summary = Some(results.summary());
+            }
+
+            let mut failed = 0;
+            let problems = quality
+                .run(&mut |check, problem| {
+                    if problem.is_some() {
+                        failed += 1;
+                    }
+                    observer.on_quality_check(check, problem);
+                })
+                .await?;
+            let Some(problems) = problems else {
+                return Ok(summary);
+            };
+            let prompt = self.quality_fix_prompt(&problems)?;
+            let reason = format!("{} quality check(s) failed", failed);
+            self.fix(&reason, &prompt, observer, handler).await?;
+            tests_passed = false;
+        }
+    }
+
+    /// Have the implement agent fix a failure, if the phase has retries left
+    async fn fix<O: CoordinatorObserver, H: StreamHandler>(
+        &mut self,
+        reason: &str,
+        prompt: &str,
+        observer: &mut O,
+        handler: &mut H,
+    ) -> Result<()> {
+        if !self.workflow.retry() {
+            return Err(Error::Agent(format!(
+                "{} (after {} fix attempts)",
+                reason,
+                self.workflow.state().retries
+            )));
+        }
+        let phase = self.workflow.phase();
+        observer.on_retry(phase, reason);
+        observer.on_agent_start(phase, AgentType::Implement, prompt);
+        self.run_agent(AgentType::Implement, prompt, handler)
+            .await?;
+        Ok(())
+    }
+
+    /// Review the changes, then re-run the tests if the review led to fixes
+    async fn review<O: CoordinatorObserver, H: StreamHandler>(
+        &mut self,
+        observer: &mut O,
+        handler: &mut H,
+    ) -> Result<Step> {
+        if !self.workflow.state().config.run_review {
+            return Ok(Step::Skipped("Review disabled".to_string()));
+        }
+
+        let state = self.workflow.state();
+        let mut review_loop = ReviewLoop::new(&state.task, self.worktree()?, self.config.clone())
+            .with_review_spawner(self.review_spawner.clone())
+            .with_fix_spawner(self.implement_spawner.clone())
+            .with_accounting(self.accounting.clone());
+        if let Some(ref base) = state.base_commit {
+            review_loop = review_loop.with_base(base);
+        }
+        if let Some(iterations) = self.review_iterations {
+            review_loop = review_loop.with_max_iterations(iterations);
+        }
+        if let Some(db) = self.db.take() {
+            review_loop = review_loop.with_database(db);
+        }
+
+        let outcome = review_loop.run(handler).await;
+        self.db = review_loop.take_database();
+        let outcome = outcome?;
+        observer.on_review(&outcome);
+
+        if !outcome.approved && !outcome.is_clean() {
+            return Err(Error::Agent(format!(
+                "Review not approved after {} round(s)",
+                outcome.rounds.len()
+            )));
+        }
+        if outcome.fixes > 0 {
+            if let Some(runner) = self.test_runner()? {
+                let results = self.run_tests(&runner, observer).await;
+                if !passed(&results) {
+                    return Err(Error::Agent(format!(
+                        "Tests fail after review fixes: {}",
+                        results.summary()
+                    )));
+                }
+            }
+        }
+        let verdict = outcome.last().map(|r| r.verdict.to_string());
+        Ok(Step::Done(verdict))
+    }
+
+    /// Commit the work, push the branch and open a pull request
+    ///
+    /// The quality checks run first, since the review may have changed the
+    /// code after the Testing phase.
+    async fn create_pr<O: CoordinatorObserver, H: StreamHandler>(
+        &mut self,
+        observer: &mut O,
+        handler: &mut H,
+    ) -> Result<Step> {
+        if !self.workflow.state().config.auto_pr {
+            return Ok(Step::Skipped("auto_pr disabled".to_string()));
+        }
+        if !self.quality_gates()?.is_empty() {
+            self.verify_changes(true, observer, handler).await?;
+        }
+
+        let state = self.workflow.state();
+        let workdir = self.worktree()?;
+        let branch = state
+            .branch_name
+            .clone()
+            .ok_or_else(|| Error::Other("No branch to open a pull request from".to_string()))?;
+        let title = pr_title(&state.task);
+
+        commit_all(&workdir, &title)?;
+        git(&workdir, &["push", "-u", "origin", &branch])?;
+
+        let mut cmd = Command::new("gh");
+        cmd.args(["pr", "create", "--head", &branch, "--title", &title])
+            .args(["--base", &state.config.main_branch])
+            .current_dir(&workdir);
+        if let Some(ref repo) = state.config.repo {
+            cmd.args(["--repo", repo]);
+        }
+        let description = workdir.join(".murmur").join("pr-description.md");
+        if description.exists() {
+            cmd.arg("--body-file").arg(&description);
+        } else {
+            cmd.args(["--body", &pr_body(state)]);
+        }
+
+        let output = cmd
+            .output()
+            .map_err(|e| Error::Other(format!("Failed to run gh: {}", e)))?;
+        if !output.status.success() {
+            return Err(Error::Other(format!(
+                "gh pr create failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+        // gh prints the URL of the new pull request last
+        let url = String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .last()
+            .unwrap_or_default()
+            .trim()
+            .to_string();
+        self.workflow.state_mut().pr_url = Some(url.clone());
+        Ok(Step::Done(Some(url)))
+    }
+
+    /// Directory the worktrees are created in
+    fn cache_dir(&self) -> Result<PathBuf> {
+        match self.cache_dir {
+            Some(ref dir) => Ok(dir.clone()),
+            None => default_cache_dir(),
+        }
+    }
+
+    /// The worktree the work happens in
+    fn worktree(&self) -> Result<PathBuf> {
+        self.workflow
+            .state()
+            .worktree_path
+            .clone()
+            .ok_or_else(|| Error::Other("Worktree has not been set up".to_string()))
+    }
+
+    /// Test runner for the worktree, if it has any test suites
+    fn test_runner(&self) -> Result<Option<TestRunner>> {
+        let runner = TestRunner::from_config(self.worktree()?, &self.test_config);
+        Ok((!runner.suites().is_empty()).then_some(runner))
+    }
+
+    /// Quality checks for the worktree
+    fn quality_gates(&self) -> Result<QualityGates> {
+        Ok(QualityGates::from_config(
+            self.worktree()?,
+            &self.quality_config,
+        ))
+    }
+
+    /// The prompt asking the implement agent to fix failing quality checks
+    fn quality_fix_prompt(&self, problems: &str) -> Result<String> {
+        let task = format!(
+            "{}\n\nThe quality checks fail after your changes. Fix every problem \
+             without disabling the checks.\n\n## Failed Checks\n\n{}\n",
+            self.workflow.state().task,
+            problems.trim()
+        );
+        self.build_prompt(AgentType::Implement, task)
+    }
+
+    /// Run the tests, reporting output and results to `observer`
+    async fn run_tests<O: CoordinatorObserver>(
+        &mut self,
+        runner: &TestRunner,
+        observer: &mut O,
+    ) -> TestResults {
+        let results = runner
+            .run_with_handler(&mut |line: &str| observer.on_test_output(line))
+            .await;
+        observer.on_test_results(&results);
+        self.last_results = Some(results.clone());
+        results
+    }
+
+    /// The prompt asking the implement agent to fix failing tests
+    fn fix_prompt(&self, results: &TestResults) -> Result<String> {
+        let mut task = format!(
+            "{}\n\nThe test suite fails after your changes ({}). Fix the failures \
+             without weakening or deleting tests.\n",
+            self.workflow.state().task,
+            results.summary()
+        );
+        let failures: Vec<_> = results.failures().collect();
+        if failures.is_empty() {
+            let lines: Vec<&str> = results.output.lines().collect();
+            let tail = &lines[lines.len().saturating_sub(FIX_PROMPT_OUTPUT_LINES)..];
+            task.push_str(&format!(
+                "\n## Test Output\n\n```\n{}\n```\n",
+                tail.join("\n")
+            ));
+        } else {
+            task.push_str("\n## Failing Tests\n\n");
+            for test in failures {
+                match test.message {
+                    Some(ref message) => task.push_str(&format!("- {}: {}\n", test.name, message)),
+                    None => task.push_str(&format!("- {}\n", test.name)),
+                }
+            }
+        }
+        self.build_prompt(AgentType::Implement, task)
+    }
+
+    /// Build an agent's prompt around a task description
+    fn build_prompt(&self, agent_type: AgentType, task: String) -> Result<String> {
+        PromptBuilder::new(agent_type)
+            .templates(self.templates.clone())
+            .task(task)
+            .try_build()
+    }
+
+    /// Run an agent, retrying the phase while retries remain
+    async fn run_with_retries<O: CoordinatorObserver, H: StreamHandler>(
+        &mut self,
+        agent_type: AgentType,
+        prompt: &str,
+        observer: &mut O,
+        handler: &mut H,
+    ) -> Result<String> {
+        let phase = self.workflow.phase();
+        loop {
+            observer.on_agent_start(phase, agent_type, prompt);
+            match self.run_agent(agent_type, prompt, handler).await {
+                Ok(output) => return Ok(output),
+                Err(e) if self.workflow.retry() => observer.on_retry(phase, &e.to_string()),
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// Run one agent to completion, returning its text output
+    ///
+    /// Agents run in the worktree once it exists, and in the project
+    /// directory before that.
+    async fn run_agent<H: StreamHandler>(
+        &self,
+        agent_type: AgentType,
+        prompt: &str,
+        handler: &mut H,
+    ) -> Result<String> {
+        let state = self.workflow.state();
+        let workdir = state.worktree_path.as_ref().unwrap_or(&state.project_dir);
+        self.run_agent_in(agent_type, prompt, workdir, handler)
+            .await
+    }
+
+    /// Run one agent to completion in `workdir`, returning its text output
+    async fn run_agent_in<H: StreamHandler>(
+        &self,
+        agent_type: AgentType,
+        prompt: &str,
+        workdir: &Path,
+        handler: &mut H,
+    ) -> Result<String> {
+        let spawner = match agent_type {
+            AgentType::Coordinator => &self.coordinator_spawner,
+            AgentType::Test => &self.test_spawner,
+            AgentType::Review => &self.review_spawner,
+            AgentType::Implement => &self.implement_spawner,
+        };
+        let runner = AgentRunner {
+            config: &self.config,
+            accounting: &self.accounting,
+            db: self.db.as_ref(),
+            issue_number: None,
+        };
+        let (output, _) = runner
+            .run(spawner, agent_type, prompt, workdir, handler)
+            .await?;
+        Ok(output)
+    }
+
+    /// Track the new worktree in the database
+    fn record_worktree(&mut self, repo: &GitRepo, path: &Path, branch: &str, base_commit: &str) {
+        let Some(ref db) = self.db else {
+            return;
+        };
+        let mut record = WorktreeRecord::new(path.to_string_lossy(), branch)
+            .with_main_repo_path(repo.root().to_string_lossy())
+            .with_base_commit(base_commit);
+        match WorktreeRepository::new(db).insert(&record) {
+            Ok(id) => {
+                record.id = Some(id);
+                self.worktree_record = Some(record);
+            }
+            Err(e) => tracing::warn!(error = %e, "Failed to track worktree"),
+        }
+    }
+
+    /// Mark the tracked worktree as completed or abandoned
+    fn finish_worktree(&mut self) {
+        let (Some(db), Some(record)) = (self.db.as_ref(), self.worktree_record.as_mut()) else {
+            return;
+        };
+        if self.workflow.is_complete() {
+            record.mark_completed();
+        } else {
+            record.mark_abandoned();
+        }
+        if let Err(e) = WorktreeRepository::new(db).update(record) {
+            tracing::warn!(error = %e, "Failed to update worktree status");
+        }
+    }
+}
+
+/// Forwards a TDD run's progress to a coordinator observer
+struct TddForwarder<'a, O> {
+    observer: &'a mut O,
+}
+
+impl<O: CoordinatorObserver> TddObserver for TddForwarder<'_, O> {
+    fn on_phase_start(&mut self, phase: TddPhase, _state: &TddState) {
+        self.observer.on_tdd_phase(phase);
+    }
+
+    fn on_agent_start(&mut self, _phase: TddPhase, agent_type: AgentType, prompt: &str) {
+        self.observer
+            .on_agent_start(CoordinatorPhase::Implementing, agent_type, prompt);
+    }
+
+    fn on_test_output(&mut self, _phase: TddPhase, line: &str) {
+        self.observer.on_test_output(line);
+    }
+
+    fn on_test_results(&mut self, _phase: TddPhase, results: &TestResults) {
+        self.observer.on_test_results(results);
+    }
+
+    fn on_retry(&mut self, _from: TddPhase, _to: TddPhase, reason: &str) {
+        self.observer
+            .on_retry(CoordinatorPhase::Implementing, reason);
+    }
+
+    fn on_quality_check(&mut self, _phase: TddPhase, check: &QualityCheck, problem: Option<&str>) {
+        self.observer.on_quality_check(check, problem);
+    }
+}
+
+/// Whether a test run counts as passing
+///
+/// A run that finds no tests passes; one that fails to execute does not.
+fn passed(results: &TestResults) -> bool {
+    results.execution_error.is_none() && results.failed == 0
+}
+
+/// Where the worktree branches from, preferring the remote-tracking branch
+fn branching_point(repo: &GitRepo, main_branch: &str) -> Result<BranchingPoint> {
+    let remote = BranchingOptions {
+        base_branch: Some(format!("origin/{}", main_branch)),
+        fetch: true,
+        remote: None,
+    };
+    repo.find_branching_point(&remote).or_else(|_| {
+        repo.find_branching_point(&BranchingOptions {
+            base_branch: Some(main_branch.to_string()),
+            fetch: false,
+            remote: None,
+        })
+    })
+}
+
+/// Commit every change in `workdir`, if there are any
+fn commit_all(workdir: &Path, message: &str) -> Result<()> {
+    if git(workdir, &["status", "--porcelain"])?.trim().is_empty() {
+        return Ok(());
+    }
+    git(workdir, &["add", "-A"])?;
+    git(workdir, &["commit", "-m", message])?;
+    Ok(())
+}
+
+/// Merge `branch` into the branch checked out in `workdir`
+///
+/// A merge that fails is aborted, leaving `workdir` as it was.
+fn merge(workdir: &Path, branch: &str) -> Result<()> {
+    if let Err(e) = git(workdir, &["merge", "--no-edit", branch]) {
+        let _ = git(workdir, &["merge", "--abort"]);
+        return Err(Error::Agent(format!("Failed to merge {}: {}", branch, e)));
+    }
+    Ok(())
+}
+
+/// Remove subtask worktrees along with their branches
+fn remove_worktrees(repo: &GitRepo, worktrees: &[WorktreeInfo]) {
+    for info in worktrees {
+        if let Err(e) = repo.remove_worktree(&info.path) {
+            tracing::warn!(error = %e, "Failed to remove subtask worktree");
+        }
+        if let Err(e) = repo.delete_branch(&info.branch) {
+            tracing::warn!(error = %e, "Failed to delete subtask branch");
+        }
+    }
+}
+
+/// A subtask id made safe for use in a branch name
+fn branch_slug(id: &str) -> String {
+    id.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect()
+}
+
+/// Branch name derived from the first words of the task
+fn default_branch_name(task: &str) -> String {
+    let slug: Vec<String> = task
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .take(6)
+        .map(|word| word.to_ascii_lowercase())
+        .collect();
+    if slug.is_empty() {
+        "murmur/coordinate".to_string()
+    } else {
+        format!("murmur/{}", slug.join("-"))
+    }
+}
+
+/// Pull request title: the first line of the task, shortened
+fn pr_title(task: &str) -> String {
+    let line = task.lines().next().unwrap_or_default().trim();
+    if line.chars().count() > 72 {
+        format!("{}...", line.chars().take(69).collect::<String>())
+    } else {
+        line.to_string()
+    }
+}
+
+/// Pull request body: the task and the coordinator's plan
+fn pr_body(state: &CoordinatorState) -> String {
+    let mut body = state.task.trim().to_string();
+    if let Some(ref plan) = state.plan {
+        body.push_str(&format!("\n\n## Plan\n\n{}", plan.trim()));
+    }
+    body
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::fixtures::{mock_config, spawner};
+    use crate::agent::MockScript;
+    use crate::workflow::approval::Decisions;
+    use murmur_db::repos::AgentRunRepository;
+
+    struct Quiet;
+
+    impl StreamHandler for Quiet {
+        fn on_assistant_text(&mut self, _text: &str) {}
+    }
+
+    /// Records the phases started, skipped and failed
+    #[derive(Default)]
+    struct Recorder {
+        phases: Vec<CoordinatorPhase>,
+        skipped: Vec<CoordinatorPhase>,
+        agents: Vec<AgentType>,
+        retries: Vec<CoordinatorPhase>,
+        /// Subtask and agent starts, in order
+        events: Vec<String>,
+        prompts: Vec<String>,
+        /// Quality checks run, and whether they passed
+        checks: Vec<bool>,
+    }
+
+    impl CoordinatorObserver for Recorder {
+        fn on_phase_start(&mut self, phase: CoordinatorPhase, _state: &CoordinatorState) {
+            self.phases.push(phase);
+        }
+
+        fn on_phase_skipped(&mut self, phase: CoordinatorPhase, _reason: &str) {
+            self.skipped.push(phase);
+        }
+
+        fn on_agent_start(
+            &mut self,
+            _phase: CoordinatorPhase,
+            agent_type: AgentType,
+            prompt: &str,
+        ) {
+            self.agents.push(agent_type);
+            self.events.push(agent_type.to_string());
+            self.prompts.push(prompt.to_string());
+        }
+
+        fn on_subtask_start(&mut self, subtask: &SubTask) {
+            self.events.push(subtask.id.clone());
+        }
+
+        fn on_retry(&mut self, phase: CoordinatorPhase, _reason: &str) {
+            self.retries.push(phase);
+        }
+
+        fn on_quality_check(&mut self, _check: &QualityCheck, problem: Option<&str>) {
+            self.checks.push(problem.is_none());
+        }
+    }
+
+    fn git_ok(dir: &Path, args: &[&str]) {
+        let status = Command::new("git")
+            .args(["-c", "user.name=Test", "-c", "user.email=test@example.com"])
+            .args(args)
+            .current_dir(dir)
+            .output()
+            .unwrap()
+            .status;
+        assert!(status.success(), "git {:?} failed", args);
+    }
+
+    /// A repository on `main` whose tests pass once `fixed` exists
+    fn project() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        git_ok(dir.path(), &["init", "-q", "-b", "main"]);
+        // Subtask worktrees are committed and merged by the executor itself
+        git_ok(dir.path(), &["config", "user.name", "Test"]);
+        git_ok(dir.path(), &["config", "user.email", "test@example.com"]);
+        std::fs::write(
+            dir.path().join(".murmur.toml"),
+            "[test]\ncommand = \"test -f fixed\"\n",
+        )
+        .unwrap();
+        git_ok(dir.path(), &["add", "."]);
+        git_ok(dir.path(), &["commit", "-q", "-m", "Initial commit"]);
+        dir
+    }
+
+    fn executor(project: &Path, cache: &Path, config: CoordinatorConfig) -> CoordinatorExecutor {
+        CoordinatorExecutor::new("Add a parser", project, config, mock_config())
+            .with_cache_dir(cache)
+            .with_coordinator_spawner(spawner(
+                cache,
+                "plan",
+                MockScript::new().assistant_text("1. Write the parser"),
+            ))
+    }
+
+    #[test]
+    fn test_default_branch_name() {
+        assert_eq!(
+            default_branch_name("Add a JSON parser (see #12)"),
+            "murmur/add-a-json-parser-see-12"
+        );
+        assert_eq!(default_branch_name("!!!"), "murmur/coordinate");
+    }
+
+    #[test]
+    fn test_pr_title() {
+        assert_eq!(pr_title("Add parser\n\nDetails"), "Add parser");
+        let title = pr_title(&"x".repeat(100));
+        assert_eq!(title.chars().count(), 72);
+        assert!(title.ends_with("..."));
+    }
+
+    #[tokio::test]
+    async fn test_runs_phases_in_worktree() {
+        let project = project();
+        let cache = tempfile::tempdir().unwrap();
+        let config = CoordinatorConfig {
+            run_review: false,
+            ..Default::default()
+        };
+        let mut executor = executor(project.path(), cache.path(), config)
+            .with_database(Database::in_memory().unwrap())
+            .with_implement_spawner(spawner(
+                cache.path(),
+                "impl",
+                MockScript::new()
+                    .write_file("fixed", "yes\n")
+                    .assistant_text("Done"),
+            ));
+
+        let mut recorder = Recorder::default();
+        let outcome = executor.run(&mut recorder, &mut Quiet).await.unwrap();
+        assert!(outcome.completed, "{:?}", outcome.error);
+        assert_eq!(
+            recorder.phases,
+            [
+                CoordinatorPhase::Planning,
+                CoordinatorPhase::SetupWorktree,
+                CoordinatorPhase::Implementing,
+                CoordinatorPhase::Testing,
+                CoordinatorPhase::Reviewing,
+                CoordinatorPhase::CreatingPR,
+            ]
+        );
+        assert_eq!(
+            recorder.skipped,
+            [CoordinatorPhase::Reviewing, CoordinatorPhase::CreatingPR]
+        );
+        assert_eq!(
+            recorder.agents,
+            [AgentType::Coordinator, AgentType::Implement]
+        );
+
+        // The work happened in the worktree, not the project
+        let worktree = outcome.worktree_path.unwrap();
+        assert!(worktree.starts_with(cache.path()));
+        assert!(worktree.join("fixed").exists());
+        assert!(!project.path().join("fixed").exists());
+        assert_eq!(outcome.branch_name.as_deref(), Some("murmur/add-a-parser"));
+        assert!(outcome.last_results.is_some());
+
+        let state = executor.workflow().state();
+        assert_eq!(state.plan.as_deref(), Some("1. Write the parser"));
+        assert!(state.base_commit.is_some());
+
+        let db = executor.db.as_ref().unwrap();
+        let record = WorktreeRepository::new(db)
+            .find_by_path(&worktree.to_string_lossy())
+            .unwrap()
+            .unwrap();
+        assert_eq!(record.status, "completed");
+        let runs = AgentRunRepository::new(db).find_all(None).unwrap();
+        let mut names: Vec<&str> = runs.iter().map(|r| r.agent_type.as_str()).collect();
+        names.sort_unstable();
+        assert_eq!(names, ["coordinator", "implementer"]);
+    }
+
+    #[tokio::test]
+    async fn test_failing_tests_retry_then_fail() {
+        let project = project();
+        let cache = tempfile::tempdir().unwrap();
+        let config = CoordinatorConfig {
+            max_retries: 1,
+            ..Default::default()
+        };
+        // The implement agent never creates the file the tests look for
+        let mut executor =
+            executor(project.path(), cache.path(), config).with_implement_spawner(spawner(
+                cache.path(),
+                "impl",
+                MockScript::new().assistant_text("Done"),
+            ));
+
+        let mut recorder = Recorder::default();
+        let outcome = executor.run(&mut recorder, &mut Quiet).await.unwrap();
+        assert!(!outcome.completed);
+        assert_eq!(outcome.failed_phase, Some(CoordinatorPhase::Testing));
+        assert!(outcome.error.unwrap().contains("Tests still failing"));
+        assert_eq!(recorder.retries, [CoordinatorPhase::Testing]);
+        // Planning, implementing and one fix attempt
+        assert_eq!(
+            recorder.agents,
+            [
+                AgentType::Coordinator,
+                AgentType::Implement,
+                AgentType::Implement
+            ]
+        );
+
+        let state = executor.workflow().state();
+        assert!(state.is_failed());
+        let last = state.history.last().unwrap();
+        assert_eq!(last.from, CoordinatorPhase::Testing);
+        assert!(!last.success);
+    }
+
+    #[tokio::test]
+    async fn test_quality_failures_are_fixed() {
+        let project = project();
+        let cache = tempfile::tempdir().unwrap();
+        let config = CoordinatorConfig {
+            run_review: false,
+            ..Default::default()
+        };
+        let quality = QualityConfig {
+            // Fails the first time only, as if the agent then fixed it
+            commands: vec![
+                "test -f .checked || { touch .checked; echo 'src/lib.rs: not formatted'; exit 1; }"
+                    .to_string(),
+            ],
+            ..Default::default()
+        };
+        let mut executor = executor(project.path(), cache.path(), config)
+            .with_quality_config(&quality)
+            .with_implement_spawner(spawner(
+                cache.path(),
+                "impl",
+                MockScript::new()
+                    .write_file("fixed", "yes\n")
+                    .assistant_text("Done"),
+            ));
+
+        let mut recorder = Recorder::default();
+        let outcome = executor.run(&mut recorder, &mut Quiet).await.unwrap();
+        assert!(outcome.completed, "{:?}", outcome.error);
+        assert_eq!(recorder.checks, [false, true]);
+        assert_eq!(recorder.retries, [CoordinatorPhase::Testing]);
+        assert_eq!(
+            recorder.agents,
+            [
+                AgentType::Coordinator,
+                AgentType::Implement,
+                AgentType::Implement
+            ]
+        );
+        let fix = recorder.prompts.last().unwrap();
+        assert!(fix.contains("## Failed Checks"));
+        assert!(fix.contains("src/lib.rs: not formatted"));
+    }
+
+    #[tokio::test]
+    async fn test_agent_failure_retries_phase() {
+        let project = project();
+        let cache = tempfile::tempdir().unwrap();
+        let failing = MockScript::new()
+            .assistant_text("Crashed")
+            .with_exit_code(1);
+        let mut executor = executor(project.path(), cache.path(), CoordinatorConfig::default())
+            .with_coordinator_spawner(spawner(cache.path(), "plan", failing));
+
+        let mut recorder = Recorder::default();
+        let outcome = executor.run(&mut recorder, &mut Quiet).await.unwrap();
+        assert_eq!(outcome.failed_phase, Some(CoordinatorPhase::Planning));
+        assert!(outcome.worktree_path.is_none());
+        assert_eq!(recorder.agents.len(), 3);
+        assert_eq!(recorder.retries.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_subtasks_run_in_dependency_order() {
+        let project = project();
+        let cache = tempfile::tempdir().unwrap();
+        let config = CoordinatorConfig {
+            run_review: false,
+            ..Default::default()
+        };
+        let mut executor = executor(project.path(), cache.path(), config)
+            .with_implement_spawner(spawner(
+                cache.path(),
+                "impl",
+                MockScript::new().write_file("fixed", "yes\n"),
+            ))
+            .with_test_spawner(spawner(cache.path(), "tests", MockScript::new()));
+        executor.workflow.state_mut().set_subtasks(vec![
+            SubTask::new("impl", "Write the parser").with_dependencies(vec!["tests".into()]),
+            SubTask::new("tests", "Test the parser").with_agent_type(AgentType::Test),
+            SubTask::new("docs", "Document it").with_dependencies(vec!["missing".into()]),
+        ]);
+
+        let mut recorder = Recorder::default();
+        let outcome = executor.run(&mut recorder, &mut Quiet).await.unwrap();
+        assert_eq!(outcome.failed_phase, Some(CoordinatorPhase::Implementing));
+        assert!(outcome.error.unwrap().contains("unmet dependencies: docs"));
+        assert_eq!(
+            recorder.agents,
+            [
+                AgentType::Coordinator,
+                AgentType::Test,
+                AgentType::Implement
+            ]
+        );
+        assert_eq!(
+            executor.workflow().state().completed_subtasks,
+            ["tests", "impl"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_plan_runs_ready_subtasks_together() {
+        let project = project();
+        let cache = tempfile::tempdir().unwrap();
+        let config = CoordinatorConfig {
+            run_review: false,
+            ..Default::default()
+        };
+        let plan = r#"Lexer and parser are independent.
+
+```json
+{"subtasks": [
+  {"id": "lexer", "description": "Add the lexer", "files": ["src/lexer.rs"]},
+  {"id": "parser", "description": "Add the parser", "files": ["src/parser.rs"]},
+  {"id": "errors", "description": "Lexer errors", "files": ["src/lexer.rs"]},
+  {"id": "tests", "description": "Test it all", "agent": "test",
+   "files": ["tests/parse.rs"], "depends_on": ["lexer", "parser", "errors"]}
+]}
+```"#;
+        let mut executor = executor(project.path(), cache.path(), config)
+            .with_coordinator_spawner(spawner(
+                cache.path(),
+                "plan",
+                MockScript::new().assistant_text(plan),
+            ))
+            .with_implement_spawner(spawner(
+                cache.path(),
+                "impl",
+                MockScript::new().write_file("fixed", "yes\n"),
+            ))
+            .with_test_spawner(spawner(cache.path(), "tests", MockScript::new()));
+
+        let mut recorder = Recorder::default();
+        let outcome = executor.run(&mut recorder, &mut Quiet).await.unwrap();
+        assert!(outcome.completed, "{:?}", outcome.error);
+
+        // errors shares a file with lexer, so it waits for the next batch
+        assert_eq!(
+            recorder.events,
+            [
+                "coordinator",
+                "lexer",
+                "parser",
+                "implement",
+                "implement",
+                "errors",
+                "implement",
+                "tests",
+                "test",
+            ]
+        );
+        let state = executor.workflow().state();
+        assert_eq!(state.subtasks.len(), 4);
+        assert!(state.all_subtasks_complete());
+        assert!(state.plan.as_deref().unwrap().contains("independent"));
+    }
+
+    #[tokio::test]
+    async fn test_isolated_subtasks_are_merged() {
+        let project = project();
+        let cache = tempfile::tempdir().unwrap();
+        let config = CoordinatorConfig {
+            run_review: false,
+            isolate_subtasks: true,
+            ..Default::default()
+        };
+        let mut executor = executor(project.path(), cache.path(), config)
+            .with_implement_spawner(spawner(
+                cache.path(),
+                "impl",
+                MockScript::new().write_file("fixed", "yes\n"),
+            ))
+            .with_test_spawner(spawner(
+                cache.path(),
+                "tests",
+                MockScript::new().write_file("tests.txt", "ok\n"),
+            ));
+        executor.workflow.state_mut().set_subtasks(vec![
+            SubTask::new("impl", "Write the parser"),
+            SubTask::new("tests", "Test the parser").with_agent_type(AgentType::Test),
+        ]);
+
+        let mut recorder = Recorder::default();
+        let outcome = executor.run(&mut recorder, &mut Quiet).await.unwrap();
+        assert!(outcome.completed, "{:?}", outcome.error);
+        assert_eq!(
+            recorder.events,
+            ["coordinator", "impl", "tests", "implement", "test"]
+        );
+
+        // Both subtasks' work ends up in the main worktree
+        let worktree = outcome.worktree_path.unwrap();
+        assert!(worktree.join("fixed").exists());
+        assert!(worktree.join("tests.txt").exists());
+        let log = git(&worktree, &["log", "--format=%s"]).unwrap();
+        assert!(log.contains("impl: Write the parser"));
+        assert!(log.contains("tests: Test the parser"));
+
+        // The subtask worktrees and branches are gone
+        let branches = git(project.path(), &["branch", "--list", "murmur/*"]).unwrap();
+        assert_eq!(branches.trim(), "+ murmur/add-a-parser");
+        let worktrees = git(project.path(), &["worktree", "list"]).unwrap();
+        assert_eq!(worktrees.lines().count(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_invalid_plan_is_retried() {
+        let project = project();
+        let cache = tempfile::tempdir().unwrap();
+        let config = CoordinatorConfig {
+            max_retries: 1,
+            ..Default::default()
+        };
+        let plan = "```json\n{\"subtasks\": [{\"id\": \"a\", \"description\": \"A\", \
+                    \"depends_on\": [\"b\"]}]}\n```";
+        let mut executor = executor(project.path(), cache.path(), config).with_coordinator_spawner(
+            spawner(cache.path(), "plan", MockScript::new().assistant_text(plan)),
+        );
+
+        let mut recorder = Recorder::default();
+        let outcome = executor.run(&mut recorder, &mut Quiet).await.unwrap();
+        assert_eq!(outcome.failed_phase, Some(CoordinatorPhase::Planning));
+        assert!(outcome
+            .error
+            .unwrap()
+            .contains("a depends on unknown subtask b"));
+        assert_eq!(
+            recorder.agents,
+            [AgentType::Coordinator, AgentType::Coordinator]
+        );
+        assert_eq!(recorder.retries, [CoordinatorPhase::Planning]);
+    }
+
+    #[tokio::test]
+    async fn test_rejection_before_pr_reworks_implementation() {
+        let project = project();
+        let cache = tempfile::tempdir().unwrap();
+        let config = CoordinatorConfig {
+            run_review: false,
+            ..Default::default()
+        };
+        let gates = ApprovalGates::before(["creating_pr"])
+            .with_poll_interval(std::time::Duration::from_millis(1))
+            .with_source(Decisions(Mutex::new(vec![
+                ApprovalDecision::Rejected("Add a docstring".to_string()),
+                ApprovalDecision::Approved,
+            ])));
+        let mut executor = executor(project.path(), cache.path(), config)
+            .with_approval_gates(gates)
+            .with_database(Database::in_memory().unwrap())
+            .with_implement_spawner(spawner(
+                cache.path(),
+                "impl",
+                MockScript::new()
+                    .write_file("fixed", "yes\n")
+                    .assistant_text("Done"),
+            ));
+
+        let mut recorder = Recorder::default();
+        let outcome = executor.run(&mut recorder, &mut Quiet).await.unwrap();
+        assert!(outcome.completed, "{:?}", outcome.error);
+        assert_eq!(
+            recorder.phases,
+            [
+                CoordinatorPhase::Planning,
+                CoordinatorPhase::SetupWorktree,
+                CoordinatorPhase::Implementing,
+                CoordinatorPhase::Testing,
+                CoordinatorPhase::Reviewing,
+                CoordinatorPhase::Implementing,
+                CoordinatorPhase::Testing,
+                CoordinatorPhase::Reviewing,
+                CoordinatorPhase::CreatingPR,
+            ]
+        );
+        assert_eq!(
+            recorder.agents,
+            [
+                AgentType::Coordinator,
+                AgentType::Implement,
+                AgentType::Implement
+            ]
+        );
+        assert!(!recorder.prompts[1].contains("Add a docstring"));
+        assert!(recorder.prompts[2].contains("rejected the previous attempt:\n\nAdd a docstring"));
+        assert!(executor.workflow().state().rejection.is_none());
+    }
+}
@@ -0,0 +1,435 @@
+//! Declarative workflow definitions
+//!
+//! A repository can describe its own pipelines in `.murmur/workflows/*.toml`
+//! instead of using the fixed TDD and coordinator phases. Each file declares
+//! the phases in order, the agent and prompt for each, the gates a phase has
+//! to pass and where to go on success and failure:
+//!
+//! ```toml
+//! description = "Documentation changes, reviewed"
+//!
+//! [[phases]]
+//! name = "write"
+//! agent = "implement"
+//! prompt = "Only change files under docs/."
+//! gates = [{ lint = "markdownlint docs" }]
+//! on_failure = "write"
+//!
+//! [[phases]]
+//! name = "review"
+//! agent = "review"
+//! gates = ["review-approved"]
+//! on_failure = "write"
+//! ```
+//!
+//! A phase goes to the next one on success and to `failed` on failure unless
+//! it says otherwise; `complete` and `failed` end the workflow. The
+//! [`WorkflowEngine`](super::WorkflowEngine) runs definitions.
+
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use super::state::StateMachine;
+use crate::agent::AgentType;
+use crate::{Error, Result};
+
+/// Transition target that ends the workflow successfully
+pub const COMPLETE: &str = "complete";
+
+/// Transition target that ends the workflow as failed
+pub const FAILED: &str = "failed";
+
+/// Times a phase may run when neither it nor the workflow sets a limit
+const DEFAULT_MAX_ATTEMPTS: u32 = 3;
+
+/// A check a phase has to pass before it succeeds
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Gate {
+    /// The tests run and at least one fails
+    TestsRed,
+    /// The tests run and all pass
+    TestsGreen,
+    /// The review agent of the phase approves
+    ReviewApproved,
+    /// A lint command, run with `sh -c`, exits successfully
+    Lint(String),
+    /// The checks of the `[quality]` config section pass
+    Quality,
+}
+
+impl std::fmt::Display for Gate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Gate::TestsRed => write!(f, "tests red"),
+            Gate::TestsGreen => write!(f, "tests green"),
+            Gate::ReviewApproved => write!(f, "review approved"),
+            Gate::Lint(command) => write!(f, "lint `{}`", command),
+            Gate::Quality => write!(f, "quality checks"),
+        }
+    }
+}
+
+/// One phase of a workflow definition (`[[phases]]`)
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct PhaseDefinition {
+    /// Name other phases refer to it by
+    pub name: String,
+
+    /// Agent run in this phase; a phase without one only checks its gates
+    #[serde(default)]
+    pub agent: Option<AgentType>,
+
+    /// Instructions for the agent, added to the task
+    #[serde(default)]
+    pub prompt: Option<String>,
+
+    /// Checks that must pass for the phase to succeed
+    #[serde(default)]
+    pub gates: Vec<Gate>,
+
+    /// Phase to go to on success, defaulting to the next one
+    #[serde(default)]
+    pub on_success: Option<String>,
+
+    /// Phase to go to on failure, defaulting to `failed`
+    #[serde(default)]
+    pub on_failure: Option<String>,
+
+    /// Times this phase may run, overriding the workflow's limit
+    #[serde(default)]
+    pub max_attempts: Option<u32>,
+}
+
+/// A workflow read from `.murmur/workflows/<name>.toml`
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct WorkflowDefinition {
+    /// Name of the workflow, taken from the file name
+    #[serde(default, skip_serializing)]
+    pub name: String,
+
+    /// What the workflow is for
+    #[serde(default)]
+    pub description: Option<String>,
+
+    /// Times each phase may run before the workflow fails
+    #[serde(default = "default_max_attempts")]
+    pub max_attempts: u32,
+
+    /// The phases, starting with the first
+    pub phases: Vec<PhaseDefinition>,
+}
+
+fn default_max_attempts() -> u32 {
+    DEFAULT_MAX_ATTEMPTS
+}
+
+impl WorkflowDefinition {
+    /// Parse and validate a definition
+    pub fn from_toml(name: impl Into<String>, content: &str) -> Result<Self> {
+        let name = name.into();
+        let mut definition: WorkflowDefinition = toml::from_str(content)
+            .map_err(|e| Error::Config(format!("Invalid workflow {}: {}", name, e)))?;
+        definition.name = name;
+        definition.validate()?;
+        Ok(definition)
+    }
+
+    /// Load a definition file, naming the workflow after it
+    pub fn load(path: &Path) -> Result<Self> {
+        let name = path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or_default();
+        let content = std::fs::read_to_string(path)?;
+        Self::from_toml(name, &content)
+    }
+
+    /// The directory of workflow definitions inside a repository
+    pub fn dir_for(repo_root: impl AsRef<Path>) -> PathBuf {
+        repo_root.as_ref().join(".murmur").join("workflows")
+    }
+
+    /// Definition files in a repository, sorted by name
+    pub fn paths(repo_root: impl AsRef<Path>) -> Result<Vec<PathBuf>> {
+        let dir = Self::dir_for(repo_root);
+        if !dir.is_dir() {
+            return Ok(Vec::new());
+        }
+        let mut paths: Vec<PathBuf> = std::fs::read_dir(&dir)?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "toml"))
+            .collect();
+        paths.sort();
+        Ok(paths)
+    }
+
+    /// Every definition in a repository
+    ///
+    /// Fails on the first file that doesn't load.
+    pub fn discover(repo_root: impl AsRef<Path>) -> Result<Vec<Self>> {
+        Self::paths(repo_root)?
+            .iter()
+            .map(|path| Self::load(path))
+            .collect()
+    }
+
+    /// The definition called `name` in a repository
+    pub fn find(repo_root: impl AsRef<Path>, name: &str) -> Result<Self> {
+        let path = Self::dir_for(repo_root).join(format!("{}.toml", name));
+        if !path.is_file() {
+            return Err(Error::Config(format!(
+                "No workflow named {} (expected {})",
+                name,
+                path.display()
+            )));
+        }
+        Self::load(&path)
+    }
+
+    /// Get a phase by name
+    pub fn phase(&self, name: &str) -> Option<&PhaseDefinition> {
+        self.phases.iter().find(|p| p.name == name)
+    }
+
+    /// Name of the phase the workflow starts in
+    pub fn start(&self) -> &str {
+        self.phases.first().map_or(COMPLETE, |p| p.name.as_str())
+    }
+
+    /// Where a phase goes when it succeeds
+    pub fn on_success(&self, phase: &str) -> &str {
+        let Some(index) = self.phases.iter().position(|p| p.name == phase) else {
+            return FAILED;
+        };
+        match self.phases[index].on_success {
+            Some(ref target) => target,
+            None => self
+                .phases
+                .get(index + 1)
+                .map_or(COMPLETE, |p| p.name.as_str()),
+        }
+    }
+
+    /// Where a phase goes when it fails
+    pub fn on_failure(&self, phase: &str) -> &str {
+        self.phase(phase)
+            .and_then(|p| p.on_failure.as_deref())
+            .unwrap_or(FAILED)
+    }
+
+    /// Times a phase may run before the workflow fails
+    pub fn max_attempts(&self, phase: &str) -> u32 {
+        self.phase(phase)
+            .and_then(|p| p.max_attempts)
+            .unwrap_or(self.max_attempts)
+    }
+
+    /// A state machine allowing exactly the declared transitions
+    pub fn state_machine(&self) -> StateMachine<String> {
+        let transitions = self
+            .phases
+            .iter()
+            .flat_map(|p| {
+                [
+                    (p.name.clone(), self.on_success(&p.name).to_string()),
+                    (p.name.clone(), self.on_failure(&p.name).to_string()),
+                ]
+            })
+            .collect();
+        StateMachine::new(self.start().to_string()).add_transitions(transitions)
+    }
+
+    /// Check that the definition can be run
+    ///
+    /// Phase names must be unique and not reserved, transitions must name a
+    /// phase or `complete`/`failed`, and a `review-approved` gate needs a
+    /// review agent in its phase.
+    pub fn validate(&self) -> Result<()> {
+        let invalid =
+            |reason: String| Err(Error::Config(format!("Workflow {}: {}", self.name, reason)));
+
+        if self.phases.is_empty() {
+            return invalid("no phases defined".to_string());
+        }
+        for (i, phase) in self.phases.iter().enumerate() {
+            if phase.name.trim().is_empty() {
+                return invalid(format!("phase {} has no name", i + 1));
+            }
+            if phase.name == COMPLETE || phase.name == FAILED {
+                return invalid(format!("phase name {} is reserved", phase.name));
+            }
+            if self.phases[..i].iter().any(|p| p.name == phase.name) {
+                return invalid(format!("duplicate phase {}", phase.name));
+            }
+        }
+        for phase in &self.phases {
+            if phase.agent.is_none() && phase.gates.is_empty() {
+                return invalid(format!(
+                    "phase {} has neither an agent nor gates",
+                    phase.name
+                ));
+            }
+            for target in [&phase.on_success, &phase.on_failure].into_iter().flatten() {
+                if target != COMPLETE && target != FAILED && self.phase(target).is_none() {
+                    return invalid(format!(
+                        "phase {} goes to unknown phase {}",
+                        phase.name, target
+                    ));
+                }
+            }
+            if phase.gates.contains(&Gate::ReviewApproved) && phase.agent != Some(AgentType::Review)
+            {
+                return invalid(format!(
+                    "phase {} has a review-approved gate but no review agent",
+                    phase.name
+                ));
+            }
+            if phase.max_attempts == Some(0) {
+                return invalid(format!("phase {} has max_attempts = 0", phase.name));
+            }
+        }
+        if self.max_attempts == 0 {
+            return invalid("max_attempts = 0".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::workflow::state::Workflow;
+
+    const DOCS: &str = r#"
+description = "Docs only"
+
+[[phases]]
+name = "write"
+agent = "implement"
+prompt = "Only touch docs/"
+gates = [{ lint = "markdownlint docs" }]
+on_failure = "write"
+
+[[phases]]
+name = "review"
+agent = "review"
+gates = ["review-approved", "quality"]
+on_failure = "write"
+max_attempts = 2
+"#;
+
+    #[test]
+    fn test_parse_definition() {
+        let definition = WorkflowDefinition::from_toml("docs", DOCS).unwrap();
+        assert_eq!(definition.name, "docs");
+        assert_eq!(definition.description.as_deref(), Some("Docs only"));
+        assert_eq!(definition.start(), "write");
+
+        let write = definition.phase("write").unwrap();
+        assert_eq!(write.agent, Some(AgentType::Implement));
+        assert_eq!(write.gates, [Gate::Lint("markdownlint docs".to_string())]);
+        assert_eq!(
+            definition.phase("review").unwrap().gates,
+            [Gate::ReviewApproved, Gate::Quality]
+        );
+
+        assert_eq!(definition.on_success("write"), "review");
+        assert_eq!(definition.on_success("review"), COMPLETE);
+        assert_eq!(definition.on_failure("review"), "write");
+        assert_eq!(definition.max_attempts("write"), 3);
+        assert_eq!(definition.max_attempts("review"), 2);
+    }
+
+    #[test]
+    fn test_state_machine_allows_declared_transitions() {
+        let definition = WorkflowDefinition::from_toml("docs", DOCS).unwrap();
+        let mut machine = definition.state_machine();
+        assert_eq!(machine.current_phase(), "write");
+        assert!(machine.can_transition_to(&"write".to_string()));
+        assert!(machine.can_transition_to(&"review".to_string()));
+        assert!(!machine.can_transition_to(&COMPLETE.to_string()));
+
+        machine.transition_to("review".to_string()).unwrap();
+        assert!(machine.can_transition_to(&COMPLETE.to_string()));
+        assert!(machine.transition_to(FAILED.to_string()).is_err());
+    }
+
+    #[test]
+    fn test_invalid_definitions() {
+        let cases = [
+            ("phases = []", "no phases"),
+            (
+                "[[phases]]\nname = \"a\"\nagent = \"implement\"\n[[phases]]\nname = \"a\"\nagent = \"test\"",
+                "duplicate phase a",
+            ),
+            (
+                "[[phases]]\nname = \"complete\"\nagent = \"implement\"",
+                "reserved",
+            ),
+            (
+                "[[phases]]\nname = \"a\"\nagent = \"implement\"\non_failure = \"b\"",
+                "unknown phase b",
+            ),
+            ("[[phases]]\nname = \"a\"", "neither an agent nor gates"),
+            (
+                "[[phases]]\nname = \"a\"\nagent = \"implement\"\ngates = [\"review-approved\"]",
+                "no review agent",
+            ),
+            (
+                "[[phases]]\nname = \"a\"\ngates = [\"tests-blue\"]",
+                "Invalid workflow",
+            ),
+            (
+                "[[phases]]\nname = \"a\"\nagent = \"implement\"\nretries = 2",
+                "Invalid workflow",
+            ),
+        ];
+        for (content, expected) in cases {
+            let err = WorkflowDefinition::from_toml("bad", content).unwrap_err();
+            assert!(
+                err.to_string().contains(expected),
+                "{:?} should fail with {:?}, got {}",
+                content,
+                expected,
+                err
+            );
+        }
+    }
+
+    #[test]
+    fn test_discover_and_find() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(WorkflowDefinition::discover(dir.path()).unwrap().is_empty());
+
+        let workflows = WorkflowDefinition::dir_for(dir.path());
+        std::fs::create_dir_all(&workflows).unwrap();
+        std::fs::write(workflows.join("docs.toml"), DOCS).unwrap();
+        std::fs::write(
+            workflows.join("build.toml"),
+            "[[phases]]\nname = \"check\"\ngates = [\"tests-green\"]",
+        )
+        .unwrap();
+        std::fs::write(workflows.join("notes.md"), "not a workflow").unwrap();
+
+        let names: Vec<String> = WorkflowDefinition::discover(dir.path())
+            .unwrap()
+            .into_iter()
+            .map(|d| d.name)
+            .collect();
+        assert_eq!(names, ["build", "docs"]);
+
+        assert_eq!(
+            WorkflowDefinition::find(dir.path(), "docs")
+                .unwrap()
+                .phases
+                .len(),
+            2
+        );
+        let err = WorkflowDefinition::find(dir.path(), "missing").unwrap_err();
+        assert!(err.to_string().contains("No workflow named missing"));
+    }
+}
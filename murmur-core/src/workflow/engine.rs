@@ -0,0 +1,757 @@
+//! Engine that runs declarative workflow definitions
+//!
+//! [`WorkflowEngine`] walks a [`WorkflowDefinition`] with a
+//! [`StateMachine`](super::StateMachine) that only allows the declared
+//! transitions. Each phase runs its agent in the working directory, then its
+//! gates in order. A phase that passes every gate follows `on_success`; an
+//! agent failure or the first failing gate follows `on_failure`, and the
+//! reason is given to the next agent as feedback. A phase that runs more
+//! than its `max_attempts` fails the workflow.
+//!
+//! When the workflow has test gates, the tests are run for a baseline before
+//! the first agent starts. If the framework reports individual tests, the
+//! gates compare against it, so tests that were already failing neither
+//! count as red nor block green.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use murmur_db::Database;
+
+use super::definition::{Gate, PhaseDefinition, WorkflowDefinition, COMPLETE, FAILED};
+use super::quality::{command_problem, QualityConfig, QualityGates};
+use super::review::{ReviewIssue, ReviewResult};
+use super::runner::{AgentRunner, RunAccounting};
+use super::state::Workflow;
+use super::test_config::TestConfig;
+use super::test_runner::{compare_to_baseline, TestCase, TestResults, TestRunner};
+use crate::agent::{AgentSpawner, AgentType, PromptBuilder, PromptTemplates, StreamHandler};
+use crate::config::AgentConfig;
+use crate::{Error, Result};
+
+/// Callbacks for following a workflow run
+///
+/// Every method has an empty default, so observers only implement the
+/// events they care about.
+pub trait WorkflowObserver: Send {
+    /// Called when a phase starts, with how many times it has run including this one
+    fn on_phase_start(&mut self, _phase: &str, _attempt: u32) {}
+
+    /// Called before an agent is spawned
+    fn on_agent_start(&mut self, _phase: &str, _agent_type: AgentType, _prompt: &str) {}
+
+    /// Called with each line of test output while the tests run
+    fn on_test_output(&mut self, _line: &str) {}
+
+    /// Called with the results of a test run
+    fn on_test_results(&mut self, _results: &TestResults) {}
+
+    /// Called with the results of the baseline run taken before the first agent
+    fn on_baseline(&mut self, _results: &TestResults) {}
+
+    /// Called after a gate is checked, with the problem if it failed
+    fn on_gate(&mut self, _phase: &str, _gate: &Gate, _problem: Option<&str>) {}
+
+    /// Called when the workflow moves from one phase to another
+    fn on_transition(&mut self, _from: &str, _to: &str) {}
+}
+
+/// Observer that ignores every event
+impl WorkflowObserver for () {}
+
+/// Outcome of a workflow run
+#[derive(Debug, Clone)]
+pub struct WorkflowOutcome {
+    /// Whether the workflow reached `complete`
+    pub completed: bool,
+    /// The phases run, in order
+    pub history: Vec<String>,
+    /// The phase the workflow failed in, if it failed
+    pub failed_phase: Option<String>,
+    /// Why the workflow failed, if it failed
+    pub error: Option<String>,
+    /// Results of the last test run, if any
+    pub last_results: Option<TestResults>,
+}
+
+/// Runs a [`WorkflowDefinition`] for a task
+pub struct WorkflowEngine {
+    definition: WorkflowDefinition,
+    task: String,
+    workdir: PathBuf,
+    config: AgentConfig,
+    test_config: TestConfig,
+    quality_config: QualityConfig,
+    templates: PromptTemplates,
+    spawners: HashMap<AgentType, AgentSpawner>,
+    accounting: RunAccounting,
+    db: Option<Database>,
+    last_results: Option<TestResults>,
+    baseline_taken: bool,
+    baseline: Option<Vec<TestCase>>,
+    new_tests: Vec<String>,
+}
+
+impl WorkflowEngine {
+    /// Create an engine running `definition` for `task` in `workdir`
+    pub fn new(
+        definition: WorkflowDefinition,
+        task: impl Into<String>,
+        workdir: impl Into<PathBuf>,
+        config: AgentConfig,
+    ) -> Self {
+        let workdir = workdir.into();
+        Self {
+            definition,
+            task: task.into(),
+            templates: PromptTemplates::discover(&workdir),
+            workdir,
+            config,
+            test_config: TestConfig::default(),
+            quality_config: QualityConfig::default(),
+            spawners: HashMap::new(),
+            accounting: RunAccounting::default(),
+            db: None,
+            last_results: None,
+            baseline_taken: false,
+            baseline: None,
+            new_tests: Vec::new(),
+        }
+    }
+
+    /// Run tests as a `[test]` config section says
+    ///
+    /// A `[test]` section in the repository's `.murmur.toml` still takes
+    /// precedence.
+    pub fn with_test_config(mut self, config: &TestConfig) -> Self {
+        self.test_config = config.clone();
+        self
+    }
+
+    /// Run the checks a `[quality]` config section asks for at `quality` gates
+    ///
+    /// A `[quality]` section in the repository's `.murmur.toml` still takes
+    /// precedence.
+    pub fn with_quality_config(mut self, config: &QualityConfig) -> Self {
+        self.quality_config = config.clone();
+        self
+    }
+
+    /// Enforce spending limits on the agent runs
+    pub fn with_accounting(mut self, accounting: RunAccounting) -> Self {
+        self.accounting = accounting;
+        self
+    }
+
+    /// Record agent runs in this database
+    pub fn with_database(mut self, db: Database) -> Self {
+        self.db = Some(db);
+        self
+    }
+
+    /// Use this spawner for agents of a type (for testing)
+    pub fn with_spawner(mut self, agent_type: AgentType, spawner: AgentSpawner) -> Self {
+        self.spawners.insert(agent_type, spawner);
+        self
+    }
+
+    /// Get the definition being run
+    pub fn definition(&self) -> &WorkflowDefinition {
+        &self.definition
+    }
+
+    /// Run the workflow until it completes or fails
+    ///
+    /// Phase failures end the workflow and are reported in the outcome;
+    /// errors are returned only for transitions the definition doesn't allow.
+    pub async fn run<O: WorkflowObserver, H: StreamHandler>(
+        &mut self,
+        observer: &mut O,
+        handler: &mut H,
+    ) -> Result<WorkflowOutcome> {
+        let mut machine = self.definition.state_machine();
+        let mut attempts: HashMap<String, u32> = HashMap::new();
+        let mut history = Vec::new();
+        let mut feedback: Option<String> = None;
+        let mut failure: Option<(String, String)> = None;
+
+        loop {
+            let name = machine.current_phase().clone();
+            if name == COMPLETE || name == FAILED {
+                break;
+            }
+            let Some(phase) = self.definition.phase(&name).cloned() else {
+                return Err(Error::Config(format!("Unknown workflow phase {}", name)));
+            };
+
+            let attempt = attempts.entry(name.clone()).or_default();
+            *attempt += 1;
+            let attempt = *attempt;
+            let max_attempts = self.definition.max_attempts(&name);
+            if attempt > max_attempts {
+                let reason = match feedback.take() {
+                    Some(problem) => format!(
+                        "Phase {} ran {} times without passing: {}",
+                        name, max_attempts, problem
+                    ),
+                    None => format!("Phase {} ran {} times", name, max_attempts),
+                };
+                failure = Some((name, reason));
+                break;
+            }
+
+            observer.on_phase_start(&name, attempt);
+            history.push(name.clone());
+            let problem = self
+                .run_phase(&phase, feedback.take(), observer, handler)
+                .await?;
+
+            let next = match problem {
+                None => self.definition.on_success(&name).to_string(),
+                Some(_) => self.definition.on_failure(&name).to_string(),
+            };
+            if next == FAILED {
+                if let Some(ref problem) = problem {
+                    failure = Some((name.clone(), problem.clone()));
+                }
+            }
+            feedback = problem;
+            observer.on_transition(&name, &next);
+            machine.transition_to(next)?;
+        }
+
+        let completed = failure.is_none() && machine.current_phase() == COMPLETE;
+        let (failed_phase, error) = match failure {
+            Some((phase, error)) => (Some(phase), Some(error)),
+            None => (None, None),
+        };
+        Ok(WorkflowOutcome {
+            completed,
+            history,
+            failed_phase,
+            error,
+            last_results: self.last_results.take(),
+        })
+    }
+
+    /// Run a phase's agent and gates, returning why it failed, if it did
+    async fn run_phase<O: WorkflowObserver, H: StreamHandler>(
+        &mut self,
+        phase: &PhaseDefinition,
+        feedback: Option<String>,
+        observer: &mut O,
+        handler: &mut H,
+    ) -> Result<Option<String>> {
+        let mut output = String::new();
+        if let Some(agent_type) = phase.agent {
+            if !self.baseline_taken && self.has_test_gates() {
+                self.take_baseline(observer).await;
+            }
+            let prompt = self.phase_prompt(phase, agent_type, feedback.as_deref())?;
+            observer.on_agent_start(&phase.name, agent_type, &prompt);
+            match self.run_agent(agent_type, &prompt, handler).await {
+                Ok(text) => output = text,
+                Err(e) => return Ok(Some(format!("The {} agent failed: {}", agent_type, e))),
+            }
+        }
+
+        // Test gates in one phase share a run
+        let mut results: Option<TestResults> = None;
+        for gate in &phase.gates {
+            let problem = match gate {
+                Gate::TestsRed | Gate::TestsGreen => {
+                    let results = match results {
+                        Some(ref results) => results,
+                        None => results.insert(self.run_tests(observer).await),
+                    };
+                    match gate {
+                        Gate::TestsRed => self.red_problem(results),
+                        _ => self.green_problem(results),
+                    }
+                }
+                Gate::ReviewApproved => review_problem(&output),
+                Gate::Lint(command) => command_problem(command, &self.workdir, None).await?,
+                Gate::Quality => self.quality_problem().await?,
+            };
+            observer.on_gate(&phase.name, gate, problem.as_deref());
+            if problem.is_some() {
+                return Ok(problem);
+            }
+        }
+        Ok(None)
+    }
+
+    /// The prompt for a phase's agent
+    ///
+    /// The phase's instructions and the feedback from the last failure are
+    /// added to the task.
+    fn phase_prompt(
+        &self,
+        phase: &PhaseDefinition,
+        agent_type: AgentType,
+        feedback: Option<&str>,
+    ) -> Result<String> {
+        let mut task = self.task.clone();
+        if let Some(ref instructions) = phase.prompt {
+            task.push_str(&format!("\n\n## Instructions\n\n{}", instructions.trim()));
+        }
+        if let Some(feedback) = feedback {
+            task.push_str(&format!(
+                "\n\n## Feedback\n\nThe previous attempt didn't pass:\n\n{}",
+                feedback.trim()
+            ));
+        }
+        PromptBuilder::new(agent_type)
+            .templates(self.templates.clone())
+            .task(task)
+            .try_build()
+    }
+
+    /// Run the tests, reporting output and results to `observer`
+    async fn run_tests<O: WorkflowObserver>(&mut self, observer: &mut O) -> TestResults {
+        let runner = TestRunner::from_config(&self.workdir, &self.test_config);
+        let results = if runner.suites().is_empty() {
+            let mut results = TestResults::empty();
+            results.execution_error = Some("No test suites found".to_string());
+            results
+        } else {
+            runner
+                .run_with_handler(&mut |line: &str| observer.on_test_output(line))
+                .await
+        };
+        observer.on_test_results(&results);
+        self.last_results = Some(results.clone());
+        results
+    }
+
+    /// Whether any phase has a test gate
+    fn has_test_gates(&self) -> bool {
+        self.definition.phases.iter().any(|phase| {
+            phase
+                .gates
+                .iter()
+                .any(|gate| matches!(gate, Gate::TestsRed | Gate::TestsGreen))
+        })
+    }
+
+    /// Run the tests before any agent changes the code
+    async fn take_baseline<O: WorkflowObserver>(&mut self, observer: &mut O) {
+        self.baseline_taken = true;
+        let runner = TestRunner::from_config(&self.workdir, &self.test_config);
+        if runner.suites().is_empty() {
+            return;
+        }
+        let results = runner
+            .run_with_handler(&mut |line: &str| observer.on_test_output(line))
+            .await;
+        observer.on_baseline(&results);
+        if results.has_test_cases() {
+            self.baseline = Some(results.tests);
+        }
+    }
+
+    /// Why a run doesn't count as red, if it doesn't
+    ///
+    /// The tests new since the baseline are remembered for the green gates.
+    fn red_problem(&mut self, results: &TestResults) -> Option<String> {
+        let Some(diff) = compare_to_baseline(results, self.baseline.as_deref(), &self.new_tests)
+        else {
+            return red_problem(results);
+        };
+        let problem = diff.red_problem();
+        if problem.is_none() {
+            self.new_tests = diff.new_failing;
+        }
+        problem
+    }
+
+    /// Why a run doesn't count as green, if it doesn't
+    fn green_problem(&self, results: &TestResults) -> Option<String> {
+        match compare_to_baseline(results, self.baseline.as_deref(), &self.new_tests) {
+            // Without a red gate first there are no new tests to insist on
+            Some(diff) if self.new_tests.is_empty() => diff.passing_problem(),
+            Some(diff) => diff.green_problem(),
+            None => green_problem(results),
+        }
+    }
+
+    /// Run the configured quality checks, returning what failed
+    async fn quality_problem(&self) -> Result<Option<String>> {
+        let gates = QualityGates::from_config(&self.workdir, &self.quality_config);
+        if gates.is_empty() {
+            return Ok(Some("No quality checks configured".to_string()));
+        }
+        gates.run(&mut |_, _| {}).await
+    }
+
+    /// Run one agent to completion in the working directory
+    async fn run_agent<H: StreamHandler>(
+        &self,
+        agent_type: AgentType,
+        prompt: &str,
+        handler: &mut H,
+    ) -> Result<String> {
+        let default;
+        let spawner = match self.spawners.get(&agent_type) {
+            Some(spawner) => spawner,
+            None => {
+                default = AgentSpawner::from_config(self.config.clone(), agent_type);
+                &default
+            }
+        };
+        let runner = AgentRunner {
+            config: &self.config,
+            accounting: &self.accounting,
+            db: self.db.as_ref(),
+            issue_number: None,
+        };
+        let (output, _) = runner
+            .run(spawner, agent_type, prompt, &self.workdir, handler)
+            .await?;
+        Ok(output)
+    }
+}
+
+/// Why a run doesn't count as red by its counts, if it doesn't
+fn red_problem(results: &TestResults) -> Option<String> {
+    if results.is_red() {
+        None
+    } else if results.execution_error.is_none() && results.passed > 0 {
+        Some("Tests passed unexpectedly - they may not be testing new behavior".to_string())
+    } else {
+        Some(format!("Expected failing tests: {}", results.summary()))
+    }
+}
+
+/// Why a run doesn't count as green by its counts, if it doesn't
+fn green_problem(results: &TestResults) -> Option<String> {
+    if results.execution_error.is_none() && results.failed == 0 {
+        return None;
+    }
+    let mut problem = format!("Tests are failing: {}", results.summary());
+    for test in results.failures() {
+        match test.message {
+            Some(ref message) => problem.push_str(&format!("\n- {}: {}", test.name, message)),
+            None => problem.push_str(&format!("\n- {}", test.name)),
+        }
+    }
+    Some(problem)
+}
+
+/// Why a review doesn't count as approved, if it doesn't
+fn review_problem(output: &str) -> Option<String> {
+    let result = match ReviewResult::parse(output) {
+        Ok(result) => result,
+        Err(e) => return Some(format!("The review could not be read: {}", e)),
+    };
+    if result.verdict.is_approved() {
+        return None;
+    }
+    let mut problem = format!("The review was not approved ({})", result.verdict.as_str());
+    push_issues(&mut problem, "Blocking", &result.blocking);
+    push_issues(&mut problem, "Important", &result.important);
+    Some(problem)
+}
+
+/// Append a list of review issues to a problem description
+fn push_issues(problem: &mut String, title: &str, issues: &[ReviewIssue]) {
+    if issues.is_empty() {
+        return;
+    }
+    problem.push_str(&format!("\n\n{}:\n", title));
+    for issue in issues {
+        problem.push_str(&format!("\n- {}", issue));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::fixtures::{mock_config, spawner};
+    use crate::agent::MockScript;
+
+    struct Quiet;
+
+    impl StreamHandler for Quiet {
+        fn on_assistant_text(&mut self, _text: &str) {}
+    }
+
+    /// Records the prompts and gate results
+    #[derive(Default)]
+    struct Recorder {
+        prompts: Vec<(String, String)>,
+        gates: Vec<(String, bool)>,
+        transitions: Vec<(String, String)>,
+    }
+
+    impl WorkflowObserver for Recorder {
+        fn on_agent_start(&mut self, phase: &str, _agent_type: AgentType, prompt: &str) {
+            self.prompts.push((phase.to_string(), prompt.to_string()));
+        }
+
+        fn on_gate(&mut self, phase: &str, _gate: &Gate, problem: Option<&str>) {
+            self.gates.push((phase.to_string(), problem.is_none()));
+        }
+
+        fn on_transition(&mut self, from: &str, to: &str) {
+            self.transitions.push((from.to_string(), to.to_string()));
+        }
+    }
+
+    /// A project whose tests pass once `fixed` exists
+    fn project() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join(".murmur.toml"),
+            "[test]\ncommand = \"test -f fixed\"\n",
+        )
+        .unwrap();
+        dir
+    }
+
+    fn definition(content: &str) -> WorkflowDefinition {
+        WorkflowDefinition::from_toml("test", content).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_runs_phases_through_gates() {
+        let project = project();
+        let scripts = tempfile::tempdir().unwrap();
+        let definition = definition(
+            r#"
+[[phases]]
+name = "red"
+gates = ["tests-red"]
+
+[[phases]]
+name = "implement"
+agent = "implement"
+prompt = "Keep it small."
+gates = ["tests-green", { lint = "test -f fixed" }]
+"#,
+        );
+        let mut engine =
+            WorkflowEngine::new(definition, "Add a parser", project.path(), mock_config())
+                .with_spawner(
+                    AgentType::Implement,
+                    spawner(
+                        scripts.path(),
+                        "impl",
+                        MockScript::new().write_file("fixed", "yes\n"),
+                    ),
+                );
+
+        let mut recorder = Recorder::default();
+        let outcome = engine.run(&mut recorder, &mut Quiet).await.unwrap();
+        assert!(outcome.completed, "{:?}", outcome.error);
+        assert_eq!(outcome.history, ["red", "implement"]);
+        assert_eq!(
+            recorder.gates,
+            [
+                ("red".to_string(), true),
+                ("implement".to_string(), true),
+                ("implement".to_string(), true),
+            ]
+        );
+        assert_eq!(recorder.prompts.len(), 1);
+        assert!(recorder.prompts[0].1.contains("Add a parser"));
+        assert!(recorder.prompts[0].1.contains("Keep it small."));
+        assert!(outcome.last_results.unwrap().execution_error.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_test_gates_compare_against_baseline() {
+        // `flaky` fails before and after; `new` exists once tests are written
+        let project = tempfile::tempdir().unwrap();
+        std::fs::write(
+            project.path().join(".murmur.toml"),
+            r#"[test]
+command = """
+echo 'ok 1 - old'
+echo 'not ok 2 - flaky'
+if [ -f new ]; then
+  if [ -f fixed ]; then echo 'ok 3 - new'; else echo 'not ok 3 - new'; fi
+fi
+false
+"""
+format = "tap"
+"#,
+        )
+        .unwrap();
+        let scripts = tempfile::tempdir().unwrap();
+        let definition = || {
+            definition(
+                r#"
+max_attempts = 1
+
+[[phases]]
+name = "write-tests"
+agent = "test"
+gates = ["tests-red"]
+
+[[phases]]
+name = "implement"
+agent = "implement"
+gates = ["tests-green"]
+"#,
+            )
+        };
+
+        // The pre-existing failure alone isn't red
+        let mut engine =
+            WorkflowEngine::new(definition(), "Add a parser", project.path(), mock_config())
+                .with_spawner(
+                    AgentType::Test,
+                    spawner(scripts.path(), "none", MockScript::new()),
+                );
+        let outcome = engine.run(&mut (), &mut Quiet).await.unwrap();
+        assert!(!outcome.completed);
+        assert!(outcome.error.unwrap().contains("No new failing tests"));
+
+        // ...and doesn't block green
+        let mut engine =
+            WorkflowEngine::new(definition(), "Add a parser", project.path(), mock_config())
+                .with_spawner(
+                    AgentType::Test,
+                    spawner(
+                        scripts.path(),
+                        "tests",
+                        MockScript::new().write_file("new", "yes\n"),
+                    ),
+                )
+                .with_spawner(
+                    AgentType::Implement,
+                    spawner(
+                        scripts.path(),
+                        "impl",
+                        MockScript::new().write_file("fixed", "yes\n"),
+                    ),
+                );
+        let outcome = engine.run(&mut (), &mut Quiet).await.unwrap();
+        assert!(outcome.completed, "{:?}", outcome.error);
+        assert_eq!(engine.new_tests, ["new"]);
+    }
+
+    #[tokio::test]
+    async fn test_failure_loops_back_with_feedback() {
+        let project = project();
+        let scripts = tempfile::tempdir().unwrap();
+        let definition = definition(
+            r#"
+max_attempts = 2
+
+[[phases]]
+name = "implement"
+agent = "implement"
+gates = [{ lint = "echo 'missing docs' && false" }]
+on_failure = "implement"
+"#,
+        );
+        let mut engine =
+            WorkflowEngine::new(definition, "Add a parser", project.path(), mock_config())
+                .with_spawner(
+                    AgentType::Implement,
+                    spawner(scripts.path(), "impl", MockScript::new()),
+                );
+
+        let mut recorder = Recorder::default();
+        let outcome = engine.run(&mut recorder, &mut Quiet).await.unwrap();
+        assert!(!outcome.completed);
+        assert_eq!(outcome.failed_phase.as_deref(), Some("implement"));
+        assert!(outcome
+            .error
+            .unwrap()
+            .contains("ran 2 times without passing"));
+        assert_eq!(outcome.history, ["implement", "implement"]);
+
+        // The second attempt is told why the first one failed
+        assert!(!recorder.prompts[0].1.contains("## Feedback"));
+        assert!(recorder.prompts[1].1.contains("## Feedback"));
+        assert!(recorder.prompts[1].1.contains("missing docs"));
+    }
+
+    #[tokio::test]
+    async fn test_review_gate_and_failed_target() {
+        let project = project();
+        let scripts = tempfile::tempdir().unwrap();
+        let definition = definition(
+            r#"
+[[phases]]
+name = "review"
+agent = "review"
+gates = ["review-approved"]
+"#,
+        );
+        let review = MockScript::new().assistant_text(
+            "REVIEW SUMMARY:\n- VERDICT: REQUEST_CHANGES\n- BLOCKING:\n  - src/lib.rs:3 Missing error handling\n",
+        );
+        let mut engine =
+            WorkflowEngine::new(definition, "Add a parser", project.path(), mock_config())
+                .with_spawner(AgentType::Review, spawner(scripts.path(), "review", review));
+
+        let mut recorder = Recorder::default();
+        let outcome = engine.run(&mut recorder, &mut Quiet).await.unwrap();
+        assert!(!outcome.completed);
+        assert_eq!(outcome.failed_phase.as_deref(), Some("review"));
+        assert!(outcome.error.unwrap().contains("not approved"));
+        assert_eq!(
+            recorder.transitions,
+            [("review".to_string(), FAILED.to_string())]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_quality_gate_runs_configured_checks() {
+        let project = project();
+        let definition = || {
+            definition(
+                r#"
+[[phases]]
+name = "check"
+gates = ["quality"]
+"#,
+            )
+        };
+
+        let quality = QualityConfig {
+            commands: vec!["echo 'src/lib.rs: not formatted'; false".to_string()],
+            ..Default::default()
+        };
+        let mut engine =
+            WorkflowEngine::new(definition(), "Add a parser", project.path(), mock_config())
+                .with_quality_config(&quality);
+        let outcome = engine.run(&mut (), &mut Quiet).await.unwrap();
+        assert!(!outcome.completed);
+        assert!(outcome.error.unwrap().contains("not formatted"));
+
+        let mut engine =
+            WorkflowEngine::new(definition(), "Add a parser", project.path(), mock_config());
+        let outcome = engine.run(&mut (), &mut Quiet).await.unwrap();
+        assert!(outcome
+            .error
+            .unwrap()
+            .contains("No quality checks configured"));
+    }
+
+    #[test]
+    fn test_gate_problems() {
+        let mut results = TestResults::empty();
+        results.passed = 2;
+        assert!(green_problem(&results).is_none());
+        assert!(red_problem(&results)
+            .unwrap()
+            .contains("passed unexpectedly"));
+
+        results.failed = 1;
+        assert!(red_problem(&results).is_none());
+        assert!(green_problem(&results)
+            .unwrap()
+            .contains("Tests are failing"));
+
+        results.execution_error = Some("No test suites found".to_string());
+        assert!(red_problem(&results).is_some());
+        assert!(green_problem(&results).is_some());
+
+        assert!(review_problem("- VERDICT: APPROVE\n").is_none());
+    }
+}
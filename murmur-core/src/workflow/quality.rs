@@ -0,0 +1,478 @@
+//! Quality gates: build, format and lint checks on the agents' changes
+//!
+//! The `[quality]` config section turns on a compile check, a formatter
+//! check and a linter, each picked for the toolchains found at the root of
+//! the repository, and adds custom commands. A `[quality]` section in the
+//! repository's `.murmur.toml` replaces it as a whole. The TDD and
+//! coordinator workflows run the checks after the implement agent changes
+//! code and before opening a pull request, and give failures back to the
+//! agent to fix.
+
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use super::test_config::PROJECT_CONFIG_FILE;
+use crate::Result;
+
+/// Lines of a failing check's output included in the feedback
+const FEEDBACK_LINES: usize = 50;
+
+/// Quality gate configuration (`[quality]`)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct QualityConfig {
+    /// Check that the code compiles
+    pub build: bool,
+
+    /// Check that the code is formatted
+    pub format: bool,
+
+    /// Run the linters detected for the repository
+    pub lint: bool,
+
+    /// Linters to run instead of the detected ones
+    pub linters: Vec<Linter>,
+
+    /// Custom check commands, run with `sh -c`
+    pub commands: Vec<String>,
+
+    /// Seconds a check may take before it is killed
+    pub timeout_secs: Option<u64>,
+}
+
+/// A linter murmur knows how to run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Linter {
+    /// Rust's `cargo clippy`
+    Clippy,
+    /// Python's `ruff check`
+    Ruff,
+    /// JavaScript and TypeScript's `eslint`
+    Eslint,
+    /// Go's `golangci-lint`
+    GolangciLint,
+}
+
+impl Linter {
+    /// The command that runs the linter, failing on any finding
+    pub fn command(&self) -> &'static str {
+        match self {
+            Linter::Clippy => "cargo clippy --workspace --all-targets -- -D warnings",
+            Linter::Ruff => "ruff check .",
+            Linter::Eslint => "npx eslint .",
+            Linter::GolangciLint => "golangci-lint run ./...",
+        }
+    }
+}
+
+/// A language toolchain whose checks murmur knows
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Toolchain {
+    Rust,
+    Go,
+    Python,
+    JavaScript,
+}
+
+impl Toolchain {
+    /// Toolchains with project files in `path`
+    fn detect(path: &Path) -> Vec<Self> {
+        let mut found = Vec::new();
+        if path.join("Cargo.toml").exists() {
+            found.push(Toolchain::Rust);
+        }
+        if path.join("go.mod").exists() {
+            found.push(Toolchain::Go);
+        }
+        if [
+            "pyproject.toml",
+            "setup.py",
+            "setup.cfg",
+            "requirements.txt",
+        ]
+        .iter()
+        .any(|file| path.join(file).exists())
+        {
+            found.push(Toolchain::Python);
+        }
+        if path.join("package.json").exists() {
+            found.push(Toolchain::JavaScript);
+        }
+        found
+    }
+
+    /// The compile check, if the toolchain has one
+    fn build_command(&self, path: &Path) -> Option<&'static str> {
+        match self {
+            Toolchain::Rust => Some("cargo check --workspace --all-targets"),
+            Toolchain::Go => Some("go build ./..."),
+            Toolchain::Python => None,
+            Toolchain::JavaScript => path
+                .join("tsconfig.json")
+                .exists()
+                .then_some("npx tsc --noEmit"),
+        }
+    }
+
+    /// The formatter check, if the project uses a formatter
+    fn format_command(&self, path: &Path) -> Option<&'static str> {
+        match self {
+            Toolchain::Rust => Some("cargo fmt --all -- --check"),
+            Toolchain::Go => Some("test -z \"$(gofmt -l .)\" || { gofmt -l .; exit 1; }"),
+            Toolchain::Python => Some("ruff format --check ."),
+            Toolchain::JavaScript => uses_prettier(path).then_some("npx prettier --check ."),
+        }
+    }
+
+    /// The linter used when none is configured
+    fn linter(&self) -> Linter {
+        match self {
+            Toolchain::Rust => Linter::Clippy,
+            Toolchain::Go => Linter::GolangciLint,
+            Toolchain::Python => Linter::Ruff,
+            Toolchain::JavaScript => Linter::Eslint,
+        }
+    }
+}
+
+/// Whether a JavaScript project has a Prettier config
+fn uses_prettier(path: &Path) -> bool {
+    let configured = std::fs::read_dir(path).is_ok_and(|entries| {
+        entries.flatten().any(|entry| {
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            name.starts_with(".prettierrc") || name.starts_with("prettier.config.")
+        })
+    });
+    configured
+        || std::fs::read_to_string(path.join("package.json"))
+            .is_ok_and(|content| content.contains("\"prettier\""))
+}
+
+/// What a quality check verifies
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckKind {
+    /// The code compiles
+    Build,
+    /// The code is formatted
+    Format,
+    /// A linter finds nothing
+    Lint,
+    /// A configured command succeeds
+    Custom,
+}
+
+/// One command that has to succeed for the quality gates to pass
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QualityCheck {
+    /// What the command verifies
+    pub kind: CheckKind,
+    /// Shell command line, run from the repository root
+    pub command: String,
+}
+
+impl std::fmt::Display for QualityCheck {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.kind {
+            CheckKind::Build => write!(f, "build `{}`", self.command),
+            CheckKind::Format => write!(f, "format `{}`", self.command),
+            CheckKind::Lint => write!(f, "lint `{}`", self.command),
+            CheckKind::Custom => write!(f, "`{}`", self.command),
+        }
+    }
+}
+
+/// A `.murmur.toml` file; only its `[quality]` section is read
+#[derive(Debug, Default, Deserialize)]
+struct ProjectFile {
+    quality: Option<QualityConfig>,
+}
+
+impl QualityConfig {
+    /// The configuration that applies in `workdir`
+    ///
+    /// A `[quality]` section in `workdir/.murmur.toml` replaces this one as
+    /// a whole. An unreadable file is ignored with a warning.
+    pub fn for_workdir(&self, workdir: &Path) -> QualityConfig {
+        let path = workdir.join(PROJECT_CONFIG_FILE);
+        let Ok(content) = std::fs::read_to_string(&path) else {
+            return self.clone();
+        };
+        match toml::from_str::<ProjectFile>(&content) {
+            Ok(ProjectFile {
+                quality: Some(quality),
+            }) => quality,
+            Ok(_) => self.clone(),
+            Err(e) => {
+                tracing::warn!(path = %path.display(), error = %e, "Ignoring invalid project config");
+                self.clone()
+            }
+        }
+    }
+
+    /// The checks to run in `workdir`, builds first and custom commands last
+    pub fn checks(&self, workdir: &Path) -> Vec<QualityCheck> {
+        let toolchains = Toolchain::detect(workdir);
+        let mut checks = Vec::new();
+        let mut add = |kind, command: &str| {
+            checks.push(QualityCheck {
+                kind,
+                command: command.to_string(),
+            })
+        };
+
+        if self.build {
+            for command in toolchains.iter().filter_map(|t| t.build_command(workdir)) {
+                add(CheckKind::Build, command);
+            }
+        }
+        if self.format {
+            for command in toolchains.iter().filter_map(|t| t.format_command(workdir)) {
+                add(CheckKind::Format, command);
+            }
+        }
+        if !self.linters.is_empty() {
+            for linter in &self.linters {
+                add(CheckKind::Lint, linter.command());
+            }
+        } else if self.lint {
+            for toolchain in &toolchains {
+                add(CheckKind::Lint, toolchain.linter().command());
+            }
+        }
+        for command in &self.commands {
+            add(CheckKind::Custom, command);
+        }
+        checks
+    }
+
+    /// The configured timeout, if any
+    pub fn timeout(&self) -> Option<Duration> {
+        self.timeout_secs.map(Duration::from_secs)
+    }
+}
+
+/// The quality checks for one working directory
+#[derive(Debug, Clone, Default)]
+pub struct QualityGates {
+    workdir: PathBuf,
+    checks: Vec<QualityCheck>,
+    timeout: Option<Duration>,
+}
+
+impl QualityGates {
+    /// The checks `config` asks for in `workdir`
+    ///
+    /// A `[quality]` section in the repository's `.murmur.toml` takes
+    /// precedence.
+    pub fn from_config(workdir: impl Into<PathBuf>, config: &QualityConfig) -> Self {
+        let workdir = workdir.into();
+        let config = config.for_workdir(&workdir);
+        Self {
+            checks: config.checks(&workdir),
+            timeout: config.timeout(),
+            workdir,
+        }
+    }
+
+    /// The checks that run
+    pub fn checks(&self) -> &[QualityCheck] {
+        &self.checks
+    }
+
+    /// Whether there is nothing to check
+    pub fn is_empty(&self) -> bool {
+        self.checks.is_empty()
+    }
+
+    /// Run every check, returning what failed
+    ///
+    /// All checks run even after one fails, so the agent can fix every
+    /// problem at once. `on_check` is called after each check with its
+    /// problem, if it failed.
+    pub async fn run(
+        &self,
+        on_check: &mut (dyn FnMut(&QualityCheck, Option<&str>) + Send),
+    ) -> Result<Option<String>> {
+        let mut problems = Vec::new();
+        for check in &self.checks {
+            let problem = command_problem(&check.command, &self.workdir, self.timeout).await?;
+            on_check(check, problem.as_deref());
+            if let Some(problem) = problem {
+                problems.push(problem);
+            }
+        }
+        Ok((!problems.is_empty()).then(|| problems.join("\n\n")))
+    }
+}
+
+/// Run a check command, returning the end of its output if it fails
+pub(crate) async fn command_problem(
+    command: &str,
+    workdir: &Path,
+    timeout: Option<Duration>,
+) -> Result<Option<String>> {
+    let output = tokio::process::Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .current_dir(workdir)
+        .kill_on_drop(true)
+        .output();
+    let output = match timeout {
+        Some(timeout) => match tokio::time::timeout(timeout, output).await {
+            Ok(output) => output?,
+            Err(_) => {
+                return Ok(Some(format!(
+                    "`{}` timed out after {}s",
+                    command,
+                    timeout.as_secs()
+                )))
+            }
+        },
+        None => output.await?,
+    };
+    if output.status.success() {
+        return Ok(None);
+    }
+
+    let mut text = String::from_utf8_lossy(&output.stdout).into_owned();
+    text.push_str(&String::from_utf8_lossy(&output.stderr));
+    let lines: Vec<&str> = text.lines().collect();
+    let tail = &lines[lines.len().saturating_sub(FEEDBACK_LINES)..];
+    let mut problem = format!("`{}` failed ({})", command, output.status);
+    if !tail.is_empty() {
+        problem.push_str(&format!(":\n\n```\n{}\n```", tail.join("\n")));
+    }
+    Ok(Some(problem))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_detected_checks() {
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join("Cargo.toml"), "[package]").unwrap();
+        fs::write(dir.path().join("package.json"), "{}").unwrap();
+
+        let config: QualityConfig =
+            toml::from_str("build = true\nformat = true\nlint = true").unwrap();
+        let checks: Vec<String> = config
+            .checks(dir.path())
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        // No tsconfig.json or Prettier config, so no tsc or prettier
+        assert_eq!(
+            checks,
+            [
+                "build `cargo check --workspace --all-targets`",
+                "format `cargo fmt --all -- --check`",
+                "lint `cargo clippy --workspace --all-targets -- -D warnings`",
+                "lint `npx eslint .`",
+            ]
+        );
+        assert!(QualityConfig::default().checks(dir.path()).is_empty());
+    }
+
+    #[test]
+    fn test_configured_linters_and_commands() {
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join("go.mod"), "module app").unwrap();
+        fs::write(dir.path().join("tsconfig.json"), "{}").unwrap();
+        fs::write(dir.path().join("package.json"), "{}").unwrap();
+        fs::write(dir.path().join(".prettierrc"), "{}").unwrap();
+
+        let config: QualityConfig = toml::from_str(
+            r#"
+            format = true
+            linters = ["golangci-lint"]
+            commands = ["./scripts/check-licenses"]
+            "#,
+        )
+        .unwrap();
+        let checks = config.checks(dir.path());
+        let kinds: Vec<CheckKind> = checks.iter().map(|c| c.kind).collect();
+        assert_eq!(
+            kinds,
+            [
+                CheckKind::Format,
+                CheckKind::Format,
+                CheckKind::Lint,
+                CheckKind::Custom
+            ]
+        );
+        assert_eq!(checks[1].command, "npx prettier --check .");
+        assert_eq!(checks[2].command, "golangci-lint run ./...");
+        assert!(toml::from_str::<QualityConfig>("linters = [\"pylint\"]").is_err());
+    }
+
+    #[test]
+    fn test_project_config_replaces_user_config() {
+        let dir = TempDir::new().unwrap();
+        let user = QualityConfig {
+            format: true,
+            ..Default::default()
+        };
+        assert_eq!(user.for_workdir(dir.path()), user);
+
+        fs::write(
+            dir.path().join(PROJECT_CONFIG_FILE),
+            "[test]\nframework = \"cargo\"\n\n[quality]\ncommands = [\"make lint\"]\n",
+        )
+        .unwrap();
+        let config = user.for_workdir(dir.path());
+        assert!(!config.format);
+        assert_eq!(config.commands, ["make lint"]);
+    }
+
+    #[tokio::test]
+    async fn test_run_reports_every_failure() {
+        let dir = TempDir::new().unwrap();
+        let config = QualityConfig {
+            commands: vec![
+                "echo 'src/lib.rs needs formatting'; exit 1".to_string(),
+                "true".to_string(),
+                "echo 'unused import' >&2; exit 2".to_string(),
+            ],
+            ..Default::default()
+        };
+        let gates = QualityGates::from_config(dir.path(), &config);
+
+        let mut seen = Vec::new();
+        let problems = gates
+            .run(&mut |check, problem| seen.push((check.command.clone(), problem.is_some())))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(seen.len(), 3);
+        assert!(!seen[1].1);
+        assert!(problems.contains("src/lib.rs needs formatting"));
+        assert!(problems.contains("unused import"));
+
+        let passing = QualityGates::from_config(
+            dir.path(),
+            &QualityConfig {
+                commands: vec!["true".to_string()],
+                ..Default::default()
+            },
+        );
+        assert_eq!(passing.run(&mut |_, _| {}).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_check_timeout() {
+        let dir = TempDir::new().unwrap();
+        let problem = command_problem("sleep 5", dir.path(), Some(Duration::from_millis(50)))
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(problem.contains("timed out"));
+    }
+}
@@ -3,10 +3,12 @@
 //! This module provides the ability to resume interrupted agent workflows by:
 //! - Detecting incomplete/interrupted runs
 //! - Reconstructing conversation history from the database
-//! - Spawning agents with conversation context to continue work
+//! - Resuming the backend's native session when it is still available
+//! - Otherwise spawning agents with conversation context to continue work
 
 use murmur_db::{repos::AgentRunRepository, repos::ConversationRepository, Database};
 use serde_json::Value;
+use std::collections::HashSet;
 use std::path::PathBuf;
 
 use crate::{Error, Result};
@@ -28,6 +30,8 @@ pub struct ResumableRun {
     pub exit_code: Option<i32>,
     /// Number of conversation messages logged
     pub message_count: i64,
+    /// Backend session ID, if the agent reported one
+    pub session_id: Option<String>,
 }
 
 impl ResumableRun {
@@ -40,6 +44,16 @@ impl ResumableRun {
     pub fn was_interrupted(&self) -> bool {
         self.exit_code.is_none()
     }
+
+    /// Describe why this run needs to be resumed
+    pub fn resume_reason(&self) -> String {
+        match self.exit_code {
+            Some(code) if code != 0 => {
+                format!("Previous session exited with error code {}", code)
+            }
+            _ => "Previous session was interrupted".to_string(),
+        }
+    }
 }
 
 /// Find incomplete or interrupted agent runs for a specific issue
@@ -48,6 +62,8 @@ impl ResumableRun {
 /// - Have no end_time (still running or interrupted)
 /// - Exited with a non-zero code
 ///
+/// Runs that a later run already resumed are left out.
+///
 /// Results are ordered by start_time descending (most recent first)
 pub fn find_incomplete_runs(db: &Database, issue_number: i64) -> Result<Vec<ResumableRun>> {
     let repo = AgentRunRepository::new(db);
@@ -58,13 +74,14 @@ pub fn find_incomplete_runs(db: &Database, issue_number: i64) -> Result<Vec<Resu
         .find_by_issue(issue_number)
         .map_err(|e| Error::Agent(format!("Failed to query agent runs: {}", e)))?;
 
+    let resumed: HashSet<i64> = runs.iter().filter_map(|run| run.resumed_from).collect();
     let mut incomplete = Vec::new();
 
     for run in runs {
         // Include runs that are incomplete (no end_time) or failed (non-zero exit)
         let is_incomplete = !run.is_completed() || !run.is_successful();
 
-        if is_incomplete {
+        if is_incomplete && !run.id.is_some_and(|id| resumed.contains(&id)) {
             let message_count = conv_repo
                 .count_by_agent_run(run.id.unwrap_or(0))
                 .map_err(|e| Error::Agent(format!("Failed to count messages: {}", e)))?;
@@ -77,6 +94,7 @@ pub fn find_incomplete_runs(db: &Database, issue_number: i64) -> Result<Vec<Resu
                 start_time: run.start_time,
                 exit_code: run.exit_code,
                 message_count,
+                session_id: run.session_id,
             });
         }
     }
@@ -186,6 +204,19 @@ pub fn build_resume_prompt(
     prompt
 }
 
+/// Build the prompt sent when natively resuming a backend session
+///
+/// The backend already has the full conversation, so this only explains why
+/// the session stopped and asks the agent to carry on.
+pub fn build_session_resume_prompt(reason: &str) -> String {
+    format!(
+        "The session was interrupted ({}). Continue the task from where you left off. \
+         Check the current state of the files first, since some of your last changes \
+         may not have been saved.\n",
+        reason
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -217,6 +248,56 @@ mod tests {
         assert_eq!(incomplete.len(), 1);
         assert_eq!(incomplete[0].run_id, run_id);
         assert!(incomplete[0].was_interrupted());
+        assert_eq!(
+            incomplete[0].resume_reason(),
+            "Previous session was interrupted"
+        );
+    }
+
+    #[test]
+    fn test_find_incomplete_runs_includes_session_id() {
+        let db = setup_test_db();
+        let agent_repo = AgentRunRepository::new(&db);
+
+        let run = AgentRun::new("implementer", "Fix bug", "/tmp/work", "{}")
+            .with_issue_number(42)
+            .with_session_id("session-abc");
+        agent_repo.insert(&run).unwrap();
+
+        let latest = find_latest_incomplete_run(&db, 42).unwrap().unwrap();
+        assert_eq!(latest.session_id, Some("session-abc".to_string()));
+    }
+
+    #[test]
+    fn test_find_incomplete_runs_skips_resumed() {
+        let db = setup_test_db();
+        let agent_repo = AgentRunRepository::new(&db);
+
+        let mut interrupted = AgentRun::new("implementer", "Fix bug", "/tmp/work", "{}");
+        interrupted.issue_number = Some(42);
+        let interrupted_id = agent_repo.insert(&interrupted).unwrap();
+
+        // A successful resume leaves nothing to resume
+        let mut resumed = AgentRun::new("implementer", "Continue", "/tmp/work", "{}")
+            .with_issue_number(42)
+            .with_resumed_from(interrupted_id);
+        resumed.complete(0);
+        agent_repo.insert(&resumed).unwrap();
+        assert!(find_incomplete_runs(&db, 42).unwrap().is_empty());
+
+        // A failed resume is resumed in turn, not the run it superseded
+        let mut failed =
+            AgentRun::new("implementer", "Continue", "/tmp/work", "{}").with_issue_number(43);
+        failed.complete(1);
+        let failed_id = agent_repo.insert(&failed).unwrap();
+        let mut retry = AgentRun::new("implementer", "Continue", "/tmp/work", "{}")
+            .with_issue_number(43)
+            .with_resumed_from(failed_id);
+        retry.complete(1);
+        let retry_id = agent_repo.insert(&retry).unwrap();
+        let incomplete = find_incomplete_runs(&db, 43).unwrap();
+        assert_eq!(incomplete.len(), 1);
+        assert_eq!(incomplete[0].run_id, retry_id);
     }
 
     #[test]
@@ -234,6 +315,10 @@ mod tests {
         assert_eq!(incomplete.len(), 1);
         assert_eq!(incomplete[0].run_id, run_id);
         assert!(incomplete[0].had_error());
+        assert_eq!(
+            incomplete[0].resume_reason(),
+            "Previous session exited with error code 1"
+        );
     }
 
     #[test]
@@ -338,6 +423,13 @@ mod tests {
         assert!(prompt.contains("Used 1 tools"));
     }
 
+    #[test]
+    fn test_build_session_resume_prompt() {
+        let prompt = build_session_resume_prompt("Previous session was interrupted");
+        assert!(prompt.contains("Previous session was interrupted"));
+        assert!(prompt.contains("Continue the task"));
+    }
+
     #[test]
     fn test_build_resume_prompt_empty_history() {
         let original = "Fix bug";
@@ -3,8 +3,10 @@
 //! This module provides review workflow that can be inserted at various points
 //! in the development process.
 
+use super::review_parser::ReviewOutputFormat;
 use crate::agent::{AgentFactory, ReviewAgent};
 use crate::config::AgentConfig;
+use crate::Result;
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
@@ -59,6 +61,16 @@ impl ReviewVerdict {
     pub fn is_blocking(&self) -> bool {
         matches!(self, ReviewVerdict::RequestChanges)
     }
+
+    /// Stable identifier used when persisting the verdict
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReviewVerdict::Pending => "pending",
+            ReviewVerdict::Approved => "approved",
+            ReviewVerdict::RequestChanges => "request_changes",
+            ReviewVerdict::Comment => "comment",
+        }
+    }
 }
 
 impl std::fmt::Display for ReviewVerdict {
@@ -214,14 +226,20 @@ impl ReviewState {
 pub struct ReviewWorkflow {
     state: ReviewState,
     factory: AgentFactory,
+    output_format: ReviewOutputFormat,
 }
 
 impl ReviewWorkflow {
     /// Create a new review workflow
-    pub fn new(trigger: ReviewTrigger, task: impl Into<String>, workdir: impl Into<PathBuf>) -> Self {
+    pub fn new(
+        trigger: ReviewTrigger,
+        task: impl Into<String>,
+        workdir: impl Into<PathBuf>,
+    ) -> Self {
         Self {
             state: ReviewState::new(trigger, task, workdir),
             factory: AgentFactory::new(),
+            output_format: ReviewOutputFormat::default(),
         }
     }
 
@@ -235,9 +253,21 @@ impl ReviewWorkflow {
         Self {
             state: ReviewState::new(trigger, task, workdir),
             factory: AgentFactory::with_config(config),
+            output_format: ReviewOutputFormat::default(),
         }
     }
 
+    /// Ask the review agent for a different output format
+    pub fn with_output_format(mut self, format: ReviewOutputFormat) -> Self {
+        self.output_format = format;
+        self
+    }
+
+    /// Get the output format the review agent is asked for
+    pub fn output_format(&self) -> ReviewOutputFormat {
+        self.output_format
+    }
+
     /// Get the state
     pub fn state(&self) -> &ReviewState {
         &self.state
@@ -257,14 +287,10 @@ impl ReviewWorkflow {
     pub fn review_prompt(&self) -> String {
         format!(
             "Review the following changes for the task:\n\n{}\n\n\
-             Diff:\n```\n{}\n```\n\n\
-             Provide your review in the following format:\n\
-             - VERDICT: APPROVE/REQUEST_CHANGES/COMMENT\n\
-             - BLOCKING: List any blocking issues\n\
-             - IMPORTANT: List important but non-blocking issues\n\
-             - SUGGESTIONS: List nice-to-have improvements\n\
-             - POSITIVE: List good patterns observed",
-            self.state.task, self.state.diff
+             Diff:\n```\n{}\n```\n\n{}",
+            self.state.task,
+            self.state.diff,
+            self.output_format.instructions()
         )
     }
 
@@ -279,6 +305,13 @@ impl ReviewWorkflow {
         self.state.record_iteration();
     }
 
+    /// Parse the review agent's output and record it
+    pub fn record_output(&mut self, output: &str) -> Result<&ReviewResult> {
+        let result = self.output_format.parse(output)?;
+        self.record_result(result);
+        Ok(&self.state.result)
+    }
+
     /// Check if approved
     pub fn is_approved(&self) -> bool {
         self.state.is_approved()
@@ -343,6 +376,24 @@ mod tests {
         assert!(!workflow.needs_changes());
     }
 
+    #[test]
+    fn test_record_output() {
+        let mut workflow = ReviewWorkflow::new(ReviewTrigger::OnDemand, "task", "/tmp");
+        let result = workflow
+            .record_output("REVIEW SUMMARY: REQUEST_CHANGES\nBLOCKING:\n- src/lib.rs:3: panics\n")
+            .unwrap();
+        assert_eq!(result.blocking[0].line, Some(3));
+        assert!(workflow.needs_changes());
+        assert_eq!(workflow.state().iterations, 1);
+
+        let mut workflow = ReviewWorkflow::new(ReviewTrigger::OnDemand, "task", "/tmp")
+            .with_output_format(ReviewOutputFormat::Json);
+        assert!(workflow.review_prompt().contains("single JSON object"));
+        workflow.record_output(r#"{"verdict": "approve"}"#).unwrap();
+        assert!(workflow.is_approved());
+        assert!(workflow.record_output("VERDICT: APPROVE").is_err());
+    }
+
     #[test]
     fn test_review_iterations() {
         let mut state = ReviewState::new(ReviewTrigger::OnDemand, "task", "/tmp");
@@ -0,0 +1,565 @@
+//! Automated review → fix → re-review loop
+//!
+//! [`ReviewLoop`] runs the review agent on a worktree's diff against its base
+//! branch, hands the blocking and important issues to an implement agent,
+//! and reviews again until the changes are approved or the iteration cap is
+//! hit. Each round is recorded in the database when one is attached.
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use murmur_db::models::ReviewRound;
+use murmur_db::repos::ReviewRoundRepository;
+use murmur_db::Database;
+
+use super::review::{ReviewIssue, ReviewResult, ReviewTrigger, ReviewWorkflow};
+use super::review_parser::ReviewOutputFormat;
+use super::runner::{AgentRunner, RunAccounting};
+use crate::agent::{AgentSpawner, AgentType, PromptBuilder, PromptTemplates, StreamHandler};
+use crate::config::AgentConfig;
+use crate::git::GitRepo;
+use crate::{Error, Result};
+
+/// Outcome of a review loop
+#[derive(Debug, Clone)]
+pub struct ReviewLoopOutcome {
+    /// Whether the final review approved the changes
+    pub approved: bool,
+    /// Parsed result of every review round, in order
+    pub rounds: Vec<ReviewResult>,
+    /// Number of fix runs performed
+    pub fixes: u32,
+}
+
+impl ReviewLoopOutcome {
+    /// The last review result, if any round ran
+    pub fn last(&self) -> Option<&ReviewResult> {
+        self.rounds.last()
+    }
+
+    /// Whether the final review left no blocking or important issues
+    pub fn is_clean(&self) -> bool {
+        self.last().is_some_and(|r| !has_actionable_issues(r))
+    }
+}
+
+/// Runs review and fix agents until the changes are approved
+pub struct ReviewLoop {
+    workflow: ReviewWorkflow,
+    config: AgentConfig,
+    review_spawner: AgentSpawner,
+    fix_spawner: AgentSpawner,
+    templates: PromptTemplates,
+    base: Option<String>,
+    issue_number: Option<i64>,
+    accounting: RunAccounting,
+    db: Option<Database>,
+}
+
+impl ReviewLoop {
+    /// Create a loop reviewing the changes in `workdir` for `task`
+    pub fn new(task: impl Into<String>, workdir: impl Into<PathBuf>, config: AgentConfig) -> Self {
+        let workdir = workdir.into();
+        Self {
+            templates: PromptTemplates::discover(&workdir),
+            workflow: ReviewWorkflow::with_config(
+                ReviewTrigger::BeforePR,
+                task,
+                workdir,
+                config.clone(),
+            ),
+            review_spawner: AgentSpawner::from_config(config.clone(), AgentType::Review),
+            fix_spawner: AgentSpawner::from_config(config.clone(), AgentType::Implement),
+            config,
+            base: None,
+            issue_number: None,
+            accounting: RunAccounting::default(),
+            db: None,
+        }
+    }
+
+    /// Diff against this ref instead of the repository's default branch
+    pub fn with_base(mut self, base: impl Into<String>) -> Self {
+        self.base = Some(base.into());
+        self
+    }
+
+    /// Stop after this many review rounds
+    pub fn with_max_iterations(mut self, max_iterations: u32) -> Self {
+        self.workflow.state_mut().max_iterations = max_iterations;
+        self
+    }
+
+    /// Ask the review agent for a different output format
+    pub fn with_output_format(mut self, format: ReviewOutputFormat) -> Self {
+        self.workflow = self.workflow.with_output_format(format);
+        self
+    }
+
+    /// Associate the rounds with a GitHub issue
+    pub fn with_issue_number(mut self, issue_number: i64) -> Self {
+        self.issue_number = Some(issue_number);
+        self
+    }
+
+    /// Enforce spending limits on the agent runs
+    pub fn with_accounting(mut self, accounting: RunAccounting) -> Self {
+        self.accounting = accounting;
+        self
+    }
+
+    /// Record agent runs and review rounds in a database
+    pub fn with_database(mut self, db: Database) -> Self {
+        self.db = Some(db);
+        self
+    }
+
+    /// Use a custom spawner for the review agent
+    pub fn with_review_spawner(mut self, spawner: AgentSpawner) -> Self {
+        self.review_spawner = spawner;
+        self
+    }
+
+    /// Use a custom spawner for the fix agent
+    pub fn with_fix_spawner(mut self, spawner: AgentSpawner) -> Self {
+        self.fix_spawner = spawner;
+        self
+    }
+
+    /// Take back the attached database
+    pub(crate) fn take_database(&mut self) -> Option<Database> {
+        self.db.take()
+    }
+
+    /// Get the underlying review workflow
+    pub fn workflow(&self) -> &ReviewWorkflow {
+        &self.workflow
+    }
+
+    /// Run the loop, streaming agent output to `handler`
+    pub async fn run<H: StreamHandler>(&mut self, handler: &mut H) -> Result<ReviewLoopOutcome> {
+        let mut outcome = ReviewLoopOutcome {
+            approved: false,
+            rounds: Vec::new(),
+            fixes: 0,
+        };
+
+        loop {
+            let diff = self.diff()?;
+            if diff.trim().is_empty() {
+                if outcome.rounds.is_empty() {
+                    return Err(Error::Agent("No changes to review".to_string()));
+                }
+                break;
+            }
+            self.workflow.set_diff(diff);
+
+            let prompt = self.review_prompt();
+            let (output, review_run_id) =
+                self.run_agent(AgentType::Review, &prompt, handler).await?;
+            let result = self.workflow.record_output(&output)?.clone();
+            let iteration = self.workflow.state().iterations;
+            tracing::info!(
+                iteration,
+                verdict = %result.verdict,
+                blocking = result.blocking.len(),
+                important = result.important.len(),
+                "Review round complete"
+            );
+            let round_id = self.record_round(iteration, &result, review_run_id);
+            outcome.rounds.push(result.clone());
+
+            if self.workflow.is_approved() || !has_actionable_issues(&result) {
+                break;
+            }
+            if self.workflow.state().exceeded_max_iterations() {
+                tracing::warn!(iteration, "Review loop hit the iteration cap");
+                break;
+            }
+
+            let prompt = self.fix_prompt(&result);
+            let (_, fix_run_id) = self
+                .run_agent(AgentType::Implement, &prompt, handler)
+                .await?;
+            outcome.fixes += 1;
+            if let (Some(db), Some(round_id), Some(fix_run_id)) = (&self.db, round_id, fix_run_id) {
+                if let Err(e) = ReviewRoundRepository::new(db).set_fix_run(round_id, fix_run_id) {
+                    tracing::warn!(error = %e, "Failed to link fix run to review round");
+                }
+            }
+        }
+
+        outcome.approved = self.workflow.is_approved();
+        Ok(outcome)
+    }
+
+    /// The prompt for the review agent
+    fn review_prompt(&self) -> String {
+        let state = self.workflow.state();
+        let mut prompt = PromptBuilder::new(AgentType::Review)
+            .templates(self.templates.clone())
+            .task(&state.task)
+            .diff(format!("```diff\n{}\n```", state.diff.trim_end()))
+            .build();
+        if self.workflow.output_format() == ReviewOutputFormat::Json {
+            prompt.push_str("\n\n## Output Format\n\n");
+            prompt.push_str(self.workflow.output_format().instructions());
+        }
+        prompt
+    }
+
+    /// The prompt asking the implement agent to address a review
+    fn fix_prompt(&self, result: &ReviewResult) -> String {
+        let mut task = format!(
+            "{}\n\nA code review of your changes found issues. Address every \
+             blocking issue and as many important issues as you can without \
+             making unrelated changes.\n",
+            self.workflow.state().task
+        );
+        push_issues(&mut task, "Blocking Issues", &result.blocking);
+        push_issues(&mut task, "Important Issues", &result.important);
+
+        PromptBuilder::new(AgentType::Implement)
+            .templates(self.templates.clone())
+            .task(task)
+            .build()
+    }
+
+    /// Changes in the working directory relative to the base branch
+    ///
+    /// Includes uncommitted edits and untracked files, since agents don't
+    /// always commit their work.
+    fn diff(&self) -> Result<String> {
+        let workdir = &self.workflow.state().workdir;
+        let base = match &self.base {
+            Some(base) => base.clone(),
+            None => default_base(workdir)?,
+        };
+        let merge_base = git(workdir, &["merge-base", &base, "HEAD"])?;
+
+        let mut diff = git(workdir, &["diff", merge_base.trim()])?;
+        let untracked = git(workdir, &["ls-files", "--others", "--exclude-standard"])?;
+        for file in untracked.lines().filter(|f| !f.is_empty()) {
+            // `--no-index` exits 1 when the files differ, which they always do here
+            let output = Command::new("git")
+                .args(["diff", "--no-index", "--", "/dev/null", file])
+                .current_dir(workdir)
+                .output()?;
+            diff.push_str(&String::from_utf8_lossy(&output.stdout));
+        }
+        Ok(diff)
+    }
+
+    /// Run one agent to completion, returning its text output and run ID
+    async fn run_agent<H: StreamHandler>(
+        &self,
+        agent_type: AgentType,
+        prompt: &str,
+        handler: &mut H,
+    ) -> Result<(String, Option<i64>)> {
+        let spawner = match agent_type {
+            AgentType::Review => &self.review_spawner,
+            _ => &self.fix_spawner,
+        };
+        let runner = AgentRunner {
+            config: &self.config,
+            accounting: &self.accounting,
+            db: self.db.as_ref(),
+            issue_number: self.issue_number,
+        };
+        runner
+            .run(
+                spawner,
+                agent_type,
+                prompt,
+                &self.workflow.state().workdir,
+                handler,
+            )
+            .await
+    }
+
+    /// Persist a review round, returning its ID
+    fn record_round(
+        &self,
+        iteration: u32,
+        result: &ReviewResult,
+        review_run_id: Option<i64>,
+    ) -> Option<i64> {
+        let db = self.db.as_ref()?;
+        let result_json = serde_json::to_string(result).unwrap_or_else(|_| "{}".to_string());
+        let mut round = ReviewRound::new(
+            self.workflow.state().workdir.to_string_lossy(),
+            iteration as i64,
+            result.verdict.as_str(),
+            result_json,
+        )
+        .with_counts(result.blocking.len() as i64, result.important.len() as i64);
+        if let Some(issue_number) = self.issue_number {
+            round = round.with_issue_number(issue_number);
+        }
+        if let Some(run_id) = review_run_id {
+            round = round.with_review_run_id(run_id);
+        }
+
+        ReviewRoundRepository::new(db)
+            .insert(&round)
+            .map_err(|e| tracing::warn!(error = %e, "Failed to record review round"))
+            .ok()
+    }
+}
+
+/// Whether a review has issues worth sending back to the implement agent
+fn has_actionable_issues(result: &ReviewResult) -> bool {
+    !result.blocking.is_empty() || !result.important.is_empty()
+}
+
+/// Append a `##` list of review issues to a prompt
+fn push_issues(prompt: &mut String, title: &str, issues: &[ReviewIssue]) {
+    if issues.is_empty() {
+        return;
+    }
+    prompt.push_str(&format!("\n## {}\n\n", title));
+    for issue in issues {
+        prompt.push_str(&format!("- {}\n", issue));
+    }
+}
+
+/// The default branch to diff against, preferring the remote-tracking ref
+fn default_base(workdir: &Path) -> Result<String> {
+    let branch = GitRepo::open(workdir)?.default_branch()?;
+    let remote = format!("origin/{}", branch);
+    if git(workdir, &["rev-parse", "--verify", "--quiet", &remote]).is_ok() {
+        Ok(remote)
+    } else {
+        Ok(branch)
+    }
+}
+
+/// Run a git command in `workdir`, returning its stdout
+pub(super) fn git(workdir: &Path, args: &[&str]) -> Result<String> {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(workdir)
+        .output()?;
+    if !output.status.success() {
+        return Err(Error::Other(format!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::fixtures::{mock_config, spawner};
+    use crate::agent::{CostInfo, MockScript, ModelPrice, PricingTable, StreamMessage};
+    use crate::config::BudgetConfig;
+    use murmur_db::repos::AgentRunRepository;
+
+    struct Quiet;
+
+    impl StreamHandler for Quiet {
+        fn on_assistant_text(&mut self, _text: &str) {}
+    }
+
+    fn git_ok(dir: &Path, args: &[&str]) {
+        let status = Command::new("git")
+            .args(["-c", "user.name=Test", "-c", "user.email=test@example.com"])
+            .args(args)
+            .current_dir(dir)
+            .output()
+            .unwrap()
+            .status;
+        assert!(status.success(), "git {:?} failed", args);
+    }
+
+    /// A repository with one commit on `main` and an uncommitted change
+    fn repo_with_change() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        git_ok(dir.path(), &["init", "-q", "-b", "main"]);
+        std::fs::write(dir.path().join("lib.rs"), "fn a() {}\n").unwrap();
+        git_ok(dir.path(), &["add", "."]);
+        git_ok(dir.path(), &["commit", "-q", "-m", "init"]);
+        std::fs::write(dir.path().join("lib.rs"), "fn a() { todo!() }\n").unwrap();
+        dir
+    }
+
+    #[tokio::test]
+    async fn test_approved_on_first_review() {
+        let repo = repo_with_change();
+        let scripts = tempfile::tempdir().unwrap();
+        let db = Database::in_memory().unwrap();
+
+        let mut review_loop = ReviewLoop::new("Implement a", repo.path(), mock_config())
+            .with_base("main")
+            .with_database(db)
+            .with_review_spawner(spawner(
+                scripts.path(),
+                "review",
+                MockScript::new().assistant_text("REVIEW SUMMARY: APPROVE\n\nPOSITIVE:\n- Tidy"),
+            ));
+
+        let outcome = review_loop.run(&mut Quiet).await.unwrap();
+        assert!(outcome.approved);
+        assert_eq!(outcome.rounds.len(), 1);
+        assert_eq!(outcome.fixes, 0);
+
+        let db = review_loop.db.as_ref().unwrap();
+        let rounds = ReviewRoundRepository::new(db)
+            .find_by_workdir(&repo.path().to_string_lossy())
+            .unwrap();
+        assert_eq!(rounds.len(), 1);
+        assert_eq!(rounds[0].verdict, "approved");
+        assert!(rounds[0].review_run_id.is_some());
+        assert_eq!(rounds[0].fix_run_id, None);
+    }
+
+    #[tokio::test]
+    async fn test_fixes_until_iteration_cap() {
+        let repo = repo_with_change();
+        let scripts = tempfile::tempdir().unwrap();
+        let review = MockScript::new().assistant_text(
+            "REVIEW SUMMARY: REQUEST_CHANGES\n\nBLOCKING:\n- lib.rs:1 - todo!() panics",
+        );
+        let fix = MockScript::new()
+            .write_file("lib.rs", "fn a() { todo!() } // still todo\n")
+            .assistant_text("Tried");
+
+        let mut review_loop = ReviewLoop::new("Implement a", repo.path(), mock_config())
+            .with_base("main")
+            .with_max_iterations(2)
+            .with_issue_number(7)
+            .with_database(Database::in_memory().unwrap())
+            .with_review_spawner(spawner(scripts.path(), "review", review))
+            .with_fix_spawner(spawner(scripts.path(), "fix", fix));
+
+        let outcome = review_loop.run(&mut Quiet).await.unwrap();
+        assert!(!outcome.approved);
+        assert!(!outcome.is_clean());
+        assert_eq!(outcome.rounds.len(), 2);
+        assert_eq!(outcome.fixes, 1);
+
+        let db = review_loop.db.as_ref().unwrap();
+        let rounds = ReviewRoundRepository::new(db).find_by_issue(7).unwrap();
+        assert_eq!(rounds.len(), 2);
+        assert_eq!(rounds[0].blocking_count, 1);
+        assert!(rounds[0].fix_run_id.is_some());
+        assert_eq!(rounds[1].iteration, 2);
+        assert_eq!(rounds[1].fix_run_id, None);
+
+        let runs = AgentRunRepository::new(db).find_by_issue(7).unwrap();
+        assert_eq!(runs.len(), 3);
+    }
+
+    /// The final message of a run that used `tokens` input tokens
+    fn usage(tokens: u64) -> StreamMessage {
+        StreamMessage::Result {
+            cost: Some(CostInfo {
+                input_tokens: tokens,
+                ..Default::default()
+            }),
+            duration_ms: None,
+            duration_api_ms: None,
+            usage: None,
+            total_cost_usd: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_issue_budget_stops_the_loop() {
+        let repo = repo_with_change();
+        let scripts = tempfile::tempdir().unwrap();
+        let review = MockScript::new()
+            .assistant_text("REVIEW SUMMARY: REQUEST_CHANGES\n\nBLOCKING:\n- lib.rs:1 - Panics")
+            .message(usage(600));
+        let fix = MockScript::new()
+            .write_file("lib.rs", "fn a() {}\n// fixed\n")
+            .message(usage(600));
+        let accounting = RunAccounting {
+            budget: BudgetConfig {
+                max_tokens_per_issue: Some(1000),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let mut review_loop = ReviewLoop::new("Implement a", repo.path(), mock_config())
+            .with_base("main")
+            .with_issue_number(7)
+            .with_accounting(accounting)
+            .with_database(Database::in_memory().unwrap())
+            .with_review_spawner(spawner(scripts.path(), "review", review))
+            .with_fix_spawner(spawner(scripts.path(), "fix", fix));
+
+        let err = review_loop.run(&mut Quiet).await.unwrap_err();
+        assert!(err.to_string().contains("issue budget exceeded"), "{}", err);
+
+        let db = review_loop.db.as_ref().unwrap();
+        let runs = AgentRunRepository::new(db).find_by_issue(7).unwrap();
+        assert_eq!(runs.len(), 2);
+        assert_eq!(
+            runs.iter().filter(|run| run.is_budget_exceeded()).count(),
+            1
+        );
+
+        // Nothing more runs once the issue's budget is spent
+        let err = review_loop.run(&mut Quiet).await.unwrap_err();
+        assert!(
+            err.to_string().contains("Issue #7 budget exceeded"),
+            "{}",
+            err
+        );
+    }
+
+    #[tokio::test]
+    async fn test_runs_are_priced_and_attributed() {
+        let repo = repo_with_change();
+        let scripts = tempfile::tempdir().unwrap();
+        let review = MockScript::new()
+            .assistant_text("REVIEW SUMMARY: APPROVE\n\nPOSITIVE:\n- Tidy")
+            .message(usage(1_000_000));
+        let accounting = RunAccounting {
+            pricing: PricingTable::new().with_price("in-house", ModelPrice::new(2.0, 8.0)),
+            ..Default::default()
+        }
+        .with_repository("octo/widgets");
+        let config = AgentConfig {
+            model: Some("in-house".to_string()),
+            ..mock_config()
+        };
+
+        let mut review_loop = ReviewLoop::new("Implement a", repo.path(), config)
+            .with_base("main")
+            .with_issue_number(7)
+            .with_accounting(accounting)
+            .with_database(Database::in_memory().unwrap())
+            .with_review_spawner(spawner(scripts.path(), "review", review));
+
+        assert!(review_loop.run(&mut Quiet).await.unwrap().approved);
+
+        let db = review_loop.db.as_ref().unwrap();
+        let runs = AgentRunRepository::new(db).find_by_issue(7).unwrap();
+        assert_eq!(runs.len(), 1);
+        assert!(
+            (runs[0].cost_usd - 2.0).abs() < 1e-9,
+            "{}",
+            runs[0].cost_usd
+        );
+        assert_eq!(runs[0].repository.as_deref(), Some("octo/widgets"));
+    }
+
+    #[test]
+    fn test_fix_prompt_lists_issues() {
+        let review_loop = ReviewLoop::new("Implement a", "/tmp", AgentConfig::default());
+        let result = ReviewResult {
+            blocking: vec![ReviewIssue::new("Panics").at_file("lib.rs").at_line(1)],
+            ..Default::default()
+        };
+        let prompt = review_loop.fix_prompt(&result);
+        assert!(prompt.contains("## Blocking Issues\n\n- lib.rs:1: Panics"));
+        assert!(!prompt.contains("## Important Issues"));
+    }
+}
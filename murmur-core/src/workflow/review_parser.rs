@@ -0,0 +1,667 @@
+//! Parsing review agent output into a [`ReviewResult`]
+//!
+//! Review agents answer either in the Markdown format from the review prompt
+//! (`REVIEW SUMMARY:` followed by `BLOCKING:`/`IMPORTANT:`/`SUGGESTIONS:`/
+//! `POSITIVE:` sections) or, in JSON mode, with a single JSON object. The
+//! Markdown parser tolerates headings, bold markers, numbered lists and
+//! wrapped lines, and pulls `file:line` references out of each finding.
+
+use serde::{Deserialize, Serialize};
+
+use super::review::{ReviewIssue, ReviewResult, ReviewVerdict};
+use crate::{Error, Result};
+
+/// How the review agent is asked to format its answer
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReviewOutputFormat {
+    /// `REVIEW SUMMARY:` block with severity sections
+    #[default]
+    Markdown,
+    /// A single JSON object
+    Json,
+}
+
+impl ReviewOutputFormat {
+    /// Prompt instructions describing the expected output
+    pub fn instructions(&self) -> &'static str {
+        match self {
+            ReviewOutputFormat::Markdown => {
+                "Provide your review in the following format:\n\
+                 - VERDICT: APPROVE/REQUEST_CHANGES/COMMENT\n\
+                 - BLOCKING: List any blocking issues\n\
+                 - IMPORTANT: List important but non-blocking issues\n\
+                 - SUGGESTIONS: List nice-to-have improvements\n\
+                 - POSITIVE: List good patterns observed\n\
+                 Reference code as path/to/file.rs:LINE at the start of each issue."
+            }
+            ReviewOutputFormat::Json => {
+                "Respond with a single JSON object and nothing else:\n\
+                 {\"verdict\": \"approve\" | \"request_changes\" | \"comment\",\n \
+                 \"blocking\": [{\"file\": \"src/lib.rs\", \"line\": 42, \
+                 \"description\": \"...\", \"suggestion\": \"...\"}],\n \
+                 \"important\": [...], \"suggestions\": [...], \"positives\": [\"...\"]}\n\
+                 `file`, `line` and `suggestion` are optional."
+            }
+        }
+    }
+
+    /// Parse agent output requested in this format
+    ///
+    /// Markdown mode also accepts JSON, since agents sometimes answer in it
+    /// unprompted.
+    pub fn parse(&self, output: &str) -> Result<ReviewResult> {
+        match self {
+            ReviewOutputFormat::Markdown => ReviewResult::parse(output),
+            ReviewOutputFormat::Json => ReviewResult::from_json(output),
+        }
+    }
+}
+
+impl ReviewResult {
+    /// Parse review output, accepting either JSON or the Markdown format
+    pub fn parse(output: &str) -> Result<Self> {
+        if extract_json(output).is_some() {
+            if let Ok(result) = Self::from_json(output) {
+                return Ok(result);
+            }
+        }
+        Self::from_markdown(output)
+    }
+
+    /// Parse a JSON review, optionally wrapped in a code fence or prose
+    pub fn from_json(output: &str) -> Result<Self> {
+        let json = extract_json(output)
+            .ok_or_else(|| Error::Agent("Review output contains no JSON object".to_string()))?;
+        let review: JsonReview = serde_json::from_str(json)?;
+
+        let verdict = match review.verdict.as_deref() {
+            Some(verdict) => parse_verdict(verdict)
+                .ok_or_else(|| Error::Agent(format!("Unknown review verdict: {}", verdict)))?,
+            None => ReviewVerdict::Pending,
+        };
+        let mut result = ReviewResult {
+            verdict,
+            blocking: review.blocking.into_iter().map(Into::into).collect(),
+            important: review.important.into_iter().map(Into::into).collect(),
+            suggestions: review.suggestions.into_iter().map(Into::into).collect(),
+            positives: review.positives,
+        };
+        result.infer_verdict();
+        Ok(result)
+    }
+
+    /// Parse the Markdown review format
+    ///
+    /// If the output contains several reviews (for example the agent echoed
+    /// the format before answering), the last one wins.
+    pub fn from_markdown(output: &str) -> Result<Self> {
+        let mut result = ReviewResult::default();
+        let mut section: Option<Section> = None;
+        let mut current: Option<String> = None;
+        let mut found = false;
+
+        for line in output.lines() {
+            let trimmed = line.trim();
+            if trimmed.starts_with("```") {
+                continue;
+            }
+
+            if let Some(verdict) = verdict_line(trimmed) {
+                flush(&mut result, section, &mut current);
+                result = ReviewResult {
+                    verdict: verdict.unwrap_or_default(),
+                    ..Default::default()
+                };
+                section = None;
+                found = true;
+                continue;
+            }
+
+            if let Some((heading, inline)) = section_heading(trimmed) {
+                flush(&mut result, section, &mut current);
+                section = Some(heading);
+                found = true;
+                if let Some(text) = inline.filter(|t| !is_placeholder(t)) {
+                    current = Some(text.to_string());
+                }
+                continue;
+            }
+
+            let Some(active) = section else {
+                continue;
+            };
+
+            if trimmed.is_empty() {
+                flush(&mut result, Some(active), &mut current);
+            } else if trimmed.starts_with('#') {
+                // An unrelated heading ends the section
+                flush(&mut result, Some(active), &mut current);
+                section = None;
+            } else if let Some(item) = strip_bullet(trimmed) {
+                flush(&mut result, Some(active), &mut current);
+                if !is_placeholder(item) {
+                    current = Some(item.to_string());
+                }
+            } else if let Some(text) = current.as_mut() {
+                text.push(' ');
+                text.push_str(trimmed);
+            } else if !is_placeholder(trimmed) {
+                current = Some(trimmed.to_string());
+            }
+        }
+        flush(&mut result, section, &mut current);
+
+        if !found {
+            return Err(Error::Agent(
+                "Review output contains no verdict or findings".to_string(),
+            ));
+        }
+        result.infer_verdict();
+        Ok(result)
+    }
+
+    /// Derive a verdict from the findings when the review didn't give one
+    fn infer_verdict(&mut self) {
+        if self.verdict != ReviewVerdict::Pending {
+            return;
+        }
+        self.verdict = if !self.blocking.is_empty() {
+            ReviewVerdict::RequestChanges
+        } else if self.important.is_empty() && self.suggestions.is_empty() {
+            ReviewVerdict::Approved
+        } else {
+            ReviewVerdict::Comment
+        };
+    }
+}
+
+/// A Markdown review section
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Section {
+    Blocking,
+    Important,
+    Suggestions,
+    Positives,
+}
+
+/// Lenient shape of a JSON review
+#[derive(Debug, Deserialize)]
+struct JsonReview {
+    #[serde(default, alias = "review_summary", alias = "summary")]
+    verdict: Option<String>,
+    #[serde(default)]
+    blocking: Vec<JsonIssue>,
+    #[serde(default)]
+    important: Vec<JsonIssue>,
+    #[serde(default, alias = "suggestion")]
+    suggestions: Vec<JsonIssue>,
+    #[serde(default, alias = "positive")]
+    positives: Vec<String>,
+}
+
+/// A JSON finding, either a plain string or an object
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum JsonIssue {
+    Text(String),
+    Detailed {
+        #[serde(default, alias = "path")]
+        file: Option<String>,
+        #[serde(default)]
+        line: Option<u32>,
+        #[serde(alias = "message", alias = "issue")]
+        description: String,
+        #[serde(default, alias = "fix")]
+        suggestion: Option<String>,
+    },
+}
+
+impl From<JsonIssue> for ReviewIssue {
+    fn from(issue: JsonIssue) -> Self {
+        match issue {
+            JsonIssue::Text(text) => parse_issue(&text),
+            JsonIssue::Detailed {
+                file,
+                line,
+                description,
+                suggestion,
+            } => ReviewIssue {
+                file,
+                line,
+                description,
+                suggestion,
+            },
+        }
+    }
+}
+
+/// Add the pending item to the active section
+fn flush(result: &mut ReviewResult, section: Option<Section>, current: &mut Option<String>) {
+    let (Some(section), Some(text)) = (section, current.take()) else {
+        return;
+    };
+    match section {
+        Section::Blocking => result.blocking.push(parse_issue(&text)),
+        Section::Important => result.important.push(parse_issue(&text)),
+        Section::Suggestions => result.suggestions.push(parse_issue(&text)),
+        Section::Positives => result.positives.push(text),
+    }
+}
+
+/// Strip Markdown emphasis and list markers from the start of a line
+fn strip_decoration(line: &str) -> &str {
+    line.trim_start_matches(|c: char| matches!(c, '#' | '*' | '_' | '-' | '>') || c.is_whitespace())
+}
+
+/// Recognize a verdict line, returning `Some(None)` if the value is unclear
+fn verdict_line(line: &str) -> Option<Option<ReviewVerdict>> {
+    let text = strip_decoration(line);
+    let upper = text.to_uppercase();
+    let rest = ["REVIEW SUMMARY", "REVIEW VERDICT", "VERDICT", "DECISION"]
+        .iter()
+        .find_map(|label| upper.strip_prefix(label))?;
+    let value = rest.trim_start_matches(['*', '_']).strip_prefix(':')?;
+    Some(parse_verdict(value))
+}
+
+/// Parse a verdict value such as `APPROVE`, `**Request changes**` or `comment`
+fn parse_verdict(value: &str) -> Option<ReviewVerdict> {
+    let normalized: String = value
+        .to_uppercase()
+        .chars()
+        .map(|c| if c == '-' || c == ' ' { '_' } else { c })
+        .filter(|c| c.is_ascii_alphanumeric() || *c == '_' || *c == '/')
+        .collect();
+    let normalized = normalized.trim_matches('_');
+
+    // The unfilled template ("APPROVE/REQUEST_CHANGES/COMMENT") is not a verdict
+    if normalized.contains('/') {
+        return None;
+    }
+    if normalized.contains("REQUEST_CHANGES")
+        || normalized.contains("CHANGES_REQUESTED")
+        || normalized.contains("REQUESTCHANGES")
+    {
+        Some(ReviewVerdict::RequestChanges)
+    } else if normalized.starts_with("APPROVE") || normalized.starts_with("LGTM") {
+        Some(ReviewVerdict::Approved)
+    } else if normalized.starts_with("COMMENT") {
+        Some(ReviewVerdict::Comment)
+    } else {
+        None
+    }
+}
+
+/// Recognize a section heading, returning any text on the same line
+fn section_heading(line: &str) -> Option<(Section, Option<&str>)> {
+    let is_bullet = strip_bullet(line).is_some();
+    let text = strip_decoration(line);
+
+    let (word, rest) = match text.find(|c: char| !c.is_ascii_alphabetic() && c != ' ') {
+        Some(end) => text.split_at(end),
+        None => (text, ""),
+    };
+    let word = word.trim();
+    // A bullet like "- Important: ..." is a finding; only "- IMPORTANT:" is a heading
+    if is_bullet && word != word.to_uppercase() {
+        return None;
+    }
+
+    let section = match word.to_uppercase().as_str() {
+        "BLOCKING" | "BLOCKING ISSUES" | "BLOCKERS" => Section::Blocking,
+        "IMPORTANT" | "IMPORTANT ISSUES" => Section::Important,
+        "SUGGESTION" | "SUGGESTIONS" | "NITS" => Section::Suggestions,
+        "POSITIVE" | "POSITIVES" | "POSITIVE FEEDBACK" | "STRENGTHS" => Section::Positives,
+        _ => return None,
+    };
+
+    let rest = rest.trim_start_matches(['*', '_', ')']).trim_start();
+    let rest = rest.strip_prefix(':').or_else(|| {
+        // Also accept "(3)" counts and bare headings
+        let rest = rest.trim_start_matches(|c: char| c == '(' || c.is_ascii_digit());
+        rest.trim_start_matches([')', '*', '_'])
+            .trim()
+            .is_empty()
+            .then_some("")
+    })?;
+    let inline = rest.trim_start_matches(['*', '_']).trim();
+    Some((section, (!inline.is_empty()).then_some(inline)))
+}
+
+/// Strip a `-`, `*`, `+` or `1.` list marker
+fn strip_bullet(line: &str) -> Option<&str> {
+    if let Some(rest) = line
+        .strip_prefix("- ")
+        .or_else(|| line.strip_prefix("* "))
+        .or_else(|| line.strip_prefix("+ "))
+    {
+        return Some(rest.trim());
+    }
+    let digits = line.len() - line.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+    if digits == 0 {
+        return None;
+    }
+    line[digits..]
+        .strip_prefix(". ")
+        .or_else(|| line[digits..].strip_prefix(") "))
+        .map(str::trim)
+}
+
+/// Whether a finding is just "None" or similar filler
+fn is_placeholder(text: &str) -> bool {
+    let text = text
+        .trim_matches(|c: char| !c.is_ascii_alphanumeric())
+        .to_lowercase();
+    matches!(
+        text.as_str(),
+        "" | "none" | "n/a" | "na" | "nothing" | "none found" | "no issues" | "no blocking issues"
+    )
+}
+
+/// Split a finding into location, description and suggested fix
+fn parse_issue(text: &str) -> ReviewIssue {
+    let (description, suggestion) = split_suggestion(text);
+
+    let tokens: Vec<&str> = description.split_whitespace().collect();
+    let mut issue = ReviewIssue::new(description.trim());
+    if let Some(suggestion) = suggestion {
+        issue = issue.with_suggestion(suggestion);
+    }
+
+    let Some((index, file, mut line)) = tokens
+        .iter()
+        .enumerate()
+        .find_map(|(i, token)| parse_location(token).map(|(file, line)| (i, file, line)))
+    else {
+        return issue;
+    };
+
+    // "src/lib.rs line 42" / "src/lib.rs (line 42)"
+    let mut end = index + 1;
+    if line.is_none() && tokens.len() > index + 2 {
+        let word = tokens[index + 1].trim_matches(|c: char| !c.is_ascii_alphabetic());
+        if word.eq_ignore_ascii_case("line") || word.eq_ignore_ascii_case("lines") {
+            line = leading_number(tokens[index + 2].trim_start_matches(['(', 'L']));
+            if line.is_some() {
+                end = index + 3;
+            }
+        }
+    }
+
+    issue.file = Some(file);
+    issue.line = line;
+
+    // A leading location is a label, not part of the description
+    if index == 0 {
+        let rest = tokens[end..].join(" ");
+        let rest = rest.trim_start_matches(|c: char| {
+            matches!(c, ':' | '-' | '–' | '—' | ')' | '*' | '`') || c.is_whitespace()
+        });
+        if !rest.is_empty() {
+            issue.description = rest.to_string();
+        }
+    }
+    issue
+}
+
+/// Separate a trailing "Suggestion: ..." or "Fix: ..." from a finding
+fn split_suggestion(text: &str) -> (&str, Option<&str>) {
+    let lower = text.to_lowercase();
+    for marker in ["suggested fix:", "suggestion:", "fix:"] {
+        if let Some(pos) = lower.find(marker) {
+            // Only split at a word boundary
+            if pos > 0 && lower.as_bytes()[pos - 1].is_ascii_alphanumeric() {
+                continue;
+            }
+            let suggestion = text[pos + marker.len()..].trim();
+            let description = text[..pos].trim_end_matches(|c: char| {
+                matches!(c, '.' | ';' | ',' | '-' | '(' | '*' | '_') || c.is_whitespace()
+            });
+            if !suggestion.is_empty() && !description.is_empty() {
+                return (description, Some(suggestion.trim_end_matches(')')));
+            }
+        }
+    }
+    (text, None)
+}
+
+/// Parse a `path:line`, `path:line-line`, `path#L42` or backticked path token
+fn parse_location(token: &str) -> Option<(String, Option<u32>)> {
+    let backticked = token.contains('`');
+    let token = token
+        .trim_matches(|c: char| {
+            matches!(
+                c,
+                '`' | '*' | '_' | '(' | ')' | '[' | ']' | ',' | ';' | '"' | '\''
+            )
+        })
+        .trim_end_matches([':', '.']);
+    if token.contains("://") {
+        return None;
+    }
+
+    let (path, line) = if let Some((path, rest)) = token.split_once("#L") {
+        (path, leading_number(rest))
+    } else if let Some((path, rest)) = token.split_once(':') {
+        (path, Some(leading_number(rest)?))
+    } else {
+        (token, None)
+    };
+
+    let looks_like_path = !path.is_empty()
+        && path
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '/' | '.' | '_' | '-'))
+        && path.chars().any(|c| c.is_ascii_alphabetic())
+        && has_extension_or_dir(path);
+    if !looks_like_path || (line.is_none() && !path.contains('/') && !backticked) {
+        return None;
+    }
+    Some((path.to_string(), line))
+}
+
+fn has_extension_or_dir(path: &str) -> bool {
+    if path.contains('/') {
+        return true;
+    }
+    match path.rsplit_once('.') {
+        Some((stem, ext)) => {
+            !stem.is_empty()
+                && (1..=6).contains(&ext.len())
+                && ext.chars().all(|c| c.is_ascii_alphanumeric())
+        }
+        None => false,
+    }
+}
+
+fn leading_number(text: &str) -> Option<u32> {
+    let digits: String = text.chars().take_while(|c| c.is_ascii_digit()).collect();
+    digits.parse().ok()
+}
+
+/// Find the JSON object in the output, inside a code fence or bare
+fn extract_json(output: &str) -> Option<&str> {
+    let start = output.find('{')?;
+    let end = output.rfind('}')?;
+    (start < end).then(|| &output[start..=end])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PROMPT_FORMAT: &str = r#"I looked at the diff.
+
+```
+REVIEW SUMMARY: REQUEST_CHANGES
+
+BLOCKING:
+- src/auth.rs:42: Token is logged in plain text. Suggestion: redact it before logging
+- `src/db.rs:10-14` SQL built with format!
+
+IMPORTANT:
+- Missing test for the expired-token path
+  in src/auth.rs line 80
+
+SUGGESTIONS:
+- None
+
+POSITIVE:
+- Clear error types
+```
+"#;
+
+    #[test]
+    fn test_parse_prompt_format() {
+        let result = ReviewResult::from_markdown(PROMPT_FORMAT).unwrap();
+        assert_eq!(result.verdict, ReviewVerdict::RequestChanges);
+
+        assert_eq!(result.blocking.len(), 2);
+        let first = &result.blocking[0];
+        assert_eq!(first.file.as_deref(), Some("src/auth.rs"));
+        assert_eq!(first.line, Some(42));
+        assert_eq!(first.description, "Token is logged in plain text");
+        assert_eq!(
+            first.suggestion.as_deref(),
+            Some("redact it before logging")
+        );
+        let second = &result.blocking[1];
+        assert_eq!(second.file.as_deref(), Some("src/db.rs"));
+        assert_eq!(second.line, Some(10));
+        assert_eq!(second.description, "SQL built with format!");
+
+        assert_eq!(result.important.len(), 1);
+        assert_eq!(result.important[0].file.as_deref(), Some("src/auth.rs"));
+        assert_eq!(result.important[0].line, Some(80));
+        assert!(result.important[0]
+            .description
+            .starts_with("Missing test for the expired-token path"));
+
+        assert!(result.suggestions.is_empty());
+        assert_eq!(result.positives, ["Clear error types"]);
+    }
+
+    #[test]
+    fn test_parse_markdown_variations() {
+        let output = "## Review Summary: **Approve**\n\n\
+                      ### Suggestions (2)\n\
+                      1. **lib.rs:7** — prefer `?` over `unwrap()`\n\
+                      2) Consider renaming `foo`\n\n\
+                      ### Notes\n\
+                      - Not a finding\n";
+        let result = ReviewResult::from_markdown(output).unwrap();
+        assert_eq!(result.verdict, ReviewVerdict::Approved);
+        assert_eq!(result.suggestions.len(), 2);
+        assert_eq!(result.suggestions[0].file.as_deref(), Some("lib.rs"));
+        assert_eq!(result.suggestions[0].line, Some(7));
+        assert_eq!(
+            result.suggestions[0].description,
+            "prefer `?` over `unwrap()`"
+        );
+        assert_eq!(result.suggestions[1].file, None);
+        assert!(result.blocking.is_empty());
+    }
+
+    #[test]
+    fn test_parse_workflow_prompt_format() {
+        let output = "- VERDICT: COMMENT\n\
+                      - BLOCKING: None\n\
+                      - IMPORTANT: src/main.rs:3 leaks a file handle\n\
+                      - SUGGESTIONS:\n\
+                      - Important: keep this as a suggestion\n";
+        let result = ReviewResult::from_markdown(output).unwrap();
+        assert_eq!(result.verdict, ReviewVerdict::Comment);
+        assert!(result.blocking.is_empty());
+        assert_eq!(
+            result.important[0].to_string(),
+            "src/main.rs:3: leaks a file handle"
+        );
+        assert_eq!(
+            result.suggestions[0].description,
+            "Important: keep this as a suggestion"
+        );
+    }
+
+    #[test]
+    fn test_last_review_wins() {
+        let output = "REVIEW SUMMARY: [APPROVE/REQUEST_CHANGES/COMMENT]\n\
+                      BLOCKING:\n- Issue description with file:line reference\n\n\
+                      REVIEW SUMMARY: APPROVE\n\
+                      POSITIVE:\n- Small, focused change\n";
+        let result = ReviewResult::from_markdown(output).unwrap();
+        assert_eq!(result.verdict, ReviewVerdict::Approved);
+        assert!(result.blocking.is_empty());
+        assert_eq!(result.positives.len(), 1);
+    }
+
+    #[test]
+    fn test_infers_missing_verdict() {
+        let result = ReviewResult::from_markdown("BLOCKING:\n- Panics on empty input\n").unwrap();
+        assert_eq!(result.verdict, ReviewVerdict::RequestChanges);
+
+        let result = ReviewResult::from_markdown("SUGGESTIONS:\n- Rename x\n").unwrap();
+        assert_eq!(result.verdict, ReviewVerdict::Comment);
+    }
+
+    #[test]
+    fn test_unparseable_output() {
+        assert!(ReviewResult::from_markdown("Looks fine to me!").is_err());
+        assert!(ReviewResult::from_json("no json here").is_err());
+    }
+
+    #[test]
+    fn test_parse_json() {
+        let output = r#"Here is my review:
+```json
+{
+  "verdict": "request_changes",
+  "blocking": [
+    {"file": "src/lib.rs", "line": 12, "description": "Off-by-one", "suggestion": "Use ..="}
+  ],
+  "important": ["src/io.rs:5: unbuffered writes"],
+  "positives": ["Good naming"]
+}
+```"#;
+        let result = ReviewResult::from_json(output).unwrap();
+        assert_eq!(result.verdict, ReviewVerdict::RequestChanges);
+        assert_eq!(
+            result.blocking[0].to_string(),
+            "src/lib.rs:12: Off-by-one (suggestion: Use ..=)"
+        );
+        assert_eq!(result.important[0].file.as_deref(), Some("src/io.rs"));
+        assert_eq!(result.important[0].line, Some(5));
+        assert!(result.suggestions.is_empty());
+
+        // Auto-detection picks JSON when present
+        let auto = ReviewResult::parse(output).unwrap();
+        assert_eq!(auto.blocking.len(), 1);
+        assert!(ReviewResult::parse(PROMPT_FORMAT).is_ok());
+    }
+
+    #[test]
+    fn test_json_unknown_verdict() {
+        assert!(ReviewResult::from_json(r#"{"verdict": "maybe"}"#).is_err());
+        let result = ReviewResult::from_json(r#"{"verdict": "APPROVE"}"#).unwrap();
+        assert!(result.verdict.is_approved());
+    }
+
+    #[test]
+    fn test_parse_location() {
+        assert_eq!(
+            parse_location("`src/a.rs:1`"),
+            Some(("src/a.rs".to_string(), Some(1)))
+        );
+        assert_eq!(
+            parse_location("src/a.rs#L20-L30"),
+            Some(("src/a.rs".to_string(), Some(20)))
+        );
+        assert_eq!(
+            parse_location("src/a.rs"),
+            Some(("src/a.rs".to_string(), None))
+        );
+        assert_eq!(parse_location("e.g."), None);
+        assert_eq!(parse_location("Fix:"), None);
+        assert_eq!(parse_location("https://example.com/a.rs:1"), None);
+    }
+}
@@ -0,0 +1,366 @@
+//! Running one agent of a multi-agent workflow to completion
+//!
+//! Shared by the workflow executors: spawns the agent, supervises it with the
+//! configured watchdog and `[budget]` limits, collects its text output and
+//! records the run in the database when one is attached.
+
+use std::path::Path;
+use std::sync::{Mutex, MutexGuard};
+
+use murmur_db::models::AgentRun;
+use murmur_db::repos::AgentRunRepository;
+use murmur_db::Database;
+
+use crate::agent::{
+    AgentSpawner, AgentType, BudgetHandler, BudgetTracker, CostInfo, OutputStreamer, PricingTable,
+    SharedUsage, StreamHandler, Usage, Watchdog,
+};
+use crate::config::{AgentConfig, BudgetConfig, Config};
+use crate::{Error, Result};
+
+/// How a workflow's agent runs are limited, priced and recorded
+#[derive(Debug, Clone, Default)]
+pub struct RunAccounting {
+    /// Limits for each run and across all runs for an issue
+    pub budget: BudgetConfig,
+
+    /// Prices for turning token usage into dollars
+    pub pricing: PricingTable,
+
+    /// The `owner/repo` runs are recorded under
+    pub repository: Option<String>,
+
+    /// Usage shared with the other runs of an orchestration
+    pub orchestration_usage: Option<SharedUsage>,
+}
+
+impl RunAccounting {
+    /// Apply the `[budget]` and `[pricing]` sections of a configuration
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            budget: config.budget.clone(),
+            pricing: config.pricing.clone(),
+            repository: None,
+            orchestration_usage: None,
+        }
+    }
+
+    /// Record runs under the `owner/repo` repository
+    pub fn with_repository(mut self, repository: impl Into<String>) -> Self {
+        self.repository = Some(repository.into());
+        self
+    }
+
+    /// Count runs against the orchestration budget, shared through `usage`
+    pub fn with_orchestration_usage(mut self, usage: SharedUsage) -> Self {
+        self.orchestration_usage = Some(usage);
+        self
+    }
+}
+
+/// Runs agents for a workflow, recording each run
+pub struct AgentRunner<'a> {
+    /// Agent configuration, for the model and watchdog of each agent type
+    pub config: &'a AgentConfig,
+
+    /// Limits, prices and repository the runs are accounted with
+    pub accounting: &'a RunAccounting,
+
+    /// Database to record runs in, if any
+    pub db: Option<&'a Database>,
+
+    /// Issue the runs work on, for recording and the issue budget
+    pub issue_number: Option<i64>,
+}
+
+impl AgentRunner<'_> {
+    /// Run one agent to completion, returning its text output and run ID
+    ///
+    /// Fails without starting the agent if the issue's budget is already used
+    /// up, and fails if the agent is stopped by the watchdog or a budget, or
+    /// exits unsuccessfully.
+    pub async fn run<H: StreamHandler>(
+        &self,
+        spawner: &AgentSpawner,
+        agent_type: AgentType,
+        prompt: &str,
+        workdir: &Path,
+        handler: &mut H,
+    ) -> Result<(String, Option<i64>)> {
+        let tracker = self.budget_tracker(agent_type)?;
+        let mut run = self.start_run(agent_type, prompt, workdir);
+        let mut handle = spawner.spawn(prompt, workdir).await?;
+        if let Some((run, _)) = run.as_mut() {
+            run.pid = handle.pid().map(|pid| pid as i32);
+        }
+        let stdout = handle
+            .child_mut()
+            .stdout
+            .take()
+            .ok_or_else(|| Error::Agent("Failed to capture agent stdout".to_string()))?;
+
+        let mut streamer = OutputStreamer::new(stdout);
+        let collector = CollectingHandler {
+            inner: handler,
+            text: String::new(),
+            session_id: None,
+        };
+        let mut budget = BudgetHandler::new(collector, tracker);
+        let watchdog = Watchdog::new(self.config.watchdog_for_type(agent_type));
+        let stopped = watchdog
+            .supervise(&mut handle, &mut streamer, &mut budget)
+            .await?;
+        let exceeded = budget.exceeded().cloned();
+        if exceeded.is_some() {
+            if let Err(e) = handle.kill().await {
+                tracing::warn!(error = %e, "Failed to kill agent over budget");
+            }
+        }
+        let status = handle.wait().await?;
+        let (collector, tracker) = budget.into_parts();
+
+        if let Some((run, _)) = run.as_mut() {
+            let usage = tracker.usage();
+            run.record_usage(
+                usage.input_tokens,
+                usage.output_tokens,
+                usage.cache_read_tokens,
+                usage.cache_write_tokens,
+                usage.cost_usd,
+            );
+            if let Some(ref session_id) = collector.session_id {
+                run.session_id = Some(session_id.clone());
+            }
+            if let Some(reason) = stopped {
+                run.mark_stopped(reason.as_str());
+            }
+            if exceeded.is_some() {
+                run.mark_budget_exceeded();
+            }
+            run.complete(status.code().unwrap_or(-1));
+        }
+        let run_id = self.finish_run(run);
+
+        if let Some(exceeded) = exceeded {
+            return Err(Error::Agent(format!(
+                "{} agent stopped: {}",
+                agent_type, exceeded
+            )));
+        }
+        if let Some(reason) = stopped {
+            return Err(Error::Agent(format!(
+                "{} agent stopped by watchdog: {}",
+                agent_type, reason
+            )));
+        }
+        if !status.success() {
+            return Err(Error::Agent(format!(
+                "{} agent exited with status: {}",
+                agent_type, status
+            )));
+        }
+        Ok((collector.text, run_id))
+    }
+
+    /// Track a run against the budgets, failing if the issue's is used up
+    fn budget_tracker(&self, agent_type: AgentType) -> Result<BudgetTracker> {
+        let budget = &self.accounting.budget;
+        let issue_spent = self.issue_spent()?;
+        if let Some(detail) = budget.issue().check(&issue_spent) {
+            return Err(Error::Agent(format!(
+                "Issue #{} budget exceeded: {}",
+                self.issue_number.unwrap_or_default(),
+                detail
+            )));
+        }
+
+        let model = self.config.resolve_for_type(agent_type).model;
+        let mut tracker = BudgetTracker::new(budget.run())
+            .with_pricing(&self.accounting.pricing, model.as_deref())
+            .with_issue_budget(budget.issue(), issue_spent);
+        if let Some(ref usage) = self.accounting.orchestration_usage {
+            tracker = tracker.with_orchestration_budget(budget.orchestration(), usage.clone());
+        }
+        Ok(tracker)
+    }
+
+    /// What earlier runs for the issue spent, if an issue budget applies
+    fn issue_spent(&self) -> Result<Usage> {
+        let (Some(db), Some(issue_number)) = (self.db, self.issue_number) else {
+            return Ok(Usage::default());
+        };
+        if self.accounting.budget.issue().is_unlimited() {
+            return Ok(Usage::default());
+        }
+        let (tokens, cost_usd) = AgentRunRepository::new(db)
+            .usage_by_issue(issue_number)
+            .map_err(|e| {
+                Error::Other(format!(
+                    "Failed to load spend for issue #{}: {}",
+                    issue_number, e
+                ))
+            })?;
+        Ok(Usage {
+            input_tokens: tokens as u64,
+            cost_usd,
+            ..Default::default()
+        })
+    }
+
+    /// Insert an agent run record, returning it with its ID
+    fn start_run(
+        &self,
+        agent_type: AgentType,
+        prompt: &str,
+        workdir: &Path,
+    ) -> Option<(AgentRun, i64)> {
+        let db = self.db?;
+        let name = match agent_type {
+            AgentType::Review => "reviewer",
+            AgentType::Test => "tester",
+            AgentType::Coordinator => "coordinator",
+            _ => "implementer",
+        };
+        let config_json = serde_json::to_string(self.config).unwrap_or_else(|_| "{}".to_string());
+        let mut run = AgentRun::new(name, prompt, workdir.to_string_lossy(), config_json);
+        if let Some(issue_number) = self.issue_number {
+            run = run.with_issue_number(issue_number);
+        }
+        if let Some(model) = self.config.resolve_for_type(agent_type).model {
+            run = run.with_model(model);
+        }
+        if let Some(ref repository) = self.accounting.repository {
+            run = run.with_repository(repository);
+        }
+
+        match AgentRunRepository::new(db).insert(&run) {
+            Ok(id) => {
+                run.id = Some(id);
+                Some((run, id))
+            }
+            Err(e) => {
+                tracing::warn!(error = %e, "Failed to record agent run");
+                None
+            }
+        }
+    }
+
+    /// Save a completed agent run record
+    fn finish_run(&self, run: Option<(AgentRun, i64)>) -> Option<i64> {
+        let (run, id) = run?;
+        let db = self.db?;
+        if let Err(e) = AgentRunRepository::new(db).update(&run) {
+            tracing::warn!(error = %e, "Failed to update agent run");
+        }
+        Some(id)
+    }
+}
+
+/// Forwards to the wrapped handler, collecting the assistant's text
+struct CollectingHandler<'a, H> {
+    inner: &'a mut H,
+    text: String,
+    session_id: Option<String>,
+}
+
+impl<H: StreamHandler> StreamHandler for CollectingHandler<'_, H> {
+    fn on_system(&mut self, subtype: Option<&str>, session_id: Option<&str>) {
+        if let Some(session_id) = session_id {
+            self.session_id = Some(session_id.to_string());
+        }
+        self.inner.on_system(subtype, session_id);
+    }
+
+    fn on_user(&mut self, message: &serde_json::Value) {
+        self.inner.on_user(message);
+    }
+
+    fn on_assistant_text(&mut self, text: &str) {
+        if !self.text.is_empty() {
+            self.text.push('\n');
+        }
+        self.text.push_str(text);
+        self.inner.on_assistant_text(text);
+    }
+
+    fn on_tool_use(&mut self, tool: &str, input: &serde_json::Value) {
+        self.inner.on_tool_use(tool, input);
+    }
+
+    fn on_tool_result(&mut self, output: &str, is_error: bool) {
+        self.inner.on_tool_result(output, is_error);
+    }
+
+    fn on_usage(&mut self, usage: &CostInfo) {
+        self.inner.on_usage(usage);
+    }
+
+    fn on_complete(&mut self, cost: Option<&CostInfo>, duration_ms: Option<u64>) {
+        self.inner.on_complete(cost, duration_ms);
+    }
+
+    fn on_parse_error(&mut self, line: &str, error: &serde_json::Error) {
+        self.inner.on_parse_error(line, error);
+    }
+
+    fn should_stop(&self) -> bool {
+        self.inner.should_stop()
+    }
+}
+
+/// Lets agents running at the same time report to one handler
+///
+/// Each event locks the shared handler, so events from different agents
+/// interleave but never overlap.
+pub(crate) struct SharedHandler<'a, 'h, H> {
+    inner: &'a Mutex<&'h mut H>,
+}
+
+impl<'a, 'h, H> SharedHandler<'a, 'h, H> {
+    pub fn new(inner: &'a Mutex<&'h mut H>) -> Self {
+        Self { inner }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, &'h mut H> {
+        // A handler that panicked mid-event is still fine to print with
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl<H: StreamHandler> StreamHandler for SharedHandler<'_, '_, H> {
+    fn on_system(&mut self, subtype: Option<&str>, session_id: Option<&str>) {
+        self.lock().on_system(subtype, session_id);
+    }
+
+    fn on_user(&mut self, message: &serde_json::Value) {
+        self.lock().on_user(message);
+    }
+
+    fn on_assistant_text(&mut self, text: &str) {
+        self.lock().on_assistant_text(text);
+    }
+
+    fn on_tool_use(&mut self, tool: &str, input: &serde_json::Value) {
+        self.lock().on_tool_use(tool, input);
+    }
+
+    fn on_tool_result(&mut self, output: &str, is_error: bool) {
+        self.lock().on_tool_result(output, is_error);
+    }
+
+    fn on_usage(&mut self, usage: &CostInfo) {
+        self.lock().on_usage(usage);
+    }
+
+    fn on_complete(&mut self, cost: Option<&CostInfo>, duration_ms: Option<u64>) {
+        self.lock().on_complete(cost, duration_ms);
+    }
+
+    fn on_parse_error(&mut self, line: &str, error: &serde_json::Error) {
+        self.lock().on_parse_error(line, error);
+    }
+
+    fn should_stop(&self) -> bool {
+        self.lock().should_stop()
+    }
+}
@@ -0,0 +1,273 @@
+//! Parsing coordinator planning output into a [`SubTask`] graph
+//!
+//! The planning prompt asks the coordinator agent to end its plan with a
+//! fenced JSON block listing the subtasks, the agent for each, the files it
+//! touches and the ids of the subtasks it depends on. [`parse_subtasks`]
+//! reads that block and [`validate_subtasks`] rejects graphs the executor
+//! can't run: duplicate ids, dependencies on unknown subtasks and cycles.
+
+use std::collections::{HashMap, HashSet};
+
+use serde::Deserialize;
+
+use super::coordinator::SubTask;
+use crate::agent::{last_json_block, AgentType};
+use crate::{Error, Result};
+
+/// Prompt instructions describing the structured plan
+pub const PLAN_FORMAT_INSTRUCTIONS: &str = "\
+End your answer with the subtasks as a fenced JSON block:\n\
+```json\n\
+{\"subtasks\": [\n  \
+{\"id\": \"parser\", \"description\": \"Add the config parser\", \"agent\": \"implement\",\n   \
+\"files\": [\"src/parser.rs\"], \"depends_on\": []},\n  \
+{\"id\": \"parser-tests\", \"description\": \"Test the config parser\", \"agent\": \"test\",\n   \
+\"files\": [\"tests/parser.rs\"], \"depends_on\": [\"parser\"]}\n\
+]}\n\
+```\n\
+`agent` is `implement` or `test`. Subtasks with no dependency between them may run at \
+the same time, so list every file each one changes. Use a single subtask for small tasks.";
+
+/// The plan as the coordinator agent writes it
+#[derive(Debug, Deserialize)]
+struct JsonPlan {
+    subtasks: Vec<JsonSubTask>,
+}
+
+#[derive(Debug, Deserialize)]
+struct JsonSubTask {
+    id: String,
+    description: String,
+    #[serde(default)]
+    agent: Option<String>,
+    #[serde(default)]
+    files: Vec<String>,
+    #[serde(default, alias = "dependencies")]
+    depends_on: Vec<String>,
+}
+
+impl TryFrom<JsonSubTask> for SubTask {
+    type Error = Error;
+
+    fn try_from(subtask: JsonSubTask) -> Result<Self> {
+        let agent_type = match subtask.agent.as_deref() {
+            Some(agent) => agent.parse::<AgentType>().map_err(Error::Agent)?,
+            None => AgentType::Implement,
+        };
+        if agent_type == AgentType::Coordinator {
+            return Err(Error::Agent(format!(
+                "Subtask {} can't be given to another coordinator",
+                subtask.id
+            )));
+        }
+        Ok(SubTask::new(subtask.id.trim(), subtask.description)
+            .with_agent_type(agent_type)
+            .with_files(subtask.files)
+            .with_dependencies(subtask.depends_on))
+    }
+}
+
+/// Parse the subtasks from a planning agent's output
+///
+/// Returns `None` if the output has no plan block, so the task is worked on
+/// as a whole. A plan block that doesn't parse or describes an invalid graph
+/// is an error.
+pub fn parse_subtasks(output: &str) -> Result<Option<Vec<SubTask>>> {
+    let plan: JsonPlan = match last_json_block(output) {
+        Some(json) => serde_json::from_str(json)
+            .map_err(|e| Error::Agent(format!("Invalid plan JSON: {}", e)))?,
+        // Agents sometimes answer with the bare object
+        None => match bare_json(output).and_then(|json| serde_json::from_str(json).ok()) {
+            Some(plan) => plan,
+            None => return Ok(None),
+        },
+    };
+
+    let subtasks = plan
+        .subtasks
+        .into_iter()
+        .map(SubTask::try_from)
+        .collect::<Result<Vec<_>>>()?;
+    if subtasks.is_empty() {
+        return Ok(None);
+    }
+    validate_subtasks(&subtasks)?;
+    Ok(Some(subtasks))
+}
+
+/// Check that the subtasks form a graph the executor can run
+///
+/// Ids must be non-empty and unique, every dependency must name another
+/// subtask, and the dependencies must not form a cycle.
+pub fn validate_subtasks(subtasks: &[SubTask]) -> Result<()> {
+    let mut ids = HashSet::new();
+    for subtask in subtasks {
+        if subtask.id.is_empty() {
+            return Err(Error::Agent(format!(
+                "Subtask without an id: {}",
+                subtask.description
+            )));
+        }
+        if !ids.insert(subtask.id.as_str()) {
+            return Err(Error::Agent(format!(
+                "Duplicate subtask id: {}",
+                subtask.id
+            )));
+        }
+    }
+    for subtask in subtasks {
+        if let Some(dep) = subtask
+            .depends_on
+            .iter()
+            .find(|d| !ids.contains(d.as_str()))
+        {
+            return Err(Error::Agent(format!(
+                "Subtask {} depends on unknown subtask {}",
+                subtask.id, dep
+            )));
+        }
+    }
+
+    let graph: HashMap<&str, &SubTask> = subtasks.iter().map(|s| (s.id.as_str(), s)).collect();
+    let mut done = HashSet::new();
+    for subtask in subtasks {
+        let mut path = Vec::new();
+        if let Some(cycle) = find_cycle(&graph, &subtask.id, &mut path, &mut done) {
+            return Err(Error::Agent(format!(
+                "Subtasks form a dependency cycle: {}",
+                cycle.join(" -> ")
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Depth-first search for a cycle through `id`'s dependencies
+///
+/// `path` holds the subtasks on the current search path and `done` those
+/// already known not to reach a cycle.
+fn find_cycle<'a>(
+    graph: &HashMap<&'a str, &'a SubTask>,
+    id: &'a str,
+    path: &mut Vec<&'a str>,
+    done: &mut HashSet<&'a str>,
+) -> Option<Vec<&'a str>> {
+    if done.contains(id) {
+        return None;
+    }
+    if let Some(start) = path.iter().position(|p| *p == id) {
+        let mut cycle = path[start..].to_vec();
+        cycle.push(id);
+        return Some(cycle);
+    }
+
+    path.push(id);
+    for dep in &graph[id].depends_on {
+        if let Some(cycle) = find_cycle(graph, dep, path, done) {
+            return Some(cycle);
+        }
+    }
+    path.pop();
+    done.insert(id);
+    None
+}
+
+/// A bare JSON object spanning the output
+fn bare_json(output: &str) -> Option<&str> {
+    let start = output.find('{')?;
+    let end = output.rfind('}')?;
+    (start < end).then(|| &output[start..=end])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn subtask(id: &str, deps: &[&str]) -> SubTask {
+        SubTask::new(id, id).with_dependencies(deps.iter().map(|d| d.to_string()).collect())
+    }
+
+    #[test]
+    fn test_parse_fenced_plan() {
+        let output = r#"The parser comes first, then its tests.
+
+```json
+{"subtasks": [
+  {"id": "parser", "description": "Add the parser", "files": ["src/parser.rs"]},
+  {"id": "tests", "description": "Test it", "agent": "test", "dependencies": ["parser"]}
+]}
+```
+"#;
+        let subtasks = parse_subtasks(output).unwrap().unwrap();
+        assert_eq!(subtasks.len(), 2);
+        assert_eq!(subtasks[0].agent_type, AgentType::Implement);
+        assert_eq!(subtasks[0].files, ["src/parser.rs"]);
+        assert_eq!(subtasks[1].agent_type, AgentType::Test);
+        assert_eq!(subtasks[1].depends_on, ["parser"]);
+    }
+
+    #[test]
+    fn test_parse_without_plan() {
+        assert!(parse_subtasks("Just implement it in one go.")
+            .unwrap()
+            .is_none());
+        assert!(parse_subtasks("Use a {placeholder} here")
+            .unwrap()
+            .is_none());
+        assert!(parse_subtasks(r#"{"subtasks": []}"#).unwrap().is_none());
+
+        let bare = r#"{"subtasks": [{"id": "a", "description": "Do it"}]}"#;
+        assert_eq!(parse_subtasks(bare).unwrap().unwrap()[0].id, "a");
+    }
+
+    #[test]
+    fn test_parse_invalid_plan() {
+        let err = parse_subtasks("```json\n{\"subtasks\": [{\"id\": \"a\"}]}\n```").unwrap_err();
+        assert!(err.to_string().contains("Invalid plan JSON"));
+
+        let unknown_agent =
+            "```json\n{\"subtasks\": [{\"id\": \"a\", \"description\": \"x\", \"agent\": \"qa\"}]}\n```";
+        assert!(parse_subtasks(unknown_agent).is_err());
+
+        let coordinator = "```json\n{\"subtasks\": [{\"id\": \"a\", \"description\": \"x\", \
+                           \"agent\": \"coordinator\"}]}\n```";
+        assert!(parse_subtasks(coordinator).is_err());
+    }
+
+    #[test]
+    fn test_validate_ids() {
+        assert!(validate_subtasks(&[subtask("a", &[]), subtask("b", &["a"])]).is_ok());
+
+        let err = validate_subtasks(&[subtask("a", &[]), subtask("a", &[])]).unwrap_err();
+        assert!(err.to_string().contains("Duplicate subtask id: a"));
+
+        let err = validate_subtasks(&[subtask("a", &["b"])]).unwrap_err();
+        assert!(err.to_string().contains("a depends on unknown subtask b"));
+
+        assert!(validate_subtasks(&[subtask("", &[])]).is_err());
+    }
+
+    #[test]
+    fn test_validate_cycles() {
+        let err = validate_subtasks(&[
+            subtask("a", &[]),
+            subtask("b", &["a", "d"]),
+            subtask("c", &["b"]),
+            subtask("d", &["c"]),
+        ])
+        .unwrap_err();
+        assert!(err.to_string().contains("cycle: b -> d -> c -> b"));
+
+        let err = validate_subtasks(&[subtask("a", &["a"])]).unwrap_err();
+        assert!(err.to_string().contains("cycle: a -> a"));
+
+        // A diamond is not a cycle
+        assert!(validate_subtasks(&[
+            subtask("a", &[]),
+            subtask("b", &["a"]),
+            subtask("c", &["a"]),
+            subtask("d", &["b", "c"]),
+        ])
+        .is_ok());
+    }
+}
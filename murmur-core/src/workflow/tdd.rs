@@ -9,6 +9,7 @@
 //! 6. Refactor: Clean up code while keeping tests green
 //! 7. Complete: TDD cycle finished
 
+use super::test_runner::TestCase;
 use crate::agent::{AgentFactory, ImplementAgent, TestAgent};
 use crate::config::AgentConfig;
 use serde::{Deserialize, Serialize};
@@ -145,6 +146,19 @@ impl TddPhase {
         }
     }
 
+    /// Get the identifier used when persisting the phase
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TddPhase::WriteSpec => "write_spec",
+            TddPhase::WriteTests => "write_tests",
+            TddPhase::VerifyRed => "verify_red",
+            TddPhase::Implement => "implement",
+            TddPhase::VerifyGreen => "verify_green",
+            TddPhase::Refactor => "refactor",
+            TddPhase::Complete => "complete",
+        }
+    }
+
     /// Check if this is a terminal state
     pub fn is_terminal(&self) -> bool {
         matches!(self, TddPhase::Complete)
@@ -160,6 +174,7 @@ impl TddPhase {
                 requires_test_failure: false,
                 requires_test_success: false,
                 requires_implementation: false,
+                requires_quality_gates: false,
             },
             TddPhase::WriteTests => PhaseValidation {
                 description: "Tests must be written based on the specification",
@@ -168,6 +183,7 @@ impl TddPhase {
                 requires_test_failure: false,
                 requires_test_success: false,
                 requires_implementation: false,
+                requires_quality_gates: false,
             },
             TddPhase::VerifyRed => PhaseValidation {
                 description: "Tests must exist and fail (proving they test something)",
@@ -176,6 +192,7 @@ impl TddPhase {
                 requires_test_failure: true,
                 requires_test_success: false,
                 requires_implementation: false,
+                requires_quality_gates: false,
             },
             TddPhase::Implement => PhaseValidation {
                 description: "Implementation code to make tests pass",
@@ -184,6 +201,7 @@ impl TddPhase {
                 requires_test_failure: false,
                 requires_test_success: false,
                 requires_implementation: false,
+                requires_quality_gates: false,
             },
             TddPhase::VerifyGreen => PhaseValidation {
                 description: "All tests must pass",
@@ -192,6 +210,7 @@ impl TddPhase {
                 requires_test_failure: false,
                 requires_test_success: true,
                 requires_implementation: true,
+                requires_quality_gates: true,
             },
             TddPhase::Refactor => PhaseValidation {
                 description: "Code can be refactored while keeping tests green",
@@ -200,6 +219,7 @@ impl TddPhase {
                 requires_test_failure: false,
                 requires_test_success: true,
                 requires_implementation: true,
+                requires_quality_gates: true,
             },
             TddPhase::Complete => PhaseValidation {
                 description: "TDD cycle is complete",
@@ -208,6 +228,7 @@ impl TddPhase {
                 requires_test_failure: false,
                 requires_test_success: true,
                 requires_implementation: true,
+                requires_quality_gates: true,
             },
         }
     }
@@ -219,6 +240,24 @@ impl std::fmt::Display for TddPhase {
     }
 }
 
+impl std::str::FromStr for TddPhase {
+    type Err = String;
+
+    /// Parse a phase from its persisted name, e.g. `write_tests` or `write-tests`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().replace('-', "_").as_str() {
+            "write_spec" => Ok(TddPhase::WriteSpec),
+            "write_tests" => Ok(TddPhase::WriteTests),
+            "verify_red" => Ok(TddPhase::VerifyRed),
+            "implement" => Ok(TddPhase::Implement),
+            "verify_green" => Ok(TddPhase::VerifyGreen),
+            "refactor" => Ok(TddPhase::Refactor),
+            "complete" => Ok(TddPhase::Complete),
+            _ => Err(format!("Unknown TDD phase: {}", s)),
+        }
+    }
+}
+
 /// Validation requirements for a TDD phase
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct PhaseValidation {
@@ -234,6 +273,8 @@ pub struct PhaseValidation {
     pub requires_test_success: bool,
     /// Whether implementation code must exist
     pub requires_implementation: bool,
+    /// Whether the configured build, format and lint checks must pass
+    pub requires_quality_gates: bool,
 }
 
 /// State tracking for a TDD workflow
@@ -261,6 +302,21 @@ pub struct TddState {
     pub skip_refactor: bool,
     /// History of phase transitions
     pub history: Vec<TddTransition>,
+    /// Tests and their outcomes before WriteTests first ran, if known
+    #[serde(default)]
+    pub baseline: Option<Vec<TestCase>>,
+    /// New tests that VerifyRed saw failing, which must pass in VerifyGreen
+    #[serde(default)]
+    pub new_tests: Vec<String>,
+    /// Why a human rejected the last attempt at the current phase's work
+    #[serde(default)]
+    pub rejection: Option<String>,
+    /// Whether the workflow is waiting for approval to start the current phase
+    #[serde(default)]
+    pub awaiting_approval: bool,
+    /// What the quality gates found wrong with the last attempt
+    #[serde(default)]
+    pub quality_problems: Option<String>,
 }
 
 /// A transition between TDD phases
@@ -291,6 +347,11 @@ impl TddState {
             skip_spec: false,
             skip_refactor: false,
             history: Vec::new(),
+            baseline: None,
+            new_tests: Vec::new(),
+            rejection: None,
+            awaiting_approval: false,
+            quality_problems: None,
         }
     }
 
@@ -308,6 +369,11 @@ impl TddState {
             skip_spec: true,
             skip_refactor: false,
             history: Vec::new(),
+            baseline: None,
+            new_tests: Vec::new(),
+            rejection: None,
+            awaiting_approval: false,
+            quality_problems: None,
         }
     }
 
@@ -464,6 +530,32 @@ impl TddState {
         }
     }
 
+    /// Go back to an earlier phase to redo its work after a rejection
+    pub fn send_back(&mut self, target: TddPhase, reason: impl Into<String>) {
+        let reason = reason.into();
+        self.history.push(TddTransition {
+            from: self.phase,
+            to: target,
+            success: false,
+            message: Some(reason.clone()),
+        });
+        self.phase = target;
+        self.rejection = Some(reason);
+    }
+
+    /// Go back to `target` to fix what the quality gates found
+    pub fn retry_quality(&mut self, target: TddPhase, problems: impl Into<String>) {
+        let problems = problems.into();
+        self.history.push(TddTransition {
+            from: self.phase,
+            to: target,
+            success: false,
+            message: Some(problems.clone()),
+        });
+        self.phase = target;
+        self.quality_problems = Some(problems);
+    }
+
     /// Restart from the beginning (WriteSpec or WriteTests depending on config)
     pub fn restart(&mut self, message: Option<String>) {
         let target = if self.skip_spec {
@@ -540,6 +632,14 @@ impl TddWorkflow {
         }
     }
 
+    /// Continue a workflow from previously saved state
+    pub fn from_state(state: TddState, config: AgentConfig) -> Self {
+        Self {
+            state,
+            factory: AgentFactory::with_config(config),
+        }
+    }
+
     /// Get the current state
     pub fn state(&self) -> &TddState {
         &self.state
@@ -566,7 +666,28 @@ impl TddWorkflow {
     }
 
     /// Get the prompt for the current phase
+    ///
+    /// When a human rejected the last attempt, or it failed the quality
+    /// gates, the reason is included.
     pub fn current_prompt(&self) -> String {
+        let mut prompt = self.phase_prompt();
+        if let Some(ref reason) = self.state.rejection {
+            prompt.push_str(&format!(
+                "\n\n## Rejected\n\nA reviewer rejected the previous attempt:\n\n{}",
+                reason.trim()
+            ));
+        }
+        if let Some(ref problems) = self.state.quality_problems {
+            prompt.push_str(&format!(
+                "\n\n## Quality Gates\n\nThe previous attempt failed these checks. \
+                 Fix every problem without disabling the checks:\n\n{}",
+                problems.trim()
+            ));
+        }
+        prompt
+    }
+
+    fn phase_prompt(&self) -> String {
         match self.state.phase {
             TddPhase::WriteSpec => self.write_spec_prompt(),
             TddPhase::WriteTests => self.write_tests_prompt(),
@@ -618,14 +739,19 @@ impl TddWorkflow {
     }
 
     fn implement_prompt(&self) -> String {
+        let tests_note = if self.state.test_files.is_empty() {
+            String::new()
+        } else {
+            format!("\n\nThe tests are in: {}", self.state.test_files.join(", "))
+        };
         format!(
-            "Implement the MINIMAL code to make the tests pass:\n\n{}\n\n\
+            "Implement the MINIMAL code to make the tests pass:\n\n{}{}\n\n\
              Guidelines:\n\
              - Write only enough code to make the tests pass\n\
              - Do not add extra features or optimizations\n\
              - Do not refactor yet - that comes later\n\
              - Focus on making tests green, not on perfect code",
-            self.state.behavior
+            self.state.behavior, tests_note
         )
     }
 
@@ -672,6 +798,16 @@ impl TddWorkflow {
         self.state.retry_implement(message);
     }
 
+    /// Go back to an earlier phase to redo its work after a rejection
+    pub fn send_back(&mut self, target: TddPhase, reason: impl Into<String>) {
+        self.state.send_back(target, reason);
+    }
+
+    /// Go back to `target` to fix what the quality gates found
+    pub fn retry_quality(&mut self, target: TddPhase, problems: impl Into<String>) {
+        self.state.retry_quality(target, problems);
+    }
+
     /// Restart the TDD cycle from the beginning
     pub fn restart(&mut self, message: Option<String>) {
         self.state.restart(message);
@@ -959,6 +1095,32 @@ mod tests {
         assert_eq!(state.iterations, 0);
     }
 
+    #[test]
+    fn test_state_send_back() {
+        let mut state = TddState::new("test", "/tmp");
+        state.advance(true, None); // WriteSpec -> WriteTests
+
+        state.send_back(TddPhase::WriteSpec, "Spec is missing error cases");
+        assert_eq!(state.phase, TddPhase::WriteSpec);
+        assert_eq!(
+            state.rejection.as_deref(),
+            Some("Spec is missing error cases")
+        );
+        let last = state.history.last().unwrap();
+        assert_eq!(last.from, TddPhase::WriteTests);
+        assert!(!last.success);
+    }
+
+    #[test]
+    fn test_phase_from_str() {
+        assert_eq!("write_tests".parse(), Ok(TddPhase::WriteTests));
+        assert_eq!("Verify-Green".parse(), Ok(TddPhase::VerifyGreen));
+        assert!("testing".parse::<TddPhase>().is_err());
+        for phase in [TddPhase::WriteSpec, TddPhase::Refactor, TddPhase::Complete] {
+            assert_eq!(phase.as_str().parse(), Ok(phase));
+        }
+    }
+
     #[test]
     fn test_state_restart_without_spec() {
         let mut state = TddState::new_without_spec("test", "/tmp");
@@ -1034,6 +1196,33 @@ mod tests {
         assert!(prompt.contains("MINIMAL"));
     }
 
+    #[test]
+    fn test_workflow_prompt_includes_rejection() {
+        let mut workflow = TddWorkflow::new("test feature", "/tmp");
+        workflow.advance(true, None); // WriteSpec -> WriteTests
+        workflow.send_back(TddPhase::WriteSpec, "Describe the error cases");
+        let prompt = workflow.current_prompt();
+        assert!(prompt.contains("specification"));
+        assert!(prompt.contains("Describe the error cases"));
+    }
+
+    #[test]
+    fn test_retry_quality() {
+        let mut workflow = TddWorkflow::new_without_spec("test feature", "/tmp");
+        workflow.transition_to(TddPhase::VerifyRed, None);
+        workflow.transition_to(TddPhase::Implement, None);
+        workflow.advance(true, None); // Implement -> VerifyGreen
+        assert!(workflow.current_validation().requires_quality_gates);
+
+        workflow.retry_quality(TddPhase::Implement, "`cargo fmt --all -- --check` failed");
+        assert_eq!(workflow.phase(), TddPhase::Implement);
+        assert!(!workflow.state().history.last().unwrap().success);
+        assert!(!workflow.current_validation().requires_quality_gates);
+        let prompt = workflow.current_prompt();
+        assert!(prompt.contains("## Quality Gates"));
+        assert!(prompt.contains("cargo fmt"));
+    }
+
     #[test]
     fn test_workflow_advance() {
         let mut workflow = TddWorkflow::new("test", "/tmp");
@@ -0,0 +1,1209 @@
+//! Executor that drives a [`TddWorkflow`] through its red/green cycle
+//!
+//! [`TddExecutor`] spawns the test and implement agents for the writing
+//! phases and runs the project's tests for the verification phases, going
+//! back to WriteTests when tests don't fail and to Implement when they don't
+//! pass. Callers follow progress through a [`TddObserver`], so the same loop
+//! serves `murmur tdd`, `murmur work --tdd` and the orchestrator.
+//!
+//! Before the tests are first written the executor runs the existing suite
+//! as a baseline. When the framework reports individual tests, VerifyRed
+//! then requires the newly added tests to fail and VerifyGreen requires them
+//! to pass without breaking any test that passed in the baseline.
+//!
+//! With a database attached, the workflow state is saved as a
+//! [`TddSession`] after every transition so [`TddExecutor::resume`] can pick
+//! up where a crashed or interrupted run left off.
+//!
+//! With [`QualityGates`] configured, the build, format and lint checks run
+//! once VerifyGreen's tests pass and after Refactor. Failures go back to the
+//! implement agent with the checks' output.
+//!
+//! With [`ApprovalGates`] the executor waits for a human to approve before
+//! starting a gated phase. A rejection sends it back to redo the work that
+//! led there, with the reason in the agent's prompt.
+
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use murmur_db::models::{Approval, TddSession, TddTransitionRecord};
+use murmur_db::repos::TddSessionRepository;
+use murmur_db::Database;
+
+use super::approval::{ApprovalDecision, ApprovalGates};
+use super::quality::{QualityCheck, QualityConfig, QualityGates};
+use super::runner::{AgentRunner, RunAccounting};
+use super::tdd::{TddPhase, TddState, TddWorkflow};
+use super::test_config::{TestConfig, TestSuite};
+use super::test_runner::{compare_to_baseline, TestFramework, TestResults, TestRunner};
+use crate::agent::{AgentSpawner, AgentType, PromptBuilder, PromptTemplates, StreamHandler};
+use crate::config::AgentConfig;
+use crate::{Error, Result};
+
+/// Callbacks for following a TDD run
+///
+/// Every method has an empty default, so observers only implement the
+/// events they care about.
+pub trait TddObserver: Send {
+    /// Called when a phase starts
+    fn on_phase_start(&mut self, _phase: TddPhase, _state: &TddState) {}
+
+    /// Called with the results of the baseline run taken before WriteTests
+    fn on_baseline(&mut self, _results: &TestResults) {}
+
+    /// Called before the agent for a writing phase is spawned
+    fn on_agent_start(&mut self, _phase: TddPhase, _agent_type: AgentType, _prompt: &str) {}
+
+    /// Called before the tests run in a verification phase
+    fn on_tests_start(&mut self, _phase: TddPhase) {}
+
+    /// Called with each line of test output while the tests run
+    ///
+    /// The baseline run taken before WriteTests reports `WriteTests` as its
+    /// phase.
+    fn on_test_output(&mut self, _phase: TddPhase, _line: &str) {}
+
+    /// Called with the results of a verification phase's test run
+    fn on_test_results(&mut self, _phase: TddPhase, _results: &TestResults) {}
+
+    /// Called when a phase succeeds and the workflow advances
+    fn on_phase_complete(&mut self, _phase: TddPhase) {}
+
+    /// Called when a verification fails and the workflow goes back to `to`
+    fn on_retry(&mut self, _from: TddPhase, _to: TddPhase, _reason: &str) {}
+
+    /// Called after a quality check runs, with the problem if it failed
+    fn on_quality_check(
+        &mut self,
+        _phase: TddPhase,
+        _check: &QualityCheck,
+        _problem: Option<&str>,
+    ) {
+    }
+
+    /// Called when the workflow starts waiting for approval to start `to`
+    fn on_approval_pending(&mut self, _from: TddPhase, _to: TddPhase, _approval_id: i64) {}
+
+    /// Called with the decision on starting `to`
+    fn on_approval(&mut self, _to: TddPhase, _decision: &ApprovalDecision) {}
+}
+
+/// Observer that ignores every event
+impl TddObserver for () {}
+
+/// Outcome of a TDD run
+#[derive(Debug, Clone)]
+pub struct TddOutcome {
+    /// Whether the workflow reached Complete
+    pub completed: bool,
+    /// Implement → VerifyGreen iterations used
+    pub iterations: u32,
+    /// Results of the last test run, if any
+    pub last_results: Option<TestResults>,
+}
+
+/// What a verification phase decided
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Verdict {
+    Advance,
+    Retry(String),
+    GiveUp,
+}
+
+/// Runs a TDD workflow's agents and test verifications
+pub struct TddExecutor {
+    workflow: TddWorkflow,
+    config: AgentConfig,
+    test_runner: TestRunner,
+    test_spawner: AgentSpawner,
+    implement_spawner: AgentSpawner,
+    templates: PromptTemplates,
+    issue_number: Option<i64>,
+    worktree_id: Option<i64>,
+    accounting: RunAccounting,
+    db: Option<Database>,
+    session: Option<TddSession>,
+    approval_gates: ApprovalGates,
+    quality_gates: QualityGates,
+    red_retries: u32,
+    refactor_retries: u32,
+}
+
+impl TddExecutor {
+    /// Create an executor for implementing `behavior` in `workdir`
+    ///
+    /// Tests are run as the repository's `.murmur.toml` configures, or else
+    /// with the suites detected in `workdir`, falling back to cargo.
+    pub fn new(
+        behavior: impl Into<String>,
+        workdir: impl Into<PathBuf>,
+        config: AgentConfig,
+    ) -> Self {
+        let workdir = workdir.into();
+        Self::from_workflow(
+            TddWorkflow::with_config(behavior, &workdir, config.clone()),
+            config,
+        )
+    }
+
+    /// Continue a saved session from its last recorded phase
+    ///
+    /// Attach the database again with [`with_database`](Self::with_database)
+    /// to keep recording into the same session.
+    pub fn resume(mut session: TddSession, config: AgentConfig) -> Result<Self> {
+        let state: TddState = serde_json::from_str(&session.state_json)?;
+        session.status = "running".to_string();
+        let mut executor =
+            Self::from_workflow(TddWorkflow::from_state(state, config.clone()), config);
+        executor.issue_number = session.issue_number;
+        executor.worktree_id = session.worktree_id;
+        executor.session = Some(session);
+        Ok(executor)
+    }
+
+    fn from_workflow(workflow: TddWorkflow, config: AgentConfig) -> Self {
+        let workdir = workflow.state().workdir.clone();
+        Self {
+            templates: PromptTemplates::discover(&workdir),
+            test_runner: default_runner(&workdir, &TestConfig::default()),
+            workflow,
+            test_spawner: AgentSpawner::from_config(config.clone(), AgentType::Test),
+            implement_spawner: AgentSpawner::from_config(config.clone(), AgentType::Implement),
+            config,
+            issue_number: None,
+            worktree_id: None,
+            accounting: RunAccounting::default(),
+            db: None,
+            session: None,
+            approval_gates: ApprovalGates::default(),
+            quality_gates: QualityGates::default(),
+            red_retries: 0,
+            refactor_retries: 0,
+        }
+    }
+
+    /// Start from WriteTests instead of WriteSpec
+    pub fn with_skip_spec(mut self) -> Self {
+        let state = self.workflow.state_mut();
+        *state = state.clone().with_skip_spec();
+        self
+    }
+
+    /// Go straight to Complete after VerifyGreen
+    pub fn with_skip_refactor(mut self) -> Self {
+        self.workflow.state_mut().skip_refactor = true;
+        self
+    }
+
+    /// Give up after this many Implement → VerifyGreen iterations
+    pub fn with_max_iterations(mut self, max_iterations: u32) -> Self {
+        self.workflow.state_mut().max_iterations = max_iterations;
+        self
+    }
+
+    /// Run tests as a `[test]` config section says
+    ///
+    /// A `[test]` section in the repository's `.murmur.toml` still takes
+    /// precedence.
+    pub fn with_test_config(mut self, config: &TestConfig) -> Self {
+        self.test_runner = default_runner(&self.workflow.state().workdir, config);
+        self
+    }
+
+    /// Run the checks a `[quality]` config section asks for
+    ///
+    /// A `[quality]` section in the repository's `.murmur.toml` still takes
+    /// precedence.
+    pub fn with_quality_config(mut self, config: &QualityConfig) -> Self {
+        self.quality_gates = QualityGates::from_config(&self.workflow.state().workdir, config);
+        self
+    }
+
+    /// Use a custom test runner for the verification phases
+    pub fn with_test_runner(mut self, test_runner: TestRunner) -> Self {
+        self.test_runner = test_runner;
+        self
+    }
+
+    /// Use a custom spawner for the test agent
+    pub fn with_test_spawner(mut self, spawner: AgentSpawner) -> Self {
+        self.test_spawner = spawner;
+        self
+    }
+
+    /// Use a custom spawner for the implement agent
+    pub fn with_implement_spawner(mut self, spawner: AgentSpawner) -> Self {
+        self.implement_spawner = spawner;
+        self
+    }
+
+    /// Associate the agent runs with a GitHub issue
+    pub fn with_issue_number(mut self, issue_number: i64) -> Self {
+        self.issue_number = Some(issue_number);
+        self
+    }
+
+    /// Link the saved session to a tracked worktree
+    pub fn with_worktree_id(mut self, worktree_id: i64) -> Self {
+        self.worktree_id = Some(worktree_id);
+        self
+    }
+
+    /// Wait for human approval before starting the gated phases
+    ///
+    /// Pending approvals are stored in the database, which must be attached.
+    pub fn with_approval_gates(mut self, gates: ApprovalGates) -> Self {
+        self.approval_gates = gates;
+        self
+    }
+
+    /// Enforce spending limits on the agent runs
+    pub fn with_accounting(mut self, accounting: RunAccounting) -> Self {
+        self.accounting = accounting;
+        self
+    }
+
+    /// Record agent runs and save the workflow state in a database
+    pub fn with_database(mut self, db: Database) -> Self {
+        self.db = Some(db);
+        self
+    }
+
+    /// Take back the attached database
+    pub(crate) fn take_database(&mut self) -> Option<Database> {
+        self.db.take()
+    }
+
+    /// ID of the saved session, once the run has started
+    pub fn session_id(&self) -> Option<i64> {
+        self.session.as_ref().and_then(|s| s.id)
+    }
+
+    /// Get the underlying workflow
+    pub fn workflow(&self) -> &TddWorkflow {
+        &self.workflow
+    }
+
+    /// Get the test framework used for verification
+    pub fn test_framework(&self) -> Option<TestFramework> {
+        self.test_runner.framework()
+    }
+
+    /// Get the test suites used for verification
+    pub fn test_suites(&self) -> &[TestSuite] {
+        self.test_runner.suites()
+    }
+
+    /// Get the quality checks run after Implement and Refactor
+    pub fn quality_checks(&self) -> &[QualityCheck] {
+        self.quality_gates.checks()
+    }
+
+    /// Run the workflow until it completes or gives up
+    ///
+    /// Agent output is streamed to `handler` and progress reported to
+    /// `observer`. Fails if an agent fails, or if the tests still don't fail
+    /// after `max_iterations` attempts at writing them.
+    pub async fn run<O: TddObserver, H: StreamHandler>(
+        &mut self,
+        observer: &mut O,
+        handler: &mut H,
+    ) -> Result<TddOutcome> {
+        let mut last_results = None;
+        self.start_session();
+
+        // A resumed run may have stopped while waiting for approval
+        if self.workflow.state().awaiting_approval {
+            let from = self
+                .workflow
+                .state()
+                .history
+                .last()
+                .map_or(self.workflow.phase(), |t| t.from);
+            self.await_approval(from, observer).await?;
+        }
+
+        // The iteration cap is checked when VerifyGreen fails rather than in
+        // the loop condition, so a final successful iteration can still finish
+        while !self.workflow.is_complete() {
+            let phase = self.workflow.phase();
+            observer.on_phase_start(phase, self.workflow.state());
+
+            match phase {
+                TddPhase::WriteSpec
+                | TddPhase::WriteTests
+                | TddPhase::Implement
+                | TddPhase::Refactor => {
+                    let agent_type = phase_agent_type(phase);
+                    let prompt = PromptBuilder::new(agent_type)
+                        .templates(self.templates.clone())
+                        .task(self.workflow.current_prompt())
+                        .try_build()?;
+                    if phase == TddPhase::WriteTests && self.needs_baseline() {
+                        let results = self
+                            .test_runner
+                            .run_with_handler(&mut |line: &str| {
+                                observer.on_test_output(phase, line)
+                            })
+                            .await;
+                        observer.on_baseline(&results);
+                        if results.has_test_cases() {
+                            self.workflow.state_mut().baseline = Some(results.tests);
+                        }
+                    }
+                    observer.on_agent_start(phase, agent_type, &prompt);
+                    let before = dirty_files(&self.workflow.state().workdir);
+                    let run_id = self.run_agent(agent_type, &prompt, handler).await?;
+                    self.record_files(phase, &before);
+                    let state = self.workflow.state_mut();
+                    state.rejection = None;
+                    state.quality_problems = None;
+
+                    if phase.validation_requirements().requires_quality_gates {
+                        if let Some((reason, problems)) =
+                            self.check_quality(phase, observer).await?
+                        {
+                            self.refactor_retries += 1;
+                            if self.refactor_retries >= self.workflow.state().max_iterations {
+                                tracing::warn!("Quality checks still fail after refactoring");
+                                self.finish_session(false);
+                                break;
+                            }
+                            self.workflow.retry_quality(phase, problems);
+                            self.save_transition(phase, false, Some(reason.clone()), run_id);
+                            observer.on_retry(phase, phase, &reason);
+                            continue;
+                        }
+                    }
+
+                    self.workflow.advance(true, None);
+                    self.save_transition(phase, true, None, run_id);
+                    observer.on_phase_complete(phase);
+                    self.await_approval(phase, observer).await?;
+                }
+                TddPhase::VerifyRed | TddPhase::VerifyGreen => {
+                    observer.on_tests_start(phase);
+                    // Only the suites covering the files written so far
+                    let state = self.workflow.state();
+                    let changed: Vec<String> =
+                        [state.test_files.as_slice(), state.impl_files.as_slice()].concat();
+                    let results = self
+                        .test_runner
+                        .run_for_changes(&changed, &mut |line: &str| {
+                            observer.on_test_output(phase, line)
+                        })
+                        .await;
+                    observer.on_test_results(phase, &results);
+
+                    let mut verdict = self.verify(phase, &results);
+                    if verdict == Verdict::Advance
+                        && phase.validation_requirements().requires_quality_gates
+                    {
+                        if let Some((reason, problems)) =
+                            self.check_quality(phase, observer).await?
+                        {
+                            verdict = if self.workflow.should_give_up() {
+                                Verdict::GiveUp
+                            } else {
+                                self.workflow.retry_quality(TddPhase::Implement, problems);
+                                Verdict::Retry(reason)
+                            };
+                        }
+                    }
+
+                    match verdict {
+                        Verdict::Advance => {
+                            self.workflow.advance(true, Some(results.summary()));
+                            self.save_transition(phase, true, Some(results.summary()), None);
+                            observer.on_phase_complete(phase);
+                            self.await_approval(phase, observer).await?;
+                        }
+                        Verdict::Retry(reason) => {
+                            self.save_transition(phase, false, Some(reason.clone()), None);
+                            observer.on_retry(phase, self.workflow.phase(), &reason);
+                        }
+                        Verdict::GiveUp if phase == TddPhase::VerifyRed => {
+                            self.finish_session(false);
+                            return Err(Error::Agent(format!(
+                                "Tests still don't fail after {} attempts at writing them",
+                                self.red_retries
+                            )));
+                        }
+                        Verdict::GiveUp => {
+                            tracing::warn!(
+                                iterations = self.workflow.state().iterations,
+                                "TDD workflow hit the iteration cap"
+                            );
+                            self.finish_session(false);
+                            last_results = Some(results);
+                            break;
+                        }
+                    }
+                    last_results = Some(results);
+                }
+                TddPhase::Complete => break,
+            }
+        }
+
+        if self.workflow.is_complete() {
+            self.finish_session(true);
+        }
+
+        Ok(TddOutcome {
+            completed: self.workflow.is_complete(),
+            iterations: self.workflow.state().iterations,
+            last_results,
+        })
+    }
+
+    /// Whether the baseline still has to be taken
+    ///
+    /// Only before WriteTests first runs; later attempts would include the
+    /// tests it wrote.
+    fn needs_baseline(&self) -> bool {
+        let state = self.workflow.state();
+        state.baseline.is_none() && !state.history.iter().any(|t| t.from == TddPhase::WriteTests)
+    }
+
+    /// Decide how a verification phase went, stepping back on failure
+    ///
+    /// Without a per-test comparison against the baseline this falls back
+    /// to the aggregate pass/fail counts.
+    fn verify(&mut self, phase: TddPhase, results: &TestResults) -> Verdict {
+        let state = self.workflow.state();
+        let diff = compare_to_baseline(results, state.baseline.as_deref(), &state.new_tests);
+        match phase {
+            TddPhase::VerifyRed => {
+                let problem = match diff {
+                    Some(ref diff) => diff.red_problem(),
+                    None if results.is_red() => None,
+                    None if results.passed > 0 && results.failed == 0 => Some(
+                        "Tests passed unexpectedly - they may not be testing new behavior"
+                            .to_string(),
+                    ),
+                    None => Some(results.summary()),
+                };
+                let Some(reason) = problem else {
+                    if let Some(diff) = diff {
+                        self.workflow.state_mut().new_tests = diff.new_failing;
+                    }
+                    return Verdict::Advance;
+                };
+                self.red_retries += 1;
+                if self.red_retries >= self.workflow.state().max_iterations {
+                    return Verdict::GiveUp;
+                }
+                self.workflow.retry_tests(Some(reason.clone()));
+                Verdict::Retry(reason)
+            }
+            TddPhase::VerifyGreen => {
+                let problem = match diff {
+                    Some(ref diff) => diff.green_problem(),
+                    None if results.is_green() => None,
+                    None if results.execution_error.is_some() => Some(results.summary()),
+                    None => Some(format!("{} tests still failing", results.failed)),
+                };
+                let Some(reason) = problem else {
+                    return Verdict::Advance;
+                };
+                if self.workflow.should_give_up() {
+                    return Verdict::GiveUp;
+                }
+                self.workflow.retry_implement(Some(reason.clone()));
+                Verdict::Retry(reason)
+            }
+            _ => Verdict::Advance,
+        }
+    }
+
+    /// Run the quality checks, returning a summary and the problems if any fail
+    async fn check_quality<O: TddObserver>(
+        &self,
+        phase: TddPhase,
+        observer: &mut O,
+    ) -> Result<Option<(String, String)>> {
+        if self.quality_gates.is_empty() {
+            return Ok(None);
+        }
+        let mut failed = 0;
+        let problems = self
+            .quality_gates
+            .run(&mut |check, problem| {
+                if problem.is_some() {
+                    failed += 1;
+                }
+                observer.on_quality_check(phase, check, problem);
+            })
+            .await?;
+        Ok(problems.map(|problems| (format!("{} quality check(s) failed", failed), problems)))
+    }
+
+    /// Wait for approval if the phase the workflow advanced to is gated
+    ///
+    /// A rejection sends the workflow back to redo the work of `from`, or of
+    /// the writing phase before it when `from` only ran the tests.
+    async fn await_approval<O: TddObserver>(
+        &mut self,
+        from: TddPhase,
+        observer: &mut O,
+    ) -> Result<()> {
+        let to = self.workflow.phase();
+        if !self.approval_gates.is_gated(to.as_str()) {
+            return Ok(());
+        }
+        self.workflow.state_mut().awaiting_approval = true;
+        self.save_state();
+
+        let state = self.workflow.state();
+        let mut approval = Approval::new(
+            "tdd",
+            &state.behavior,
+            state.workdir.to_string_lossy(),
+            from.as_str(),
+            to.as_str(),
+        );
+        if let Some(issue_number) = self.issue_number {
+            approval = approval.with_issue_number(issue_number);
+        }
+        let decision = self
+            .approval_gates
+            .wait(self.db.as_ref(), approval, |id| {
+                observer.on_approval_pending(from, to, id)
+            })
+            .await?;
+        observer.on_approval(to, &decision);
+        self.workflow.state_mut().awaiting_approval = false;
+
+        match decision {
+            ApprovalDecision::Approved => self.save_state(),
+            ApprovalDecision::Rejected(reason) => {
+                let redo = match from {
+                    TddPhase::VerifyRed => TddPhase::WriteTests,
+                    TddPhase::VerifyGreen => TddPhase::Implement,
+                    phase => phase,
+                };
+                self.workflow.send_back(redo, reason.clone());
+                self.save_transition(to, false, Some(reason), None);
+            }
+        }
+        Ok(())
+    }
+
+    /// Remember the files a writing phase produced
+    fn record_files(&mut self, phase: TddPhase, before: &HashMap<String, u64>) {
+        let changed: Vec<String> = dirty_files(&self.workflow.state().workdir)
+            .into_iter()
+            .filter(|(path, hash)| before.get(path) != Some(hash))
+            .map(|(path, _)| path)
+            .collect();
+        let state = self.workflow.state_mut();
+
+        match phase {
+            TddPhase::WriteSpec => {
+                if let Some(spec) = changed.iter().find(|p| p.ends_with(".md")) {
+                    state.spec_file = Some(spec.clone());
+                }
+            }
+            TddPhase::WriteTests => {
+                for path in changed {
+                    if !state.test_files.contains(&path) {
+                        state.test_files.push(path);
+                    }
+                }
+            }
+            TddPhase::Implement => {
+                for path in changed {
+                    if !state.test_files.contains(&path) && !state.impl_files.contains(&path) {
+                        state.impl_files.push(path);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Create the saved session if a database is attached and none exists yet
+    fn start_session(&mut self) {
+        let Some(db) = self.db.as_ref() else {
+            return;
+        };
+        if self.session_id().is_some() {
+            return;
+        }
+
+        let state = self.workflow.state();
+        let mut session = TddSession::new(
+            &state.behavior,
+            state.workdir.to_string_lossy(),
+            state.phase.as_str(),
+            serde_json::to_string(state).unwrap_or_else(|_| "{}".to_string()),
+        );
+        if let Some(issue_number) = self.issue_number {
+            session = session.with_issue_number(issue_number);
+        }
+        if let Some(worktree_id) = self.worktree_id {
+            session = session.with_worktree_id(worktree_id);
+        }
+
+        match TddSessionRepository::new(db).insert(&session) {
+            Ok(id) => {
+                session.id = Some(id);
+                self.session = Some(session);
+            }
+            Err(e) => tracing::warn!(error = %e, "Failed to save TDD session"),
+        }
+    }
+
+    /// Record a transition into the current phase and save the new state
+    fn save_transition(
+        &mut self,
+        from: TddPhase,
+        success: bool,
+        message: Option<String>,
+        agent_run_id: Option<i64>,
+    ) {
+        let (Some(db), Some(session_id)) = (self.db.as_ref(), self.session_id()) else {
+            return;
+        };
+
+        let mut transition = TddTransitionRecord::new(
+            session_id,
+            from.as_str(),
+            self.workflow.phase().as_str(),
+            success,
+        );
+        if let Some(message) = message {
+            transition = transition.with_message(message);
+        }
+        if let Some(run_id) = agent_run_id {
+            transition = transition.with_agent_run_id(run_id);
+        }
+        if let Err(e) = TddSessionRepository::new(db).insert_transition(&transition) {
+            tracing::warn!(error = %e, "Failed to record TDD transition");
+        }
+
+        self.save_state();
+    }
+
+    /// Save the workflow's current phase and state in the session
+    fn save_state(&mut self) {
+        let (Some(db), Some(session)) = (self.db.as_ref(), self.session.as_mut()) else {
+            return;
+        };
+        let state = self.workflow.state();
+        session.set_state(
+            state.phase.as_str(),
+            serde_json::to_string(state).unwrap_or_else(|_| "{}".to_string()),
+        );
+        if let Err(e) = TddSessionRepository::new(db).update(session) {
+            tracing::warn!(error = %e, "Failed to save TDD session");
+        }
+    }
+
+    /// Mark the saved session as completed or failed
+    fn finish_session(&mut self, completed: bool) {
+        let (Some(db), Some(session)) = (self.db.as_ref(), self.session.as_mut()) else {
+            return;
+        };
+        if completed {
+            session.mark_completed();
+        } else {
+            session.mark_failed();
+        }
+        if let Err(e) = TddSessionRepository::new(db).update(session) {
+            tracing::warn!(error = %e, "Failed to save TDD session");
+        }
+    }
+
+    /// Run one agent to completion, returning its run ID if recorded
+    async fn run_agent<H: StreamHandler>(
+        &self,
+        agent_type: AgentType,
+        prompt: &str,
+        handler: &mut H,
+    ) -> Result<Option<i64>> {
+        let spawner = match agent_type {
+            AgentType::Test => &self.test_spawner,
+            _ => &self.implement_spawner,
+        };
+        let runner = AgentRunner {
+            config: &self.config,
+            accounting: &self.accounting,
+            db: self.db.as_ref(),
+            issue_number: self.issue_number,
+        };
+        let (_, run_id) = runner
+            .run(
+                spawner,
+                agent_type,
+                prompt,
+                &self.workflow.state().workdir,
+                handler,
+            )
+            .await?;
+        Ok(run_id)
+    }
+}
+
+/// Test runner configured for `workdir`, falling back to cargo
+fn default_runner(workdir: &Path, config: &TestConfig) -> TestRunner {
+    let runner = TestRunner::from_config(workdir, config);
+    if runner.suites().is_empty() {
+        runner.with_framework(TestFramework::Cargo)
+    } else {
+        runner
+    }
+}
+
+/// Uncommitted files in `workdir` with a hash of their contents
+///
+/// Comparing snapshots taken around an agent run shows which files it
+/// touched, even ones that were already dirty. Empty outside a git repository.
+fn dirty_files(workdir: &Path) -> HashMap<String, u64> {
+    let Ok(output) = Command::new("git")
+        .args(["status", "--porcelain", "--untracked-files=all"])
+        .current_dir(workdir)
+        .output()
+    else {
+        return HashMap::new();
+    };
+    if !output.status.success() {
+        return HashMap::new();
+    }
+
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| line.get(3..))
+        .map(|path| {
+            // Renames are reported as "old -> new"
+            let path = path.rsplit(" -> ").next().unwrap_or(path).to_string();
+            let mut hasher = std::collections::hash_map::DefaultHasher::new();
+            std::fs::read(workdir.join(&path))
+                .unwrap_or_default()
+                .hash(&mut hasher);
+            (path, hasher.finish())
+        })
+        .collect()
+}
+
+/// The agent that does the work of a writing phase
+pub fn phase_agent_type(phase: TddPhase) -> AgentType {
+    match phase {
+        TddPhase::WriteTests => AgentType::Test,
+        _ => AgentType::Implement,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::fixtures::{mock_config, spawner};
+    use crate::agent::MockScript;
+    use crate::workflow::approval::Decisions;
+    use murmur_db::repos::{AgentRunRepository, ApprovalRepository};
+    use std::sync::Mutex;
+
+    struct Quiet;
+
+    impl StreamHandler for Quiet {
+        fn on_assistant_text(&mut self, _text: &str) {}
+    }
+
+    /// Records the phases started, prompts sent and retries taken
+    #[derive(Default)]
+    struct Recorder {
+        phases: Vec<TddPhase>,
+        prompts: Vec<String>,
+        retries: Vec<(TddPhase, TddPhase)>,
+        approvals: Vec<(TddPhase, ApprovalDecision)>,
+    }
+
+    impl TddObserver for Recorder {
+        fn on_phase_start(&mut self, phase: TddPhase, _state: &TddState) {
+            self.phases.push(phase);
+        }
+
+        fn on_agent_start(&mut self, _phase: TddPhase, _agent_type: AgentType, prompt: &str) {
+            self.prompts.push(prompt.to_string());
+        }
+
+        fn on_retry(&mut self, from: TddPhase, to: TddPhase, _reason: &str) {
+            self.retries.push((from, to));
+        }
+
+        fn on_approval(&mut self, to: TddPhase, decision: &ApprovalDecision) {
+            self.approvals.push((to, decision.clone()));
+        }
+    }
+
+    fn results(passed: u32, failed: u32) -> TestResults {
+        TestResults {
+            passed,
+            failed,
+            ..TestResults::empty()
+        }
+    }
+
+    #[test]
+    fn test_verify_red() {
+        let mut executor = TddExecutor::new("b", "/tmp", AgentConfig::default()).with_skip_spec();
+        executor.workflow.advance(true, None);
+        assert_eq!(executor.workflow.phase(), TddPhase::VerifyRed);
+
+        let verdict = executor.verify(TddPhase::VerifyRed, &results(3, 0));
+        assert!(matches!(verdict, Verdict::Retry(ref r) if r.contains("passed unexpectedly")));
+        assert_eq!(executor.workflow.phase(), TddPhase::WriteTests);
+
+        executor.workflow.advance(true, None);
+        assert_eq!(
+            executor.verify(TddPhase::VerifyRed, &results(2, 1)),
+            Verdict::Advance
+        );
+    }
+
+    #[test]
+    fn test_verify_red_gives_up() {
+        let mut executor = TddExecutor::new("b", "/tmp", AgentConfig::default())
+            .with_skip_spec()
+            .with_max_iterations(2);
+        executor.workflow.advance(true, None);
+
+        let no_tests = TestResults::empty();
+        assert!(matches!(
+            executor.verify(TddPhase::VerifyRed, &no_tests),
+            Verdict::Retry(_)
+        ));
+        executor.workflow.advance(true, None);
+        assert_eq!(
+            executor.verify(TddPhase::VerifyRed, &no_tests),
+            Verdict::GiveUp
+        );
+    }
+
+    #[test]
+    fn test_verify_per_test_against_baseline() {
+        use crate::workflow::test_runner::{TestCase, TestOutcome};
+
+        let mut executor = TddExecutor::new("b", "/tmp", AgentConfig::default()).with_skip_spec();
+        executor.workflow.state_mut().baseline = Some(vec![
+            TestCase::new("old", TestOutcome::Passed),
+            TestCase::new("broken", TestOutcome::Failed),
+        ]);
+        let run = |tests: &[(&str, TestOutcome)]| TestResults {
+            tests: tests
+                .iter()
+                .map(|(name, outcome)| TestCase::new(*name, *outcome))
+                .collect(),
+            ..TestResults::empty()
+        };
+
+        // The pre-existing failure alone doesn't count as red
+        executor.workflow.advance(true, None);
+        let verdict = executor.verify(
+            TddPhase::VerifyRed,
+            &run(&[
+                ("old", TestOutcome::Passed),
+                ("broken", TestOutcome::Failed),
+            ]),
+        );
+        assert!(matches!(verdict, Verdict::Retry(ref r) if r.contains("No new failing tests")));
+
+        executor.workflow.advance(true, None);
+        let red = run(&[
+            ("old", TestOutcome::Passed),
+            ("broken", TestOutcome::Failed),
+            ("new", TestOutcome::Failed),
+        ]);
+        assert_eq!(executor.verify(TddPhase::VerifyRed, &red), Verdict::Advance);
+        assert_eq!(executor.workflow.state().new_tests, ["new"]);
+
+        // The new test no longer running isn't green
+        executor.workflow.advance(true, None);
+        executor.workflow.advance(true, None);
+        let verdict = executor.verify(
+            TddPhase::VerifyGreen,
+            &run(&[
+                ("old", TestOutcome::Passed),
+                ("broken", TestOutcome::Failed),
+            ]),
+        );
+        assert!(matches!(verdict, Verdict::Retry(ref r) if r.contains("did not run: new")));
+
+        executor.workflow.advance(true, None);
+        let green = run(&[
+            ("old", TestOutcome::Passed),
+            ("broken", TestOutcome::Failed),
+            ("new", TestOutcome::Passed),
+        ]);
+        assert_eq!(
+            executor.verify(TddPhase::VerifyGreen, &green),
+            Verdict::Advance
+        );
+    }
+
+    #[test]
+    fn test_verify_green_retries_until_cap() {
+        let mut executor = TddExecutor::new("b", "/tmp", AgentConfig::default())
+            .with_skip_spec()
+            .with_max_iterations(2);
+        let state = executor.workflow.state_mut();
+        state.phase = TddPhase::Implement;
+
+        executor.workflow.advance(true, None);
+        assert_eq!(
+            executor.verify(TddPhase::VerifyGreen, &results(1, 2)),
+            Verdict::Retry("2 tests still failing".to_string())
+        );
+        assert_eq!(executor.workflow.phase(), TddPhase::Implement);
+
+        executor.workflow.advance(true, None);
+        assert_eq!(
+            executor.verify(TddPhase::VerifyGreen, &results(1, 2)),
+            Verdict::GiveUp
+        );
+        assert!(executor.workflow.should_give_up());
+    }
+
+    #[tokio::test]
+    async fn test_agent_failure_stops_run() {
+        let workdir = tempfile::tempdir().unwrap();
+        let scripts = tempfile::tempdir().unwrap();
+        let failing = MockScript::new()
+            .assistant_text("Giving up")
+            .with_exit_code(1);
+
+        let mut executor = TddExecutor::new("b", workdir.path(), mock_config())
+            .with_skip_spec()
+            .with_issue_number(3)
+            .with_database(Database::in_memory().unwrap())
+            .with_test_spawner(spawner(scripts.path(), "tests", failing));
+
+        let mut recorder = Recorder::default();
+        let err = executor.run(&mut recorder, &mut Quiet).await.unwrap_err();
+        assert!(err.to_string().contains("exited with status"));
+        assert_eq!(recorder.phases, [TddPhase::WriteTests]);
+        assert_eq!(executor.workflow.phase(), TddPhase::WriteTests);
+
+        let db = executor.db.as_ref().unwrap();
+        let runs = AgentRunRepository::new(db).find_by_issue(3).unwrap();
+        assert_eq!(runs.len(), 1);
+        assert_eq!(runs[0].agent_type, "tester");
+    }
+
+    fn git_ok(dir: &std::path::Path, args: &[&str]) {
+        let status = Command::new("git")
+            .args(["-c", "user.name=Test", "-c", "user.email=test@example.com"])
+            .args(args)
+            .current_dir(dir)
+            .output()
+            .unwrap()
+            .status;
+        assert!(status.success(), "git {:?} failed", args);
+    }
+
+    #[tokio::test]
+    async fn test_session_records_transitions_and_files() {
+        let workdir = tempfile::tempdir().unwrap();
+        git_ok(workdir.path(), &["init", "-q"]);
+        let scripts = tempfile::tempdir().unwrap();
+        let write_tests = MockScript::new()
+            .write_file("tests/parser_test.py", "def test_parse(): assert False\n")
+            .assistant_text("Wrote tests");
+
+        // No framework in the workdir, so VerifyRed can never go red
+        let mut executor = TddExecutor::new("Add parser", workdir.path(), mock_config())
+            .with_skip_spec()
+            .with_max_iterations(1)
+            .with_issue_number(5)
+            .with_database(Database::in_memory().unwrap())
+            .with_test_runner(TestRunner::new(workdir.path()))
+            .with_test_spawner(spawner(scripts.path(), "tests", write_tests));
+
+        let err = executor.run(&mut (), &mut Quiet).await.unwrap_err();
+        assert!(err.to_string().contains("still don't fail"));
+
+        let db = executor.db.as_ref().unwrap();
+        let repo = TddSessionRepository::new(db);
+        let session = repo
+            .find_by_id(executor.session_id().unwrap())
+            .unwrap()
+            .unwrap();
+        assert_eq!(session.status, "failed");
+        assert_eq!(session.phase, "verify_red");
+        assert_eq!(session.issue_number, Some(5));
+        let state: TddState = serde_json::from_str(&session.state_json).unwrap();
+        assert_eq!(state.test_files, ["tests/parser_test.py"]);
+
+        let transitions = repo.find_transitions(session.id.unwrap()).unwrap();
+        assert_eq!(transitions.len(), 1);
+        assert_eq!(transitions[0].from_phase, "write_tests");
+        assert_eq!(transitions[0].to_phase, "verify_red");
+        assert!(transitions[0].agent_run_id.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_rejected_spec_is_rewritten_with_reason() {
+        let workdir = tempfile::tempdir().unwrap();
+        let scripts = tempfile::tempdir().unwrap();
+        let failing = MockScript::new()
+            .assistant_text("Giving up")
+            .with_exit_code(1);
+        let gates = ApprovalGates::before(["write_tests"])
+            .with_poll_interval(std::time::Duration::from_millis(1))
+            .with_source(Decisions(Mutex::new(vec![
+                ApprovalDecision::Rejected("Cover the error cases".to_string()),
+                ApprovalDecision::Approved,
+            ])));
+
+        let mut executor = TddExecutor::new("Add parser", workdir.path(), mock_config())
+            .with_approval_gates(gates)
+            .with_database(Database::in_memory().unwrap())
+            .with_implement_spawner(spawner(
+                scripts.path(),
+                "spec",
+                MockScript::new().assistant_text("Wrote spec"),
+            ))
+            .with_test_spawner(spawner(scripts.path(), "tests", failing));
+
+        let mut recorder = Recorder::default();
+        assert!(executor.run(&mut recorder, &mut Quiet).await.is_err());
+        assert_eq!(
+            recorder.phases,
+            [
+                TddPhase::WriteSpec,
+                TddPhase::WriteSpec,
+                TddPhase::WriteTests
+            ]
+        );
+        assert!(!recorder.prompts[0].contains("Cover the error cases"));
+        assert!(recorder.prompts[1].contains("rejected the previous attempt"));
+        assert!(recorder.prompts[1].contains("Cover the error cases"));
+        assert!(!recorder.prompts[2].contains("Cover the error cases"));
+        assert_eq!(
+            recorder.approvals,
+            [
+                (
+                    TddPhase::WriteTests,
+                    ApprovalDecision::Rejected("Cover the error cases".to_string())
+                ),
+                (TddPhase::WriteTests, ApprovalDecision::Approved),
+            ]
+        );
+
+        let db = executor.db.as_ref().unwrap();
+        assert!(ApprovalRepository::new(db)
+            .find_pending()
+            .unwrap()
+            .is_empty());
+        let transitions = TddSessionRepository::new(db)
+            .find_transitions(executor.session_id().unwrap())
+            .unwrap();
+        assert_eq!(transitions[1].from_phase, "write_tests");
+        assert_eq!(transitions[1].to_phase, "write_spec");
+        assert!(!transitions[1].success);
+    }
+
+    #[tokio::test]
+    async fn test_quality_failures_go_back_to_implement() {
+        let workdir = tempfile::tempdir().unwrap();
+        let scripts = tempfile::tempdir().unwrap();
+        let quality = QualityConfig {
+            // Fails the first time only, as if the agent then fixed it
+            commands: vec![
+                "test -f .checked || { touch .checked; echo 'src/lib.rs: not formatted'; exit 1; }"
+                    .to_string(),
+            ],
+            ..Default::default()
+        };
+        let tests = TestConfig {
+            command: Some("true".to_string()),
+            ..Default::default()
+        };
+
+        let mut executor = TddExecutor::new("Add parser", workdir.path(), mock_config())
+            .with_skip_spec()
+            .with_skip_refactor()
+            .with_test_config(&tests)
+            .with_quality_config(&quality)
+            .with_implement_spawner(spawner(
+                scripts.path(),
+                "impl",
+                MockScript::new().assistant_text("Implemented"),
+            ));
+        assert_eq!(executor.quality_checks().len(), 1);
+        executor.workflow.state_mut().phase = TddPhase::Implement;
+
+        let mut recorder = Recorder::default();
+        let outcome = executor.run(&mut recorder, &mut Quiet).await.unwrap();
+        assert!(outcome.completed);
+        assert_eq!(
+            recorder.phases,
+            [
+                TddPhase::Implement,
+                TddPhase::VerifyGreen,
+                TddPhase::Implement,
+                TddPhase::VerifyGreen
+            ]
+        );
+        assert_eq!(
+            recorder.retries,
+            [(TddPhase::VerifyGreen, TddPhase::Implement)]
+        );
+        assert!(!recorder.prompts[0].contains("## Quality Gates"));
+        assert!(recorder.prompts[1].contains("## Quality Gates"));
+        assert!(recorder.prompts[1].contains("src/lib.rs: not formatted"));
+        assert!(executor.workflow.state().quality_problems.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_approval_gates_need_database() {
+        let workdir = tempfile::tempdir().unwrap();
+        let scripts = tempfile::tempdir().unwrap();
+        let mut executor = TddExecutor::new("Add parser", workdir.path(), mock_config())
+            .with_approval_gates(ApprovalGates::before(["write_tests"]))
+            .with_implement_spawner(spawner(
+                scripts.path(),
+                "spec",
+                MockScript::new().assistant_text("Wrote spec"),
+            ));
+
+        let err = executor.run(&mut (), &mut Quiet).await.unwrap_err();
+        assert!(err.to_string().contains("need a database"));
+    }
+
+    #[tokio::test]
+    async fn test_resume_continues_from_saved_phase() {
+        let workdir = tempfile::tempdir().unwrap();
+        let scripts = tempfile::tempdir().unwrap();
+        let db = Database::in_memory().unwrap();
+
+        let mut state = TddState::new_without_spec("Add parser", workdir.path())
+            .with_test_files(vec!["tests/parser_test.py".to_string()]);
+        state.phase = TddPhase::Implement;
+        let mut session = TddSession::new(
+            "Add parser",
+            workdir.path().to_string_lossy(),
+            "implement",
+            serde_json::to_string(&state).unwrap(),
+        );
+        session.mark_failed();
+        session.id = Some(TddSessionRepository::new(&db).insert(&session).unwrap());
+
+        let failing = MockScript::new()
+            .assistant_text("Crashed")
+            .with_exit_code(1);
+        let mut executor = TddExecutor::resume(session, mock_config())
+            .unwrap()
+            .with_database(db)
+            .with_implement_spawner(spawner(scripts.path(), "impl", failing));
+        let session_id = executor.session_id();
+
+        let mut recorder = Recorder::default();
+        assert!(executor.run(&mut recorder, &mut Quiet).await.is_err());
+        assert_eq!(recorder.phases, [TddPhase::Implement]);
+        assert!(recorder.prompts[0].contains("The tests are in: tests/parser_test.py"));
+        assert_eq!(executor.session_id(), session_id);
+    }
+}
@@ -0,0 +1,504 @@
+//! Test configuration and test suite discovery
+//!
+//! The `[test]` config section picks how the verification phases run tests:
+//! a built-in framework, a custom command, or one suite per subdirectory of
+//! a monorepo. A repository can carry its own `[test]` section in a
+//! `.murmur.toml` at its root. Without either, suites are detected from the
+//! project files in the repository and its subdirectories.
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use super::test_runner::{ReportFormat, TestFramework};
+
+/// Name of the repository-local config file
+pub const PROJECT_CONFIG_FILE: &str = ".murmur.toml";
+
+/// How many directory levels below the root are searched for suites
+const MAX_SUITE_DEPTH: usize = 2;
+
+/// Directories never searched for suites
+const SKIPPED_DIRS: &[&str] = &[
+    "node_modules",
+    "target",
+    "vendor",
+    "build",
+    "dist",
+    "out",
+    "bin",
+    "obj",
+    "venv",
+    "__pycache__",
+];
+
+/// Test running configuration (`[test]`)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct TestConfig {
+    /// Built-in framework to use instead of detecting one
+    pub framework: Option<TestFramework>,
+
+    /// Custom test command, run with `sh -c`
+    pub command: Option<String>,
+
+    /// How to read the custom command's results
+    pub format: CustomFormat,
+
+    /// Report file the custom command writes, relative to the repository
+    pub report: Option<String>,
+
+    /// Seconds a test run may take before it is killed
+    pub timeout_secs: Option<u64>,
+
+    /// Suites of a monorepo, each run from its own directory
+    pub suites: Vec<TestSuiteConfig>,
+}
+
+/// One suite of a monorepo (`[[test.suites]]`)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct TestSuiteConfig {
+    /// Directory of the suite, relative to the repository root
+    pub path: String,
+
+    /// Name shown in output, defaulting to the path
+    pub name: Option<String>,
+
+    /// Built-in framework, detected in `path` if neither it nor a command is set
+    pub framework: Option<TestFramework>,
+
+    /// Custom test command, run with `sh -c` in `path`
+    pub command: Option<String>,
+
+    /// How to read the custom command's results
+    pub format: CustomFormat,
+
+    /// Report file the custom command writes, relative to `path`
+    pub report: Option<String>,
+}
+
+/// How the results of a custom test command are read
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CustomFormat {
+    /// Only the exit status, so the run passes or fails as a whole
+    #[default]
+    ExitCode,
+    /// TAP on stdout
+    Tap,
+    /// libtest JSON events on stdout
+    LibtestJson,
+    /// `go test -json` events on stdout
+    GoJson,
+    /// JUnit XML in the report file
+    Junit,
+    /// Visual Studio TRX in the report file
+    Trx,
+}
+
+impl CustomFormat {
+    /// The structured report this format reads, if any
+    pub fn report_format(&self) -> Option<ReportFormat> {
+        match self {
+            Self::ExitCode | Self::Tap => None,
+            Self::LibtestJson => Some(ReportFormat::LibtestJson),
+            Self::GoJson => Some(ReportFormat::GoJson),
+            Self::Junit => Some(ReportFormat::JunitXml),
+            Self::Trx => Some(ReportFormat::Trx),
+        }
+    }
+}
+
+/// A `.murmur.toml` file; only its `[test]` section is read
+#[derive(Debug, Default, Deserialize)]
+struct ProjectFile {
+    test: Option<TestConfig>,
+}
+
+impl TestConfig {
+    /// The configuration that applies in `workdir`
+    ///
+    /// A `[test]` section in `workdir/.murmur.toml` replaces this one as a
+    /// whole. An unreadable file is ignored with a warning.
+    pub fn for_workdir(&self, workdir: &Path) -> TestConfig {
+        let path = workdir.join(PROJECT_CONFIG_FILE);
+        let Ok(content) = std::fs::read_to_string(&path) else {
+            return self.clone();
+        };
+        match toml::from_str::<ProjectFile>(&content) {
+            Ok(ProjectFile { test: Some(test) }) => test,
+            Ok(_) => self.clone(),
+            Err(e) => {
+                tracing::warn!(path = %path.display(), error = %e, "Ignoring invalid project config");
+                self.clone()
+            }
+        }
+    }
+
+    /// The test suites to run in `workdir`
+    ///
+    /// Configured suites come first, then a custom command or framework for
+    /// the whole repository, and detection when nothing is configured.
+    pub fn suites(&self, workdir: &Path) -> Vec<TestSuite> {
+        if !self.suites.is_empty() {
+            let mut suites = Vec::new();
+            for config in &self.suites {
+                let path = if config.path.is_empty() {
+                    "."
+                } else {
+                    config.path.as_str()
+                };
+                let kinds = match (&config.command, config.framework) {
+                    (Some(command), _) => vec![SuiteKind::Custom(CustomCommand {
+                        command: command.clone(),
+                        format: config.format,
+                        report: config.report.clone(),
+                    })],
+                    (None, Some(framework)) => vec![SuiteKind::Framework(framework)],
+                    (None, None) => TestFramework::detect_all(&workdir.join(path))
+                        .into_iter()
+                        .map(SuiteKind::Framework)
+                        .collect(),
+                };
+                for kind in kinds {
+                    let mut suite = TestSuite::new(path, kind);
+                    if let Some(ref name) = config.name {
+                        suite.name = name.clone();
+                    }
+                    suites.push(suite);
+                }
+            }
+            return unique_names(suites);
+        }
+
+        if let Some(ref command) = self.command {
+            return vec![TestSuite::new(
+                ".",
+                SuiteKind::Custom(CustomCommand {
+                    command: command.clone(),
+                    format: self.format,
+                    report: self.report.clone(),
+                }),
+            )];
+        }
+        if let Some(framework) = self.framework {
+            return vec![TestSuite::new(".", SuiteKind::Framework(framework))];
+        }
+        TestSuite::discover(workdir)
+    }
+
+    /// The configured timeout, if any
+    pub fn timeout(&self) -> Option<Duration> {
+        self.timeout_secs.map(Duration::from_secs)
+    }
+}
+
+/// Tests run from one directory with one framework or command
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TestSuite {
+    /// Name shown in output and prefixed to test names
+    pub name: String,
+    /// Directory relative to the working directory, `.` for the root
+    pub path: PathBuf,
+    /// How the tests are run
+    pub kind: SuiteKind,
+}
+
+/// How a suite's tests are run
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SuiteKind {
+    /// A built-in framework
+    Framework(TestFramework),
+    /// A configured command
+    Custom(CustomCommand),
+}
+
+/// A custom test command and how to read its results
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CustomCommand {
+    /// Shell command line
+    pub command: String,
+    /// How to read the results
+    pub format: CustomFormat,
+    /// Report file the command writes, relative to the suite
+    pub report: Option<String>,
+}
+
+impl CustomCommand {
+    /// The command to spawn
+    ///
+    /// Report files written to `$MURMUR_TEST_REPORT_DIR` are read too.
+    pub fn command(&self) -> Command {
+        let mut cmd = Command::new("sh");
+        cmd.arg("-c").arg(&self.command);
+        cmd
+    }
+}
+
+impl TestSuite {
+    /// Create a suite, named after its path or, at the root, its framework
+    pub fn new(path: impl Into<PathBuf>, kind: SuiteKind) -> Self {
+        let path = path.into();
+        let name = if path == Path::new(".") {
+            kind.label()
+        } else {
+            path.to_string_lossy().into_owned()
+        };
+        Self { name, path, kind }
+    }
+
+    /// Detect the suites of a repository
+    ///
+    /// Every framework detected at the root is a suite. Subdirectories up to
+    /// two levels down add suites for frameworks not already run from a
+    /// parent directory, so a Cargo workspace stays one suite while a Go
+    /// service in a JavaScript monorepo gets its own.
+    pub fn discover(workdir: &Path) -> Vec<TestSuite> {
+        let mut suites: Vec<TestSuite> = TestFramework::detect_all(workdir)
+            .into_iter()
+            .map(|f| TestSuite::new(".", SuiteKind::Framework(f)))
+            .collect();
+        discover_nested(workdir, Path::new(""), 1, &mut suites);
+        unique_names(suites)
+    }
+
+    /// The built-in framework, unless this is a custom command
+    pub fn framework(&self) -> Option<TestFramework> {
+        match self.kind {
+            SuiteKind::Framework(framework) => Some(framework),
+            SuiteKind::Custom(_) => None,
+        }
+    }
+
+    /// What runs the suite, for display
+    pub fn label(&self) -> String {
+        self.kind.label()
+    }
+
+    /// Whether a change to `file` should be tested by this suite
+    ///
+    /// The file must be inside the suite's directory. Source files must
+    /// also be in a language the framework tests; other files, such as
+    /// manifests, count for every suite around them.
+    pub fn covers(&self, file: &str) -> bool {
+        let file = Path::new(file);
+        if self.path != Path::new(".") && !file.starts_with(&self.path) {
+            return false;
+        }
+        let Some(framework) = self.framework() else {
+            return true;
+        };
+        let extensions = framework.source_extensions();
+        let Some(ext) = file.extension().and_then(|e| e.to_str()) else {
+            return true;
+        };
+        extensions.is_empty()
+            || extensions.contains(&ext)
+            || !TestFramework::ALL
+                .iter()
+                .any(|f| f.source_extensions().contains(&ext))
+    }
+
+    /// Whether the suite's result is only its exit status
+    pub(crate) fn exit_code_only(&self) -> bool {
+        matches!(self.kind, SuiteKind::Custom(ref c) if c.format == CustomFormat::ExitCode)
+    }
+}
+
+impl SuiteKind {
+    fn label(&self) -> String {
+        match self {
+            Self::Framework(framework) => framework.name().to_string(),
+            Self::Custom(custom) => custom.command.clone(),
+        }
+    }
+}
+
+/// Add suites found in the subdirectories of `workdir/rel`
+fn discover_nested(workdir: &Path, rel: &Path, depth: usize, suites: &mut Vec<TestSuite>) {
+    if depth > MAX_SUITE_DEPTH {
+        return;
+    }
+    let Ok(entries) = std::fs::read_dir(workdir.join(rel)) else {
+        return;
+    };
+    let mut dirs: Vec<String> = entries
+        .flatten()
+        .filter(|e| e.file_type().is_ok_and(|t| t.is_dir()))
+        .map(|e| e.file_name().to_string_lossy().into_owned())
+        .filter(|name| !name.starts_with('.') && !SKIPPED_DIRS.contains(&name.as_str()))
+        .collect();
+    dirs.sort();
+
+    for dir in dirs {
+        let path = rel.join(&dir);
+        // Only clear matches; a stray requirements.txt isn't a test suite
+        for framework in TestFramework::detect_in(&workdir.join(&path), false) {
+            let covered = suites.iter().any(|s| {
+                (s.path == Path::new(".") || path.starts_with(&s.path))
+                    && s.framework().is_some_and(|f| same_runner(f, framework))
+            });
+            if !covered {
+                suites.push(TestSuite::new(&path, SuiteKind::Framework(framework)));
+            }
+        }
+        discover_nested(workdir, &path, depth + 1, suites);
+    }
+}
+
+/// Whether running `a` from a parent directory also runs `b`'s tests
+fn same_runner(a: TestFramework, b: TestFramework) -> bool {
+    use TestFramework::{Cargo, Nextest};
+    matches!((a, b), (Cargo | Nextest, Cargo | Nextest)) || a == b
+}
+
+/// Tell apart suites that would share a name, adding what runs them
+fn unique_names(mut suites: Vec<TestSuite>) -> Vec<TestSuite> {
+    let names: Vec<String> = suites.iter().map(|s| s.name.clone()).collect();
+    for suite in &mut suites {
+        if names.iter().filter(|n| **n == suite.name).count() > 1 {
+            suite.name = format!("{} ({})", suite.name, suite.label());
+        }
+    }
+    suites
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_parse_test_config() {
+        let config: TestConfig = toml::from_str(
+            r#"
+            timeout_secs = 600
+
+            [[suites]]
+            path = "services/api"
+            framework = "go"
+
+            [[suites]]
+            path = "web"
+            name = "frontend"
+            command = "pnpm test -- --reporter=tap"
+            format = "tap"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.timeout(), Some(Duration::from_secs(600)));
+
+        let suites = config.suites(Path::new("/nonexistent"));
+        assert_eq!(suites.len(), 2);
+        assert_eq!(suites[0].name, "services/api");
+        assert_eq!(suites[0].framework(), Some(TestFramework::Go));
+        assert_eq!(suites[1].name, "frontend");
+        assert!(matches!(
+            suites[1].kind,
+            SuiteKind::Custom(ref c) if c.format == CustomFormat::Tap
+        ));
+    }
+
+    #[test]
+    fn test_custom_command_config() {
+        let config: TestConfig = toml::from_str(
+            "command = \"make check\"\nformat = \"junit\"\nreport = \"out/junit.xml\"",
+        )
+        .unwrap();
+        let suites = config.suites(Path::new("/nonexistent"));
+        assert_eq!(suites.len(), 1);
+        assert_eq!(suites[0].name, "make check");
+        assert_eq!(
+            suites[0].kind,
+            SuiteKind::Custom(CustomCommand {
+                command: "make check".to_string(),
+                format: CustomFormat::Junit,
+                report: Some("out/junit.xml".to_string()),
+            })
+        );
+
+        let config: TestConfig = toml::from_str("framework = \"nextest\"").unwrap();
+        assert_eq!(
+            config.suites(Path::new("/nonexistent"))[0].framework(),
+            Some(TestFramework::Nextest)
+        );
+        assert!(toml::from_str::<TestConfig>("framework = \"ant\"").is_err());
+    }
+
+    #[test]
+    fn test_project_config_replaces_user_config() {
+        let dir = TempDir::new().unwrap();
+        let user = TestConfig {
+            framework: Some(TestFramework::Cargo),
+            ..Default::default()
+        };
+        assert_eq!(user.for_workdir(dir.path()), user);
+
+        fs::write(
+            dir.path().join(PROJECT_CONFIG_FILE),
+            "[test]\nframework = \"pytest\"\n",
+        )
+        .unwrap();
+        let config = user.for_workdir(dir.path());
+        assert_eq!(config.framework, Some(TestFramework::Pytest));
+
+        fs::write(dir.path().join(PROJECT_CONFIG_FILE), "[test\n").unwrap();
+        assert_eq!(user.for_workdir(dir.path()), user);
+    }
+
+    #[test]
+    fn test_discover_monorepo_suites() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path();
+        fs::write(root.join("Cargo.toml"), "[workspace]").unwrap();
+        fs::write(
+            root.join("package.json"),
+            r#"{"devDependencies":{"jest":"^29"}}"#,
+        )
+        .unwrap();
+        fs::create_dir_all(root.join("crates/core")).unwrap();
+        fs::write(root.join("crates/core/Cargo.toml"), "[package]").unwrap();
+        fs::create_dir_all(root.join("services/api")).unwrap();
+        fs::write(root.join("services/api/go.mod"), "module api").unwrap();
+        fs::create_dir_all(root.join("scripts")).unwrap();
+        fs::write(root.join("scripts/requirements.txt"), "requests").unwrap();
+        fs::create_dir_all(root.join("node_modules/dep")).unwrap();
+        fs::write(root.join("node_modules/dep/go.mod"), "module dep").unwrap();
+
+        let suites = TestSuite::discover(root);
+        let names: Vec<&str> = suites.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, ["cargo test", "jest", "services/api"]);
+        assert_eq!(suites[2].framework(), Some(TestFramework::Go));
+    }
+
+    #[test]
+    fn test_suite_covers_changed_files() {
+        let api = TestSuite::new("services/api", SuiteKind::Framework(TestFramework::Go));
+        assert!(api.covers("services/api/handler.go"));
+        assert!(api.covers("services/api/go.sum"));
+        assert!(!api.covers("services/web/app.go"));
+        assert!(!api.covers("services/api/scripts/gen.py"));
+
+        let root = TestSuite::new(".", SuiteKind::Framework(TestFramework::Cargo));
+        assert!(root.covers("src/lib.rs"));
+        assert!(root.covers("Cargo.toml"));
+        assert!(!root.covers("services/api/handler.go"));
+
+        let bazel = TestSuite::new(".", SuiteKind::Framework(TestFramework::Bazel));
+        assert!(bazel.covers("services/api/handler.go"));
+    }
+
+    #[test]
+    fn test_unique_suite_names() {
+        let suites = unique_names(vec![
+            TestSuite::new("web", SuiteKind::Framework(TestFramework::Jest)),
+            TestSuite::new("web", SuiteKind::Framework(TestFramework::Vitest)),
+        ]);
+        assert_eq!(suites[0].name, "web (jest)");
+        assert_eq!(suites[1].name, "web (vitest)");
+    }
+}
@@ -0,0 +1,654 @@
+//! Parsers for machine-readable test reports
+//!
+//! Structured output is preferred over scraping summary lines: libtest JSON
+//! from nightly `cargo test` or nextest, `go test -json` events, JUnit XML
+//! from pytest, Jest, Vitest, Mocha, Maven, Gradle, PHPUnit and CTest, TRX
+//! from `dotnet test`, RSpec's JSON formatter and TAP from custom commands.
+//! Each parser returns the individual tests; an empty list means the report
+//! had nothing usable and the caller falls back to the textual parsers.
+
+use std::collections::HashMap;
+
+use serde::Deserialize;
+
+use super::test_runner::{ReportFormat, TestCase, TestOutcome};
+
+/// How a line of a JSON report should be shown while the tests run
+///
+/// Returns the text the human-readable output would have had, or `None`
+/// for events with nothing to show. Lines that aren't JSON are shown as is.
+pub(crate) fn display_line(format: ReportFormat, line: &str) -> Option<String> {
+    match format {
+        ReportFormat::JunitXml | ReportFormat::Trx | ReportFormat::RspecJson => {
+            Some(line.to_string())
+        }
+        ReportFormat::LibtestJson => match serde_json::from_str::<LibtestEvent>(line.trim()) {
+            Ok(event) if event.kind == "test" && event.event != "started" => {
+                Some(format!("test {} ... {}", event.name, event.event))
+            }
+            Ok(_) => None,
+            Err(_) => Some(line.to_string()),
+        },
+        ReportFormat::GoJson => match serde_json::from_str::<GoEvent>(line.trim()) {
+            Ok(event) => event.output.map(|o| o.trim_end().to_string()),
+            Err(_) => Some(line.to_string()),
+        },
+    }
+}
+
+/// One line of libtest JSON output
+#[derive(Debug, Deserialize)]
+struct LibtestEvent {
+    #[serde(rename = "type")]
+    kind: String,
+    event: String,
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    exec_time: Option<f64>,
+    #[serde(default)]
+    stdout: Option<String>,
+}
+
+/// Parse libtest JSON events, as printed by `cargo test -- --format json`
+///
+/// Like the text parser, repeated names from different test binaries get a
+/// " (2)", " (3)" ... suffix in order of appearance.
+pub(crate) fn parse_libtest_json(stdout: &str) -> Vec<TestCase> {
+    let mut tests = Vec::new();
+    let mut seen: HashMap<String, u32> = HashMap::new();
+
+    for line in stdout.lines() {
+        let Ok(event) = serde_json::from_str::<LibtestEvent>(line.trim()) else {
+            continue;
+        };
+        if event.kind != "test" {
+            continue;
+        }
+        let outcome = match event.event.as_str() {
+            "ok" => TestOutcome::Passed,
+            "failed" => TestOutcome::Failed,
+            "ignored" => TestOutcome::Skipped,
+            _ => continue,
+        };
+
+        let count = seen.entry(event.name.clone()).or_insert(0);
+        *count += 1;
+        let name = if *count > 1 {
+            format!("{} ({})", event.name, count)
+        } else {
+            event.name
+        };
+
+        let mut case = TestCase::new(name, outcome);
+        if let Some(secs) = event.exec_time {
+            case = case.with_duration_ms(secs_to_ms(secs));
+        }
+        if outcome == TestOutcome::Failed {
+            if let Some(output) = event.stdout {
+                let (location, message) = parse_panic(&output);
+                case.location = location;
+                case.message = message.or_else(|| first_line(&output));
+            }
+        }
+        tests.push(case);
+    }
+
+    tests
+}
+
+/// One line of `go test -json` output
+#[derive(Debug, Deserialize)]
+struct GoEvent {
+    #[serde(rename = "Action")]
+    action: String,
+    #[serde(rename = "Package", default)]
+    package: Option<String>,
+    #[serde(rename = "Test", default)]
+    test: Option<String>,
+    #[serde(rename = "Elapsed", default)]
+    elapsed: Option<f64>,
+    #[serde(rename = "Output", default)]
+    output: Option<String>,
+}
+
+/// Parse `go test -json` events
+///
+/// Tests are named `package/TestName`, since `go test ./...` runs packages
+/// that may share test names. The failure message and location come from
+/// the first `file_test.go:12:` line the failing test logged.
+pub(crate) fn parse_go_json(stdout: &str) -> Vec<TestCase> {
+    let mut tests: Vec<TestCase> = Vec::new();
+    let mut logs: HashMap<String, Vec<String>> = HashMap::new();
+
+    for line in stdout.lines() {
+        let Ok(event) = serde_json::from_str::<GoEvent>(line.trim()) else {
+            continue;
+        };
+        // Package-level events have no test name
+        let Some(test) = event.test else {
+            continue;
+        };
+        let name = match event.package {
+            Some(package) if !package.is_empty() => format!("{}/{}", package, test),
+            _ => test,
+        };
+        let outcome = match event.action.as_str() {
+            "output" => {
+                if let Some(output) = event.output {
+                    logs.entry(name).or_default().push(output);
+                }
+                continue;
+            }
+            "pass" => TestOutcome::Passed,
+            "fail" => TestOutcome::Failed,
+            "skip" => TestOutcome::Skipped,
+            _ => continue,
+        };
+
+        let mut case = TestCase::new(name.as_str(), outcome);
+        if let Some(secs) = event.elapsed {
+            case = case.with_duration_ms(secs_to_ms(secs));
+        }
+        if outcome == TestOutcome::Failed {
+            let logged = logs.get(&name).map(Vec::as_slice).unwrap_or_default();
+            if let Some((location, message)) = logged.iter().find_map(|l| go_log_location(l)) {
+                case = case.with_location(location).with_message(message);
+            }
+        }
+        match tests.iter_mut().find(|t| t.name == case.name) {
+            Some(existing) => *existing = case,
+            None => tests.push(case),
+        }
+    }
+
+    tests
+}
+
+/// Split a `    parser_test.go:12: want 3, got 2` log line
+fn go_log_location(line: &str) -> Option<(String, String)> {
+    let trimmed = line.trim();
+    let (file, rest) = trimmed.split_once(".go:")?;
+    let (line_no, message) = rest.split_once(':')?;
+    if file.contains(char::is_whitespace) || line_no.parse::<u32>().is_err() {
+        return None;
+    }
+    Some((
+        format!("{}.go:{}", file, line_no),
+        message.trim().to_string(),
+    ))
+}
+
+/// Parse a JUnit XML report
+///
+/// Tests are named `classname::name` when the report gives a class name
+/// that the test name doesn't already start with. Returns `None` if the
+/// report isn't well-formed XML.
+pub(crate) fn parse_junit_xml(xml: &str) -> Option<Vec<TestCase>> {
+    let doc = roxmltree::Document::parse(xml).ok()?;
+    let mut tests = Vec::new();
+
+    for node in doc.descendants().filter(|n| n.has_tag_name("testcase")) {
+        let Some(name) = node.attribute("name") else {
+            continue;
+        };
+        let name = match node.attribute("classname") {
+            Some(class) if !class.is_empty() && !name.starts_with(class) => {
+                format!("{}::{}", class, name)
+            }
+            _ => name.to_string(),
+        };
+
+        let failure = node
+            .children()
+            .find(|c| c.has_tag_name("failure") || c.has_tag_name("error"));
+        let skipped = node.children().any(|c| c.has_tag_name("skipped"));
+        let outcome = if failure.is_some() {
+            TestOutcome::Failed
+        } else if skipped {
+            TestOutcome::Skipped
+        } else {
+            TestOutcome::Passed
+        };
+
+        let mut case = TestCase::new(name, outcome);
+        if let Some(secs) = node.attribute("time").and_then(|t| t.parse::<f64>().ok()) {
+            case = case.with_duration_ms(secs_to_ms(secs));
+        }
+        if let Some(file) = node.attribute("file") {
+            case = case.with_location(match node.attribute("line") {
+                Some(line) => format!("{}:{}", file, line),
+                None => file.to_string(),
+            });
+        }
+        if let Some(failure) = failure {
+            let message = failure
+                .attribute("message")
+                .map(str::to_string)
+                .or_else(|| failure.text().and_then(first_line));
+            if let Some(message) = message {
+                case = case.with_message(message);
+            }
+        }
+        tests.push(case);
+    }
+
+    Some(tests)
+}
+
+/// Parse a report file written in `format`
+///
+/// Returns `None` for formats printed on stdout rather than written to a
+/// file, and for reports that can't be read.
+pub(crate) fn parse_report_file(format: ReportFormat, content: &str) -> Option<Vec<TestCase>> {
+    match format {
+        ReportFormat::JunitXml => parse_junit_xml(content),
+        ReportFormat::Trx => parse_trx(content),
+        ReportFormat::RspecJson => parse_rspec_json(content),
+        ReportFormat::LibtestJson | ReportFormat::GoJson => None,
+    }
+}
+
+/// Parse a Visual Studio TRX report, as written by `dotnet test --logger trx`
+pub(crate) fn parse_trx(xml: &str) -> Option<Vec<TestCase>> {
+    let doc = roxmltree::Document::parse(xml).ok()?;
+    let mut tests = Vec::new();
+
+    for node in doc
+        .descendants()
+        .filter(|n| n.has_tag_name("UnitTestResult"))
+    {
+        let Some(name) = node.attribute("testName") else {
+            continue;
+        };
+        let outcome = match node.attribute("outcome") {
+            Some("Passed") => TestOutcome::Passed,
+            Some("NotExecuted" | "Inconclusive") => TestOutcome::Skipped,
+            _ => TestOutcome::Failed,
+        };
+
+        let mut case = TestCase::new(name, outcome);
+        if let Some(ms) = node.attribute("duration").and_then(parse_timespan_ms) {
+            case = case.with_duration_ms(ms);
+        }
+        let message = node
+            .descendants()
+            .find(|n| n.has_tag_name("Message"))
+            .and_then(|n| n.text())
+            .and_then(first_line);
+        if let (TestOutcome::Failed, Some(message)) = (outcome, message) {
+            case = case.with_message(message);
+        }
+        tests.push(case);
+    }
+
+    Some(tests)
+}
+
+/// Milliseconds in a TRX `hh:mm:ss.fffffff` duration
+fn parse_timespan_ms(value: &str) -> Option<u64> {
+    let mut parts = value.split(':');
+    let hours: f64 = parts.next()?.parse().ok()?;
+    let minutes: f64 = parts.next()?.parse().ok()?;
+    let seconds: f64 = parts.next()?.parse().ok()?;
+    Some(secs_to_ms(hours * 3600.0 + minutes * 60.0 + seconds))
+}
+
+/// RSpec's JSON formatter output
+#[derive(Debug, Deserialize)]
+struct RspecReport {
+    #[serde(default)]
+    examples: Vec<RspecExample>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RspecExample {
+    full_description: String,
+    status: String,
+    #[serde(default)]
+    file_path: Option<String>,
+    #[serde(default)]
+    line_number: Option<u32>,
+    #[serde(default)]
+    run_time: Option<f64>,
+    #[serde(default)]
+    exception: Option<RspecException>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RspecException {
+    #[serde(default)]
+    message: String,
+}
+
+/// Parse the report of `rspec --format json`
+pub(crate) fn parse_rspec_json(json: &str) -> Option<Vec<TestCase>> {
+    let report: RspecReport = serde_json::from_str(json).ok()?;
+    let tests = report
+        .examples
+        .into_iter()
+        .map(|example| {
+            let outcome = match example.status.as_str() {
+                "passed" => TestOutcome::Passed,
+                "pending" => TestOutcome::Skipped,
+                _ => TestOutcome::Failed,
+            };
+            let mut case = TestCase::new(example.full_description, outcome);
+            if let Some(secs) = example.run_time {
+                case = case.with_duration_ms(secs_to_ms(secs));
+            }
+            if let Some(file) = example.file_path {
+                let file = file.strip_prefix("./").unwrap_or(&file).to_string();
+                case = case.with_location(match example.line_number {
+                    Some(line) => format!("{}:{}", file, line),
+                    None => file,
+                });
+            }
+            if let Some(message) = example.exception.and_then(|e| first_line(&e.message)) {
+                case = case.with_message(message);
+            }
+            case
+        })
+        .collect();
+    Some(tests)
+}
+
+/// Parse TAP output: `ok 1 - name`, `not ok 2 - name # SKIP reason`
+///
+/// Tests marked `# SKIP` are skipped; `# TODO` tests are expected to fail,
+/// so they count as skipped whatever their result.
+pub(crate) fn parse_tap(stdout: &str) -> Vec<TestCase> {
+    let mut tests: Vec<TestCase> = Vec::new();
+
+    // Indented lines belong to subtests, which their parent line sums up
+    for line in stdout.lines() {
+        let (ok, rest) = if let Some(rest) = line.strip_prefix("not ok") {
+            (false, rest)
+        } else if let Some(rest) = line.strip_prefix("ok") {
+            (true, rest)
+        } else {
+            continue;
+        };
+        if !rest.is_empty() && !rest.starts_with(' ') {
+            continue;
+        }
+
+        let (description, directive) = match rest.split_once(" # ") {
+            Some((description, directive)) => (description, directive.trim().to_uppercase()),
+            None => (rest, String::new()),
+        };
+        let description = description
+            .trim()
+            .trim_start_matches(|c: char| c.is_ascii_digit())
+            .trim_start()
+            .trim_start_matches("- ")
+            .trim();
+        let number = rest
+            .split_whitespace()
+            .next()
+            .unwrap_or_default()
+            .to_string();
+        let name = if description.is_empty() {
+            format!("test {}", number)
+        } else {
+            description.to_string()
+        };
+
+        let outcome = if directive.starts_with("SKIP") || directive.starts_with("TODO") {
+            TestOutcome::Skipped
+        } else if ok {
+            TestOutcome::Passed
+        } else {
+            TestOutcome::Failed
+        };
+        tests.push(TestCase::new(name, outcome));
+    }
+
+    tests
+}
+
+/// Find the location and message of a Rust panic in captured output
+///
+/// Handles both `panicked at src/lib.rs:3:5:\nmessage` and the older
+/// `panicked at 'message', src/lib.rs:3:5`.
+pub(crate) fn parse_panic(output: &str) -> (Option<String>, Option<String>) {
+    let mut lines = output.lines();
+    while let Some(line) = lines.next() {
+        let Some((_, rest)) = line.split_once("panicked at ") else {
+            continue;
+        };
+        if let Some(quoted) = rest.strip_prefix('\'') {
+            if let Some((message, location)) = quoted.rsplit_once("', ") {
+                return (Some(location.trim().to_string()), Some(message.to_string()));
+            }
+        }
+        let location = rest.trim().trim_end_matches(':').to_string();
+        let message = lines.next().and_then(first_line);
+        return (Some(location), message);
+    }
+    (None, None)
+}
+
+/// The first non-empty line of some text
+fn first_line(text: &str) -> Option<String> {
+    text.lines()
+        .map(str::trim)
+        .find(|l| !l.is_empty())
+        .map(str::to_string)
+}
+
+fn secs_to_ms(secs: f64) -> u64 {
+    (secs * 1000.0).round() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_libtest_json() {
+        let stdout = r#"
+{ "type": "suite", "event": "started", "test_count": 3 }
+{ "type": "test", "event": "started", "name": "parser::tests::test_parse" }
+{ "type": "test", "name": "parser::tests::test_parse", "event": "ok", "exec_time": 0.002 }
+{ "type": "test", "name": "parser::tests::test_empty", "event": "failed", "exec_time": 0.01, "stdout": "\nthread 'parser::tests::test_empty' panicked at src/parser.rs:42:9:\nassertion `left == right` failed\n  left: 1\n right: 2\n" }
+{ "type": "test", "name": "parser::tests::test_slow", "event": "ignored" }
+{ "type": "suite", "event": "failed", "passed": 1, "failed": 1, "ignored": 1 }
+"#;
+        let tests = parse_libtest_json(stdout);
+        assert_eq!(tests.len(), 3);
+        assert_eq!(tests[0].outcome, TestOutcome::Passed);
+        assert_eq!(tests[0].duration_ms, Some(2));
+
+        let failed = &tests[1];
+        assert_eq!(failed.outcome, TestOutcome::Failed);
+        assert_eq!(failed.location.as_deref(), Some("src/parser.rs:42:9"));
+        assert_eq!(
+            failed.message.as_deref(),
+            Some("assertion `left == right` failed")
+        );
+        assert_eq!(tests[2].outcome, TestOutcome::Skipped);
+    }
+
+    #[test]
+    fn test_parse_go_json() {
+        let stdout = r#"
+{"Action":"run","Package":"example/calc","Test":"TestAdd"}
+{"Action":"pass","Package":"example/calc","Test":"TestAdd","Elapsed":0.01}
+{"Action":"run","Package":"example/calc","Test":"TestDiv"}
+{"Action":"output","Package":"example/calc","Test":"TestDiv","Output":"=== RUN   TestDiv\n"}
+{"Action":"output","Package":"example/calc","Test":"TestDiv","Output":"    calc_test.go:21: want 2, got 0\n"}
+{"Action":"fail","Package":"example/calc","Test":"TestDiv","Elapsed":0}
+{"Action":"skip","Package":"example/calc","Test":"TestNet","Elapsed":0}
+{"Action":"fail","Package":"example/calc","Elapsed":0.02}
+"#;
+        let tests = parse_go_json(stdout);
+        assert_eq!(tests.len(), 3);
+        assert_eq!(tests[0].name, "example/calc/TestAdd");
+        assert_eq!(tests[0].duration_ms, Some(10));
+
+        let failed = &tests[1];
+        assert_eq!(failed.outcome, TestOutcome::Failed);
+        assert_eq!(failed.location.as_deref(), Some("calc_test.go:21"));
+        assert_eq!(failed.message.as_deref(), Some("want 2, got 0"));
+        assert_eq!(tests[2].outcome, TestOutcome::Skipped);
+    }
+
+    #[test]
+    fn test_parse_go_json_keeps_packages_apart() {
+        let stdout = r#"
+{"Action":"run","Package":"example/a","Test":"TestParse"}
+{"Action":"run","Package":"example/b","Test":"TestParse"}
+{"Action":"output","Package":"example/a","Test":"TestParse","Output":"    a_test.go:9: bad input\n"}
+{"Action":"output","Package":"example/b","Test":"TestParse","Output":"    b_test.go:4: all good\n"}
+{"Action":"fail","Package":"example/a","Test":"TestParse","Elapsed":0}
+{"Action":"pass","Package":"example/b","Test":"TestParse","Elapsed":0}
+"#;
+        let tests = parse_go_json(stdout);
+        assert_eq!(tests.len(), 2);
+        assert_eq!(tests[0].name, "example/a/TestParse");
+        assert_eq!(tests[0].outcome, TestOutcome::Failed);
+        assert_eq!(tests[0].location.as_deref(), Some("a_test.go:9"));
+        assert_eq!(tests[0].message.as_deref(), Some("bad input"));
+        assert_eq!(tests[1].name, "example/b/TestParse");
+        assert_eq!(tests[1].outcome, TestOutcome::Passed);
+    }
+
+    #[test]
+    fn test_parse_junit_xml() {
+        let xml = r#"<?xml version="1.0" encoding="utf-8"?>
+<testsuites>
+  <testsuite name="pytest" tests="3" failures="1" skipped="1">
+    <testcase classname="tests.test_calc" name="test_add" time="0.003" file="tests/test_calc.py" line="4"/>
+    <testcase classname="tests.test_calc" name="test_div" time="0.010">
+      <failure message="assert 0 == 2">def test_div():
+&gt;       assert div(4, 2) == 2</failure>
+    </testcase>
+    <testcase classname="tests.test_calc" name="test_net" time="0">
+      <skipped message="offline"/>
+    </testcase>
+  </testsuite>
+</testsuites>"#;
+        let tests = parse_junit_xml(xml).unwrap();
+        assert_eq!(tests.len(), 3);
+        assert_eq!(tests[0].name, "tests.test_calc::test_add");
+        assert_eq!(tests[0].outcome, TestOutcome::Passed);
+        assert_eq!(tests[0].duration_ms, Some(3));
+        assert_eq!(tests[0].location.as_deref(), Some("tests/test_calc.py:4"));
+
+        assert_eq!(tests[1].outcome, TestOutcome::Failed);
+        assert_eq!(tests[1].message.as_deref(), Some("assert 0 == 2"));
+        assert_eq!(tests[2].outcome, TestOutcome::Skipped);
+
+        assert!(parse_junit_xml("<testsuite><testcase").is_none());
+    }
+
+    #[test]
+    fn test_parse_trx() {
+        let xml = r#"<?xml version="1.0" encoding="utf-8"?>
+<TestRun xmlns="http://microsoft.com/schemas/VisualStudio/TeamTest/2010">
+  <Results>
+    <UnitTestResult testName="Calc.Tests.Adds" outcome="Passed" duration="00:00:00.0120000" />
+    <UnitTestResult testName="Calc.Tests.Divides" outcome="Failed" duration="00:00:01.5">
+      <Output>
+        <ErrorInfo>
+          <Message>Assert.Equal() Failure
+Expected: 2</Message>
+        </ErrorInfo>
+      </Output>
+    </UnitTestResult>
+    <UnitTestResult testName="Calc.Tests.Network" outcome="NotExecuted" />
+  </Results>
+</TestRun>"#;
+        let tests = parse_report_file(ReportFormat::Trx, xml).unwrap();
+        assert_eq!(tests.len(), 3);
+        assert_eq!(tests[0].name, "Calc.Tests.Adds");
+        assert_eq!(tests[0].duration_ms, Some(12));
+        assert_eq!(tests[1].outcome, TestOutcome::Failed);
+        assert_eq!(tests[1].duration_ms, Some(1500));
+        assert_eq!(tests[1].message.as_deref(), Some("Assert.Equal() Failure"));
+        assert_eq!(tests[2].outcome, TestOutcome::Skipped);
+    }
+
+    #[test]
+    fn test_parse_rspec_json() {
+        let json = r#"{"version":"3.13.0","examples":[
+            {"full_description":"Calc adds","status":"passed","file_path":"./spec/calc_spec.rb","line_number":4,"run_time":0.002},
+            {"full_description":"Calc divides","status":"failed","file_path":"./spec/calc_spec.rb","line_number":8,
+             "exception":{"class":"RSpec::Expectations::ExpectationNotMetError","message":"\nexpected: 2\n     got: 0"}},
+            {"full_description":"Calc fetches rates","status":"pending"}
+        ],"summary":{"example_count":3}}"#;
+        let tests = parse_report_file(ReportFormat::RspecJson, json).unwrap();
+        assert_eq!(tests.len(), 3);
+        assert_eq!(tests[0].location.as_deref(), Some("spec/calc_spec.rb:4"));
+        assert_eq!(tests[0].duration_ms, Some(2));
+        assert_eq!(tests[1].outcome, TestOutcome::Failed);
+        assert_eq!(tests[1].message.as_deref(), Some("expected: 2"));
+        assert_eq!(tests[2].outcome, TestOutcome::Skipped);
+        assert!(parse_rspec_json("Randomized with seed 1234").is_none());
+    }
+
+    #[test]
+    fn test_parse_tap() {
+        let stdout = "TAP version 13
+1..5
+ok 1 - adds numbers
+not ok 2 - divides numbers
+  ---
+  message: expected 2
+  ...
+ok 3 - fetches rates # SKIP offline
+not ok 4 rounds # TODO not done
+ok 5
+";
+        let tests = parse_tap(stdout);
+        assert_eq!(tests.len(), 5);
+        assert_eq!(tests[0].name, "adds numbers");
+        assert_eq!(tests[0].outcome, TestOutcome::Passed);
+        assert_eq!(tests[1].name, "divides numbers");
+        assert_eq!(tests[1].outcome, TestOutcome::Failed);
+        assert_eq!(tests[2].outcome, TestOutcome::Skipped);
+        assert_eq!(tests[3].name, "rounds");
+        assert_eq!(tests[3].outcome, TestOutcome::Skipped);
+        assert_eq!(tests[4].name, "test 5");
+    }
+
+    #[test]
+    fn test_display_line() {
+        assert_eq!(
+            display_line(
+                ReportFormat::LibtestJson,
+                r#"{ "type": "test", "name": "a::b", "event": "ok" }"#
+            )
+            .as_deref(),
+            Some("test a::b ... ok")
+        );
+        assert!(display_line(
+            ReportFormat::LibtestJson,
+            r#"{ "type": "test", "event": "started", "name": "a::b" }"#
+        )
+        .is_none());
+        assert_eq!(
+            display_line(
+                ReportFormat::GoJson,
+                r#"{"Action":"output","Test":"TestA","Output":"--- PASS: TestA (0.00s)\n"}"#
+            )
+            .as_deref(),
+            Some("--- PASS: TestA (0.00s)")
+        );
+        assert_eq!(
+            display_line(ReportFormat::GoJson, "build failed").as_deref(),
+            Some("build failed")
+        );
+    }
+
+    #[test]
+    fn test_parse_panic_formats() {
+        let (location, message) =
+            parse_panic("thread 'a' panicked at 'boom', src/lib.rs:3:5\nnote: run with backtrace");
+        assert_eq!(location.as_deref(), Some("src/lib.rs:3:5"));
+        assert_eq!(message.as_deref(), Some("boom"));
+
+        assert_eq!(parse_panic("all good"), (None, None));
+    }
+}
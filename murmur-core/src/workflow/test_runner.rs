@@ -1,20 +1,44 @@
 //! Test runner integration for TDD workflow
 //!
 //! This module provides framework detection and test execution capabilities
-//! to validate VerifyRed and VerifyGreen phases.
+//! to validate VerifyRed and VerifyGreen phases. Besides aggregate counts,
+//! each run records the individual tests and their outcomes so a run can be
+//! compared against a baseline with [`TestDiff`].
+//!
+//! Where the framework can produce a machine-readable report (see
+//! [`ReportFormat`]) it is used for the individual tests, with the parsers
+//! for human-readable output kept as a fallback.
+//!
+//! A runner can have several [`TestSuite`]s, each run from its own
+//! directory, and only run the ones covering the files that changed.
 
-use std::path::Path;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
-use std::time::{Duration, Instant};
+use std::time::{Duration, Instant, SystemTime};
+
+use serde::{Deserialize, Serialize};
+
+use super::test_config::{CustomFormat, SuiteKind, TestConfig, TestSuite};
+use super::test_reports::{
+    display_line, parse_go_json, parse_libtest_json, parse_panic, parse_report_file, parse_tap,
+};
+use crate::agent::own_process_group;
+#[cfg(unix)]
+use crate::agent::signal_process_group;
 
 /// Supported test frameworks
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum TestFramework {
     /// Rust/Cargo test runner
     Cargo,
+    /// Rust tests run with cargo-nextest
+    Nextest,
     /// Python pytest
     Pytest,
     /// Python unittest
+    #[serde(rename = "unittest")]
     PythonUnittest,
     /// JavaScript Jest
     Jest,
@@ -24,19 +48,77 @@ pub enum TestFramework {
     Vitest,
     /// Go test
     Go,
+    /// JVM tests run by Maven Surefire
+    Maven,
+    /// JVM tests run by Gradle
+    Gradle,
+    /// .NET tests run by `dotnet test`
+    Dotnet,
+    /// Ruby RSpec
+    RSpec,
+    /// PHP PHPUnit
+    PhpUnit,
+    /// CMake's CTest, run in the `build` directory
+    CTest,
+    /// Bazel test targets
+    Bazel,
 }
 
 impl TestFramework {
+    /// Every supported framework
+    pub const ALL: [Self; 15] = [
+        Self::Cargo,
+        Self::Nextest,
+        Self::Pytest,
+        Self::PythonUnittest,
+        Self::Jest,
+        Self::Mocha,
+        Self::Vitest,
+        Self::Go,
+        Self::Maven,
+        Self::Gradle,
+        Self::Dotnet,
+        Self::RSpec,
+        Self::PhpUnit,
+        Self::CTest,
+        Self::Bazel,
+    ];
+
     /// Detect the test framework from project files in the given directory
+    ///
+    /// When several frameworks match, this is the first of
+    /// [`detect_all`](Self::detect_all).
     pub fn detect(path: &Path) -> Option<Self> {
-        // Check for Rust/Cargo
+        Self::detect_all(path).into_iter().next()
+    }
+
+    /// Detect every test framework with project files in the given directory
+    ///
+    /// A polyglot project can have several, such as a Cargo.toml next to a
+    /// package.json that uses Jest. Guesses from weaker hints (unittest for
+    /// any Python project, Jest for any package.json) are only made when
+    /// nothing else matches.
+    pub fn detect_all(path: &Path) -> Vec<Self> {
+        Self::detect_in(path, true)
+    }
+
+    /// Detect frameworks, optionally including the weak guesses
+    pub(crate) fn detect_in(path: &Path, guess: bool) -> Vec<Self> {
+        let mut found = Vec::new();
+        let mut guesses = Vec::new();
+
+        // Check for Rust/Cargo, using nextest when the project configures it
         if path.join("Cargo.toml").exists() {
-            return Some(Self::Cargo);
+            if path.join(".config/nextest.toml").exists() {
+                found.push(Self::Nextest);
+            } else {
+                found.push(Self::Cargo);
+            }
         }
 
         // Check for Go
         if path.join("go.mod").exists() {
-            return Some(Self::Go);
+            found.push(Self::Go);
         }
 
         // Check for Python (pytest or unittest)
@@ -45,23 +127,77 @@ impl TestFramework {
             || path.join("setup.cfg").exists() && Self::has_pytest_in_setup_cfg(path)
             || path.join("conftest.py").exists()
         {
-            return Some(Self::Pytest);
-        }
-
-        // Fallback to Python unittest if Python files exist
-        if path.join("setup.py").exists()
+            found.push(Self::Pytest);
+        } else if path.join("setup.py").exists()
             || path.join("pyproject.toml").exists()
             || path.join("requirements.txt").exists()
         {
-            return Some(Self::PythonUnittest);
+            // Fallback to Python unittest if Python files exist
+            guesses.push(Self::PythonUnittest);
         }
 
         // Check for JavaScript/TypeScript
         if path.join("package.json").exists() {
-            return Self::detect_js_framework(path);
+            match Self::detect_js_framework(path) {
+                Some(framework) => found.push(framework),
+                None => guesses.push(Self::Jest),
+            }
         }
 
-        None
+        // Check for the JVM build tools
+        if path.join("pom.xml").exists() {
+            found.push(Self::Maven);
+        } else if [
+            "build.gradle",
+            "build.gradle.kts",
+            "settings.gradle",
+            "settings.gradle.kts",
+        ]
+        .iter()
+        .any(|f| path.join(f).exists())
+        {
+            found.push(Self::Gradle);
+        }
+
+        // Check for .NET solutions and projects
+        if Self::has_dotnet_project(path) {
+            found.push(Self::Dotnet);
+        }
+
+        // Check for Ruby RSpec
+        if path.join(".rspec").exists()
+            || path.join("spec/spec_helper.rb").exists()
+            || file_contains(&path.join("Gemfile"), "rspec")
+        {
+            found.push(Self::RSpec);
+        }
+
+        // Check for PHPUnit
+        if path.join("phpunit.xml").exists()
+            || path.join("phpunit.xml.dist").exists()
+            || file_contains(&path.join("composer.json"), "phpunit/phpunit")
+        {
+            found.push(Self::PhpUnit);
+        }
+
+        // Check for CMake projects with tests
+        let cmake = path.join("CMakeLists.txt");
+        if file_contains(&cmake, "enable_testing") || file_contains(&cmake, "add_test") {
+            found.push(Self::CTest);
+        }
+
+        // Check for Bazel workspaces
+        if ["MODULE.bazel", "WORKSPACE", "WORKSPACE.bazel"]
+            .iter()
+            .any(|f| path.join(f).exists())
+        {
+            found.push(Self::Bazel);
+        }
+
+        if found.is_empty() && guess {
+            found = guesses;
+        }
+        found
     }
 
     /// Check if pyproject.toml has pytest config
@@ -85,6 +221,8 @@ impl TestFramework {
     }
 
     /// Detect JS test framework from package.json
+    ///
+    /// Returns `None` if package.json doesn't mention a known framework.
     fn detect_js_framework(path: &Path) -> Option<Self> {
         let package_json = path.join("package.json");
         let content = std::fs::read_to_string(package_json).ok()?;
@@ -111,18 +249,39 @@ impl TestFramework {
             return Some(Self::Mocha);
         }
 
-        // Default to Jest if none found but package.json exists
-        Some(Self::Jest)
+        None
     }
 
-    /// Get the command to run tests
-    pub fn run_command(&self) -> Command {
+    /// Check for a .NET solution or project file
+    fn has_dotnet_project(path: &Path) -> bool {
+        let Ok(entries) = std::fs::read_dir(path) else {
+            return false;
+        };
+        entries.flatten().any(|entry| {
+            entry
+                .path()
+                .extension()
+                .and_then(|ext| ext.to_str())
+                .is_some_and(|ext| matches!(ext, "sln" | "csproj" | "fsproj" | "vbproj"))
+        })
+    }
+
+    /// Get the command to run tests in `workdir`
+    ///
+    /// Build tool wrappers checked into the project (`mvnw`, `gradlew`,
+    /// `vendor/bin/phpunit`) are preferred over a global install.
+    pub fn run_command(&self, workdir: &Path) -> Command {
         match self {
             Self::Cargo => {
                 let mut cmd = Command::new("cargo");
                 cmd.args(["test", "--no-fail-fast"]);
                 cmd
             }
+            Self::Nextest => {
+                let mut cmd = Command::new("cargo");
+                cmd.args(["nextest", "run", "--no-fail-fast"]);
+                cmd
+            }
             Self::Pytest => {
                 let mut cmd = Command::new("pytest");
                 cmd.args(["--tb=short", "-v"]);
@@ -135,7 +294,7 @@ impl TestFramework {
             }
             Self::Jest => {
                 let mut cmd = Command::new("npx");
-                cmd.args(["jest", "--passWithNoTests"]);
+                cmd.args(["jest", "--passWithNoTests", "--verbose"]);
                 cmd
             }
             Self::Mocha => {
@@ -145,7 +304,7 @@ impl TestFramework {
             }
             Self::Vitest => {
                 let mut cmd = Command::new("npx");
-                cmd.args(["vitest", "run"]);
+                cmd.args(["vitest", "run", "--reporter=verbose"]);
                 cmd
             }
             Self::Go => {
@@ -153,6 +312,224 @@ impl TestFramework {
                 cmd.args(["test", "-v", "./..."]);
                 cmd
             }
+            Self::Maven => {
+                let mut cmd = Command::new(local_tool(workdir, "mvnw", "mvn"));
+                cmd.args(["-B", "--fail-at-end", "test"]);
+                cmd
+            }
+            Self::Gradle => {
+                let mut cmd = Command::new(local_tool(workdir, "gradlew", "gradle"));
+                cmd.args(["test", "--continue"]);
+                cmd
+            }
+            Self::Dotnet => {
+                let mut cmd = Command::new("dotnet");
+                cmd.args(["test", "--logger", "console;verbosity=normal"]);
+                cmd
+            }
+            Self::RSpec => {
+                let mut cmd = if workdir.join("Gemfile").exists() {
+                    let mut cmd = Command::new("bundle");
+                    cmd.args(["exec", "rspec"]);
+                    cmd
+                } else {
+                    Command::new("rspec")
+                };
+                cmd.args(["--format", "documentation"]);
+                cmd
+            }
+            Self::PhpUnit => Command::new(local_tool(workdir, "vendor/bin/phpunit", "phpunit")),
+            Self::CTest => {
+                let mut cmd = Command::new("ctest");
+                cmd.args(["--test-dir", "build", "--output-on-failure"]);
+                cmd
+            }
+            Self::Bazel => {
+                let mut cmd = Command::new("bazel");
+                cmd.args(["test", "//...", "--keep_going", "--test_output=errors"]);
+                cmd
+            }
+        }
+    }
+
+    /// Get a command that also produces a machine-readable report
+    ///
+    /// Returns `None` when the framework, or the tooling installed in
+    /// `workdir`, has no structured output, in which case
+    /// [`run_command`](Self::run_command) is used. Report files are written
+    /// to `report_dir`, except for Maven and Gradle, which always write
+    /// their JUnit reports into the build directory.
+    pub fn report_command(
+        &self,
+        workdir: &Path,
+        report_dir: &Path,
+    ) -> Option<(Command, ReportFormat)> {
+        let report = |name: &str| report_dir.join(name).to_string_lossy().into_owned();
+        match self {
+            Self::Cargo => {
+                if command_succeeds(workdir, "cargo", &["nextest", "--version"]) {
+                    Self::Nextest.report_command(workdir, report_dir)
+                } else if rustc_is_nightly(workdir) {
+                    let mut cmd = Command::new("cargo");
+                    cmd.args([
+                        "test",
+                        "--no-fail-fast",
+                        "--",
+                        "-Z",
+                        "unstable-options",
+                        "--format",
+                        "json",
+                        "--report-time",
+                    ]);
+                    Some((cmd, ReportFormat::LibtestJson))
+                } else {
+                    None
+                }
+            }
+            Self::Nextest => {
+                let mut cmd = self.run_command(workdir);
+                cmd.args(["--message-format", "libtest-json"]);
+                cmd.env("NEXTEST_EXPERIMENTAL_LIBTEST_JSON", "1");
+                Some((cmd, ReportFormat::LibtestJson))
+            }
+            Self::Pytest => {
+                let mut cmd = self.run_command(workdir);
+                cmd.arg(format!("--junitxml={}", report("pytest.xml")));
+                Some((cmd, ReportFormat::JunitXml))
+            }
+            Self::PythonUnittest | Self::Bazel => None,
+            Self::Jest => {
+                // JUnit output needs the jest-junit reporter package
+                if !file_contains(&workdir.join("package.json"), "\"jest-junit\"") {
+                    return None;
+                }
+                let mut cmd = self.run_command(workdir);
+                cmd.args(["--reporters=default", "--reporters=jest-junit"]);
+                cmd.env("JEST_JUNIT_OUTPUT_FILE", report("jest.xml"));
+                Some((cmd, ReportFormat::JunitXml))
+            }
+            Self::Mocha => {
+                let mut cmd = self.run_command(workdir);
+                cmd.args(["--reporter", "xunit", "--reporter-option"]);
+                cmd.arg(format!("output={}", report("mocha.xml")));
+                Some((cmd, ReportFormat::JunitXml))
+            }
+            Self::Vitest => {
+                let mut cmd = self.run_command(workdir);
+                cmd.arg("--reporter=junit");
+                cmd.arg(format!("--outputFile.junit={}", report("vitest.xml")));
+                Some((cmd, ReportFormat::JunitXml))
+            }
+            Self::Go => {
+                let mut cmd = Command::new("go");
+                cmd.args(["test", "-json", "./..."]);
+                Some((cmd, ReportFormat::GoJson))
+            }
+            Self::Maven | Self::Gradle => Some((self.run_command(workdir), ReportFormat::JunitXml)),
+            Self::Dotnet => {
+                let mut cmd = self.run_command(workdir);
+                cmd.args(["--logger", "trx", "--results-directory"]);
+                cmd.arg(report_dir);
+                Some((cmd, ReportFormat::Trx))
+            }
+            Self::RSpec => {
+                let mut cmd = self.run_command(workdir);
+                cmd.args(["--format", "json", "--out"]);
+                cmd.arg(report("rspec.json"));
+                Some((cmd, ReportFormat::RspecJson))
+            }
+            Self::PhpUnit => {
+                let mut cmd = self.run_command(workdir);
+                cmd.arg("--log-junit");
+                cmd.arg(report("phpunit.xml"));
+                Some((cmd, ReportFormat::JunitXml))
+            }
+            Self::CTest => {
+                // --output-junit needs CMake 3.21
+                if !ctest_supports_junit(workdir) {
+                    return None;
+                }
+                let mut cmd = self.run_command(workdir);
+                cmd.arg("--output-junit");
+                cmd.arg(report("ctest.xml"));
+                Some((cmd, ReportFormat::JunitXml))
+            }
+        }
+    }
+
+    /// Name of the build directories holding the framework's own JUnit reports
+    fn build_report_dir(&self) -> Option<&'static str> {
+        match self {
+            Self::Maven => Some("surefire-reports"),
+            Self::Gradle => Some("test-results"),
+            _ => None,
+        }
+    }
+
+    /// Arguments that limit a run to tests matching `filter`
+    fn filter_args(&self, filter: &str) -> Vec<String> {
+        let args: &[&str] = match self {
+            Self::Cargo | Self::Nextest => &[],
+            Self::Pytest | Self::PythonUnittest => &["-k"],
+            Self::Jest | Self::Vitest => &["-t"],
+            Self::Go => &["-run"],
+            Self::Mocha => &["--grep"],
+            Self::Maven => {
+                return vec![
+                    format!("-Dtest={}", filter),
+                    "-Dsurefire.failIfNoSpecifiedTests=false".to_string(),
+                ]
+            }
+            Self::Gradle => &["--tests"],
+            Self::Dotnet => &["--filter"],
+            Self::RSpec => &["-e"],
+            Self::PhpUnit => &["--filter"],
+            Self::CTest => &["-R"],
+            Self::Bazel => return vec![format!("--test_filter={}", filter)],
+        };
+        args.iter()
+            .map(|a| a.to_string())
+            .chain([filter.to_string()])
+            .collect()
+    }
+
+    /// Parse the human-readable output of a run
+    fn parse_output(&self, stdout: &str, stderr: &str) -> TestResults {
+        match self {
+            Self::Cargo => parse_cargo_output(stdout, stderr),
+            Self::Nextest => parse_nextest_output(stdout, stderr),
+            Self::Pytest => parse_pytest_output(stdout),
+            Self::PythonUnittest => parse_unittest_output(stdout, stderr),
+            Self::Jest | Self::Vitest => parse_jest_output(stdout, stderr),
+            Self::Mocha => parse_mocha_output(stdout),
+            Self::Go => parse_go_output(stdout, stderr),
+            Self::Maven => parse_maven_output(stdout),
+            Self::Gradle => parse_gradle_output(stdout, stderr),
+            Self::Dotnet => parse_dotnet_output(stdout),
+            Self::RSpec => parse_rspec_output(stdout),
+            Self::PhpUnit => parse_phpunit_output(stdout),
+            Self::CTest => parse_ctest_output(stdout),
+            Self::Bazel => parse_bazel_output(stdout, stderr),
+        }
+    }
+
+    /// Extensions of the source files the framework's tests cover
+    ///
+    /// Empty for frameworks that build any language.
+    pub fn source_extensions(&self) -> &'static [&'static str] {
+        match self {
+            Self::Cargo | Self::Nextest => &["rs"],
+            Self::Pytest | Self::PythonUnittest => &["py"],
+            Self::Jest | Self::Mocha | Self::Vitest => &[
+                "js", "jsx", "ts", "tsx", "mjs", "cjs", "mts", "cts", "vue", "svelte",
+            ],
+            Self::Go => &["go"],
+            Self::Maven | Self::Gradle => &["java", "kt", "kts", "groovy", "scala"],
+            Self::Dotnet => &["cs", "fs", "vb"],
+            Self::RSpec => &["rb"],
+            Self::PhpUnit => &["php"],
+            Self::CTest => &["c", "cc", "cpp", "cxx", "h", "hh", "hpp", "hxx"],
+            Self::Bazel => &[],
         }
     }
 
@@ -160,14 +537,167 @@ impl TestFramework {
     pub fn name(&self) -> &'static str {
         match self {
             Self::Cargo => "cargo test",
+            Self::Nextest => "cargo nextest",
             Self::Pytest => "pytest",
             Self::PythonUnittest => "unittest",
             Self::Jest => "jest",
             Self::Mocha => "mocha",
             Self::Vitest => "vitest",
             Self::Go => "go test",
+            Self::Maven => "maven",
+            Self::Gradle => "gradle",
+            Self::Dotnet => "dotnet test",
+            Self::RSpec => "rspec",
+            Self::PhpUnit => "phpunit",
+            Self::CTest => "ctest",
+            Self::Bazel => "bazel test",
+        }
+    }
+}
+
+/// Machine-readable test output
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportFormat {
+    /// libtest JSON events on stdout (nightly `cargo test` or nextest)
+    LibtestJson,
+    /// `go test -json` events on stdout
+    GoJson,
+    /// JUnit XML written to report files
+    JunitXml,
+    /// Visual Studio TRX written by `dotnet test`
+    Trx,
+    /// RSpec's JSON formatter written to a report file
+    RspecJson,
+}
+
+impl ReportFormat {
+    /// Whether the report is written to files rather than printed
+    pub fn is_file(&self) -> bool {
+        matches!(self, Self::JunitXml | Self::Trx | Self::RspecJson)
+    }
+}
+
+/// Whether a file exists and contains `needle`
+fn file_contains(path: &Path, needle: &str) -> bool {
+    std::fs::read_to_string(path).is_ok_and(|content| content.contains(needle))
+}
+
+/// A wrapper script in the project, or else the globally installed tool
+fn local_tool(workdir: &Path, wrapper: &str, tool: &str) -> PathBuf {
+    let wrapper = workdir.join(wrapper);
+    if wrapper.exists() {
+        wrapper
+    } else {
+        PathBuf::from(tool)
+    }
+}
+
+/// Whether a command runs successfully in `workdir`
+fn command_succeeds(workdir: &Path, program: &str, args: &[&str]) -> bool {
+    Command::new(program)
+        .args(args)
+        .current_dir(workdir)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .is_ok_and(|status| status.success())
+}
+
+/// Whether the toolchain used in `workdir` accepts unstable libtest options
+fn rustc_is_nightly(workdir: &Path) -> bool {
+    Command::new("rustc")
+        .arg("--version")
+        .current_dir(workdir)
+        .output()
+        .is_ok_and(|output| {
+            let version = String::from_utf8_lossy(&output.stdout);
+            version.contains("-nightly") || version.contains("-dev")
+        })
+}
+
+/// Whether the installed CTest can write JUnit reports (CMake 3.21+)
+fn ctest_supports_junit(workdir: &Path) -> bool {
+    Command::new("ctest")
+        .arg("--version")
+        .current_dir(workdir)
+        .output()
+        .is_ok_and(|output| {
+            let version = String::from_utf8_lossy(&output.stdout);
+            let mut parts = version
+                .split_whitespace()
+                .nth(2)
+                .unwrap_or_default()
+                .split('.')
+                .map(|p| p.parse::<u32>().unwrap_or(0));
+            let major = parts.next().unwrap_or(0);
+            let minor = parts.next().unwrap_or(0);
+            (major, minor) >= (3, 21)
+        })
+}
+
+/// Outcome of a single test
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TestOutcome {
+    /// The test passed
+    Passed,
+    /// The test failed or errored
+    Failed,
+    /// The test was skipped or ignored
+    Skipped,
+}
+
+/// A single test and how it went
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TestCase {
+    /// Test name as reported by the framework
+    pub name: String,
+    /// How the test went
+    pub outcome: TestOutcome,
+    /// How long the test took, when reported
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration_ms: Option<u64>,
+    /// Why the test failed, when reported
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    /// Where the test or its failure is, as `file` or `file:line`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub location: Option<String>,
+    /// Suite the test belongs to, when the runner has several
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub suite: Option<String>,
+}
+
+impl TestCase {
+    /// Create a test case
+    pub fn new(name: impl Into<String>, outcome: TestOutcome) -> Self {
+        Self {
+            name: name.into(),
+            outcome,
+            duration_ms: None,
+            message: None,
+            location: None,
+            suite: None,
         }
     }
+
+    /// Set how long the test took
+    pub fn with_duration_ms(mut self, duration_ms: u64) -> Self {
+        self.duration_ms = Some(duration_ms);
+        self
+    }
+
+    /// Set the failure message
+    pub fn with_message(mut self, message: impl Into<String>) -> Self {
+        self.message = Some(message.into());
+        self
+    }
+
+    /// Set the file location
+    pub fn with_location(mut self, location: impl Into<String>) -> Self {
+        self.location = Some(location.into());
+        self
+    }
 }
 
 /// Results of a test run
@@ -185,6 +715,12 @@ pub struct TestResults {
     pub output: String,
     /// Error if the test command itself failed to execute
     pub execution_error: Option<String>,
+    /// Individual tests, when the framework's output lists them
+    pub tests: Vec<TestCase>,
+    /// Whether the run was killed for exceeding the timeout
+    pub timed_out: bool,
+    /// Suites that ran, when the runner has several
+    pub suites: Vec<String>,
 }
 
 impl TestResults {
@@ -197,6 +733,9 @@ impl TestResults {
             duration_ms: 0,
             output: String::new(),
             execution_error: None,
+            tests: Vec::new(),
+            timed_out: false,
+            suites: Vec::new(),
         }
     }
 
@@ -209,19 +748,86 @@ impl TestResults {
             duration_ms: 0,
             output: String::new(),
             execution_error: Some(error),
+            tests: Vec::new(),
+            timed_out: false,
+            suites: Vec::new(),
         }
     }
 
+    /// Create results from individual tests, counting their outcomes
+    pub fn from_tests(tests: Vec<TestCase>) -> Self {
+        let count = |outcome| tests.iter().filter(|t| t.outcome == outcome).count() as u32;
+        Self {
+            passed: count(TestOutcome::Passed),
+            failed: count(TestOutcome::Failed),
+            skipped: count(TestOutcome::Skipped),
+            tests,
+            ..Self::empty()
+        }
+    }
+
+    /// Mark the run as killed after `timeout`
+    ///
+    /// A timed-out run is neither red nor green, whatever had run so far.
+    pub fn mark_timed_out(&mut self, timeout: Duration) {
+        self.timed_out = true;
+        self.execution_error = Some(format!("Tests timed out after {}s", timeout.as_secs_f64()));
+    }
+
+    /// Attribute these results to the named suite
+    ///
+    /// Test names get the suite as a prefix, so tests with the same name in
+    /// different suites stay apart.
+    pub fn tag_suite(&mut self, suite: &str) {
+        for test in &mut self.tests {
+            test.name = format!("{}: {}", suite, test.name);
+            test.suite = Some(suite.to_string());
+        }
+        if let Some(ref mut error) = self.execution_error {
+            *error = format!("{}: {}", suite, error);
+        }
+        self.suites = vec![suite.to_string()];
+    }
+
+    /// Add the results of another suite's run
+    ///
+    /// The first execution error is kept.
+    pub fn merge(&mut self, other: TestResults) {
+        self.passed += other.passed;
+        self.failed += other.failed;
+        self.skipped += other.skipped;
+        self.duration_ms += other.duration_ms;
+        if !self.output.is_empty() && !other.output.is_empty() {
+            self.output.push('\n');
+        }
+        self.output.push_str(&other.output);
+        if self.execution_error.is_none() {
+            self.execution_error = other.execution_error;
+        }
+        self.tests.extend(other.tests);
+        self.timed_out |= other.timed_out;
+        self.suites.extend(other.suites);
+    }
+
+    /// Tests that failed
+    pub fn failures(&self) -> impl Iterator<Item = &TestCase> {
+        self.tests
+            .iter()
+            .filter(|t| t.outcome == TestOutcome::Failed)
+    }
+
     /// Check if tests are in "red" state (at least one failing)
     ///
-    /// This is used by the VerifyRed phase to ensure tests fail before implementation.
+    /// This only looks at the aggregate counts; use [`diff`](Self::diff) to
+    /// check that the failures come from newly added tests.
     pub fn is_red(&self) -> bool {
         self.execution_error.is_none() && self.failed > 0
     }
 
     /// Check if tests are in "green" state (all passing, none failing)
     ///
-    /// This is used by the VerifyGreen phase to ensure implementation is correct.
+    /// This only looks at the aggregate counts; use [`diff`](Self::diff) to
+    /// check that the new tests actually ran.
     pub fn is_green(&self) -> bool {
         self.execution_error.is_none() && self.failed == 0 && self.passed > 0
     }
@@ -233,6 +839,14 @@ impl TestResults {
 
     /// Get a summary string
     pub fn summary(&self) -> String {
+        if self.timed_out {
+            return format!(
+                "{} (after {} passed, {} failed)",
+                self.execution_error.as_deref().unwrap_or("Tests timed out"),
+                self.passed,
+                self.failed
+            );
+        }
         if let Some(ref error) = self.execution_error {
             return format!("Execution error: {}", error);
         }
@@ -249,31 +863,244 @@ impl TestResults {
     pub fn total(&self) -> u32 {
         self.passed + self.failed + self.skipped
     }
+
+    /// Whether the individual tests of this run can be used as a baseline
+    ///
+    /// A run that failed without listing any tests (a compile error, say)
+    /// doesn't say which tests exist.
+    pub fn has_test_cases(&self) -> bool {
+        self.execution_error.is_none() && (!self.tests.is_empty() || self.no_tests_found())
+    }
+
+    /// Outcome of the named test, if it ran
+    pub fn outcome_of(&self, name: &str) -> Option<TestOutcome> {
+        self.tests
+            .iter()
+            .find(|t| t.name == name)
+            .map(|t| t.outcome)
+    }
+
+    /// Compare this run against a baseline taken before the tests were written
+    ///
+    /// `expected` names new tests that must run, such as the ones that
+    /// failed in VerifyRed. Baseline tests of suites that weren't run this
+    /// time aren't expected to run.
+    pub fn diff(&self, baseline: &[TestCase], expected: &[String]) -> TestDiff {
+        let before: HashMap<&str, TestOutcome> = baseline
+            .iter()
+            .map(|t| (t.name.as_str(), t.outcome))
+            .collect();
+        let mut diff = TestDiff::default();
+
+        for test in &self.tests {
+            match (before.get(test.name.as_str()), test.outcome) {
+                (None, TestOutcome::Passed) => diff.new_passing.push(test.name.clone()),
+                (None, TestOutcome::Failed) => diff.new_failing.push(test.name.clone()),
+                (Some(TestOutcome::Passed), TestOutcome::Failed) => {
+                    diff.regressed.push(test.name.clone())
+                }
+                _ => {}
+            }
+        }
+
+        let ran: HashSet<&str> = self
+            .tests
+            .iter()
+            .filter(|t| t.outcome != TestOutcome::Skipped)
+            .map(|t| t.name.as_str())
+            .collect();
+        let should_run = baseline
+            .iter()
+            .filter(|t| t.outcome == TestOutcome::Passed)
+            .filter(|t| t.suite.as_ref().is_none_or(|s| self.suites.contains(s)))
+            .map(|t| t.name.as_str())
+            .chain(expected.iter().map(String::as_str));
+        for name in should_run {
+            if !ran.contains(name) && !diff.missing.iter().any(|m| m == name) {
+                diff.missing.push(name.to_string());
+            }
+        }
+
+        diff
+    }
+}
+
+/// Per-test comparison of a run against a baseline
+///
+/// Tests that already failed in the baseline and still fail are ignored, so
+/// unrelated pre-existing failures don't count towards red or green.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TestDiff {
+    /// New tests that passed
+    pub new_passing: Vec<String>,
+    /// New tests that failed
+    pub new_failing: Vec<String>,
+    /// Tests that passed in the baseline but fail now
+    pub regressed: Vec<String>,
+    /// Previously passing or expected tests that didn't run or were skipped
+    pub missing: Vec<String>,
+}
+
+impl TestDiff {
+    /// Why this run isn't a valid red state, if it isn't
+    ///
+    /// Red requires at least one new test, with every new test failing and
+    /// every previously passing test still passing.
+    pub fn red_problem(&self) -> Option<String> {
+        if !self.regressed.is_empty() {
+            return Some(format!(
+                "{} previously passing tests now fail - writing tests shouldn't break them: {}",
+                self.regressed.len(),
+                name_list(&self.regressed)
+            ));
+        }
+        if !self.new_passing.is_empty() {
+            return Some(format!(
+                "New tests passed unexpectedly - they may not be testing new behavior: {}",
+                name_list(&self.new_passing)
+            ));
+        }
+        if self.new_failing.is_empty() {
+            return Some(
+                "No new failing tests were found - the new tests may not be running".to_string(),
+            );
+        }
+        None
+    }
+
+    /// Why this run isn't a valid green state, if it isn't
+    ///
+    /// Green requires every new test to pass, every expected test to run and
+    /// every previously passing test to still pass.
+    pub fn green_problem(&self) -> Option<String> {
+        if let Some(problem) = self.passing_problem() {
+            return Some(problem);
+        }
+        if self.new_passing.is_empty() {
+            return Some("No new passing tests were found".to_string());
+        }
+        None
+    }
+
+    /// Why this run has failing, regressed or missing tests, if it does
+    ///
+    /// Green without requiring new tests, for when none were expected.
+    pub fn passing_problem(&self) -> Option<String> {
+        if !self.new_failing.is_empty() {
+            return Some(format!(
+                "{} tests still failing: {}",
+                self.new_failing.len(),
+                name_list(&self.new_failing)
+            ));
+        }
+        if !self.regressed.is_empty() {
+            return Some(format!(
+                "{} previously passing tests now fail: {}",
+                self.regressed.len(),
+                name_list(&self.regressed)
+            ));
+        }
+        if !self.missing.is_empty() {
+            return Some(format!(
+                "{} tests did not run: {}",
+                self.missing.len(),
+                name_list(&self.missing)
+            ));
+        }
+        None
+    }
+
+    /// Whether this is a valid red state
+    pub fn is_red(&self) -> bool {
+        self.red_problem().is_none()
+    }
+
+    /// Whether this is a valid green state
+    pub fn is_green(&self) -> bool {
+        self.green_problem().is_none()
+    }
+}
+
+/// Compare a run against a baseline, when both list individual tests
+///
+/// Returns `None` when the comparison isn't possible, in which case callers
+/// fall back to the aggregate counts.
+pub(crate) fn compare_to_baseline(
+    results: &TestResults,
+    baseline: Option<&[TestCase]>,
+    expected: &[String],
+) -> Option<TestDiff> {
+    let baseline = baseline?;
+    if results.execution_error.is_some() || results.tests.is_empty() {
+        return None;
+    }
+    Some(results.diff(baseline, expected))
+}
+
+/// Join test names for a message, eliding all but the first few
+fn name_list(names: &[String]) -> String {
+    const SHOWN: usize = 5;
+    let mut list = names
+        .iter()
+        .take(SHOWN)
+        .map(String::as_str)
+        .collect::<Vec<_>>()
+        .join(", ");
+    if names.len() > SHOWN {
+        list.push_str(&format!(" and {} more", names.len() - SHOWN));
+    }
+    list
 }
 
 /// Test runner that executes tests and parses results
+///
+/// A runner has one or more [`TestSuite`]s; a monorepo can have one per
+/// subdirectory, each with its own framework or custom command.
 pub struct TestRunner {
-    workdir: std::path::PathBuf,
-    framework: Option<TestFramework>,
+    workdir: PathBuf,
+    suites: Vec<TestSuite>,
     filter: Option<String>,
     timeout: Duration,
+    structured: bool,
 }
 
 impl TestRunner {
     /// Create a new test runner for the given directory
     ///
-    /// Automatically detects the test framework from project files.
-    pub fn new(workdir: impl Into<std::path::PathBuf>) -> Self {
+    /// Automatically detects the test suites from project files, see
+    /// [`TestSuite::discover`].
+    pub fn new(workdir: impl Into<PathBuf>) -> Self {
         let workdir = workdir.into();
-        let framework = TestFramework::detect(&workdir);
+        let suites = TestSuite::discover(&workdir);
         Self {
             workdir,
-            framework,
+            suites,
             filter: None,
             timeout: Duration::from_secs(300), // 5 minute default timeout
+            structured: true,
         }
     }
 
+    /// Create a test runner following a `[test]` config section
+    ///
+    /// A `[test]` section in the repository's `.murmur.toml` takes the place
+    /// of `config`.
+    pub fn from_config(workdir: impl Into<PathBuf>, config: &TestConfig) -> Self {
+        let workdir = workdir.into();
+        let config = config.for_workdir(&workdir);
+        let mut runner = Self::new(&workdir).with_suites(config.suites(&workdir));
+        if let Some(timeout) = config.timeout() {
+            runner.timeout = timeout;
+        }
+        runner
+    }
+
+    /// Only parse human-readable output, never requesting a structured report
+    pub fn without_structured_output(mut self) -> Self {
+        self.structured = false;
+        self
+    }
+
     /// Set a test filter (e.g., test name pattern)
     pub fn with_filter(mut self, filter: impl Into<String>) -> Self {
         self.filter = Some(filter.into());
@@ -281,96 +1108,536 @@ impl TestRunner {
     }
 
     /// Set the timeout for test execution
+    ///
+    /// With several suites, this is the time allowed for all of them.
     pub fn with_timeout(mut self, timeout: Duration) -> Self {
         self.timeout = timeout;
         self
     }
 
-    /// Explicitly set the test framework
+    /// Explicitly set the test framework, run from the working directory
     pub fn with_framework(mut self, framework: TestFramework) -> Self {
-        self.framework = Some(framework);
+        self.suites = vec![TestSuite::new(".", SuiteKind::Framework(framework))];
+        self
+    }
+
+    /// Explicitly set the test suites
+    pub fn with_suites(mut self, suites: Vec<TestSuite>) -> Self {
+        self.suites = suites;
         self
     }
 
-    /// Get the detected framework
+    /// Get the framework of the first suite
     pub fn framework(&self) -> Option<TestFramework> {
-        self.framework
+        self.suites.first().and_then(TestSuite::framework)
+    }
+
+    /// Get the test suites
+    pub fn suites(&self) -> &[TestSuite] {
+        &self.suites
     }
 
     /// Run tests and return results
-    pub fn run(&self) -> TestResults {
-        let Some(framework) = self.framework else {
+    pub async fn run(&self) -> TestResults {
+        self.run_with_handler(&mut ()).await
+    }
+
+    /// Run tests, passing each line of output to `handler` as it is printed
+    ///
+    /// If the tests take longer than the timeout, the whole process group is
+    /// killed and the results are marked as timed out.
+    pub async fn run_with_handler<H: TestOutputHandler>(&self, handler: &mut H) -> TestResults {
+        self.run_for_changes(&[], handler).await
+    }
+
+    /// Run the suites covering `changed_files`, streaming output to `handler`
+    ///
+    /// Paths are relative to the working directory. All suites run when no
+    /// files are given or none of them is covered by a suite.
+    pub async fn run_for_changes<H: TestOutputHandler>(
+        &self,
+        changed_files: &[String],
+        handler: &mut H,
+    ) -> TestResults {
+        if self.suites.is_empty() {
             return TestResults::with_error("No test framework detected".to_string());
-        };
+        }
 
-        let mut cmd = framework.run_command();
-        cmd.current_dir(&self.workdir);
-        cmd.stdout(Stdio::piped());
-        cmd.stderr(Stdio::piped());
+        let mut suites: Vec<&TestSuite> = self
+            .suites
+            .iter()
+            .filter(|s| changed_files.iter().any(|f| s.covers(f)))
+            .collect();
+        if suites.is_empty() {
+            suites = self.suites.iter().collect();
+        }
+        let several = self.suites.len() > 1;
+        let deadline = tokio::time::Instant::now() + self.timeout;
 
-        // Add filter if specified
-        if let Some(ref filter) = self.filter {
-            match framework {
-                TestFramework::Cargo => {
-                    cmd.arg(filter);
-                }
-                TestFramework::Pytest => {
-                    cmd.args(["-k", filter]);
-                }
-                TestFramework::Jest | TestFramework::Vitest => {
-                    cmd.args(["-t", filter]);
-                }
-                TestFramework::Go => {
-                    cmd.args(["-run", filter]);
+        let mut merged: Option<TestResults> = None;
+        for suite in suites {
+            if several {
+                handler.on_output(&format!("==> {} ({})", suite.name, suite.label()));
+            }
+            let mut results = self.run_suite(suite, deadline, handler).await;
+            if several {
+                results.tag_suite(&suite.name);
+            }
+            let timed_out = results.timed_out;
+            match merged {
+                Some(ref mut merged) => merged.merge(results),
+                None => merged = Some(results),
+            }
+            if timed_out {
+                break;
+            }
+        }
+        merged.unwrap_or_else(TestResults::empty)
+    }
+
+    /// Run one suite, killing it at `deadline`
+    async fn run_suite<H: TestOutputHandler>(
+        &self,
+        suite: &TestSuite,
+        deadline: tokio::time::Instant,
+        handler: &mut H,
+    ) -> TestResults {
+        let workdir = self.workdir.join(&suite.path);
+        let report_dir = report_dir();
+        let (mut cmd, format) = match suite.kind {
+            SuiteKind::Framework(framework) => {
+                let report = self
+                    .structured
+                    .then(|| framework.report_command(&workdir, &report_dir))
+                    .flatten();
+                let (mut cmd, format) = match report {
+                    Some((cmd, format)) => (cmd, Some(format)),
+                    None => (framework.run_command(&workdir), None),
+                };
+                // Add filter if specified
+                if let Some(ref filter) = self.filter {
+                    cmd.args(framework.filter_args(filter));
                 }
-                TestFramework::PythonUnittest => {
-                    cmd.args(["-k", filter]);
+                (cmd, format)
+            }
+            SuiteKind::Custom(ref custom) => {
+                let mut cmd = custom.command();
+                cmd.env("MURMUR_TEST_REPORT_DIR", &report_dir);
+                if let Some(ref filter) = self.filter {
+                    cmd.env("MURMUR_TEST_FILTER", filter);
                 }
-                TestFramework::Mocha => {
-                    cmd.args(["--grep", filter]);
+                (cmd, custom.format.report_format())
+            }
+        };
+        cmd.current_dir(&workdir);
+        cmd.stdout(Stdio::piped());
+        cmd.stderr(Stdio::piped());
+
+        let start = Instant::now();
+        // Report files older than this are left over from earlier runs
+        let fresh_since = SystemTime::now() - Duration::from_secs(2);
+        let mut cmd = tokio::process::Command::from(cmd);
+        cmd.kill_on_drop(true);
+        // Run in a process group of its own so a timeout can kill test
+        // processes spawned by the test command too
+        own_process_group(&mut cmd);
+        let mut child = match cmd.spawn() {
+            Ok(child) => child,
+            Err(e) => {
+                let _ = std::fs::remove_dir_all(&report_dir);
+                return TestResults::with_error(format!("Failed to run tests: {}", e));
+            }
+        };
+
+        let mut stdout_reader = child.stdout.take().map(LineReader::new);
+        let mut stderr_reader = child.stderr.take().map(LineReader::new);
+        let mut stdout = String::new();
+        let mut stderr = String::new();
+        let deadline = tokio::time::sleep_until(deadline);
+        tokio::pin!(deadline);
+        let mut timed_out = false;
+
+        while stdout_reader.is_some() || stderr_reader.is_some() {
+            tokio::select! {
+                line = next_line(&mut stdout_reader) => match line {
+                    Some(line) => {
+                        forward_line(handler, format, &line);
+                        stdout.push_str(&line);
+                        stdout.push('\n');
+                    }
+                    None => stdout_reader = None,
+                },
+                line = next_line(&mut stderr_reader) => match line {
+                    Some(line) => {
+                        handler.on_output(&line);
+                        stderr.push_str(&line);
+                        stderr.push('\n');
+                    }
+                    None => stderr_reader = None,
+                },
+                _ = &mut deadline => {
+                    timed_out = true;
+                    break;
                 }
             }
         }
 
-        let start = Instant::now();
-        let output = match cmd.output() {
-            Ok(output) => output,
-            Err(e) => return TestResults::with_error(format!("Failed to run tests: {}", e)),
+        // The output can close before the process exits, so the deadline
+        // still applies while waiting
+        let status = match timed_out {
+            true => None,
+            false => match tokio::time::timeout_at(deadline.deadline(), child.wait()).await {
+                Ok(Ok(status)) => Some(status),
+                Ok(Err(e)) => {
+                    let _ = std::fs::remove_dir_all(&report_dir);
+                    return TestResults::with_error(format!("Failed to run tests: {}", e));
+                }
+                Err(_) => None,
+            },
         };
+        if status.is_none() {
+            kill_process_group(&mut child).await;
+        }
         let duration_ms = start.elapsed().as_millis() as u64;
-
-        let stdout = String::from_utf8_lossy(&output.stdout).to_string();
-        let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+        let report_files = match format {
+            Some(format) if format.is_file() => {
+                report_files(suite, &workdir, &report_dir, fresh_since)
+            }
+            _ => Vec::new(),
+        };
         let combined_output = format!("{}\n{}", stdout, stderr);
 
         // Parse results based on framework
-        let mut results = match framework {
-            TestFramework::Cargo => parse_cargo_output(&stdout, &stderr),
-            TestFramework::Pytest => parse_pytest_output(&stdout),
-            TestFramework::PythonUnittest => parse_unittest_output(&stdout, &stderr),
-            TestFramework::Jest | TestFramework::Vitest => parse_jest_output(&stdout, &stderr),
-            TestFramework::Mocha => parse_mocha_output(&stdout),
-            TestFramework::Go => parse_go_output(&stdout, &stderr),
+        let mut results = match suite.kind {
+            SuiteKind::Framework(framework) => framework.parse_output(&stdout, &stderr),
+            SuiteKind::Custom(ref custom) if custom.format == CustomFormat::Tap => {
+                TestResults::from_tests(parse_tap(&stdout))
+            }
+            SuiteKind::Custom(_) => TestResults::empty(),
         };
 
+        if let Some(format) = format {
+            let tests = match format {
+                ReportFormat::LibtestJson => parse_libtest_json(&stdout),
+                ReportFormat::GoJson => parse_go_json(&stdout),
+                _ => report_files
+                    .iter()
+                    .filter_map(|path| std::fs::read_to_string(path).ok())
+                    .filter_map(|content| parse_report_file(format, &content))
+                    .flatten()
+                    .collect(),
+            };
+            if !tests.is_empty() {
+                results = TestResults::from_tests(tests);
+            } else if !format.is_file() {
+                // The text parsers can't read JSON output, so names are
+                // unreliable; keep only the counts
+                results.tests.clear();
+            }
+        }
+        let _ = std::fs::remove_dir_all(&report_dir);
+
         results.duration_ms = duration_ms;
         results.output = combined_output;
 
-        // If output parsing found no results but exit code indicates failure,
-        // mark as at least one failure
-        if results.no_tests_found() && !output.status.success() {
-            results.failed = 1;
+        match status {
+            // If output parsing found no results but exit code indicates failure,
+            // mark as at least one failure
+            Some(status) if results.no_tests_found() && !status.success() => results.failed = 1,
+            // A command judged by its exit status alone passes as a whole
+            Some(_) if results.no_tests_found() && suite.exit_code_only() => results.passed = 1,
+            Some(_) => {}
+            // Whatever ran before the timeout is kept for display only
+            None => results.mark_timed_out(self.timeout),
         }
 
         results
     }
 }
 
+/// Receives test output as it is printed
+pub trait TestOutputHandler: Send {
+    /// Called with each line of output, from stdout or stderr
+    fn on_output(&mut self, line: &str);
+}
+
+/// Handler that discards the output
+impl TestOutputHandler for () {
+    fn on_output(&mut self, _line: &str) {}
+}
+
+impl<F: FnMut(&str) + Send> TestOutputHandler for F {
+    fn on_output(&mut self, line: &str) {
+        self(line)
+    }
+}
+
+/// Pass a stdout line to the handler, showing JSON reports as text
+fn forward_line<H: TestOutputHandler>(handler: &mut H, format: Option<ReportFormat>, line: &str) {
+    match format {
+        Some(format) => {
+            if let Some(text) = display_line(format, line) {
+                handler.on_output(&text);
+            }
+        }
+        None => handler.on_output(line),
+    }
+}
+
+/// Reads lines from a child's output, tolerating invalid UTF-8
+///
+/// Partial lines stay buffered if a read is cancelled, so
+/// [`next_line`](Self::next_line) is safe to use in `select!`.
+struct LineReader<R> {
+    reader: tokio::io::BufReader<R>,
+    buf: Vec<u8>,
+}
+
+impl<R: tokio::io::AsyncRead + Unpin> LineReader<R> {
+    fn new(inner: R) -> Self {
+        Self {
+            reader: tokio::io::BufReader::new(inner),
+            buf: Vec::new(),
+        }
+    }
+
+    /// The next line without its line ending, or `None` at end of output
+    async fn next_line(&mut self) -> Option<String> {
+        use tokio::io::AsyncBufReadExt;
+
+        let read = self.reader.read_until(b'\n', &mut self.buf).await.ok()?;
+        if read == 0 && self.buf.is_empty() {
+            return None;
+        }
+        let line = String::from_utf8_lossy(&self.buf)
+            .trim_end_matches(['\n', '\r'])
+            .to_string();
+        self.buf.clear();
+        Some(line)
+    }
+}
+
+/// Read the next line from a reader that may already be closed
+///
+/// A closed reader never yields, leaving the other `select!` branches to run.
+async fn next_line<R: tokio::io::AsyncRead + Unpin>(
+    reader: &mut Option<LineReader<R>>,
+) -> Option<String> {
+    match reader {
+        Some(reader) => reader.next_line().await,
+        None => std::future::pending().await,
+    }
+}
+
+/// Kill a test command and everything it started
+async fn kill_process_group(child: &mut tokio::process::Child) {
+    #[cfg(unix)]
+    if let Some(pid) = child.id() {
+        signal_process_group(pid, libc::SIGKILL);
+    }
+    let _ = child.kill().await;
+}
+
+/// A fresh directory for report files
+fn report_dir() -> PathBuf {
+    static COUNTER: std::sync::atomic::AtomicU32 = std::sync::atomic::AtomicU32::new(0);
+    let n = COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+    let dir = std::env::temp_dir().join(format!("murmur-tests-{}-{}", std::process::id(), n));
+    let _ = std::fs::create_dir_all(&dir);
+    dir
+}
+
+/// Report files written by a suite's run
+///
+/// These are the files in `report_dir`, the custom command's configured
+/// report and, for Maven and Gradle, the JUnit reports in the build
+/// directories. Files in the source tree count only if written since
+/// `since`.
+fn report_files(
+    suite: &TestSuite,
+    workdir: &Path,
+    report_dir: &Path,
+    since: SystemTime,
+) -> Vec<PathBuf> {
+    let is_fresh = |path: &Path| {
+        std::fs::metadata(path)
+            .and_then(|m| m.modified())
+            .is_ok_and(|modified| modified >= since)
+    };
+
+    let mut files: Vec<PathBuf> = std::fs::read_dir(report_dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|e| e.path())
+                .filter(|p| p.is_file())
+                .collect()
+        })
+        .unwrap_or_default();
+    files.sort();
+
+    match suite.kind {
+        SuiteKind::Framework(framework) => {
+            if let Some(dir_name) = framework.build_report_dir() {
+                find_build_reports(workdir, dir_name, false, 0, &mut files);
+                files.retain(|p| p.starts_with(report_dir) || is_fresh(p));
+            }
+        }
+        SuiteKind::Custom(ref custom) => {
+            if let Some(ref report) = custom.report {
+                let path = workdir.join(report);
+                if is_fresh(&path) {
+                    files.push(path);
+                }
+            }
+        }
+    }
+    files
+}
+
+/// Find `TEST-*.xml` reports inside directories named `dir_name`
+fn find_build_reports(
+    dir: &Path,
+    dir_name: &str,
+    inside: bool,
+    depth: usize,
+    found: &mut Vec<PathBuf>,
+) {
+    const MAX_DEPTH: usize = 8;
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    let mut paths: Vec<PathBuf> = entries.flatten().map(|e| e.path()).collect();
+    paths.sort();
+
+    for path in paths {
+        let name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        if path.is_dir() {
+            if depth < MAX_DEPTH && !name.starts_with('.') && name != "node_modules" {
+                find_build_reports(
+                    &path,
+                    dir_name,
+                    inside || name == dir_name,
+                    depth + 1,
+                    found,
+                );
+            }
+        } else if inside && name.starts_with("TEST-") && name.ends_with(".xml") {
+            found.push(path);
+        }
+    }
+}
+
+/// Record a test, keeping the worst outcome if it was already reported
+fn record_test(tests: &mut Vec<TestCase>, name: &str, outcome: TestOutcome) {
+    match tests.iter_mut().find(|t| t.name == name) {
+        Some(existing) if outcome == TestOutcome::Failed => existing.outcome = outcome,
+        Some(_) => {}
+        None => tests.push(TestCase::new(name, outcome)),
+    }
+}
+
+/// Strip a trailing duration such as " (5 ms)", " (0.01s)" or " 12ms"
+fn strip_duration(name: &str) -> &str {
+    let is_number = |s: &str| !s.is_empty() && s.parse::<f64>().is_ok();
+    if let Some((rest, inner)) = name
+        .strip_suffix(')')
+        .and_then(|rest| rest.rsplit_once(" ("))
+    {
+        let inner = inner.replace(' ', "");
+        let value = inner.strip_suffix("ms").or_else(|| inner.strip_suffix('s'));
+        if value.is_some_and(is_number) {
+            return rest.trim_end();
+        }
+    }
+    match name.rsplit_once(' ') {
+        Some((rest, last)) if last.strip_suffix("ms").is_some_and(is_number) => rest.trim_end(),
+        _ => name,
+    }
+}
+
+/// Parse the individual "test name ... ok" lines of Cargo test output
+///
+/// Test binaries of different crates can contain tests with the same path,
+/// so repeated names get a " (2)", " (3)" ... suffix in order of appearance.
+fn parse_cargo_tests(stdout: &str) -> Vec<TestCase> {
+    let mut tests = Vec::new();
+    let mut seen: HashMap<String, u32> = HashMap::new();
+
+    for line in stdout.lines() {
+        let Some((name, status)) = line
+            .strip_prefix("test ")
+            .and_then(|rest| rest.rsplit_once(" ... "))
+        else {
+            continue;
+        };
+        let outcome = if status.starts_with("ok") {
+            TestOutcome::Passed
+        } else if status.starts_with("FAILED") {
+            TestOutcome::Failed
+        } else if status.starts_with("ignored") {
+            TestOutcome::Skipped
+        } else {
+            continue;
+        };
+
+        let count = seen.entry(name.to_string()).or_insert(0);
+        *count += 1;
+        let name = if *count > 1 {
+            format!("{} ({})", name, count)
+        } else {
+            name.to_string()
+        };
+        tests.push(TestCase::new(name, outcome));
+    }
+
+    attach_cargo_failures(stdout, &mut tests);
+    tests
+}
+
+/// Fill in failure messages from the "---- name stdout ----" sections
+fn attach_cargo_failures(stdout: &str, tests: &mut [TestCase]) {
+    let mut sections: Vec<(&str, String)> = Vec::new();
+    let mut in_section = false;
+    for line in stdout.lines() {
+        if let Some(name) = line
+            .strip_prefix("---- ")
+            .and_then(|rest| rest.strip_suffix(" stdout ----"))
+        {
+            sections.push((name, String::new()));
+            in_section = true;
+        } else if line == "failures:" || line.starts_with("test result:") {
+            // The list of failed names follows the captured output
+            in_section = false;
+        } else if in_section {
+            if let Some((_, output)) = sections.last_mut() {
+                output.push_str(line);
+                output.push('\n');
+            }
+        }
+    }
+
+    for (name, output) in sections {
+        let (location, message) = parse_panic(&output);
+        if let Some(test) = tests
+            .iter_mut()
+            .find(|t| t.name == name && t.outcome == TestOutcome::Failed && t.message.is_none())
+        {
+            test.location = location;
+            test.message = message;
+        }
+    }
+}
+
 /// Parse Cargo test output
 fn parse_cargo_output(stdout: &str, stderr: &str) -> TestResults {
     let mut passed = 0u32;
     let mut failed = 0u32;
     let mut ignored = 0u32;
+    let tests = parse_cargo_tests(stdout);
 
     // Look for the summary line: "test result: ok. X passed; Y failed; Z ignored"
     for line in stdout.lines().chain(stderr.lines()) {
@@ -403,6 +1670,9 @@ fn parse_cargo_output(stdout: &str, stderr: &str) -> TestResults {
         duration_ms: 0,
         output: String::new(),
         execution_error: None,
+        tests,
+        timed_out: false,
+        suites: Vec::new(),
     }
 }
 
@@ -411,9 +1681,25 @@ fn parse_pytest_output(stdout: &str) -> TestResults {
     let mut passed = 0u32;
     let mut failed = 0u32;
     let mut skipped = 0u32;
+    let mut tests = Vec::new();
 
     // Look for summary line: "=== 5 passed, 2 failed, 1 skipped ==="
     for line in stdout.lines() {
+        // Verbose lines: "test_example.py::test_one PASSED    [ 20%]"
+        let words: Vec<&str> = line.split_whitespace().collect();
+        if words.len() >= 2 && words[0].contains("::") {
+            let outcome = match words[1] {
+                "PASSED" | "XPASS" => Some(TestOutcome::Passed),
+                "FAILED" | "ERROR" => Some(TestOutcome::Failed),
+                "SKIPPED" | "XFAIL" => Some(TestOutcome::Skipped),
+                _ => None,
+            };
+            if let Some(outcome) = outcome {
+                record_test(&mut tests, words[0], outcome);
+                continue;
+            }
+        }
+
         // Check for the summary line with === markers and result keywords
         if (line.contains("passed") || line.contains("failed") || line.contains("skipped"))
             && line.contains("===")
@@ -445,6 +1731,9 @@ fn parse_pytest_output(stdout: &str) -> TestResults {
         duration_ms: 0,
         output: String::new(),
         execution_error: None,
+        tests,
+        timed_out: false,
+        suites: Vec::new(),
     }
 }
 
@@ -454,11 +1743,27 @@ fn parse_unittest_output(stdout: &str, stderr: &str) -> TestResults {
     let mut failed = 0u32;
     let mut errors = 0u32;
     let mut skipped = 0u32;
+    let mut tests = Vec::new();
 
     let combined = format!("{}\n{}", stdout, stderr);
 
     // Look for "Ran X tests" and "OK" or "FAILED"
     for line in combined.lines() {
+        // Verbose lines: "test_one (test_module.TestCase.test_one) ... ok"
+        if let Some((name, status)) = line.rsplit_once(" ... ") {
+            if name.contains(" (") {
+                let outcome = match status.trim() {
+                    "ok" | "expected failure" => Some(TestOutcome::Passed),
+                    "FAIL" | "ERROR" | "unexpected success" => Some(TestOutcome::Failed),
+                    s if s.starts_with("skipped") => Some(TestOutcome::Skipped),
+                    _ => None,
+                };
+                if let Some(outcome) = outcome {
+                    record_test(&mut tests, name.trim(), outcome);
+                    continue;
+                }
+            }
+        }
         if line.starts_with("Ran ") && line.contains(" test") {
             // "Ran 5 tests in 0.001s"
             if let Some(num) = line
@@ -504,6 +1809,9 @@ fn parse_unittest_output(stdout: &str, stderr: &str) -> TestResults {
         duration_ms: 0,
         output: String::new(),
         execution_error: None,
+        tests,
+        timed_out: false,
+        suites: Vec::new(),
     }
 }
 
@@ -513,10 +1821,36 @@ fn parse_jest_output(stdout: &str, stderr: &str) -> TestResults {
     let mut failed = 0u32;
     let mut skipped = 0u32;
 
+    let mut tests = Vec::new();
     let combined = format!("{}\n{}", stdout, stderr);
 
     // Look for "Tests:       2 passed, 1 skipped, 3 total"
     for line in combined.lines() {
+        // Verbose lines: "✓ adds numbers (5 ms)", "✕ ...", "○ skipped ..."
+        let trimmed = line.trim();
+        let outcome = match trimmed.chars().next() {
+            Some('✓' | '√') => Some(TestOutcome::Passed),
+            Some('✕' | '×') => Some(TestOutcome::Failed),
+            Some('○' | '↓' | '✎') => Some(TestOutcome::Skipped),
+            _ => None,
+        };
+        if let Some(outcome) = outcome {
+            let rest = trimmed
+                .split_once(char::is_whitespace)
+                .map_or("", |(_, rest)| rest.trim());
+            let rest = rest.strip_prefix("skipped ").unwrap_or(rest);
+            let name = strip_duration(rest);
+            // Vitest also marks whole files: "✓ src/math.test.ts (3 tests)"
+            let is_file = name
+                .strip_suffix(')')
+                .and_then(|n| n.rsplit_once(" ("))
+                .is_some_and(|(_, count)| count.starts_with(|c: char| c.is_ascii_digit()));
+            if !name.is_empty() && !is_file {
+                record_test(&mut tests, name, outcome);
+            }
+            continue;
+        }
+
         if line.contains("Tests:") {
             // Strip "Tests:" prefix and parse the rest
             if let Some(rest) = line.split("Tests:").nth(1) {
@@ -545,6 +1879,9 @@ fn parse_jest_output(stdout: &str, stderr: &str) -> TestResults {
         duration_ms: 0,
         output: String::new(),
         execution_error: None,
+        tests,
+        timed_out: false,
+        suites: Vec::new(),
     }
 }
 
@@ -553,13 +1890,37 @@ fn parse_mocha_output(stdout: &str) -> TestResults {
     let mut passed = 0u32;
     let mut failed = 0u32;
     let mut pending = 0u32;
+    let mut tests = Vec::new();
+    let mut in_summary = false;
 
     // Mocha output: "  5 passing (10ms)"
     //               "  2 failing"
     //               "  1 pending"
     for line in stdout.lines() {
         let line = line.trim();
+
+        // Individual tests come before the summary, which repeats the
+        // failures with their numbers
+        if !in_summary {
+            let numbered = line
+                .split_once(") ")
+                .filter(|(n, _)| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()));
+            let case = if let Some(name) = line.strip_prefix(['✓', '✔']) {
+                Some((name, TestOutcome::Passed))
+            } else if let Some((_, name)) = numbered {
+                Some((name, TestOutcome::Failed))
+            } else {
+                line.strip_prefix("- ")
+                    .map(|name| (name, TestOutcome::Skipped))
+            };
+            if let Some((name, outcome)) = case {
+                record_test(&mut tests, strip_duration(name.trim()), outcome);
+                continue;
+            }
+        }
+
         if line.contains("passing") {
+            in_summary = true;
             if let Some(num) = line.split_whitespace().next() {
                 passed = num.parse().unwrap_or(0);
             }
@@ -581,6 +1942,9 @@ fn parse_mocha_output(stdout: &str) -> TestResults {
         duration_ms: 0,
         output: String::new(),
         execution_error: None,
+        tests,
+        timed_out: false,
+        suites: Vec::new(),
     }
 }
 
@@ -596,46 +1960,316 @@ fn parse_go_output(stdout: &str, stderr: &str) -> TestResults {
     // "--- PASS: TestFoo (0.00s)"
     // "--- FAIL: TestBar (0.01s)"
     // "--- SKIP: TestBaz (0.00s)"
+    let mut tests = Vec::new();
     for line in combined.lines() {
-        if line.contains("--- PASS:") {
+        let outcome = if line.contains("--- PASS:") {
             passed += 1;
+            TestOutcome::Passed
         } else if line.contains("--- FAIL:") {
             failed += 1;
+            TestOutcome::Failed
         } else if line.contains("--- SKIP:") {
             skipped += 1;
+            TestOutcome::Skipped
+        } else {
+            continue;
+        };
+        if let Some(name) = line
+            .split_once(':')
+            .and_then(|(_, rest)| rest.split_whitespace().next())
+        {
+            record_test(&mut tests, name, outcome);
+        }
+    }
+
+    // Also check for "ok" and "FAIL" package summaries
+    // "ok      package/name    0.005s"
+    // "FAIL    package/name    0.005s"
+    if passed == 0 && failed == 0 {
+        for line in combined.lines() {
+            if line.starts_with("ok") || line.starts_with("FAIL") {
+                // If we see package summaries but no individual test results,
+                // at least count the packages
+                if line.starts_with("ok") {
+                    passed += 1;
+                } else {
+                    failed += 1;
+                }
+            }
+        }
+    }
+
+    TestResults {
+        passed,
+        failed,
+        skipped,
+        duration_ms: 0,
+        output: String::new(),
+        execution_error: None,
+        tests,
+        timed_out: false,
+        suites: Vec::new(),
+    }
+}
+
+/// Numbers directly before any of `words`, summed
+///
+/// For summary lines such as "3 passed, 1 failed" or "5 examples, 1 failure".
+fn count_before(line: &str, words: &[&str]) -> u32 {
+    let tokens: Vec<&str> = line
+        .split(|c: char| c.is_whitespace() || matches!(c, ',' | ';' | '(' | ')' | '.'))
+        .filter(|t| !t.is_empty())
+        .collect();
+    tokens
+        .windows(2)
+        .filter(|pair| words.contains(&pair[1]))
+        .filter_map(|pair| pair[0].parse::<u32>().ok())
+        .sum()
+}
+
+/// The number after `label` in a line such as "Tests run: 5, Failures: 1"
+fn count_after(line: &str, label: &str) -> u32 {
+    line.split_once(label)
+        .and_then(|(_, rest)| {
+            rest.trim_start()
+                .split(|c: char| !c.is_ascii_digit())
+                .next()
+                .and_then(|n| n.parse().ok())
+        })
+        .unwrap_or(0)
+}
+
+/// Parse cargo-nextest output
+///
+/// Nextest prints a status line per test, on stderr:
+/// "        PASS [   0.004s] my-crate tests::adds".
+fn parse_nextest_output(stdout: &str, stderr: &str) -> TestResults {
+    let mut tests = Vec::new();
+    let combined = format!("{}\n{}", stdout, stderr);
+
+    for line in combined.lines() {
+        let line = line.trim();
+        let Some((status, rest)) = line.split_once(' ') else {
+            continue;
+        };
+        let outcome = match status {
+            "PASS" | "LEAK" => TestOutcome::Passed,
+            "SKIP" => TestOutcome::Skipped,
+            "FAIL" | "TIMEOUT" | "ABORT" | "LEAK-FAIL" => TestOutcome::Failed,
+            s if s.starts_with("SIG") => TestOutcome::Failed,
+            _ => continue,
+        };
+        if let Some((_, name)) = rest
+            .trim_start()
+            .strip_prefix('[')
+            .and_then(|r| r.split_once("] "))
+        {
+            record_test(&mut tests, name.trim(), outcome);
+        }
+    }
+
+    TestResults::from_tests(tests)
+}
+
+/// Parse Maven Surefire output
+///
+/// Each module ends with a "Tests run: 5, Failures: 1, Errors: 0, Skipped: 1"
+/// total; the per-class lines also give a "Time elapsed" and are skipped.
+fn parse_maven_output(stdout: &str) -> TestResults {
+    let mut results = TestResults::empty();
+
+    for line in stdout.lines() {
+        if !line.contains("Tests run:") || line.contains("Time elapsed") {
+            continue;
+        }
+        let run = count_after(line, "Tests run:");
+        let failed = count_after(line, "Failures:") + count_after(line, "Errors:");
+        let skipped = count_after(line, "Skipped:");
+        results.passed += run.saturating_sub(failed + skipped);
+        results.failed += failed;
+        results.skipped += skipped;
+    }
+
+    results
+}
+
+/// Parse Gradle output
+///
+/// Gradle only lists failed tests by default ("CalcTest > divides() FAILED")
+/// and sums up with "5 tests completed, 1 failed, 1 skipped".
+fn parse_gradle_output(stdout: &str, stderr: &str) -> TestResults {
+    let mut results = TestResults::empty();
+    let combined = format!("{}\n{}", stdout, stderr);
+
+    for line in combined.lines() {
+        if line.contains(" tests completed") || line.contains(" test completed") {
+            let total = count_before(line, &["tests", "test"]);
+            let failed = count_before(line, &["failed"]);
+            let skipped = count_before(line, &["skipped"]);
+            results.passed += total.saturating_sub(failed + skipped);
+            results.failed += failed;
+            results.skipped += skipped;
+        }
+    }
+
+    results
+}
+
+/// Parse `dotnet test` output at normal console verbosity
+///
+/// Tests are listed as "  Passed Calc.Tests.Adds [12 ms]"; the totals come
+/// from "Failed!  - Failed: 1, Passed: 4, Skipped: 0, Total: 5".
+fn parse_dotnet_output(stdout: &str) -> TestResults {
+    let mut tests = Vec::new();
+    let (mut passed, mut failed, mut skipped) = (0, 0, 0);
+
+    for line in stdout.lines() {
+        let trimmed = line.trim();
+        if let Some((status, rest)) = trimmed.split_once(' ') {
+            let outcome = match status {
+                "Passed" => Some(TestOutcome::Passed),
+                "Failed" => Some(TestOutcome::Failed),
+                "Skipped" => Some(TestOutcome::Skipped),
+                _ => None,
+            };
+            if let Some((name, _)) = rest.strip_suffix(']').and_then(|r| r.rsplit_once(" [")) {
+                if let Some(outcome) = outcome {
+                    record_test(&mut tests, name.trim(), outcome);
+                    continue;
+                }
+            }
+        }
+        if trimmed.starts_with("Passed!") || trimmed.starts_with("Failed!") {
+            passed += count_after(trimmed, "Passed:");
+            failed += count_after(trimmed, "Failed:");
+            skipped += count_after(trimmed, "Skipped:");
+        }
+    }
+
+    if tests.is_empty() {
+        TestResults {
+            passed,
+            failed,
+            skipped,
+            ..TestResults::empty()
+        }
+    } else {
+        TestResults::from_tests(tests)
+    }
+}
+
+/// Parse RSpec output
+///
+/// The documentation format doesn't give full test names, so only the
+/// "5 examples, 1 failure, 1 pending" summary is used.
+fn parse_rspec_output(stdout: &str) -> TestResults {
+    let mut results = TestResults::empty();
+
+    for line in stdout.lines() {
+        if line.contains(" example") && line.contains(" failure") {
+            let total = count_before(line, &["examples", "example"]);
+            let failed = count_before(line, &["failures", "failure"]);
+            let pending = count_before(line, &["pending"]);
+            results.passed += total.saturating_sub(failed + pending);
+            results.failed += failed;
+            results.skipped += pending;
+        }
+    }
+
+    results
+}
+
+/// Parse PHPUnit output
+///
+/// Either "OK (5 tests, 10 assertions)" or
+/// "Tests: 5, Assertions: 8, Failures: 1, Errors: 1, Skipped: 1.".
+fn parse_phpunit_output(stdout: &str) -> TestResults {
+    let mut results = TestResults::empty();
+
+    for line in stdout.lines() {
+        if let Some(rest) = line.strip_prefix("OK (") {
+            results.passed = count_before(rest, &["tests", "test"]);
+        } else if line.starts_with("Tests:") {
+            let total = count_after(line, "Tests:");
+            let failed = count_after(line, "Failures:") + count_after(line, "Errors:");
+            let skipped = count_after(line, "Skipped:") + count_after(line, "Incomplete:");
+            results.passed = total.saturating_sub(failed + skipped);
+            results.failed = failed;
+            results.skipped = skipped;
         }
     }
 
-    // Also check for "ok" and "FAIL" package summaries
-    // "ok      package/name    0.005s"
-    // "FAIL    package/name    0.005s"
-    if passed == 0 && failed == 0 {
-        for line in combined.lines() {
-            if line.starts_with("ok") || line.starts_with("FAIL") {
-                // If we see package summaries but no individual test results,
-                // at least count the packages
-                if line.starts_with("ok") {
-                    passed += 1;
-                } else {
-                    failed += 1;
-                }
-            }
-        }
+    results
+}
+
+/// Parse CTest output
+///
+/// Tests are listed as "1/3 Test #1: adds .........   Passed    0.01 sec"
+/// or "2/3 Test #2: divides ......***Failed    0.01 sec".
+fn parse_ctest_output(stdout: &str) -> TestResults {
+    let mut tests = Vec::new();
+
+    for line in stdout.lines() {
+        let Some((_, rest)) = line.split_once("Test #") else {
+            continue;
+        };
+        let Some((_, rest)) = rest.split_once(": ") else {
+            continue;
+        };
+        let Some((name, status)) = rest.split_once(" .") else {
+            continue;
+        };
+        let status = status.trim_start_matches(['.', ' ', '*']);
+        let outcome = if status.starts_with("Passed") {
+            TestOutcome::Passed
+        } else if status.starts_with("Skipped") || status.starts_with("Not Run") {
+            TestOutcome::Skipped
+        } else {
+            TestOutcome::Failed
+        };
+        record_test(&mut tests, name.trim(), outcome);
     }
 
-    TestResults {
-        passed,
-        failed,
-        skipped,
-        duration_ms: 0,
-        output: String::new(),
-        execution_error: None,
+    TestResults::from_tests(tests)
+}
+
+/// Parse Bazel test output
+///
+/// Each test target is listed with its status, as in
+/// "//calc:calc_test    PASSED in 0.4s" or "//calc:div_test   FAILED in 0.3s".
+fn parse_bazel_output(stdout: &str, stderr: &str) -> TestResults {
+    let mut tests = Vec::new();
+    let combined = format!("{}\n{}", stdout, stderr);
+
+    for line in combined.lines() {
+        let mut words = line.split_whitespace();
+        let Some(target) = words
+            .next()
+            .filter(|t| t.starts_with("//") || t.starts_with('@'))
+        else {
+            continue;
+        };
+        let status: Vec<&str> = words.collect();
+        let has = |word: &str| status.iter().any(|w| w.trim_end_matches(',') == word);
+        let outcome = if has("PASSED") || has("FLAKY") {
+            TestOutcome::Passed
+        } else if has("FAILED") || has("TIMEOUT") || has("INCOMPLETE") {
+            TestOutcome::Failed
+        } else if has("SKIPPED") || status.starts_with(&["NO", "STATUS"]) {
+            TestOutcome::Skipped
+        } else {
+            continue;
+        };
+        record_test(&mut tests, target, outcome);
     }
+
+    TestResults::from_tests(tests)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::workflow::test_config::CustomCommand;
     use std::fs;
     use tempfile::TempDir;
 
@@ -867,6 +2501,523 @@ FAIL
         assert_eq!(results.skipped, 1);
     }
 
+    fn names(results: &TestResults, outcome: TestOutcome) -> Vec<&str> {
+        results
+            .tests
+            .iter()
+            .filter(|t| t.outcome == outcome)
+            .map(|t| t.name.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_cargo_test_cases() {
+        let stdout = r#"
+running 3 tests
+test parser::tests::test_parse ... ok
+test parser::tests::test_empty ... FAILED
+test parser::tests::test_slow ... ignored, needs network
+
+test result: FAILED. 1 passed; 1 failed; 1 ignored
+
+running 1 test
+test parser::tests::test_parse ... ok
+
+test result: ok. 1 passed; 0 failed; 0 ignored
+"#;
+        let results = parse_cargo_output(stdout, "");
+        assert_eq!(
+            names(&results, TestOutcome::Passed),
+            ["parser::tests::test_parse", "parser::tests::test_parse (2)"]
+        );
+        assert_eq!(
+            names(&results, TestOutcome::Failed),
+            ["parser::tests::test_empty"]
+        );
+        assert_eq!(
+            names(&results, TestOutcome::Skipped),
+            ["parser::tests::test_slow"]
+        );
+    }
+
+    #[test]
+    fn test_parse_cargo_failure_details() {
+        let stdout = r#"
+running 2 tests
+test parser::tests::test_parse ... ok
+test parser::tests::test_empty ... FAILED
+
+failures:
+
+---- parser::tests::test_empty stdout ----
+
+thread 'parser::tests::test_empty' panicked at src/parser.rs:42:9:
+expected an empty list
+note: run with `RUST_BACKTRACE=1` environment variable to display a backtrace
+
+
+failures:
+    parser::tests::test_empty
+
+test result: FAILED. 1 passed; 1 failed; 0 ignored
+"#;
+        let results = parse_cargo_output(stdout, "");
+        let failed: Vec<&TestCase> = results.failures().collect();
+        assert_eq!(failed.len(), 1);
+        assert_eq!(failed[0].location.as_deref(), Some("src/parser.rs:42:9"));
+        assert_eq!(failed[0].message.as_deref(), Some("expected an empty list"));
+    }
+
+    #[test]
+    fn test_results_from_tests() {
+        let results = TestResults::from_tests(vec![
+            TestCase::new("a", TestOutcome::Passed).with_duration_ms(3),
+            TestCase::new("b", TestOutcome::Failed).with_message("boom"),
+            TestCase::new("c", TestOutcome::Skipped),
+        ]);
+        assert_eq!((results.passed, results.failed, results.skipped), (1, 1, 1));
+        assert_eq!(results.failures().count(), 1);
+    }
+
+    #[test]
+    fn test_report_command() {
+        let dir = TempDir::new().unwrap();
+        let report = dir.path().join("reports");
+
+        let (_, format) = TestFramework::Go
+            .report_command(dir.path(), &report)
+            .unwrap();
+        assert_eq!(format, ReportFormat::GoJson);
+        let (cmd, format) = TestFramework::Pytest
+            .report_command(dir.path(), &report)
+            .unwrap();
+        assert_eq!(format, ReportFormat::JunitXml);
+        assert!(cmd
+            .get_args()
+            .any(|a| a.to_string_lossy().starts_with("--junitxml=")));
+
+        // Jest needs the jest-junit reporter installed
+        fs::write(
+            dir.path().join("package.json"),
+            r#"{"devDependencies": {"jest": "^29.0.0"}}"#,
+        )
+        .unwrap();
+        assert!(TestFramework::Jest
+            .report_command(dir.path(), &report)
+            .is_none());
+        assert!(TestFramework::PythonUnittest
+            .report_command(dir.path(), &report)
+            .is_none());
+
+        let (cmd, format) = TestFramework::Dotnet
+            .report_command(dir.path(), &report)
+            .unwrap();
+        assert_eq!(format, ReportFormat::Trx);
+        assert!(cmd.get_args().any(|a| a == report.as_os_str()));
+        let (_, format) = TestFramework::RSpec
+            .report_command(dir.path(), &report)
+            .unwrap();
+        assert_eq!(format, ReportFormat::RspecJson);
+        assert!(TestFramework::Bazel
+            .report_command(dir.path(), &report)
+            .is_none());
+    }
+
+    #[test]
+    fn test_detect_new_frameworks() {
+        let cases: &[(&str, &str, TestFramework)] = &[
+            ("pom.xml", "<project/>", TestFramework::Maven),
+            ("build.gradle.kts", "plugins {}", TestFramework::Gradle),
+            ("Calc.csproj", "<Project/>", TestFramework::Dotnet),
+            (".rspec", "--require spec_helper", TestFramework::RSpec),
+            ("phpunit.xml.dist", "<phpunit/>", TestFramework::PhpUnit),
+            ("CMakeLists.txt", "enable_testing()", TestFramework::CTest),
+            (
+                "MODULE.bazel",
+                "module(name = \"calc\")",
+                TestFramework::Bazel,
+            ),
+        ];
+        for (file, content, framework) in cases {
+            let dir = TempDir::new().unwrap();
+            fs::write(dir.path().join(file), content).unwrap();
+            assert_eq!(
+                TestFramework::detect(dir.path()),
+                Some(*framework),
+                "{}",
+                file
+            );
+        }
+
+        // A CMake project without tests isn't a CTest suite
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join("CMakeLists.txt"), "project(calc)").unwrap();
+        assert_eq!(TestFramework::detect(dir.path()), None);
+    }
+
+    #[test]
+    fn test_detect_all_polyglot() {
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join("Cargo.toml"), "[package]").unwrap();
+        fs::create_dir(dir.path().join(".config")).unwrap();
+        fs::write(dir.path().join(".config/nextest.toml"), "").unwrap();
+        fs::write(
+            dir.path().join("package.json"),
+            r#"{"devDependencies": {"vitest": "^1.0.0"}}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            TestFramework::detect_all(dir.path()),
+            [TestFramework::Nextest, TestFramework::Vitest]
+        );
+
+        // Guesses from a bare package.json only apply on their own
+        fs::write(dir.path().join("package.json"), r#"{"name": "tools"}"#).unwrap();
+        assert_eq!(
+            TestFramework::detect_all(dir.path()),
+            [TestFramework::Nextest]
+        );
+        fs::remove_file(dir.path().join("Cargo.toml")).unwrap();
+        assert_eq!(TestFramework::detect_all(dir.path()), [TestFramework::Jest]);
+    }
+
+    #[test]
+    fn test_parse_nextest_output() {
+        let stderr = r#"
+    Starting 3 tests across 1 binary (1 test skipped)
+        PASS [   0.004s] calc tests::adds
+        FAIL [   0.005s] calc tests::divides
+        SKIP [         ] calc tests::network
+     Summary [   0.010s] 2 tests run: 1 passed, 1 failed, 1 skipped
+        FAIL [   0.005s] calc tests::divides
+"#;
+        let results = parse_nextest_output("", stderr);
+        assert_eq!((results.passed, results.failed, results.skipped), (1, 1, 1));
+        assert_eq!(
+            names(&results, TestOutcome::Failed),
+            ["calc tests::divides"]
+        );
+    }
+
+    #[test]
+    fn test_parse_jvm_output() {
+        let maven = r#"
+[INFO] Tests run: 2, Failures: 0, Errors: 0, Skipped: 0, Time elapsed: 0.05 s - in calc.AddTest
+[ERROR] Tests run: 3, Failures: 1, Errors: 0, Skipped: 1, Time elapsed: 0.02 s <<< FAILURE! - in calc.DivTest
+[INFO] Results:
+[ERROR] Tests run: 5, Failures: 1, Errors: 0, Skipped: 1
+"#;
+        let results = parse_maven_output(maven);
+        assert_eq!((results.passed, results.failed, results.skipped), (3, 1, 1));
+
+        let gradle = "CalcTest > divides() FAILED\n    org.opentest4j.AssertionFailedError\n\n5 tests completed, 1 failed, 1 skipped\n";
+        let results = parse_gradle_output(gradle, "");
+        assert_eq!((results.passed, results.failed, results.skipped), (3, 1, 1));
+    }
+
+    #[test]
+    fn test_parse_dotnet_output() {
+        let stdout = r#"
+  Passed Calc.Tests.Adds [12 ms]
+  Failed Calc.Tests.Divides [3 ms]
+  Error Message:
+   Assert.Equal() Failure
+  Skipped Calc.Tests.Network [1 ms]
+
+Failed!  - Failed:     1, Passed:     1, Skipped:     1, Total:     3, Duration: 20 ms - Calc.Tests.dll (net8.0)
+"#;
+        let results = parse_dotnet_output(stdout);
+        assert_eq!((results.passed, results.failed, results.skipped), (1, 1, 1));
+        assert_eq!(names(&results, TestOutcome::Failed), ["Calc.Tests.Divides"]);
+
+        let summary_only = parse_dotnet_output(
+            "Passed!  - Failed:     0, Passed:     4, Skipped:     0, Total:     4",
+        );
+        assert_eq!((summary_only.passed, summary_only.failed), (4, 0));
+        assert!(summary_only.tests.is_empty());
+    }
+
+    #[test]
+    fn test_parse_rspec_and_phpunit_output() {
+        let results =
+            parse_rspec_output("Finished in 0.01 seconds\n5 examples, 1 failure, 1 pending\n");
+        assert_eq!((results.passed, results.failed, results.skipped), (3, 1, 1));
+
+        let results = parse_phpunit_output("OK (5 tests, 10 assertions)\n");
+        assert_eq!((results.passed, results.failed), (5, 0));
+        let results = parse_phpunit_output(
+            "FAILURES!\nTests: 6, Assertions: 8, Failures: 1, Errors: 1, Skipped: 1.\n",
+        );
+        assert_eq!((results.passed, results.failed, results.skipped), (3, 2, 1));
+    }
+
+    #[test]
+    fn test_parse_ctest_output() {
+        let stdout = r#"
+    Start 1: adds
+1/3 Test #1: adds .............................   Passed    0.01 sec
+    Start 2: divides
+2/3 Test #2: divides ..........................***Failed    0.01 sec
+    Start 3: network
+3/3 Test #3: network ..........................***Skipped   0.00 sec
+
+67% tests passed, 1 tests failed out of 3
+"#;
+        let results = parse_ctest_output(stdout);
+        assert_eq!((results.passed, results.failed, results.skipped), (1, 1, 1));
+        assert_eq!(names(&results, TestOutcome::Failed), ["divides"]);
+    }
+
+    #[test]
+    fn test_parse_bazel_output() {
+        let stdout = r#"
+//calc:add_test                                                 PASSED in 0.4s
+//calc:div_test                                                 FAILED in 0.3s
+  /home/user/.cache/bazel/execroot/calc/bazel-out/k8-fastbuild/testlogs/calc/div_test/test.log
+//calc:flaky_test                                 FLAKY, failed in 1 out of 2 in 0.5s
+//calc:net_test                                              NO STATUS
+
+Executed 3 out of 4 tests: 2 tests pass and 1 fails locally.
+"#;
+        let results = parse_bazel_output(stdout, "");
+        assert_eq!((results.passed, results.failed, results.skipped), (2, 1, 1));
+        assert_eq!(names(&results, TestOutcome::Failed), ["//calc:div_test"]);
+    }
+
+    #[test]
+    fn test_parse_pytest_test_cases() {
+        let stdout = r#"
+test_example.py::test_one PASSED                                           [ 33%]
+test_example.py::test_two FAILED                                           [ 66%]
+test_example.py::test_two ERROR                                            [ 66%]
+test_example.py::test_three SKIPPED (no db)                                [100%]
+
+=========================== short test summary info ============================
+FAILED test_example.py::test_two - assert 1 == 2
+=================== 1 passed, 1 failed, 1 skipped, 1 error =====================
+"#;
+        let results = parse_pytest_output(stdout);
+        assert_eq!(results.tests.len(), 3);
+        assert_eq!(
+            results.outcome_of("test_example.py::test_two"),
+            Some(TestOutcome::Failed)
+        );
+        assert_eq!(
+            results.outcome_of("test_example.py::test_three"),
+            Some(TestOutcome::Skipped)
+        );
+    }
+
+    #[test]
+    fn test_parse_unittest_test_cases() {
+        let stderr = r#"
+test_add (test_math.TestMath.test_add) ... ok
+test_div (test_math.TestMath.test_div) ... FAIL
+test_net (test_math.TestMath.test_net) ... skipped 'offline'
+
+----------------------------------------------------------------------
+Ran 3 tests in 0.001s
+
+FAILED (failures=1, skipped=1)
+"#;
+        let results = parse_unittest_output("", stderr);
+        assert_eq!(results.failed, 1);
+        assert_eq!(
+            names(&results, TestOutcome::Passed),
+            ["test_add (test_math.TestMath.test_add)"]
+        );
+        assert_eq!(
+            names(&results, TestOutcome::Failed),
+            ["test_div (test_math.TestMath.test_div)"]
+        );
+        assert_eq!(
+            names(&results, TestOutcome::Skipped),
+            ["test_net (test_math.TestMath.test_net)"]
+        );
+    }
+
+    #[test]
+    fn test_parse_jest_test_cases() {
+        let stdout = r#"
+ PASS  tests/example.test.js
+  ✓ test one (5 ms)
+  ✕ test two (3 ms)
+  ○ skipped test three
+
+Tests:       1 passed, 1 failed, 1 skipped, 3 total
+"#;
+        let results = parse_jest_output(stdout, "");
+        assert_eq!(names(&results, TestOutcome::Passed), ["test one"]);
+        assert_eq!(names(&results, TestOutcome::Failed), ["test two"]);
+        assert_eq!(names(&results, TestOutcome::Skipped), ["test three"]);
+    }
+
+    #[test]
+    fn test_parse_vitest_test_cases() {
+        let stdout = r#"
+ ✓ src/math.test.ts (2 tests) 4ms
+   ✓ math > adds 1ms
+   × math > divides 2ms
+
+ Tests  1 failed | 1 passed (2)
+"#;
+        let results = parse_jest_output(stdout, "");
+        assert_eq!(names(&results, TestOutcome::Passed), ["math > adds"]);
+        assert_eq!(names(&results, TestOutcome::Failed), ["math > divides"]);
+    }
+
+    #[test]
+    fn test_parse_mocha_test_cases() {
+        let stdout = r#"
+  Example Tests
+    ✓ should pass test one
+    ✓ should pass test two (45ms)
+    1) should fail test three
+    - should skip test four
+
+  2 passing (15ms)
+  1 failing
+  1 pending
+
+  1) Example Tests
+       should fail test three:
+     AssertionError: expected 1 to equal 2
+"#;
+        let results = parse_mocha_output(stdout);
+        assert_eq!(
+            names(&results, TestOutcome::Passed),
+            ["should pass test one", "should pass test two"]
+        );
+        assert_eq!(
+            names(&results, TestOutcome::Failed),
+            ["should fail test three"]
+        );
+        assert_eq!(
+            names(&results, TestOutcome::Skipped),
+            ["should skip test four"]
+        );
+    }
+
+    #[test]
+    fn test_parse_go_test_cases() {
+        let stdout = r#"
+=== RUN   TestOne
+--- PASS: TestOne (0.00s)
+=== RUN   TestTwo
+    --- FAIL: TestTwo/empty (0.01s)
+--- FAIL: TestTwo (0.01s)
+"#;
+        let results = parse_go_output(stdout, "");
+        assert_eq!(names(&results, TestOutcome::Passed), ["TestOne"]);
+        assert_eq!(
+            names(&results, TestOutcome::Failed),
+            ["TestTwo/empty", "TestTwo"]
+        );
+    }
+
+    fn run_of(tests: &[(&str, TestOutcome)]) -> TestResults {
+        let count = |outcome| tests.iter().filter(|(_, o)| *o == outcome).count() as u32;
+        TestResults {
+            passed: count(TestOutcome::Passed),
+            failed: count(TestOutcome::Failed),
+            skipped: count(TestOutcome::Skipped),
+            tests: tests
+                .iter()
+                .map(|(name, outcome)| TestCase::new(*name, *outcome))
+                .collect(),
+            ..TestResults::empty()
+        }
+    }
+
+    #[test]
+    fn test_diff_ignores_preexisting_failures() {
+        let baseline = run_of(&[
+            ("old_pass", TestOutcome::Passed),
+            ("flaky", TestOutcome::Failed),
+        ]);
+
+        // Only the unrelated failure fails: not red
+        let red = run_of(&[
+            ("old_pass", TestOutcome::Passed),
+            ("flaky", TestOutcome::Failed),
+            ("new_test", TestOutcome::Passed),
+        ]);
+        let diff = red.diff(&baseline.tests, &[]);
+        assert!(red.is_red());
+        assert!(!diff.is_red());
+        assert!(diff.red_problem().unwrap().contains("new_test"));
+
+        let red = run_of(&[
+            ("old_pass", TestOutcome::Passed),
+            ("flaky", TestOutcome::Failed),
+            ("new_test", TestOutcome::Failed),
+        ]);
+        let diff = red.diff(&baseline.tests, &[]);
+        assert!(diff.is_red());
+        assert_eq!(diff.new_failing, ["new_test"]);
+
+        // Breaking a passing test isn't red, even with the new test failing
+        let broken = run_of(&[
+            ("old_pass", TestOutcome::Failed),
+            ("flaky", TestOutcome::Failed),
+            ("new_test", TestOutcome::Failed),
+        ]);
+        let diff = broken.diff(&baseline.tests, &[]);
+        assert_eq!(diff.regressed, ["old_pass"]);
+        assert!(diff.red_problem().unwrap().contains("previously passing"));
+
+        // The unrelated failure doesn't stop green
+        let green = run_of(&[
+            ("old_pass", TestOutcome::Passed),
+            ("flaky", TestOutcome::Failed),
+            ("new_test", TestOutcome::Passed),
+        ]);
+        let diff = green.diff(&baseline.tests, &["new_test".to_string()]);
+        assert!(!green.is_green());
+        assert!(diff.is_green());
+    }
+
+    #[test]
+    fn test_diff_green_problems() {
+        let baseline = [TestCase::new("old_pass", TestOutcome::Passed)];
+        let expected = ["new_test".to_string()];
+
+        // The new test silently stopped running
+        let run = run_of(&[
+            ("old_pass", TestOutcome::Passed),
+            ("other", TestOutcome::Passed),
+        ]);
+        let diff = run.diff(&baseline, &expected);
+        assert_eq!(diff.missing, ["new_test"]);
+        assert!(diff.green_problem().unwrap().contains("did not run"));
+
+        let run = run_of(&[
+            ("old_pass", TestOutcome::Failed),
+            ("new_test", TestOutcome::Passed),
+        ]);
+        let diff = run.diff(&baseline, &expected);
+        assert_eq!(diff.regressed, ["old_pass"]);
+        assert!(diff.green_problem().unwrap().contains("previously passing"));
+
+        let run = run_of(&[
+            ("old_pass", TestOutcome::Passed),
+            ("new_test", TestOutcome::Skipped),
+        ]);
+        assert!(!run.diff(&baseline, &expected).is_green());
+    }
+
+    #[test]
+    fn test_has_test_cases() {
+        assert!(TestResults::empty().has_test_cases());
+        assert!(run_of(&[("a", TestOutcome::Passed)]).has_test_cases());
+
+        // A failed build lists no tests
+        let mut broken = TestResults::empty();
+        broken.failed = 1;
+        assert!(!broken.has_test_cases());
+        assert!(!TestResults::with_error("no cargo".to_string()).has_test_cases());
+    }
+
     #[test]
     fn test_results_is_red() {
         let mut results = TestResults::empty();
@@ -900,6 +3051,9 @@ FAIL
             duration_ms: 150,
             output: String::new(),
             execution_error: None,
+            tests: Vec::new(),
+            timed_out: false,
+            suites: Vec::new(),
         };
         assert_eq!(results.summary(), "10 passed, 2 failed, 1 skipped (150ms)");
     }
@@ -931,12 +3085,157 @@ FAIL
         assert_eq!(runner.timeout, Duration::from_secs(60));
     }
 
-    #[test]
-    fn test_runner_no_framework_detected() {
+    #[tokio::test]
+    async fn test_runner_no_framework_detected() {
         let dir = TempDir::new().unwrap();
         let runner = TestRunner::new(dir.path());
         assert!(runner.framework().is_none());
-        let results = runner.run();
+        let results = runner.run().await;
         assert!(results.execution_error.is_some());
     }
+
+    fn has_python() -> bool {
+        std::process::Command::new("python")
+            .arg("--version")
+            .output()
+            .is_ok_and(|o| o.status.success())
+    }
+
+    #[tokio::test]
+    async fn test_runner_streams_output() {
+        if !has_python() {
+            return;
+        }
+        let dir = TempDir::new().unwrap();
+        fs::write(
+            dir.path().join("test_math.py"),
+            "import unittest\n\nclass TestMath(unittest.TestCase):\n    def test_add(self):\n        self.assertEqual(1 + 1, 2)\n",
+        )
+        .unwrap();
+
+        let mut lines = Vec::new();
+        let results = TestRunner::new(dir.path())
+            .with_framework(TestFramework::PythonUnittest)
+            .run_with_handler(&mut |line: &str| lines.push(line.to_string()))
+            .await;
+
+        assert!(results.is_green(), "{}", results.output);
+        assert!(!results.timed_out);
+        assert!(lines
+            .iter()
+            .any(|l| l.contains("test_add") && l.ends_with("ok")));
+    }
+
+    #[tokio::test]
+    async fn test_runner_custom_command() {
+        let dir = TempDir::new().unwrap();
+        let custom = |command: &str, format| {
+            vec![TestSuite::new(
+                ".",
+                SuiteKind::Custom(CustomCommand {
+                    command: command.to_string(),
+                    format,
+                    report: None,
+                }),
+            )]
+        };
+
+        let results = TestRunner::new(dir.path())
+            .with_suites(custom(
+                "printf 'ok 1 - adds\\nnot ok 2 - divides\\n'; exit 1",
+                CustomFormat::Tap,
+            ))
+            .run()
+            .await;
+        assert!(results.is_red(), "{}", results.summary());
+        assert_eq!(names(&results, TestOutcome::Failed), ["divides"]);
+
+        let results = TestRunner::new(dir.path())
+            .with_suites(custom("true", CustomFormat::ExitCode))
+            .run()
+            .await;
+        assert!(results.is_green(), "{}", results.summary());
+        assert!(results.tests.is_empty());
+
+        // Reports written to $MURMUR_TEST_REPORT_DIR are read
+        let results = TestRunner::new(dir.path())
+            .with_suites(custom(
+                r#"echo '<testsuite><testcase name="adds"/></testsuite>' > "$MURMUR_TEST_REPORT_DIR/junit.xml""#,
+                CustomFormat::Junit,
+            ))
+            .run()
+            .await;
+        assert_eq!(names(&results, TestOutcome::Passed), ["adds"]);
+    }
+
+    #[tokio::test]
+    async fn test_runner_runs_suites_for_changes() {
+        if !has_python() {
+            return;
+        }
+        let dir = TempDir::new().unwrap();
+        for (service, body) in [("api", "1 + 1, 2"), ("worker", "1 + 1, 3")] {
+            let path = dir.path().join(service);
+            fs::create_dir(&path).unwrap();
+            fs::write(
+                path.join("test_math.py"),
+                format!("import unittest\n\nclass TestMath(unittest.TestCase):\n    def test_add(self):\n        self.assertEqual({})\n", body),
+            )
+            .unwrap();
+        }
+        let runner = TestRunner::new(dir.path()).with_suites(vec![
+            TestSuite::new("api", SuiteKind::Framework(TestFramework::PythonUnittest)),
+            TestSuite::new(
+                "worker",
+                SuiteKind::Framework(TestFramework::PythonUnittest),
+            ),
+        ]);
+
+        let mut lines = Vec::new();
+        let all = runner
+            .run_with_handler(&mut |line: &str| lines.push(line.to_string()))
+            .await;
+        assert_eq!((all.passed, all.failed), (1, 1), "{}", all.output);
+        assert_eq!(all.suites, ["api", "worker"]);
+        assert!(lines.iter().any(|l| l == "==> worker (unittest)"));
+        let failed = all.failures().next().unwrap();
+        assert!(failed.name.starts_with("worker: test_add"));
+        assert_eq!(failed.suite.as_deref(), Some("worker"));
+
+        let api = runner
+            .run_for_changes(&["api/calc.py".to_string()], &mut ())
+            .await;
+        assert_eq!(api.suites, ["api"]);
+        assert!(api.is_green());
+
+        // Baseline tests of suites that didn't run aren't missing
+        let diff = api.diff(&all.tests, &[]);
+        assert!(diff.missing.is_empty(), "{:?}", diff.missing);
+    }
+
+    #[tokio::test]
+    async fn test_runner_times_out() {
+        if !has_python() {
+            return;
+        }
+        let dir = TempDir::new().unwrap();
+        fs::write(
+            dir.path().join("test_slow.py"),
+            "import time, unittest\n\nclass TestSlow(unittest.TestCase):\n    def test_slow(self):\n        time.sleep(60)\n",
+        )
+        .unwrap();
+
+        let start = Instant::now();
+        let results = TestRunner::new(dir.path())
+            .with_framework(TestFramework::PythonUnittest)
+            .with_timeout(Duration::from_millis(500))
+            .run()
+            .await;
+
+        assert!(start.elapsed() < Duration::from_secs(30));
+        assert!(results.timed_out);
+        assert!(!results.is_red());
+        assert!(!results.is_green());
+        assert!(results.summary().contains("timed out"));
+    }
 }
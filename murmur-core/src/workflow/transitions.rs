@@ -2,9 +2,12 @@
 //!
 //! This module provides async validation for TDD phase transitions,
 //! integrating with the TestRunner to enforce red/green requirements.
+//! When a baseline run listing individual tests is available, red requires
+//! the new tests to fail and green requires them to pass with no regressions;
+//! otherwise the aggregate counts are used.
 
 use super::tdd::{TddPhase, TddState, TddWorkflow};
-use super::test_runner::{TestResults, TestRunner};
+use super::test_runner::{compare_to_baseline, TestCase, TestDiff, TestResults, TestRunner};
 use crate::error::Result;
 use std::path::Path;
 
@@ -106,10 +109,18 @@ impl TddTransitionValidator for TddWorkflow {
         // For VerifyRed and VerifyGreen, we need test results
         let validation = to.validation_requirements();
 
+        let state = self.state();
+        let diff = test_results.and_then(|results| {
+            compare_to_baseline(results, state.baseline.as_deref(), &state.new_tests)
+        });
+
         if validation.requires_test_failure {
             match test_results {
                 Some(results) => {
-                    if results.is_green() {
+                    if let Some(blocked) = diff.as_ref().and_then(red_blocked) {
+                        return blocked;
+                    }
+                    if diff.is_none() && results.is_green() {
                         return TransitionResult::Blocked {
                             reason: "Tests should FAIL in red phase but all pass".to_string(),
                             suggestion:
@@ -117,12 +128,9 @@ impl TddTransitionValidator for TddWorkflow {
                                     .to_string(),
                         };
                     }
-                    if results.execution_error.is_some() {
+                    if let Some(error) = &results.execution_error {
                         return TransitionResult::Blocked {
-                            reason: format!(
-                                "Tests have execution errors: {}",
-                                results.execution_error.as_ref().unwrap()
-                            ),
+                            reason: format!("Tests have execution errors: {}", error),
                             suggestion: "Fix test setup errors before proceeding".to_string(),
                         };
                     }
@@ -139,18 +147,18 @@ impl TddTransitionValidator for TddWorkflow {
         if validation.requires_test_success {
             match test_results {
                 Some(results) => {
+                    if let Some(diff) = &diff {
+                        return green_blocked(diff).unwrap_or(TransitionResult::Allowed);
+                    }
                     if results.is_red() {
                         return TransitionResult::Blocked {
                             reason: format!("{} tests still failing", results.failed),
                             suggestion: "Fix implementation to make tests pass".to_string(),
                         };
                     }
-                    if results.execution_error.is_some() {
+                    if let Some(error) = &results.execution_error {
                         return TransitionResult::Blocked {
-                            reason: format!(
-                                "Tests have execution errors: {}",
-                                results.execution_error.as_ref().unwrap()
-                            ),
+                            reason: format!("Tests have execution errors: {}", error),
                             suggestion: "Fix test or implementation errors before proceeding"
                                 .to_string(),
                         };
@@ -182,7 +190,7 @@ impl TddTransitionValidator for TddWorkflow {
         if validation.requires_test_failure || validation.requires_test_success {
             let workdir = &self.state().workdir;
             let runner = TestRunner::new(workdir);
-            let results = runner.run();
+            let results = runner.run().await;
             Ok(self.validate_transition(to, Some(&results)))
         } else {
             Ok(self.validate_transition(to, None))
@@ -234,10 +242,33 @@ impl TddTransitionValidator for TddWorkflow {
     }
 }
 
+/// Blocked result for a per-test comparison that isn't red
+fn red_blocked(diff: &TestDiff) -> Option<TransitionResult> {
+    diff.red_problem().map(|reason| TransitionResult::Blocked {
+        reason,
+        suggestion:
+            "Write tests that verify unimplemented behavior, and make sure the new tests are run"
+                .to_string(),
+    })
+}
+
+/// Blocked result for a per-test comparison that isn't green
+fn green_blocked(diff: &TestDiff) -> Option<TransitionResult> {
+    diff.green_problem()
+        .map(|reason| TransitionResult::Blocked {
+            reason,
+            suggestion:
+                "Fix implementation to make the new tests pass without breaking existing ones"
+                    .to_string(),
+        })
+}
+
 /// Helper functions for running tests and validating phases
 pub struct PhaseValidator<'a> {
     workdir: &'a Path,
     test_runner: TestRunner,
+    baseline: Option<Vec<TestCase>>,
+    new_tests: Vec<String>,
 }
 
 impl<'a> PhaseValidator<'a> {
@@ -246,28 +277,66 @@ impl<'a> PhaseValidator<'a> {
         Self {
             workdir,
             test_runner: TestRunner::new(workdir),
+            baseline: None,
+            new_tests: Vec::new(),
+        }
+    }
+
+    /// Compare runs against a baseline taken before the tests were written
+    pub fn with_baseline(mut self, baseline: Vec<TestCase>) -> Self {
+        self.baseline = Some(baseline);
+        self
+    }
+
+    /// Use a custom test runner
+    pub fn with_test_runner(mut self, test_runner: TestRunner) -> Self {
+        self.test_runner = test_runner;
+        self
+    }
+
+    /// Run tests and keep their outcomes as the baseline
+    ///
+    /// Call this before writing the new tests. The baseline is only kept if
+    /// the framework listed the individual tests.
+    pub async fn capture_baseline(&mut self) -> TestResults {
+        let results = self.run_tests().await;
+        if results.has_test_cases() {
+            self.baseline = Some(results.tests.clone());
         }
+        results
+    }
+
+    /// New tests seen failing by the last successful [`validate_red`](Self::validate_red)
+    pub fn new_tests(&self) -> &[String] {
+        &self.new_tests
     }
 
     /// Run tests and return the results
-    pub fn run_tests(&self) -> TestResults {
-        self.test_runner.run()
+    pub async fn run_tests(&self) -> TestResults {
+        self.test_runner.run().await
     }
 
     /// Validate that we're in a valid red state (tests fail)
-    pub fn validate_red(&self) -> TransitionResult {
-        let results = self.run_tests();
+    ///
+    /// With a baseline, the newly added tests must be the ones failing.
+    pub async fn validate_red(&mut self) -> TransitionResult {
+        let results = self.run_tests().await;
 
-        if results.execution_error.is_some() {
+        if let Some(error) = &results.execution_error {
             return TransitionResult::Blocked {
-                reason: format!(
-                    "Tests have execution errors: {}",
-                    results.execution_error.as_ref().unwrap()
-                ),
+                reason: format!("Tests have execution errors: {}", error),
                 suggestion: "Fix test setup errors before proceeding".to_string(),
             };
         }
 
+        if let Some(diff) = compare_to_baseline(&results, self.baseline.as_deref(), &[]) {
+            if let Some(blocked) = red_blocked(&diff) {
+                return blocked;
+            }
+            self.new_tests = diff.new_failing;
+            return TransitionResult::Allowed;
+        }
+
         if results.is_green() {
             return TransitionResult::Blocked {
                 reason: "Tests should FAIL in red phase but all pass".to_string(),
@@ -281,19 +350,24 @@ impl<'a> PhaseValidator<'a> {
     }
 
     /// Validate that we're in a valid green state (tests pass)
-    pub fn validate_green(&self) -> TransitionResult {
-        let results = self.run_tests();
+    ///
+    /// With a baseline, the new tests must pass and previously passing tests
+    /// must still pass.
+    pub async fn validate_green(&self) -> TransitionResult {
+        let results = self.run_tests().await;
 
-        if results.execution_error.is_some() {
+        if let Some(error) = &results.execution_error {
             return TransitionResult::Blocked {
-                reason: format!(
-                    "Tests have execution errors: {}",
-                    results.execution_error.as_ref().unwrap()
-                ),
+                reason: format!("Tests have execution errors: {}", error),
                 suggestion: "Fix test or implementation errors before proceeding".to_string(),
             };
         }
 
+        if let Some(diff) = compare_to_baseline(&results, self.baseline.as_deref(), &self.new_tests)
+        {
+            return green_blocked(&diff).unwrap_or(TransitionResult::Allowed);
+        }
+
         if results.is_red() {
             return TransitionResult::Blocked {
                 reason: format!("{} tests still failing", results.failed),
@@ -376,6 +450,7 @@ impl<'a> TddIterator<'a> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::workflow::test_runner::TestOutcome;
     use std::path::PathBuf;
 
     fn mock_passing_results() -> TestResults {
@@ -386,6 +461,9 @@ mod tests {
             duration_ms: 100,
             output: "5 passed".to_string(),
             execution_error: None,
+            tests: Vec::new(),
+            timed_out: false,
+            suites: Vec::new(),
         }
     }
 
@@ -397,6 +475,9 @@ mod tests {
             duration_ms: 100,
             output: "3 passed, 2 failed".to_string(),
             execution_error: None,
+            tests: Vec::new(),
+            timed_out: false,
+            suites: Vec::new(),
         }
     }
 
@@ -408,6 +489,9 @@ mod tests {
             duration_ms: 0,
             output: String::new(),
             execution_error: Some("Test execution failed".to_string()),
+            tests: Vec::new(),
+            timed_out: false,
+            suites: Vec::new(),
         }
     }
 
@@ -530,6 +614,9 @@ mod tests {
             duration_ms: 100,
             output: String::new(),
             execution_error: None,
+            tests: Vec::new(),
+            timed_out: false,
+            suites: Vec::new(),
         };
         let result = workflow.validate_transition(TddPhase::Refactor, Some(&results));
         assert!(result.is_blocked());
@@ -539,6 +626,39 @@ mod tests {
             .contains("No tests passed"));
     }
 
+    #[test]
+    fn test_validate_transition_against_baseline() {
+        let mut workflow = TddWorkflow::new_without_spec("test", PathBuf::from("/tmp"));
+        workflow.state_mut().baseline = Some(vec![
+            TestCase::new("old", TestOutcome::Passed),
+            TestCase::new("broken", TestOutcome::Failed),
+        ]);
+
+        // A pre-existing failure doesn't make the new tests red
+        let results = TestResults {
+            passed: 2,
+            failed: 1,
+            tests: vec![
+                TestCase::new("old", TestOutcome::Passed),
+                TestCase::new("broken", TestOutcome::Failed),
+                TestCase::new("new", TestOutcome::Passed),
+            ],
+            ..TestResults::empty()
+        };
+        let result = workflow.validate_transition(TddPhase::VerifyRed, Some(&results));
+        assert!(result
+            .blocking_reason()
+            .unwrap()
+            .contains("passed unexpectedly"));
+
+        workflow.advance(true, None); // WriteTests -> VerifyRed
+        workflow.advance(true, None); // VerifyRed -> Implement
+        workflow.advance(true, None); // Implement -> VerifyGreen
+        workflow.state_mut().new_tests = vec!["new".to_string()];
+        let result = workflow.validate_transition(TddPhase::Refactor, Some(&results));
+        assert!(result.is_allowed());
+    }
+
     #[test]
     fn test_tdd_iterator() {
         let mut state = TddState::new_without_spec("test", PathBuf::from("/tmp"));
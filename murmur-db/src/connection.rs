@@ -71,7 +71,17 @@ impl Database {
                 end_time TEXT,
                 exit_code INTEGER,
                 duration_seconds REAL,
-                created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+                created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                session_id TEXT,
+                input_tokens INTEGER NOT NULL DEFAULT 0,
+                output_tokens INTEGER NOT NULL DEFAULT 0,
+                cache_read_tokens INTEGER NOT NULL DEFAULT 0,
+                cache_write_tokens INTEGER NOT NULL DEFAULT 0,
+                cost_usd REAL NOT NULL DEFAULT 0,
+                outcome TEXT,
+                model TEXT,
+                repository TEXT,
+                resumed_from INTEGER
             )",
             [],
         )?;
@@ -81,6 +91,48 @@ impl Database {
             .execute("ALTER TABLE agent_runs ADD COLUMN pid INTEGER", [])
             .ok(); // Ignore error if column already exists
 
+        // Migrate existing agent_runs table to add session_id column if it doesn't exist
+        let has_session_id: i32 = self.conn.query_row(
+            "SELECT COUNT(*) FROM pragma_table_info('agent_runs') WHERE name='session_id'",
+            [],
+            |row| row.get::<_, i32>(0),
+        )?;
+
+        if has_session_id == 0 {
+            self.conn
+                .execute("ALTER TABLE agent_runs ADD COLUMN session_id TEXT", [])?;
+        }
+
+        // Migrate existing agent_runs table to add usage, outcome, model, repository and
+        // resume link columns
+        for (column, definition) in [
+            ("input_tokens", "INTEGER NOT NULL DEFAULT 0"),
+            ("output_tokens", "INTEGER NOT NULL DEFAULT 0"),
+            ("cache_read_tokens", "INTEGER NOT NULL DEFAULT 0"),
+            ("cache_write_tokens", "INTEGER NOT NULL DEFAULT 0"),
+            ("cost_usd", "REAL NOT NULL DEFAULT 0"),
+            ("outcome", "TEXT"),
+            ("model", "TEXT"),
+            ("repository", "TEXT"),
+            ("resumed_from", "INTEGER"),
+        ] {
+            let has_column: i32 = self.conn.query_row(
+                "SELECT COUNT(*) FROM pragma_table_info('agent_runs') WHERE name=?1",
+                [column],
+                |row| row.get::<_, i32>(0),
+            )?;
+
+            if has_column == 0 {
+                self.conn.execute(
+                    &format!(
+                        "ALTER TABLE agent_runs ADD COLUMN {} {}",
+                        column, definition
+                    ),
+                    [],
+                )?;
+            }
+        }
+
         // Create indexes for common queries
         self.conn.execute(
             "CREATE INDEX IF NOT EXISTS idx_agent_runs_issue
@@ -241,6 +293,116 @@ impl Database {
             [],
         )?;
 
+        // Create review_rounds table for automated review/fix loops
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS review_rounds (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                workdir TEXT NOT NULL,
+                issue_number INTEGER,
+                iteration INTEGER NOT NULL,
+                verdict TEXT NOT NULL,
+                blocking_count INTEGER NOT NULL DEFAULT 0,
+                important_count INTEGER NOT NULL DEFAULT 0,
+                result_json TEXT NOT NULL,
+                review_run_id INTEGER,
+                fix_run_id INTEGER,
+                created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                FOREIGN KEY (review_run_id) REFERENCES agent_runs(id) ON DELETE SET NULL,
+                FOREIGN KEY (fix_run_id) REFERENCES agent_runs(id) ON DELETE SET NULL
+            )",
+            [],
+        )?;
+
+        self.conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_review_rounds_workdir
+             ON review_rounds(workdir)",
+            [],
+        )?;
+
+        self.conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_review_rounds_issue
+             ON review_rounds(issue_number)",
+            [],
+        )?;
+
+        // Create tdd_sessions table so TDD workflows can be resumed
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS tdd_sessions (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                behavior TEXT NOT NULL,
+                workdir TEXT NOT NULL,
+                issue_number INTEGER,
+                worktree_id INTEGER,
+                phase TEXT NOT NULL,
+                status TEXT NOT NULL DEFAULT 'running',
+                state_json TEXT NOT NULL,
+                created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                updated_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                FOREIGN KEY (worktree_id) REFERENCES worktrees(id) ON DELETE SET NULL
+            )",
+            [],
+        )?;
+
+        self.conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_tdd_sessions_workdir
+             ON tdd_sessions(workdir)",
+            [],
+        )?;
+
+        self.conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_tdd_sessions_issue
+             ON tdd_sessions(issue_number)",
+            [],
+        )?;
+
+        // Create tdd_transitions table for the phase history of each session
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS tdd_transitions (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                session_id INTEGER NOT NULL,
+                from_phase TEXT NOT NULL,
+                to_phase TEXT NOT NULL,
+                success INTEGER NOT NULL,
+                message TEXT,
+                agent_run_id INTEGER,
+                created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                FOREIGN KEY (session_id) REFERENCES tdd_sessions(id) ON DELETE CASCADE,
+                FOREIGN KEY (agent_run_id) REFERENCES agent_runs(id) ON DELETE SET NULL
+            )",
+            [],
+        )?;
+
+        self.conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_tdd_transitions_session
+             ON tdd_transitions(session_id)",
+            [],
+        )?;
+
+        // Create approvals table for human approval gates between phases
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS approvals (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                workflow TEXT NOT NULL,
+                task TEXT NOT NULL,
+                workdir TEXT NOT NULL,
+                issue_number INTEGER,
+                from_phase TEXT NOT NULL,
+                to_phase TEXT NOT NULL,
+                status TEXT NOT NULL DEFAULT 'pending',
+                reason TEXT,
+                decided_by TEXT,
+                created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                decided_at TEXT
+            )",
+            [],
+        )?;
+
+        self.conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_approvals_status
+             ON approvals(status)",
+            [],
+        )?;
+
         Ok(())
     }
 
@@ -4,6 +4,7 @@
 //! - Agent run history
 //! - Issue state tracking
 //! - Conversation logs
+//! - Human approvals between workflow phases
 //!
 //! The database is stored at `~/.cache/murmur/murmur.db`
 
@@ -16,7 +17,11 @@ pub mod repos;
 pub use connection::Database;
 pub use conversation_logger::ConversationLogger;
 pub use error::{Error, Result};
-pub use models::{AgentRun, ConversationLog, IssueState, WorktreeRecord};
+pub use models::{
+    AgentRun, Approval, ConversationLog, CostGroupBy, CostSummary, IssueState, ReviewRound,
+    TddSession, TddTransitionRecord, WorktreeRecord,
+};
 pub use repos::{
-    AgentRunRepository, ConversationRepository, IssueStateRepository, WorktreeRepository,
+    AgentRunRepository, ApprovalRepository, ConversationRepository, IssueStateRepository,
+    ReviewRoundRepository, TddSessionRepository, WorktreeRepository,
 };
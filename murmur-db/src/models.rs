@@ -41,6 +41,37 @@ pub struct AgentRun {
 
     /// When this record was created
     pub created_at: DateTime<Utc>,
+
+    /// Backend session ID reported by the agent (used for native resume)
+    pub session_id: Option<String>,
+
+    /// Input tokens used by the run
+    pub input_tokens: i64,
+
+    /// Output tokens used by the run
+    pub output_tokens: i64,
+
+    /// Tokens read from the prompt cache
+    pub cache_read_tokens: i64,
+
+    /// Tokens written to the prompt cache
+    pub cache_write_tokens: i64,
+
+    /// Dollar cost of the run
+    pub cost_usd: f64,
+
+    /// How the run ended ("completed", "failed", "budget_exceeded", "timeout", "stalled");
+    /// None while running
+    pub outcome: Option<String>,
+
+    /// Model the agent ran with (None for the backend default)
+    pub model: Option<String>,
+
+    /// Repository the run worked on (owner/repo format)
+    pub repository: Option<String>,
+
+    /// ID of the interrupted or failed run this one resumed
+    pub resumed_from: Option<i64>,
 }
 
 impl AgentRun {
@@ -65,6 +96,16 @@ impl AgentRun {
             exit_code: None,
             duration_seconds: None,
             created_at: now,
+            session_id: None,
+            input_tokens: 0,
+            output_tokens: 0,
+            cache_read_tokens: 0,
+            cache_write_tokens: 0,
+            cost_usd: 0.0,
+            outcome: None,
+            model: None,
+            repository: None,
+            resumed_from: None,
         }
     }
 
@@ -80,12 +121,75 @@ impl AgentRun {
         self
     }
 
+    /// Set the backend session ID for this run
+    pub fn with_session_id(mut self, session_id: impl Into<String>) -> Self {
+        self.session_id = Some(session_id.into());
+        self
+    }
+
+    /// Set the model for this run
+    pub fn with_model(mut self, model: impl Into<String>) -> Self {
+        self.model = Some(model.into());
+        self
+    }
+
+    /// Set the repository for this run
+    pub fn with_repository(mut self, repository: impl Into<String>) -> Self {
+        self.repository = Some(repository.into());
+        self
+    }
+
+    /// Record that this run resumes the run with the given ID
+    pub fn with_resumed_from(mut self, run_id: i64) -> Self {
+        self.resumed_from = Some(run_id);
+        self
+    }
+
     /// Mark the run as completed
     pub fn complete(&mut self, exit_code: i32) {
         let now = Utc::now();
         self.end_time = Some(now);
         self.exit_code = Some(exit_code);
         self.duration_seconds = Some((now - self.start_time).num_milliseconds() as f64 / 1000.0);
+        if self.outcome.is_none() {
+            let outcome = if exit_code == 0 {
+                "completed"
+            } else {
+                "failed"
+            };
+            self.outcome = Some(outcome.to_string());
+        }
+    }
+
+    /// Record token and dollar usage for the run
+    pub fn record_usage(
+        &mut self,
+        input_tokens: u64,
+        output_tokens: u64,
+        cache_read_tokens: u64,
+        cache_write_tokens: u64,
+        cost_usd: f64,
+    ) {
+        self.input_tokens = input_tokens as i64;
+        self.output_tokens = output_tokens as i64;
+        self.cache_read_tokens = cache_read_tokens as i64;
+        self.cache_write_tokens = cache_write_tokens as i64;
+        self.cost_usd = cost_usd;
+    }
+
+    /// Mark the run as killed for exceeding its budget
+    pub fn mark_budget_exceeded(&mut self) {
+        self.outcome = Some("budget_exceeded".to_string());
+    }
+
+    /// Mark the run as stopped by the watchdog ("timeout" or "stalled")
+    pub fn mark_stopped(&mut self, reason: impl Into<String>) {
+        self.outcome = Some(reason.into());
+    }
+
+    /// Check if the run was killed for exceeding its budget
+    pub fn is_budget_exceeded(&self) -> bool {
+        self.outcome.as_deref() == Some("budget_exceeded")
     }
 
     /// Check if the run is completed
@@ -99,6 +203,41 @@ impl AgentRun {
     }
 }
 
+/// Dimension to aggregate agent run costs by
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CostGroupBy {
+    Issue,
+    AgentType,
+    Model,
+    Repository,
+    Day,
+}
+
+/// Aggregated usage and cost for one group of agent runs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CostSummary {
+    /// Group key (e.g. "#42", "implementer", "2025-01-31")
+    pub key: String,
+
+    /// Number of runs in the group
+    pub runs: i64,
+
+    /// Total input tokens
+    pub input_tokens: i64,
+
+    /// Total output tokens
+    pub output_tokens: i64,
+
+    /// Total prompt cache reads
+    pub cache_read_tokens: i64,
+
+    /// Total prompt cache writes
+    pub cache_write_tokens: i64,
+
+    /// Total dollar cost
+    pub cost_usd: f64,
+}
+
 /// Conversation log entry storing JSON output from agents
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ConversationLog {
@@ -405,6 +544,338 @@ impl IssueState {
     }
 }
 
+/// One review → fix round of an automated review loop
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReviewRound {
+    /// Unique identifier for this record
+    pub id: Option<i64>,
+
+    /// Working directory that was reviewed
+    pub workdir: String,
+
+    /// GitHub issue number if the work is for an issue
+    pub issue_number: Option<i64>,
+
+    /// Round number, starting at 1
+    pub iteration: i64,
+
+    /// Review verdict ("approved", "request_changes", "comment")
+    pub verdict: String,
+
+    /// Number of blocking issues found
+    pub blocking_count: i64,
+
+    /// Number of important issues found
+    pub important_count: i64,
+
+    /// Full parsed review as JSON
+    pub result_json: String,
+
+    /// Agent run that produced the review
+    pub review_run_id: Option<i64>,
+
+    /// Agent run that addressed the review (None if no fix was needed or attempted)
+    pub fix_run_id: Option<i64>,
+
+    /// When this record was created
+    pub created_at: DateTime<Utc>,
+}
+
+impl ReviewRound {
+    /// Create a new review round record
+    pub fn new(
+        workdir: impl Into<String>,
+        iteration: i64,
+        verdict: impl Into<String>,
+        result_json: impl Into<String>,
+    ) -> Self {
+        Self {
+            id: None,
+            workdir: workdir.into(),
+            issue_number: None,
+            iteration,
+            verdict: verdict.into(),
+            blocking_count: 0,
+            important_count: 0,
+            result_json: result_json.into(),
+            review_run_id: None,
+            fix_run_id: None,
+            created_at: Utc::now(),
+        }
+    }
+
+    /// Set the issue number for this round
+    pub fn with_issue_number(mut self, issue_number: i64) -> Self {
+        self.issue_number = Some(issue_number);
+        self
+    }
+
+    /// Set the blocking and important issue counts
+    pub fn with_counts(mut self, blocking: i64, important: i64) -> Self {
+        self.blocking_count = blocking;
+        self.important_count = important;
+        self
+    }
+
+    /// Set the agent run that produced the review
+    pub fn with_review_run_id(mut self, run_id: i64) -> Self {
+        self.review_run_id = Some(run_id);
+        self
+    }
+}
+
+/// Persisted state of a TDD workflow
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TddSession {
+    /// Unique identifier for this record
+    pub id: Option<i64>,
+
+    /// The behavior being implemented
+    pub behavior: String,
+
+    /// Working directory the workflow runs in
+    pub workdir: String,
+
+    /// GitHub issue number if the work is for an issue
+    pub issue_number: Option<i64>,
+
+    /// Worktree the workflow runs in, if tracked
+    pub worktree_id: Option<i64>,
+
+    /// Current phase (e.g. "write_tests", "verify_green")
+    pub phase: String,
+
+    /// Status: running, completed, failed
+    pub status: String,
+
+    /// Full workflow state as JSON
+    pub state_json: String,
+
+    /// When the session was created
+    pub created_at: DateTime<Utc>,
+
+    /// When the session was last updated
+    pub updated_at: DateTime<Utc>,
+}
+
+impl TddSession {
+    /// Create a new running TDD session record
+    pub fn new(
+        behavior: impl Into<String>,
+        workdir: impl Into<String>,
+        phase: impl Into<String>,
+        state_json: impl Into<String>,
+    ) -> Self {
+        let now = Utc::now();
+        Self {
+            id: None,
+            behavior: behavior.into(),
+            workdir: workdir.into(),
+            issue_number: None,
+            worktree_id: None,
+            phase: phase.into(),
+            status: "running".to_string(),
+            state_json: state_json.into(),
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    /// Set the issue number for this session
+    pub fn with_issue_number(mut self, issue_number: i64) -> Self {
+        self.issue_number = Some(issue_number);
+        self
+    }
+
+    /// Set the worktree this session runs in
+    pub fn with_worktree_id(mut self, worktree_id: i64) -> Self {
+        self.worktree_id = Some(worktree_id);
+        self
+    }
+
+    /// Record the workflow's current phase and state
+    pub fn set_state(&mut self, phase: impl Into<String>, state_json: impl Into<String>) {
+        self.phase = phase.into();
+        self.state_json = state_json.into();
+        self.updated_at = Utc::now();
+    }
+
+    /// Mark the session as completed
+    pub fn mark_completed(&mut self) {
+        self.status = "completed".to_string();
+        self.updated_at = Utc::now();
+    }
+
+    /// Mark the session as failed
+    pub fn mark_failed(&mut self) {
+        self.status = "failed".to_string();
+        self.updated_at = Utc::now();
+    }
+
+    /// Check if the session can be resumed
+    pub fn is_resumable(&self) -> bool {
+        self.status != "completed"
+    }
+}
+
+/// A recorded transition between TDD phases
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TddTransitionRecord {
+    /// Unique identifier for this record
+    pub id: Option<i64>,
+
+    /// Session the transition belongs to
+    pub session_id: i64,
+
+    /// Phase transitioned from
+    pub from_phase: String,
+
+    /// Phase transitioned to
+    pub to_phase: String,
+
+    /// Whether the phase succeeded (false for retries)
+    pub success: bool,
+
+    /// Optional message about the transition
+    pub message: Option<String>,
+
+    /// Agent run that did the work of the phase, if any
+    pub agent_run_id: Option<i64>,
+
+    /// When the transition happened
+    pub created_at: DateTime<Utc>,
+}
+
+impl TddTransitionRecord {
+    /// Create a new transition record
+    pub fn new(
+        session_id: i64,
+        from_phase: impl Into<String>,
+        to_phase: impl Into<String>,
+        success: bool,
+    ) -> Self {
+        Self {
+            id: None,
+            session_id,
+            from_phase: from_phase.into(),
+            to_phase: to_phase.into(),
+            success,
+            message: None,
+            agent_run_id: None,
+            created_at: Utc::now(),
+        }
+    }
+
+    /// Set the transition message
+    pub fn with_message(mut self, message: impl Into<String>) -> Self {
+        self.message = Some(message.into());
+        self
+    }
+
+    /// Set the agent run that did the work of the phase
+    pub fn with_agent_run_id(mut self, agent_run_id: i64) -> Self {
+        self.agent_run_id = Some(agent_run_id);
+        self
+    }
+}
+
+/// A human approval a workflow waits for before moving to another phase
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Approval {
+    /// Unique identifier for this record
+    pub id: Option<i64>,
+
+    /// Workflow that is waiting ("tdd", "coordinator")
+    pub workflow: String,
+
+    /// The task or behavior the workflow is working on
+    pub task: String,
+
+    /// Working directory the workflow runs in
+    pub workdir: String,
+
+    /// GitHub issue number if the work is for an issue
+    pub issue_number: Option<i64>,
+
+    /// Phase the workflow finished
+    pub from_phase: String,
+
+    /// Phase the workflow waits to start
+    pub to_phase: String,
+
+    /// Status: pending, approved, rejected
+    pub status: String,
+
+    /// Why the transition was rejected
+    pub reason: Option<String>,
+
+    /// Who approved or rejected the transition
+    pub decided_by: Option<String>,
+
+    /// When the approval was requested
+    pub created_at: DateTime<Utc>,
+
+    /// When the approval was decided
+    pub decided_at: Option<DateTime<Utc>>,
+}
+
+impl Approval {
+    /// Create a new pending approval
+    pub fn new(
+        workflow: impl Into<String>,
+        task: impl Into<String>,
+        workdir: impl Into<String>,
+        from_phase: impl Into<String>,
+        to_phase: impl Into<String>,
+    ) -> Self {
+        Self {
+            id: None,
+            workflow: workflow.into(),
+            task: task.into(),
+            workdir: workdir.into(),
+            issue_number: None,
+            from_phase: from_phase.into(),
+            to_phase: to_phase.into(),
+            status: "pending".to_string(),
+            reason: None,
+            decided_by: None,
+            created_at: Utc::now(),
+            decided_at: None,
+        }
+    }
+
+    /// Set the issue number for this approval
+    pub fn with_issue_number(mut self, issue_number: i64) -> Self {
+        self.issue_number = Some(issue_number);
+        self
+    }
+
+    /// Check if the approval is still waiting for a decision
+    pub fn is_pending(&self) -> bool {
+        self.status == "pending"
+    }
+
+    /// Check if the transition was approved
+    pub fn is_approved(&self) -> bool {
+        self.status == "approved"
+    }
+
+    /// Approve the transition
+    pub fn approve(&mut self, decided_by: impl Into<String>) {
+        self.status = "approved".to_string();
+        self.decided_by = Some(decided_by.into());
+        self.decided_at = Some(Utc::now());
+    }
+
+    /// Reject the transition, giving the reason to the workflow
+    pub fn reject(&mut self, reason: impl Into<String>, decided_by: impl Into<String>) {
+        self.status = "rejected".to_string();
+        self.reason = Some(reason.into());
+        self.decided_by = Some(decided_by.into());
+        self.decided_at = Some(Utc::now());
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -449,6 +920,28 @@ mod tests {
         assert_eq!(run.exit_code, Some(1));
     }
 
+    #[test]
+    fn test_agent_run_outcome() {
+        let mut run = AgentRun::new("implementer", "Run task", "/tmp", "{}");
+        run.complete(0);
+        assert_eq!(run.outcome.as_deref(), Some("completed"));
+
+        let mut run = AgentRun::new("implementer", "Run task", "/tmp", "{}");
+        run.mark_budget_exceeded();
+        run.complete(-1);
+        assert!(run.is_budget_exceeded());
+        assert_eq!(run.outcome.as_deref(), Some("budget_exceeded"));
+    }
+
+    #[test]
+    fn test_agent_run_stopped_by_watchdog() {
+        let mut run = AgentRun::new("implementer", "Run task", "/tmp", "{}");
+        run.mark_stopped("stalled");
+        run.complete(-1);
+        assert_eq!(run.outcome.as_deref(), Some("stalled"));
+        assert!(!run.is_budget_exceeded());
+    }
+
     #[test]
     fn test_conversation_log_new() {
         let log = ConversationLog::new(
@@ -488,4 +981,25 @@ mod tests {
         let parsed: Value = log.parse_message().unwrap();
         assert_eq!(parsed["type"], "assistant");
     }
+
+    #[test]
+    fn test_approval_decisions() {
+        let mut approval = Approval::new("tdd", "Add parser", "/tmp", "write_spec", "write_tests");
+        assert!(approval.is_pending());
+        assert!(approval.decided_at.is_none());
+
+        approval.reject("Spec misses the error cases", "alice");
+        assert!(!approval.is_pending());
+        assert!(!approval.is_approved());
+        assert_eq!(
+            approval.reason.as_deref(),
+            Some("Spec misses the error cases")
+        );
+        assert_eq!(approval.decided_by.as_deref(), Some("alice"));
+        assert!(approval.decided_at.is_some());
+
+        let mut approval = Approval::new("tdd", "Add parser", "/tmp", "write_spec", "write_tests");
+        approval.approve("bob");
+        assert!(approval.is_approved());
+    }
 }
@@ -3,7 +3,7 @@
 use chrono::{DateTime, Utc};
 use rusqlite::{params, Row};
 
-use crate::models::AgentRun;
+use crate::models::{AgentRun, CostGroupBy, CostSummary};
 use crate::{Database, Error, Result};
 
 /// Repository for managing agent run records
@@ -24,8 +24,11 @@ impl<'db> AgentRunRepository<'db> {
         conn.execute(
             "INSERT INTO agent_runs (
                 agent_type, issue_number, prompt, workdir, config_json, pid,
-                start_time, end_time, exit_code, duration_seconds, created_at
-            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+                start_time, end_time, exit_code, duration_seconds, created_at, session_id,
+                input_tokens, output_tokens, cache_read_tokens, cache_write_tokens,
+                cost_usd, outcome, model, repository, resumed_from
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16,
+                ?17, ?18, ?19, ?20, ?21)",
             params![
                 run.agent_type,
                 run.issue_number,
@@ -38,6 +41,16 @@ impl<'db> AgentRunRepository<'db> {
                 run.exit_code,
                 run.duration_seconds,
                 run.created_at.to_rfc3339(),
+                run.session_id,
+                run.input_tokens,
+                run.output_tokens,
+                run.cache_read_tokens,
+                run.cache_write_tokens,
+                run.cost_usd,
+                run.outcome,
+                run.model,
+                run.repository,
+                run.resumed_from,
             ],
         )?;
 
@@ -62,8 +75,18 @@ impl<'db> AgentRunRepository<'db> {
                 start_time = ?7,
                 end_time = ?8,
                 exit_code = ?9,
-                duration_seconds = ?10
-             WHERE id = ?11",
+                duration_seconds = ?10,
+                session_id = ?11,
+                input_tokens = ?12,
+                output_tokens = ?13,
+                cache_read_tokens = ?14,
+                cache_write_tokens = ?15,
+                cost_usd = ?16,
+                outcome = ?17,
+                model = ?18,
+                repository = ?19,
+                resumed_from = ?20
+             WHERE id = ?21",
             params![
                 run.agent_type,
                 run.issue_number,
@@ -75,6 +98,16 @@ impl<'db> AgentRunRepository<'db> {
                 run.end_time.map(|dt| dt.to_rfc3339()),
                 run.exit_code,
                 run.duration_seconds,
+                run.session_id,
+                run.input_tokens,
+                run.output_tokens,
+                run.cache_read_tokens,
+                run.cache_write_tokens,
+                run.cost_usd,
+                run.outcome,
+                run.model,
+                run.repository,
+                run.resumed_from,
                 id,
             ],
         )?;
@@ -94,7 +127,9 @@ impl<'db> AgentRunRepository<'db> {
         let conn = self.db.connection();
         conn.query_row(
             "SELECT id, agent_type, issue_number, prompt, workdir, config_json, pid,
-                    start_time, end_time, exit_code, duration_seconds, created_at
+                    start_time, end_time, exit_code, duration_seconds, created_at, session_id,
+                    input_tokens, output_tokens, cache_read_tokens, cache_write_tokens,
+                    cost_usd, outcome, model, repository, resumed_from
              FROM agent_runs
              WHERE id = ?1",
             params![id],
@@ -113,7 +148,9 @@ impl<'db> AgentRunRepository<'db> {
         let conn = self.db.connection();
         let mut stmt = conn.prepare(
             "SELECT id, agent_type, issue_number, prompt, workdir, config_json, pid,
-                    start_time, end_time, exit_code, duration_seconds, created_at
+                    start_time, end_time, exit_code, duration_seconds, created_at, session_id,
+                    input_tokens, output_tokens, cache_read_tokens, cache_write_tokens,
+                    cost_usd, outcome, model, repository, resumed_from
              FROM agent_runs
              WHERE issue_number = ?1
              ORDER BY start_time DESC",
@@ -135,7 +172,9 @@ impl<'db> AgentRunRepository<'db> {
         let conn = self.db.connection();
         let mut stmt = conn.prepare(
             "SELECT id, agent_type, issue_number, prompt, workdir, config_json, pid,
-                    start_time, end_time, exit_code, duration_seconds, created_at
+                    start_time, end_time, exit_code, duration_seconds, created_at, session_id,
+                    input_tokens, output_tokens, cache_read_tokens, cache_write_tokens,
+                    cost_usd, outcome, model, repository, resumed_from
              FROM agent_runs
              WHERE start_time >= ?1 AND start_time <= ?2
              ORDER BY start_time DESC",
@@ -153,7 +192,9 @@ impl<'db> AgentRunRepository<'db> {
         let conn = self.db.connection();
         let mut stmt = conn.prepare(
             "SELECT id, agent_type, issue_number, prompt, workdir, config_json, pid,
-                    start_time, end_time, exit_code, duration_seconds, created_at
+                    start_time, end_time, exit_code, duration_seconds, created_at, session_id,
+                    input_tokens, output_tokens, cache_read_tokens, cache_write_tokens,
+                    cost_usd, outcome, model, repository, resumed_from
              FROM agent_runs
              WHERE agent_type = ?1
              ORDER BY start_time DESC",
@@ -173,7 +214,9 @@ impl<'db> AgentRunRepository<'db> {
         let query = if let Some(limit) = limit {
             format!(
                 "SELECT id, agent_type, issue_number, prompt, workdir, config_json, pid,
-                        start_time, end_time, exit_code, duration_seconds, created_at
+                        start_time, end_time, exit_code, duration_seconds, created_at, session_id,
+                        input_tokens, output_tokens, cache_read_tokens, cache_write_tokens,
+                        cost_usd, outcome, model, repository, resumed_from
                  FROM agent_runs
                  ORDER BY start_time DESC
                  LIMIT {}",
@@ -181,7 +224,9 @@ impl<'db> AgentRunRepository<'db> {
             )
         } else {
             "SELECT id, agent_type, issue_number, prompt, workdir, config_json, pid,
-                    start_time, end_time, exit_code, duration_seconds, created_at
+                    start_time, end_time, exit_code, duration_seconds, created_at, session_id,
+                    input_tokens, output_tokens, cache_read_tokens, cache_write_tokens,
+                    cost_usd, outcome, model, repository, resumed_from
              FROM agent_runs
              ORDER BY start_time DESC"
                 .to_string()
@@ -228,12 +273,92 @@ impl<'db> AgentRunRepository<'db> {
         Ok(count)
     }
 
+    /// Record the backend session ID for an agent run
+    pub fn update_session_id(&self, id: i64, session_id: &str) -> Result<()> {
+        let conn = self.db.connection();
+        let affected = conn.execute(
+            "UPDATE agent_runs SET session_id = ?1 WHERE id = ?2",
+            params![session_id, id],
+        )?;
+
+        if affected == 0 {
+            return Err(Error::NotFound(format!(
+                "Agent run with id {} not found",
+                id
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Total tokens (input + output) and dollars spent by all runs for an issue
+    pub fn usage_by_issue(&self, issue_number: i64) -> Result<(i64, f64)> {
+        let conn = self.db.connection();
+        let usage = conn.query_row(
+            "SELECT COALESCE(SUM(input_tokens + output_tokens), 0), COALESCE(SUM(cost_usd), 0)
+             FROM agent_runs WHERE issue_number = ?1",
+            params![issue_number],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )?;
+        Ok(usage)
+    }
+
+    /// Aggregate usage and cost of runs started since `since`, grouped by `group_by`
+    ///
+    /// Groups are ordered by cost (highest first), except days which are chronological.
+    pub fn cost_summary(
+        &self,
+        group_by: CostGroupBy,
+        since: Option<DateTime<Utc>>,
+    ) -> Result<Vec<CostSummary>> {
+        let (key, order) = match group_by {
+            CostGroupBy::Issue => (
+                "CASE WHEN issue_number IS NULL THEN '(none)' ELSE '#' || issue_number END",
+                "cost DESC",
+            ),
+            CostGroupBy::AgentType => ("agent_type", "cost DESC"),
+            CostGroupBy::Model => ("COALESCE(model, '(default)')", "cost DESC"),
+            CostGroupBy::Repository => ("COALESCE(repository, '(unknown)')", "cost DESC"),
+            CostGroupBy::Day => ("substr(start_time, 1, 10)", "key ASC"),
+        };
+
+        let query = format!(
+            "SELECT {} AS key, COUNT(*), SUM(input_tokens), SUM(output_tokens),
+                    SUM(cache_read_tokens), SUM(cache_write_tokens), SUM(cost_usd) AS cost
+             FROM agent_runs
+             WHERE ?1 IS NULL OR start_time >= ?1
+             GROUP BY key
+             ORDER BY {}",
+            key, order
+        );
+
+        let conn = self.db.connection();
+        let mut stmt = conn.prepare(&query)?;
+        let summaries = stmt
+            .query_map(params![since.map(|dt| dt.to_rfc3339())], |row| {
+                Ok(CostSummary {
+                    key: row.get(0)?,
+                    runs: row.get(1)?,
+                    input_tokens: row.get(2)?,
+                    output_tokens: row.get(3)?,
+                    cache_read_tokens: row.get(4)?,
+                    cache_write_tokens: row.get(5)?,
+                    cost_usd: row.get(6)?,
+                })
+            })?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        Ok(summaries)
+    }
+
     /// Find all running agents (no end_time, has PID)
     pub fn find_running(&self) -> Result<Vec<AgentRun>> {
         let conn = self.db.connection();
         let mut stmt = conn.prepare(
             "SELECT id, agent_type, issue_number, prompt, workdir, config_json, pid,
-                    start_time, end_time, exit_code, duration_seconds, created_at
+                    start_time, end_time, exit_code, duration_seconds, created_at, session_id,
+                    input_tokens, output_tokens, cache_read_tokens, cache_write_tokens,
+                    cost_usd, outcome, model, repository, resumed_from
              FROM agent_runs
              WHERE end_time IS NULL AND pid IS NOT NULL
              ORDER BY start_time DESC",
@@ -290,6 +415,16 @@ impl<'db> AgentRunRepository<'db> {
                     )
                 })?
                 .with_timezone(&Utc),
+            session_id: row.get(12)?,
+            input_tokens: row.get(13)?,
+            output_tokens: row.get(14)?,
+            cache_read_tokens: row.get(15)?,
+            cache_write_tokens: row.get(16)?,
+            cost_usd: row.get(17)?,
+            outcome: row.get(18)?,
+            model: row.get(19)?,
+            repository: row.get(20)?,
+            resumed_from: row.get(21)?,
         })
     }
 }
@@ -339,6 +474,109 @@ mod tests {
         assert!(updated.is_successful());
     }
 
+    #[test]
+    fn test_session_id_roundtrip() {
+        let db = setup_db();
+        let repo = AgentRunRepository::new(&db);
+
+        let run = AgentRun::new("implementer", "Task", "/tmp", "{}").with_session_id("abc-123");
+        let id = repo.insert(&run).unwrap();
+        assert_eq!(
+            repo.find_by_id(id).unwrap().session_id,
+            Some("abc-123".to_string())
+        );
+
+        repo.update_session_id(id, "def-456").unwrap();
+        assert_eq!(
+            repo.find_by_id(id).unwrap().session_id,
+            Some("def-456".to_string())
+        );
+
+        assert!(repo.update_session_id(9999, "missing").is_err());
+    }
+
+    #[test]
+    fn test_usage_and_outcome_roundtrip() {
+        let db = setup_db();
+        let repo = AgentRunRepository::new(&db);
+
+        let mut run = AgentRun::new("implementer", "Task", "/tmp", "{}").with_issue_number(42);
+        let id = repo.insert(&run).unwrap();
+        run.id = Some(id);
+        run.record_usage(1000, 200, 5000, 300, 0.12);
+        run.mark_budget_exceeded();
+        run.complete(-1);
+        repo.update(&run).unwrap();
+
+        let found = repo.find_by_id(id).unwrap();
+        assert_eq!(found.input_tokens, 1000);
+        assert_eq!(found.cache_read_tokens, 5000);
+        assert_eq!(found.cost_usd, 0.12);
+        assert!(found.is_budget_exceeded());
+
+        let mut other = AgentRun::new("reviewer", "Task", "/tmp", "{}").with_issue_number(42);
+        other.record_usage(100, 50, 0, 0, 0.03);
+        repo.insert(&other).unwrap();
+
+        let (tokens, cost) = repo.usage_by_issue(42).unwrap();
+        assert_eq!(tokens, 1350);
+        assert!((cost - 0.15).abs() < 1e-9);
+        assert_eq!(repo.usage_by_issue(99).unwrap(), (0, 0.0));
+    }
+
+    #[test]
+    fn test_cost_summary() {
+        let db = setup_db();
+        let repo = AgentRunRepository::new(&db);
+
+        let mut run = AgentRun::new("implementer", "Task", "/tmp", "{}")
+            .with_issue_number(42)
+            .with_model("sonnet")
+            .with_repository("owner/repo");
+        run.record_usage(100, 10, 0, 0, 1.5);
+        repo.insert(&run).unwrap();
+
+        let mut run = AgentRun::new("reviewer", "Task", "/tmp", "{}")
+            .with_issue_number(42)
+            .with_model("haiku")
+            .with_repository("owner/repo");
+        run.record_usage(50, 5, 0, 0, 0.25);
+        repo.insert(&run).unwrap();
+
+        let mut run = AgentRun::new("implementer", "Task", "/tmp", "{}");
+        run.record_usage(10, 1, 0, 0, 2.0);
+        repo.insert(&run).unwrap();
+
+        let by_issue = repo.cost_summary(CostGroupBy::Issue, None).unwrap();
+        assert_eq!(by_issue.len(), 2);
+        assert_eq!(by_issue[0].key, "(none)");
+        assert_eq!(by_issue[1].key, "#42");
+        assert_eq!(by_issue[1].runs, 2);
+        assert_eq!(by_issue[1].input_tokens, 150);
+        assert!((by_issue[1].cost_usd - 1.75).abs() < 1e-9);
+
+        let by_type = repo.cost_summary(CostGroupBy::AgentType, None).unwrap();
+        assert_eq!(by_type[0].key, "implementer");
+        assert!((by_type[0].cost_usd - 3.5).abs() < 1e-9);
+
+        let by_model = repo.cost_summary(CostGroupBy::Model, None).unwrap();
+        let keys: Vec<_> = by_model.iter().map(|s| s.key.as_str()).collect();
+        assert_eq!(keys, vec!["(default)", "sonnet", "haiku"]);
+
+        let by_repo = repo.cost_summary(CostGroupBy::Repository, None).unwrap();
+        assert_eq!(by_repo.len(), 2);
+
+        let by_day = repo.cost_summary(CostGroupBy::Day, None).unwrap();
+        assert_eq!(by_day.len(), 1);
+        assert_eq!(by_day[0].key, Utc::now().format("%Y-%m-%d").to_string());
+
+        let future = Utc::now() + chrono::Duration::hours(1);
+        assert!(repo
+            .cost_summary(CostGroupBy::Issue, Some(future))
+            .unwrap()
+            .is_empty());
+    }
+
     #[test]
     fn test_find_by_issue() {
         let db = setup_db();
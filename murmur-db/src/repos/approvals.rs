@@ -0,0 +1,203 @@
+//! Repository for human approvals between workflow phases
+
+use chrono::{DateTime, Utc};
+use rusqlite::{params, OptionalExtension, Row};
+
+use crate::models::Approval;
+use crate::{Database, Error, Result};
+
+/// Columns selected for every approval query
+const COLUMNS: &str = "id, workflow, task, workdir, issue_number, from_phase, to_phase, status,
+                       reason, decided_by, created_at, decided_at";
+
+/// Repository for managing approvals
+pub struct ApprovalRepository<'db> {
+    db: &'db Database,
+}
+
+impl<'db> ApprovalRepository<'db> {
+    /// Create a new repository instance
+    pub fn new(db: &'db Database) -> Self {
+        Self { db }
+    }
+
+    /// Insert a new approval
+    pub fn insert(&self, approval: &Approval) -> Result<i64> {
+        let conn = self.db.connection();
+        conn.execute(
+            "INSERT INTO approvals (
+                workflow, task, workdir, issue_number, from_phase, to_phase, status,
+                reason, decided_by, created_at, decided_at
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+            params![
+                approval.workflow,
+                approval.task,
+                approval.workdir,
+                approval.issue_number,
+                approval.from_phase,
+                approval.to_phase,
+                approval.status,
+                approval.reason,
+                approval.decided_by,
+                approval.created_at.to_rfc3339(),
+                approval.decided_at.map(|t| t.to_rfc3339()),
+            ],
+        )?;
+
+        Ok(conn.last_insert_rowid())
+    }
+
+    /// Record the decision on an approval
+    pub fn update(&self, approval: &Approval) -> Result<()> {
+        let id = approval
+            .id
+            .ok_or_else(|| Error::InvalidData("Approval has no ID".to_string()))?;
+
+        let affected = self.db.connection().execute(
+            "UPDATE approvals SET status = ?1, reason = ?2, decided_by = ?3, decided_at = ?4
+             WHERE id = ?5",
+            params![
+                approval.status,
+                approval.reason,
+                approval.decided_by,
+                approval.decided_at.map(|t| t.to_rfc3339()),
+                id,
+            ],
+        )?;
+
+        if affected == 0 {
+            return Err(Error::NotFound(format!(
+                "Approval with id {} not found",
+                id
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Find an approval by ID
+    pub fn find_by_id(&self, id: i64) -> Result<Option<Approval>> {
+        let conn = self.db.connection();
+        let mut stmt = conn.prepare(&format!("SELECT {} FROM approvals WHERE id = ?1", COLUMNS))?;
+
+        Ok(stmt.query_row(params![id], Self::map_row).optional()?)
+    }
+
+    /// Find the approvals still waiting for a decision, oldest first
+    pub fn find_pending(&self) -> Result<Vec<Approval>> {
+        let conn = self.db.connection();
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM approvals WHERE status = 'pending' ORDER BY id ASC",
+            COLUMNS
+        ))?;
+
+        let approvals = stmt
+            .query_map([], Self::map_row)?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        Ok(approvals)
+    }
+
+    fn map_row(row: &Row) -> rusqlite::Result<Approval> {
+        Ok(Approval {
+            id: Some(row.get(0)?),
+            workflow: row.get(1)?,
+            task: row.get(2)?,
+            workdir: row.get(3)?,
+            issue_number: row.get(4)?,
+            from_phase: row.get(5)?,
+            to_phase: row.get(6)?,
+            status: row.get(7)?,
+            reason: row.get(8)?,
+            decided_by: row.get(9)?,
+            created_at: parse_timestamp(row, 10)?,
+            decided_at: row
+                .get::<_, Option<String>>(11)?
+                .map(|_| parse_timestamp(row, 11))
+                .transpose()?,
+        })
+    }
+}
+
+/// Parse an RFC 3339 timestamp column
+fn parse_timestamp(row: &Row, idx: usize) -> rusqlite::Result<DateTime<Utc>> {
+    let value: String = row.get(idx)?;
+    DateTime::parse_from_rfc3339(&value)
+        .map(|dt| dt.with_timezone(&Utc))
+        .map_err(|e| {
+            rusqlite::Error::FromSqlConversionFailure(idx, rusqlite::types::Type::Text, Box::new(e))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_approval_lifecycle() {
+        let db = Database::in_memory().unwrap();
+        let repo = ApprovalRepository::new(&db);
+
+        let id = repo
+            .insert(
+                &Approval::new("tdd", "Add parser", "/tmp/wt", "write_spec", "write_tests")
+                    .with_issue_number(7),
+            )
+            .unwrap();
+        let mut approval = repo.find_by_id(id).unwrap().unwrap();
+        assert!(approval.is_pending());
+        assert_eq!(approval.issue_number, Some(7));
+        assert!(approval.decided_at.is_none());
+
+        approval.reject("Cover the empty input case", "alice");
+        repo.update(&approval).unwrap();
+        let found = repo.find_by_id(id).unwrap().unwrap();
+        assert_eq!(found.status, "rejected");
+        assert_eq!(found.reason.as_deref(), Some("Cover the empty input case"));
+        assert_eq!(found.decided_by.as_deref(), Some("alice"));
+        assert!(found.decided_at.is_some());
+    }
+
+    #[test]
+    fn test_find_pending() {
+        let db = Database::in_memory().unwrap();
+        let repo = ApprovalRepository::new(&db);
+
+        let first = repo
+            .insert(&Approval::new(
+                "tdd",
+                "First",
+                "/tmp/a",
+                "write_spec",
+                "write_tests",
+            ))
+            .unwrap();
+        let mut approved = Approval::new(
+            "coordinator",
+            "Second",
+            "/tmp/b",
+            "reviewing",
+            "creating_pr",
+        );
+        approved.approve("bob");
+        repo.insert(&approved).unwrap();
+        let third = repo
+            .insert(&Approval::new(
+                "coordinator",
+                "Third",
+                "/tmp/c",
+                "planning",
+                "setup_worktree",
+            ))
+            .unwrap();
+
+        let pending: Vec<i64> = repo
+            .find_pending()
+            .unwrap()
+            .into_iter()
+            .filter_map(|a| a.id)
+            .collect();
+        assert_eq!(pending, vec![first, third]);
+        assert!(repo.update(&approved).is_err());
+    }
+}
@@ -1,11 +1,17 @@
 //! Repository modules for database operations
 
 pub mod agents;
+pub mod approvals;
 pub mod conversations;
 pub mod issues;
+pub mod reviews;
+pub mod tdd;
 pub mod worktrees;
 
 pub use agents::AgentRunRepository;
+pub use approvals::ApprovalRepository;
 pub use conversations::ConversationRepository;
 pub use issues::IssueStateRepository;
+pub use reviews::ReviewRoundRepository;
+pub use tdd::TddSessionRepository;
 pub use worktrees::WorktreeRepository;
@@ -0,0 +1,179 @@
+//! Repository for automated review rounds
+
+use chrono::{DateTime, Utc};
+use rusqlite::{params, Row};
+
+use crate::models::ReviewRound;
+use crate::{Database, Error, Result};
+
+/// Columns selected for every review round query
+const COLUMNS: &str = "id, workdir, issue_number, iteration, verdict, blocking_count,
+                       important_count, result_json, review_run_id, fix_run_id, created_at";
+
+/// Repository for managing review round records
+pub struct ReviewRoundRepository<'db> {
+    db: &'db Database,
+}
+
+impl<'db> ReviewRoundRepository<'db> {
+    /// Create a new repository instance
+    pub fn new(db: &'db Database) -> Self {
+        Self { db }
+    }
+
+    /// Insert a new review round
+    pub fn insert(&self, round: &ReviewRound) -> Result<i64> {
+        let conn = self.db.connection();
+        conn.execute(
+            "INSERT INTO review_rounds (
+                workdir, issue_number, iteration, verdict, blocking_count,
+                important_count, result_json, review_run_id, fix_run_id, created_at
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+            params![
+                round.workdir,
+                round.issue_number,
+                round.iteration,
+                round.verdict,
+                round.blocking_count,
+                round.important_count,
+                round.result_json,
+                round.review_run_id,
+                round.fix_run_id,
+                round.created_at.to_rfc3339(),
+            ],
+        )?;
+
+        Ok(conn.last_insert_rowid())
+    }
+
+    /// Record the agent run that addressed a round's review
+    pub fn set_fix_run(&self, id: i64, fix_run_id: i64) -> Result<()> {
+        let affected = self.db.connection().execute(
+            "UPDATE review_rounds SET fix_run_id = ?1 WHERE id = ?2",
+            params![fix_run_id, id],
+        )?;
+
+        if affected == 0 {
+            return Err(Error::NotFound(format!(
+                "Review round with id {} not found",
+                id
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Find the rounds run in a working directory, oldest first
+    pub fn find_by_workdir(&self, workdir: &str) -> Result<Vec<ReviewRound>> {
+        let conn = self.db.connection();
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM review_rounds WHERE workdir = ?1 ORDER BY id ASC",
+            COLUMNS
+        ))?;
+
+        let rounds = stmt
+            .query_map(params![workdir], Self::map_row)?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        Ok(rounds)
+    }
+
+    /// Find the rounds run for an issue, oldest first
+    pub fn find_by_issue(&self, issue_number: i64) -> Result<Vec<ReviewRound>> {
+        let conn = self.db.connection();
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM review_rounds WHERE issue_number = ?1 ORDER BY id ASC",
+            COLUMNS
+        ))?;
+
+        let rounds = stmt
+            .query_map(params![issue_number], Self::map_row)?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        Ok(rounds)
+    }
+
+    fn map_row(row: &Row) -> rusqlite::Result<ReviewRound> {
+        let created_at_str: String = row.get(10)?;
+
+        Ok(ReviewRound {
+            id: Some(row.get(0)?),
+            workdir: row.get(1)?,
+            issue_number: row.get(2)?,
+            iteration: row.get(3)?,
+            verdict: row.get(4)?,
+            blocking_count: row.get(5)?,
+            important_count: row.get(6)?,
+            result_json: row.get(7)?,
+            review_run_id: row.get(8)?,
+            fix_run_id: row.get(9)?,
+            created_at: DateTime::parse_from_rfc3339(&created_at_str)
+                .map_err(|e| {
+                    rusqlite::Error::FromSqlConversionFailure(
+                        10,
+                        rusqlite::types::Type::Text,
+                        Box::new(e),
+                    )
+                })?
+                .with_timezone(&Utc),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::AgentRun;
+    use crate::repos::AgentRunRepository;
+
+    #[test]
+    fn test_insert_and_find_rounds() {
+        let db = Database::in_memory().unwrap();
+        let repo = ReviewRoundRepository::new(&db);
+        let run_id = AgentRunRepository::new(&db)
+            .insert(&AgentRun::new("reviewer", "Review", "/tmp/wt", "{}"))
+            .unwrap();
+
+        let first = ReviewRound::new("/tmp/wt", 1, "request_changes", "{}")
+            .with_issue_number(7)
+            .with_counts(2, 1)
+            .with_review_run_id(run_id);
+        let id = repo.insert(&first).unwrap();
+        repo.insert(&ReviewRound::new("/tmp/wt", 2, "approved", "{}").with_issue_number(7))
+            .unwrap();
+        repo.insert(&ReviewRound::new("/tmp/other", 1, "approved", "{}"))
+            .unwrap();
+
+        let rounds = repo.find_by_workdir("/tmp/wt").unwrap();
+        assert_eq!(rounds.len(), 2);
+        assert_eq!(rounds[0].id, Some(id));
+        assert_eq!(rounds[0].blocking_count, 2);
+        assert_eq!(rounds[0].review_run_id, Some(run_id));
+        assert_eq!(rounds[1].verdict, "approved");
+
+        assert_eq!(repo.find_by_issue(7).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_set_fix_run() {
+        let db = Database::in_memory().unwrap();
+        let repo = ReviewRoundRepository::new(&db);
+        let fix_id = AgentRunRepository::new(&db)
+            .insert(&AgentRun::new("implementer", "Fix", "/tmp/wt", "{}"))
+            .unwrap();
+
+        let id = repo
+            .insert(&ReviewRound::new("/tmp/wt", 1, "request_changes", "{}"))
+            .unwrap();
+        repo.set_fix_run(id, fix_id).unwrap();
+        assert_eq!(
+            repo.find_by_workdir("/tmp/wt").unwrap()[0].fix_run_id,
+            Some(fix_id)
+        );
+
+        assert!(matches!(
+            repo.set_fix_run(999, fix_id),
+            Err(Error::NotFound(_))
+        ));
+    }
+}
@@ -0,0 +1,282 @@
+//! Repository for persisted TDD workflow sessions
+
+use chrono::{DateTime, Utc};
+use rusqlite::{params, OptionalExtension, Row};
+
+use crate::models::{TddSession, TddTransitionRecord};
+use crate::{Database, Error, Result};
+
+/// Columns selected for every session query
+const SESSION_COLUMNS: &str = "id, behavior, workdir, issue_number, worktree_id, phase, status,
+                               state_json, created_at, updated_at";
+
+/// Repository for managing TDD sessions and their transitions
+pub struct TddSessionRepository<'db> {
+    db: &'db Database,
+}
+
+impl<'db> TddSessionRepository<'db> {
+    /// Create a new repository instance
+    pub fn new(db: &'db Database) -> Self {
+        Self { db }
+    }
+
+    /// Insert a new session
+    pub fn insert(&self, session: &TddSession) -> Result<i64> {
+        let conn = self.db.connection();
+        conn.execute(
+            "INSERT INTO tdd_sessions (
+                behavior, workdir, issue_number, worktree_id, phase, status,
+                state_json, created_at, updated_at
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            params![
+                session.behavior,
+                session.workdir,
+                session.issue_number,
+                session.worktree_id,
+                session.phase,
+                session.status,
+                session.state_json,
+                session.created_at.to_rfc3339(),
+                session.updated_at.to_rfc3339(),
+            ],
+        )?;
+
+        Ok(conn.last_insert_rowid())
+    }
+
+    /// Update a session's phase, status and state
+    pub fn update(&self, session: &TddSession) -> Result<()> {
+        let id = session
+            .id
+            .ok_or_else(|| Error::InvalidData("TDD session has no ID".to_string()))?;
+
+        let affected = self.db.connection().execute(
+            "UPDATE tdd_sessions SET phase = ?1, status = ?2, state_json = ?3, updated_at = ?4
+             WHERE id = ?5",
+            params![
+                session.phase,
+                session.status,
+                session.state_json,
+                session.updated_at.to_rfc3339(),
+                id,
+            ],
+        )?;
+
+        if affected == 0 {
+            return Err(Error::NotFound(format!(
+                "TDD session with id {} not found",
+                id
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Find a session by ID
+    pub fn find_by_id(&self, id: i64) -> Result<Option<TddSession>> {
+        let conn = self.db.connection();
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM tdd_sessions WHERE id = ?1",
+            SESSION_COLUMNS
+        ))?;
+
+        Ok(stmt.query_row(params![id], Self::map_session).optional()?)
+    }
+
+    /// Find the most recent unfinished session in a working directory
+    pub fn find_latest_resumable_by_workdir(&self, workdir: &str) -> Result<Option<TddSession>> {
+        let conn = self.db.connection();
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM tdd_sessions WHERE workdir = ?1 AND status != 'completed'
+             ORDER BY id DESC LIMIT 1",
+            SESSION_COLUMNS
+        ))?;
+
+        Ok(stmt
+            .query_row(params![workdir], Self::map_session)
+            .optional()?)
+    }
+
+    /// Find the most recent unfinished session for an issue
+    pub fn find_latest_resumable_by_issue(&self, issue_number: i64) -> Result<Option<TddSession>> {
+        let conn = self.db.connection();
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM tdd_sessions WHERE issue_number = ?1 AND status != 'completed'
+             ORDER BY id DESC LIMIT 1",
+            SESSION_COLUMNS
+        ))?;
+
+        Ok(stmt
+            .query_row(params![issue_number], Self::map_session)
+            .optional()?)
+    }
+
+    /// Record a phase transition
+    pub fn insert_transition(&self, transition: &TddTransitionRecord) -> Result<i64> {
+        let conn = self.db.connection();
+        conn.execute(
+            "INSERT INTO tdd_transitions (
+                session_id, from_phase, to_phase, success, message, agent_run_id, created_at
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                transition.session_id,
+                transition.from_phase,
+                transition.to_phase,
+                transition.success,
+                transition.message,
+                transition.agent_run_id,
+                transition.created_at.to_rfc3339(),
+            ],
+        )?;
+
+        Ok(conn.last_insert_rowid())
+    }
+
+    /// Get the transitions of a session, oldest first
+    pub fn find_transitions(&self, session_id: i64) -> Result<Vec<TddTransitionRecord>> {
+        let conn = self.db.connection();
+        let mut stmt = conn.prepare(
+            "SELECT id, session_id, from_phase, to_phase, success, message, agent_run_id, created_at
+             FROM tdd_transitions WHERE session_id = ?1 ORDER BY id ASC",
+        )?;
+
+        let transitions = stmt
+            .query_map(params![session_id], |row| {
+                Ok(TddTransitionRecord {
+                    id: Some(row.get(0)?),
+                    session_id: row.get(1)?,
+                    from_phase: row.get(2)?,
+                    to_phase: row.get(3)?,
+                    success: row.get(4)?,
+                    message: row.get(5)?,
+                    agent_run_id: row.get(6)?,
+                    created_at: parse_timestamp(row, 7)?,
+                })
+            })?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        Ok(transitions)
+    }
+
+    fn map_session(row: &Row) -> rusqlite::Result<TddSession> {
+        Ok(TddSession {
+            id: Some(row.get(0)?),
+            behavior: row.get(1)?,
+            workdir: row.get(2)?,
+            issue_number: row.get(3)?,
+            worktree_id: row.get(4)?,
+            phase: row.get(5)?,
+            status: row.get(6)?,
+            state_json: row.get(7)?,
+            created_at: parse_timestamp(row, 8)?,
+            updated_at: parse_timestamp(row, 9)?,
+        })
+    }
+}
+
+/// Parse an RFC 3339 timestamp column
+fn parse_timestamp(row: &Row, idx: usize) -> rusqlite::Result<DateTime<Utc>> {
+    let value: String = row.get(idx)?;
+    DateTime::parse_from_rfc3339(&value)
+        .map(|dt| dt.with_timezone(&Utc))
+        .map_err(|e| {
+            rusqlite::Error::FromSqlConversionFailure(idx, rusqlite::types::Type::Text, Box::new(e))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::AgentRun;
+    use crate::repos::AgentRunRepository;
+
+    #[test]
+    fn test_session_lifecycle() {
+        let db = Database::in_memory().unwrap();
+        let repo = TddSessionRepository::new(&db);
+
+        let id = repo
+            .insert(
+                &TddSession::new("Add parser", "/tmp/wt", "write_tests", "{}").with_issue_number(4),
+            )
+            .unwrap();
+        let mut session = repo.find_by_id(id).unwrap().unwrap();
+        assert_eq!(session.status, "running");
+        assert_eq!(session.issue_number, Some(4));
+
+        session.set_state("implement", r#"{"phase":"Implement"}"#);
+        repo.update(&session).unwrap();
+        let found = repo.find_latest_resumable_by_issue(4).unwrap().unwrap();
+        assert_eq!(found.id, Some(id));
+        assert_eq!(found.phase, "implement");
+
+        session.mark_completed();
+        repo.update(&session).unwrap();
+        assert!(repo.find_latest_resumable_by_issue(4).unwrap().is_none());
+        assert!(repo
+            .find_latest_resumable_by_workdir("/tmp/wt")
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_latest_resumable_by_workdir() {
+        let db = Database::in_memory().unwrap();
+        let repo = TddSessionRepository::new(&db);
+
+        let mut failed = TddSession::new("First", "/tmp/wt", "verify_green", "{}");
+        failed.mark_failed();
+        repo.insert(&failed).unwrap();
+        let latest = repo
+            .insert(&TddSession::new("Second", "/tmp/wt", "write_tests", "{}"))
+            .unwrap();
+        repo.insert(&TddSession::new("Other", "/tmp/other", "write_tests", "{}"))
+            .unwrap();
+
+        let found = repo
+            .find_latest_resumable_by_workdir("/tmp/wt")
+            .unwrap()
+            .unwrap();
+        assert_eq!(found.id, Some(latest));
+        assert!(repo.update(&failed).is_err());
+    }
+
+    #[test]
+    fn test_transitions() {
+        let db = Database::in_memory().unwrap();
+        let repo = TddSessionRepository::new(&db);
+        let session_id = repo
+            .insert(&TddSession::new(
+                "Add parser",
+                "/tmp/wt",
+                "write_tests",
+                "{}",
+            ))
+            .unwrap();
+        let run_id = AgentRunRepository::new(&db)
+            .insert(&AgentRun::new("tester", "Write tests", "/tmp/wt", "{}"))
+            .unwrap();
+
+        repo.insert_transition(
+            &TddTransitionRecord::new(session_id, "write_tests", "verify_red", true)
+                .with_agent_run_id(run_id),
+        )
+        .unwrap();
+        repo.insert_transition(
+            &TddTransitionRecord::new(session_id, "verify_red", "write_tests", false)
+                .with_message("Tests passed unexpectedly"),
+        )
+        .unwrap();
+
+        let transitions = repo.find_transitions(session_id).unwrap();
+        assert_eq!(transitions.len(), 2);
+        assert_eq!(transitions[0].agent_run_id, Some(run_id));
+        assert!(transitions[0].success);
+        assert!(!transitions[1].success);
+        assert_eq!(
+            transitions[1].message.as_deref(),
+            Some("Tests passed unexpectedly")
+        );
+    }
+}
@@ -111,6 +111,30 @@ impl GitHubClient {
         info!("GitHub connection successful");
         Ok(())
     }
+
+    /// Whether `login` can push to the repository
+    ///
+    /// True for collaborators with write, maintain or admin permission.
+    pub async fn can_push(&self, login: &str) -> Result<bool> {
+        #[derive(serde::Deserialize)]
+        struct CollaboratorPermission {
+            permission: String,
+        }
+
+        debug!(login, "Checking collaborator permission");
+        let route = format!(
+            "/repos/{}/{}/collaborators/{}/permission",
+            self.owner, self.repo, login
+        );
+        let response: CollaboratorPermission = self
+            .client
+            .get(route, None::<&()>)
+            .await
+            .map_err(Error::Api)?;
+
+        // "maintain" is reported as "write"
+        Ok(matches!(response.permission.as_str(), "admin" | "write"))
+    }
 }
 
 impl std::fmt::Debug for GitHubClient {
@@ -0,0 +1,115 @@
+//! Repository context for agents working on an issue
+
+use std::path::Path;
+
+use murmur_core::agent::{ContextCollector, ContextConfig, RepositoryContext};
+use tracing::warn;
+
+use crate::{GitHubClient, Issue, IssueDependencies, PrState};
+
+impl GitHubClient {
+    /// Collect repository context for work on an issue in `workdir`
+    ///
+    /// Adds the issue's comments and the diffs of its dependencies' PRs to the
+    /// local context. GitHub failures are logged and the affected section is
+    /// left out rather than failing the run.
+    pub async fn collect_issue_context(
+        &self,
+        issue: &Issue,
+        workdir: &Path,
+        config: &ContextConfig,
+    ) -> RepositoryContext {
+        if !config.enabled {
+            return RepositoryContext::default();
+        }
+
+        let mut collector = ContextCollector::new(workdir, config.clone())
+            .with_touched_files(&mentioned_paths(&issue.body, workdir));
+
+        if config.issue_comments {
+            match self.get_issue_comments(issue.number).await {
+                Ok(comments) => {
+                    for comment in comments {
+                        collector = collector.with_issue_comment(comment.author, comment.body);
+                    }
+                }
+                Err(e) => warn!(issue = issue.number, error = %e, "Skipping issue comments"),
+            }
+        }
+
+        if config.dependency_diffs {
+            let deps = IssueDependencies::parse(&issue.body)
+                .map(|deps| deps.all_local_deps())
+                .unwrap_or_default();
+            for dep in deps {
+                match self.dependency_diff(dep).await {
+                    Ok(Some((title, diff))) => {
+                        collector = collector.with_dependency_diff(title, diff);
+                    }
+                    Ok(None) => {}
+                    Err(e) => warn!(dependency = dep, error = %e, "Skipping dependency diff"),
+                }
+            }
+        }
+
+        let context = collector.collect();
+        if !context.truncated.is_empty() {
+            warn!(
+                sections = ?context.truncated,
+                "Repository context exceeded the token budget"
+            );
+        }
+        context
+    }
+
+    /// Diff of the PR that implements a dependency, preferring merged PRs
+    async fn dependency_diff(&self, issue_number: u64) -> crate::Result<Option<(String, String)>> {
+        let prs = self.find_prs_for_issue(issue_number).await?;
+        let pr = prs
+            .iter()
+            .find(|pr| pr.merged)
+            .or_else(|| prs.iter().find(|pr| pr.state == PrState::Open));
+        let Some(pr) = pr else {
+            return Ok(None);
+        };
+
+        let diff = self.get_pr_diff(pr.number).await?;
+        let title = format!(
+            "Dependency #{}: PR #{} {}",
+            issue_number, pr.number, pr.title
+        );
+        Ok(Some((title, diff)))
+    }
+}
+
+/// Backtick-quoted paths in an issue body that exist in `workdir`
+fn mentioned_paths(body: &str, workdir: &Path) -> Vec<String> {
+    let mut paths: Vec<String> = Vec::new();
+    for span in body.split('`').skip(1).step_by(2) {
+        let span = span.trim();
+        if span.is_empty() || span.contains(char::is_whitespace) || paths.iter().any(|p| p == span)
+        {
+            continue;
+        }
+        if workdir.join(span).exists() {
+            paths.push(span.to_string());
+        }
+    }
+    paths
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mentioned_paths() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("src")).unwrap();
+        std::fs::write(dir.path().join("src/lib.rs"), "").unwrap();
+
+        let body = "Update `src/lib.rs` and `src/missing.rs`, then run `cargo test`.\n\
+                    Also see `src/lib.rs` again.";
+        assert_eq!(mentioned_paths(body, dir.path()), ["src/lib.rs"]);
+    }
+}
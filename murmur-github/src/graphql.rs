@@ -1,6 +1,7 @@
 //! GitHub GraphQL API support for features not available in REST API
 
-use crate::{Error, GitHubClient, Result, SubIssuesSummary};
+use crate::{Error, GitHubClient, Result, ReviewComment, ReviewThread, SubIssuesSummary};
+use chrono::{DateTime, Utc};
 use serde::Deserialize;
 use serde_json::json;
 use tracing::debug;
@@ -70,7 +71,156 @@ impl From<SubIssuesSummaryData> for SubIssuesSummary {
     }
 }
 
+/// Pull request review threads (GraphQL)
+#[derive(Debug, Deserialize)]
+struct PullRequestThreads {
+    repository: Option<PullRequestRepository>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PullRequestRepository {
+    pull_request: Option<PullRequestData>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PullRequestData {
+    review_threads: Connection<ReviewThreadNode>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Connection<T> {
+    nodes: Vec<T>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ReviewThreadNode {
+    id: String,
+    is_resolved: bool,
+    is_outdated: bool,
+    path: String,
+    line: Option<u64>,
+    comments: Connection<ThreadCommentNode>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ThreadCommentNode {
+    database_id: u64,
+    body: String,
+    author: Option<AuthorNode>,
+    created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+struct AuthorNode {
+    login: String,
+}
+
+impl From<ReviewThreadNode> for ReviewThread {
+    fn from(node: ReviewThreadNode) -> Self {
+        let comments = node
+            .comments
+            .nodes
+            .into_iter()
+            .map(|c| ReviewComment {
+                id: c.database_id,
+                body: c.body,
+                author: c.author.map(|a| a.login).unwrap_or_default(),
+                path: Some(node.path.clone()),
+                line: node.line,
+                created_at: c.created_at,
+            })
+            .collect();
+
+        ReviewThread {
+            id: node.id,
+            is_resolved: node.is_resolved,
+            is_outdated: node.is_outdated,
+            path: node.path,
+            line: node.line,
+            comments,
+        }
+    }
+}
+
 impl GitHubClient {
+    /// Fetch the review comment threads of a pull request using GraphQL
+    pub async fn get_review_threads(&self, pr_number: u64) -> Result<Vec<ReviewThread>> {
+        debug!(pr_number, "Fetching review threads via GraphQL");
+
+        let query = r#"
+            query($owner: String!, $repo: String!, $number: Int!) {
+                repository(owner: $owner, name: $repo) {
+                    pullRequest(number: $number) {
+                        reviewThreads(first: 100) {
+                            nodes {
+                                id
+                                isResolved
+                                isOutdated
+                                path
+                                line
+                                comments(first: 50) {
+                                    nodes {
+                                        databaseId
+                                        body
+                                        author {
+                                            login
+                                        }
+                                        createdAt
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        "#;
+
+        let variables = json!({
+            "owner": self.owner(),
+            "repo": self.repo(),
+            "number": pr_number,
+        });
+
+        let response = self
+            .graphql_query::<PullRequestThreads>(query, &variables)
+            .await?;
+
+        let pr = response
+            .repository
+            .and_then(|r| r.pull_request)
+            .ok_or(Error::PrNotFound(pr_number))?;
+
+        Ok(pr
+            .review_threads
+            .nodes
+            .into_iter()
+            .map(ReviewThread::from)
+            .collect())
+    }
+
+    /// Mark a review thread as resolved using GraphQL
+    pub async fn resolve_review_thread(&self, thread_id: &str) -> Result<()> {
+        debug!(thread_id, "Resolving review thread via GraphQL");
+
+        let query = r#"
+            mutation($threadId: ID!) {
+                resolveReviewThread(input: { threadId: $threadId }) {
+                    thread {
+                        isResolved
+                    }
+                }
+            }
+        "#;
+
+        self.graphql_query::<serde_json::Value>(query, &json!({ "threadId": thread_id }))
+            .await?;
+        Ok(())
+    }
+
     /// Fetch tracked issues for a given issue using GraphQL
     ///
     /// Returns (tracked_issues, tracked_in_issues, sub_issues_summary)
@@ -222,4 +372,47 @@ mod tests {
         assert_eq!(summary.completed, 5);
         assert_eq!(summary.percent_completed, 50);
     }
+
+    #[test]
+    fn test_review_thread_conversion() {
+        let data: PullRequestThreads = serde_json::from_value(json!({
+            "repository": {
+                "pullRequest": {
+                    "reviewThreads": {
+                        "nodes": [{
+                            "id": "PRRT_kwDO",
+                            "isResolved": false,
+                            "isOutdated": false,
+                            "path": "src/lib.rs",
+                            "line": 7,
+                            "comments": {
+                                "nodes": [{
+                                    "databaseId": 101,
+                                    "body": "Use a match here",
+                                    "author": { "login": "alice" },
+                                    "createdAt": "2024-05-01T12:00:00Z"
+                                }]
+                            }
+                        }]
+                    }
+                }
+            }
+        }))
+        .unwrap();
+
+        let node = data
+            .repository
+            .and_then(|r| r.pull_request)
+            .unwrap()
+            .review_threads
+            .nodes
+            .remove(0);
+        let thread = ReviewThread::from(node);
+        assert_eq!(thread.id, "PRRT_kwDO");
+        assert_eq!(thread.line, Some(7));
+        let root = thread.root_comment().unwrap();
+        assert_eq!(root.id, 101);
+        assert_eq!(root.author, "alice");
+        assert_eq!(root.path.as_deref(), Some("src/lib.rs"));
+    }
 }
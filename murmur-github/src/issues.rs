@@ -83,6 +83,30 @@ impl From<OctocrabIssue> for Issue {
     }
 }
 
+/// A comment on an issue
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IssueComment {
+    /// Comment ID
+    pub id: u64,
+    /// Login of the comment author
+    pub author: String,
+    /// Comment body
+    pub body: String,
+    /// When the comment was created
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<octocrab::models::issues::Comment> for IssueComment {
+    fn from(comment: octocrab::models::issues::Comment) -> Self {
+        IssueComment {
+            id: comment.id.0,
+            author: comment.user.login,
+            body: comment.body.unwrap_or_default(),
+            created_at: comment.created_at,
+        }
+    }
+}
+
 /// Filter options for listing issues
 #[derive(Debug, Clone, Default)]
 pub struct IssueFilter {
@@ -134,6 +158,23 @@ impl GitHubClient {
         Ok(issue)
     }
 
+    /// Fetch the comments on an issue, oldest first
+    pub async fn get_issue_comments(&self, number: u64) -> Result<Vec<IssueComment>> {
+        debug!(number, "Fetching issue comments");
+
+        let page = self
+            .client()
+            .issues(self.owner(), self.repo())
+            .list_comments(number)
+            .per_page(100)
+            .send()
+            .await
+            .map_err(Error::Api)?;
+        let comments = self.client().all_pages(page).await.map_err(Error::Api)?;
+
+        Ok(comments.into_iter().map(IssueComment::from).collect())
+    }
+
     /// List issues with optional filters
     pub async fn list_issues(&self, filter: &IssueFilter) -> Result<Vec<Issue>> {
         debug!(?filter, "Listing issues");
@@ -3,7 +3,9 @@
 //! This crate provides GitHub API access for reading issues, managing PRs,
 //! and tracking dependencies between work items.
 
+mod approvals;
 mod client;
+mod context;
 mod create;
 mod dependencies;
 mod error;
@@ -13,11 +15,15 @@ mod metadata;
 mod pr;
 mod review;
 
+pub use approvals::IssueApprovals;
 pub use client::GitHubClient;
 pub use create::{ImportOptions, ImportResult};
 pub use dependencies::{DependencyGraph, EpicChildren, IssueDependencies, IssueRef};
 pub use error::{Error, Result};
-pub use issues::{Issue, IssueFilter, IssueState, SubIssuesSummary};
+pub use issues::{Issue, IssueComment, IssueFilter, IssueState, SubIssuesSummary};
 pub use metadata::{parse_depends_on_links, IssueMetadata};
 pub use pr::{DependencyStatus, PrState, PullRequest};
-pub use review::{Review, ReviewComment};
+pub use review::{
+    parse_thread_reports, DraftReviewComment, PrFeedback, Review, ReviewComment, ReviewDraft,
+    ReviewEvent, ReviewThread, ThreadOutcome, ThreadReport, THREAD_REPORT_INSTRUCTIONS,
+};
@@ -118,6 +118,22 @@ impl GitHubClient {
         Ok(pr.into())
     }
 
+    /// Get the unified diff of a pull request
+    pub async fn get_pr_diff(&self, number: u64) -> Result<String> {
+        debug!(number, "Fetching pull request diff");
+
+        self.client()
+            .pulls(self.owner(), self.repo())
+            .get_diff(number)
+            .await
+            .map_err(|e| match &e {
+                octocrab::Error::GitHub { source, .. } if source.message.contains("Not Found") => {
+                    Error::PrNotFound(number)
+                }
+                _ => Error::Api(e),
+            })
+    }
+
     /// List PRs with optional state filter
     pub async fn list_prs(&self, state: Option<PrState>) -> Result<Vec<PullRequest>> {
         debug!(?state, "Listing pull requests");
@@ -1,8 +1,13 @@
 //! Pull request review management
 
+use std::collections::HashMap;
+
 use crate::{Error, GitHubClient, Result};
 use chrono::{DateTime, Utc};
+use murmur_core::agent::last_json_block;
+use murmur_core::workflow::{ReviewIssue, ReviewResult, ReviewVerdict};
 use serde::{Deserialize, Serialize};
+use tracing::{debug, warn};
 
 /// A review comment on a pull request
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -36,7 +41,339 @@ pub struct Review {
     pub submitted_at: Option<DateTime<Utc>>,
 }
 
+/// A review comment thread on a pull request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReviewThread {
+    /// GraphQL node ID, used to resolve the thread
+    pub id: String,
+    /// Whether the thread has been resolved
+    pub is_resolved: bool,
+    /// Whether the code the thread is on has since changed
+    pub is_outdated: bool,
+    /// File path the thread is on
+    pub path: String,
+    /// Line number in the new version of the file (None if outdated)
+    pub line: Option<u64>,
+    /// Comments in the thread, oldest first
+    pub comments: Vec<ReviewComment>,
+}
+
+impl ReviewThread {
+    /// The comment that started the thread, which replies are attached to
+    pub fn root_comment(&self) -> Option<&ReviewComment> {
+        self.comments.first()
+    }
+}
+
+/// Review feedback on a pull request that hasn't been addressed yet
+#[derive(Debug, Clone, Default)]
+pub struct PrFeedback {
+    /// Unresolved comment threads
+    pub threads: Vec<ReviewThread>,
+    /// Latest review of each reviewer still requesting changes, with a body
+    pub change_requests: Vec<Review>,
+}
+
+impl PrFeedback {
+    /// Whether there is nothing to address
+    pub fn is_empty(&self) -> bool {
+        self.threads.is_empty() && self.change_requests.is_empty()
+    }
+
+    /// Render the feedback as Markdown for an agent prompt
+    pub fn to_markdown(&self) -> String {
+        let mut out = String::new();
+        for review in &self.change_requests {
+            out.push_str(&format!(
+                "### Review by @{}\n\n{}\n\n",
+                review.author,
+                review.body.as_deref().unwrap_or_default().trim()
+            ));
+        }
+        for thread in &self.threads {
+            match thread.line {
+                Some(line) => out.push_str(&format!(
+                    "### {}:{} (thread `{}`)\n\n",
+                    thread.path, line, thread.id
+                )),
+                None => out.push_str(&format!(
+                    "### {} (outdated, thread `{}`)\n\n",
+                    thread.path, thread.id
+                )),
+            }
+            for comment in &thread.comments {
+                out.push_str(&format!(
+                    "**@{}:** {}\n\n",
+                    comment.author,
+                    comment.body.trim()
+                ));
+            }
+        }
+        out.truncate(out.trim_end().len());
+        out
+    }
+}
+
+/// Prompt instructions describing the per-thread report on review feedback
+pub const THREAD_REPORT_INSTRUCTIONS: &str = "\
+End your final message with what you did about each review thread, keyed by the \
+thread id in its heading, as a fenced JSON block:\n\
+```json\n\
+{\"PRRT_abc\": {\"outcome\": \"addressed\", \"reply\": \"Now returns the parse error\"},\n \
+\"PRRT_def\": {\"outcome\": \"declined\", \"reply\": \"The panic is unreachable because ...\"}}\n\
+```\n\
+`outcome` is `addressed` if your changes resolve the thread, or `declined` if you left \
+the code as is. `reply` is posted on the thread for the reviewer.";
+
+/// What an agent did about a review thread
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ThreadOutcome {
+    /// The changes resolve the thread
+    Addressed,
+    /// The code was left as is
+    Declined,
+}
+
+/// An agent's report on one review thread
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct ThreadReport {
+    /// What the agent did
+    pub outcome: ThreadOutcome,
+    /// Explanation to post on the thread
+    #[serde(default)]
+    pub reply: String,
+}
+
+/// Parse an agent's per-thread reports, keyed by thread id
+///
+/// Reads the last fenced JSON block of the output, as described by
+/// [`THREAD_REPORT_INSTRUCTIONS`]. Returns no reports if there is no block or
+/// it doesn't parse, so no thread is treated as addressed.
+pub fn parse_thread_reports(output: &str) -> HashMap<String, ThreadReport> {
+    let Some(json) = last_json_block(output) else {
+        return HashMap::new();
+    };
+    serde_json::from_str(json).unwrap_or_else(|e| {
+        warn!(error = %e, "Ignoring invalid review thread report");
+        HashMap::new()
+    })
+}
+
+/// The action a submitted review takes on a pull request
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ReviewEvent {
+    /// Approve the changes
+    Approve,
+    /// Request changes before merging
+    RequestChanges,
+    /// Leave feedback without approving or blocking
+    Comment,
+}
+
+impl ReviewEvent {
+    /// The event matching a local review verdict
+    pub fn from_verdict(verdict: ReviewVerdict) -> Self {
+        match verdict {
+            ReviewVerdict::Approved => ReviewEvent::Approve,
+            ReviewVerdict::RequestChanges => ReviewEvent::RequestChanges,
+            ReviewVerdict::Comment | ReviewVerdict::Pending => ReviewEvent::Comment,
+        }
+    }
+}
+
+/// A line comment in a review being submitted
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DraftReviewComment {
+    /// File path relative to the repository root
+    pub path: String,
+    /// Line in the new version of the file
+    pub line: u64,
+    /// Side of the diff the line is on (always `RIGHT`)
+    pub side: &'static str,
+    /// Comment body
+    pub body: String,
+}
+
+/// A pull request review ready to submit
+#[derive(Debug, Clone, Serialize)]
+pub struct ReviewDraft {
+    /// Summary body
+    pub body: String,
+    /// Approve, request changes or comment
+    pub event: ReviewEvent,
+    /// Line comments anchored to the PR diff
+    pub comments: Vec<DraftReviewComment>,
+}
+
+impl ReviewDraft {
+    /// Build a review from a local review result and the PR's unified diff
+    ///
+    /// Issues are posted as line comments when their file and line are part
+    /// of the diff; GitHub rejects comments outside it, so the rest are
+    /// listed in the summary body instead.
+    pub fn from_result(result: &ReviewResult, diff: &str) -> Self {
+        let lines = commentable_lines(diff);
+        let mut comments = Vec::new();
+        let mut body = format!(
+            "## Review Summary: {}\n\n{} blocking, {} important, {} suggestions\n",
+            result.verdict,
+            result.blocking.len(),
+            result.important.len(),
+            result.suggestions.len()
+        );
+
+        for (label, issues) in [
+            ("Blocking", &result.blocking),
+            ("Important", &result.important),
+            ("Suggestion", &result.suggestions),
+        ] {
+            let mut unanchored = Vec::new();
+            for issue in issues {
+                match anchor(issue, &lines) {
+                    Some((path, line)) => comments.push(DraftReviewComment {
+                        body: comment_body(label, issue, &lines[&path][&line]),
+                        path,
+                        line,
+                        side: "RIGHT",
+                    }),
+                    None => unanchored.push(issue),
+                }
+            }
+            if !unanchored.is_empty() {
+                body.push_str(&format!("\n### {}\n\n", label));
+                for issue in unanchored {
+                    body.push_str(&format!("- {}\n", issue));
+                }
+            }
+        }
+
+        if !result.positives.is_empty() {
+            body.push_str("\n### Positive\n\n");
+            for positive in &result.positives {
+                body.push_str(&format!("- {}\n", positive));
+            }
+        }
+
+        Self {
+            body,
+            event: ReviewEvent::from_verdict(result.verdict),
+            comments,
+        }
+    }
+}
+
 impl GitHubClient {
+    /// Submit a review on a pull request
+    pub async fn submit_review(&self, pr_number: u64, draft: &ReviewDraft) -> Result<Review> {
+        debug!(
+            pr_number,
+            event = ?draft.event,
+            comments = draft.comments.len(),
+            "Submitting pull request review"
+        );
+
+        let route = format!(
+            "/repos/{}/{}/pulls/{}/reviews",
+            self.owner(),
+            self.repo(),
+            pr_number
+        );
+        let review: octocrab::models::pulls::Review = self
+            .client()
+            .post(route, Some(draft))
+            .await
+            .map_err(|e| match &e {
+                octocrab::Error::GitHub { source, .. } if source.message.contains("Not Found") => {
+                    Error::PrNotFound(pr_number)
+                }
+                _ => Error::Api(e),
+            })?;
+
+        Ok(Review {
+            id: review.id.0,
+            state: review_state_name(review.state),
+            body: review.body,
+            author: review.user.map(|u| u.login).unwrap_or_default(),
+            submitted_at: review.submitted_at,
+        })
+    }
+
+    /// Post a local review result as a review on a pull request
+    ///
+    /// GitHub doesn't let an account approve or request changes on its own
+    /// pull request, so in that case the review is posted as a comment.
+    pub async fn post_review_result(
+        &self,
+        pr_number: u64,
+        result: &ReviewResult,
+    ) -> Result<Review> {
+        let diff = self.get_pr_diff(pr_number).await?;
+        let mut draft = ReviewDraft::from_result(result, &diff);
+
+        match self.submit_review(pr_number, &draft).await {
+            Err(Error::Api(octocrab::Error::GitHub { source, .. }))
+                if draft.event != ReviewEvent::Comment
+                    && format!("{:?}", source.errors).contains("own pull request") =>
+            {
+                warn!(
+                    pr_number,
+                    event = ?draft.event,
+                    "Can't review own pull request, posting as a comment"
+                );
+                draft.event = ReviewEvent::Comment;
+                self.submit_review(pr_number, &draft).await
+            }
+            other => other,
+        }
+    }
+
+    /// Get the review feedback on a pull request that is still unaddressed
+    pub async fn get_unresolved_feedback(&self, pr_number: u64) -> Result<PrFeedback> {
+        let threads = self
+            .get_review_threads(pr_number)
+            .await?
+            .into_iter()
+            .filter(|t| !t.is_resolved)
+            .collect();
+
+        // A reviewer's latest approval or dismissal supersedes earlier change requests
+        let mut latest: Vec<Review> = Vec::new();
+        for review in self.get_pr_reviews(pr_number).await? {
+            if review.state == "COMMENTED" || review.state == "PENDING" {
+                continue;
+            }
+            latest.retain(|r| r.author != review.author);
+            latest.push(review);
+        }
+        let change_requests = latest
+            .into_iter()
+            .filter(|r| r.state == "CHANGES_REQUESTED")
+            .filter(|r| r.body.as_deref().is_some_and(|b| !b.trim().is_empty()))
+            .collect();
+
+        Ok(PrFeedback {
+            threads,
+            change_requests,
+        })
+    }
+
+    /// Reply to a review comment thread
+    pub async fn reply_to_review_comment(
+        &self,
+        pr_number: u64,
+        comment_id: u64,
+        body: &str,
+    ) -> Result<()> {
+        self.client()
+            .pulls(self.owner(), self.repo())
+            .reply_to_comment(pr_number, octocrab::models::CommentId(comment_id), body)
+            .await
+            .map_err(Error::Api)?;
+        Ok(())
+    }
+
     /// Get all reviews for a pull request
     pub async fn get_pr_reviews(&self, pr_number: u64) -> Result<Vec<Review>> {
         let reviews = self
@@ -52,10 +389,7 @@ impl GitHubClient {
             .into_iter()
             .map(|r| Review {
                 id: r.id.0,
-                state: r
-                    .state
-                    .map(|s| format!("{:?}", s))
-                    .unwrap_or_else(|| "UNKNOWN".to_string()),
+                state: review_state_name(r.state),
                 body: r.body,
                 author: r.user.map(|u| u.login).unwrap_or_default(),
                 submitted_at: r.submitted_at,
@@ -86,63 +420,345 @@ impl GitHubClient {
             })
             .collect())
     }
+}
+
+/// GitHub's name for a review state, e.g. `CHANGES_REQUESTED`
+fn review_state_name(state: Option<octocrab::models::pulls::ReviewState>) -> String {
+    state
+        .and_then(|s| serde_json::to_value(s).ok())
+        .and_then(|v| v.as_str().map(str::to_string))
+        .unwrap_or_else(|| "UNKNOWN".to_string())
+}
 
-    /// Check if a PR has pending review feedback that requires changes
-    pub async fn has_pending_review_feedback(&self, pr_number: u64) -> Result<bool> {
-        let reviews = self.get_pr_reviews(pr_number).await?;
+/// Lines on the new side of each file's hunks, which GitHub accepts comments
+/// on, with their text
+fn commentable_lines(diff: &str) -> HashMap<String, HashMap<u64, String>> {
+    let mut lines: HashMap<String, HashMap<u64, String>> = HashMap::new();
+    let mut path: Option<String> = None;
+    let mut line = 0;
+    // File headers sit between `diff` and the first hunk; inside hunks a
+    // removed "-- x" line would otherwise look like a header
+    let mut in_header = false;
 
-        // Check if the most recent review requests changes
-        if let Some(latest) = reviews.last() {
-            return Ok(latest.state.contains("CHANGES_REQUESTED"));
+    for text in diff.lines() {
+        if text.starts_with("diff ") {
+            in_header = true;
+            path = None;
+        } else if let Some(hunk) = text.strip_prefix("@@ ") {
+            in_header = false;
+            // "@@ -a,b +c,d @@": the new side starts at line c
+            line = hunk
+                .split_whitespace()
+                .find_map(|part| part.strip_prefix('+'))
+                .and_then(|range| range.split(',').next())
+                .and_then(|start| start.parse().ok())
+                .unwrap_or(0);
+        } else if in_header {
+            if let Some(new_path) = text.strip_prefix("+++ ") {
+                path = new_path.strip_prefix("b/").map(str::to_string);
+            }
+        } else if let Some(ref path) = path {
+            if text.starts_with('+') || text.starts_with(' ') {
+                lines
+                    .entry(path.clone())
+                    .or_default()
+                    .insert(line, text[1..].to_string());
+                line += 1;
+            }
         }
-
-        Ok(false)
     }
 
-    /// Build a feedback summary from PR reviews and comments
-    pub async fn build_review_feedback_summary(&self, pr_number: u64) -> Result<String> {
-        let reviews = self.get_pr_reviews(pr_number).await?;
-        let comments = self.get_pr_review_comments(pr_number).await?;
+    lines
+}
 
-        let mut summary = String::new();
-        summary.push_str(&format!("# Review Feedback for PR #{}\n\n", pr_number));
+/// The diff path and line an issue can be commented on, if any
+///
+/// Agents sometimes give paths relative to a subdirectory, so a unique
+/// suffix match is accepted too.
+fn anchor(
+    issue: &ReviewIssue,
+    lines: &HashMap<String, HashMap<u64, String>>,
+) -> Option<(String, u64)> {
+    let file = issue.file.as_deref()?.trim_start_matches("./");
+    let line = u64::from(issue.line?);
 
-        // Add reviews
-        let has_reviews = !reviews.is_empty();
-        if has_reviews {
-            summary.push_str("## Reviews\n\n");
-            for review in &reviews {
-                summary.push_str(&format!("### {} by {}\n", review.state, review.author));
-                if let Some(body) = &review.body {
-                    if !body.is_empty() {
-                        summary.push_str(&format!("{}\n\n", body));
-                    }
-                }
-            }
+    let path = if lines.contains_key(file) {
+        file.to_string()
+    } else {
+        let suffix = format!("/{}", file);
+        let mut matches = lines.keys().filter(|p| p.ends_with(&suffix));
+        let path = matches.next()?;
+        if matches.next().is_some() {
+            return None;
         }
+        path.clone()
+    };
 
-        // Add comments
-        let has_comments = !comments.is_empty();
-        if has_comments {
-            summary.push_str("## Code Review Comments\n\n");
-            for comment in &comments {
-                if let Some(path) = &comment.path {
-                    summary.push_str(&format!(
-                        "### {} (line {})\n",
-                        path,
-                        comment.line.unwrap_or(0)
-                    ));
-                } else {
-                    summary.push_str(&format!("### Comment by {}\n", comment.author));
-                }
-                summary.push_str(&format!("{}\n\n", comment.body));
-            }
-        }
+    lines[&path].contains_key(&line).then_some((path, line))
+}
 
-        if !has_reviews && !has_comments {
-            summary.push_str("No review feedback found.\n");
+/// Body of a line comment on `line_text`
+///
+/// GitHub applies a suggestion block by replacing the whole commented line,
+/// so code is only offered as one when it reads as a replacement for that
+/// line; fragments are shown as a plain code block instead.
+fn comment_body(label: &str, issue: &ReviewIssue, line_text: &str) -> String {
+    let mut body = format!("**{}:** {}", label, issue.description);
+    if let Some(ref suggestion) = issue.suggestion {
+        match suggestion_code(suggestion) {
+            Some(code) if replaces_line(code, line_text) => body.push_str(&format!(
+                "\n\n```suggestion\n{}\n```",
+                reindent(code, line_text)
+            )),
+            Some(code) => body.push_str(&format!("\n\nSuggestion:\n\n```\n{}\n```", code)),
+            None => body.push_str(&format!("\n\nSuggestion: {}", suggestion)),
         }
+    }
+    body
+}
+
+/// Whether `code` looks like a rewrite of the whole of `line_text` rather
+/// than a fragment of it: it starts with the same token and ends with the
+/// same character
+fn replaces_line(code: &str, line_text: &str) -> bool {
+    let line = line_text.trim();
+    let code_lines: Vec<&str> = code
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty())
+        .collect();
+    let (Some(first), Some(last)) = (code_lines.first(), code_lines.last()) else {
+        return false;
+    };
+    if line.is_empty() {
+        return true;
+    }
+    first.split_whitespace().next() == line.split_whitespace().next()
+        && last.chars().last() == line.chars().last()
+}
+
+/// Give unindented code the commented line's indentation
+fn reindent(code: &str, line_text: &str) -> String {
+    let indent = &line_text[..line_text.len() - line_text.trim_start().len()];
+    if indent.is_empty() || code.starts_with(char::is_whitespace) {
+        return code.to_string();
+    }
+    code.lines()
+        .map(|l| {
+            if l.is_empty() {
+                String::new()
+            } else {
+                format!("{}{}", indent, l)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// The code in a suggestion written as a code span or fenced block
+fn suggestion_code(suggestion: &str) -> Option<&str> {
+    let s = suggestion.trim();
+    if let Some(fenced) = s.strip_prefix("```") {
+        let (_, code) = fenced.split_once('\n')?;
+        return Some(code.trim_end().strip_suffix("```")?.trim_end_matches('\n'));
+    }
+    let code = s.strip_prefix('`')?.strip_suffix('`')?;
+    (!code.is_empty() && !code.contains('`')).then_some(code)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DIFF: &str = "\
+diff --git a/src/lib.rs b/src/lib.rs
+index 1111111..2222222 100644
+--- a/src/lib.rs
++++ b/src/lib.rs
+@@ -10,4 +10,4 @@ fn context() {
+ fn a() {}
+-fn b() {}
+--- not a header
++fn b() { todo!() }
++fn c() {}
+ fn d() {}
+diff --git a/README.md b/README.md
+--- a/README.md
++++ /dev/null
+@@ -1 +0,0 @@
+-old
+";
+
+    #[test]
+    fn test_commentable_lines() {
+        let lines = commentable_lines(DIFF);
+        assert_eq!(lines.len(), 1);
+        let mut src: Vec<_> = lines["src/lib.rs"].keys().copied().collect();
+        src.sort();
+        assert_eq!(src, [10, 11, 12, 13]);
+        assert_eq!(lines["src/lib.rs"][&11], "fn b() { todo!() }");
+    }
+
+    #[test]
+    fn test_draft_anchors_issues_in_diff() {
+        let result = ReviewResult {
+            verdict: ReviewVerdict::RequestChanges,
+            blocking: vec![ReviewIssue::new("Panics")
+                .at_file("lib.rs")
+                .at_line(11)
+                .with_suggestion("`fn b() {}`")],
+            important: vec![ReviewIssue::new("Unrelated")
+                .at_file("src/lib.rs")
+                .at_line(40)],
+            suggestions: vec![ReviewIssue::new("Rename c")
+                .at_file("src/lib.rs")
+                .at_line(12)
+                .with_suggestion("Call it `helper`")],
+            positives: vec!["Small change".to_string()],
+        };
+
+        let draft = ReviewDraft::from_result(&result, DIFF);
+        assert_eq!(draft.event, ReviewEvent::RequestChanges);
+        assert_eq!(draft.comments.len(), 2);
+        assert_eq!(draft.comments[0].path, "src/lib.rs");
+        assert_eq!(draft.comments[0].line, 11);
+        assert_eq!(
+            draft.comments[0].body,
+            "**Blocking:** Panics\n\n```suggestion\nfn b() {}\n```"
+        );
+        assert!(draft.comments[1]
+            .body
+            .ends_with("Suggestion: Call it `helper`"));
+
+        assert!(draft
+            .body
+            .contains("1 blocking, 1 important, 1 suggestions"));
+        assert!(draft
+            .body
+            .contains("### Important\n\n- src/lib.rs:40: Unrelated"));
+        assert!(!draft.body.contains("### Blocking"));
+        assert!(draft.body.contains("### Positive\n\n- Small change"));
+
+        let json = serde_json::to_value(&draft).unwrap();
+        assert_eq!(json["event"], "REQUEST_CHANGES");
+        assert_eq!(json["comments"][0]["side"], "RIGHT");
+    }
+
+    #[test]
+    fn test_suggestion_code() {
+        assert_eq!(suggestion_code("`let x = 1;`"), Some("let x = 1;"));
+        assert_eq!(
+            suggestion_code("```rust\nlet x = 1;\nlet y = 2;\n```"),
+            Some("let x = 1;\nlet y = 2;")
+        );
+        assert_eq!(suggestion_code("Use `x` instead of `y`"), None);
+        assert_eq!(suggestion_code("Handle the error"), None);
+    }
+
+    #[test]
+    fn test_fragment_suggestions_are_not_applied() {
+        let issue = |suggestion: &str| ReviewIssue::new("Fix").with_suggestion(suggestion);
+
+        assert_eq!(
+            comment_body(
+                "Blocking",
+                &issue("`x.unwrap_or_default()`"),
+                "    let y = x.unwrap();"
+            ),
+            "**Blocking:** Fix\n\nSuggestion:\n\n```\nx.unwrap_or_default()\n```"
+        );
+        assert_eq!(
+            comment_body(
+                "Blocking",
+                &issue("`let y = x?;`"),
+                "    let y = x.unwrap();"
+            ),
+            "**Blocking:** Fix\n\n```suggestion\n    let y = x?;\n```"
+        );
+        assert_eq!(
+            comment_body(
+                "Important",
+                &issue("```rust\nif ok {\n    run();\n}\n```"),
+                "\tif ok { run() }"
+            ),
+            "**Important:** Fix\n\n```suggestion\n\tif ok {\n\t    run();\n\t}\n```"
+        );
+    }
+
+    #[test]
+    fn test_review_state_name() {
+        use octocrab::models::pulls::ReviewState;
+        assert_eq!(
+            review_state_name(Some(ReviewState::ChangesRequested)),
+            "CHANGES_REQUESTED"
+        );
+        assert_eq!(review_state_name(None), "UNKNOWN");
+    }
+
+    #[test]
+    fn test_feedback_markdown() {
+        let comment = |author: &str, body: &str| ReviewComment {
+            id: 1,
+            body: body.to_string(),
+            author: author.to_string(),
+            path: Some("src/lib.rs".to_string()),
+            line: Some(12),
+            created_at: Utc::now(),
+        };
+        let feedback = PrFeedback {
+            threads: vec![ReviewThread {
+                id: "PRRT_1".to_string(),
+                is_resolved: false,
+                is_outdated: false,
+                path: "src/lib.rs".to_string(),
+                line: Some(12),
+                comments: vec![comment("alice", "Handle the error"), comment("bob", "+1")],
+            }],
+            change_requests: vec![Review {
+                id: 2,
+                state: "CHANGES_REQUESTED".to_string(),
+                body: Some("Needs tests.".to_string()),
+                author: "carol".to_string(),
+                submitted_at: None,
+            }],
+        };
+
+        assert_eq!(
+            feedback.to_markdown(),
+            "### Review by @carol\n\nNeeds tests.\n\n\
+             ### src/lib.rs:12 (thread `PRRT_1`)\n\n\
+             **@alice:** Handle the error\n\n**@bob:** +1"
+        );
+        assert!(PrFeedback::default().is_empty());
+    }
+
+    #[test]
+    fn test_parse_thread_reports() {
+        let output = r#"Fixed the error handling; the panic stays.
+
+```json
+{"PRRT_1": {"outcome": "addressed", "reply": "Now returns the error"},
+ "PRRT_2": {"outcome": "declined", "reply": "The index is checked above"}}
+```"#;
+        let reports = parse_thread_reports(output);
+        assert_eq!(reports.len(), 2);
+        assert_eq!(reports["PRRT_1"].outcome, ThreadOutcome::Addressed);
+        assert_eq!(reports["PRRT_1"].reply, "Now returns the error");
+        assert_eq!(reports["PRRT_2"].outcome, ThreadOutcome::Declined);
+
+        assert!(parse_thread_reports("Fixed everything").is_empty());
+        assert!(parse_thread_reports("```json\n{\"PRRT_1\": \"done\"}\n```").is_empty());
+    }
 
-        Ok(summary)
+    #[test]
+    fn test_event_from_verdict() {
+        assert_eq!(
+            ReviewEvent::from_verdict(ReviewVerdict::Approved),
+            ReviewEvent::Approve
+        );
+        assert_eq!(
+            ReviewEvent::from_verdict(ReviewVerdict::Pending),
+            ReviewEvent::Comment
+        );
     }
 }